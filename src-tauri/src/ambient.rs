@@ -0,0 +1,270 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Sounds to fall asleep or focus to: "play rain sounds for 30 minutes", "play brown
+// noise", "stop sounds". They loop under speech on the voice loop's output (see
+// audio_output) and fade out when the sleep timer runs out. White, pink and brown
+// noise are generated; recordings are looked up as assets/sounds/<name>.ogg (or .mp3,
+// .wav, .flac), and a missing recording is stood in for by the noise closest to it.
+
+use crate::audio_output::{self, AmbientSound, NoiseColor};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AmbientConfig {
+    // 0.0 to 1.0
+    pub volume: f32,
+    // how long the sleep timer's fade-out takes
+    pub fade_seconds: u64,
+    // more sounds by name, e.g. "cafe": "C:\\Sounds\\cafe.mp3"
+    pub sounds: BTreeMap<String, String>,
+}
+
+impl Default for AmbientConfig {
+    fn default() -> Self {
+        Self {
+            volume: 0.4,
+            fade_seconds: 30,
+            sounds: BTreeMap::new(),
+        }
+    }
+}
+
+// names you can say, the recording they play and the noise standing in for it
+const RECORDINGS: &[(&str, &str, NoiseColor)] = &[
+    ("rain", "rain", NoiseColor::Pink),
+    ("ocean", "ocean", NoiseColor::Brown),
+    ("waves", "ocean", NoiseColor::Brown),
+    ("sea", "ocean", NoiseColor::Brown),
+    ("thunderstorm", "thunderstorm", NoiseColor::Brown),
+    ("storm", "thunderstorm", NoiseColor::Brown),
+    ("stream", "stream", NoiseColor::Pink),
+    ("river", "stream", NoiseColor::Pink),
+    ("waterfall", "stream", NoiseColor::White),
+    ("fireplace", "fireplace", NoiseColor::Brown),
+    ("fire", "fireplace", NoiseColor::Brown),
+    ("forest", "forest", NoiseColor::Pink),
+    ("wind", "wind", NoiseColor::Brown),
+    ("fan", "fan", NoiseColor::Brown),
+];
+
+const NOISES: &[(&str, NoiseColor)] = &[
+    ("white", NoiseColor::White),
+    ("pink", NoiseColor::Pink),
+    ("brown", NoiseColor::Brown),
+    ("brownian", NoiseColor::Brown),
+];
+
+const EXTENSIONS: &[&str] = &["ogg", "mp3", "wav", "flac"];
+
+static PLAY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:play|put on|start)\s+(?:some\s+|the\s+)?(.+?)",
+        r"(?:\s+(?:for|until)\s+(.+?))?[?.!]*\s*$"
+    ))
+    .expect("Failed to compile ambient sound regex")
+});
+
+static STOP: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:stop|end|turn off|switch off)\s+(?:the\s+)?(?:\w+\s+)?",
+        r"(?:sounds?|noise|ambien(?:ce|t(?:\s+sounds?)?))\b"
+    ))
+    .expect("Failed to compile stop sounds regex")
+});
+
+// a newer sound or "stop sounds" makes the pending sleep timer moot
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+struct Choice {
+    name: String,
+    sound: AmbientSound,
+    // the noise playing because the recording is missing
+    stand_in: bool,
+}
+
+fn find_recording(app: &tauri::AppHandle, file: &str) -> Option<PathBuf> {
+    EXTENSIONS.iter().find_map(|ext| {
+        let asset = PathBuf::from("assets").join("sounds").join(format!("{}.{}", file, ext));
+        let path = audio_output::resolve_asset(app, &asset);
+        path.exists().then_some(path)
+    })
+}
+
+// "rain sounds", "white noise", "the ocean": which sound that is
+fn choose(app: &tauri::AppHandle, config: &AmbientConfig, said: &str) -> Option<Choice> {
+    let said = said.to_lowercase();
+    let words: Vec<&str> = said
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let is_noise = words.contains(&"noise");
+    let stripped: Vec<&str> = words
+        .iter()
+        .copied()
+        .filter(|w| !matches!(*w, "sound" | "sounds" | "noise" | "noises" | "ambience"))
+        .collect();
+    let name = stripped.join(" ");
+    if let Some((_, path)) = config
+        .sounds
+        .iter()
+        .find(|(n, _)| n.trim().to_lowercase() == name)
+    {
+        return Some(Choice {
+            name,
+            sound: AmbientSound::File(PathBuf::from(path.trim())),
+            stand_in: false,
+        });
+    }
+    if is_noise {
+        let color = NOISES.iter().find(|(n, _)| *n == name).map(|(_, c)| *c)?;
+        return Some(Choice {
+            name: format!("{} noise", name),
+            sound: AmbientSound::Noise(color),
+            stand_in: false,
+        });
+    }
+    // "play rain" only counts with "sounds" said, so "play Purple Rain" stays music
+    if stripped.len() == words.len() {
+        return None;
+    }
+    let (_, file, color) = RECORDINGS.iter().find(|(n, _, _)| *n == name)?;
+    Some(match find_recording(app, file) {
+        Some(path) => Choice {
+            name: format!("{} sounds", name),
+            sound: AmbientSound::File(path),
+            stand_in: false,
+        },
+        None => Choice {
+            name: format!("{} sounds", name),
+            sound: AmbientSound::Noise(*color),
+            stand_in: true,
+        },
+    })
+}
+
+fn noise_name(sound: &AmbientSound) -> &'static str {
+    match sound {
+        AmbientSound::Noise(NoiseColor::White) => "white noise",
+        AmbientSound::Noise(NoiseColor::Pink) => "pink noise",
+        AmbientSound::Noise(NoiseColor::Brown) => "brown noise",
+        AmbientSound::File(_) => "the recording",
+    }
+}
+
+// Starts the sound and, when `duration` is given, the sleep timer that fades it out
+fn play(
+    config: &AmbientConfig,
+    sound: AmbientSound,
+    duration: Option<Duration>,
+) -> Result<(), String> {
+    audio_output::start_ambient(sound, config.volume).map_err(|e| e.to_string())?;
+    let generation = GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+    if let Some(duration) = duration {
+        let fade = Duration::from_secs(config.fade_seconds).min(duration);
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(duration - fade).await;
+            if GENERATION.load(Ordering::Relaxed) == generation {
+                audio_output::fade_out_ambient(fade);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn stop() {
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+    audio_output::fade_out_ambient(Duration::from_secs(2));
+}
+
+// The spoken answer to an ambient sound command, or None when the prompt isn't one
+pub fn answer(prompt: &str, app: &tauri::AppHandle, config: &AmbientConfig) -> Option<String> {
+    if STOP.is_match(prompt) {
+        if !audio_output::ambient_playing() {
+            return None;
+        }
+        stop();
+        return Some("Stopping the sounds.".to_string());
+    }
+    let m = PLAY.captures(prompt)?;
+    let choice = choose(app, config, &m[1])?;
+    let duration = m
+        .get(2)
+        .and_then(|d| crate::timers::parse_duration(d.as_str()))
+        .map(Duration::from_secs);
+    let stand_in = choice.stand_in.then(|| noise_name(&choice.sound));
+    if let Err(e) = play(config, choice.sound, duration) {
+        eprintln!("[ERROR] Failed to play {}: {}", choice.name, e);
+        return Some(format!("Sorry, I couldn't play {}.", choice.name));
+    }
+    let mut reply = format!("Playing {}", stand_in.unwrap_or(choice.name.as_str()));
+    match duration {
+        Some(d) => reply.push_str(&format!(
+            " for {}.",
+            crate::timers::spoken_duration(d.as_secs())
+        )),
+        None => reply.push('.'),
+    }
+    if stand_in.is_some() {
+        reply.push_str(&format!(" I don't have a recording of {}.", choice.name));
+    }
+    Some(reply)
+}
+
+fn resolve_for_command(
+    app: &tauri::AppHandle,
+    config: &AmbientConfig,
+    sound: &str,
+) -> Result<AmbientSound, String> {
+    let said = sound.trim();
+    if Path::new(said).is_file() {
+        return Ok(AmbientSound::File(PathBuf::from(said)));
+    }
+    // by voice the name needs "sounds" or "noise" after it
+    let named = if said.contains("noise") || said.contains("sound") {
+        said.to_string()
+    } else {
+        format!("{} sounds", said)
+    };
+    choose(app, config, &named)
+        .map(|c| c.sound)
+        .ok_or_else(|| format!("Unknown sound '{}'", said))
+}
+
+// `sound` is a name ("rain", "brown noise") or a path to a sound file
+#[tauri::command]
+pub fn cmd_play_ambient(
+    app: tauri::AppHandle,
+    sound: String,
+    minutes: Option<u64>,
+) -> Result<(), String> {
+    let config = crate::cmd_load_config(app.clone())?.ambient;
+    let sound = resolve_for_command(&app, &config, &sound)?;
+    play(&config, sound, minutes.map(|m| Duration::from_secs(m * 60)))
+}
+
+#[tauri::command]
+pub fn cmd_stop_ambient() {
+    stop();
+}
@@ -0,0 +1,144 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Rules that act when an app gains or loses focus or goes fullscreen, e.g. start
+// listening when the editor is focused or switch to a quiet persona during a
+// fullscreen meeting. Fed by the foreground watcher (see foreground.rs).
+
+use crate::foreground::Foreground;
+use crate::JarvisState;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AppEvent {
+    Focus,
+    Blur,
+    Fullscreen,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AppAction {
+    StartListening,
+    StopListening,
+    // switches to `persona`; empty goes back to the plain settings
+    Persona,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AppTrigger {
+    // program file name, e.g. "Code.exe" ("Code" works too); empty matches any app
+    pub app: String,
+    // part of the window title, case-insensitive; empty matches any title
+    pub title_contains: String,
+    pub when: AppEvent,
+    pub action: AppAction,
+    pub persona: String,
+}
+
+impl Default for AppTrigger {
+    fn default() -> Self {
+        Self {
+            app: String::new(),
+            title_contains: String::new(),
+            when: AppEvent::Focus,
+            action: AppAction::StartListening,
+            persona: String::new(),
+        }
+    }
+}
+
+impl AppTrigger {
+    fn matches(&self, window: &Foreground) -> bool {
+        let app = self.app.trim().to_lowercase();
+        let exe = window.exe.to_lowercase();
+        let app_matches = app.is_empty()
+            || exe == app
+            || exe.strip_suffix(".exe") == Some(app.as_str());
+        let title = self.title_contains.trim().to_lowercase();
+        app_matches && (title.is_empty() || window.title.to_lowercase().contains(&title))
+    }
+
+    // Whether the change from `previous` to `now` is the one this rule waits for
+    fn fires(&self, previous: Option<&Foreground>, now: &Foreground) -> bool {
+        let was = previous.filter(|p| self.matches(p));
+        let is = self.matches(now);
+        match self.when {
+            AppEvent::Focus => was.is_none() && is,
+            AppEvent::Blur => was.is_some() && !is,
+            AppEvent::Fullscreen => is && now.fullscreen && !was.is_some_and(|p| p.fullscreen),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AppTriggersConfig {
+    pub enabled: bool,
+    pub rules: Vec<AppTrigger>,
+}
+
+impl Default for AppTriggersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rules: Vec::new(),
+        }
+    }
+}
+
+fn run(app: &tauri::AppHandle, rule: &AppTrigger) -> Result<(), String> {
+    match rule.action {
+        AppAction::StartListening => {
+            let state = app.state::<JarvisState>();
+            if state.is_running.load(std::sync::atomic::Ordering::Relaxed) {
+                return Ok(());
+            }
+            crate::cmd_start_jarvis(state, app.clone()).map(|_| ())
+        }
+        AppAction::StopListening => {
+            let state = app.state::<JarvisState>();
+            if !state.is_running.load(std::sync::atomic::Ordering::Relaxed) {
+                return Ok(());
+            }
+            crate::cmd_stop_jarvis(state).map(|_| ())
+        }
+        AppAction::Persona => {
+            let name = Some(rule.persona.trim().to_string()).filter(|n| !n.is_empty());
+            crate::persona::cmd_set_persona(app.clone(), name)
+        }
+    }
+}
+
+pub fn on_change(app: &tauri::AppHandle, previous: Option<&Foreground>, now: &Foreground) {
+    let cfg = match crate::cmd_load_config(app.clone()) {
+        Ok(cfg) => cfg.app_triggers,
+        Err(_) => return,
+    };
+    if !cfg.enabled {
+        return;
+    }
+    for rule in cfg.rules.iter().filter(|r| r.fires(previous, now)) {
+        println!("[DEBUG] App trigger {:?} {:?} for {}", rule.when, rule.action, now.exe);
+        if let Err(e) = run(app, rule) {
+            eprintln!("[WARN] App trigger for '{}' failed: {}", rule.app, e);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::audio_input::SAMPLE_RATE;
+use crate::transform_text::extract_image_parts;
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+// reference stored in a turn's meta under "attachments"
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AttachmentRef {
+    pub id: String,
+    pub kind: String,
+    pub mime: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AttachmentInfo {
+    pub id: String,
+    pub kind: String,
+    pub mime: String,
+    pub size: u64,
+}
+
+// attachments live next to the history files, one folder per conversation
+fn attachments_dir(app: &tauri::AppHandle, filename: &str) -> Result<PathBuf, String> {
+    let (stem, _) = crate::history::split_extension(filename);
+    Ok(crate::history_dir(app)?.join("attachments").join(stem))
+}
+
+fn mime_for(id: &str) -> &'static str {
+    let lc = id.to_lowercase();
+    if lc.ends_with(".wav") {
+        "audio/wav"
+    } else if lc.ends_with(".png") {
+        "image/png"
+    } else if lc.ends_with(".jpg") || lc.ends_with(".jpeg") {
+        "image/jpeg"
+    } else if lc.ends_with(".gif") {
+        "image/gif"
+    } else if lc.ends_with(".webp") {
+        "image/webp"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+fn kind_for(mime: &str) -> &'static str {
+    if mime.starts_with("audio/") {
+        "audio"
+    } else if mime.starts_with("image/") {
+        "image"
+    } else {
+        "file"
+    }
+}
+
+// saves the recorded command audio for a turn so the UI can replay what Jarvis heard
+pub fn save_audio(
+    app: &tauri::AppHandle,
+    filename: &str,
+    created_at: i64,
+    samples: &[i16],
+) -> Result<AttachmentRef, String> {
+    let dir = attachments_dir(app, filename)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let id = format!("{}-audio.wav", created_at);
+    crate::utils::write_wav_i16(&dir.join(&id), samples, SAMPLE_RATE as u32)
+        .map_err(|e| e.to_string())?;
+    Ok(AttachmentRef {
+        id,
+        kind: "audio".into(),
+        mime: "audio/wav".into(),
+    })
+}
+
+pub fn save_bytes(
+    app: &tauri::AppHandle,
+    filename: &str,
+    created_at: i64,
+    mime: &str,
+    bytes: &[u8],
+) -> Result<AttachmentRef, String> {
+    let dir = attachments_dir(app, filename)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let ext = match mime {
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/png" => "png",
+        _ => "bin",
+    };
+    let kind = kind_for(mime);
+    let id = format!("{}-{}.{}", created_at, kind, ext);
+    std::fs::write(dir.join(&id), bytes).map_err(|e| e.to_string())?;
+    Ok(AttachmentRef {
+        id,
+        kind: kind.into(),
+        mime: mime.into(),
+    })
+}
+
+// stores an image embedded in the prompt (e.g. pasted from the clipboard), if any
+pub fn save_prompt_image(
+    app: &tauri::AppHandle,
+    filename: &str,
+    created_at: i64,
+    prompt: &str,
+) -> Option<AttachmentRef> {
+    match extract_image_parts(prompt) {
+        Ok(_) => None,
+        Err((_, mime, bytes, _)) if !bytes.is_empty() => {
+            match save_bytes(app, filename, created_at, &format!("image/{}", mime), &bytes) {
+                Ok(r) => Some(r),
+                Err(e) => {
+                    eprintln!("[WARN] Failed to save pasted image attachment: {}", e);
+                    None
+                }
+            }
+        }
+        Err(_) => None,
+    }
+}
+
+pub fn rename(app: &tauri::AppHandle, old: &str, new: &str) {
+    if let (Ok(from), Ok(to)) = (attachments_dir(app, old), attachments_dir(app, new)) {
+        if from.exists() {
+            if let Err(e) = std::fs::rename(&from, &to) {
+                eprintln!("[WARN] Failed to move attachments: {}", e);
+            }
+        }
+    }
+}
+
+pub fn remove(app: &tauri::AppHandle, filename: &str) {
+    if let Ok(dir) = attachments_dir(app, filename) {
+        if dir.exists() {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn cmd_list_attachments(
+    app: tauri::AppHandle,
+    filename: String,
+) -> Result<Vec<AttachmentInfo>, String> {
+    let dir = attachments_dir(&app, &filename)?;
+    let mut out = Vec::new();
+    if let Ok(rd) = std::fs::read_dir(&dir) {
+        for entry in rd.flatten() {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            let id = entry.file_name().to_string_lossy().to_string();
+            let mime = mime_for(&id);
+            out.push(AttachmentInfo {
+                kind: kind_for(mime).into(),
+                mime: mime.into(),
+                size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+                id,
+            });
+        }
+    }
+    out.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(out)
+}
+
+// returns the attachment as a data URI that can be fed straight into <audio>/<img>
+#[tauri::command]
+pub fn cmd_read_attachment(
+    app: tauri::AppHandle,
+    filename: String,
+    id: String,
+) -> Result<String, String> {
+    if id.contains('/') || id.contains('\\') || id.contains("..") {
+        return Err("Invalid attachment id".into());
+    }
+    let path = attachments_dir(&app, &filename)?.join(&id);
+    let bytes = std::fs::read(&path).map_err(|e| e.to_string())?;
+    Ok(format!(
+        "data:{};base64,{}",
+        mime_for(&id),
+        general_purpose::STANDARD.encode(bytes)
+    ))
+}
@@ -18,160 +18,905 @@ along with this program.  If not, see <https://www.gnu.org/licenses/>.
 use anyhow::{Result, anyhow};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Device, Host, SampleFormat, StreamConfig};
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
-use std::sync::{Arc, Mutex};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Condvar, LockResult, Mutex, MutexGuard};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 pub const SAMPLE_RATE: usize = 16_000;
 
-// sets up and runs the audio input stream in a separate thread.
-pub fn start_audio_stream(
-    buffer: Arc<Mutex<VecDeque<i16>>>,
-    microphone_name: Option<String>,
-    default_microphone_index: usize,
-) -> Result<()> {
-    println!("[DEBUG] Spawning audio input thread...");
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InputKind {
+    Microphone,
+    // WAV files played into the voice loop as if spoken into the microphone
+    Wav,
+}
 
-    thread::spawn(move || {
-        let device = match choose_input_device(
-            microphone_name.as_deref(),
-            default_microphone_index,
-        ) {
-            Some(d) => d,
-            None => {
-                eprintln!(
-                    "[ERROR] No input device found at index {}. Exiting audio thread.",
-                    default_microphone_index
-                );
-                return;
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct InputSourceConfig {
+    pub kind: InputKind,
+    // a WAV file, or a folder whose WAV files are played in name order
+    pub path: String,
+    // 1.0 plays in real time, 4.0 four times as fast; 0 hands the audio over as fast as
+    // Jarvis reads it, pausing while it is busy, so runs come out the same every time
+    pub speed: f32,
+    // start over after the last file instead of going quiet
+    pub repeat: bool,
+}
+
+impl Default for InputSourceConfig {
+    fn default() -> Self {
+        Self {
+            kind: InputKind::Microphone,
+            path: String::new(),
+            speed: 1.0,
+            repeat: false,
+        }
+    }
+}
+
+// called once per overflow episode with the ring's counters
+type OverflowHook = Box<dyn Fn(&AudioRing) + Send>;
+
+// Fixed-size buffer between the capture callback and the consumers. When the
+// consumers fall behind (long Processing/Speaking phases) the oldest samples are
+// dropped and counted instead of letting the buffer grow.
+pub struct AudioRing {
+    samples: VecDeque<i16>,
+    max_len: usize,
+    pub dropped_samples: u64,
+    pub overflow_events: u64,
+    // true while the buffer keeps overflowing; one event per overflow episode
+    overflowing: bool,
+    on_overflow: Option<OverflowHook>,
+    // samples a waiting consumer needs; the capture callback only wakes it once they're in
+    wanted: usize,
+}
+
+// The ring plus a condition variable, so consumers sleep until their frame is complete
+// instead of polling
+pub struct AudioBuffer {
+    ring: Mutex<AudioRing>,
+    ready: Condvar,
+}
+
+pub type SharedAudioBuffer = Arc<AudioBuffer>;
+
+impl AudioBuffer {
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, AudioRing>> {
+        self.ring.lock()
+    }
+
+    // Waits up to `timeout` until `n` samples are buffered and takes them; None on timeout
+    pub fn take_within(&self, n: usize, timeout: Duration) -> Result<Option<Vec<i16>>> {
+        let deadline = Instant::now() + timeout;
+        let mut ring = self
+            .lock()
+            .map_err(|e| anyhow!("Failed to lock audio buffer (poisoned): {e}"))?;
+        while ring.len() < n {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                ring.wanted = 0;
+                return Ok(None);
             }
+            ring.wanted = n;
+            ring = self
+                .ready
+                .wait_timeout(ring, left)
+                .map_err(|e| anyhow!("Failed to lock audio buffer (poisoned): {e}"))?
+                .0;
+        }
+        ring.wanted = 0;
+        Ok(Some(ring.take(n)))
+    }
+
+    // Appends samples and wakes a consumer whose frame is now complete
+    pub fn extend(&self, samples: &[i16]) {
+        let Ok(mut ring) = self.lock() else {
+            return;
         };
+        for &sample in samples {
+            ring.push(sample);
+        }
+        let complete = ring.len() >= ring.wanted;
+        drop(ring);
+        if complete {
+            self.ready.notify_all();
+        }
+    }
+}
 
-        let device_name = device.name().unwrap_or_else(|err| {
-            eprintln!("[ERROR] Failed to get device name: {}", err);
-            "<unknown device>".to_string()
-        });
+impl AudioRing {
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(max_len),
+            max_len: max_len.max(1),
+            dropped_samples: 0,
+            overflow_events: 0,
+            overflowing: false,
+            on_overflow: None,
+            wanted: 0,
+        }
+    }
+
+    pub fn shared(max_len: usize) -> SharedAudioBuffer {
+        Arc::new(AudioBuffer {
+            ring: Mutex::new(Self::new(max_len)),
+            ready: Condvar::new(),
+        })
+    }
+
+    // called (from the audio thread) when an overflow episode starts
+    pub fn set_on_overflow<F: Fn(&AudioRing) + Send + 'static>(&mut self, f: F) {
+        self.on_overflow = Some(Box::new(f));
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.overflowing = false;
+    }
+
+    pub fn push(&mut self, sample: i16) {
+        if self.samples.len() >= self.max_len {
+            self.samples.pop_front();
+            self.dropped_samples += 1;
+            if !self.overflowing {
+                self.overflowing = true;
+                self.overflow_events += 1;
+                if let Some(cb) = &self.on_overflow {
+                    cb(self);
+                }
+            }
+        }
+        self.samples.push_back(sample);
+    }
+
+    // removes the oldest `n` samples (all of them if fewer are buffered)
+    pub fn take(&mut self, n: usize) -> Vec<i16> {
+        let n = n.min(self.samples.len());
+        if n > 0 {
+            // a consumer caught up, so the next overflow is a new episode
+            self.overflowing = false;
+        }
+        self.samples.drain(..n).collect()
+    }
+
+    // puts samples that were taken but not used back in front, oldest first
+    pub fn unread(&mut self, samples: &[i16]) {
+        for &sample in samples.iter().rev() {
+            self.samples.push_front(sample);
+        }
+        while self.samples.len() > self.max_len {
+            self.samples.pop_back();
+        }
+    }
+}
+
+enum InputCommand {
+    Open {
+        buffer: SharedAudioBuffer,
+        microphone_name: Option<String>,
+        default_microphone_index: usize,
+        source: InputSourceConfig,
+        ack: mpsc::Sender<Result<String>>,
+    },
+    // reported by cpal's error callback; `generation` tells errors of an old stream apart
+    StreamFailed {
+        generation: u64,
+        error: cpal::StreamError,
+    },
+    Close,
+    Shutdown,
+}
+
+// What the capture thread tells the app about the microphone
+#[derive(Debug, Clone)]
+pub enum InputEvent {
+    DeviceLost { device: String, error: String },
+    // `fallback` is true when the configured device never came back and the default was used
+    Reconnected { device: String, fallback: bool },
+    ReconnectFailed { device: String },
+}
+
+type InputListener = Box<dyn Fn(&InputEvent) + Send>;
+
+// retry the lost device for this long before settling for the system default
+const RECONNECT_SAME_DEVICE_FOR: Duration = Duration::from_secs(5);
+const RECONNECT_GIVE_UP_AFTER: Duration = Duration::from_secs(60);
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+// The capture thread. It owns the cpal stream (which can't leave its thread) and
+// outlives a single Jarvis run: stopping closes the stream, starting again reopens
+// it on the same thread.
+pub struct AudioInput {
+    commands: Mutex<mpsc::Sender<InputCommand>>,
+    listener: Arc<Mutex<Option<InputListener>>>,
+    thread: Mutex<Option<thread::JoinHandle<()>>>,
+}
+
+static INPUT: Mutex<Option<Arc<AudioInput>>> = Mutex::new(None);
+
+// a stream from a device, or WAV files played in its place
+struct OpenStream {
+    _stream: Option<cpal::Stream>,
+    _files: Option<FileFeed>,
+    buffer: SharedAudioBuffer,
+    device_name: String,
+}
+
+struct Reconnect {
+    buffer: SharedAudioBuffer,
+    device_name: String,
+    since: Instant,
+}
+
+struct InputThread {
+    commands: mpsc::Sender<InputCommand>,
+    listener: Arc<Mutex<Option<InputListener>>>,
+    generation: u64,
+}
+
+impl InputThread {
+    fn notify(&self, event: InputEvent) {
+        if let Ok(listener) = self.listener.lock() {
+            if let Some(listener) = listener.as_ref() {
+                listener(&event);
+            }
+        }
+    }
+
+    fn open(&mut self, device: &Device, buffer: SharedAudioBuffer) -> Result<OpenStream> {
+        self.generation += 1;
+        let generation = self.generation;
+        let commands = self.commands.clone();
+        let device_name = device
+            .name()
+            .unwrap_or_else(|_| "<unknown device>".to_string());
         println!("[INFO] Using input device: {}", device_name);
+        let stream = open_input_stream(device, Arc::clone(&buffer), move |error| {
+            eprintln!("[ERROR] Stream error: {}", error);
+            let _ = commands.send(InputCommand::StreamFailed { generation, error });
+        })?;
+        Ok(OpenStream {
+            _stream: Some(stream),
+            _files: None,
+            buffer,
+            device_name,
+        })
+    }
+
+    fn open_files(
+        &mut self,
+        source: &InputSourceConfig,
+        buffer: SharedAudioBuffer,
+    ) -> Result<OpenStream> {
+        // errors of the stream this replaces no longer count
+        self.generation += 1;
+        let feed = FileFeed::start(source, Arc::clone(&buffer))?;
+        let device_name = format!("WAV: {}", source.path);
+        println!("[INFO] Using input: {}", device_name);
+        Ok(OpenStream {
+            _stream: None,
+            _files: Some(feed),
+            buffer,
+            device_name,
+        })
+    }
 
-        let supported_config = match device.supported_input_configs() {
-            Ok(mut configs) => configs.find(|c| {
-                c.channels() == 1
-                    && c.min_sample_rate().0 <= 16_000
-                    && c.max_sample_rate().0 >= 16_000
-                    && c.sample_format() == SampleFormat::I16
-            }),
+    // one reconnection attempt: the lost device first, the system default once that took too long
+    fn try_reconnect(&mut self, pending: &Reconnect) -> Option<(OpenStream, bool)> {
+        let fallback = pending.since.elapsed() >= RECONNECT_SAME_DEVICE_FOR;
+        let device = if fallback {
+            cpal::default_host().default_input_device()
+        } else {
+            find_input_device_by_name(&pending.device_name)
+        }?;
+        match self.open(&device, Arc::clone(&pending.buffer)) {
+            Ok(open) => Some((open, fallback)),
             Err(e) => {
-                eprintln!("[ERROR] Error getting supported configs: {e}");
-                return;
+                eprintln!("[WARN] Reconnecting microphone failed: {}", e);
+                None
             }
-        };
+        }
+    }
+}
 
-        let config = if let Some(c) = supported_config {
-            c.with_sample_rate(cpal::SampleRate(16_000))
+fn run_input_thread(
+    commands: mpsc::Receiver<InputCommand>,
+    mut input: InputThread,
+) {
+    let mut stream: Option<OpenStream> = None;
+    let mut reconnect: Option<Reconnect> = None;
+    loop {
+        let command = if reconnect.is_some() {
+            match commands.recv_timeout(RECONNECT_INTERVAL) {
+                Ok(c) => Some(c),
+                Err(mpsc::RecvTimeoutError::Timeout) => None,
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
         } else {
-            match device.default_input_config() {
-                Ok(cfg) => cfg,
-                Err(e) => {
-                    eprintln!("[ERROR] No default config found: {e}");
-                    return;
+            match commands.recv() {
+                Ok(c) => Some(c),
+                Err(_) => break,
+            }
+        };
+
+        match command {
+            None => {
+                let Some(pending) = reconnect.take() else {
+                    continue;
+                };
+                if let Some((open, fallback)) = input.try_reconnect(&pending) {
+                    println!("[INFO] Microphone reconnected: {}", open.device_name);
+                    input.notify(InputEvent::Reconnected {
+                        device: open.device_name.clone(),
+                        fallback,
+                    });
+                    stream = Some(open);
+                } else if pending.since.elapsed() >= RECONNECT_GIVE_UP_AFTER {
+                    eprintln!("[ERROR] Giving up on reconnecting the microphone");
+                    input.notify(InputEvent::ReconnectFailed {
+                        device: pending.device_name,
+                    });
+                } else {
+                    reconnect = Some(pending);
                 }
             }
+            Some(InputCommand::Open {
+                buffer,
+                microphone_name,
+                default_microphone_index,
+                source,
+                ack,
+            }) => {
+                // release the old device before grabbing the (possibly same) new one
+                stream = None;
+                reconnect = None;
+                let opened = if source.kind == InputKind::Wav {
+                    input.open_files(&source, buffer)
+                } else {
+                    choose_input_device(microphone_name.as_deref(), default_microphone_index)
+                        .ok_or_else(|| {
+                            anyhow!("No input device found at index {}", default_microphone_index)
+                        })
+                        .and_then(|device| input.open(&device, buffer))
+                };
+                let _ = ack.send(opened.map(|open| {
+                    let name = open.device_name.clone();
+                    stream = Some(open);
+                    name
+                }));
+            }
+            Some(InputCommand::StreamFailed { generation, error }) => {
+                if generation != input.generation || stream.is_none() {
+                    continue;
+                }
+                // other errors are usually transient glitches; only a vanished device kills the stream
+                if !matches!(error, cpal::StreamError::DeviceNotAvailable) {
+                    continue;
+                }
+                let Some(lost) = stream.take() else {
+                    continue;
+                };
+                eprintln!("[ERROR] Microphone lost: {}", lost.device_name);
+                input.notify(InputEvent::DeviceLost {
+                    device: lost.device_name.clone(),
+                    error: error.to_string(),
+                });
+                reconnect = Some(Reconnect {
+                    buffer: lost.buffer,
+                    device_name: lost.device_name,
+                    since: Instant::now(),
+                });
+            }
+            Some(InputCommand::Close) => {
+                reconnect = None;
+                if stream.take().is_some() {
+                    println!("[DEBUG] Audio input stream closed");
+                }
+            }
+            Some(InputCommand::Shutdown) => break,
+        }
+    }
+    drop(stream);
+    println!("[DEBUG] Audio input thread exited");
+}
+
+impl AudioInput {
+    fn spawn() -> Result<Arc<Self>> {
+        let (tx, rx) = mpsc::channel();
+        let listener: Arc<Mutex<Option<InputListener>>> = Arc::new(Mutex::new(None));
+        let input = InputThread {
+            commands: tx.clone(),
+            listener: Arc::clone(&listener),
+            generation: 0,
         };
+        let handle = thread::Builder::new()
+            .name("audio-input".into())
+            .spawn(move || run_input_thread(rx, input))
+            .map_err(|e| anyhow!("Failed to spawn audio input thread: {e}"))?;
+        Ok(Arc::new(Self {
+            commands: Mutex::new(tx),
+            listener,
+            thread: Mutex::new(Some(handle)),
+        }))
+    }
 
-        println!(
-            "[INFO] Using sample rate: {} Hz, channels: {}, format: {:?}",
-            config.sample_rate().0,
-            config.channels(),
-            config.sample_format()
-        );
+    // Replaces the callback for device loss / reconnection; runs on the capture thread
+    pub fn set_listener<F: Fn(&InputEvent) + Send + 'static>(&self, f: F) {
+        if let Ok(mut listener) = self.listener.lock() {
+            *listener = Some(Box::new(f));
+        }
+    }
 
-        let stream_config: StreamConfig = config.clone().into();
-        let err_fn = |err| eprintln!("[ERROR] Stream error: {}", err);
-        let channels = stream_config.channels as usize;
+    fn send(&self, command: InputCommand) -> Result<()> {
+        self.commands
+            .lock()
+            .map_err(|_| anyhow!("Audio input handle poisoned"))?
+            .send(command)
+            .map_err(|_| anyhow!("Audio input thread is not running"))
+    }
 
-        let input_sample_rate = stream_config.sample_rate.0;
-        let resample_factor = if input_sample_rate != SAMPLE_RATE as u32 {
-            input_sample_rate as f64 / SAMPLE_RATE as f64
-        } else {
-            1.0
-        };
+    // (Re)opens the microphone (or the WAV files of `source`), writing into `buffer`.
+    // Returns the device name.
+    pub fn open(
+        &self,
+        buffer: SharedAudioBuffer,
+        microphone_name: Option<String>,
+        default_microphone_index: usize,
+        source: &InputSourceConfig,
+    ) -> Result<String> {
+        let (ack, done) = mpsc::channel();
+        self.send(InputCommand::Open {
+            buffer,
+            microphone_name,
+            default_microphone_index,
+            source: source.clone(),
+            ack,
+        })?;
+        done.recv()
+            .map_err(|_| anyhow!("Audio input thread exited while opening the device"))?
+    }
+
+    // Releases the microphone; the thread stays around for the next start
+    pub fn stop(&self) {
+        let _ = self.send(InputCommand::Close);
+    }
+
+    // Ends the thread for good (app exit)
+    pub fn shutdown(&self) {
+        let _ = self.send(InputCommand::Shutdown);
+        if let Some(handle) = self.thread.lock().ok().and_then(|mut t| t.take()) {
+            let _ = handle.join();
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        self.thread
+            .lock()
+            .map(|t| t.as_ref().map(|h| !h.is_finished()).unwrap_or(false))
+            .unwrap_or(false)
+    }
+}
+
+// Starts capturing into `buffer`, reusing the capture thread from an earlier run if there is one.
+pub fn start_audio_stream(
+    buffer: SharedAudioBuffer,
+    microphone_name: Option<String>,
+    default_microphone_index: usize,
+    source: &InputSourceConfig,
+) -> Result<Arc<AudioInput>> {
+    let input = {
+        let mut slot = INPUT
+            .lock()
+            .map_err(|_| anyhow!("Audio input registry poisoned"))?;
+        match slot.as_ref().filter(|i| i.is_alive()) {
+            Some(existing) => {
+                println!("[DEBUG] Reusing audio input thread");
+                Arc::clone(existing)
+            }
+            None => {
+                println!("[DEBUG] Spawning audio input thread...");
+                let input = AudioInput::spawn()?;
+                *slot = Some(Arc::clone(&input));
+                input
+            }
+        }
+    };
+    input.open(buffer, microphone_name, default_microphone_index, source)?;
+    println!("[DEBUG] Audio input stream is now playing in the background.");
+    Ok(input)
+}
+
+// Stops the capture thread, if any; called when the app exits
+pub fn shutdown_audio_input() {
+    let input = INPUT.lock().ok().and_then(|mut slot| slot.take());
+    if let Some(input) = input {
+        input.shutdown();
+    }
+}
+
+// records `duration` of audio from the selected device on the calling thread (used by the setup check)
+pub fn capture_for(
+    microphone_name: Option<&str>,
+    default_microphone_index: usize,
+    duration: Duration,
+) -> Result<(String, Vec<i16>)> {
+    let device = choose_input_device(microphone_name, default_microphone_index)
+        .ok_or_else(|| anyhow!("No input device found"))?;
+    let device_name = device.name().unwrap_or_else(|_| "<unknown device>".to_string());
+    let capacity = (duration.as_millis() as usize * SAMPLE_RATE) / 1000 + SAMPLE_RATE;
+    let buffer = AudioRing::shared(capacity);
+    let stream = open_input_stream(&device, Arc::clone(&buffer), |err| {
+        eprintln!("[ERROR] Stream error: {}", err)
+    })?;
+    thread::sleep(duration);
+    drop(stream);
+    let samples = buffer
+        .lock()
+        .map_err(|e| anyhow!("Failed to lock audio buffer (poisoned): {e}"))?
+        .take(usize::MAX);
+    Ok((device_name, samples))
+}
+
+// builds and starts a 16 kHz mono stream on `device` that appends samples to `buffer`
+fn open_input_stream(
+    device: &Device,
+    buffer: SharedAudioBuffer,
+    err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
+) -> Result<cpal::Stream> {
+    let supported_config = device
+        .supported_input_configs()
+        .map_err(|e| anyhow!("Error getting supported configs: {e}"))?
+        .find(|c| {
+            c.channels() == 1
+                && c.min_sample_rate().0 <= 16_000
+                && c.max_sample_rate().0 >= 16_000
+                && c.sample_format() == SampleFormat::I16
+        });
+
+    let config = if let Some(c) = supported_config {
+        c.with_sample_rate(cpal::SampleRate(16_000))
+    } else {
+        device
+            .default_input_config()
+            .map_err(|e| anyhow!("No default config found: {e}"))?
+    };
+    build_capture_stream(device, config, buffer, err_fn)
+}
+
+// downmixes and resamples whatever `device` delivers with `config` into 16 kHz mono in `buffer`
+fn build_capture_stream(
+    device: &Device,
+    config: cpal::SupportedStreamConfig,
+    buffer: SharedAudioBuffer,
+    err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
+) -> Result<cpal::Stream> {
+    println!(
+        "[INFO] Using sample rate: {} Hz, channels: {}, format: {:?}",
+        config.sample_rate().0,
+        config.channels(),
+        config.sample_format()
+    );
 
-        let mut resample_pos = 0.0;
+    let stream_config: StreamConfig = config.clone().into();
+    let channels = stream_config.channels as usize;
 
-        let stream = match device.build_input_stream(
+    let input_sample_rate = stream_config.sample_rate.0;
+    let resample_factor = if input_sample_rate != SAMPLE_RATE as u32 {
+        input_sample_rate as f64 / SAMPLE_RATE as f64
+    } else {
+        1.0
+    };
+
+    let mut resample_pos = 0.0;
+    // the callback runs on the stream's own thread, so its settings are applied from there
+    let mut priority_applied = false;
+    // takes the first channel of each interleaved frame and resamples it to 16 kHz
+    let mut push_frames = move |data: &mut dyn Iterator<Item = i16>| {
+        if !priority_applied {
+            crate::thread_priority::apply(crate::thread_priority::AudioThread::Input);
+            priority_applied = true;
+        }
+        let mut buf = match buffer.lock() {
+            Ok(b) => b,
+            Err(_) => return,
+        };
+        for sample in data.step_by(channels) {
+            if resample_factor == 1.0 {
+                buf.push(sample);
+                continue;
+            }
+            while resample_pos < 1.0 {
+                buf.push(sample);
+                resample_pos += resample_factor;
+            }
+            resample_pos -= 1.0;
+        }
+        let complete = buf.len() >= buf.wanted;
+        drop(buf);
+        if complete {
+            buffer.ready.notify_all();
+        }
+    };
+
+    // loopback devices usually only offer f32
+    let stream = match config.sample_format() {
+        SampleFormat::F32 => device.build_input_stream(
             &stream_config,
-            move |data: &[i16], _| {
-                let mut buf = match buffer.lock() {
-                    Ok(b) => b,
-                    Err(_) => return,
-                };
-                let samples_iterator: Box<dyn Iterator<Item = i16>> = if resample_factor != 1.0 {
-                    let mut resampled = Vec::new();
-                    let input_samples = data.iter().step_by(channels).cloned();
-                    for sample in input_samples {
-                        while resample_pos < 1.0 {
-                            resampled.push(sample);
-                            resample_pos += resample_factor;
-                        }
-                        resample_pos -= 1.0;
-                    }
-                    Box::new(resampled.into_iter())
-                } else {
-                    Box::new(data.iter().step_by(channels).cloned())
-                };
-                for sample in samples_iterator {
-                    if buf.len() >= buf.capacity() {
-                        buf.pop_front();
-                    }
-                    buf.push_back(sample);
-                }
+            move |data: &[f32], _| {
+                push_frames(&mut data.iter().map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16))
             },
             err_fn,
             None,
-        ) {
-            Ok(s) => s,
-            Err(e) => {
-                eprintln!("[ERROR] Failed to build input stream: {e}");
-                return;
-            }
-        };
+        ),
+        _ => device.build_input_stream(
+            &stream_config,
+            move |data: &[i16], _| push_frames(&mut data.iter().copied()),
+            err_fn,
+            None,
+        ),
+    }
+    .map_err(|e| anyhow!("Failed to build input stream: {e}"))?;
 
-        if let Err(e) = stream.play() {
-            eprintln!("[ERROR] Failed to start input stream: {e}");
-            return;
+    stream
+        .play()
+        .map_err(|e| anyhow!("Failed to start input stream: {e}"))?;
+    Ok(stream)
+}
+
+// Opens the microphone for a consumer other than the wake-word loop (meeting notes).
+// Returns the device name with the stream; the stream must stay on the calling thread.
+pub fn open_microphone(
+    microphone_name: Option<&str>,
+    default_microphone_index: usize,
+    buffer: SharedAudioBuffer,
+) -> Result<(String, cpal::Stream)> {
+    let device = choose_input_device(microphone_name, default_microphone_index)
+        .ok_or_else(|| anyhow!("No input device found"))?;
+    let name = device.name().unwrap_or_else(|_| "<unknown device>".to_string());
+    let stream = open_input_stream(&device, buffer, |err| {
+        eprintln!("[ERROR] Stream error: {}", err)
+    })?;
+    Ok((name, stream))
+}
+
+// Records what an output device is playing. WASAPI does this by opening an input
+// stream on the render device; other hosts have no loopback in cpal.
+#[cfg(target_os = "windows")]
+pub fn open_loopback(
+    output_device_name: Option<&str>,
+    buffer: SharedAudioBuffer,
+) -> Result<(String, cpal::Stream)> {
+    let host = cpal::default_host();
+    let device = match output_device_name.filter(|n| !n.trim().is_empty()) {
+        Some(query) => {
+            let q = query.to_lowercase();
+            host.output_devices()
+                .map_err(|e| anyhow!("Error enumerating output devices: {e}"))?
+                .find(|d| {
+                    d.name()
+                        .map(|n| n.to_lowercase().contains(&q))
+                        .unwrap_or(false)
+                })
+                .or_else(|| host.default_output_device())
         }
+        None => host.default_output_device(),
+    }
+    .ok_or_else(|| anyhow!("No output device to record from"))?;
+    let name = device.name().unwrap_or_else(|_| "<unknown device>".to_string());
+    let config = device
+        .default_output_config()
+        .map_err(|e| anyhow!("No default output config found: {e}"))?;
+    let stream = build_capture_stream(&device, config, buffer, |err| {
+        eprintln!("[ERROR] Loopback stream error: {}", err)
+    })?;
+    Ok((name, stream))
+}
 
-        println!("[DEBUG] Audio input stream is now playing in the background.");
-        loop {
-            thread::sleep(Duration::from_secs(u64::MAX));
+#[cfg(not(target_os = "windows"))]
+pub fn open_loopback(
+    _output_device_name: Option<&str>,
+    _buffer: SharedAudioBuffer,
+) -> Result<(String, cpal::Stream)> {
+    Err(anyhow!("Recording system audio is only supported on Windows"))
+}
+
+// Converts mono audio at `rate` to 16 kHz, by repeating or skipping samples like the capture
+// callback does
+pub fn resample_to_16k(samples: impl IntoIterator<Item = i16>, rate: u32) -> Vec<i16> {
+    if rate == 0 {
+        return Vec::new();
+    }
+    let step = rate as f64 / SAMPLE_RATE as f64;
+    let mut out = Vec::new();
+    let mut pos = 0.0;
+    for sample in samples {
+        while pos < 1.0 {
+            out.push(sample);
+            pos += step;
         }
-    });
+        pos -= 1.0;
+    }
+    out
+}
+
+// Reads the first channel of a WAV file as 16 kHz samples
+pub fn read_wav(path: &Path) -> Result<Vec<i16>> {
+    let mut reader =
+        hound::WavReader::open(path).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    let spec = reader.spec();
+    let channels = spec.channels.max(1) as usize;
+    let interleaved: Vec<i16> = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .map(|s| s.map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16))
+            .collect::<Result<_, _>>()?,
+        hound::SampleFormat::Int => {
+            let shift = spec.bits_per_sample.saturating_sub(16) as u32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| (s >> shift) as i16))
+                .collect::<Result<_, _>>()?
+        }
+    };
+    let first_channel = interleaved.into_iter().step_by(channels);
+    Ok(resample_to_16k(first_channel, spec.sample_rate))
+}
+
+// The WAV files of an input source: the file itself, or a folder's WAV files by name
+fn wav_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files: Vec<PathBuf> = std::fs::read_dir(path)
+        .map_err(|e| anyhow!("{}: {}", path.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|ext| ext.to_string_lossy().eq_ignore_ascii_case("wav"))
+        })
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        return Err(anyhow!("No WAV files in {}", path.display()));
+    }
+    Ok(files)
+}
 
-    thread::sleep(Duration::from_millis(500));
-    println!("[DEBUG] Audio thread spawned. Continuing main execution.");
+// 10 ms, about what a microphone delivers per callback
+const FEED_CHUNK: usize = SAMPLE_RATE / 100;
 
-    Ok(())
+// Plays WAV files into the buffer on a thread of its own until dropped
+struct FileFeed {
+    stop: Arc<AtomicBool>,
+}
+
+impl FileFeed {
+    fn start(source: &InputSourceConfig, buffer: SharedAudioBuffer) -> Result<Self> {
+        let files = wav_files(Path::new(source.path.trim()))?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let feed_stop = Arc::clone(&stop);
+        let (speed, repeat) = (source.speed, source.repeat);
+        thread::Builder::new()
+            .name("audio-file-input".into())
+            .spawn(move || feed_files(&files, speed, repeat, &buffer, &feed_stop))
+            .map_err(|e| anyhow!("Failed to spawn audio file thread: {e}"))?;
+        Ok(Self { stop })
+    }
+}
+
+impl Drop for FileFeed {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+// Hands one chunk over at `speed`, or (speed 0) once the consumer needs more audio.
+// Returns false when the feed was stopped.
+fn feed_chunk(
+    chunk: &[i16],
+    speed: f32,
+    next: &mut Instant,
+    buffer: &AudioBuffer,
+    stop: &AtomicBool,
+) -> bool {
+    if speed > 0.0 {
+        *next += Duration::from_secs_f32(chunk.len() as f32 / SAMPLE_RATE as f32 / speed);
+        let wait = next.saturating_duration_since(Instant::now());
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    } else {
+        // keep at most a chunk ahead of what is asked for, so nothing is dropped or skipped
+        while buffer
+            .lock()
+            .map(|ring| ring.len() >= ring.wanted.max(FEED_CHUNK))
+            .unwrap_or(false)
+        {
+            if stop.load(Ordering::Relaxed) {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+    if stop.load(Ordering::Relaxed) {
+        return false;
+    }
+    buffer.extend(chunk);
+    true
+}
+
+fn feed_files(
+    files: &[PathBuf],
+    speed: f32,
+    repeat: bool,
+    buffer: &AudioBuffer,
+    stop: &AtomicBool,
+) {
+    let mut next = Instant::now();
+    loop {
+        for file in files {
+            let samples = match read_wav(file) {
+                Ok(samples) => samples,
+                Err(e) => {
+                    eprintln!("[WARN] Skipping {}: {}", file.display(), e);
+                    continue;
+                }
+            };
+            println!("[DEBUG] Playing {} into the audio input", file.display());
+            for chunk in samples.chunks(FEED_CHUNK) {
+                if !feed_chunk(chunk, speed, &mut next, buffer, stop) {
+                    return;
+                }
+            }
+        }
+        if !repeat {
+            break;
+        }
+    }
+    println!("[DEBUG] Finished playing the audio input files");
+    // then silence, like a microphone in a quiet room, so the last command still ends
+    // and the voice loop keeps getting audio until it is stopped
+    let silence = [0i16; FEED_CHUNK];
+    while feed_chunk(&silence, speed, &mut next, buffer, stop) {}
 }
 
 // blocks until a full frame of audio is available from the buffer
 pub fn next_audio_frame(
-    buffer: Arc<Mutex<VecDeque<i16>>>,
+    buffer: SharedAudioBuffer,
     frame_size: usize,
 ) -> anyhow::Result<Vec<i16>> {
     loop {
-        let mut buf = buffer
-            .lock()
-            .map_err(|e| anyhow!("Failed to lock audio buffer (poisoned): {e}"))?;
-
-        if buf.len() >= frame_size {
-            return Ok(buf.drain(..frame_size).collect());
+        if let Some(frame) = buffer.take_within(frame_size, Duration::from_secs(1))? {
+            return Ok(frame);
         }
-        drop(buf);
-        thread::sleep(Duration::from_millis(10));
     }
 }
 
+// exact (case-insensitive) name match only, used when waiting for a lost device to return
+fn find_input_device_by_name(name: &str) -> Option<Device> {
+    let mut devices = cpal::default_host().input_devices().ok()?;
+    devices.find(|d| d.name().map(|n| n.eq_ignore_ascii_case(name)).unwrap_or(false))
+}
+
 // chooses an input device by name (case-insensitive contains) or falls back to index
 fn choose_input_device(name: Option<&str>, index: usize) -> Option<Device> {
     if let Some(name_query) = name {
@@ -232,7 +977,7 @@ fn choose_input_device(name: Option<&str>, index: usize) -> Option<Device> {
                 index,
                 devices.len()
             );
-            if let Some(d) = devices.get(0) {
+            if let Some(d) = devices.first() {
                 d.clone()
             } else {
                 return None;
@@ -0,0 +1,647 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anyhow::{anyhow, Result};
+use cpal::traits::{DeviceTrait, HostTrait};
+use rodio::source::{SineWave, Source};
+use rodio::{Decoder, OutputStream, OutputStreamBuilder, Sink};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+// How urgently a sound wants the speaker. Higher runs first; High also pauses whatever
+// lower-priority sound is playing and lets it resume afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    // earcons and beeps
+    Low,
+    // spoken responses
+    Normal,
+    // timer alarms and other alerts
+    High,
+}
+
+enum Item {
+    Encoded(Vec<u8>),
+    File(PathBuf),
+    Tone(f32, Duration),
+}
+
+struct Job {
+    priority: Priority,
+    item: Item,
+}
+
+struct Playing {
+    priority: Priority,
+    sink: Sink,
+}
+
+// A looping background sound (rain, white noise) that plays under everything else
+// instead of waiting in the queue
+pub enum AmbientSound {
+    File(PathBuf),
+    Noise(NoiseColor),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseColor {
+    White,
+    Pink,
+    Brown,
+}
+
+struct Ambient {
+    sink: Sink,
+    volume: f32,
+    // when the fade-out started and how long it takes
+    fade: Option<(Instant, Duration)>,
+}
+
+// ambient sounds drop to this share of their volume while something else plays
+const AMBIENT_DUCK: f32 = 0.3;
+// speech playing through play_once, which doesn't go through the queue
+static ONE_SHOTS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Default)]
+struct State {
+    queue: VecDeque<Job>,
+    playing: Option<Playing>,
+    // paused by a higher-priority sound, resumed once it is done
+    suspended: Vec<Playing>,
+    ambient: Option<Ambient>,
+    // picked up by the worker, which owns the stream
+    ambient_request: Option<(AmbientSound, f32)>,
+    shutdown: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    wake: Condvar,
+}
+
+// The one place audio leaves the app. Sounds go through a priority queue played by a
+// worker thread that owns the device stream, so nothing overlaps or fights for the device.
+pub struct AudioOutput {
+    shared: Arc<Shared>,
+    app_handle: Option<tauri::AppHandle>,
+    worker: Option<std::thread::JoinHandle<()>>,
+    // drops everything instead of playing it (see `muted`)
+    muted: bool,
+}
+
+// the output the voice loop uses, for cmd_clear_audio_queue
+static ACTIVE: Mutex<Option<Weak<Shared>>> = Mutex::new(None);
+
+// Opens the named output device (case-insensitive substring match) or the default one
+fn open_stream(device_name: Option<&str>) -> Result<OutputStream> {
+    let name = match device_name.map(str::trim).filter(|n| !n.is_empty()) {
+        Some(n) => n,
+        None => {
+            return OutputStreamBuilder::from_default_device()?
+                .open_stream()
+                .map_err(|e| anyhow!("Audio init error: {}", e))
+        }
+    };
+    let host = cpal::default_host();
+    let name_lower = name.to_lowercase();
+    let device = match host.output_devices() {
+        Ok(mut devs) => devs.find(|d| {
+            d.name()
+                .map(|n| n.to_lowercase().contains(&name_lower))
+                .unwrap_or(false)
+        }),
+        Err(_) => {
+            println!("[WARN] Failed to enumerate output devices. Falling back to default output.");
+            None
+        }
+    };
+    let builder = match device {
+        Some(device) => {
+            println!(
+                "[INFO] Using output device by name: {}",
+                device.name().unwrap_or_else(|_| "<unknown>".into())
+            );
+            OutputStreamBuilder::from_device(device)?
+        }
+        None => {
+            println!("[WARN] Output device '{}' not found. Falling back to default.", name);
+            OutputStreamBuilder::from_default_device()?
+        }
+    };
+    builder
+        .open_stream()
+        .map_err(|e| anyhow!("Audio init error: {}", e))
+}
+
+// Finds a bundled sound: user override in <AppData>/assets, then the bundled
+// resource, then public/ when running in dev, else the path as given.
+pub(crate) fn resolve_asset(app_handle: &tauri::AppHandle, requested_path: &Path) -> PathBuf {
+    // If the requested path starts with the "assets" component, strip that for user dir
+    let user_rel: PathBuf = match requested_path.components().next() {
+        Some(Component::Normal(first)) if first == std::ffi::OsStr::new("assets") => {
+            requested_path.components().skip(1).collect()
+        }
+        _ => requested_path.to_path_buf(),
+    };
+    let user_override = app_handle
+        .path()
+        .app_config_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join("assets")
+        .join(user_rel);
+    if user_override.exists() {
+        println!("[DEBUG] Playing sound from user assets override: {}", user_override.display());
+        return user_override;
+    }
+
+    if let Ok(resolved) = app_handle
+        .path()
+        .resolve(requested_path, tauri::path::BaseDirectory::Resource)
+    {
+        if resolved.exists() {
+            println!("[DEBUG] Playing sound from bundled resource: {}", resolved.display());
+            return resolved;
+        }
+    }
+
+    let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    if let Some(parent) = current_dir.parent() {
+        let dev_path = parent.join("public").join(requested_path);
+        if dev_path.exists() {
+            println!("[DEBUG] Playing sound from dev public assets: {}", dev_path.display());
+            return dev_path;
+        }
+    }
+
+    println!(
+        "[DEBUG] No user override, bundled resource, or dev asset; falling back to requested path: {}",
+        requested_path.display()
+    );
+    requested_path.to_path_buf()
+}
+
+fn source_for(item: Item) -> Result<Box<dyn Source + Send>> {
+    Ok(match item {
+        Item::Encoded(bytes) => {
+            Box::new(Decoder::new(Cursor::new(bytes)).map_err(|e| anyhow!("Decode error: {}", e))?)
+        }
+        Item::File(path) => Box::new(Decoder::new(BufReader::new(File::open(path)?))?),
+        Item::Tone(frequency, duration) => {
+            Box::new(SineWave::new(frequency).take_duration(duration).amplify(0.2))
+        }
+    })
+}
+
+// Generated noise, so the noises work without any sound files. Pink and brown are
+// white noise filtered towards the low end; a small xorshift is random enough here.
+struct Noise {
+    color: NoiseColor,
+    seed: u32,
+    pink: [f32; 7],
+    brown: f32,
+}
+
+impl Noise {
+    fn new(color: NoiseColor) -> Self {
+        Self {
+            color,
+            seed: 0x9E37_79B9,
+            pink: [0.0; 7],
+            brown: 0.0,
+        }
+    }
+
+    fn white(&mut self) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        self.seed as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+impl Iterator for Noise {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let white = self.white();
+        Some(match self.color {
+            NoiseColor::White => white * 0.3,
+            // Paul Kellet's filter
+            NoiseColor::Pink => {
+                let b = &mut self.pink;
+                b[0] = 0.99886 * b[0] + white * 0.0555179;
+                b[1] = 0.99332 * b[1] + white * 0.0750759;
+                b[2] = 0.96900 * b[2] + white * 0.153852;
+                b[3] = 0.86650 * b[3] + white * 0.3104856;
+                b[4] = 0.55000 * b[4] + white * 0.5329522;
+                b[5] = -0.7616 * b[5] - white * 0.0168980;
+                let out = b.iter().sum::<f32>() + white * 0.5362;
+                b[6] = white * 0.115926;
+                out * 0.08
+            }
+            NoiseColor::Brown => {
+                self.brown = (self.brown + 0.02 * white) / 1.02;
+                self.brown * 2.5
+            }
+        })
+    }
+}
+
+impl Source for Noise {
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        44_100
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+fn ambient_source(sound: AmbientSound) -> Result<Box<dyn Source + Send>> {
+    Ok(match sound {
+        AmbientSound::File(path) => {
+            Box::new(Decoder::new(BufReader::new(File::open(path)?))?.repeat_infinite())
+        }
+        AmbientSound::Noise(color) => Box::new(Noise::new(color)),
+    })
+}
+
+// Starts a requested ambient sound and keeps the current one's volume in step with
+// ducking and its fade-out
+fn update_ambient(st: &mut State, stream: &OutputStream) {
+    if let Some((sound, volume)) = st.ambient_request.take() {
+        if let Some(old) = st.ambient.take() {
+            old.sink.stop();
+        }
+        match ambient_source(sound) {
+            Ok(source) => {
+                let sink = Sink::connect_new(stream.mixer());
+                sink.set_volume(0.0);
+                sink.append(source);
+                st.ambient = Some(Ambient {
+                    sink,
+                    volume,
+                    fade: None,
+                });
+            }
+            Err(e) => eprintln!("[ERROR] Failed to start ambient sound: {}", e),
+        }
+    }
+    let busy = st.playing.is_some() || ONE_SHOTS.load(Ordering::Relaxed) > 0;
+    let Some(ambient) = st.ambient.as_mut() else {
+        return;
+    };
+    let mut level = ambient.volume;
+    if busy {
+        level *= AMBIENT_DUCK;
+    }
+    if let Some((started, length)) = ambient.fade {
+        let done = started.elapsed().as_secs_f32() / length.as_secs_f32().max(0.001);
+        if done >= 1.0 {
+            st.ambient.take().unwrap().sink.stop();
+            return;
+        }
+        level *= 1.0 - done;
+    }
+    ambient.sink.set_volume(level);
+}
+
+// highest priority first, first-in first-out within a priority
+fn pop_next(queue: &mut VecDeque<Job>) -> Option<Job> {
+    let best = queue.iter().map(|j| j.priority).max()?;
+    let idx = queue.iter().position(|j| j.priority == best)?;
+    queue.remove(idx)
+}
+
+fn run_worker(shared: Arc<Shared>, stream: OutputStream, volume: f32) {
+    let mut st = shared.state.lock().unwrap();
+    loop {
+        if st.shutdown {
+            break;
+        }
+        if st.playing.as_ref().map(|p| p.sink.empty()).unwrap_or(false) {
+            st.playing = None;
+        }
+        update_ambient(&mut st, &stream);
+        if st.playing.is_none() {
+            let queued = st.queue.iter().map(|j| j.priority).max();
+            let resumable = st.suspended.last().map(|p| p.priority);
+            if resumable.is_some() && resumable >= queued {
+                let p = st.suspended.pop().unwrap();
+                p.sink.play();
+                st.playing = Some(p);
+            } else if let Some(job) = pop_next(&mut st.queue) {
+                match source_for(job.item) {
+                    Ok(source) => {
+                        let sink = Sink::connect_new(stream.mixer());
+                        if job.priority == Priority::Normal {
+                            sink.set_volume(volume);
+                        }
+                        sink.append(source);
+                        st.playing = Some(Playing {
+                            priority: job.priority,
+                            sink,
+                        });
+                    }
+                    Err(e) => eprintln!("[ERROR] Skipping queued sound: {}", e),
+                }
+                continue;
+            }
+        }
+        // woken early by new jobs; the timeout notices sinks running dry
+        st = shared
+            .wake
+            .wait_timeout(st, Duration::from_millis(30))
+            .unwrap()
+            .0;
+    }
+    if let Some(p) = st.playing.take() {
+        p.sink.stop();
+    }
+    if let Some(a) = st.ambient.take() {
+        a.sink.stop();
+    }
+}
+
+impl AudioOutput {
+    // `volume` applies to spoken responses only; 1.0 is full volume
+    pub fn open(
+        app_handle: Option<tauri::AppHandle>,
+        device_name: Option<&str>,
+        volume: f32,
+    ) -> Result<Self> {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::default()),
+            wake: Condvar::new(),
+        });
+        let device_name = device_name.map(str::to_string);
+        let volume = volume.clamp(0.0, 1.0);
+        // the stream has to live on the thread that uses it
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+        let worker_shared = Arc::clone(&shared);
+        let worker = std::thread::spawn(move || match open_stream(device_name.as_deref()) {
+            Ok(stream) => {
+                let _ = ready_tx.send(Ok(()));
+                run_worker(worker_shared, stream, volume);
+            }
+            Err(e) => {
+                let _ = ready_tx.send(Err(e));
+            }
+        });
+        ready_rx
+            .recv()
+            .map_err(|_| anyhow!("Audio output thread exited during startup"))??;
+        Ok(Self {
+            shared,
+            app_handle,
+            worker: Some(worker),
+            muted: false,
+        })
+    }
+
+    // An output without a device that drops whatever it is given, for running the voice
+    // loop where there are no speakers (see Pipeline::muted)
+    pub fn muted(app_handle: Option<tauri::AppHandle>) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State::default()),
+                wake: Condvar::new(),
+            }),
+            app_handle,
+            worker: None,
+            muted: true,
+        }
+    }
+
+    // makes this the output cmd_clear_audio_queue acts on
+    pub fn make_active(&self) {
+        *ACTIVE.lock().unwrap() = Some(Arc::downgrade(&self.shared));
+    }
+
+    fn enqueue(&self, priority: Priority, item: Item) {
+        if self.muted {
+            return;
+        }
+        let mut st = self.shared.state.lock().unwrap();
+        if priority == Priority::High {
+            let preempt = st
+                .playing
+                .as_ref()
+                .map(|p| p.priority < Priority::High)
+                .unwrap_or(false);
+            if preempt {
+                let p = st.playing.take().unwrap();
+                p.sink.pause();
+                st.suspended.push(p);
+            }
+        }
+        st.queue.push_back(Job { priority, item });
+        self.shared.wake.notify_all();
+    }
+
+    fn resolve(&self, path: &Path) -> PathBuf {
+        match &self.app_handle {
+            Some(app_handle) => resolve_asset(app_handle, path),
+            None => path.to_path_buf(),
+        }
+    }
+
+    // Queues a short sound file (wake beep, earcon)
+    pub fn play_sound<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let sound_path = self.resolve(path.as_ref());
+        if !sound_path.exists() {
+            return Err(anyhow!("Sound file not found: {}", sound_path.display()));
+        }
+        self.enqueue(Priority::Low, Item::File(sound_path));
+        Ok(())
+    }
+
+    // Queues a short sine beep
+    pub fn play_tone(&self, frequency: f32, duration: Duration) {
+        self.enqueue(Priority::Low, Item::Tone(frequency, duration));
+    }
+
+    // Plays an alert sound ahead of everything else, pausing speech until it is done
+    pub fn play_alert<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let sound_path = self.resolve(path.as_ref());
+        if !sound_path.exists() {
+            return Err(anyhow!("Sound file not found: {}", sound_path.display()));
+        }
+        self.enqueue(Priority::High, Item::File(sound_path));
+        Ok(())
+    }
+
+    // Adds encoded speech (MP3, WAV, ...) to the end of the queue
+    pub fn queue(&self, audio: Vec<u8>) -> Result<()> {
+        self.enqueue(Priority::Normal, Item::Encoded(audio));
+        Ok(())
+    }
+
+    // Replaces whatever is being said with `audio`
+    pub fn play(&self, audio: Vec<u8>) -> Result<()> {
+        self.stop();
+        self.queue(audio)
+    }
+
+    // Cuts off the current speech and drops speech queued behind it; alerts and beeps stay
+    pub fn stop(&self) {
+        let mut st = self.shared.state.lock().unwrap();
+        st.queue.retain(|j| j.priority != Priority::Normal);
+        st.suspended.retain(|p| p.priority != Priority::Normal);
+        if st.playing.as_ref().map(|p| p.priority == Priority::Normal).unwrap_or(false) {
+            st.playing.take().unwrap().sink.stop();
+        }
+        self.shared.wake.notify_all();
+    }
+
+    // Drops everything: playing, paused and queued
+    pub fn clear(&self) {
+        clear_shared(&self.shared);
+    }
+
+    pub fn is_idle(&self) -> bool {
+        let st = self.shared.state.lock().unwrap();
+        st.queue.is_empty()
+            && st.suspended.is_empty()
+            && st.playing.as_ref().map(|p| p.sink.empty()).unwrap_or(true)
+    }
+
+    // Blocks until the queue has played out
+    pub fn wait_until_idle(&self) {
+        while !self.is_idle() {
+            std::thread::sleep(Duration::from_millis(30));
+        }
+    }
+}
+
+impl Drop for AudioOutput {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().shutdown = true;
+        self.shared.wake.notify_all();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn clear_shared(shared: &Shared) {
+    let mut st = shared.state.lock().unwrap();
+    st.queue.clear();
+    for p in st.suspended.drain(..) {
+        p.sink.stop();
+    }
+    if let Some(p) = st.playing.take() {
+        p.sink.stop();
+    }
+    shared.wake.notify_all();
+}
+
+// Decodes encoded audio (e.g. a voice's MP3) into interleaved 16-bit samples;
+// returns (sample rate, channels, samples)
+pub fn decode(audio: Vec<u8>) -> Result<(u32, u16, Vec<i16>)> {
+    let decoder = Decoder::new(Cursor::new(audio)).map_err(|e| anyhow!("Decode error: {}", e))?;
+    let rate = decoder.sample_rate();
+    let channels = decoder.channels();
+    let samples = decoder
+        .map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+        .collect();
+    Ok((rate, channels, samples))
+}
+
+// Plays `audio` once on a temporary output and waits for it to finish. For speech
+// outside the voice loop (UI, local commands), where no AppContext is around.
+pub async fn play_once(device_name: Option<String>, volume: f32, audio: Vec<u8>) -> Result<()> {
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let output = AudioOutput::open(None, device_name.as_deref(), volume)?;
+        output.queue(audio)?;
+        ONE_SHOTS.fetch_add(1, Ordering::Relaxed);
+        output.wait_until_idle();
+        ONE_SHOTS.fetch_sub(1, Ordering::Relaxed);
+        Ok(())
+    })
+    .await
+    .map_err(|e| anyhow!("Playback thread panic: {}", e))?
+}
+
+// Same as play_once for a beep
+pub async fn tone_once(device_name: Option<String>, frequency: f32, duration: Duration) -> Result<()> {
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let output = AudioOutput::open(None, device_name.as_deref(), 1.0)?;
+        output.play_tone(frequency, duration);
+        output.wait_until_idle();
+        Ok(())
+    })
+    .await
+    .map_err(|e| anyhow!("Playback thread panic: {}", e))?
+}
+
+fn active() -> Option<Arc<Shared>> {
+    ACTIVE.lock().unwrap().as_ref().and_then(Weak::upgrade)
+}
+
+// Loops `sound` under everything else on the voice loop's output, replacing the
+// current ambient sound; `volume` is 0.0 to 1.0
+pub fn start_ambient(sound: AmbientSound, volume: f32) -> Result<()> {
+    let shared = active().ok_or_else(|| anyhow!("Start listening first"))?;
+    let mut st = shared.state.lock().unwrap();
+    st.ambient_request = Some((sound, volume.clamp(0.0, 1.0)));
+    shared.wake.notify_all();
+    Ok(())
+}
+
+// Fades the ambient sound out and stops it; a zero duration stops it right away
+pub fn fade_out_ambient(over: Duration) {
+    let Some(shared) = active() else {
+        return;
+    };
+    let mut st = shared.state.lock().unwrap();
+    st.ambient_request = None;
+    if let Some(ambient) = st.ambient.as_mut() {
+        ambient.fade = Some((Instant::now(), over));
+    }
+    shared.wake.notify_all();
+}
+
+pub fn ambient_playing() -> bool {
+    active().is_some_and(|shared| {
+        let st = shared.state.lock().unwrap();
+        st.ambient.is_some() || st.ambient_request.is_some()
+    })
+}
+
+// Stops and drops everything queued on the voice loop's output
+#[tauri::command]
+pub fn cmd_clear_audio_queue() -> Result<(), String> {
+    if let Some(shared) = active() {
+        clear_shared(&shared);
+    }
+    Ok(())
+}
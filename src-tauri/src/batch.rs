@@ -0,0 +1,238 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Operations on many conversations at once (multi-select in the history list). Each
+// walks the selection once off the async runtime and reports `batch-progress`, instead
+// of the UI calling the single-file commands in a loop.
+
+use crate::events::{self, BatchProgress};
+use crate::{attachments, branches, conversation_settings};
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+
+// archived conversations live here, out of the history list but with their
+// attachments and settings untouched
+const ARCHIVE_DIR: &str = "archive";
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchResult {
+    pub succeeded: usize,
+    // "<filename>: <error>" for every conversation that could not be handled
+    pub failed: Vec<String>,
+}
+
+// only names from the history list, never paths
+fn check_name(filename: &str) -> Result<(), String> {
+    if filename.is_empty() || filename.contains(['/', '\\']) || filename.contains("..") {
+        return Err("Invalid conversation name".into());
+    }
+    Ok(())
+}
+
+fn archive_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::history_dir(app)?.join(ARCHIVE_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+fn is_conversation(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("json") || e.eq_ignore_ascii_case("jsonl"))
+}
+
+fn conversations_in(dir: &Path) -> Vec<PathBuf> {
+    let mut out: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|rd| rd.flatten().map(|e| e.path()).filter(|p| is_conversation(p)).collect())
+        .unwrap_or_default();
+    out.sort();
+    out
+}
+
+// Runs `f` on each name, collecting failures and emitting progress after each one
+fn for_each(
+    app: &tauri::AppHandle,
+    operation: &str,
+    filenames: &[String],
+    mut f: impl FnMut(&str) -> Result<(), String>,
+) -> BatchResult {
+    let mut result = BatchResult::default();
+    for (i, filename) in filenames.iter().enumerate() {
+        match check_name(filename).and_then(|_| f(filename)) {
+            Ok(()) => result.succeeded += 1,
+            Err(e) => result.failed.push(format!("{}: {}", filename, e)),
+        }
+        events::emit(
+            app,
+            BatchProgress {
+                operation: operation.to_string(),
+                done: i + 1,
+                total: filenames.len(),
+                filename: filename.clone(),
+            },
+        );
+    }
+    result
+}
+
+fn delete_all(app: &tauri::AppHandle, filenames: &[String]) -> Result<BatchResult, String> {
+    let history = crate::history_dir(app)?;
+    let result = for_each(app, "delete", filenames, |filename| {
+        let path = history.join(filename);
+        if path.exists() {
+            std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+        }
+        branches::remove(app, filename);
+        attachments::remove(app, filename);
+        Ok(())
+    });
+    // settings share one file, so they are rewritten once at the end
+    conversation_settings::remove_many(app, filenames);
+    Ok(result)
+}
+
+fn move_all(
+    app: &tauri::AppHandle,
+    operation: &str,
+    filenames: &[String],
+    from: &Path,
+    to: &Path,
+) -> BatchResult {
+    for_each(app, operation, filenames, |filename| {
+        let target = to.join(filename);
+        if target.exists() {
+            return Err("a conversation with this name already exists".into());
+        }
+        std::fs::rename(from.join(filename), target).map_err(|e| e.to_string())
+    })
+}
+
+// Conversations and their attachments; archived ones under `archive/`
+fn export_all(app: &tauri::AppHandle, path: &str) -> Result<String, String> {
+    let history = crate::history_dir(app)?;
+    let mut out = PathBuf::from(path);
+    if out.is_dir() {
+        out = out.join(format!(
+            "jarvis-conversations-{}.zip",
+            chrono::Local::now().format("%Y-%m-%d_%H-%M-%S")
+        ));
+    }
+
+    let archived = archive_dir(app)?;
+    let archive_prefix = format!("{}/", ARCHIVE_DIR);
+    let files: Vec<(String, PathBuf)> = conversations_in(&history)
+        .into_iter()
+        .map(|p| (String::new(), p))
+        .chain(conversations_in(&archived).into_iter().map(|p| (archive_prefix.clone(), p)))
+        .collect();
+
+    let file = std::fs::File::create(&out).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let mut add = |name: &str, bytes: &[u8]| -> Result<(), String> {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(bytes).map_err(|e| e.to_string())
+    };
+
+    for (i, (prefix, path)) in files.iter().enumerate() {
+        let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+        add(&format!("{}{}", prefix, filename), &bytes)?;
+        let (stem, _) = crate::history::split_extension(&filename);
+        for attachment in std::fs::read_dir(history.join("attachments").join(stem))
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            if let Ok(bytes) = std::fs::read(attachment.path()) {
+                let name = attachment.file_name().to_string_lossy().to_string();
+                add(&format!("attachments/{}/{}", stem, name), &bytes)?;
+            }
+        }
+        events::emit(
+            app,
+            BatchProgress {
+                operation: "export".into(),
+                done: i + 1,
+                total: files.len(),
+                filename,
+            },
+        );
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(out.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub async fn cmd_delete_conversations(
+    app: tauri::AppHandle,
+    filenames: Vec<String>,
+) -> Result<BatchResult, String> {
+    tokio::task::spawn_blocking(move || delete_all(&app, &filenames))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// Moves conversations out of the history list without deleting anything
+#[tauri::command]
+pub async fn cmd_archive_conversations(
+    app: tauri::AppHandle,
+    filenames: Vec<String>,
+) -> Result<BatchResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let history = crate::history_dir(&app)?;
+        let archived = archive_dir(&app)?;
+        Ok(move_all(&app, "archive", &filenames, &history, &archived))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn cmd_unarchive_conversations(
+    app: tauri::AppHandle,
+    filenames: Vec<String>,
+) -> Result<BatchResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let history = crate::history_dir(&app)?;
+        let archived = archive_dir(&app)?;
+        Ok(move_all(&app, "unarchive", &filenames, &archived, &history))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn cmd_list_archived_conversations(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    Ok(conversations_in(&archive_dir(&app)?)
+        .iter()
+        .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .collect())
+}
+
+// Writes every conversation into one zip; `path` is the file or a folder to put it in.
+// Returns where the zip was written.
+#[tauri::command]
+pub async fn cmd_export_all(app: tauri::AppHandle, path: String) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || export_all(&app, &path))
+        .await
+        .map_err(|e| e.to_string())?
+}
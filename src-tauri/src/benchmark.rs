@@ -0,0 +1,232 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Times each stage of the voice pipeline on this machine with the current settings,
+// to compare Whisper models, LLMs and voices. The voice reads a fixed sentence, and
+// Whisper transcribes that recording (or a WAV file of your own) back.
+
+use crate::audio_input::{read_wav, resample_to_16k, SAMPLE_RATE};
+use crate::models::Config;
+use crate::stt::SttWorker;
+use crate::tts::Speech;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::AtomicBool;
+use std::time::Instant;
+
+const SENTENCE: &str =
+    "The quick brown fox jumps over the lazy dog while the weather stays sunny and warm.";
+const PROMPT: &str = "In three sentences, explain why the sky is blue.";
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BenchmarkOptions {
+    // WAV file to transcribe instead of the voice's recording of the test sentence
+    pub audio_path: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StageResult {
+    // "tts", "stt_load", "stt" or "llm"
+    pub stage: String,
+    // model or provider that was measured
+    pub model: String,
+    pub ms: u64,
+    // characters per second for tts and llm; seconds of audio per second for stt
+    pub throughput: Option<f64>,
+    // what came out: the transcript or the start of the answer
+    pub output: String,
+    pub error: Option<String>,
+}
+
+impl StageResult {
+    fn new(stage: &str, model: &str) -> Self {
+        Self {
+            stage: stage.to_string(),
+            model: model.to_string(),
+            ms: 0,
+            throughput: None,
+            output: String::new(),
+            error: None,
+        }
+    }
+
+    fn failed(mut self, e: impl std::fmt::Display) -> Self {
+        self.error = Some(e.to_string());
+        self
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkReport {
+    pub stages: Vec<StageResult>,
+    // share of the test sentence's words found in the transcript, when it was transcribed
+    pub word_accuracy: Option<f32>,
+}
+
+fn per_second(amount: f64, started: Instant) -> Option<f64> {
+    let secs = started.elapsed().as_secs_f64();
+    (secs > 0.0).then(|| amount / secs)
+}
+
+// Synthesizes the test sentence; returns the stage and the audio, if the voice made any
+async fn run_tts(config: &Config) -> (StageResult, Option<Vec<u8>>) {
+    let provider = crate::tts::provider_for(config);
+    let mut stage = StageResult::new("tts", provider.name());
+    if provider.name() == "elevenlabs" {
+        stage.model = format!("elevenlabs {}", config.elevenlabs_model);
+    }
+    let started = Instant::now();
+    let result = provider.synthesize(SENTENCE).await;
+    stage.ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok(speech) => {
+            stage.throughput = per_second(SENTENCE.chars().count() as f64, started);
+            stage.output = SENTENCE.to_string();
+            let audio = match speech {
+                Speech::Audio(bytes) => Some(bytes),
+                // the system voice speaks aloud and can't be recorded
+                Speech::Spoken => None,
+            };
+            (stage, audio)
+        }
+        Err(e) => (stage.failed(e), None),
+    }
+}
+
+fn decode_for_whisper(audio: Vec<u8>) -> Result<Vec<i16>> {
+    let (rate, channels, samples) = crate::audio_output::decode(audio)?;
+    let first_channel = samples.into_iter().step_by(channels.max(1) as usize);
+    Ok(resample_to_16k(first_channel, rate))
+}
+
+// Share of the expected words that appear in the transcript, ignoring case and punctuation
+fn word_accuracy(expected: &str, transcript: &str) -> f32 {
+    let words = |text: &str| -> Vec<String> {
+        text.split_whitespace()
+            .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+            .filter(|w| !w.is_empty())
+            .collect()
+    };
+    let expected = words(expected);
+    let heard = words(transcript);
+    if expected.is_empty() {
+        return 0.0;
+    }
+    let found = expected.iter().filter(|w| heard.contains(w)).count();
+    found as f32 / expected.len() as f32
+}
+
+async fn run_stt(app: &tauri::AppHandle, config: &Config, samples: Vec<i16>) -> Vec<StageResult> {
+    let model = crate::run_jarvis::DEFAULT_WHISPER_MODEL;
+    let mut load = StageResult::new("stt_load", model);
+    let started = Instant::now();
+    let stt = match SttWorker::shared(app).await {
+        Ok(stt) => stt,
+        Err(e) => return vec![load.failed(e)],
+    };
+    // near zero once the model is in memory
+    load.ms = started.elapsed().as_millis() as u64;
+
+    let mut stage = StageResult::new("stt", model);
+    let audio_secs = samples.len() as f64 / SAMPLE_RATE as f64;
+    let keep_going = AtomicBool::new(true);
+    let started = Instant::now();
+    let result = stt
+        .submit(crate::get_text::pad_for_whisper(&samples), &config.whisper_language)
+        .wait_while(&keep_going)
+        .await;
+    stage.ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok(text) => {
+            stage.throughput = per_second(audio_secs, started);
+            stage.output = text.unwrap_or_default().trim().to_string();
+            vec![load, stage]
+        }
+        Err(e) => vec![load, stage.failed(e)],
+    }
+}
+
+async fn run_llm(config: &Config) -> StageResult {
+    let mut stage = StageResult::new("llm", &config.gemini_model);
+    if config.gemini_key.trim().is_empty() {
+        return stage.failed("Missing Gemini API key");
+    }
+    let started = Instant::now();
+    let result = crate::send_to_llm::query_gemini(PROMPT, config, "").await;
+    stage.ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok(answer) => {
+            stage.throughput = per_second(answer.chars().count() as f64, started);
+            stage.output = answer.chars().take(200).collect();
+            stage
+        }
+        Err(e) => stage.failed(e),
+    }
+}
+
+#[tauri::command]
+pub async fn cmd_run_benchmark(
+    app: tauri::AppHandle,
+    options: Option<BenchmarkOptions>,
+) -> Result<BenchmarkReport, String> {
+    let options = options.unwrap_or_default();
+    let config = crate::to_run_config(crate::cmd_load_config(app.clone())?);
+    let mut stages = Vec::new();
+
+    let (tts, speech) = run_tts(&config).await;
+    stages.push(tts);
+
+    let audio_path = options.audio_path.filter(|p| !p.trim().is_empty());
+    let (samples, expected) = match (&audio_path, speech) {
+        (Some(path), _) => (read_wav(std::path::Path::new(path)), None),
+        (None, Some(speech)) => {
+            let decoded = tokio::task::spawn_blocking(move || decode_for_whisper(speech))
+                .await
+                .map_err(|e| e.to_string())?;
+            (decoded, Some(SENTENCE))
+        }
+        (None, None) => (
+            Err(anyhow!(
+                "Nothing to transcribe: the system voice can't be recorded, so pick a WAV file"
+            )),
+            None,
+        ),
+    };
+    let mut accuracy = None;
+    match samples {
+        Ok(samples) => {
+            let results = run_stt(&app, &config, samples).await;
+            let transcript = results.iter().find(|s| s.stage == "stt" && s.error.is_none());
+            if let (Some(expected), Some(stt)) = (expected, transcript) {
+                accuracy = Some(word_accuracy(expected, &stt.output));
+            }
+            stages.extend(results);
+        }
+        Err(e) => stages.push(
+            StageResult::new("stt", crate::run_jarvis::DEFAULT_WHISPER_MODEL).failed(e),
+        ),
+    }
+
+    stages.push(run_llm(&config).await);
+    Ok(BenchmarkReport {
+        stages,
+        word_accuracy: accuracy,
+    })
+}
@@ -0,0 +1,212 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::TurnDto;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+// One alternative continuation of a conversation from a branch point onwards.
+// Branch points further down that variant are kept with it so switching back restores them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Variant {
+    pub turns: Vec<TurnDto>,
+    #[serde(default)]
+    pub branches: Vec<BranchPoint>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BranchPoint {
+    #[serde(rename = "turnIndex")]
+    pub turn_index: usize,
+    pub variants: Vec<Variant>,
+    pub active: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BranchSummary {
+    #[serde(rename = "turnIndex")]
+    pub turn_index: usize,
+    #[serde(rename = "variantCount")]
+    pub variant_count: usize,
+    pub active: usize,
+}
+
+fn branches_path(app: &tauri::AppHandle, filename: &str) -> Result<PathBuf, String> {
+    let dir = crate::history_dir(app)?.join("branches");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(filename))
+}
+
+fn load(app: &tauri::AppHandle, filename: &str) -> Vec<BranchPoint> {
+    let Ok(path) = branches_path(app, filename) else {
+        return Vec::new();
+    };
+    std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save(app: &tauri::AppHandle, filename: &str, points: &[BranchPoint]) -> Result<(), String> {
+    let path = branches_path(app, filename)?;
+    if points.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+        }
+        return Ok(());
+    }
+    let s = serde_json::to_string_pretty(points).map_err(|e| e.to_string())?;
+    crate::history::atomic_write(&path, s.as_bytes()).map_err(|e| e.to_string())
+}
+
+// pulls out branch points that live inside the tail starting at `from`
+fn split_nested(points: &mut Vec<BranchPoint>, from: usize) -> Vec<BranchPoint> {
+    let (nested, kept): (Vec<_>, Vec<_>) = points.drain(..).partition(|p| p.turn_index > from);
+    *points = kept;
+    nested
+}
+
+// Replaces everything from `turn_index` on with `new_tail`, keeping the old tail as a variant.
+// Returns the resulting turns.
+pub fn replace_tail(
+    app: &tauri::AppHandle,
+    filename: &str,
+    turn_index: usize,
+    new_tail: Vec<TurnDto>,
+) -> Result<Vec<TurnDto>, String> {
+    let mut turns = crate::read_turns(app, filename)?;
+    if turn_index > turns.len() {
+        return Err(format!(
+            "Turn index {} is out of range ({} turns)",
+            turn_index,
+            turns.len()
+        ));
+    }
+    let mut points = load(app, filename);
+    let nested = split_nested(&mut points, turn_index);
+    let old_tail = Variant {
+        turns: turns.split_off(turn_index),
+        branches: nested,
+    };
+
+    match points.iter_mut().find(|p| p.turn_index == turn_index) {
+        Some(point) => {
+            let active = point.active;
+            if let Some(slot) = point.variants.get_mut(active) {
+                *slot = old_tail;
+            } else {
+                point.variants.push(old_tail);
+            }
+            point.variants.push(Variant {
+                turns: new_tail.clone(),
+                branches: Vec::new(),
+            });
+            point.active = point.variants.len() - 1;
+        }
+        None => points.push(BranchPoint {
+            turn_index,
+            variants: vec![
+                old_tail,
+                Variant {
+                    turns: new_tail.clone(),
+                    branches: Vec::new(),
+                },
+            ],
+            active: 1,
+        }),
+    }
+
+    turns.extend(new_tail);
+    crate::write_turns(app, filename, &turns)?;
+    save(app, filename, &points)?;
+    Ok(turns)
+}
+
+// Swaps the conversation tail at a branch point for another stored variant
+pub fn switch(
+    app: &tauri::AppHandle,
+    filename: &str,
+    turn_index: usize,
+    variant: usize,
+) -> Result<Vec<TurnDto>, String> {
+    let mut turns = crate::read_turns(app, filename)?;
+    let mut points = load(app, filename);
+    let nested = split_nested(&mut points, turn_index);
+    let point = points
+        .iter_mut()
+        .find(|p| p.turn_index == turn_index)
+        .ok_or_else(|| format!("No branch at turn {}", turn_index))?;
+    if variant >= point.variants.len() {
+        return Err(format!(
+            "Branch at turn {} has only {} variant(s)",
+            turn_index,
+            point.variants.len()
+        ));
+    }
+    if turn_index > turns.len() {
+        return Err("Conversation is shorter than the branch point".into());
+    }
+
+    let active = point.active;
+    point.variants[active] = Variant {
+        turns: turns.split_off(turn_index),
+        branches: nested,
+    };
+    let target = std::mem::take(&mut point.variants[variant]);
+    point.active = variant;
+    point.variants[variant] = Variant {
+        turns: target.turns.clone(),
+        branches: Vec::new(),
+    };
+
+    turns.extend(target.turns);
+    points.extend(target.branches);
+    crate::write_turns(app, filename, &turns)?;
+    save(app, filename, &points)?;
+    Ok(turns)
+}
+
+pub fn list(app: &tauri::AppHandle, filename: &str) -> Vec<BranchSummary> {
+    let mut out: Vec<BranchSummary> = load(app, filename)
+        .iter()
+        .map(|p| BranchSummary {
+            turn_index: p.turn_index,
+            variant_count: p.variants.len(),
+            active: p.active,
+        })
+        .collect();
+    out.sort_by_key(|b| b.turn_index);
+    out
+}
+
+pub fn rename(app: &tauri::AppHandle, old: &str, new: &str) {
+    if let (Ok(from), Ok(to)) = (branches_path(app, old), branches_path(app, new)) {
+        if from.exists() {
+            if let Err(e) = std::fs::rename(&from, &to) {
+                eprintln!("[WARN] Failed to move branch data: {}", e);
+            }
+        }
+    }
+}
+
+pub fn remove(app: &tauri::AppHandle, filename: &str) {
+    if let Ok(path) = branches_path(app, filename) {
+        if path.exists() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
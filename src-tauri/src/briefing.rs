@@ -0,0 +1,658 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// The start of the day in one spoken update: "good morning", "what does my day look
+// like". The sections and their order come from the settings, and a section with
+// nothing to say is left out. Events and reminders (to-dos) are read from iCalendar
+// feeds such as the secret address of a Google or Outlook calendar, headlines from RSS
+// or Atom feeds. `cmd_run_briefing` lets the app run it on a schedule.
+
+use crate::models::Config;
+use anyhow::{anyhow, Result};
+use chrono::{
+    DateTime, Datelike, Days, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BriefingSection {
+    Date,
+    Weather,
+    Calendar,
+    Reminders,
+    News,
+    // the finance watchlist
+    Markets,
+    Packages,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct BriefingConfig {
+    // spoken in this order
+    pub sections: Vec<BriefingSection>,
+    // iCalendar (.ics or webcal://) addresses; their events make up the calendar
+    // section and their to-dos due today or earlier the reminders
+    pub calendars: Vec<String>,
+    // RSS or Atom feeds
+    pub news_feeds: Vec<String>,
+    // per feed
+    pub headlines: usize,
+}
+
+impl Default for BriefingConfig {
+    fn default() -> Self {
+        Self {
+            sections: vec![
+                BriefingSection::Date,
+                BriefingSection::Weather,
+                BriefingSection::Calendar,
+                BriefingSection::Reminders,
+                BriefingSection::News,
+            ],
+            calendars: Vec::new(),
+            news_feeds: vec!["https://feeds.bbci.co.uk/news/world/rss.xml".to_string()],
+            headlines: 3,
+        }
+    }
+}
+
+static ITEM: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)<(?:item|entry)\b[^>]*>(.*?)</(?:item|entry)>")
+        .expect("Failed to compile feed item regex")
+});
+
+static TITLE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)<title\b[^>]*>(.*?)</title>").expect("Failed to compile feed title regex")
+});
+
+static CDATA: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<!\[CDATA\[(.*?)\]\]>").expect("Failed to compile CDATA regex"));
+
+static TAGS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<[^>]+>").expect("Failed to compile tag regex"));
+
+static ENTITY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"&#(x?)([0-9a-fA-F]+);").expect("Failed to compile entity regex"));
+
+fn client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .user_agent(concat!("Jarvis/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| anyhow!("Failed to build HTTP client: {e}"))
+}
+
+async fn fetch(client: &reqwest::Client, url: &str) -> Result<String> {
+    let url = url.trim();
+    // calendar apps hand out webcal:// links for the same address
+    let url = match url.strip_prefix("webcal://") {
+        Some(rest) => format!("https://{}", rest),
+        None => url.to_string(),
+    };
+    let resp = client.get(&url).send().await?.error_for_status()?;
+    Ok(resp.text().await?)
+}
+
+// "a", "a and b", "a, b, and c"
+fn spoken_list(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [one] => one.clone(),
+        [a, b] => format!("{} and {}", a, b),
+        [rest @ .., last] => format!("{}, and {}", rest.join(", "), last),
+    }
+}
+
+fn spoken_time(t: NaiveTime) -> String {
+    if t.minute() == 0 {
+        t.format("%-I %p").to_string()
+    } else {
+        t.format("%-I:%M %p").to_string()
+    }
+}
+
+fn date_section() -> String {
+    let now = Local::now();
+    let part = match now.hour() {
+        0..=11 => "morning",
+        12..=17 => "afternoon",
+        _ => "evening",
+    };
+    let name = crate::user_profile::current().name;
+    let greeting = if name.trim().is_empty() {
+        format!("Good {}.", part)
+    } else {
+        format!("Good {}, {}.", part, name.trim())
+    };
+    format!("{} It's {}.", greeting, now.format("%A, %B %-d"))
+}
+
+struct Property {
+    name: String,
+    params: HashMap<String, String>,
+    value: String,
+}
+
+struct Component {
+    // VEVENT or VTODO
+    kind: String,
+    properties: Vec<Property>,
+}
+
+impl Component {
+    fn get(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        let text = unescape(&self.get(name)?.value);
+        (!text.is_empty()).then_some(text)
+    }
+
+    fn time(&self, name: &str) -> Option<When> {
+        let p = self.get(name)?;
+        parse_time(&p.value, &p.params)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum When {
+    Day(NaiveDate),
+    At(DateTime<Local>),
+}
+
+impl When {
+    fn date(&self) -> NaiveDate {
+        match self {
+            When::Day(d) => *d,
+            When::At(t) => t.date_naive(),
+        }
+    }
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+        .trim()
+        .to_string()
+}
+
+fn parse_property(line: &str) -> Option<Property> {
+    let (head, value) = line.split_once(':')?;
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_uppercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.to_uppercase(), v.trim_matches('"').to_string()))
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+// The events and to-dos of a calendar, without their alarms
+fn parse_calendar(ics: &str) -> Vec<Component> {
+    // long lines continue on the next one after a space
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    let mut components = Vec::new();
+    let mut current: Option<Component> = None;
+    let mut nested = 0;
+    for property in lines.iter().filter_map(|l| parse_property(l)) {
+        match property.name.as_str() {
+            "BEGIN" if current.is_some() => nested += 1,
+            "BEGIN" => {
+                let kind = property.value.trim().to_uppercase();
+                if kind == "VEVENT" || kind == "VTODO" {
+                    current = Some(Component {
+                        kind,
+                        properties: Vec::new(),
+                    });
+                }
+            }
+            "END" if nested > 0 => nested -= 1,
+            "END" => components.extend(current.take()),
+            _ if nested == 0 => {
+                if let Some(c) = current.as_mut() {
+                    c.properties.push(property);
+                }
+            }
+            _ => {}
+        }
+    }
+    components
+}
+
+// Times without a zone are local; Windows zone names (from Outlook) aren't known
+// and are taken as local too
+fn parse_time(value: &str, params: &HashMap<String, String>) -> Option<When> {
+    let value = value.trim();
+    if value.len() == 8 || params.get("VALUE").is_some_and(|v| v == "DATE") {
+        let day = NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()?;
+        return Some(When::Day(day));
+    }
+    let (text, utc) = match value.strip_suffix('Z') {
+        Some(text) => (text, true),
+        None => (value, false),
+    };
+    let naive = NaiveDateTime::parse_from_str(text, "%Y%m%dT%H%M%S").ok()?;
+    let zone = params.get("TZID").and_then(|z| z.parse::<Tz>().ok());
+    let at = if utc {
+        Utc.from_utc_datetime(&naive).with_timezone(&Local)
+    } else if let Some(zone) = zone {
+        zone.from_local_datetime(&naive)
+            .earliest()?
+            .with_timezone(&Local)
+    } else {
+        Local.from_local_datetime(&naive).earliest()?
+    };
+    Some(When::At(at))
+}
+
+fn weekday(code: &str) -> Option<Weekday> {
+    Some(match code {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+fn days_in_month(day: NaiveDate) -> u32 {
+    let first = day.with_day(1).expect("first of the month exists");
+    let next = first
+        .checked_add_months(chrono::Months::new(1))
+        .unwrap_or(first);
+    (next - first).num_days() as u32
+}
+
+// BYMONTHDAY: 15 is the 15th, -1 the last day
+fn is_month_day(day: NaiveDate, n: i64) -> bool {
+    let from_end = (days_in_month(day) - day.day() + 1) as i64;
+    (n > 0 && day.day() as i64 == n) || (n < 0 && from_end == -n)
+}
+
+// BYDAY in a month: 2TU is the second Tuesday, -1FR the last Friday, FR every Friday
+fn is_nth_weekday(day: NaiveDate, n: i64, wd: Weekday) -> bool {
+    let from_start = (day.day() as i64 - 1) / 7 + 1;
+    let from_end = (days_in_month(day) as i64 - day.day() as i64) / 7 + 1;
+    day.weekday() == wd && (n == 0 || from_start == n || from_end == -n)
+}
+
+// Whether a recurring event that began on `start` happens on `day`. Covers the rules
+// calendar apps write for repeating events; COUNT is counted per period, which is
+// only approximate when a weekly event started mid-week.
+fn recurs_on(rule: &str, start: NaiveDate, day: NaiveDate) -> bool {
+    if day < start {
+        return false;
+    }
+    let rule = rule.to_uppercase();
+    let parts: HashMap<&str, &str> = rule.split(';').filter_map(|p| p.split_once('=')).collect();
+    let interval = parts
+        .get("INTERVAL")
+        .and_then(|i| i.parse::<i64>().ok())
+        .unwrap_or(1)
+        .max(1);
+    let until = parts
+        .get("UNTIL")
+        .and_then(|u| u.get(..8))
+        .and_then(|u| NaiveDate::parse_from_str(u, "%Y%m%d").ok());
+    if until.is_some_and(|until| day > until) {
+        return false;
+    }
+    let by_day: Vec<(i64, Weekday)> = parts
+        .get("BYDAY")
+        .map(|days| {
+            days.split(',')
+                .filter_map(|d| {
+                    let split = d.len().checked_sub(2)?;
+                    let n = if split == 0 {
+                        0
+                    } else {
+                        d[..split].parse().ok()?
+                    };
+                    Some((n, weekday(&d[split..])?))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let listed_weekday = by_day.iter().any(|(_, wd)| *wd == day.weekday());
+    let months =
+        ((day.year() - start.year()) * 12 + day.month() as i32 - start.month() as i32) as i64;
+    let (hit, index) = match parts.get("FREQ").copied() {
+        Some("DAILY") => {
+            let days = (day - start).num_days();
+            (
+                days % interval == 0 && (by_day.is_empty() || listed_weekday),
+                days / interval,
+            )
+        }
+        Some("WEEKLY") => {
+            let monday = |d: NaiveDate| d - Days::new(d.weekday().num_days_from_monday() as u64);
+            let weeks = (monday(day) - monday(start)).num_days() / 7;
+            let on_day = if by_day.is_empty() {
+                day.weekday() == start.weekday()
+            } else {
+                listed_weekday
+            };
+            let earlier = by_day
+                .iter()
+                .filter(|(_, wd)| wd.num_days_from_monday() < day.weekday().num_days_from_monday())
+                .count() as i64;
+            let index = weeks / interval * by_day.len().max(1) as i64 + earlier;
+            (weeks % interval == 0 && on_day, index)
+        }
+        Some("MONTHLY") => {
+            let on_day = if let Some(days) = parts.get("BYMONTHDAY") {
+                days.split(',')
+                    .filter_map(|d| d.parse().ok())
+                    .any(|n| is_month_day(day, n))
+            } else if !by_day.is_empty() {
+                by_day.iter().any(|(n, wd)| is_nth_weekday(day, *n, *wd))
+            } else {
+                day.day() == start.day()
+            };
+            (months % interval == 0 && on_day, months / interval)
+        }
+        Some("YEARLY") => {
+            let on_day = months % 12 == 0 && day.day() == start.day();
+            (
+                on_day && (months / 12) % interval == 0,
+                months / 12 / interval,
+            )
+        }
+        _ => (false, 0),
+    };
+    let within_count = parts
+        .get("COUNT")
+        .and_then(|c| c.parse::<i64>().ok())
+        .is_none_or(|count| index < count);
+    hit && within_count
+}
+
+// Today's events as (start time, title); all-day ones have no time and come first
+fn events_on(components: &[Component], day: NaiveDate) -> Vec<(Option<NaiveTime>, String)> {
+    let events: Vec<&Component> = components.iter().filter(|c| c.kind == "VEVENT").collect();
+    // a single occurrence of a repeating event that was moved or changed
+    let moved: HashSet<(String, NaiveDate)> = events
+        .iter()
+        .filter_map(|e| Some((e.text("UID")?, e.time("RECURRENCE-ID")?.date())))
+        .collect();
+    let mut found = Vec::new();
+    for event in events {
+        if event
+            .text("STATUS")
+            .is_some_and(|s| s.eq_ignore_ascii_case("CANCELLED"))
+        {
+            continue;
+        }
+        let Some(start) = event.time("DTSTART") else {
+            continue;
+        };
+        let rule = event
+            .get("RRULE")
+            .filter(|_| event.get("RECURRENCE-ID").is_none());
+        let occurs = match rule {
+            Some(rule) => {
+                let uid = event.text("UID").unwrap_or_default();
+                let excluded = event
+                    .properties
+                    .iter()
+                    .filter(|p| p.name == "EXDATE")
+                    .flat_map(|p| p.value.split(',').filter_map(|v| parse_time(v, &p.params)))
+                    .any(|w| w.date() == day);
+                recurs_on(&rule.value, start.date(), day)
+                    && !excluded
+                    && !moved.contains(&(uid, day))
+            }
+            None => {
+                // all-day events end the day before their DTEND
+                let end = match event.time("DTEND") {
+                    Some(When::Day(d)) => d.pred_opt().unwrap_or(d),
+                    Some(When::At(t)) => (t - chrono::Duration::seconds(1)).date_naive(),
+                    None => start.date(),
+                };
+                start.date() <= day && day <= end.max(start.date())
+            }
+        };
+        if !occurs {
+            continue;
+        }
+        // an event that began on an earlier day just takes up today
+        let time = match start {
+            When::At(t) if rule.is_some() || t.date_naive() == day => Some(t.time()),
+            _ => None,
+        };
+        let title = event
+            .text("SUMMARY")
+            .unwrap_or_else(|| "an event".to_string());
+        found.push((time, title));
+    }
+    found.sort();
+    found
+}
+
+fn calendar_section(components: &[Component]) -> String {
+    let events = events_on(components, Local::now().date_naive());
+    if events.is_empty() {
+        return "There's nothing on your calendar today.".to_string();
+    }
+    let items: Vec<String> = events
+        .into_iter()
+        .map(|(time, title)| match time {
+            Some(t) => format!("{} at {}", title, spoken_time(t)),
+            None => format!("{} all day", title),
+        })
+        .collect();
+    let count = if items.len() == 1 {
+        "one thing".to_string()
+    } else {
+        format!("{} things", items.len())
+    };
+    format!(
+        "You have {} on your calendar today: {}.",
+        count,
+        spoken_list(&items)
+    )
+}
+
+// Open to-dos due today, and the overdue ones
+fn reminders_section(components: &[Component]) -> Option<String> {
+    let today = Local::now().date_naive();
+    let (mut due, mut overdue) = (Vec::new(), Vec::new());
+    for todo in components.iter().filter(|c| c.kind == "VTODO") {
+        let status = todo.text("STATUS").unwrap_or_default().to_uppercase();
+        if todo.get("COMPLETED").is_some() || status == "COMPLETED" || status == "CANCELLED" {
+            continue;
+        }
+        let Some(date) = todo.time("DUE").map(|d| d.date()) else {
+            continue;
+        };
+        let title = todo
+            .text("SUMMARY")
+            .unwrap_or_else(|| "a to-do".to_string());
+        if date == today {
+            due.push(title);
+        } else if date < today {
+            overdue.push(title);
+        }
+    }
+    let mut lines = Vec::new();
+    if !due.is_empty() {
+        lines.push(format!("Don't forget to {} today.", spoken_list(&due)));
+    }
+    if !overdue.is_empty() {
+        lines.push(format!(
+            "Still open from before: {}.",
+            spoken_list(&overdue)
+        ));
+    }
+    (!lines.is_empty()).then(|| lines.join(" "))
+}
+
+fn clean(text: &str) -> String {
+    let text = CDATA.replace_all(text, "$1");
+    let text = TAGS.replace_all(&text, " ");
+    let text = ENTITY.replace_all(&text, |c: &regex::Captures| {
+        let radix = if c[1].is_empty() { 10 } else { 16 };
+        u32::from_str_radix(&c[2], radix)
+            .ok()
+            .and_then(char::from_u32)
+            .map(String::from)
+            .unwrap_or_default()
+    });
+    let text = text
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// "Headlines from BBC News: ..." with the feed's first few items
+fn headlines(feed: &str, count: usize) -> Option<String> {
+    let first_item = ITEM.find(feed).map(|m| m.start()).unwrap_or(feed.len());
+    let source = TITLE
+        .captures(&feed[..first_item])
+        .map(|c| clean(&c[1]))
+        .filter(|s| !s.is_empty());
+    let titles: Vec<String> = ITEM
+        .captures_iter(feed)
+        .filter_map(|item| TITLE.captures(&item[1]).map(|t| clean(&t[1])))
+        .filter(|t| !t.is_empty())
+        .take(count)
+        .map(|t| {
+            if t.ends_with(['.', '!', '?']) {
+                t
+            } else {
+                format!("{}.", t)
+            }
+        })
+        .collect();
+    if titles.is_empty() {
+        return None;
+    }
+    Some(match source {
+        Some(source) => format!("Headlines from {}: {}", source, titles.join(" ")),
+        None => format!("In the news: {}", titles.join(" ")),
+    })
+}
+
+async fn news_section(client: &reqwest::Client, config: &BriefingConfig) -> Option<String> {
+    let mut parts = Vec::new();
+    for url in config.news_feeds.iter().filter(|u| !u.trim().is_empty()) {
+        match fetch(client, url).await {
+            Ok(feed) => parts.extend(headlines(&feed, config.headlines.max(1))),
+            Err(e) => eprintln!("[WARN] Failed to fetch news feed {}: {}", url, e),
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+async fn load_calendars(client: &reqwest::Client, config: &BriefingConfig) -> Vec<Component> {
+    let mut components = Vec::new();
+    for url in config.calendars.iter().filter(|u| !u.trim().is_empty()) {
+        match fetch(client, url).await {
+            Ok(ics) => components.extend(parse_calendar(&ics)),
+            Err(e) => eprintln!("[WARN] Failed to fetch calendar {}: {}", url, e),
+        }
+    }
+    components
+}
+
+// The whole briefing as one text, sections in the configured order
+pub async fn compose(app: &tauri::AppHandle, config: &Config) -> String {
+    let briefing = &config.briefing;
+    let client = match client() {
+        Ok(c) => c,
+        Err(e) => return format!("Sorry, I couldn't put the briefing together: {}", e),
+    };
+    let wants_calendar = briefing
+        .sections
+        .iter()
+        .any(|s| matches!(s, BriefingSection::Calendar | BriefingSection::Reminders));
+    let has_calendars = briefing.calendars.iter().any(|u| !u.trim().is_empty());
+    let calendar = if wants_calendar && has_calendars {
+        load_calendars(&client, briefing).await
+    } else {
+        Vec::new()
+    };
+    let mut parts = Vec::new();
+    for section in &briefing.sections {
+        let part = match section {
+            BriefingSection::Date => Some(date_section()),
+            BriefingSection::Weather => Some(
+                crate::transform_text::get_weather(app, &crate::intent::Slots::default()).await,
+            ),
+            // without a calendar there is nothing to say either way
+            BriefingSection::Calendar => has_calendars.then(|| calendar_section(&calendar)),
+            BriefingSection::Reminders => reminders_section(&calendar),
+            BriefingSection::News => news_section(&client, briefing).await,
+            BriefingSection::Markets if config.finance.watchlist.is_empty() => None,
+            BriefingSection::Markets => {
+                Some(crate::finance::watchlist_briefing(&config.finance).await)
+            }
+            BriefingSection::Packages => crate::packages::summary(app, &config.packages).await,
+        };
+        parts.extend(part.filter(|p| !p.trim().is_empty()));
+    }
+    parts.join(" ")
+}
+
+// Composes the briefing, shows it in the chat and speaks it; returns the text
+pub async fn run(app: &tauri::AppHandle, config: &Config) -> String {
+    let text = compose(app, config).await;
+    crate::events::emit(
+        app,
+        crate::events::NewMessage::new(crate::events::Role::Assistant, &text)
+            .with("localCommand", true),
+    );
+    if let Err(e) = crate::tts::speak_with(config, &text).await {
+        eprintln!("[ERROR] Failed to speak briefing: {}", e);
+    }
+    text
+}
+
+#[tauri::command]
+pub async fn cmd_run_briefing(app: tauri::AppHandle) -> Result<String, String> {
+    let config = crate::to_run_config(crate::cmd_load_config(app.clone())?);
+    Ok(run(&app, &config).await)
+}
@@ -0,0 +1,191 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::audio_input::SAMPLE_RATE;
+use crate::{get_text, run_jarvis};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::Manager;
+use whisper_rs::{WhisperContext, WhisperContextParameters};
+
+// opt-in archive of recorded speech segments for reproducing transcription bugs
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CaptureConfig {
+    pub enabled: bool,
+    pub max_captures: usize,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_captures: 50,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CaptureInfo {
+    pub id: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    pub transcript: String,
+    pub language: String,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: u64,
+}
+
+pub(crate) fn captures_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| e.to_string())?
+        .join("diagnostics")
+        .join("captures");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+pub(crate) fn wav_path(app: &tauri::AppHandle, id: &str) -> Result<PathBuf, String> {
+    if id.contains('/') || id.contains('\\') || id.contains("..") {
+        return Err("Invalid capture id".into());
+    }
+    Ok(captures_dir(app)?.join(format!("{}.wav", id)))
+}
+
+fn read_infos(app: &tauri::AppHandle) -> Vec<CaptureInfo> {
+    let Ok(dir) = captures_dir(app) else {
+        return Vec::new();
+    };
+    let mut infos: Vec<CaptureInfo> = std::fs::read_dir(dir)
+        .map(|rd| {
+            rd.flatten()
+                .filter(|e| {
+                    e.path()
+                        .extension()
+                        .map(|x| x.eq_ignore_ascii_case("json"))
+                        .unwrap_or(false)
+                })
+                .filter_map(|e| std::fs::read_to_string(e.path()).ok())
+                .filter_map(|s| serde_json::from_str::<CaptureInfo>(&s).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    infos.sort_by_key(|i| std::cmp::Reverse(i.created_at));
+    infos
+}
+
+// saves a segment and its transcript, then trims the archive to `max_captures`
+pub fn save_capture(
+    app: &tauri::AppHandle,
+    cfg: &CaptureConfig,
+    samples: &[i16],
+    transcript: &str,
+    language: &str,
+) -> Result<CaptureInfo, String> {
+    let dir = captures_dir(app)?;
+    let created_at = chrono::Utc::now().timestamp_millis();
+    let info = CaptureInfo {
+        id: format!("capture-{}", created_at),
+        created_at,
+        transcript: transcript.to_string(),
+        language: language.to_string(),
+        duration_ms: (samples.len() as u64 * 1000) / SAMPLE_RATE as u64,
+    };
+    crate::utils::write_wav_i16(
+        &dir.join(format!("{}.wav", info.id)),
+        samples,
+        SAMPLE_RATE as u32,
+    )
+    .map_err(|e| e.to_string())?;
+    let s = serde_json::to_string_pretty(&info).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(format!("{}.json", info.id)), s).map_err(|e| e.to_string())?;
+
+    // ring behaviour: drop the oldest captures beyond the cap
+    for old in read_infos(app).into_iter().skip(cfg.max_captures.max(1)) {
+        let _ = std::fs::remove_file(dir.join(format!("{}.wav", old.id)));
+        let _ = std::fs::remove_file(dir.join(format!("{}.json", old.id)));
+    }
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn cmd_list_recent_captures(
+    app: tauri::AppHandle,
+    limit: Option<usize>,
+) -> Result<Vec<CaptureInfo>, String> {
+    let mut infos = read_infos(&app);
+    if let Some(n) = limit {
+        infos.truncate(n);
+    }
+    Ok(infos)
+}
+
+fn read_wav_i16(path: &std::path::Path) -> Result<Vec<i16>, String> {
+    let mut reader = hound::WavReader::open(path).map_err(|e| e.to_string())?;
+    reader
+        .samples::<i16>()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+// runs Whisper again on a saved capture, optionally with another model size or language
+#[tauri::command]
+pub async fn cmd_retranscribe(
+    app: tauri::AppHandle,
+    capture_id: String,
+    model: Option<String>,
+    language: Option<String>,
+) -> Result<String, String> {
+    let samples = read_wav_i16(&wav_path(&app, &capture_id)?)?;
+    let language = match language.filter(|l| !l.trim().is_empty()) {
+        Some(l) => l,
+        None => crate::cmd_load_config(app.clone())?.whisper_language,
+    };
+    let model = model
+        .filter(|m| !m.trim().is_empty())
+        .unwrap_or_else(|| run_jarvis::DEFAULT_WHISPER_MODEL.to_string());
+    if !model
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_')
+    {
+        return Err(format!("Invalid Whisper model name '{}'", model));
+    }
+
+    let model_path = run_jarvis::whisper_model_path(&app, &model);
+    run_jarvis::download_whisper_with_progress(
+        &app,
+        &run_jarvis::whisper_model_url(&model),
+        &model_path,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    tokio::task::spawn_blocking(move || -> Result<String, String> {
+        let ctx = WhisperContext::new_with_params(
+            model_path.to_str().ok_or("Invalid model path")?,
+            WhisperContextParameters::default(),
+        )
+        .map_err(|e| format!("Failed to load Whisper model: {}", e))?;
+        get_text::transcribe(&ctx, &samples, &language)
+            .map(|t| t.trim().to_string())
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,183 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Clipboard workflows started by a hotkey or a phrase rather than by "control V"
+// in a prompt: summarizing what was copied, and rewriting or answering it back onto
+// the clipboard ("rewrite this more formally", "answer this email").
+
+use crate::events::{self, NewMessage, Role};
+use crate::models::Config;
+use crate::transform_text;
+use anyhow::{anyhow, Result};
+use arboard::Clipboard;
+use regex::Regex;
+
+const SUMMARY_PROMPT: &str = "You summarize content the user copied. Reply with a short summary of at most three sentences in plain spoken language, without lists, markdown or code. If the content is an image, summarize the text and information shown in it. Use the language of the content.";
+
+const REWRITE_PROMPT: &str = "You transform content the user copied. Follow the user's instruction and reply with only the resulting text, ready to paste: no preamble, no quotes around it, no explanations and no [[copy]] tags. Keep the language of the content unless the instruction asks for a translation. When asked to answer or reply to it, write the reply itself.";
+
+// words in a rewrite request; longer prompts are treated as questions of their own
+const MAX_REWRITE_WORDS: usize = 16;
+
+// "rewrite this more formally", "translate my clipboard to German", "answer this email";
+// returns the whole request as the instruction for the model. "translate this: ..."
+// brings its own text and is left to the normal chat.
+pub fn match_rewrite_command(prompt: &str) -> Option<String> {
+    let re = Regex::new(
+        r"(?i)^\s*(?:please\s+|can you\s+|could you\s+)?(?:rewrite|rephrase|reword|translate|shorten|expand|proofread|fix|polish|answer|reply to|respond to|make)\s+(?:this|that|my clipboard|the clipboard|what i copied|the copied text)\b",
+    )
+    .expect("Failed to compile rewrite regex");
+    let prompt = prompt.trim();
+    if prompt.contains(':')
+        || prompt.contains('\n')
+        || prompt.split_whitespace().count() > MAX_REWRITE_WORDS
+    {
+        return None;
+    }
+    re.is_match(prompt).then(|| prompt.to_string())
+}
+
+// Summarizes the clipboard text, or the text in a copied image, with the chat model;
+// None when there is nothing on the clipboard
+pub async fn summarize(config: &Config) -> Result<Option<String>> {
+    let Some(content) = transform_text::clipboard_as_prompt_text().filter(|c| !c.trim().is_empty())
+    else {
+        return Ok(None);
+    };
+    let mut summarizer = config.clone();
+    summarizer.llm_system_prompt = SUMMARY_PROMPT.to_string();
+    let summary = crate::send_to_llm::query_gemini(&content, &summarizer, "").await?;
+    Ok(Some(summary.trim().to_string()))
+}
+
+// Transforms the clipboard per `instruction` and puts the result back on the clipboard;
+// None when there is nothing on the clipboard
+pub async fn rewrite(config: &Config, instruction: &str) -> Result<Option<String>> {
+    let Some(content) = transform_text::clipboard_as_prompt_text().filter(|c| !c.trim().is_empty())
+    else {
+        return Ok(None);
+    };
+    let mut rewriter = config.clone();
+    rewriter.llm_system_prompt = REWRITE_PROMPT.to_string();
+    let prompt = format!("Instruction: {}\n\nContent:\n{}", instruction, content);
+    let result = crate::send_to_llm::query_gemini(&prompt, &rewriter, "").await?;
+    let result = result.trim().to_string();
+    if result.is_empty() {
+        return Err(anyhow!("The model returned nothing"));
+    }
+    Clipboard::new()
+        .and_then(|mut c| c.set_text(result.clone()))
+        .map_err(|e| anyhow!("Failed to set clipboard text: {}", e))?;
+    Ok(Some(result))
+}
+
+// Runs a rewrite command, shows the result in the chat and announces it; returns the reply
+pub async fn rewrite_and_announce(
+    app: &tauri::AppHandle,
+    config: &Config,
+    instruction: &str,
+    speak: bool,
+) -> String {
+    if config.gemini_key.trim().is_empty() {
+        let reply = "Please enter your Gemini API key in Settings > API Keys.".to_string();
+        respond(app, config, &reply, speak).await;
+        return reply;
+    }
+    let (shown, spoken) = match rewrite(config, instruction).await {
+        Ok(Some(result)) => (
+            format!("Copied to your clipboard:\n\n{}", result),
+            "Done, it's on your clipboard.".to_string(),
+        ),
+        Ok(None) => {
+            let reply = "Your clipboard is empty.".to_string();
+            (reply.clone(), reply)
+        }
+        Err(e) => {
+            eprintln!("[ERROR] Clipboard rewrite failed: {}", e);
+            let reply = "I couldn't rewrite your clipboard.".to_string();
+            (reply.clone(), reply)
+        }
+    };
+    respond(app, config, &shown, false).await;
+    if speak {
+        if let Err(e) = crate::tts::speak_with(config, &spoken).await {
+            eprintln!("[ERROR] Failed to speak clipboard result: {}", e);
+        }
+    }
+    shown
+}
+
+// Posts the result to the chat and optionally reads it out
+pub async fn respond(app: &tauri::AppHandle, config: &Config, text: &str, speak: bool) {
+    events::emit(app, NewMessage::new(Role::Assistant, text).with("localCommand", true));
+    if speak {
+        if let Err(e) = crate::tts::speak_with(config, text).await {
+            eprintln!("[ERROR] Failed to speak clipboard result: {}", e);
+        }
+    }
+}
+
+// Summary, or the reason there is none, as something to say
+pub async fn summarize_reply(config: &Config) -> String {
+    if config.gemini_key.trim().is_empty() {
+        return "Please enter your Gemini API key in Settings > API Keys.".to_string();
+    }
+    match summarize(config).await {
+        Ok(Some(summary)) if !summary.is_empty() => summary,
+        Ok(None) => "Your clipboard is empty.".to_string(),
+        Ok(Some(_)) => "I couldn't summarize your clipboard.".to_string(),
+        Err(e) => {
+            eprintln!("[ERROR] Failed to summarize clipboard: {}", e);
+            "I couldn't summarize your clipboard.".to_string()
+        }
+    }
+}
+
+// Hotkey entry point; the summary is spoken since the main window may be hidden
+pub fn summarize_hotkey(app: &tauri::AppHandle) -> Result<(), String> {
+    let config = crate::to_run_config(crate::cmd_load_config(app.clone())?);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let reply = summarize_reply(&config).await;
+        respond(&app, &config, &reply, true).await;
+    });
+    Ok(())
+}
+
+// Returns the summary; `speak` also reads it aloud
+#[tauri::command]
+pub async fn cmd_summarize_clipboard(app: tauri::AppHandle, speak: Option<bool>) -> Result<String, String> {
+    let config = crate::to_run_config(crate::cmd_load_config(app.clone())?);
+    let summary = summarize(&config)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "The clipboard is empty".to_string())?;
+    respond(&app, &config, &summary, speak.unwrap_or(false)).await;
+    Ok(summary)
+}
+
+// Transforms the clipboard per `instruction` and returns the text now on it
+#[tauri::command]
+pub async fn cmd_rewrite_clipboard(app: tauri::AppHandle, instruction: String) -> Result<String, String> {
+    let config = crate::to_run_config(crate::cmd_load_config(app.clone())?);
+    let result = rewrite(&config, &instruction)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "The clipboard is empty".to_string())?;
+    respond(&app, &config, &format!("Copied to your clipboard:\n\n{}", result), false).await;
+    Ok(result)
+}
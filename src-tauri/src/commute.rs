@@ -0,0 +1,542 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Travel times: "how long to get to work right now", "how's my commute", "how long
+// to the airport by train". "Work" and "home" are the addresses in the user profile;
+// other places are looked up by name. Google, HERE or OpenRouteService work out the
+// route; Google and HERE know the live traffic and public transport, OpenRouteService
+// only the drive without traffic.
+
+use crate::user_profile::{self, Units};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteProvider {
+    Google,
+    Here,
+    Openrouteservice,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TravelMode {
+    Driving,
+    Transit,
+    Walking,
+    Cycling,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CommuteConfig {
+    pub provider: RouteProvider,
+    pub api_key: String,
+    // used when the question doesn't say "by train", "walk", ...
+    pub mode: TravelMode,
+}
+
+impl Default for CommuteConfig {
+    fn default() -> Self {
+        Self {
+            provider: RouteProvider::Google,
+            api_key: String::new(),
+            mode: TravelMode::Driving,
+        }
+    }
+}
+
+// the last "to ..." in the question, without the "right now" or "by bus" after it
+static DESTINATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)^.*\b(?:to|into)\s+(?:the\s+|my\s+)?(.+?)",
+        r"(?:\s+(?:right now|now|today|tonight|this morning|this evening|at the moment",
+        r"|by [a-z]+(?: transport)?|on foot|from\s+.+))*[?.!]*\s*$"
+    ))
+    .expect("Failed to compile destination regex")
+});
+
+static ORIGIN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\bfrom\s+(?:the\s+|my\s+)?(.+?)(?:\s+(?:to|into)\b.*)?[?.!]*\s*$")
+        .expect("Failed to compile origin regex")
+});
+
+// travel time questions, as opposed to anything else with "to" in it
+static TRAVEL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:how long|how far|eta|travel time|drive|driving|commute|traffic)\b",
+        r"|\bget(?:ting)? (?:to|home)\b"
+    ))
+    .expect("Failed to compile travel question regex")
+});
+
+// other places only count when asked how long or how far to go there, so "get to bed"
+// and "how long does it take to learn Rust" stay with the LLM
+static DISTANCE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\bhow long\s+(?:will it\s+|would it\s+|does it\s+|is it\s+)?",
+        r"(?:take\s+(?:me\s+|us\s+)?)?(?:to\s+)?(?:get|drive|walk|cycle|bike|ride|go|travel)\b",
+        r"|\bhow long to (?:the|my)\b",
+        r"|\bhow long is the (?:drive|trip|ride|walk|journey)\b",
+        r"|\b(?:how far|eta|travel time|how(?:'s| is) the (?:drive|traffic))\b",
+    ))
+    .expect("Failed to compile distance question regex")
+});
+
+// no place named, but clearly about the daily trip
+static COMMUTE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:my|the) commute\b|\bhow(?:'s| is) (?:the )?(?:traffic|drive)\b")
+        .expect("Failed to compile commute regex")
+});
+
+static HOME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:get|go|going|drive|driving|head|heading|commute|way|back)\s+home\b")
+        .expect("Failed to compile home regex")
+});
+
+#[derive(Debug, Clone, PartialEq)]
+enum Place {
+    Home,
+    Work,
+    Named(String),
+}
+
+impl Place {
+    fn parse(said: &str) -> Place {
+        match said.trim().to_lowercase().as_str() {
+            "home" | "my place" | "the house" | "house" => Place::Home,
+            "work" | "office" | "the office" | "my office" | "my job" | "job" => Place::Work,
+            _ => Place::Named(said.trim().to_string()),
+        }
+    }
+
+    fn spoken(&self) -> String {
+        match self {
+            Place::Home => "home".to_string(),
+            Place::Work => "work".to_string(),
+            Place::Named(name) => name.clone(),
+        }
+    }
+
+    // the address to route to, or None when the profile doesn't have it
+    fn address(&self) -> Option<String> {
+        let profile = user_profile::current();
+        let address = match self {
+            Place::Home if profile.home.is_empty() => profile.location,
+            Place::Home => profile.home,
+            Place::Work => profile.work,
+            Place::Named(name) => name.clone(),
+        };
+        (!address.is_empty()).then_some(address)
+    }
+}
+
+fn mode(prompt: &str, default: TravelMode) -> TravelMode {
+    let p = prompt.to_lowercase();
+    let words: Vec<&str> = p.split(|c: char| !c.is_alphanumeric()).collect();
+    let said = |phrases: &[&str]| {
+        phrases.iter().any(|w| {
+            if w.contains(' ') {
+                p.contains(w)
+            } else {
+                words.contains(w)
+            }
+        })
+    };
+    if said(&["walk", "walking", "on foot"]) {
+        TravelMode::Walking
+    } else if said(&["bike", "cycle", "cycling", "bicycle"]) {
+        TravelMode::Cycling
+    } else if said(&[
+        "train",
+        "bus",
+        "tram",
+        "subway",
+        "metro",
+        "transit",
+        "public transport",
+        "public transportation",
+    ]) {
+        TravelMode::Transit
+    } else if said(&["drive", "driving", "car", "traffic"]) {
+        TravelMode::Driving
+    } else {
+        default
+    }
+}
+
+struct Trip {
+    from: Place,
+    to: Place,
+    mode: TravelMode,
+}
+
+fn parse(prompt: &str, config: &CommuteConfig) -> Option<Trip> {
+    if !TRAVEL.is_match(prompt) {
+        return None;
+    }
+    let to = if HOME.is_match(prompt) {
+        Place::Home
+    } else if let Some(m) = DESTINATION.captures(prompt) {
+        Place::parse(&m[1])
+    } else if COMMUTE.is_match(prompt) {
+        Place::Work
+    } else {
+        return None;
+    };
+    if matches!(to, Place::Named(_)) && !DISTANCE.is_match(prompt) {
+        return None;
+    }
+    let from = match ORIGIN.captures(prompt) {
+        Some(m) => Place::parse(&m[1]),
+        // asking the way home is usually done from work
+        None if to == Place::Home => Place::Work,
+        None => Place::Home,
+    };
+    (from != to).then(|| Trip {
+        from,
+        to,
+        mode: mode(prompt, config.mode),
+    })
+}
+
+struct Route {
+    // with the traffic right now, where the provider knows it
+    seconds: f64,
+    // the same trip without traffic, when known
+    typical_seconds: Option<f64>,
+    meters: f64,
+    // when to leave for public transport
+    departure: Option<DateTime<Local>>,
+}
+
+fn client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .user_agent(concat!("Jarvis/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| anyhow!("Failed to build HTTP client: {e}"))
+}
+
+async fn get_json(request: reqwest::RequestBuilder) -> Result<Value> {
+    let resp = request.send().await?;
+    if !resp.status().is_success() {
+        return Err(anyhow!("The route service returned {}", resp.status()));
+    }
+    Ok(resp.json().await?)
+}
+
+async fn google(
+    client: &reqwest::Client,
+    key: &str,
+    from: &str,
+    to: &str,
+    mode: TravelMode,
+) -> Result<Option<Route>> {
+    let mode = match mode {
+        TravelMode::Driving => "driving",
+        TravelMode::Transit => "transit",
+        TravelMode::Walking => "walking",
+        TravelMode::Cycling => "bicycling",
+    };
+    let data = get_json(
+        client
+            .get("https://maps.googleapis.com/maps/api/distancematrix/json")
+            .query(&[
+                ("origins", from),
+                ("destinations", to),
+                ("mode", mode),
+                ("departure_time", "now"),
+                ("key", key),
+            ]),
+    )
+    .await?;
+    let element = &data["rows"][0]["elements"][0];
+    if element["status"].as_str() != Some("OK") {
+        return Ok(None);
+    }
+    let normal = element["duration"]["value"].as_f64();
+    let Some(seconds) = element["duration_in_traffic"]["value"].as_f64().or(normal) else {
+        return Ok(None);
+    };
+    Ok(Some(Route {
+        seconds,
+        typical_seconds: element["duration_in_traffic"].is_object().then_some(normal).flatten(),
+        meters: element["distance"]["value"].as_f64().unwrap_or(0.0),
+        departure: None,
+    }))
+}
+
+// "lat,lng" for an address
+async fn here_geocode(
+    client: &reqwest::Client,
+    key: &str,
+    address: &str,
+) -> Result<Option<String>> {
+    let data = get_json(
+        client
+            .get("https://geocode.search.hereapi.com/v1/geocode")
+            .query(&[("q", address), ("limit", "1"), ("apiKey", key)]),
+    )
+    .await?;
+    let position = &data["items"][0]["position"];
+    Ok(position["lat"]
+        .as_f64()
+        .zip(position["lng"].as_f64())
+        .map(|(lat, lng)| format!("{},{}", lat, lng)))
+}
+
+fn section_time(section: &Value, field: &str) -> Option<DateTime<Local>> {
+    let time = section[field]["time"].as_str()?;
+    DateTime::parse_from_rfc3339(time)
+        .ok()
+        .map(|t| t.with_timezone(&Local))
+}
+
+async fn here(
+    client: &reqwest::Client,
+    key: &str,
+    from: &str,
+    to: &str,
+    mode: TravelMode,
+) -> Result<Option<Route>> {
+    let (Some(origin), Some(destination)) = (
+        here_geocode(client, key, from).await?,
+        here_geocode(client, key, to).await?,
+    ) else {
+        return Ok(None);
+    };
+    let query = [
+        ("origin", origin.as_str()),
+        ("destination", destination.as_str()),
+        ("apiKey", key),
+    ];
+    if mode == TravelMode::Transit {
+        let data = get_json(
+            client
+                .get("https://transit.router.hereapi.com/v8/routes")
+                .query(&query)
+                .query(&[("return", "travelSummary")]),
+        )
+        .await?;
+        let Some(sections) = data["routes"][0]["sections"].as_array() else {
+            return Ok(None);
+        };
+        let (Some(start), Some(end)) = (
+            sections.first().and_then(|s| section_time(s, "departure")),
+            sections.last().and_then(|s| section_time(s, "arrival")),
+        ) else {
+            return Ok(None);
+        };
+        let meters = sections
+            .iter()
+            .filter_map(|s| s["travelSummary"]["length"].as_f64())
+            .sum();
+        return Ok(Some(Route {
+            seconds: (end - start).num_seconds() as f64,
+            typical_seconds: None,
+            meters,
+            departure: Some(start),
+        }));
+    }
+    let transport = match mode {
+        TravelMode::Walking => "pedestrian",
+        TravelMode::Cycling => "bicycle",
+        _ => "car",
+    };
+    let data = get_json(
+        client
+            .get("https://router.hereapi.com/v8/routes")
+            .query(&query)
+            .query(&[
+                ("transportMode", transport),
+                ("return", "summary"),
+            ]),
+    )
+    .await?;
+    let Some(sections) = data["routes"][0]["sections"].as_array() else {
+        return Ok(None);
+    };
+    let total = |field: &str| -> Option<f64> {
+        sections.iter().map(|s| s["summary"][field].as_f64()).sum()
+    };
+    let Some(seconds) = total("duration") else {
+        return Ok(None);
+    };
+    Ok(Some(Route {
+        seconds,
+        typical_seconds: total("baseDuration"),
+        meters: total("length").unwrap_or(0.0),
+        departure: None,
+    }))
+}
+
+// [lng, lat] for an address
+async fn ors_geocode(client: &reqwest::Client, key: &str, address: &str) -> Result<Option<Value>> {
+    let data = get_json(
+        client
+            .get("https://api.openrouteservice.org/geocode/search")
+            .query(&[("api_key", key), ("text", address), ("size", "1")]),
+    )
+    .await?;
+    let coordinates = &data["features"][0]["geometry"]["coordinates"];
+    Ok(coordinates.is_array().then(|| coordinates.clone()))
+}
+
+async fn openrouteservice(
+    client: &reqwest::Client,
+    key: &str,
+    from: &str,
+    to: &str,
+    mode: TravelMode,
+) -> Result<Option<Route>> {
+    let profile = match mode {
+        TravelMode::Walking => "foot-walking",
+        TravelMode::Cycling => "cycling-regular",
+        TravelMode::Driving => "driving-car",
+        TravelMode::Transit => return Err(anyhow!("OpenRouteService has no public transport")),
+    };
+    let (Some(start), Some(end)) = (
+        ors_geocode(client, key, from).await?,
+        ors_geocode(client, key, to).await?,
+    ) else {
+        return Ok(None);
+    };
+    let data = get_json(
+        client
+            .post(format!("https://api.openrouteservice.org/v2/directions/{}", profile))
+            .header("Authorization", key)
+            .json(&serde_json::json!({ "coordinates": [start, end] })),
+    )
+    .await?;
+    let summary = &data["routes"][0]["summary"];
+    Ok(summary["duration"].as_f64().map(|seconds| Route {
+        seconds,
+        typical_seconds: None,
+        meters: summary["distance"].as_f64().unwrap_or(0.0),
+        departure: None,
+    }))
+}
+
+fn spoken_duration(seconds: f64) -> String {
+    let minutes = (seconds / 60.0).round().max(1.0) as i64;
+    match (minutes / 60, minutes % 60) {
+        (0, 1) => "a minute".to_string(),
+        (0, m) => format!("{} minutes", m),
+        (1, 0) => "an hour".to_string(),
+        (h, 0) => format!("{} hours", h),
+        (1, m) => format!("an hour and {} minutes", m),
+        (h, m) => format!("{} hours and {} minutes", h, m),
+    }
+}
+
+fn spoken_distance(meters: f64) -> String {
+    let imperial = user_profile::current().units == Some(Units::Imperial);
+    let (amount, unit) = if imperial {
+        (meters / 1609.344, "miles")
+    } else {
+        (meters / 1000.0, "kilometers")
+    };
+    if amount < 10.0 {
+        format!("{:.1} {}", amount, unit).replace(".0 ", " ")
+    } else {
+        format!("{:.0} {}", amount, unit)
+    }
+}
+
+fn traffic(route: &Route) -> Option<String> {
+    let typical = route.typical_seconds.filter(|t| *t > 0.0)?;
+    let delay = route.seconds - typical;
+    Some(match route.seconds / typical {
+        r if r < 1.1 => "Traffic is light.".to_string(),
+        r if r < 1.3 => format!("Some traffic, {} slower than usual.", spoken_duration(delay)),
+        _ => format!("Heavy traffic, {} slower than usual.", spoken_duration(delay)),
+    })
+}
+
+fn describe(trip: &Trip, route: &Route) -> String {
+    let how = match trip.mode {
+        TravelMode::Driving => "by car",
+        TravelMode::Transit => "by public transport",
+        TravelMode::Walking => "on foot",
+        TravelMode::Cycling => "by bike",
+    };
+    let mut reply = format!(
+        "It's about {} to {} {}",
+        spoken_duration(route.seconds),
+        trip.to.spoken(),
+        how
+    );
+    if route.meters > 0.0 {
+        reply.push_str(&format!(", {}", spoken_distance(route.meters)));
+    }
+    reply.push('.');
+    if let Some(departure) = route.departure {
+        let time = departure.format("%-I:%M %p").to_string();
+        reply.push_str(&format!(" The next connection leaves at {}.", time));
+    }
+    if trip.mode == TravelMode::Driving {
+        if let Some(traffic) = traffic(route) {
+            reply.push(' ');
+            reply.push_str(&traffic);
+        }
+    }
+    reply
+}
+
+// The spoken travel time, or None when the prompt isn't a travel question this can
+// answer; a missing address or key for "work" or "home" is explained instead
+pub async fn answer(prompt: &str, config: &CommuteConfig) -> Option<String> {
+    let trip = parse(prompt, config)?;
+    let saved = !matches!(trip.to, Place::Named(_));
+    let (Some(from), Some(to)) = (trip.from.address(), trip.to.address()) else {
+        return saved.then(|| {
+            "I don't know your home and work addresses yet. Add them to your profile.".to_string()
+        });
+    };
+    let key = config.api_key.trim();
+    if key.is_empty() {
+        return saved.then(|| {
+            "I need a route service key for travel times. Add one in the settings.".to_string()
+        });
+    }
+    let client = client().ok()?;
+    let route = match config.provider {
+        RouteProvider::Google => google(&client, key, &from, &to, trip.mode).await,
+        RouteProvider::Here => here(&client, key, &from, &to, trip.mode).await,
+        RouteProvider::Openrouteservice => {
+            openrouteservice(&client, key, &from, &to, trip.mode).await
+        }
+    };
+    match route {
+        Ok(Some(route)) => Some(describe(&trip, &route)),
+        // a place nobody could find probably wasn't a place
+        Ok(None) if !saved => None,
+        Ok(None) => Some(format!("I couldn't find a route to {}.", trip.to.spoken())),
+        Err(e) => {
+            eprintln!("[ERROR] Route lookup to '{}' failed: {}", trip.to.spoken(), e);
+            let place = trip.to.spoken();
+            saved.then(|| format!("Sorry, I couldn't get the route to {} right now.", place))
+        }
+    }
+}
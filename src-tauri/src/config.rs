@@ -0,0 +1,171 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Layered configuration: config.json, then JARVIS_* environment variables, then
+// `--set key=value` command-line flags. Overrides only live for the current run and
+// are never written back to config.json.
+//
+// Nested keys use dots on the command line (`--set wake.cooldown_ms=2000`) and a
+// double underscore in the environment (`JARVIS_WAKE__COOLDOWN_MS=2000`).
+
+use serde_json::{Map, Value};
+use std::sync::OnceLock;
+
+const ENV_PREFIX: &str = "JARVIS_";
+
+#[derive(Debug, Clone)]
+pub struct Override {
+    pub path: Vec<String>,
+    pub raw: String,
+    pub source: &'static str,
+}
+
+fn parse_env() -> Vec<Override> {
+    let mut out: Vec<Override> = std::env::vars()
+        .filter_map(|(k, v)| {
+            let key = k.strip_prefix(ENV_PREFIX)?;
+            if key.is_empty() {
+                return None;
+            }
+            Some(Override {
+                path: key.split("__").map(|p| p.to_lowercase()).collect(),
+                raw: v,
+                source: "env",
+            })
+        })
+        .collect();
+    // env iteration order is unspecified; keep it stable
+    out.sort_by(|a, b| a.path.cmp(&b.path));
+    out
+}
+
+fn parse_args(args: &[String]) -> Vec<Override> {
+    let mut out = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let pair = if arg == "--set" {
+            iter.next().map(String::as_str)
+        } else {
+            arg.strip_prefix("--set=")
+        };
+        let Some(pair) = pair else { continue };
+        match pair.split_once('=') {
+            Some((k, v)) if !k.trim().is_empty() => out.push(Override {
+                path: k.trim().split('.').map(str::to_string).collect(),
+                raw: v.to_string(),
+                source: "cli",
+            }),
+            _ => eprintln!("[WARN] Ignoring malformed --set '{}', expected key=value", pair),
+        }
+    }
+    out
+}
+
+// Collected once per process; CLI flags come last so they win over the environment
+pub fn overrides() -> &'static [Override] {
+    static OVERRIDES: OnceLock<Vec<Override>> = OnceLock::new();
+    OVERRIDES.get_or_init(|| {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let mut all = parse_env();
+        all.extend(parse_args(&args));
+        for o in &all {
+            println!("[DEBUG] Config override from {}: {}", o.source, o.path.join("."));
+        }
+        all
+    })
+}
+
+fn get<'a>(map: &'a Map<String, Value>, path: &[String]) -> Option<&'a Value> {
+    let (first, rest) = path.split_first()?;
+    let v = map.get(first)?;
+    if rest.is_empty() {
+        return Some(v);
+    }
+    get(v.as_object()?, rest)
+}
+
+fn set(map: &mut Map<String, Value>, path: &[String], value: Option<Value>) {
+    let Some((first, rest)) = path.split_first() else {
+        return;
+    };
+    if rest.is_empty() {
+        match value {
+            Some(v) => map.insert(first.clone(), v),
+            None => map.remove(first),
+        };
+        return;
+    }
+    if let Some(Value::Object(child)) = map.get_mut(first) {
+        set(child, rest, value);
+    }
+}
+
+// Interpret the raw string using the type of the value it replaces
+fn coerce(raw: &str, current: &Value) -> Value {
+    if current.is_string() {
+        return Value::String(raw.to_string());
+    }
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+// Applies all overrides whose key exists in the config; unknown keys are reported and skipped
+pub fn apply_overrides(map: &mut Map<String, Value>) {
+    for o in overrides() {
+        match get(map, &o.path) {
+            Some(current) => {
+                let v = coerce(&o.raw, current);
+                set(map, &o.path, Some(v));
+            }
+            None => eprintln!(
+                "[WARN] Ignoring {} override for unknown config key '{}'",
+                o.source,
+                o.path.join(".")
+            ),
+        }
+    }
+}
+
+// Before saving: put back the on-disk value for every key that still holds its override
+pub fn strip_overrides(map: &mut Map<String, Value>, on_disk: &Map<String, Value>) {
+    for o in overrides() {
+        let Some(current) = get(map, &o.path) else {
+            continue;
+        };
+        if *current == coerce(&o.raw, current) {
+            set(map, &o.path, get(on_disk, &o.path).cloned());
+        }
+    }
+}
+
+// Runs `cfg` through the override layers. If an override produces an invalid config
+// the overrides are dropped rather than failing to start.
+pub fn layer<T: serde::Serialize + serde::de::DeserializeOwned>(cfg: T) -> T {
+    if overrides().is_empty() {
+        return cfg;
+    }
+    let Ok(Value::Object(mut map)) = serde_json::to_value(&cfg) else {
+        return cfg;
+    };
+    apply_overrides(&mut map);
+    match serde_json::from_value(Value::Object(map)) {
+        Ok(layered) => layered,
+        Err(e) => {
+            eprintln!("[WARN] Config overrides produce an invalid config, ignoring them: {}", e);
+            cfg
+        }
+    }
+}
@@ -0,0 +1,87 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Voice commands that change something (turning Wi-Fi off, connecting a VPN, ...) ask
+// first. The question is spoken, and the next thing said within CONFIRM_WINDOW carries
+// the action out ("yes") or drops it ("no"); anything else drops it too and is handled
+// as usual. One question is open at a time, a new one replaces it.
+
+use futures::future::BoxFuture;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// how long a question waits for a yes/no
+pub const CONFIRM_WINDOW: Duration = Duration::from_secs(30);
+
+static YES: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(?:yes|yeah|yep|sure|confirm|do it|go ahead)\b")
+        .expect("Failed to compile confirmation regex")
+});
+
+static NO: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(?:no|nope|cancel|never ?mind|don't|do not)\b")
+        .expect("Failed to compile cancel regex")
+});
+
+struct Pending {
+    // e.g. "turn off Wi-Fi", for "Okay, I won't turn off Wi-Fi."
+    what: String,
+    // not started until the answer is yes; resolves to the reply
+    action: BoxFuture<'static, String>,
+    asked_at: Instant,
+}
+
+static PENDING: Mutex<Option<Pending>> = Mutex::new(None);
+
+pub fn is_yes(prompt: &str) -> bool {
+    YES.is_match(prompt)
+}
+
+pub fn is_no(prompt: &str) -> bool {
+    NO.is_match(prompt)
+}
+
+// Holds `action` until the next reply; returns the question to show and speak
+pub fn ask<F>(what: &str, question: &str, action: F) -> String
+where
+    F: Future<Output = String> + Send + 'static,
+{
+    *PENDING.lock().unwrap() = Some(Pending {
+        what: what.to_string(),
+        action: Box::pin(action),
+        asked_at: Instant::now(),
+    });
+    format!("{} Say yes to confirm.", question)
+}
+
+// Returns the reply when the prompt answered an open question
+pub async fn handle(prompt: &str) -> Option<String> {
+    let pending = PENDING.lock().unwrap().take()?;
+    if pending.asked_at.elapsed() > CONFIRM_WINDOW {
+        return None;
+    }
+    if is_yes(prompt) {
+        return Some(pending.action.await);
+    }
+    if is_no(prompt) {
+        return Some(format!("Okay, I won't {}.", pending.what));
+    }
+    None
+}
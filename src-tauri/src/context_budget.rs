@@ -0,0 +1,184 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Keeps system prompt + conversation context + prompt inside the model's input
+// window. The oldest turns go first; the part before the turns (remembered facts and
+// other notes) is kept, and the prompt is only shortened as a last resort.
+
+use crate::events::{self, ContextTruncated};
+use crate::models::Config;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+// Gemini bills an image as a flat number of tokens, whatever its base64 length
+const IMAGE_TOKENS: usize = 258;
+// for models not in context_window()
+const DEFAULT_WINDOW_TOKENS: usize = 32_768;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ContextBudgetConfig {
+    // 0 uses the model's input window; lower values keep requests cheap
+    pub max_input_tokens: usize,
+    // room left for the answer and for pages, PDFs and transcripts added from links
+    pub reserve_tokens: usize,
+}
+
+impl Default for ContextBudgetConfig {
+    fn default() -> Self {
+        Self {
+            max_input_tokens: 0,
+            reserve_tokens: 100_000,
+        }
+    }
+}
+
+static APP: OnceLock<tauri::AppHandle> = OnceLock::new();
+
+static DATA_URI: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"data:[\w.+-]+/[\w.+-]+;base64,[A-Za-z0-9+/=]+").expect("Failed to compile data URI regex")
+});
+// turns as written by format_ctx_text
+static TURN_START: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^(?:USER|ASSISTANT|SYSTEM): ").expect("Failed to compile turn regex"));
+
+// Called once at startup so query_gemini can report truncation without an AppHandle
+pub fn init(app: &tauri::AppHandle) {
+    let _ = APP.set(app.clone());
+}
+
+// ~4 characters per token, the same rule of thumb as the TTS estimate
+pub fn estimate_tokens(text: &str) -> usize {
+    let images = DATA_URI.find_iter(text).count();
+    let rest = DATA_URI.replace_all(text, "");
+    rest.chars().count().div_ceil(4) + images * IMAGE_TOKENS
+}
+
+// Input window of the configured model
+pub fn context_window(model: &str) -> usize {
+    let model = model.to_lowercase();
+    if model.starts_with("gemini-1.0") || model == "gemini-pro" {
+        30_720
+    } else if model.starts_with("gemini-") {
+        1_048_576
+    } else if model.starts_with("gemma") {
+        8_192
+    } else {
+        DEFAULT_WINDOW_TOKENS
+    }
+}
+
+fn budget(config: &Config) -> usize {
+    let window = context_window(&config.gemini_model);
+    let budget = &config.context_budget;
+    let limit = if budget.max_input_tokens > 0 {
+        budget.max_input_tokens.min(window)
+    } else {
+        window
+    };
+    // never squeeze the request to nothing because the reserve is set too high
+    limit.saturating_sub(budget.reserve_tokens).max(limit / 4)
+}
+
+// Leading notes and the turns after them
+fn split_turns(ctx_text: &str) -> (&str, Vec<&str>) {
+    let starts: Vec<usize> = TURN_START.find_iter(ctx_text).map(|m| m.start()).collect();
+    let Some(&first) = starts.first() else {
+        return (ctx_text, Vec::new());
+    };
+    let mut turns = Vec::new();
+    for (i, &start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(ctx_text.len());
+        turns.push(&ctx_text[start..end]);
+    }
+    (&ctx_text[..first], turns)
+}
+
+// Keeps the beginning and end of a prompt that can't fit, e.g. a huge paste
+fn shorten(prompt: &str, max_tokens: usize) -> String {
+    let keep = max_tokens * 4 / 2;
+    let chars: Vec<char> = prompt.chars().collect();
+    if chars.len() <= keep * 2 {
+        return prompt.to_string();
+    }
+    let head: String = chars[..keep].iter().collect();
+    let tail: String = chars[chars.len() - keep..].iter().collect();
+    format!("{}\n[… part of this message was left out to fit the context window …]\n{}", head, tail)
+}
+
+// Returns the context and prompt to send; emits `context-truncated` when anything was cut
+pub fn fit(config: &Config, system_prompt: &str, ctx_text: &str, prompt: &str) -> (String, String) {
+    let budget = budget(config);
+    let fixed = estimate_tokens(system_prompt) + estimate_tokens(prompt);
+    let estimated = fixed + estimate_tokens(ctx_text);
+    if estimated <= budget {
+        return (ctx_text.to_string(), prompt.to_string());
+    }
+
+    let (notes, turns) = split_turns(ctx_text);
+    let mut used = fixed + estimate_tokens(notes);
+    // newest turns first, until the budget is spent
+    let mut kept = 0;
+    for turn in turns.iter().rev() {
+        let cost = estimate_tokens(turn);
+        if used + cost > budget {
+            break;
+        }
+        used += cost;
+        kept += 1;
+    }
+    let dropped = turns.len() - kept;
+    let mut ctx = notes.to_string();
+    if dropped > 0 {
+        ctx.push_str(&format!("({} earlier messages were left out to fit the context window)\n", dropped));
+    }
+    for turn in &turns[dropped..] {
+        ctx.push_str(turn);
+    }
+
+    // images can't be cut in half, so prompts with one are sent as they are
+    let mut prompt = prompt.to_string();
+    let prompt_shortened = used > budget && !DATA_URI.is_match(&prompt);
+    if prompt_shortened {
+        let room = budget.saturating_sub(used - estimate_tokens(&prompt));
+        prompt = shorten(&prompt, room.max(1_000));
+    }
+
+    let event = ContextTruncated {
+        dropped_turns: dropped,
+        prompt_shortened,
+        estimated_tokens: estimated,
+        budget_tokens: budget,
+    };
+    eprintln!(
+        "[WARN] Context over budget (~{} of {} tokens): dropped {} turn(s){}",
+        estimated,
+        budget,
+        dropped,
+        if prompt_shortened { ", shortened prompt" } else { "" }
+    );
+    crate::trace::update(|t| {
+        t.dropped_turns = dropped;
+        t.prompt_shortened = prompt_shortened;
+    });
+    if let Some(app) = APP.get() {
+        events::emit(app, event);
+    }
+    (ctx, prompt)
+}
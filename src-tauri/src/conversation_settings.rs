@@ -0,0 +1,162 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::send_to_llm::GenerationSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::Manager;
+
+// per-conversation overrides, keyed by history filename
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ConversationSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redaction_enabled: Option<bool>,
+    // turns before this timestamp are left out of the LLM context ("forget this conversation")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_cleared_at: Option<i64>,
+    // temperature, top-p and length limits for this conversation only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generation: Option<GenerationSettings>,
+}
+
+fn settings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join("conversation_settings.json"))
+}
+
+fn load_all(app: &tauri::AppHandle) -> HashMap<String, ConversationSettings> {
+    let Ok(path) = settings_path(app) else {
+        return HashMap::new();
+    };
+    std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_all(
+    app: &tauri::AppHandle,
+    all: &HashMap<String, ConversationSettings>,
+) -> Result<(), String> {
+    let path = settings_path(app)?;
+    let s = serde_json::to_string_pretty(all).map_err(|e| e.to_string())?;
+    crate::history::atomic_write(&path, s.as_bytes()).map_err(|e| e.to_string())
+}
+
+pub fn get(app: &tauri::AppHandle, filename: &str) -> ConversationSettings {
+    load_all(app).remove(filename).unwrap_or_default()
+}
+
+pub fn update<F>(app: &tauri::AppHandle, filename: &str, f: F) -> Result<ConversationSettings, String>
+where
+    F: FnOnce(&mut ConversationSettings),
+{
+    let mut all = load_all(app);
+    let entry = all.entry(filename.to_string()).or_default();
+    f(entry);
+    let updated = entry.clone();
+    save_all(app, &all)?;
+    Ok(updated)
+}
+
+// keeps overrides attached to a conversation across renames
+pub fn rename(app: &tauri::AppHandle, old: &str, new: &str) {
+    let mut all = load_all(app);
+    if let Some(s) = all.remove(old) {
+        all.insert(new.to_string(), s);
+        if let Err(e) = save_all(app, &all) {
+            eprintln!("[WARN] Failed to move conversation settings: {}", e);
+        }
+    }
+}
+
+pub fn remove(app: &tauri::AppHandle, filename: &str) {
+    let mut all = load_all(app);
+    if all.remove(filename).is_some() {
+        if let Err(e) = save_all(app, &all) {
+            eprintln!("[WARN] Failed to remove conversation settings: {}", e);
+        }
+    }
+}
+
+// same as remove() for many conversations, with one write
+pub fn remove_many(app: &tauri::AppHandle, filenames: &[String]) {
+    let mut all = load_all(app);
+    let before = all.len();
+    for filename in filenames {
+        all.remove(filename);
+    }
+    if all.len() != before {
+        if let Err(e) = save_all(app, &all) {
+            eprintln!("[WARN] Failed to remove conversation settings: {}", e);
+        }
+    }
+}
+
+// resolves whether redaction applies to a conversation, falling back to the global setting
+pub fn redaction_enabled(app: &tauri::AppHandle, filename: Option<&str>, global: bool) -> bool {
+    filename
+        .and_then(|f| get(app, f).redaction_enabled)
+        .unwrap_or(global)
+}
+
+// createdAt cutoff for context building, 0 when the conversation was never cleared
+pub fn context_cutoff(app: &tauri::AppHandle, filename: &str) -> i64 {
+    get(app, filename).context_cleared_at.unwrap_or(0)
+}
+
+// layers the conversation's generation overrides over the global settings
+pub fn apply_generation(app: &tauri::AppHandle, filename: Option<&str>, global: &mut GenerationSettings) {
+    if let Some(overrides) = filename.and_then(|f| get(app, f).generation) {
+        *global = global.clone().overridden_by(&overrides);
+    }
+}
+
+#[tauri::command]
+pub fn cmd_get_conversation_settings(
+    app: tauri::AppHandle,
+    filename: String,
+) -> Result<ConversationSettings, String> {
+    Ok(get(&app, &filename))
+}
+
+#[tauri::command]
+pub fn cmd_set_conversation_redaction(
+    app: tauri::AppHandle,
+    filename: String,
+    enabled: Option<bool>,
+) -> Result<ConversationSettings, String> {
+    update(&app, &filename, |s| s.redaction_enabled = enabled)
+}
+
+// `None` (or all fields empty) goes back to the global settings
+#[tauri::command]
+pub fn cmd_set_conversation_generation(
+    app: tauri::AppHandle,
+    filename: String,
+    generation: Option<GenerationSettings>,
+) -> Result<ConversationSettings, String> {
+    let generation = generation.filter(|g| {
+        g.temperature.is_some()
+            || g.top_p.is_some()
+            || g.max_output_tokens.is_some()
+            || g.voice_max_output_tokens.is_some()
+    });
+    update(&app, &filename, |s| s.generation = generation)
+}
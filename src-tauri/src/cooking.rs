@@ -0,0 +1,529 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Cooking mode: "start cooking" loads the recipe on the clipboard (a link or the text
+// itself; the app can hand one over with cmd_load_recipe) and then goes through it
+// hands-free: "next step", "go back", "repeat", "step 4", "how much flour", "what do I
+// need". A step that says "bake for 25 minutes" starts a timer (see timers). While the
+// mode is on, other questions get the recipe as context, so "can I use butter instead"
+// is answered for this dish. "Stop cooking" ends it.
+
+use crate::timers;
+use anyhow::{anyhow, Result};
+use arboard::Clipboard;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Mutex;
+use ts_rs::TS;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CookingConfig {
+    // start a timer when a step mentions how long it takes
+    pub auto_timers: bool,
+}
+
+impl Default for CookingConfig {
+    fn default() -> Self {
+        Self { auto_timers: true }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct Recipe {
+    pub title: String,
+    pub ingredients: Vec<String>,
+    pub steps: Vec<String>,
+    pub source: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CookingState {
+    pub recipe: Recipe,
+    // index into steps; None before the first "next step"
+    #[ts(type = "number | null")]
+    pub step: Option<usize>,
+    // steps whose timer was already started, so "repeat" doesn't start another
+    #[ts(type = "Array<number>")]
+    pub timed_steps: Vec<usize>,
+}
+
+static SESSION: Mutex<Option<CookingState>> = Mutex::new(None);
+
+static START: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:start|begin|enter)\s+cooking(?:\s+mode)?\b|\bcooking mode\s+on\b",
+        r"|\blet's\s+(?:cook|make)\s+(?:this|that|it|the recipe)\b",
+        r"|\bcook\s+(?:this|that|the recipe)\b"
+    ))
+    .expect("Failed to compile start cooking regex")
+});
+
+static STOP: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:stop|exit|end|quit|leave|done|finish(?:ed)?)\s+(?:with\s+)?(?:the\s+)?",
+        r"(?:cooking|recipe)(?:\s+mode)?\b|\bcooking mode\s+off\b"
+    ))
+    .expect("Failed to compile stop cooking regex")
+});
+
+static NEXT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:next step|what's next|what is next|the next one|go on)\b",
+        r"|^\s*(?:ok(?:ay)?,?\s+)?(?:next|continue|done)[.!?]*\s*$"
+    ))
+    .expect("Failed to compile next step regex")
+});
+
+static PREVIOUS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:previous step|last step|step back|go back|back one)\b")
+        .expect("Failed to compile previous step regex")
+});
+
+static REPEAT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:repeat|again|say that|what was that|current step|which step|what step)\b")
+        .expect("Failed to compile repeat step regex")
+});
+
+static GOTO: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\bstep\s+(\d+|one|two|three|four|five|six|seven|eight|nine|ten|eleven|twelve)\b",
+    )
+    .expect("Failed to compile go to step regex")
+});
+
+static INGREDIENTS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:ingredients|what do i need|shopping list)\b")
+        .expect("Failed to compile ingredients regex")
+});
+
+static AMOUNT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\bhow (?:much|many)\s+(.+?)",
+        r"(?:\s+(?:do i|should i|does it|do we|is|are|goes|go|in)\b.*)?[?.!]*\s*$"
+    ))
+    .expect("Failed to compile ingredient amount regex")
+});
+
+static SECTION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)^\s*(?:#+\s*)?(ingredients|instructions|directions|method|steps|preparation)",
+        r"\s*:?\s*$"
+    ))
+    .expect("Failed to compile recipe section regex")
+});
+
+static BULLET: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(?:[-*•▢]\s*|\d+[.)]\s+|step\s+\d+[.:)]?\s*)")
+        .expect("Failed to compile bullet regex")
+});
+
+static TAGS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<[^>]*>").expect("Failed to compile tag regex"));
+
+const NUMBER_WORDS: &[&str] = &[
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven",
+    "twelve",
+];
+
+// words in "how much ... " that never name an ingredient
+const NOT_INGREDIENTS: &[&str] = &[
+    "the", "some", "time", "longer", "more", "left", "step", "minute", "hour", "people",
+    "serving",
+];
+
+pub fn is_active() -> bool {
+    SESSION.lock().unwrap().is_some()
+}
+
+fn emit_changed(app: &tauri::AppHandle) {
+    let state = SESSION.lock().unwrap().clone();
+    crate::events::emit(app, crate::events::CookingChanged(state));
+}
+
+fn clean(text: &str) -> String {
+    let text = TAGS.replace_all(text, " ");
+    let text = text
+        .replace("&amp;", "&")
+        .replace("&nbsp;", " ")
+        .replace("&#39;", "'")
+        .replace("&quot;", "\"");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn is_recipe(v: &Value) -> bool {
+    match &v["@type"] {
+        Value::String(t) => t == "Recipe",
+        Value::Array(types) => types.iter().any(|t| t.as_str() == Some("Recipe")),
+        _ => false,
+    }
+}
+
+// the Recipe object anywhere in a JSON-LD block (top level, a list or an @graph)
+fn find_recipe(v: &Value) -> Option<&Value> {
+    if is_recipe(v) {
+        return Some(v);
+    }
+    match v {
+        Value::Array(items) => items.iter().find_map(find_recipe),
+        Value::Object(map) => map.get("@graph").and_then(find_recipe),
+        _ => None,
+    }
+}
+
+// plain strings, HowToStep objects and HowToSection groups of them
+fn instruction_steps(v: &Value, out: &mut Vec<String>) {
+    match v {
+        Value::String(s) => out.extend(s.lines().map(clean).filter(|l| !l.is_empty())),
+        Value::Array(items) => items.iter().for_each(|i| instruction_steps(i, out)),
+        Value::Object(map) => {
+            if let Some(items) = map.get("itemListElement") {
+                instruction_steps(items, out);
+            } else if let Some(text) = map.get("text").or_else(|| map.get("name")) {
+                instruction_steps(text, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Most recipe sites describe the recipe in schema.org JSON-LD for search engines
+fn from_json_ld(html: &str) -> Option<Recipe> {
+    let doc = Html::parse_document(html);
+    let selector = Selector::parse(r#"script[type="application/ld+json"]"#).ok()?;
+    doc.select(&selector).find_map(|script| {
+        let data: Value = serde_json::from_str(&script.text().collect::<String>()).ok()?;
+        let recipe = find_recipe(&data)?;
+        let ingredients = recipe["recipeIngredient"]
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|i| i.as_str().map(clean))
+                    .filter(|i| !i.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut steps = Vec::new();
+        instruction_steps(&recipe["recipeInstructions"], &mut steps);
+        if steps.is_empty() {
+            return None;
+        }
+        Some(Recipe {
+            title: recipe["name"].as_str().map(clean).unwrap_or_default(),
+            ingredients,
+            steps,
+            source: None,
+        })
+    })
+}
+
+fn looks_like_ingredient(line: &str) -> bool {
+    let first = line.chars().next().unwrap_or(' ');
+    (first.is_ascii_digit() || "½⅓¼¾⅔".contains(first)) && line.chars().count() < 80
+}
+
+// A recipe written out: "Ingredients" and "Instructions" headings when there are
+// any, otherwise lines starting with an amount are the ingredients
+fn from_text(text: &str) -> Option<Recipe> {
+    let lines: Vec<&str> = text.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+    let has_headings = lines.iter().any(|l| SECTION.is_match(l));
+    let mut recipe = Recipe::default();
+    // Some(true) under "Ingredients", Some(false) under the steps
+    let mut section: Option<bool> = None;
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(m) = SECTION.captures(line) {
+            section = Some(m[1].eq_ignore_ascii_case("ingredients"));
+            continue;
+        }
+        let item = clean(&BULLET.replace(line, ""));
+        if item.is_empty() {
+            continue;
+        }
+        let is_ingredient = match section {
+            Some(ingredients) => ingredients,
+            // the first line is usually the recipe's name
+            None if i == 0 && !looks_like_ingredient(&item) => {
+                recipe.title = item;
+                continue;
+            }
+            // an introduction before the headings
+            None if has_headings => continue,
+            None => looks_like_ingredient(&item),
+        };
+        if is_ingredient {
+            recipe.ingredients.push(item);
+        } else {
+            recipe.steps.push(item);
+        }
+    }
+    (!recipe.steps.is_empty()).then_some(recipe)
+}
+
+// Loads a recipe from a link or from the recipe text itself
+pub async fn load(source: &str, fetch_config: &crate::web_fetch::WebFetchConfig) -> Result<Recipe> {
+    let source = source.trim();
+    if !(source.starts_with("http://") || source.starts_with("https://")) {
+        return from_text(source).ok_or_else(|| anyhow!("That doesn't look like a recipe"));
+    }
+    let url = source.split_whitespace().next().unwrap_or(source);
+    let client = crate::web_fetch::client(fetch_config)?;
+    let fetched = crate::web_fetch::fetch(&client, fetch_config, url).await?;
+    let body = String::from_utf8_lossy(&fetched.bytes).into_owned();
+    let mut recipe = match from_json_ld(&body) {
+        Some(recipe) => recipe,
+        None => {
+            // no structured recipe: try the page text
+            let article = crate::readability::extract(&body);
+            let mut recipe = from_text(&article.text)
+                .ok_or_else(|| anyhow!("I couldn't find a recipe on that page"))?;
+            if let Some(title) = article.title {
+                recipe.title = title;
+            }
+            recipe
+        }
+    };
+    recipe.source = Some(fetched.url);
+    Ok(recipe)
+}
+
+fn begin(app: &tauri::AppHandle, recipe: Recipe) -> CookingState {
+    let state = CookingState {
+        recipe,
+        step: None,
+        timed_steps: Vec::new(),
+    };
+    *SESSION.lock().unwrap() = Some(state.clone());
+    emit_changed(app);
+    state
+}
+
+fn title(recipe: &Recipe) -> String {
+    if recipe.title.is_empty() {
+        "the recipe".to_string()
+    } else {
+        recipe.title.clone()
+    }
+}
+
+fn introduce(state: &CookingState) -> String {
+    let recipe = &state.recipe;
+    format!(
+        "Let's make {}. It has {} ingredients and {} steps. Say \"ingredients\" to hear what \
+         you need, or \"next step\" to start.",
+        title(recipe),
+        recipe.ingredients.len(),
+        recipe.steps.len()
+    )
+}
+
+// Reads step `index` and starts its timer the first time it comes up
+fn read_step(app: &tauri::AppHandle, config: &CookingConfig, index: usize) -> String {
+    let mut session = SESSION.lock().unwrap();
+    let Some(state) = session.as_mut() else {
+        return "Cooking mode is off.".to_string();
+    };
+    let count = state.recipe.steps.len();
+    let index = index.min(count - 1);
+    state.step = Some(index);
+    let step = state.recipe.steps[index].clone();
+    let mut reply = format!("Step {} of {}: {}", index + 1, count, step);
+    if !reply.ends_with(['.', '!', '?']) {
+        reply.push('.');
+    }
+    if config.auto_timers && !state.timed_steps.contains(&index) {
+        if let Some(seconds) = timers::first_duration(&step) {
+            state.timed_steps.push(index);
+            timers::start(app, &format!("step {}", index + 1), seconds);
+            reply.push_str(&format!(
+                " I've started a timer for {}.",
+                timers::spoken_duration(seconds)
+            ));
+        }
+    }
+    if index + 1 == count {
+        reply.push_str(" That's the last step.");
+    }
+    drop(session);
+    emit_changed(app);
+    reply
+}
+
+fn step_number(said: &str) -> Option<usize> {
+    let said = said.to_lowercase();
+    said.parse::<usize>()
+        .ok()
+        .or_else(|| NUMBER_WORDS.iter().position(|w| *w == said).map(|i| i + 1))
+}
+
+fn singular(word: &str) -> &str {
+    word.strip_suffix("es")
+        .filter(|w| w.ends_with(['o', 's', 'h']))
+        .or_else(|| word.strip_suffix('s'))
+        .unwrap_or(word)
+}
+
+// What the recipe says about an ingredient; None when "how much ..." isn't about one
+fn amount_of(recipe: &Recipe, asked: &str) -> Option<String> {
+    let words: Vec<String> = asked
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .map(|w| singular(w).to_string())
+        .filter(|w| w.len() > 2 && !NOT_INGREDIENTS.contains(&w.as_str()))
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    let found: Vec<&String> = recipe
+        .ingredients
+        .iter()
+        .filter(|i| {
+            let line = i.to_lowercase();
+            words.iter().all(|w| line.contains(w.as_str()))
+        })
+        .collect();
+    if found.is_empty() {
+        return Some(format!("The recipe doesn't list {}.", asked.trim()));
+    }
+    let listed: Vec<&str> = found.iter().map(|s| s.as_str()).collect();
+    Some(format!("You need {}.", listed.join(", and ")))
+}
+
+// Context for other questions while cooking, added ahead of the conversation
+pub fn prompt_section() -> String {
+    let session = SESSION.lock().unwrap();
+    let Some(state) = session.as_ref() else {
+        return String::new();
+    };
+    let recipe = &state.recipe;
+    let mut out = format!("\n\nThe user is cooking {} right now", title(recipe));
+    match state.step {
+        Some(step) => out.push_str(&format!(", at step {}.\n", step + 1)),
+        None => out.push_str(".\n"),
+    }
+    out.push_str("Ingredients:\n");
+    for item in &recipe.ingredients {
+        out.push_str(&format!("- {}\n", item));
+    }
+    out.push_str("Steps:\n");
+    for (i, step) in recipe.steps.iter().enumerate() {
+        out.push_str(&format!("{}. {}\n", i + 1, step));
+    }
+    out
+}
+
+fn clipboard_text() -> Option<String> {
+    Clipboard::new()
+        .and_then(|mut c| c.get_text())
+        .ok()
+        .filter(|t| !t.trim().is_empty())
+}
+
+// The spoken answer to a cooking command, or None for anything else. Outside cooking
+// mode only "start cooking" counts.
+pub async fn answer(
+    prompt: &str,
+    app: &tauri::AppHandle,
+    config: &CookingConfig,
+    fetch_config: &crate::web_fetch::WebFetchConfig,
+) -> Option<String> {
+    let state = SESSION.lock().unwrap().clone();
+    let Some(state) = state else {
+        if !START.is_match(prompt) {
+            return None;
+        }
+        let Some(source) = clipboard_text() else {
+            return Some("Copy a recipe or a link to one first, then say start cooking.".into());
+        };
+        return Some(match load(&source, fetch_config).await {
+            Ok(recipe) => introduce(&begin(app, recipe)),
+            Err(e) => {
+                eprintln!("[ERROR] Failed to load recipe: {}", e);
+                format!("Sorry, I couldn't load that recipe. {}.", e)
+            }
+        });
+    };
+    if STOP.is_match(prompt) {
+        *SESSION.lock().unwrap() = None;
+        emit_changed(app);
+        return Some("Cooking mode is off. Enjoy your meal!".to_string());
+    }
+    if let Some(reply) = AMOUNT.captures(prompt).and_then(|m| amount_of(&state.recipe, &m[1])) {
+        return Some(reply);
+    }
+    if INGREDIENTS.is_match(prompt) {
+        return Some(if state.recipe.ingredients.is_empty() {
+            "The recipe doesn't list its ingredients.".to_string()
+        } else {
+            format!("You need {}.", state.recipe.ingredients.join(", "))
+        });
+    }
+    if let Some(n) = GOTO.captures(prompt).and_then(|m| step_number(&m[1])) {
+        if n == 0 || n > state.recipe.steps.len() {
+            return Some(format!("The recipe only has {} steps.", state.recipe.steps.len()));
+        }
+        return Some(read_step(app, config, n - 1));
+    }
+    if NEXT.is_match(prompt) {
+        let next = state.step.map_or(0, |s| s + 1);
+        if next >= state.recipe.steps.len() {
+            return Some("That was the last step. Say stop cooking when you're done.".into());
+        }
+        return Some(read_step(app, config, next));
+    }
+    if PREVIOUS.is_match(prompt) {
+        return Some(match state.step {
+            Some(step) if step > 0 => read_step(app, config, step - 1),
+            _ => "This is the first step.".to_string(),
+        });
+    }
+    if REPEAT.is_match(prompt) {
+        return Some(match state.step {
+            Some(step) => read_step(app, config, step),
+            None => introduce(&state),
+        });
+    }
+    None
+}
+
+#[tauri::command]
+pub async fn cmd_load_recipe(
+    app: tauri::AppHandle,
+    source: String,
+) -> Result<CookingState, String> {
+    let fetch_config = crate::cmd_load_config(app.clone())?.web_fetch;
+    let recipe = load(&source, &fetch_config).await.map_err(|e| e.to_string())?;
+    Ok(begin(&app, recipe))
+}
+
+#[tauri::command]
+pub fn cmd_get_cooking() -> Option<CookingState> {
+    SESSION.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn cmd_stop_cooking(app: tauri::AppHandle) {
+    *SESSION.lock().unwrap() = None;
+    emit_changed(&app);
+}
@@ -0,0 +1,229 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Crash dumps. Panics on any thread (audio, Whisper, playback) and fatal errors are
+// written to `crashes/` in the app config dir. Nothing is sent anywhere unless the
+// user turns on `crash_reporting.submit` and sets an endpoint.
+
+use crate::redact::{self, RedactionConfig, Redactions};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::Manager;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct CrashReportingConfig {
+    // off unless the user opts in
+    pub submit: bool,
+    // reports are POSTed here as JSON
+    pub endpoint: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub id: String,
+    // "panic" or "error"
+    pub kind: String,
+    pub created_at: i64,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    // a fixed label such as "index out of bounds"; the panic or error text itself is never
+    // kept, since it can quote the prompt or transcript being processed
+    #[serde(default)]
+    pub category: String,
+    pub location: Option<String>,
+    pub backtrace: Option<String>,
+}
+
+static CRASH_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+// panic message fragment -> category
+const PANIC_CATEGORIES: &[(&str, &str)] = &[
+    ("is not a char boundary", "string slice out of bounds"),
+    ("out of range for slice", "slice out of bounds"),
+    ("index out of bounds", "index out of bounds"),
+    ("on a `None` value", "unwrap on None"),
+    ("on an `Err` value", "unwrap on Err"),
+    ("with overflow", "arithmetic overflow"),
+    ("divide by zero", "division by zero"),
+    ("PoisonError", "poisoned lock"),
+    ("capacity overflow", "capacity overflow"),
+];
+
+fn panic_category(message: &str) -> &'static str {
+    PANIC_CATEGORIES
+        .iter()
+        .find(|(fragment, _)| message.contains(fragment))
+        .map(|(_, category)| *category)
+        .unwrap_or("other")
+}
+
+// Strips what could identify the user from backtrace frames: home directory paths,
+// emails and numbers that look like phone or card numbers.
+fn anonymize(text: &str) -> String {
+    let mut out = text.to_string();
+    for var in ["HOME", "USERPROFILE"] {
+        if let Ok(home) = std::env::var(var) {
+            if !home.is_empty() {
+                out = out.replace(&home, "~");
+            }
+        }
+    }
+    let scrub = RedactionConfig {
+        enabled: true,
+        ..Default::default()
+    };
+    redact::redact(&out, &scrub, &mut Redactions::default())
+}
+
+fn new_report(kind: &str, category: &str, location: Option<String>, backtrace: Option<String>) -> CrashReport {
+    let now = chrono::Utc::now();
+    CrashReport {
+        id: format!("{}-{}", now.format("%Y%m%d-%H%M%S"), now.timestamp_subsec_millis()),
+        kind: kind.to_string(),
+        created_at: now.timestamp_millis(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        category: category.to_string(),
+        location,
+        backtrace: backtrace.map(|b| anonymize(&b)),
+    }
+}
+
+fn write_report(report: &CrashReport) {
+    let Some(dir) = CRASH_DIR.get() else {
+        return;
+    };
+    let result = std::fs::create_dir_all(dir).and_then(|_| {
+        let s = serde_json::to_string_pretty(report).unwrap_or_default();
+        std::fs::write(dir.join(format!("crash-{}.json", report.id)), s)
+    });
+    match result {
+        Ok(()) => eprintln!("[ERROR] Crash report written: crash-{}.json", report.id),
+        Err(e) => eprintln!("[ERROR] Failed to write crash report: {}", e),
+    }
+}
+
+// Installs the panic hook; the default hook still runs afterwards so stderr output is unchanged
+pub fn install(app: &tauri::AppHandle) {
+    if let Ok(dir) = app.path().app_config_dir() {
+        let _ = CRASH_DIR.set(dir.join("crashes"));
+    }
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("");
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+        let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+        write_report(&new_report("panic", panic_category(message), location, Some(backtrace)));
+        previous(info);
+    }));
+}
+
+// For errors that end a component without panicking (e.g. the voice loop giving up). The
+// fixed `context` becomes the category; the error text stays in the caller's log.
+pub fn report_error(context: &'static str) {
+    write_report(&new_report("error", context, None, None));
+}
+
+fn pending_reports(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .map(|rd| {
+            rd.flatten()
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.is_file() && p.extension().map(|x| x.eq_ignore_ascii_case("json")).unwrap_or(false)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Sends stored reports when the user opted in; sent ones move to `crashes/sent`
+pub async fn submit_pending(config: &CrashReportingConfig) {
+    if !config.submit {
+        return;
+    }
+    let Some(endpoint) = config.endpoint.as_deref().filter(|e| !e.trim().is_empty()) else {
+        return;
+    };
+    let Some(dir) = CRASH_DIR.get() else {
+        return;
+    };
+    let client = reqwest::Client::new();
+    for path in pending_reports(dir) {
+        let Ok(body) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(report) = serde_json::from_str::<CrashReport>(&body) else {
+            continue;
+        };
+        match client.post(endpoint).json(&report).send().await {
+            Ok(resp) if resp.status().is_success() => {
+                let sent = dir.join("sent");
+                let _ = std::fs::create_dir_all(&sent);
+                if let Some(name) = path.file_name() {
+                    let _ = std::fs::rename(&path, sent.join(name));
+                }
+            }
+            Ok(resp) => {
+                eprintln!("[WARN] Crash report endpoint returned {}", resp.status());
+                break;
+            }
+            Err(e) => {
+                eprintln!("[WARN] Failed to submit crash report: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+// Lets the user see exactly what would be sent
+#[tauri::command]
+pub fn cmd_list_crash_reports() -> Result<Vec<CrashReport>, String> {
+    let Some(dir) = CRASH_DIR.get() else {
+        return Ok(Vec::new());
+    };
+    let mut reports: Vec<CrashReport> = pending_reports(dir)
+        .iter()
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .filter_map(|s| serde_json::from_str(&s).ok())
+        .collect();
+    reports.sort_by_key(|r| std::cmp::Reverse(r.created_at));
+    Ok(reports)
+}
+
+#[tauri::command]
+pub fn cmd_delete_crash_reports() -> Result<(), String> {
+    let Some(dir) = CRASH_DIR.get() else {
+        return Ok(());
+    };
+    for path in pending_reports(dir) {
+        std::fs::remove_file(path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,188 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Support bundle: one zip with what is needed to look into a bug report and
+// nothing personal (no API keys, no conversations, no recordings).
+
+use crate::run_jarvis;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+use zip::write::SimpleFileOptions;
+
+// newest crash reports and log files to include, and how much of each log
+const MAX_CRASH_REPORTS: usize = 10;
+const MAX_LOG_FILES: usize = 5;
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ModelFile {
+    name: String,
+    present: bool,
+    size: Option<u64>,
+    sha256: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsBundle {
+    pub path: String,
+    pub size: u64,
+}
+
+fn sha256_of(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1 << 16];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+fn model_file(name: &str, path: Option<PathBuf>) -> ModelFile {
+    let meta = path.as_ref().and_then(|p| std::fs::metadata(p).ok());
+    ModelFile {
+        name: name.to_string(),
+        present: meta.is_some(),
+        size: meta.as_ref().map(|m| m.len()),
+        sha256: path
+            .filter(|_| meta.is_some())
+            .and_then(|p| sha256_of(&p).ok()),
+    }
+}
+
+// newest first, at most `limit`
+fn newest_files(dir: &Path, ext: &str, limit: usize) -> Vec<PathBuf> {
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = std::fs::read_dir(dir)
+        .map(|rd| {
+            rd.flatten()
+                .filter(|e| {
+                    e.path()
+                        .extension()
+                        .map(|x| x.eq_ignore_ascii_case(ext))
+                        .unwrap_or(false)
+                })
+                .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort_by_key(|f| std::cmp::Reverse(f.0));
+    files.into_iter().take(limit).map(|(_, p)| p).collect()
+}
+
+// the last MAX_LOG_BYTES of a log file
+fn tail_of(path: &Path) -> std::io::Result<Vec<u8>> {
+    use std::io::{Seek, SeekFrom};
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(MAX_LOG_BYTES)))?;
+    let mut out = Vec::new();
+    file.read_to_end(&mut out)?;
+    Ok(out)
+}
+
+fn build_bundle(app: &tauri::AppHandle) -> Result<DiagnosticsBundle, String> {
+    let config_dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    let out_dir = config_dir.join("diagnostics");
+    std::fs::create_dir_all(&out_dir).map_err(|e| e.to_string())?;
+    let out_path = out_dir.join(format!(
+        "jarvis-diagnostics-{}.zip",
+        chrono::Local::now().format("%Y-%m-%d_%H-%M-%S")
+    ));
+
+    let info = serde_json::json!({
+        "appVersion": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "family": std::env::consts::FAMILY,
+        "debugBuild": cfg!(debug_assertions),
+        "createdAt": chrono::Utc::now().to_rfc3339(),
+        "tauriVersion": tauri::VERSION,
+    });
+    let devices = serde_json::json!({
+        "input": crate::cmd_list_input_devices().unwrap_or_default(),
+        "output": crate::cmd_list_output_devices().unwrap_or_default(),
+    });
+    let (porcupine_params, porcupine_lib) = run_jarvis::resolve_porcupine_paths(app);
+    let models = vec![
+        model_file(
+            &format!("whisper {}", run_jarvis::DEFAULT_WHISPER_MODEL),
+            Some(run_jarvis::whisper_model_path(app, run_jarvis::DEFAULT_WHISPER_MODEL)),
+        ),
+        model_file("wake word", run_jarvis::resolve_wakeword_path(app).ok()),
+        model_file("porcupine params", Some(porcupine_params)),
+        model_file("porcupine library", Some(porcupine_lib)),
+    ];
+
+    let file = std::fs::File::create(&out_path).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let mut add = |name: &str, bytes: &[u8]| -> Result<(), String> {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(bytes).map_err(|e| e.to_string())
+    };
+
+    let pretty = |v: &serde_json::Value| serde_json::to_vec_pretty(v).unwrap_or_default();
+    add("info.json", &pretty(&info))?;
+    add("config.json", &pretty(&crate::settings::redacted_config(app)?))?;
+    add("devices.json", &pretty(&devices))?;
+    add(
+        "models.json",
+        &serde_json::to_vec_pretty(&models).map_err(|e| e.to_string())?,
+    )?;
+    // crash reports are anonymized when written (see crash.rs)
+    for path in newest_files(&config_dir.join("crashes"), "json", MAX_CRASH_REPORTS) {
+        if let (Some(name), Ok(bytes)) = (path.file_name(), std::fs::read(&path)) {
+            add(&format!("crashes/{}", name.to_string_lossy()), &bytes)?;
+        }
+    }
+    // log files, if the user redirected output there
+    for path in newest_files(&config_dir.join("logs"), "log", MAX_LOG_FILES) {
+        if let (Some(name), Ok(bytes)) = (path.file_name(), tail_of(&path)) {
+            add(&format!("logs/{}", name.to_string_lossy()), &bytes)?;
+        }
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+
+    let size = std::fs::metadata(&out_path).map(|m| m.len()).unwrap_or(0);
+    Ok(DiagnosticsBundle {
+        path: out_path.to_string_lossy().to_string(),
+        size,
+    })
+}
+
+// Hashing the Whisper model takes a few seconds, so this runs off the async runtime
+#[tauri::command]
+pub async fn cmd_create_diagnostics_bundle(
+    app: tauri::AppHandle,
+) -> Result<DiagnosticsBundle, String> {
+    tokio::task::spawn_blocking(move || build_bundle(&app))
+        .await
+        .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,416 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Docker: "are my containers running" and "is the postgres container up" answer from the
+// local Docker engine, and "restart the postgres container" (or start, stop) does it
+// after a yes, see confirm.rs. Off by default, since whoever can talk to the engine can do anything on
+// this machine.
+//
+// The engine is reached on its own socket, Docker Desktop's named pipe on Windows, or
+// over TCP when `host` is e.g. "tcp://localhost:2375". The few calls made here are plain
+// HTTP/1.1 requests written by hand, which keeps a full Docker client out of the build.
+
+use crate::confirm;
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DockerConfig {
+    pub enabled: bool,
+    // "npipe:////./pipe/docker_engine", "unix:///var/run/docker.sock" or
+    // "tcp://host:port"
+    pub host: String,
+    // start, stop and restart; off leaves only the questions
+    pub allow_control: bool,
+}
+
+impl Default for DockerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: DEFAULT_HOST.to_string(),
+            allow_control: true,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerInfo {
+    // the short, 12 character form
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    // "running", "exited", "restarting", ...
+    pub state: String,
+    // as `docker ps` shows it, e.g. "Up 2 hours (healthy)"
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Start,
+    Stop,
+    Restart,
+}
+
+impl Action {
+    fn verb(self) -> &'static str {
+        match self {
+            Action::Start => "start",
+            Action::Stop => "stop",
+            Action::Restart => "restart",
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+const DEFAULT_HOST: &str = "npipe:////./pipe/docker_engine";
+#[cfg(not(target_os = "windows"))]
+const DEFAULT_HOST: &str = "unix:///var/run/docker.sock";
+
+// a stop waits up to ten seconds for the container before killing it
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+// "restart the postgres container", "stop redis"
+static CONTROL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?P<verb>restart|start|stop)\s+(?:the\s+|my\s+)?(?P<name>[\w.-]+)")
+        .expect("Failed to compile container control regex")
+});
+
+// a prompt that is only "restart the postgres container"; these need no trigger phrase
+static COMMAND: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)^\s*(?:please\s+)?(?:restart|start|stop)\s+(?:the\s+|my\s+)?[\w.-]+",
+        r"\s+container(?:\s+please)?\s*[.!?]?\s*$",
+    ))
+    .expect("Failed to compile container command regex")
+});
+
+// "are my containers running", "what's up with docker"
+static GENERAL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:my|the|all|which|any)\s+(?:docker\s+)?containers?\b|\bdocker\b")
+        .expect("Failed to compile container question regex")
+});
+
+static MENTIONS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:docker|containers?)\b").expect("Failed to compile docker regex")
+});
+
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+async fn connect(host: &str) -> Result<Box<dyn Stream>> {
+    if let Some(address) = host.strip_prefix("tcp://") {
+        return Ok(Box::new(tokio::net::TcpStream::connect(address).await?));
+    }
+    #[cfg(target_os = "windows")]
+    if let Some(path) = host.strip_prefix("npipe://") {
+        use tokio::net::windows::named_pipe::ClientOptions;
+        const ERROR_PIPE_BUSY: i32 = 231;
+        // "//./pipe/docker_engine" is \\.\pipe\docker_engine
+        let path = path.replace('/', "\\");
+        // the engine serves one client per pipe instance; wait for a free one
+        for _ in 0..10 {
+            match ClientOptions::new().open(&path) {
+                Ok(pipe) => return Ok(Box::new(pipe)),
+                Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) => {
+                    tokio::time::sleep(Duration::from_millis(50)).await
+                }
+                Err(e) => return Err(anyhow!("Docker isn't running ({})", e)),
+            }
+        }
+        return Err(anyhow!("Docker is busy"));
+    }
+    #[cfg(unix)]
+    if let Some(path) = host.strip_prefix("unix://") {
+        return Ok(Box::new(tokio::net::UnixStream::connect(path).await?));
+    }
+    Err(anyhow!(
+        "docker.host \"{}\" isn't an address I can use",
+        host
+    ))
+}
+
+// Undoes "Transfer-Encoding: chunked"
+fn dechunk(mut body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    while let Some(end) = body.windows(2).position(|w| w == b"\r\n") {
+        let size_line = String::from_utf8_lossy(&body[..end]);
+        let size_hex = size_line.split(';').next().unwrap_or("").trim();
+        let Ok(size) = usize::from_str_radix(size_hex, 16) else {
+            break;
+        };
+        let start = end + 2;
+        if size == 0 || start + size > body.len() {
+            break;
+        }
+        out.extend_from_slice(&body[start..start + size]);
+        body = &body[(start + size + 2).min(body.len())..];
+    }
+    out
+}
+
+// One request on a fresh connection; the status code and body
+async fn call(config: &DockerConfig, method: &str, path: &str) -> Result<(u16, Vec<u8>)> {
+    let exchange = async {
+        let mut stream = connect(config.host.trim()).await?;
+        let request = format!(
+            "{} {} HTTP/1.1\r\nHost: docker\r\nUser-Agent: Jarvis/{}\r\n\
+             Content-Length: 0\r\nConnection: close\r\n\r\n",
+            method,
+            path,
+            env!("CARGO_PKG_VERSION")
+        );
+        stream.write_all(request.as_bytes()).await?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await?;
+        Ok::<_, anyhow::Error>(response)
+    };
+    let response = tokio::time::timeout(REQUEST_TIMEOUT, exchange)
+        .await
+        .map_err(|_| anyhow!("Docker took too long to answer"))??;
+    let split = response
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| anyhow!("Docker sent an incomplete answer"))?;
+    let head = String::from_utf8_lossy(&response[..split]).to_lowercase();
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| anyhow!("Docker sent an unreadable answer"))?;
+    let body = &response[split + 4..];
+    let body = if head.contains("transfer-encoding: chunked") {
+        dechunk(body)
+    } else {
+        body.to_vec()
+    };
+    Ok((status, body))
+}
+
+pub async fn list(config: &DockerConfig) -> Result<Vec<ContainerInfo>> {
+    let (status, body) = call(config, "GET", "/containers/json?all=1").await?;
+    if status != 200 {
+        return Err(anyhow!("Docker returned {}", status));
+    }
+    let data: Value = serde_json::from_slice(&body)?;
+    let text = |v: &Value| v.as_str().unwrap_or("").to_string();
+    let mut containers: Vec<ContainerInfo> = data
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .map(|c| ContainerInfo {
+            id: text(&c["Id"]).chars().take(12).collect(),
+            // names come with a leading slash
+            name: text(&c["Names"][0]).trim_start_matches('/').to_string(),
+            image: text(&c["Image"]),
+            state: text(&c["State"]),
+            status: text(&c["Status"]),
+        })
+        .collect();
+    containers.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(containers)
+}
+
+async fn control(config: DockerConfig, container: ContainerInfo, action: Action) -> String {
+    let path = format!("/containers/{}/{}", container.id, action.verb());
+    match call(&config, "POST", &path).await {
+        // 304: it already was started or stopped
+        Ok((204 | 304, _)) => match action {
+            Action::Start => format!("{} is running.", container.name),
+            Action::Stop => format!("{} is stopped.", container.name),
+            Action::Restart => format!("{} has restarted.", container.name),
+        },
+        Ok((status, body)) => {
+            let message: Value = serde_json::from_slice(&body).unwrap_or_default();
+            eprintln!(
+                "[ERROR] Docker returned {} for {}: {}",
+                status, path, message["message"]
+            );
+            format!("Docker wouldn't {} {}.", action.verb(), container.name)
+        }
+        Err(e) => {
+            eprintln!(
+                "[ERROR] Failed to {} {}: {}",
+                action.verb(),
+                container.name,
+                e
+            );
+            format!("Sorry, I couldn't {} {}.", action.verb(), container.name)
+        }
+    }
+}
+
+fn healthy(c: &ContainerInfo) -> bool {
+    c.state == "running" && !c.status.contains("(unhealthy)")
+}
+
+fn describe_one(c: &ContainerInfo) -> String {
+    match c.state.as_str() {
+        "running" if c.status.contains("(unhealthy)") => {
+            format!("{} is running but unhealthy.", c.name)
+        }
+        "running" => format!("{} is running, {}.", c.name, c.status.to_lowercase()),
+        "" => format!("{} is in an unknown state.", c.name),
+        state => format!("{} is {}.", c.name, state),
+    }
+}
+
+fn describe_all(containers: &[ContainerInfo]) -> String {
+    let running = containers.iter().filter(|c| c.state == "running").count();
+    let troubled: Vec<String> = containers
+        .iter()
+        .filter(|c| !healthy(c))
+        .map(|c| {
+            let state = if c.state == "running" {
+                "unhealthy"
+            } else {
+                c.state.as_str()
+            };
+            format!("{} is {}", c.name, state)
+        })
+        .collect();
+    match (containers.len(), troubled.as_slice()) {
+        (0, _) => "You don't have any containers.".to_string(),
+        (1, []) => format!("{} is running.", containers[0].name),
+        (n, []) => format!("All {} containers are running.", n),
+        (n, _) => format!(
+            "{} of {} containers are running. {}.",
+            running,
+            n,
+            troubled.join(", ")
+        ),
+    }
+}
+
+// The containers whose name is `wanted` or contains it, e.g. "postgres" for the
+// Compose container "shop-postgres-1"
+fn find<'a>(containers: &'a [ContainerInfo], wanted: &str) -> Vec<&'a ContainerInfo> {
+    let wanted = wanted.to_lowercase();
+    if let Some(exact) = containers.iter().find(|c| c.name.to_lowercase() == wanted) {
+        return vec![exact];
+    }
+    containers
+        .iter()
+        .filter(|c| c.name.to_lowercase().contains(&wanted))
+        .collect()
+}
+
+// A container the prompt names, for "is postgres up"
+fn named_in<'a>(prompt: &str, containers: &'a [ContainerInfo]) -> Option<&'a ContainerInfo> {
+    let lower = prompt.to_lowercase();
+    containers.iter().find(|c| {
+        let name = c.name.to_lowercase();
+        lower.contains(&name)
+            || name
+                .split(['-', '_', '.'])
+                .filter(|part| part.len() > 2 && part.chars().any(|ch| ch.is_alphabetic()))
+                .any(|part| {
+                    lower
+                        .split(|ch: char| !ch.is_alphanumeric())
+                        .any(|w| w == part)
+                })
+    })
+}
+
+pub async fn answer(prompt: &str, config: &DockerConfig) -> Option<String> {
+    if !config.enabled {
+        return None;
+    }
+    let containers = match list(config).await {
+        Ok(containers) => containers,
+        Err(e) => {
+            eprintln!("[ERROR] Failed to list containers: {}", e);
+            return Some("I couldn't reach Docker. Is it running?".to_string());
+        }
+    };
+
+    if let Some(captures) = CONTROL.captures(prompt) {
+        let action = match captures["verb"].to_lowercase().as_str() {
+            "start" => Action::Start,
+            "stop" => Action::Stop,
+            _ => Action::Restart,
+        };
+        let wanted = &captures["name"];
+        // "restart my containers" and the like are questions of their own, and "stop the
+        // music" isn't about a container at all
+        if !wanted.to_lowercase().starts_with("container")
+            && (MENTIONS.is_match(prompt) || !find(&containers, wanted).is_empty())
+        {
+            if !config.allow_control {
+                return Some("I'm only allowed to look at your containers.".to_string());
+            }
+            return Some(match find(&containers, wanted).as_slice() {
+                [] => format!("I don't see a container called {}.", wanted),
+                [one] => {
+                    let container = (*one).clone();
+                    let what = format!("{} {}", action.verb(), container.name);
+                    let question = format!(
+                        "{} the {} container?",
+                        capitalized(action.verb()),
+                        container.name
+                    );
+                    confirm::ask(&what, &question, control(config.clone(), container, action))
+                }
+                many => {
+                    let names: Vec<&str> = many.iter().map(|c| c.name.as_str()).collect();
+                    format!(
+                        "More than one container matches {}: {}. Say the full name.",
+                        wanted,
+                        names.join(", ")
+                    )
+                }
+            });
+        }
+    }
+
+    match named_in(prompt, &containers) {
+        Some(container) => Some(describe_one(container)),
+        None if GENERAL.is_match(prompt) => Some(describe_all(&containers)),
+        None => None,
+    }
+}
+
+// Whether the prompt is only a command for one container, e.g. "stop the redis container"
+pub fn is_command(prompt: &str) -> bool {
+    COMMAND.is_match(prompt)
+}
+
+fn capitalized(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[tauri::command]
+pub async fn cmd_list_containers(app: tauri::AppHandle) -> Result<Vec<ContainerInfo>, String> {
+    let config = crate::cmd_load_config(app)?.docker;
+    if !config.enabled {
+        return Err("Docker access is turned off in the settings".to_string());
+    }
+    list(&config).await.map_err(|e| e.to_string())
+}
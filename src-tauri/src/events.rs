@@ -0,0 +1,504 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Payloads of the events the chat UI listens to. The TypeScript side is generated
+// from these with ts-rs (`cargo test export_bindings`) into src/bindings/, so a
+// renamed field breaks the frontend build instead of silently showing nothing.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::Emitter;
+use ts_rs::TS;
+
+// Free-form message details (model, persona, attachments, ...)
+pub type Meta = Map<String, Value>;
+
+// An event payload and the name it is emitted under
+pub trait Event: Serialize + Clone {
+    const NAME: &'static str;
+}
+
+pub fn emit<E: Event>(app: &tauri::AppHandle, event: E) {
+    if let Err(e) = app.emit(E::NAME, event) {
+        eprintln!("[WARN] Failed to emit {}: {}", E::NAME, e);
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum Role {
+    User,
+    Assistant,
+    System,
+}
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct NewMessage {
+    pub role: Role,
+    pub content: String,
+    // unix milliseconds; also identifies the message in later `message-meta` events
+    #[ts(type = "number")]
+    pub created_at: i64,
+    #[ts(type = "Record<string, unknown>")]
+    pub meta: Meta,
+}
+
+impl Event for NewMessage {
+    const NAME: &'static str = "new-message";
+}
+
+impl NewMessage {
+    pub fn new(role: Role, content: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: content.into(),
+            created_at: chrono::Utc::now().timestamp_millis(),
+            meta: Meta::new(),
+        }
+    }
+
+    pub fn at(mut self, created_at: i64) -> Self {
+        self.created_at = created_at;
+        self
+    }
+
+    pub fn with(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.meta.insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn with_meta(mut self, meta: Meta) -> Self {
+        self.meta.extend(meta);
+        self
+    }
+}
+
+// Details added to a message that was already emitted
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MessageMeta {
+    #[ts(type = "number")]
+    pub created_at: i64,
+    pub role: Role,
+    #[ts(type = "Record<string, unknown>")]
+    pub meta: Meta,
+}
+
+impl Event for MessageMeta {
+    const NAME: &'static str = "message-meta";
+}
+
+impl MessageMeta {
+    pub fn new(role: Role, created_at: i64) -> Self {
+        Self {
+            created_at,
+            role,
+            meta: Meta::new(),
+        }
+    }
+
+    pub fn with(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.meta.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct WhisperDownloadProgress {
+    #[ts(type = "number")]
+    pub downloaded: u64,
+    #[ts(type = "number")]
+    pub total: u64,
+    #[ts(type = "number")]
+    pub percent: u64,
+}
+
+impl Event for WhisperDownloadProgress {
+    const NAME: &'static str = "whisper-download-progress";
+}
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct WhisperDownloadComplete {}
+
+impl Event for WhisperDownloadComplete {
+    const NAME: &'static str = "whisper-download-complete";
+}
+
+// [[copy]] blocks from an answer that were put on the clipboard
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CodeCopied {
+    // blocks copied, and how many the answer had
+    #[ts(type = "number")]
+    pub copied: usize,
+    #[ts(type = "number")]
+    pub total: usize,
+    pub text: String,
+}
+
+impl Event for CodeCopied {
+    const NAME: &'static str = "code-copied";
+}
+
+// Progress of a batch operation on conversations (delete, archive, unarchive, export)
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct BatchProgress {
+    pub operation: String,
+    #[ts(type = "number")]
+    pub done: usize,
+    #[ts(type = "number")]
+    pub total: usize,
+    // the conversation just handled
+    pub filename: String,
+}
+
+impl Event for BatchProgress {
+    const NAME: &'static str = "batch-progress";
+}
+
+// Conversation files added, changed or removed outside Jarvis (e.g. by a sync tool)
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct HistoryChanged {
+    pub filenames: Vec<String>,
+}
+
+impl Event for HistoryChanged {
+    const NAME: &'static str = "history-changed";
+}
+
+// Outcome of a sync with the other devices; also what `cmd_sync_now` returns
+#[derive(Serialize, Debug, Clone, Default, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SyncReport {
+    // conversations, memories and settings sent to or taken from the server
+    #[ts(type = "number")]
+    pub uploaded: usize,
+    #[ts(type = "number")]
+    pub downloaded: usize,
+    // conversations removed here because they were deleted on another device
+    #[ts(type = "number")]
+    pub deleted: usize,
+    pub settings_changed: bool,
+    pub errors: Vec<String>,
+}
+
+impl Event for SyncReport {
+    const NAME: &'static str = "sync-finished";
+}
+
+// Whether game mode is on, and for which program (see game_mode.rs)
+#[derive(Serialize, Debug, Clone, Default, TS)]
+#[ts(export)]
+pub struct GameMode {
+    pub active: bool,
+    pub app: String,
+}
+
+impl Event for GameMode {
+    const NAME: &'static str = "game-mode";
+}
+
+impl GameMode {
+    pub fn on(app: &str) -> Self {
+        Self {
+            active: true,
+            app: app.to_string(),
+        }
+    }
+}
+
+// Everything that shaped the answer to one voice command, for working out why Jarvis
+// replied the way it did (see trace.rs)
+#[derive(Serialize, Debug, Clone, Default, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct InteractionTrace {
+    #[ts(type = "number")]
+    pub started_at: i64,
+    // "voice" or "simulated"
+    pub source: String,
+    // what was heard
+    pub input: String,
+    pub persona: Option<String>,
+    // the built-in voice command that handled it instead of the LLM, e.g. "weather"
+    pub command: Option<String>,
+    // clipboard pasted, images attached, facts remembered, links and PDFs read
+    pub tools: Vec<String>,
+    pub provider: String,
+    pub model: String,
+    // prompt and context as sent: after redaction and trimming to the context window
+    pub prompt: String,
+    pub context: String,
+    #[ts(type = "number")]
+    pub redactions: usize,
+    // conversation turns left out to fit the context window
+    #[ts(type = "number")]
+    pub dropped_turns: usize,
+    pub prompt_shortened: bool,
+    // asked a second time after an empty answer
+    pub retried: bool,
+    pub answer: String,
+    // what was read aloud, and by which voice
+    pub spoken: String,
+    pub voice: String,
+    pub error: Option<String>,
+    #[ts(type = "number")]
+    pub duration_ms: u64,
+}
+
+impl Event for InteractionTrace {
+    const NAME: &'static str = "interaction-trace";
+}
+
+// Microphone audio dropped because nothing read it in time (see audio_input.rs)
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct AudioOverflow {
+    #[ts(type = "number")]
+    pub dropped_samples: u64,
+    #[ts(type = "number")]
+    pub overflow_events: u64,
+    #[ts(type = "number")]
+    pub capacity: usize,
+}
+
+impl Event for AudioOverflow {
+    const NAME: &'static str = "audio-overflow";
+}
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct DeviceLost {
+    pub device: String,
+    pub error: String,
+}
+
+impl Event for DeviceLost {
+    const NAME: &'static str = "device-lost";
+}
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct DeviceReconnected {
+    pub device: String,
+    // true when the lost device never came back and the system default is used instead
+    pub fallback: bool,
+}
+
+impl Event for DeviceReconnected {
+    const NAME: &'static str = "device-reconnected";
+}
+
+// Reconnecting was retried for a minute without any input device showing up
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct DeviceReconnectFailed {
+    pub device: String,
+}
+
+impl Event for DeviceReconnectFailed {
+    const NAME: &'static str = "device-reconnect-failed";
+}
+
+// ElevenLabs usage crossed the configured warning threshold; sent once per billing period
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct TtsQuotaLow {
+    pub quota: crate::tts::TtsQuota,
+    pub threshold_percent: f32,
+}
+
+impl Event for TtsQuotaLow {
+    const NAME: &'static str = "tts-quota-low";
+}
+
+// Rolling meeting notes covering the transcript between `from` and `to` (unix ms)
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct MeetingNotes {
+    #[ts(type = "number")]
+    pub from: i64,
+    #[ts(type = "number")]
+    pub to: i64,
+    pub notes: String,
+}
+
+impl Event for MeetingNotes {
+    const NAME: &'static str = "meeting-notes";
+}
+
+// Sent after the user profile is saved (see user_profile.rs)
+impl Event for crate::user_profile::UserProfile {
+    const NAME: &'static str = "user-profile-changed";
+}
+
+// Every tracked package, after one was added, removed or checked (see packages.rs)
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(transparent)]
+#[ts(export)]
+pub struct PackagesChanged(pub Vec<crate::packages::Package>);
+
+impl Event for PackagesChanged {
+    const NAME: &'static str = "packages-changed";
+}
+
+// Every running kitchen timer, after one was set, cancelled or went off (see timers.rs)
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(transparent)]
+#[ts(export)]
+pub struct TimersChanged(pub Vec<crate::timers::Timer>);
+
+impl Event for TimersChanged {
+    const NAME: &'static str = "timers-changed";
+}
+
+// The recipe being cooked and the current step; None when cooking mode ends
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(transparent)]
+#[ts(export)]
+pub struct CookingChanged(pub Option<crate::cooking::CookingState>);
+
+impl Event for CookingChanged {
+    const NAME: &'static str = "cooking-changed";
+}
+
+// A voice memo was recorded or deleted (see voice_memos.rs)
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct VoiceMemosChanged {}
+
+impl Event for VoiceMemosChanged {
+    const NAME: &'static str = "voice-memos-changed";
+}
+
+// The running focus session; None once it is stopped (see pomodoro.rs)
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(transparent)]
+#[ts(export)]
+pub struct PomodoroChanged(pub Option<crate::pomodoro::Pomodoro>);
+
+impl Event for PomodoroChanged {
+    const NAME: &'static str = "pomodoro-changed";
+}
+
+// Sent when the phone arrives or leaves (see presence.rs)
+impl Event for crate::presence::PresenceStatus {
+    const NAME: &'static str = "presence-changed";
+}
+
+// What was heard so far of the command being spoken (see overlay.rs)
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PartialTranscript {
+    pub text: String,
+    // false while the user may still be speaking
+    pub is_final: bool,
+}
+
+impl Event for PartialTranscript {
+    const NAME: &'static str = "partial-transcript";
+}
+
+// Progress of reading the selected text aloud (see read_aloud.rs)
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReadAloudState {
+    pub reading: bool,
+    // 1-based chunk being spoken, 0 when stopped
+    #[ts(type = "number")]
+    pub chunk: usize,
+    #[ts(type = "number")]
+    pub total: usize,
+}
+
+impl Event for ReadAloudState {
+    const NAME: &'static str = "read-aloud-state";
+}
+
+// Conversation turns or the prompt were cut to fit the context window (see context_budget.rs)
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ContextTruncated {
+    #[ts(type = "number")]
+    pub dropped_turns: usize,
+    pub prompt_shortened: bool,
+    // estimates before trimming, and the budget they had to fit in
+    #[ts(type = "number")]
+    pub estimated_tokens: usize,
+    #[ts(type = "number")]
+    pub budget_tokens: usize,
+}
+
+impl Event for ContextTruncated {
+    const NAME: &'static str = "context-truncated";
+}
+
+// How many memories there are, after one was added, removed or synced (see memory.rs)
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(transparent)]
+#[ts(export)]
+pub struct MemoriesChanged(#[ts(type = "number")] pub usize);
+
+impl Event for MemoriesChanged {
+    const NAME: &'static str = "memories-changed";
+}
+
+// How many pronunciation entries there are, after the lexicon was edited (see lexicon.rs)
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(transparent)]
+#[ts(export)]
+pub struct LexiconChanged(#[ts(type = "number")] pub usize);
+
+impl Event for LexiconChanged {
+    const NAME: &'static str = "lexicon-changed";
+}
+
+// A check found a newer app version or models (see updates.rs)
+impl Event for crate::updates::UpdateInfo {
+    const NAME: &'static str = "update-available";
+}
+
+// Sent for every audio frame while a command is recorded (see get_text.rs)
+impl Event for crate::get_text::VadFrame {
+    const NAME: &'static str = "vad-activity";
+}
+
+// A transcribed chunk of the meeting being recorded (see meeting.rs)
+impl Event for crate::meeting::Segment {
+    const NAME: &'static str = "meeting-segment";
+}
+
+// Meeting transcription started or stopped
+impl Event for crate::meeting::MeetingStatus {
+    const NAME: &'static str = "meeting-state";
+}
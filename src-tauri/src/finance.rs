@@ -0,0 +1,673 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Stock, crypto and currency quotes: "what's the EUR to USD rate", "how's NVDA doing",
+// "how's my watchlist". Stocks come from Yahoo Finance or Alpha Vantage, crypto from
+// CoinGecko and exchange rates from Frankfurter (ECB) or open.er-api.com. Quotes are
+// kept for `cache_seconds` and read out rounded the way people say prices.
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StockProvider {
+    Yahoo,
+    AlphaVantage,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CurrencyProvider {
+    Frankfurter,
+    OpenErApi,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct FinanceConfig {
+    pub stock_provider: StockProvider,
+    // only needed for Alpha Vantage
+    pub alpha_vantage_key: String,
+    // optional CoinGecko demo key; without one the public rate limit applies
+    pub coingecko_key: String,
+    pub currency_provider: CurrencyProvider,
+    // what crypto prices and "the euro rate" are given in
+    pub base_currency: String,
+    // how long a quote is reused before asking again
+    pub cache_seconds: u64,
+    // extra company names for tickers, e.g. "my employer": "ACME"
+    pub symbols: BTreeMap<String, String>,
+    // read out for "how's my watchlist": tickers, coins ("bitcoin") or pairs ("EUR/USD")
+    pub watchlist: Vec<String>,
+}
+
+impl Default for FinanceConfig {
+    fn default() -> Self {
+        Self {
+            stock_provider: StockProvider::Yahoo,
+            alpha_vantage_key: String::new(),
+            coingecko_key: String::new(),
+            currency_provider: CurrencyProvider::Frankfurter,
+            base_currency: "USD".to_string(),
+            cache_seconds: 300,
+            symbols: BTreeMap::new(),
+            watchlist: Vec::new(),
+        }
+    }
+}
+
+// code, singular, plural; the names are also what is listened for
+const CURRENCIES: &[(&str, &str, &str)] = &[
+    ("USD", "dollar", "dollars"),
+    ("EUR", "euro", "euros"),
+    ("GBP", "pound", "pounds"),
+    ("JPY", "yen", "yen"),
+    ("CHF", "Swiss franc", "Swiss francs"),
+    ("CNY", "yuan", "yuan"),
+    ("INR", "rupee", "rupees"),
+    ("CAD", "Canadian dollar", "Canadian dollars"),
+    ("AUD", "Australian dollar", "Australian dollars"),
+    ("NZD", "New Zealand dollar", "New Zealand dollars"),
+    ("HKD", "Hong Kong dollar", "Hong Kong dollars"),
+    ("SGD", "Singapore dollar", "Singapore dollars"),
+    ("SEK", "Swedish krona", "Swedish kronor"),
+    ("NOK", "Norwegian krone", "Norwegian kroner"),
+    ("DKK", "Danish krone", "Danish kroner"),
+    ("PLN", "zloty", "zloty"),
+    ("CZK", "Czech koruna", "Czech korunas"),
+    ("HUF", "forint", "forints"),
+    ("TRY", "Turkish lira", "Turkish lira"),
+    ("MXN", "Mexican peso", "Mexican pesos"),
+    ("BRL", "real", "reais"),
+    ("KRW", "won", "won"),
+    ("ZAR", "rand", "rand"),
+];
+
+// other names for the same currencies
+const CURRENCY_NAMES: &[(&str, &str)] = &[
+    ("us dollar", "USD"),
+    ("us dollars", "USD"),
+    ("bucks", "USD"),
+    ("sterling", "GBP"),
+    ("british pound", "GBP"),
+    ("british pounds", "GBP"),
+    ("quid", "GBP"),
+    ("franc", "CHF"),
+    ("francs", "CHF"),
+    ("renminbi", "CNY"),
+    ("brazilian real", "BRL"),
+    ("korean won", "KRW"),
+    ("south african rand", "ZAR"),
+];
+
+// codes and names that are mostly ordinary words; only the longer names above count
+const NOT_CURRENCIES: &[&str] = &["try", "real", "won", "rand"];
+
+// CoinGecko id, spoken name, names listened for
+const COINS: &[(&str, &str, &[&str])] = &[
+    ("bitcoin", "Bitcoin", &["bitcoin", "btc"]),
+    ("ethereum", "Ethereum", &["ethereum", "ether", "eth"]),
+    ("solana", "Solana", &["solana"]),
+    ("dogecoin", "Dogecoin", &["dogecoin", "doge"]),
+    ("cardano", "Cardano", &["cardano"]),
+    ("ripple", "XRP", &["xrp", "ripple"]),
+    ("litecoin", "Litecoin", &["litecoin", "ltc"]),
+    ("polkadot", "Polkadot", &["polkadot"]),
+    ("binancecoin", "BNB", &["bnb"]),
+];
+
+// company and index names people say instead of the ticker
+const COMPANIES: &[(&str, &str)] = &[
+    ("apple", "AAPL"),
+    ("microsoft", "MSFT"),
+    ("nvidia", "NVDA"),
+    ("tesla", "TSLA"),
+    ("google", "GOOGL"),
+    ("alphabet", "GOOGL"),
+    ("amazon", "AMZN"),
+    ("meta", "META"),
+    ("facebook", "META"),
+    ("netflix", "NFLX"),
+    ("intel", "INTC"),
+    ("amd", "AMD"),
+    ("ibm", "IBM"),
+    ("sap", "SAP"),
+    ("s&p 500", "^GSPC"),
+    ("s&p", "^GSPC"),
+    ("dow jones", "^DJI"),
+    ("the dow", "^DJI"),
+    ("nasdaq", "^IXIC"),
+    ("dax", "^GDAXI"),
+];
+
+// capitalized words that look like tickers but aren't
+const NOT_TICKERS: &[&str] = &[
+    "AI", "OK", "TV", "US", "USA", "UK", "EU", "PM", "AM", "CEO", "GDP", "ETF", "IPO", "PC",
+    "API", "IT", "ID", "NYSE",
+];
+
+static PRICE_WORDS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(stocks?|shares?|ticker|trading|doing|price|quote)\b")
+        .expect("Failed to compile price words regex")
+});
+
+static RATE_WORDS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(rate|rates|exchange|convert|conversion)\b")
+        .expect("Failed to compile rate words regex")
+});
+
+// only a rate question between two currencies: "how many pounds is a stone" isn't one
+static CONVERSION_WORDS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(in|to|is|are|worth|how many|how much)\b")
+        .expect("Failed to compile conversion words regex")
+});
+
+static WATCHLIST: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(watchlist|watch list|my stocks|the markets?)\b")
+        .expect("Failed to compile watchlist regex")
+});
+
+static AMOUNT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\d[\d,]*(?:\.\d+)?").expect("Failed to compile amount regex"));
+
+static SPELLED_TICKER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:[A-Z][.\-]){1,4}[A-Z]$").expect("Failed to compile spelled ticker regex")
+});
+
+const YAHOO_CHART_URL: &str = "https://query1.finance.yahoo.com/v8/finance/chart/";
+
+#[derive(Debug, Clone)]
+struct Quote {
+    name: String,
+    price: f64,
+    currency: String,
+    change_pct: Option<f64>,
+}
+
+static CACHE: Lazy<Mutex<HashMap<String, (Instant, Quote)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+enum Query {
+    Rate { amount: f64, from: String, to: String },
+    Stock(String),
+    Coin(&'static str, &'static str),
+}
+
+fn currency(key: &str) -> Option<&'static str> {
+    if NOT_CURRENCIES.contains(&key.to_lowercase().as_str()) {
+        return None;
+    }
+    CURRENCIES
+        .iter()
+        .find(|(code, one, many)| {
+            code.eq_ignore_ascii_case(key)
+                || one.eq_ignore_ascii_case(key)
+                || many.eq_ignore_ascii_case(key)
+        })
+        .map(|(code, _, _)| *code)
+        .or_else(|| CURRENCY_NAMES.iter().find(|(name, _)| *name == key).map(|(_, c)| *c))
+}
+
+fn coin(key: &str) -> Option<(&'static str, &'static str)> {
+    COINS
+        .iter()
+        .find(|(_, _, names)| names.contains(&key))
+        .map(|(id, name, _)| (*id, *name))
+}
+
+fn company(key: &str, config: &FinanceConfig) -> Option<String> {
+    config
+        .symbols
+        .iter()
+        .find(|(name, _)| name.trim().to_lowercase() == key)
+        .map(|(_, symbol)| symbol.trim().to_uppercase())
+        .or_else(|| {
+            COMPANIES
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, s)| s.to_string())
+        })
+}
+
+// The words of the text as said and lowercased, without the punctuation around them
+fn words(text: &str) -> (Vec<&str>, Vec<String>) {
+    let shown: Vec<&str> = text
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric() && c != '&'))
+        .collect();
+    let keys = shown
+        .iter()
+        .map(|w| {
+            let w = w.to_lowercase();
+            let stem = w.strip_suffix("'s").or_else(|| w.strip_suffix("’s"));
+            stem.unwrap_or(&w).to_string()
+        })
+        .collect();
+    (shown, keys)
+}
+
+// Everything `find` recognizes in the text, in order; longer names win
+fn scan<T>(keys: &[String], find: impl Fn(&str) -> Option<T>) -> Vec<T> {
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < keys.len() {
+        let hit = (1..=3)
+            .rev()
+            .filter(|n| i + n <= keys.len())
+            .find_map(|n| find(&keys[i..i + n].join(" ")).map(|t| (n, t)));
+        match hit {
+            Some((n, t)) => {
+                found.push(t);
+                i += n;
+            }
+            None => i += 1,
+        }
+    }
+    found
+}
+
+// "NVDA" or "N.V.D.A." as Whisper writes a spelled-out ticker
+fn ticker(word: &str) -> Option<String> {
+    let symbol = if SPELLED_TICKER.is_match(word) {
+        word.replace(['.', '-'], "")
+    } else {
+        word.to_string()
+    };
+    let plausible = (2..=5).contains(&symbol.len())
+        && symbol.chars().all(|c| c.is_ascii_uppercase())
+        && !NOT_TICKERS.contains(&symbol.as_str())
+        && currency(&symbol).is_none()
+        && coin(&symbol.to_lowercase()).is_none();
+    plausible.then_some(symbol)
+}
+
+fn parse(prompt: &str, config: &FinanceConfig) -> Option<Query> {
+    let (shown, keys) = words(prompt);
+
+    let mut currencies = scan(&keys, currency);
+    currencies.dedup();
+    let asks_rate = RATE_WORDS.is_match(prompt)
+        || (currencies.len() >= 2 && CONVERSION_WORDS.is_match(prompt));
+    if !currencies.is_empty() && asks_rate {
+        let amount = AMOUNT
+            .find(prompt)
+            .and_then(|m| m.as_str().replace(',', "").parse::<f64>().ok())
+            .filter(|a| *a > 0.0)
+            .unwrap_or(1.0);
+        let base = config.base_currency.trim().to_uppercase();
+        let (from, to) = match currencies.as_slice() {
+            [only] if *only == base => return None,
+            [only] => (only.to_string(), base),
+            // "how many yen is a dollar" asks the other way round
+            [a, b, ..] if prompt.to_lowercase().contains("how many") => {
+                (b.to_string(), a.to_string())
+            }
+            [a, b, ..] => (a.to_string(), b.to_string()),
+            [] => return None,
+        };
+        return Some(Query::Rate { amount, from, to });
+    }
+
+    if !PRICE_WORDS.is_match(prompt) {
+        return None;
+    }
+    if let Some((id, name)) = scan(&keys, coin).into_iter().next() {
+        return Some(Query::Coin(id, name));
+    }
+    if let Some(symbol) = scan(&keys, |k| company(k, config)).into_iter().next() {
+        return Some(Query::Stock(symbol));
+    }
+    shown.iter().find_map(|w| ticker(w)).map(Query::Stock)
+}
+
+// significant digits -> decimals to show, e.g. 1.0851 with 4 -> 3
+fn decimals_for(value: f64, significant: i32) -> usize {
+    if value == 0.0 {
+        return 0;
+    }
+    let before_point = value.abs().log10().floor() as i32 + 1;
+    (significant - before_point).clamp(0, 8) as usize
+}
+
+// 67234.5 -> "67,235", 1.5 -> "1.50"
+fn with_separators(value: f64, decimals: usize) -> String {
+    let s = format!("{:.*}", decimals, value.abs());
+    let (int, frac) = s.split_once('.').unwrap_or((&s, ""));
+    let mut grouped = String::new();
+    for (i, c) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    let sign = if value < 0.0 { "-" } else { "" };
+    if frac.is_empty() {
+        format!("{}{}", sign, grouped)
+    } else {
+        format!("{}{}.{}", sign, grouped, frac)
+    }
+}
+
+// prices as people say them: whole numbers from 1,000, cents below, three significant
+// digits under 1
+fn spoken_price(value: f64) -> String {
+    let decimals = if value.abs() >= 1000.0 {
+        0
+    } else if value.abs() >= 1.0 {
+        2
+    } else {
+        decimals_for(value, 3)
+    };
+    with_separators(value, decimals)
+}
+
+// exchange rates to four significant digits: 1.085, 156.2
+fn spoken_rate(value: f64) -> String {
+    with_separators(value, decimals_for(value, 4))
+}
+
+fn money(amount: &str, code: &str) -> String {
+    let one = amount == "1";
+    match CURRENCIES.iter().find(|(c, _, _)| *c == code) {
+        Some((_, singular, _)) if one => format!("1 {}", singular),
+        Some((_, _, plural)) => format!("{} {}", amount, plural),
+        None => format!("{} {}", amount, code),
+    }
+}
+
+fn spoken_change(pct: f64) -> String {
+    if pct.abs() < 0.05 {
+        "unchanged".to_string()
+    } else if pct > 0.0 {
+        format!("up {:.1} percent", pct)
+    } else {
+        format!("down {:.1} percent", -pct)
+    }
+}
+
+fn client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        // Yahoo rejects requests without a user agent
+        .user_agent(concat!("Jarvis/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| anyhow!("Failed to build HTTP client: {e}"))
+}
+
+async fn get_json(request: reqwest::RequestBuilder) -> Result<Value> {
+    let resp = request.send().await?;
+    if !resp.status().is_success() {
+        return Err(anyhow!("The quote service returned {}", resp.status()));
+    }
+    Ok(resp.json().await?)
+}
+
+async fn cached<F>(key: String, config: &FinanceConfig, fetch: F) -> Result<Quote>
+where
+    F: Future<Output = Result<Quote>>,
+{
+    let max_age = Duration::from_secs(config.cache_seconds);
+    if let Some((at, quote)) = CACHE.lock().unwrap().get(&key) {
+        if at.elapsed() < max_age {
+            return Ok(quote.clone());
+        }
+    }
+    let quote = fetch.await?;
+    CACHE
+        .lock()
+        .unwrap()
+        .insert(key, (Instant::now(), quote.clone()));
+    Ok(quote)
+}
+
+fn number(v: &Value) -> Option<f64> {
+    v.as_f64().or_else(|| v.as_str()?.trim().parse().ok())
+}
+
+async fn fetch_stock(
+    client: &reqwest::Client,
+    config: &FinanceConfig,
+    symbol: &str,
+) -> Result<Quote> {
+    match config.stock_provider {
+        StockProvider::Yahoo => {
+            let mut url = reqwest::Url::parse(YAHOO_CHART_URL)?;
+            url.path_segments_mut()
+                .map_err(|_| anyhow!("Invalid quote URL"))?
+                .pop_if_empty()
+                .push(symbol);
+            url.set_query(Some("range=1d&interval=1d"));
+            let data = get_json(client.get(url)).await?;
+            let meta = &data["chart"]["result"][0]["meta"];
+            let price = number(&meta["regularMarketPrice"])
+                .ok_or_else(|| anyhow!("No quote for {}", symbol))?;
+            let previous = number(&meta["chartPreviousClose"]).filter(|p| *p > 0.0);
+            let name = meta["shortName"]
+                .as_str()
+                .or_else(|| meta["longName"].as_str())
+                .unwrap_or(symbol);
+            Ok(Quote {
+                name: name.trim().to_string(),
+                price,
+                currency: meta["currency"].as_str().unwrap_or("USD").to_uppercase(),
+                change_pct: previous.map(|p| (price - p) / p * 100.0),
+            })
+        }
+        StockProvider::AlphaVantage => {
+            if config.alpha_vantage_key.trim().is_empty() {
+                return Err(anyhow!("No Alpha Vantage key is set"));
+            }
+            let request = client.get("https://www.alphavantage.co/query").query(&[
+                ("function", "GLOBAL_QUOTE"),
+                ("symbol", symbol),
+                ("apikey", config.alpha_vantage_key.trim()),
+            ]);
+            let data = get_json(request).await?;
+            let quote = &data["Global Quote"];
+            let price =
+                number(&quote["05. price"]).ok_or_else(|| anyhow!("No quote for {}", symbol))?;
+            let change = quote["10. change percent"]
+                .as_str()
+                .and_then(|p| p.trim_end_matches('%').trim().parse().ok());
+            Ok(Quote {
+                name: symbol.to_string(),
+                price,
+                // Alpha Vantage doesn't say; its global quotes are US listings
+                currency: "USD".to_string(),
+                change_pct: change,
+            })
+        }
+    }
+}
+
+async fn fetch_coin(
+    client: &reqwest::Client,
+    config: &FinanceConfig,
+    id: &str,
+    name: &str,
+) -> Result<Quote> {
+    let vs = config.base_currency.trim().to_lowercase();
+    let mut request = client
+        .get("https://api.coingecko.com/api/v3/simple/price")
+        .query(&[
+            ("ids", id),
+            ("vs_currencies", vs.as_str()),
+            ("include_24hr_change", "true"),
+        ]);
+    if !config.coingecko_key.trim().is_empty() {
+        request = request.header("x-cg-demo-api-key", config.coingecko_key.trim());
+    }
+    let data = get_json(request).await?;
+    let price = number(&data[id][vs.as_str()]).ok_or_else(|| anyhow!("No price for {}", name))?;
+    Ok(Quote {
+        name: name.to_string(),
+        price,
+        currency: vs.to_uppercase(),
+        change_pct: number(&data[id][format!("{}_24h_change", vs).as_str()]),
+    })
+}
+
+async fn fetch_rate(
+    client: &reqwest::Client,
+    config: &FinanceConfig,
+    from: &str,
+    to: &str,
+) -> Result<Quote> {
+    let data = match config.currency_provider {
+        CurrencyProvider::Frankfurter => {
+            let request = client
+                .get("https://api.frankfurter.app/latest")
+                .query(&[("from", from), ("to", to)]);
+            get_json(request).await?
+        }
+        CurrencyProvider::OpenErApi => {
+            let mut url = reqwest::Url::parse("https://open.er-api.com/v6/latest/")?;
+            url.path_segments_mut()
+                .map_err(|_| anyhow!("Invalid rate URL"))?
+                .pop_if_empty()
+                .push(from);
+            get_json(client.get(url)).await?
+        }
+    };
+    let rate = number(&data["rates"][to]).ok_or_else(|| anyhow!("No rate for {} to {}", from, to))?;
+    Ok(Quote {
+        name: format!("{}/{}", from, to),
+        price: rate,
+        currency: to.to_string(),
+        change_pct: None,
+    })
+}
+
+async fn describe(client: &reqwest::Client, config: &FinanceConfig, query: &Query) -> String {
+    match query {
+        Query::Rate { amount, from, to } => {
+            let key = format!("fx:{}:{}", from, to);
+            match cached(key, config, fetch_rate(client, config, from, to)).await {
+                Ok(q) if *amount == 1.0 => {
+                    format!("{} is {}.", money("1", from), money(&spoken_rate(q.price), to))
+                }
+                Ok(q) => format!(
+                    "{} is {}.",
+                    money(&spoken_price(*amount), from),
+                    money(&spoken_price(amount * q.price), to)
+                ),
+                Err(e) => {
+                    eprintln!("[ERROR] Failed to get exchange rate: {}", e);
+                    format!("Sorry, I couldn't get the {} to {} rate right now.", from, to)
+                }
+            }
+        }
+        Query::Stock(symbol) => {
+            let key = format!("stock:{}", symbol);
+            match cached(key, config, fetch_stock(client, config, symbol)).await {
+                Ok(q) => {
+                    let price = money(&spoken_price(q.price), &q.currency);
+                    match q.change_pct {
+                        Some(pct) => {
+                            format!("{} is at {}, {} today.", q.name, price, spoken_change(pct))
+                        }
+                        None => format!("{} is at {}.", q.name, price),
+                    }
+                }
+                Err(e) => {
+                    eprintln!("[ERROR] Failed to get stock quote: {}", e);
+                    format!("Sorry, I couldn't get a quote for {} right now.", symbol)
+                }
+            }
+        }
+        Query::Coin(id, name) => {
+            let key = format!("coin:{}:{}", id, config.base_currency.trim().to_lowercase());
+            match cached(key, config, fetch_coin(client, config, id, name)).await {
+                Ok(q) => {
+                    let price = money(&spoken_price(q.price), &q.currency);
+                    match q.change_pct {
+                        Some(pct) => format!(
+                            "{} is at {}, {} in the last 24 hours.",
+                            q.name,
+                            price,
+                            spoken_change(pct)
+                        ),
+                        None => format!("{} is at {}.", q.name, price),
+                    }
+                }
+                Err(e) => {
+                    eprintln!("[ERROR] Failed to get crypto price: {}", e);
+                    format!("Sorry, I couldn't get the price of {} right now.", name)
+                }
+            }
+        }
+    }
+}
+
+// a watchlist entry: "EUR/USD", "bitcoin" or a ticker
+fn watchlist_query(item: &str, config: &FinanceConfig) -> Option<Query> {
+    let item = item.trim();
+    if let Some((from, to)) = item.split_once('/') {
+        return Some(Query::Rate {
+            amount: 1.0,
+            from: currency(from.trim())?.to_string(),
+            to: currency(to.trim())?.to_string(),
+        });
+    }
+    let key = item.to_lowercase();
+    if let Some((id, name)) = coin(&key) {
+        return Some(Query::Coin(id, name));
+    }
+    let symbol = company(&key, config).unwrap_or_else(|| item.to_uppercase());
+    (!symbol.is_empty()).then_some(Query::Stock(symbol))
+}
+
+// One sentence per watchlist entry; also used by the briefing
+pub async fn watchlist_briefing(config: &FinanceConfig) -> String {
+    if config.watchlist.is_empty() {
+        return "Your watchlist is empty. Add tickers to finance.watchlist in the settings."
+            .to_string();
+    }
+    let client = match client() {
+        Ok(c) => c,
+        Err(e) => return format!("Sorry, I couldn't check your watchlist: {}", e),
+    };
+    let mut lines = Vec::new();
+    for item in &config.watchlist {
+        match watchlist_query(item, config) {
+            Some(query) => lines.push(describe(&client, config, &query).await),
+            None => lines.push(format!("I don't know how to look up {}.", item.trim())),
+        }
+    }
+    lines.join(" ")
+}
+
+// The spoken answer, or None when the prompt isn't about a price or rate
+pub async fn answer(prompt: &str, config: &FinanceConfig) -> Option<String> {
+    if WATCHLIST.is_match(prompt) {
+        return Some(watchlist_briefing(config).await);
+    }
+    let query = parse(prompt, config)?;
+    let client = match client() {
+        Ok(c) => c,
+        Err(e) => return Some(format!("Sorry, I couldn't look that up: {}", e)),
+    };
+    Some(describe(&client, config, &query).await)
+}
@@ -0,0 +1,166 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Watches which window is in the foreground through a Windows event hook, so features
+// can react when an app gains or loses focus or goes fullscreen. The hook needs a
+// message loop on the thread that set it, so it gets a thread of its own.
+
+use std::sync::{Mutex, OnceLock};
+use winapi::shared::minwindef::{DWORD, FALSE};
+use winapi::shared::ntdef::LONG;
+use winapi::shared::windef::{HWINEVENTHOOK, HWND, RECT};
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::processthreadsapi::OpenProcess;
+use winapi::um::winbase::QueryFullProcessImageNameW;
+use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+use winapi::um::winuser::{
+    DispatchMessageW, GetDesktopWindow, GetForegroundWindow, GetMessageW, GetMonitorInfoW,
+    GetShellWindow, GetWindowRect, GetWindowTextW, GetWindowThreadProcessId, MonitorFromWindow,
+    SetWinEventHook, TranslateMessage, EVENT_OBJECT_LOCATIONCHANGE, EVENT_SYSTEM_FOREGROUND,
+    MONITORINFO, MONITOR_DEFAULTTONEAREST, MSG, OBJID_WINDOW, WINEVENT_OUTOFCONTEXT,
+    WINEVENT_SKIPOWNPROCESS,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Foreground {
+    // file name of the program, e.g. "Code.exe"
+    pub exe: String,
+    pub title: String,
+    // covers the whole monitor it is on
+    pub fullscreen: bool,
+}
+
+static APP: OnceLock<tauri::AppHandle> = OnceLock::new();
+static CURRENT: Mutex<Option<Foreground>> = Mutex::new(None);
+
+// The window in the foreground right now; None before the watcher has seen one
+pub fn current() -> Option<Foreground> {
+    CURRENT.lock().unwrap().clone()
+}
+
+fn exe_name(hwnd: HWND) -> String {
+    let mut pid: DWORD = 0;
+    unsafe { GetWindowThreadProcessId(hwnd, &mut pid) };
+    if pid == 0 {
+        return String::new();
+    }
+    let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, pid) };
+    if process.is_null() {
+        return String::new();
+    }
+    let mut buf = [0u16; 1024];
+    let mut len = buf.len() as DWORD;
+    let ok = unsafe { QueryFullProcessImageNameW(process, 0, buf.as_mut_ptr(), &mut len) };
+    unsafe { CloseHandle(process) };
+    if ok == 0 {
+        return String::new();
+    }
+    let path = String::from_utf16_lossy(&buf[..len as usize]);
+    path.rsplit(['\\', '/']).next().unwrap_or_default().to_string()
+}
+
+fn title(hwnd: HWND) -> String {
+    let mut buf = [0u16; 512];
+    let len = unsafe { GetWindowTextW(hwnd, buf.as_mut_ptr(), buf.len() as i32) };
+    String::from_utf16_lossy(&buf[..len.max(0) as usize])
+}
+
+fn is_fullscreen(hwnd: HWND) -> bool {
+    // the desktop and shell windows cover the screen too
+    if hwnd == unsafe { GetDesktopWindow() } || hwnd == unsafe { GetShellWindow() } {
+        return false;
+    }
+    let mut rect: RECT = unsafe { std::mem::zeroed() };
+    if unsafe { GetWindowRect(hwnd, &mut rect) } == 0 {
+        return false;
+    }
+    let monitor = unsafe { MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST) };
+    let mut info: MONITORINFO = unsafe { std::mem::zeroed() };
+    info.cbSize = std::mem::size_of::<MONITORINFO>() as DWORD;
+    if unsafe { GetMonitorInfoW(monitor, &mut info) } == 0 {
+        return false;
+    }
+    let screen = info.rcMonitor;
+    rect.left <= screen.left
+        && rect.top <= screen.top
+        && rect.right >= screen.right
+        && rect.bottom >= screen.bottom
+}
+
+fn check(hwnd: HWND) {
+    if hwnd.is_null() {
+        return;
+    }
+    let now = Foreground {
+        exe: exe_name(hwnd),
+        title: title(hwnd),
+        fullscreen: is_fullscreen(hwnd),
+    };
+    let previous = {
+        let mut current = CURRENT.lock().unwrap();
+        if current.as_ref() == Some(&now) {
+            return;
+        }
+        current.replace(now.clone())
+    };
+    if let Some(app) = APP.get() {
+        crate::game_mode::on_change(app, &now);
+        crate::app_triggers::on_change(app, previous.as_ref(), &now);
+    }
+}
+
+unsafe extern "system" fn on_event(
+    _hook: HWINEVENTHOOK,
+    event: DWORD,
+    hwnd: HWND,
+    id_object: LONG,
+    _id_child: LONG,
+    _thread: DWORD,
+    _time: DWORD,
+) {
+    // location changes fire for every window and caret; only the foreground window's
+    // own moves matter, since that is how a window goes fullscreen
+    if event == EVENT_OBJECT_LOCATIONCHANGE
+        && (id_object != OBJID_WINDOW || hwnd != GetForegroundWindow())
+    {
+        return;
+    }
+    check(hwnd);
+}
+
+// Called once at startup
+pub fn init(app: &tauri::AppHandle) {
+    if APP.set(app.clone()).is_err() {
+        return;
+    }
+    std::thread::spawn(|| unsafe {
+        let flags = WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS;
+        for event in [EVENT_SYSTEM_FOREGROUND, EVENT_OBJECT_LOCATIONCHANGE] {
+            let hook =
+                SetWinEventHook(event, event, std::ptr::null_mut(), Some(on_event), 0, 0, flags);
+            if hook.is_null() {
+                eprintln!("[WARN] Failed to watch the foreground window (event {:#x})", event);
+            }
+        }
+        check(GetForegroundWindow());
+        let mut msg: MSG = std::mem::zeroed();
+        while GetMessageW(&mut msg, std::ptr::null_mut(), 0, 0) > 0 {
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    });
+}
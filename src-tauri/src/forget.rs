@@ -0,0 +1,111 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::confirm;
+use crate::JarvisState;
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::Manager;
+
+// what a "forget" request wipes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForgetScope {
+    // context of the active conversation; the chat log itself is kept
+    Conversation,
+    // all long-term memories about the user
+    Memories,
+}
+
+static PENDING: Mutex<Option<(ForgetScope, Instant)>> = Mutex::new(None);
+
+// phrases live in triggers.json; "forget that" on its own is a single-memory
+// command, see memory::match_forget_command
+pub fn match_request(app: &tauri::AppHandle, prompt: &str, language: &str) -> Option<ForgetScope> {
+    let triggers = crate::triggers::load(app);
+    // checked first: "forget everything about me" also contains "forget everything"
+    if triggers.forget_memories.matches(prompt, language) {
+        return Some(ForgetScope::Memories);
+    }
+    triggers
+        .forget_conversation
+        .matches(prompt, language)
+        .then_some(ForgetScope::Conversation)
+}
+
+fn clear(app: &tauri::AppHandle, scope: ForgetScope) -> String {
+    match scope {
+        ForgetScope::Conversation => {
+            crate::intent::forget();
+            let active = app
+                .state::<JarvisState>()
+                .active_conversation
+                .lock()
+                .unwrap()
+                .clone();
+            let Some(fname) = active else {
+                return "Okay, I've cleared what we were talking about.".to_string();
+            };
+            let now = chrono::Utc::now().timestamp_millis();
+            match crate::conversation_settings::update(app, &fname, |s| {
+                s.context_cleared_at = Some(now)
+            }) {
+                Ok(_) => "Okay, I've forgotten this conversation.".to_string(),
+                Err(e) => {
+                    eprintln!("[ERROR] Failed to clear conversation context: {}", e);
+                    "Sorry, I couldn't clear this conversation.".to_string()
+                }
+            }
+        }
+        ForgetScope::Memories => match crate::memory::clear_all(app) {
+            Ok(0) => "I don't have anything remembered about you.".to_string(),
+            Ok(_) => "Okay, I've forgotten everything I knew about you.".to_string(),
+            Err(e) => {
+                eprintln!("[ERROR] Failed to clear memories: {}", e);
+                "Sorry, I couldn't clear my memory.".to_string()
+            }
+        },
+    }
+}
+
+// Two-step forget: a request asks for confirmation, the next reply carries it out or
+// cancels it. Returns the reply to show and speak when the prompt was handled here.
+pub fn handle(app: &tauri::AppHandle, prompt: &str, language: &str) -> Option<String> {
+    let pending = PENDING.lock().unwrap().take();
+    if let Some((scope, asked_at)) = pending {
+        if asked_at.elapsed() <= confirm::CONFIRM_WINDOW {
+            if confirm::is_yes(prompt) {
+                return Some(clear(app, scope));
+            }
+            if confirm::is_no(prompt) {
+                return Some("Okay, I won't forget anything.".to_string());
+            }
+        }
+        // anything else drops the pending request and is handled normally
+    }
+
+    let scope = match_request(app, prompt, language)?;
+    *PENDING.lock().unwrap() = Some((scope, Instant::now()));
+    Some(match scope {
+        ForgetScope::Conversation => {
+            "Do you want me to forget this conversation? Say yes to confirm.".to_string()
+        }
+        ForgetScope::Memories => {
+            "Do you want me to forget everything I know about you? Say yes to confirm."
+                .to_string()
+        }
+    })
+}
@@ -0,0 +1,168 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Game mode: while a game runs fullscreen, Jarvis frees the Whisper model and lowers
+// the priority of the wake-word thread so the game gets the memory and CPU. The wake
+// word still works; the first command after that waits for Whisper to load again,
+// which otherwise happens in the background as soon as the game is closed.
+
+use crate::events::{self, GameMode};
+use crate::foreground::Foreground;
+use crate::thread_priority::{self, AudioThread, Priority};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use winapi::um::shellapi::{SHQueryUserNotificationState, QUNS_RUNNING_D3D_FULL_SCREEN};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct GameModeConfig {
+    pub enabled: bool,
+    // also count borderless fullscreen windows, which many games use, but so do
+    // fullscreen videos and presentations
+    pub include_borderless: bool,
+    // programs never treated as games, e.g. "vlc.exe"
+    pub ignore_apps: Vec<String>,
+    pub unload_whisper: bool,
+    pub lower_priority: bool,
+}
+
+impl Default for GameModeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            include_borderless: false,
+            ignore_apps: Vec::new(),
+            unload_whisper: true,
+            lower_priority: true,
+        }
+    }
+}
+
+static STATE: Mutex<Option<GameMode>> = Mutex::new(None);
+// read by the wake-word loop on every frame, so kept apart from STATE
+static LOW_PRIORITY: AtomicBool = AtomicBool::new(false);
+
+pub fn is_active() -> bool {
+    STATE.lock().unwrap().as_ref().is_some_and(|s| s.active)
+}
+
+fn exclusive_fullscreen() -> bool {
+    let mut state = 0;
+    let ok = unsafe { SHQueryUserNotificationState(&mut state) };
+    ok == 0 && state == QUNS_RUNNING_D3D_FULL_SCREEN
+}
+
+fn is_game(cfg: &GameModeConfig, window: &Foreground) -> bool {
+    let exe = window.exe.to_lowercase();
+    let ignored = cfg.ignore_apps.iter().any(|app| {
+        let app = app.trim().to_lowercase();
+        exe == app || exe.strip_suffix(".exe") == Some(app.as_str())
+    });
+    if ignored {
+        return false;
+    }
+    exclusive_fullscreen() || (cfg.include_borderless && window.fullscreen)
+}
+
+fn enter(app: &tauri::AppHandle, cfg: &GameModeConfig, window: &Foreground) {
+    println!("[INFO] Game mode on for {}", window.exe);
+    if cfg.unload_whisper {
+        crate::stt::unload_all();
+    }
+    LOW_PRIORITY.store(cfg.lower_priority, Ordering::Relaxed);
+    events::emit(app, GameMode::on(&window.exe));
+}
+
+fn leave(app: &tauri::AppHandle) {
+    println!("[INFO] Game mode off");
+    LOW_PRIORITY.store(false, Ordering::Relaxed);
+    crate::stt::reload_all();
+    events::emit(app, GameMode::default());
+}
+
+// Called by the foreground watcher whenever the foreground window changes
+pub fn on_change(app: &tauri::AppHandle, now: &Foreground) {
+    let cfg = match crate::cmd_load_config(app.clone()) {
+        Ok(cfg) => cfg.game_mode,
+        Err(_) => return,
+    };
+    let game = cfg.enabled && is_game(&cfg, now);
+    let was_active = {
+        let mut state = STATE.lock().unwrap();
+        let was = state.as_ref().is_some_and(|s| s.active);
+        if game == was {
+            return;
+        }
+        *state = Some(if game { GameMode::on(&now.exe) } else { GameMode::default() });
+        was
+    };
+    if was_active {
+        leave(app);
+    } else {
+        enter(app, &cfg, now);
+    }
+}
+
+// The voice loop unloads a freshly loaded model right away if a game is already running
+pub fn on_voice_loop_start(app: &tauri::AppHandle) {
+    let unload = crate::cmd_load_config(app.clone())
+        .map(|cfg| cfg.game_mode.unload_whisper)
+        .unwrap_or(false);
+    if unload && is_active() {
+        crate::stt::unload_all();
+    }
+}
+
+// Keeps the current thread's priority in step with game mode while it lives; outside
+// game mode the thread runs at its configured priority (see thread_priority.rs)
+pub struct PriorityGuard {
+    lowered: bool,
+}
+
+impl PriorityGuard {
+    pub fn start() -> Self {
+        let mut guard = Self { lowered: false };
+        guard.update();
+        guard
+    }
+
+    pub fn update(&mut self) {
+        let low = LOW_PRIORITY.load(Ordering::Relaxed);
+        if low != self.lowered {
+            if low {
+                thread_priority::set_current(Priority::BelowNormal);
+            } else {
+                thread_priority::apply(AudioThread::WakeWord);
+            }
+            self.lowered = low;
+        }
+    }
+}
+
+impl Drop for PriorityGuard {
+    fn drop(&mut self) {
+        if self.lowered {
+            thread_priority::apply(AudioThread::WakeWord);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn cmd_get_game_mode() -> GameMode {
+    STATE.lock().unwrap().clone().unwrap_or_default()
+}
@@ -19,71 +19,242 @@ use crate::audio_input::{next_audio_frame, SAMPLE_RATE};
 use crate::models::AppContext;
 use crate::utils::convert_i16_to_f32;
 use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::io::Write;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use ts_rs::TS;
 use whisper_rs::{FullParams, SamplingStrategy, WhisperContext};
 
-// waits for the wake word to be detected by Porcupine
-pub fn wait_for_wakeword(app: &AppContext, is_running: &Arc<AtomicBool>) -> Result<()> {
+// guards against Porcupine retriggering on the tail of TTS playback or a cough
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct WakeConfig {
+    // detections within this window after listening resumes are ignored
+    pub cooldown_ms: u64,
+    // amount of fresh audio that must be captured before a detection counts
+    pub min_fresh_audio_ms: u64,
+    pub power: PowerProfile,
+}
+
+impl Default for WakeConfig {
+    fn default() -> Self {
+        Self {
+            cooldown_ms: 1500,
+            min_fresh_audio_ms: 300,
+            power: PowerProfile::Normal,
+        }
+    }
+}
+
+// How wake-word listening trades reaction time for CPU use
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerProfile {
+    Normal,
+    // wakes up a few times a second and checks the audio collected since in one go,
+    // which notices the wake word about a quarter of a second later
+    LowPower,
+}
+
+impl PowerProfile {
+    // wake-word frames handed to Porcupine per wake-up
+    fn batch_frames(self) -> usize {
+        match self {
+            PowerProfile::Normal => 1,
+            PowerProfile::LowPower => 8,
+        }
+    }
+}
+
+// What ended the wait for the wake word
+pub enum Wake {
+    WakeWord,
+    // a typed command handed to the voice loop (see run_jarvis::cmd_simulate_voice_command)
+    Simulated(String),
+}
+
+// waits for the wake word to be detected by Porcupine, or for a simulated command
+pub fn wait_for_wakeword(app: &AppContext, is_running: &Arc<AtomicBool>) -> Result<Wake> {
     println!("[DEBUG] Entered wait_for_wakeword");
     let frame_length_wwd = app.config.frame_length_wwd;
+    let batch = app.config.wake.power.batch_frames();
     let mut frame_count = 0;
 
+    // Anything buffered while we were recording/processing/speaking is stale
+    if let Ok(mut buf) = app.audio_buffer.lock() {
+        buf.clear();
+    }
+    let listening_since = Instant::now();
+    let cooldown = Duration::from_millis(app.config.wake.cooldown_ms);
+    let min_fresh_samples = (SAMPLE_RATE as u64 * app.config.wake.min_fresh_audio_ms / 1000) as usize;
+    let mut fresh_samples = 0usize;
+    // lowered while a game runs fullscreen
+    let mut priority = crate::game_mode::PriorityGuard::start();
+
     loop {
+        priority.update();
         // Check if we should stop every 100 frames (about 3 seconds at 30ms frame duration)
-        if frame_count % 100 == 0 {
-            if !is_running.load(Ordering::Relaxed) {
-                println!("[DEBUG] Wake word detection stopped by user");
-                return Err(anyhow!("Wake word detection stopped"));
-            }
+        if frame_count % 100 < batch && !is_running.load(Ordering::Relaxed) {
+            println!("[DEBUG] Wake word detection stopped by user");
+            return Err(anyhow!("Wake word detection stopped"));
+        }
+        if let Some(text) = crate::run_jarvis::take_simulated() {
+            return Ok(Wake::Simulated(text));
         }
 
-        let frame = next_audio_frame(app.audio_buffer.clone(), frame_length_wwd)?;
-        match app.porcupine.process(&frame) {
-            Ok(keyword_index) if keyword_index >= 0 => break,
-            Ok(_) => {
-                frame_count += 1;
-                continue;
-            }
-            Err(e) => {
-                return Err(anyhow!("Porcupine process error: {:?}", e));
+        // sleeps until the whole batch has been captured
+        let samples = next_audio_frame(app.audio_buffer.clone(), frame_length_wwd * batch)?;
+        for (i, frame) in samples.chunks_exact(frame_length_wwd).enumerate() {
+            frame_count += 1;
+            fresh_samples += frame.len();
+            match app.pipeline.wake_word.detect(frame) {
+                Ok(true) => {
+                    if listening_since.elapsed() < cooldown || fresh_samples < min_fresh_samples {
+                        println!("[DEBUG] Ignoring wake word detection during cooldown");
+                        continue;
+                    }
+                    // the rest of the batch is the start of the command
+                    if let Ok(mut buf) = app.audio_buffer.lock() {
+                        buf.unread(&samples[(i + 1) * frame_length_wwd..]);
+                    }
+                    println!("[DEBUG] Wakeword detected");
+                    return Ok(Wake::WakeWord);
+                }
+                Ok(false) => {}
+                Err(e) => return Err(e),
             }
         }
     }
-    println!("[DEBUG] Wakeword detected");
-    Ok(())
+}
+
+pub const MIN_SILENCE_THRESHOLD_MS: usize = 300;
+
+// "smart" endpointing: when silence ends an utterance that sounds unfinished
+// (trailing "and", comma, ...), keep listening once more before finalising
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct EndpointingConfig {
+    pub smart: bool,
+    pub extension_ms: usize,
+}
+
+impl Default for EndpointingConfig {
+    fn default() -> Self {
+        Self {
+            smart: false,
+            extension_ms: 1500,
+        }
+    }
+}
+
+const TRAILING_CONTINUATIONS: &[&str] = &[
+    // en
+    "and", "or", "but", "so", "because", "then", "with", "to", "of", "for", "the", "a", "an",
+    "like", "um", "uh", "if", "that", "which",
+    // de / fr / es
+    "und", "oder", "aber", "weil", "dass", "et", "ou", "mais", "parce", "y", "o", "pero", "que",
+];
+
+// heuristically decides whether a partial transcript stops mid-sentence
+pub fn utterance_seems_unfinished(text: &str) -> bool {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return false;
+    }
+    if trimmed.ends_with(',') || trimmed.ends_with("...") || trimmed.ends_with('…') {
+        return true;
+    }
+    if trimmed.ends_with(['.', '?', '!']) {
+        return false;
+    }
+    let last_word = trimmed
+        .split_whitespace()
+        .last()
+        .unwrap_or_default()
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase();
+    TRAILING_CONTINUATIONS.contains(&last_word.as_str())
+}
+
+// Whisper needs >= ~1000 ms of audio; pad with silence to ~1.2 s to be safe
+pub fn pad_for_whisper(samples: &[i16]) -> Vec<i16> {
+    let min_samples = (SAMPLE_RATE * 12) / 10;
+    let mut padded = samples.to_vec();
+    if padded.len() < min_samples {
+        padded.resize(min_samples, 0);
+    }
+    padded
+}
+
+// per-frame VAD decision reported while recording, for visualising detection behaviour
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct VadFrame {
+    #[serde(rename = "frameIndex")]
+    #[ts(type = "number")]
+    pub frame_index: usize,
+    pub speech: bool,
+    pub speaking: bool,
+    pub energy: f32,
+    #[serde(rename = "silentFrames")]
+    pub silent_frames: i32,
+    // transcript so far, set on the frame where a pause was checked for an unfinished sentence
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub partial: Option<String>,
+}
+
+// RMS energy of a frame, normalised to 0.0..=1.0
+fn frame_energy(frame: &[i16]) -> f32 {
+    if frame.is_empty() {
+        return 0.0;
+    }
+    let sum: f64 = frame.iter().map(|&s| (s as f64) * (s as f64)).sum();
+    ((sum / frame.len() as f64).sqrt() / 32768.0) as f32
 }
 
 // records a segment of audio until the user stops speaking
-pub fn record_command(app: &AppContext, is_running: &Arc<AtomicBool>) -> Result<Vec<i16>> {
+pub fn record_command<F>(
+    app: &AppContext,
+    is_running: &Arc<AtomicBool>,
+    mut on_frame: F,
+) -> Result<Vec<i16>>
+where
+    F: FnMut(VadFrame),
+{
     println!("[DEBUG] Entered record_command");
     let frame_length_vad = (SAMPLE_RATE / 1000) * app.config.frame_duration_ms;
     let speech_trigger_frames = app.config.speech_trigger_frames;
-    // Use ceil for threshold frames and enforce a sensible minimum (e.g., 5 frames)
+    // Use ceil for threshold frames; anything below ~300 ms cuts people off mid-sentence
+    let silence_threshold_ms = app.config.silence_threshold_ms.max(MIN_SILENCE_THRESHOLD_MS);
     let silence_threshold_frames = {
-        let frames = ((app.config.silence_threshold_seconds as f32)
-            * (1000.0 / app.config.frame_duration_ms as f32))
-            .ceil() as i32;
-        frames.max(5)
+        let frames = ((silence_threshold_ms as f32) / app.config.frame_duration_ms as f32).ceil()
+            as i32;
+        frames.max(1)
     };
 
+    let extension_frames = ((app.config.endpointing.extension_ms as f32)
+        / app.config.frame_duration_ms as f32)
+        .ceil() as i32;
+    let mut extended = false;
+    let mut partial_transcript: Option<String> = None;
+
     let mut is_speaking = false;
     let mut silent_frames = 0;
     let mut speech_frames = 0;
     let mut speech_segment = Vec::new();
     let mut recent_frames: VecDeque<Vec<i16>> =
-        VecDeque::with_capacity(speech_trigger_frames as usize);
+        VecDeque::with_capacity(speech_trigger_frames);
 
     let mut frame_count = 0;
     loop {
         // Check if we should stop every 100 frames
-        if frame_count % 100 == 0 {
-            if !is_running.load(Ordering::Relaxed) {
-                println!("[DEBUG] Recording stopped by user");
-                return Err(anyhow!("Recording stopped"));
-            }
+        if frame_count % 100 == 0 && !is_running.load(Ordering::Relaxed) {
+            println!("[DEBUG] Recording stopped by user");
+            return Err(anyhow!("Recording stopped"));
         }
 
         let frame = next_audio_frame(app.audio_buffer.clone(), frame_length_vad)?;
@@ -101,6 +272,9 @@ pub fn record_command(app: &AppContext, is_running: &Arc<AtomicBool>) -> Result<
         };
         drop(vad);
 
+        let energy = frame_energy(&frame);
+        let frame_index = frame_count;
+
         if is_speaking {
             speech_segment.extend_from_slice(&frame);
 
@@ -113,7 +287,37 @@ pub fn record_command(app: &AppContext, is_running: &Arc<AtomicBool>) -> Result<
                 print!("_");
                 let _ = std::io::stdout().flush();
 
+                if silent_frames >= silence_threshold_frames
+                    && app.config.endpointing.smart
+                    && !extended
+                {
+                    // Only extend once, and only if the partial transcript sounds cut off
+                    extended = true;
+                    let language = &app.config.whisper_language;
+                    let partial = app
+                        .pipeline
+                        .stt
+                        .as_ref()
+                        .and_then(|stt| stt.transcribe(pad_for_whisper(&speech_segment), language).ok())
+                        .unwrap_or_default();
+                    if utterance_seems_unfinished(&partial) {
+                        println!(
+                            "\n[DEBUG] Utterance sounds unfinished ({:?}), extending listening window",
+                            partial.trim()
+                        );
+                        silent_frames = silent_frames.min(silence_threshold_frames) - extension_frames;
+                    }
+                    partial_transcript = Some(partial);
+                }
                 if silent_frames >= silence_threshold_frames {
+                    on_frame(VadFrame {
+                        frame_index,
+                        speech: is_speech,
+                        speaking: false,
+                        energy,
+                        silent_frames,
+                        partial: partial_transcript.take(),
+                    });
                     println!("\nDetected end of speech.");
                     println!("[DEBUG] End of speech detected, returning segment");
                     return Ok(speech_segment);
@@ -122,7 +326,7 @@ pub fn record_command(app: &AppContext, is_running: &Arc<AtomicBool>) -> Result<
         } else if is_speech {
             speech_frames += 1;
             recent_frames.push_back(frame.clone());
-            if recent_frames.len() > speech_trigger_frames as usize {
+            if recent_frames.len() > speech_trigger_frames {
                 recent_frames.pop_front();
             }
 
@@ -144,15 +348,133 @@ pub fn record_command(app: &AppContext, is_running: &Arc<AtomicBool>) -> Result<
             speech_frames = 0;
             recent_frames.clear();
         }
+        on_frame(VadFrame {
+            frame_index,
+            speech: is_speech,
+            speaking: is_speaking,
+            energy,
+            silent_frames,
+            partial: partial_transcript.take(),
+        });
         frame_count += 1;
     }
 }
 
+// words that cut a spoken response short, checked while Jarvis is talking
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct InterruptConfig {
+    pub enabled: bool,
+    pub words: Vec<String>,
+}
+
+impl Default for InterruptConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            words: vec!["stop".into(), "cancel".into()],
+        }
+    }
+}
+
+const INTERRUPT_MIN_BURST_MS: usize = 150;
+const INTERRUPT_MAX_BURST_MS: usize = 2000;
+const INTERRUPT_END_SILENCE_MS: usize = 300;
+
+fn contains_interrupt_word(text: &str, words: &[String]) -> bool {
+    let lower = text.to_lowercase();
+    lower
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|w| !w.is_empty() && words.iter().any(|iw| iw.trim().eq_ignore_ascii_case(w)))
+}
+
+// Watches the mic during playback. Short bursts of speech are transcribed and checked
+// against the interrupt words; longer ones (talking over Jarvis, echo) are ignored.
+// Returns true when an interrupt word was heard, false once `finished()` reports the end.
+pub fn listen_for_interrupt<F>(app: &AppContext, cfg: &InterruptConfig, finished: F) -> bool
+where
+    F: Fn() -> bool,
+{
+    // without Whisper the words can't be recognised
+    let Some(stt) = app.pipeline.stt.as_ref() else {
+        return false;
+    };
+    let frame_ms = app.config.frame_duration_ms.max(10);
+    let frame_length = (SAMPLE_RATE / 1000) * frame_ms;
+    let end_silence_frames = INTERRUPT_END_SILENCE_MS.div_ceil(frame_ms);
+    let min_burst = (SAMPLE_RATE / 1000) * INTERRUPT_MIN_BURST_MS;
+    let max_burst = (SAMPLE_RATE / 1000) * INTERRUPT_MAX_BURST_MS;
+
+    if let Ok(mut buf) = app.audio_buffer.lock() {
+        buf.clear();
+    }
+    let mut burst: Vec<i16> = Vec::new();
+    let mut silent_frames = 0usize;
+    let mut too_long = false;
+
+    while !finished() {
+        // a short wait, so the end of playback is noticed quickly
+        let waited = app.audio_buffer.take_within(frame_length, Duration::from_millis(50));
+        let frame: Vec<i16> = match waited {
+            Ok(Some(frame)) => frame,
+            Ok(None) => continue,
+            Err(_) => return false,
+        };
+        let is_speech = match app.vad.lock() {
+            Ok(mut vad) => vad.is_voice_segment(&frame).unwrap_or(false),
+            Err(_) => return false,
+        };
+
+        if is_speech {
+            silent_frames = 0;
+            if !too_long {
+                burst.extend_from_slice(&frame);
+                if burst.len() > max_burst {
+                    too_long = true;
+                    burst.clear();
+                }
+            }
+            continue;
+        }
+
+        silent_frames += 1;
+        if silent_frames < end_silence_frames || (burst.is_empty() && !too_long) {
+            continue;
+        }
+        if !too_long && burst.len() >= min_burst {
+            // playback ending mid-transcription makes the result moot, so drop the job
+            let job = stt.submit(pad_for_whisper(&burst), &app.config.whisper_language);
+            let heard = match job.wait_or_abandon(&finished) {
+                Ok(Some(text)) => text,
+                Ok(None) => return false,
+                Err(_) => String::new(),
+            };
+            if contains_interrupt_word(&heard, &cfg.words) {
+                println!("[DEBUG] Interrupt word heard during playback: {:?}", heard.trim());
+                return true;
+            }
+        }
+        burst.clear();
+        too_long = false;
+    }
+    false
+}
+
 // transcribes the audio segment using Whisper
 pub fn transcribe(
     ctx: &WhisperContext,
     audio_data_i16: &[i16],
     whisper_language: &str,
+) -> Result<String> {
+    transcribe_cancellable(ctx, audio_data_i16, whisper_language, None)
+}
+
+// Like transcribe, but Whisper gives up between decoding steps once `cancel` is set
+pub fn transcribe_cancellable(
+    ctx: &WhisperContext,
+    audio_data_i16: &[i16],
+    whisper_language: &str,
+    cancel: Option<Arc<AtomicBool>>,
 ) -> Result<String> {
     println!("[DEBUG] Entered transcribe");
     let audio_data_f32 = convert_i16_to_f32(audio_data_i16);
@@ -163,10 +485,15 @@ pub fn transcribe(
     let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
     params.set_language(Some(whisper_language));
     params.set_initial_prompt("clipboard");
+    if let Some(flag) = cancel.clone() {
+        params.set_abort_callback_safe(move || flag.load(Ordering::Relaxed));
+    }
 
-    state
-        .full(params, &audio_data_f32[..])
-        .map_err(|e| anyhow!("Failed to run Whisper model: {}", e))?;
+    let run = state.full(params, &audio_data_f32[..]);
+    if cancel.map(|c| c.load(Ordering::Relaxed)).unwrap_or(false) {
+        return Err(anyhow!("Transcription cancelled"));
+    }
+    run.map_err(|e| anyhow!("Failed to run Whisper model: {}", e))?;
 
     let num_segments = state
         .full_n_segments()
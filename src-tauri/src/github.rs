@@ -0,0 +1,511 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// GitHub: "any new issues on my project?" reads out the issues opened since you last
+// asked, "what's the CI status of main" sums up the checks on the branch's latest
+// commit, and "comment on issue 42" takes the next thing you say as the comment and
+// posts it after a yes (see confirm.rs). The repos are the ones in `repos`; the first is
+// used when a question doesn't name one. When each repo was last asked about is kept in
+// github.json next to config.json.
+//
+// Needs a personal access token that can read issues and checks, and write issues for
+// comments (a fine-grained token with Issues: read and write, Checks: read, limited to
+// these repos, is enough). The token lives in the OS keychain (see keychain.rs); a token
+// put in `token` is moved there the next time the config is loaded or saved.
+
+use crate::confirm;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct GithubConfig {
+    // a new token to store; never written back to config.json
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub token: String,
+    // whether the keychain holds a token; clearing it removes the token
+    pub token_set: bool,
+    // "owner/name"
+    pub repos: Vec<String>,
+}
+
+const KEYCHAIN_NAME: &str = "github-token";
+
+// Moves a token given in the config into the keychain, or drops the stored one when
+// `token_set` was cleared since the last save; true when the config changed
+pub fn store_token(config: &mut GithubConfig, was_set: bool) -> bool {
+    let token = std::mem::take(&mut config.token);
+    if !token.trim().is_empty() {
+        if let Err(e) = crate::keychain::set(KEYCHAIN_NAME, token.trim()) {
+            eprintln!("[ERROR] Failed to store the GitHub token in the keychain: {}", e);
+            config.token = token;
+            return false;
+        }
+        config.token_set = true;
+        return true;
+    }
+    if was_set && !config.token_set {
+        if let Err(e) = crate::keychain::delete(KEYCHAIN_NAME) {
+            eprintln!("[WARN] Failed to remove the GitHub token from the keychain: {}", e);
+        }
+    }
+    false
+}
+
+// The token from the keychain; one still in the config (e.g. from an environment
+// override) wins
+fn token(config: &GithubConfig) -> Option<String> {
+    if !config.token.trim().is_empty() {
+        return Some(config.token.trim().to_string());
+    }
+    if !config.token_set {
+        return None;
+    }
+    crate::keychain::get(KEYCHAIN_NAME).filter(|t| !t.trim().is_empty())
+}
+
+// an issue in one of the repos, for "comment on that issue"
+#[derive(Debug, Clone)]
+struct IssueRef {
+    repo: String,
+    number: u64,
+}
+
+// "any new issues", "open issues on the jarvis repo"; not "I have new issues with my boss"
+static ISSUES: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:new|open|latest|recent)\s+(?:github\s+)?issues?",
+        r"(?:\s+(?:on|in|for|today|since)\b|\s*[?.!]?\s*$)",
+        r"|\bissues?\s+(?:on|in|for)\s+(?:the\s+|my\s+)?[\w.-]+\s+(?:repo|repository)\b",
+    ))
+    .expect("Failed to compile issues regex")
+});
+
+// "is the build passing", "CI status of main"; not "what's the status of my build order"
+static CI: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:ci|build|builds|checks|pipeline)\s+",
+        r"(?:status|passing|green|failing|broken|red)\b",
+        r"|\bstatus\s+of\s+(?:the\s+)?(?:ci|build|checks|pipeline)\b",
+        r"|\b(?:is|are)\s+(?:the\s+)?(?:ci|build|builds|checks|pipeline)\b",
+        r"(?:\s+(?:on|of|for)\s+[\w./-]+)?(?:\s+branch)?",
+        r"\s+(?:passing|green|failing|broken|red)\b",
+    ))
+    .expect("Failed to compile CI regex")
+});
+
+// "of main", "on the develop branch", "branch release/2.0"
+static BRANCH: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\bbranch\s+(?P<after>[\w./-]+)",
+        r"|\b(?:of|on|for)\s+(?:the\s+)?(?P<named>main|master|develop|dev|trunk)\b",
+        r"|(?P<before>[\w./-]+)\s+branch\b",
+    ))
+    .expect("Failed to compile branch regex")
+});
+
+// "comment on issue 42", "reply to that issue"
+static COMMENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:comment|reply|respond)\s+(?:on|to)\s+",
+        r"(?:(?:issue|pull request|pr)\s+(?:number\s+)?#?(?P<number>\d+)|(?:that|the last) issue)",
+    ))
+    .expect("Failed to compile comment regex")
+});
+
+// the issue last read out, for "comment on that issue"
+static LAST_ISSUE: Mutex<Option<IssueRef>> = Mutex::new(None);
+
+// the issue "comment on issue 42" is waiting to hear the comment for
+static DICTATING: Mutex<Option<(IssueRef, Instant)>> = Mutex::new(None);
+
+const API_URL: &str = "https://api.github.com";
+// how far back "new issues" looks the first time a repo is asked about
+const FIRST_LOOK_DAYS: i64 = 7;
+// issue titles read out per repo
+const MAX_READ: usize = 3;
+
+fn client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .user_agent(concat!("Jarvis/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| anyhow!("Failed to build HTTP client: {e}"))
+}
+
+fn request(
+    client: &reqwest::Client,
+    method: reqwest::Method,
+    path: &str,
+    config: &GithubConfig,
+) -> reqwest::RequestBuilder {
+    client
+        .request(method, format!("{}{}", API_URL, path))
+        .bearer_auth(token(config).unwrap_or_default())
+        .header("Accept", "application/vnd.github+json")
+        .header("X-GitHub-Api-Version", "2022-11-28")
+}
+
+async fn get(client: &reqwest::Client, path: &str, config: &GithubConfig) -> Result<Value> {
+    let resp = request(client, reqwest::Method::GET, path, config)
+        .send()
+        .await?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(anyhow!("GitHub returned {} for {}", status, path));
+    }
+    Ok(resp.json().await?)
+}
+
+fn seen_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join("github.json"))
+}
+
+// repo -> when its issues were last read out, RFC 3339
+fn load_seen(app: &tauri::AppHandle) -> HashMap<String, String> {
+    seen_path(app)
+        .ok()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_seen(app: &tauri::AppHandle, seen: &HashMap<String, String>) -> Result<(), String> {
+    let s = serde_json::to_string_pretty(seen).map_err(|e| e.to_string())?;
+    crate::history::atomic_write(&seen_path(app)?, s.as_bytes()).map_err(|e| e.to_string())
+}
+
+// "Hav1ck/Jarvis" as "Jarvis"
+fn short_name(repo: &str) -> &str {
+    repo.rsplit('/').next().unwrap_or(repo)
+}
+
+// The repos the prompt names, or all of them
+fn mentioned<'a>(prompt: &str, repos: &'a [String]) -> Vec<&'a String> {
+    let lower = prompt.to_lowercase();
+    let named: Vec<&String> = repos
+        .iter()
+        .filter(|r| {
+            let name = short_name(r).to_lowercase();
+            lower.contains(&name) || lower.contains(&name.replace(['-', '_'], " "))
+        })
+        .collect();
+    if named.is_empty() {
+        repos.iter().collect()
+    } else {
+        named
+    }
+}
+
+async fn new_issues(
+    app: &tauri::AppHandle,
+    client: &reqwest::Client,
+    repos: &[&String],
+    config: &GithubConfig,
+) -> Result<String> {
+    let mut seen = load_seen(app);
+    let now = Utc::now();
+    let mut lines = Vec::new();
+    let mut last = None;
+    for repo in repos {
+        let since = seen
+            .get(repo.as_str())
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or(now - ChronoDuration::days(FIRST_LOOK_DAYS));
+        let path = format!(
+            "/repos/{}/issues?state=open&sort=created&direction=desc&per_page=50",
+            repo
+        );
+        let data = get(client, &path, config).await?;
+        // the issues endpoint lists pull requests too
+        let fresh: Vec<&Value> = data
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter(|i| i.get("pull_request").is_none())
+            .filter(|i| {
+                i["created_at"]
+                    .as_str()
+                    .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                    .is_some_and(|t| t.with_timezone(&Utc) > since)
+            })
+            .collect();
+        seen.insert(repo.to_string(), now.to_rfc3339());
+        let Some(newest) = fresh.first() else {
+            continue;
+        };
+        if last.is_none() {
+            last = Some(IssueRef {
+                repo: repo.to_string(),
+                number: newest["number"].as_u64().unwrap_or_default(),
+            });
+        }
+        let titles: Vec<String> = fresh
+            .iter()
+            .take(MAX_READ)
+            .map(|i| {
+                format!(
+                    "number {}, {}, from {}",
+                    i["number"].as_u64().unwrap_or_default(),
+                    i["title"]
+                        .as_str()
+                        .unwrap_or("untitled")
+                        .trim_end_matches('.'),
+                    i["user"]["login"].as_str().unwrap_or("someone")
+                )
+            })
+            .collect();
+        let count = match fresh.len() {
+            1 => "one new issue".to_string(),
+            n => format!("{} new issues", n),
+        };
+        let mut line = format!("{} has {}: {}", short_name(repo), count, titles.join("; "));
+        if fresh.len() > MAX_READ {
+            line.push_str(&format!(", and {} more", fresh.len() - MAX_READ));
+        }
+        lines.push(format!("{}.", line));
+    }
+    if let Err(e) = save_seen(app, &seen) {
+        eprintln!("[ERROR] Failed to save GitHub state: {}", e);
+    }
+    if last.is_some() {
+        *LAST_ISSUE.lock().unwrap() = last;
+    }
+    if lines.is_empty() {
+        return Ok(match repos {
+            [only] => format!("No new issues on {}.", short_name(only)),
+            _ => "No new issues on your repos.".to_string(),
+        });
+    }
+    Ok(lines.join(" "))
+}
+
+fn branch_in(prompt: &str) -> Option<String> {
+    let captures = BRANCH.captures(prompt)?;
+    let branch = captures
+        .name("after")
+        .or(captures.name("named"))
+        .or(captures.name("before"))?
+        .as_str();
+    (!matches!(
+        branch.to_lowercase().as_str(),
+        "the" | "this" | "that" | "my" | "which"
+    ))
+    .then(|| branch.to_string())
+}
+
+async fn ci_status(
+    client: &reqwest::Client,
+    repo: &str,
+    branch: Option<String>,
+    config: &GithubConfig,
+) -> Result<String> {
+    let branch = match branch {
+        Some(branch) => branch,
+        None => get(client, &format!("/repos/{}", repo), config).await?["default_branch"]
+            .as_str()
+            .unwrap_or("main")
+            .to_string(),
+    };
+    let path = format!("/repos/{}/commits/{}/check-runs?per_page=100", repo, branch);
+    let data = get(client, &path, config).await?;
+    let runs = data["check_runs"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let subject = format!("{} on {}", branch, short_name(repo));
+    if runs.is_empty() {
+        return Ok(format!("{} has no checks on its latest commit.", subject));
+    }
+    let name = |r: &Value| r["name"].as_str().unwrap_or("a check").to_string();
+    let running = runs
+        .iter()
+        .filter(|r| r["status"].as_str() != Some("completed"))
+        .count();
+    let failed: Vec<String> = runs
+        .iter()
+        .filter(|r| {
+            matches!(
+                r["conclusion"].as_str(),
+                Some("failure" | "timed_out" | "action_required" | "cancelled")
+            )
+        })
+        .map(name)
+        .collect();
+    Ok(match (failed.as_slice(), running) {
+        ([], 0) => format!(
+            "{} is passing, all {} checks succeeded.",
+            subject,
+            runs.len()
+        ),
+        ([], n) => format!(
+            "{} is still running {} of {} checks, none have failed so far.",
+            subject,
+            n,
+            runs.len()
+        ),
+        ([one], _) => format!("{} is failing: {} failed.", subject, one),
+        (many, _) => format!(
+            "{} is failing: {} checks failed, including {}.",
+            subject,
+            many.len(),
+            many[..2].join(" and ")
+        ),
+    })
+}
+
+async fn send_comment(issue: &IssueRef, text: &str, config: &GithubConfig) -> Result<()> {
+    let path = format!("/repos/{}/issues/{}/comments", issue.repo, issue.number);
+    let resp = request(&client()?, reqwest::Method::POST, &path, config)
+        .json(&json!({ "body": text }))
+        .send()
+        .await?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(anyhow!("GitHub returned {}", status));
+    }
+    Ok(())
+}
+
+async fn post_comment(issue: IssueRef, text: String, config: GithubConfig) -> String {
+    match send_comment(&issue, &text, &config).await {
+        Ok(()) => format!(
+            "Posted your comment on {} issue {}.",
+            short_name(&issue.repo),
+            issue.number
+        ),
+        Err(e) => {
+            eprintln!("[ERROR] Failed to post GitHub comment: {}", e);
+            "Sorry, I couldn't post the comment.".to_string()
+        }
+    }
+}
+
+fn ask_to_post(issue: IssueRef, text: &str, config: &GithubConfig) -> String {
+    let question = format!(
+        "I'll post this on {} issue {}: \"{}\"",
+        short_name(&issue.repo),
+        issue.number,
+        text
+    );
+    confirm::ask(
+        "post the comment",
+        &question,
+        post_comment(issue, text.to_string(), config.clone()),
+    )
+}
+
+// Whether "comment on issue 42" is waiting for the comment itself
+pub fn is_dictating() -> bool {
+    DICTATING
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|(_, asked_at)| asked_at.elapsed() <= confirm::CONFIRM_WINDOW)
+}
+
+// "comment on issue 42" with the comment right after it, or waiting for it
+fn comment(prompt: &str, config: &GithubConfig) -> Option<String> {
+    let captures = COMMENT.captures(prompt)?;
+    let issue = match captures.name("number") {
+        Some(number) => {
+            let repos = mentioned(prompt, &config.repos);
+            IssueRef {
+                repo: repos.first()?.to_string(),
+                number: number.as_str().parse().ok()?,
+            }
+        }
+        None => match LAST_ISSUE.lock().unwrap().clone() {
+            Some(issue) => issue,
+            None => return Some("Which issue? Tell me its number.".to_string()),
+        },
+    };
+    // "... saying the fix is in 2.1", "...: the fix is in 2.1"
+    let rest = prompt[captures.get(0)?.end()..].trim_start_matches([',', ':', ' ']);
+    let text = ["saying ", "that ", "with "]
+        .iter()
+        .find_map(|p| rest.strip_prefix(p))
+        .unwrap_or(rest)
+        .trim();
+    if text.split_whitespace().count() >= 2 {
+        return Some(ask_to_post(issue, text, config));
+    }
+    let reply = format!(
+        "What should the comment on {} issue {} say?",
+        short_name(&issue.repo),
+        issue.number
+    );
+    *DICTATING.lock().unwrap() = Some((issue, Instant::now()));
+    Some(reply)
+}
+
+pub async fn answer(prompt: &str, app: &tauri::AppHandle, config: &GithubConfig) -> Option<String> {
+    // the comment "comment on issue 42" asked for
+    let dictating = DICTATING.lock().unwrap().take();
+    if let Some((issue, asked_at)) = dictating {
+        if asked_at.elapsed() <= confirm::CONFIRM_WINDOW {
+            if confirm::is_no(prompt) {
+                return Some("Okay, no comment.".to_string());
+            }
+            return Some(ask_to_post(issue, prompt.trim(), config));
+        }
+    }
+
+    let asks_issues = ISSUES.is_match(prompt);
+    let asks_ci = CI.is_match(prompt);
+    let asks_comment = COMMENT.is_match(prompt);
+    if !asks_issues && !asks_ci && !asks_comment {
+        return None;
+    }
+    if token(config).is_none() {
+        return Some("Add a GitHub token in the settings so I can check your repos.".to_string());
+    }
+    if config.repos.is_empty() {
+        return Some("Tell me which repos to watch under GitHub in the settings.".to_string());
+    }
+    if asks_comment {
+        return comment(prompt, config);
+    }
+
+    let repos = mentioned(prompt, &config.repos);
+    let client = match client() {
+        Ok(client) => client,
+        Err(e) => return Some(format!("Sorry, I couldn't reach GitHub. {}.", e)),
+    };
+    let result = if asks_ci {
+        ci_status(&client, repos[0], branch_in(prompt), config).await
+    } else {
+        new_issues(app, &client, &repos, config).await
+    };
+    Some(result.unwrap_or_else(|e| {
+        eprintln!("[ERROR] GitHub request failed: {}", e);
+        "Sorry, I couldn't get that from GitHub.".to_string()
+    }))
+}
@@ -0,0 +1,421 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Conversation files on disk. A conversation is either a pretty-printed JSON array
+// (`.json`, the default) or one turn per line (`.jsonl`), which can be appended to
+// without rewriting the file.
+
+use crate::TurnDto;
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+// one writer at a time, so two turns landing together can't interleave
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+// modification time of each file after our last write, so the history watcher can
+// tell our own changes from ones made by a sync tool
+static OWN_WRITES: Mutex<Option<HashMap<PathBuf, SystemTime>>> = Mutex::new(None);
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn record_write(path: &Path) {
+    if let Some(time) = modified(path) {
+        let mut own = OWN_WRITES.lock().unwrap_or_else(|e| e.into_inner());
+        own.get_or_insert_with(HashMap::new).insert(path.to_path_buf(), time);
+    }
+}
+
+// True when the file is exactly as Jarvis last wrote it
+pub fn written_by_us(path: &Path) -> bool {
+    let own = OWN_WRITES.lock().unwrap_or_else(|e| e.into_inner());
+    match (own.as_ref().and_then(|m| m.get(path)), modified(path)) {
+        (Some(ours), Some(now)) => *ours == now,
+        _ => false,
+    }
+}
+
+fn is_jsonl(path: &Path) -> bool {
+    path.extension()
+        .map(|e| e.eq_ignore_ascii_case("jsonl"))
+        .unwrap_or(false)
+}
+
+// "<title> - <ts>.jsonl" -> ("<title> - <ts>", "jsonl")
+pub fn split_extension(filename: &str) -> (&str, &str) {
+    for ext in [".jsonl", ".json"] {
+        let at = filename.len().saturating_sub(ext.len());
+        if at > 0 && filename.get(at..).is_some_and(|e| e.eq_ignore_ascii_case(ext)) {
+            return (&filename[..at], &filename[at + 1..]);
+        }
+    }
+    (filename, "json")
+}
+
+// Writes next to the target and renames over it, so a crash leaves either the old
+// file or the new one, never half of each.
+pub fn atomic_write(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let tmp = path.with_file_name(format!(".{}.tmp", name));
+    {
+        let mut f = std::fs::File::create(&tmp)?;
+        f.write_all(contents)?;
+        f.sync_all()?;
+    }
+    std::fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&tmp);
+    })?;
+    record_write(path);
+    Ok(())
+}
+
+// Reads as many turns as the text holds. The bool is true when something had to be
+// skipped: trailing garbage, a garbled turn, a cut-off array or a torn last line.
+pub fn parse_turns(s: &str, jsonl: bool) -> (Vec<TurnDto>, bool) {
+    if s.trim().is_empty() {
+        return (Vec::new(), false);
+    }
+    if jsonl {
+        let mut damaged = false;
+        let turns = s
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| {
+                let turn = serde_json::from_str::<TurnDto>(l).ok();
+                damaged |= turn.is_none();
+                turn
+            })
+            .collect();
+        return (turns, damaged);
+    }
+
+    if let Ok(turns) = serde_json::from_str::<Vec<TurnDto>>(s) {
+        return (turns, false);
+    }
+    // a complete array followed by junk
+    if let Some(Ok(turns)) = serde_json::Deserializer::from_str(s)
+        .into_iter::<Vec<TurnDto>>()
+        .next()
+    {
+        return (turns, true);
+    }
+    // a damaged array: keep every turn that parses, skipping over the ones that don't
+    let mut turns = Vec::new();
+    let Some(mut rest) = s.trim_start().strip_prefix('[') else {
+        return (turns, true);
+    };
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if rest.is_empty() || rest.starts_with(']') {
+            break;
+        }
+        let mut stream = serde_json::Deserializer::from_str(rest).into_iter::<serde_json::Value>();
+        match stream.next() {
+            // well-formed JSON that isn't a turn is dropped
+            Some(Ok(value)) => {
+                turns.extend(serde_json::from_value::<TurnDto>(value).ok());
+                rest = &rest[stream.byte_offset()..];
+            }
+            // garbled or cut off: pick up again at the next object, if there is one
+            _ => {
+                let first = rest.chars().next().map_or(0, char::len_utf8);
+                match rest[first..].find('{') {
+                    Some(at) => rest = &rest[first + at..],
+                    None => break,
+                }
+            }
+        }
+    }
+    (turns, true)
+}
+
+fn serialize(turns: &[TurnDto], jsonl: bool) -> Result<String, String> {
+    if !jsonl {
+        return serde_json::to_string_pretty(turns).map_err(|e| e.to_string());
+    }
+    let mut out = String::new();
+    for turn in turns {
+        out.push_str(&serde_json::to_string(turn).map_err(|e| e.to_string())?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+// keeps the damaged original as `<name>.corrupt-<ts>`; caller holds WRITE_LOCK
+fn back_up_damaged(path: &Path, recovered: usize) -> Result<(), String> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let backup = path.with_file_name(format!(
+        "{}.corrupt-{}",
+        name,
+        chrono::Utc::now().format("%Y-%m-%d_%H-%M-%S")
+    ));
+    eprintln!(
+        "[WARN] Recovered {} turn(s) from damaged conversation {:?}; original kept at {:?}",
+        recovered, path, backup
+    );
+    std::fs::copy(path, &backup)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+// caller holds WRITE_LOCK
+fn read_locked(path: &Path) -> Result<(Vec<TurnDto>, bool), String> {
+    let s = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let (turns, damaged) = parse_turns(&s, is_jsonl(path));
+    if damaged {
+        back_up_damaged(path, turns.len())?;
+    }
+    Ok((turns, damaged))
+}
+
+// Loads a conversation. A damaged file is backed up and replaced by what could be
+// recovered, so the next write starts from clean data.
+pub fn load(path: &Path) -> Result<Vec<TurnDto>, String> {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let (turns, damaged) = read_locked(path)?;
+    if damaged {
+        let clean = serialize(&turns, is_jsonl(path))?;
+        atomic_write(path, clean.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    Ok(turns)
+}
+
+fn non_empty_lines(s: &str) -> impl Iterator<Item = &str> {
+    s.lines().filter(|l| !l.trim().is_empty())
+}
+
+// Number of turns. JSONL files are counted by line without parsing the turns.
+pub fn count(path: &Path) -> Result<usize, String> {
+    if !is_jsonl(path) {
+        return load(path).map(|turns| turns.len());
+    }
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let s = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    Ok(non_empty_lines(&s).count())
+}
+
+// Turns `offset..offset + limit`. For JSONL only those lines are parsed; a damaged
+// line among them falls back to a full load, which repairs the file.
+pub fn load_range(path: &Path, offset: usize, limit: usize) -> Result<Vec<TurnDto>, String> {
+    if is_jsonl(path) {
+        let s = {
+            let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            std::fs::read_to_string(path).map_err(|e| e.to_string())?
+        };
+        let page: Option<Vec<TurnDto>> = non_empty_lines(&s)
+            .skip(offset)
+            .take(limit)
+            .map(|l| serde_json::from_str(l).ok())
+            .collect();
+        if let Some(page) = page {
+            return Ok(page);
+        }
+    }
+    Ok(load(path)?.into_iter().skip(offset).take(limit).collect())
+}
+
+pub fn save(path: &Path, turns: &[TurnDto]) -> Result<(), String> {
+    let s = serialize(turns, is_jsonl(path))?;
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    atomic_write(path, s.as_bytes()).map_err(|e| e.to_string())
+}
+
+// JSONL files get one more line; JSON files are rewritten (atomically) with the turn added
+pub fn append(path: &Path, turn: &TurnDto) -> Result<(), String> {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if !is_jsonl(path) {
+        let mut turns = if path.exists() {
+            read_locked(path)?.0
+        } else {
+            Vec::new()
+        };
+        turns.push(turn.clone());
+        let s = serialize(&turns, false)?;
+        return atomic_write(path, s.as_bytes()).map_err(|e| e.to_string());
+    }
+    let line = serde_json::to_string(turn).map_err(|e| e.to_string())?;
+    let mut f = std::fs::OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+    // a torn last line must not swallow the new turn
+    let mut last = [0u8; 1];
+    let needs_newline = f.seek(SeekFrom::End(-1)).is_ok()
+        && f.read_exact(&mut last).is_ok()
+        && last[0] != b'\n';
+    let prefix = if needs_newline { "\n" } else { "" };
+    f.write_all(format!("{}{}\n", prefix, line).as_bytes())
+        .and_then(|_| f.sync_data())
+        .map_err(|e| e.to_string())?;
+    record_write(path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(role: &str, content: &str, created_at: i64) -> TurnDto {
+        TurnDto {
+            role: role.to_string(),
+            content: content.to_string(),
+            created_at,
+            meta: None,
+        }
+    }
+
+    fn contents(turns: &[TurnDto]) -> Vec<&str> {
+        turns.iter().map(|t| t.content.as_str()).collect()
+    }
+
+    fn three_turns() -> Vec<TurnDto> {
+        vec![
+            turn("user", "hi", 1),
+            turn("assistant", "hello {there}", 2),
+            turn("user", "bye", 3),
+        ]
+    }
+
+    #[test]
+    fn intact_files_are_not_damaged() {
+        for jsonl in [false, true] {
+            let s = serialize(&three_turns(), jsonl).unwrap();
+            let (turns, damaged) = parse_turns(&s, jsonl);
+            assert_eq!(contents(&turns), ["hi", "hello {there}", "bye"]);
+            assert!(!damaged);
+        }
+        assert_eq!(parse_turns("  \n", false).0.len(), 0);
+        assert!(!parse_turns("  \n", false).1);
+    }
+
+    #[test]
+    fn truncated_array_keeps_complete_turns() {
+        let s = serialize(&three_turns(), false).unwrap();
+        let cut = &s[..s.find("bye").unwrap()];
+        let (turns, damaged) = parse_turns(cut, false);
+        assert_eq!(contents(&turns), ["hi", "hello {there}"]);
+        assert!(damaged);
+    }
+
+    #[test]
+    fn truncated_turn_with_meta_is_dropped() {
+        let s = r#"[{"role":"user","content":"hi","createdAt":1},
+            {"role":"assistant","content":"a","createdAt":2,"meta":{"model":"x"}"#;
+        let (turns, damaged) = parse_turns(s, false);
+        assert_eq!(contents(&turns), ["hi"]);
+        assert!(damaged);
+    }
+
+    #[test]
+    fn garbled_turn_in_array_is_skipped() {
+        let s = r#"[
+  {"role":"user","content":"hi","createdAt":1},
+  {"role":"assistant","content":"broken {","createdAt":},
+  {"role":"user","content":"bye","createdAt":3}
+]"#;
+        let (turns, damaged) = parse_turns(s, false);
+        assert_eq!(contents(&turns), ["hi", "bye"]);
+        assert!(damaged);
+    }
+
+    #[test]
+    fn non_turn_values_in_array_are_skipped() {
+        let s = r#"[{"role":"user","content":"hi","createdAt":1}, 42, {"role":"user"},
+            {"role":"user","content":"bye","createdAt":3}]"#;
+        let (turns, damaged) = parse_turns(s, false);
+        assert_eq!(contents(&turns), ["hi", "bye"]);
+        assert!(damaged);
+    }
+
+    #[test]
+    fn trailing_garbage_after_array() {
+        let s = format!("{}\n\0\0garbage", serialize(&three_turns(), false).unwrap());
+        let (turns, damaged) = parse_turns(&s, false);
+        assert_eq!(turns.len(), 3);
+        assert!(damaged);
+    }
+
+    #[test]
+    fn not_an_array_recovers_nothing() {
+        let (turns, damaged) = parse_turns("\u{feff}not json at all", false);
+        assert!(turns.is_empty());
+        assert!(damaged);
+    }
+
+    #[test]
+    fn jsonl_skips_garbled_and_torn_lines() {
+        let s = concat!(
+            r#"{"role":"user","content":"hi","createdAt":1}"#,
+            "\n{\"role\":\"assi\u{fffd}\n",
+            r#"{"role":"user","content":"bye","createdAt":3}"#,
+            "\n{\"role\":\"assistant\",\"con",
+        );
+        let (turns, damaged) = parse_turns(s, true);
+        assert_eq!(contents(&turns), ["hi", "bye"]);
+        assert!(damaged);
+    }
+
+    #[test]
+    fn load_repairs_damaged_file_and_keeps_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["chat - 1.json", "chat - 2.jsonl"] {
+            let path = dir.path().join(name);
+            let jsonl = is_jsonl(&path);
+            let mut s = serialize(&three_turns(), jsonl).unwrap();
+            s.truncate(s.find("bye").unwrap());
+            std::fs::write(&path, &s).unwrap();
+
+            let turns = load(&path).unwrap();
+            assert_eq!(contents(&turns), ["hi", "hello {there}"]);
+
+            // the file now holds just the recovered turns
+            let repaired = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(repaired, serialize(&turns, jsonl).unwrap());
+            assert!(!parse_turns(&repaired, jsonl).1);
+
+            let backup = std::fs::read_dir(dir.path())
+                .unwrap()
+                .filter_map(|e| e.ok())
+                .find(|e| {
+                    let file = e.file_name().to_string_lossy().to_string();
+                    file.starts_with(&format!("{}.corrupt-", name))
+                })
+                .expect("damaged original should be backed up");
+            assert_eq!(std::fs::read_to_string(backup.path()).unwrap(), s);
+        }
+    }
+
+    #[test]
+    fn load_leaves_intact_file_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chat - 1.json");
+        save(&path, &three_turns()).unwrap();
+        assert_eq!(load(&path).unwrap().len(), 3);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,86 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Notices conversations added, changed or removed by something other than Jarvis,
+// e.g. a history folder synced with Syncthing or Dropbox, and tells the UI with
+// `history-changed` so the sidebar refreshes.
+
+use crate::events::{self, HistoryChanged};
+use notify_debouncer_mini::{new_debouncer, notify::RecursiveMode, DebounceEventResult};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::time::Duration;
+
+// sync tools write in bursts; one event per burst is enough
+const DEBOUNCE: Duration = Duration::from_millis(750);
+
+// conversation files only: no temp files from atomic writes, no corrupt backups
+fn conversation_name(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+    let lower = name.to_lowercase();
+    let is_conversation = lower.ends_with(".json") || lower.ends_with(".jsonl");
+    (is_conversation && !name.starts_with('.')).then_some(name)
+}
+
+// Called once at startup; the watcher runs on its own thread for the life of the app
+pub fn init(app: &tauri::AppHandle) {
+    let dir = match crate::history_dir(app) {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("[WARN] Not watching history folder: {}", e);
+            return;
+        }
+    };
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let (tx, rx) = std::sync::mpsc::channel::<DebounceEventResult>();
+        let mut debouncer = match new_debouncer(DEBOUNCE, tx) {
+            Ok(d) => d,
+            Err(e) => {
+                eprintln!("[WARN] Failed to start history watcher: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = debouncer.watcher().watch(&dir, RecursiveMode::NonRecursive) {
+            eprintln!("[WARN] Failed to watch {:?}: {}", dir, e);
+            return;
+        }
+        for result in rx {
+            let changed = match result {
+                Ok(changed) => changed,
+                Err(e) => {
+                    eprintln!("[WARN] History watcher error: {}", e);
+                    continue;
+                }
+            };
+            let filenames: BTreeSet<String> = changed
+                .iter()
+                .filter(|e| !crate::history::written_by_us(&e.path))
+                .filter_map(|e| conversation_name(&e.path))
+                .collect();
+            if !filenames.is_empty() {
+                println!("[DEBUG] History changed on disk: {:?}", filenames);
+                events::emit(
+                    &app,
+                    HistoryChanged {
+                        filenames: filenames.into_iter().collect(),
+                    },
+                );
+            }
+        }
+    });
+}
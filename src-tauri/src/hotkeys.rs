@@ -0,0 +1,111 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// System-wide keyboard shortcuts. Accelerators use the Tauri syntax, e.g.
+// "CommandOrControl+Shift+Space"; an empty string leaves the action unbound.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::plugin::TauriPlugin;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct HotkeyConfig {
+    pub quick_ask: String,
+    // reads the selection in any app aloud; pressing it again stops
+    pub read_selection: String,
+    pub summarize_clipboard: String,
+}
+
+impl Default for HotkeyConfig {
+    fn default() -> Self {
+        Self {
+            quick_ask: "CommandOrControl+Shift+Space".to_string(),
+            read_selection: "CommandOrControl+Shift+R".to_string(),
+            summarize_clipboard: "CommandOrControl+Alt+S".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Action {
+    QuickAsk,
+    ReadSelection,
+    SummarizeClipboard,
+}
+
+// shortcuts currently registered with the OS and what they do
+static BINDINGS: Mutex<Vec<(Shortcut, Action)>> = Mutex::new(Vec::new());
+
+fn run(app: &tauri::AppHandle, action: Action) {
+    let result = match action {
+        Action::QuickAsk => crate::quick_ask::toggle(app),
+        Action::ReadSelection => crate::read_aloud::toggle(app),
+        Action::SummarizeClipboard => crate::clipboard_tools::summarize_hotkey(app),
+    };
+    if let Err(e) = result {
+        eprintln!("[ERROR] Hotkey {:?}: {}", action, e);
+    }
+}
+
+pub fn plugin() -> TauriPlugin<tauri::Wry> {
+    tauri_plugin_global_shortcut::Builder::new()
+        .with_handler(|app, shortcut, event| {
+            if event.state() != ShortcutState::Pressed {
+                return;
+            }
+            let action = BINDINGS
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(s, _)| s == shortcut)
+                .map(|(_, a)| *a);
+            if let Some(action) = action {
+                run(app, action);
+            }
+        })
+        .build()
+}
+
+// Replaces all registered shortcuts; a bad or taken accelerator only skips that one
+pub fn register(app: &tauri::AppHandle, config: &HotkeyConfig) {
+    let shortcuts = app.global_shortcut();
+    let mut bindings = BINDINGS.lock().unwrap();
+    let _ = shortcuts.unregister_all();
+    bindings.clear();
+    for (accelerator, action) in [
+        (&config.quick_ask, Action::QuickAsk),
+        (&config.read_selection, Action::ReadSelection),
+        (&config.summarize_clipboard, Action::SummarizeClipboard),
+    ] {
+        if accelerator.trim().is_empty() {
+            continue;
+        }
+        let shortcut = match accelerator.parse::<Shortcut>() {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("[WARN] Invalid hotkey '{}': {}", accelerator, e);
+                continue;
+            }
+        };
+        match shortcuts.register(shortcut) {
+            Ok(()) => bindings.push((shortcut, action)),
+            Err(e) => eprintln!("[WARN] Could not register hotkey '{}': {}", accelerator, e),
+        }
+    }
+}
@@ -0,0 +1,224 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// "Generate an image of ..." with a Gemini image model. Results are stored with the
+// active conversation (or under `images/` when there is none) and shown in the chat.
+
+use crate::events::{self, NewMessage, Role};
+use crate::models::Config;
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine as _};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::Manager;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ImageGenerationConfig {
+    pub enabled: bool,
+    // any Gemini model that can answer with images
+    pub model: String,
+    // also put the picture on the clipboard, ready to paste
+    pub copy_to_clipboard: bool,
+}
+
+impl Default for ImageGenerationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            model: "gemini-2.5-flash-image".to_string(),
+            copy_to_clipboard: false,
+        }
+    }
+}
+
+pub struct GeneratedImage {
+    pub mime: String,
+    pub bytes: Vec<u8>,
+    // text the model sent along with the picture, if any
+    pub caption: Option<String>,
+}
+
+// "generate an image of a red fox in the snow" -> "a red fox in the snow"
+pub fn match_request(prompt: &str) -> Option<String> {
+    let re = Regex::new(
+        r"(?i)^\s*(?:please\s+|can you\s+|could you\s+)?(?:generate|create|draw|make|paint|render)\s+(?:me\s+)?(?:an?\s+)?(?:image|picture|drawing|illustration|painting|photo)\s+(?:of|showing|with)\s+(.+?)[.!?]?\s*$",
+    )
+    .expect("Failed to compile image request regex");
+    let cap = re.captures(prompt)?;
+    let description = cap.get(1)?.as_str().trim();
+    (!description.is_empty()).then(|| description.to_string())
+}
+
+pub async fn generate(config: &Config, description: &str) -> Result<GeneratedImage> {
+    let url = format!(
+        "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent",
+        config.image_generation.model
+    );
+    // nothing comes back as text worth restoring, so redacted values just stay out of the prompt
+    let description = crate::redact::redact(
+        description,
+        &config.redaction,
+        &mut crate::redact::Redactions::default(),
+    );
+    let body = serde_json::json!({
+        "contents": [{ "parts": [{ "text": format!("Generate an image of {}", description) }] }],
+        "generationConfig": { "responseModalities": ["TEXT", "IMAGE"] }
+    });
+    let resp = reqwest::Client::new()
+        .post(&url)
+        .header("x-goog-api-key", &config.gemini_key)
+        .json(&body)
+        .timeout(std::time::Duration::from_secs(120))
+        .send()
+        .await
+        .map_err(|e| anyhow!("Image request failed: {}", e))?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        return Err(anyhow!("Gemini returned {}: {}", status, text));
+    }
+    let json: Value = resp.json().await?;
+    if let Some(reason) = json["promptFeedback"]["blockReason"].as_str() {
+        return Err(anyhow!("The request was blocked ({})", reason));
+    }
+
+    let parts = json["candidates"][0]["content"]["parts"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    let caption = parts
+        .iter()
+        .filter_map(|p| p["text"].as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let image = parts
+        .iter()
+        .find_map(|p| p.get("inlineData").or_else(|| p.get("inline_data")))
+        .ok_or_else(|| anyhow!("The model did not return an image"))?;
+    let mime = image["mimeType"]
+        .as_str()
+        .or_else(|| image["mime_type"].as_str())
+        .unwrap_or("image/png")
+        .to_string();
+    let bytes = general_purpose::STANDARD
+        .decode(image["data"].as_str().unwrap_or_default())
+        .map_err(|e| anyhow!("Invalid image data: {}", e))?;
+    crate::stats::record_llm(description.chars().count(), caption.chars().count());
+    Ok(GeneratedImage {
+        mime,
+        bytes,
+        caption: Some(caption.trim().to_string()).filter(|c| !c.is_empty()),
+    })
+}
+
+fn copy_to_clipboard(bytes: &[u8]) -> Result<()> {
+    let img = image::load_from_memory(bytes)?.to_rgba8();
+    let (width, height) = (img.width() as usize, img.height() as usize);
+    arboard::Clipboard::new()?.set_image(arboard::ImageData {
+        width,
+        height,
+        bytes: img.into_raw().into(),
+    })?;
+    Ok(())
+}
+
+// Outside a conversation the picture goes to `images/` in the app config dir
+fn save_loose(app: &tauri::AppHandle, created_at: i64, image: &GeneratedImage) -> Result<String, String> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| e.to_string())?
+        .join("images");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let ext = if image.mime == "image/jpeg" { "jpg" } else { "png" };
+    let path = dir.join(format!("image-{}.{}", created_at, ext));
+    std::fs::write(&path, &image.bytes).map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+// Generates and stores the picture; Err carries the reply explaining what went wrong
+async fn create(
+    app: &tauri::AppHandle,
+    config: &Config,
+    description: &str,
+    meta: &mut serde_json::Map<String, Value>,
+    created_at: i64,
+) -> Result<(String, Option<String>), String> {
+    if !config.image_generation.enabled {
+        return Err("Image generation is turned off in the settings.".to_string());
+    }
+    if config.gemini_key.trim().is_empty() {
+        return Err("Please enter your Gemini API key in Settings > API Keys.".to_string());
+    }
+    let image = generate(config, description).await.map_err(|e| {
+        eprintln!("[ERROR] Image generation failed: {}", e);
+        "Sorry, I couldn't generate that image.".to_string()
+    })?;
+
+    let active = app
+        .state::<crate::JarvisState>()
+        .active_conversation
+        .lock()
+        .unwrap()
+        .clone();
+    let saved = match active.as_deref() {
+        Some(fname) => crate::attachments::save_bytes(app, fname, created_at, &image.mime, &image.bytes)
+            .map(|r| {
+                meta.insert("attachments".into(), serde_json::json!([r]));
+            }),
+        None => save_loose(app, created_at, &image).map(|path| {
+            meta.insert("imagePath".into(), path.into());
+        }),
+    };
+    saved.map_err(|e| {
+        eprintln!("[ERROR] Failed to save generated image: {}", e);
+        "I generated the image but couldn't save it.".to_string()
+    })?;
+    meta.insert("model".into(), config.image_generation.model.clone().into());
+
+    let mut reply = format!("Here is an image of {}.", description);
+    if config.image_generation.copy_to_clipboard {
+        match copy_to_clipboard(&image.bytes) {
+            Ok(()) => reply.push_str(" It's on your clipboard."),
+            Err(e) => eprintln!("[WARN] Failed to copy image to clipboard: {}", e),
+        }
+    }
+    Ok((reply, image.caption))
+}
+
+// Generates, stores and shows the picture in the chat; returns the reply to speak
+pub async fn handle(app: &tauri::AppHandle, config: &Config, description: &str) -> String {
+    let created_at = chrono::Utc::now().timestamp_millis();
+    let mut meta = serde_json::Map::new();
+    meta.insert("localCommand".into(), true.into());
+    let (reply, content) = match create(app, config, description, &mut meta, created_at).await {
+        Ok((reply, Some(caption))) => (reply.clone(), format!("{}\n\n{}", reply, caption)),
+        Ok((reply, None)) => (reply.clone(), reply),
+        Err(reply) => (reply.clone(), reply),
+    };
+    events::emit(app, NewMessage::new(Role::Assistant, &content).at(created_at).with_meta(meta));
+    reply
+}
+
+// Same as saying "generate an image of ..."; returns the reply text
+#[tauri::command]
+pub async fn cmd_generate_image(app: tauri::AppHandle, description: String) -> Result<String, String> {
+    let config = crate::to_run_config(crate::cmd_load_config(app.clone())?);
+    Ok(handle(&app, &config, description.trim()).await)
+}
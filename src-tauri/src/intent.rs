@@ -0,0 +1,122 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Short-term memory for local tools: remembers the slots of the last tool request so a
+// follow-up like "what about tomorrow?" or "and in Berlin?" can re-run it with the
+// missing pieces filled in. Entries expire after `context_window_expiration_seconds`.
+
+use regex::Regex;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Slots {
+    pub location: Option<String>,
+    // 0 = today, 1 = tomorrow, 2 = day after tomorrow
+    pub day_offset: Option<u8>,
+}
+
+impl Slots {
+    // values in `newer` win, everything else is carried over
+    pub fn merged_with(&self, newer: &Slots) -> Slots {
+        Slots {
+            location: newer.location.clone().or_else(|| self.location.clone()),
+            day_offset: newer.day_offset.or(self.day_offset),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.location.is_none() && self.day_offset.is_none()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LastIntent {
+    name: &'static str,
+    slots: Slots,
+    at: Instant,
+}
+
+static LAST_INTENT: Mutex<Option<LastIntent>> = Mutex::new(None);
+
+pub fn remember(name: &'static str, slots: &Slots) {
+    if let Ok(mut last) = LAST_INTENT.lock() {
+        *last = Some(LastIntent {
+            name,
+            slots: slots.clone(),
+            at: Instant::now(),
+        });
+    }
+}
+
+pub fn forget() {
+    if let Ok(mut last) = LAST_INTENT.lock() {
+        *last = None;
+    }
+}
+
+fn day_offset(text: &str) -> Option<u8> {
+    let lower = text.to_lowercase();
+    if lower.contains("day after tomorrow") || lower.contains("übermorgen") {
+        Some(2)
+    } else if lower.contains("tomorrow") || lower.contains("morgen") {
+        Some(1)
+    } else if lower.contains("today") || lower.contains("tonight") || lower.contains("heute") {
+        Some(0)
+    } else {
+        None
+    }
+}
+
+fn location(text: &str) -> Option<String> {
+    let re = Regex::new(r"(?i)\b(?:in|at|for)\s+([\p{L}][\p{L}\s\-']{1,40}?)\s*(?:today|tonight|tomorrow|day after tomorrow|right now|now)?[?.!]*\s*$")
+        .expect("Failed to compile location regex");
+    let loc = re.captures(text.trim())?.get(1)?.as_str().trim().to_string();
+    // "for tomorrow" / "for today" are times, not places
+    let lc = loc.to_lowercase();
+    if loc.is_empty() || ["today", "tonight", "tomorrow", "now", "the day"].contains(&lc.as_str()) {
+        return None;
+    }
+    Some(loc)
+}
+
+// Extracts whatever slots the text mentions
+pub fn extract_slots(text: &str) -> Slots {
+    Slots {
+        location: location(text),
+        day_offset: day_offset(text),
+    }
+}
+
+// Resolves a short follow-up against the last intent if it is still fresh.
+// Returns the intent name and the merged slots.
+pub fn follow_up(prompt: &str, expiry: Duration) -> Option<(&'static str, Slots)> {
+    let re = Regex::new(r"(?i)^\s*(?:and|what about|how about|and what about|what's it like|same for)\b")
+        .expect("Failed to compile follow-up regex");
+    if !re.is_match(prompt) || prompt.split_whitespace().count() > 8 {
+        return None;
+    }
+    let newer = extract_slots(prompt);
+    if newer.is_empty() {
+        return None;
+    }
+    let last = LAST_INTENT.lock().ok()?.clone()?;
+    if last.at.elapsed() > expiry {
+        return None;
+    }
+    Some((last.name, last.slots.merged_with(&newer)))
+}
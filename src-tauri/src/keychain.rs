@@ -0,0 +1,55 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Secrets kept in the OS keychain (Windows Credential Manager, the macOS Keychain or the
+// Secret Service on Linux) instead of config.json. config.json only records that one is
+// set.
+
+use anyhow::Result;
+
+const SERVICE: &str = "Jarvis";
+
+fn entry(name: &str) -> Result<keyring::Entry> {
+    Ok(keyring::Entry::new(SERVICE, name)?)
+}
+
+// None when nothing is stored under `name` or the keychain can't be reached
+pub fn get(name: &str) -> Option<String> {
+    let entry = entry(name)
+        .map_err(|e| eprintln!("[WARN] Failed to open the keychain: {}", e))
+        .ok()?;
+    match entry.get_password() {
+        Ok(secret) => Some(secret),
+        Err(keyring::Error::NoEntry) => None,
+        Err(e) => {
+            eprintln!("[WARN] Failed to read '{}' from the keychain: {}", name, e);
+            None
+        }
+    }
+}
+
+pub fn set(name: &str, secret: &str) -> Result<()> {
+    entry(name)?.set_password(secret)?;
+    Ok(())
+}
+
+pub fn delete(name: &str) -> Result<()> {
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,418 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Kubernetes: "are all pods healthy in my cluster" checks the pods (and, for the whole
+// cluster, the nodes) and says which ones aren't, e.g. "in media, jellyfin is stuck in
+// CrashLoopBackOff". "In the media namespace" narrows it down; otherwise `namespaces`
+// does, or everything is checked. Off by default, and it only ever reads.
+//
+// The cluster and credentials come from the kubeconfig, the same file kubectl uses.
+// Client certificates and tokens work, which covers k3s, microk8s, kubeadm and the like;
+// exec plugins (the cloud providers' login helpers) aren't run.
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine as _};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct KubernetesConfig {
+    pub enabled: bool,
+    // empty is $KUBECONFIG, or .kube\config in the home folder
+    pub kubeconfig: String,
+    // empty is the kubeconfig's current context
+    pub context: String,
+    // checked when a question doesn't name one; empty is all of them
+    pub namespaces: Vec<String>,
+}
+
+// the parts of a kubeconfig used here
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, rename_all = "kebab-case")]
+struct KubeConfig {
+    current_context: String,
+    contexts: Vec<NamedContext>,
+    clusters: Vec<NamedCluster>,
+    users: Vec<NamedUser>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct NamedContext {
+    name: String,
+    context: Context,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct Context {
+    cluster: String,
+    user: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct NamedCluster {
+    name: String,
+    cluster: Cluster,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, rename_all = "kebab-case")]
+struct Cluster {
+    server: String,
+    certificate_authority: Option<String>,
+    certificate_authority_data: Option<String>,
+    insecure_skip_tls_verify: bool,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct NamedUser {
+    name: String,
+    user: User,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, rename_all = "kebab-case")]
+struct User {
+    token: Option<String>,
+    token_file: Option<String>,
+    client_certificate: Option<String>,
+    client_certificate_data: Option<String>,
+    client_key: Option<String>,
+    client_key_data: Option<String>,
+    exec: Option<serde_yaml::Value>,
+}
+
+// a pod that isn't running as it should
+struct Trouble {
+    namespace: String,
+    pod: String,
+    // "is pending", "is stuck in CrashLoopBackOff", ...
+    reason: String,
+}
+
+// "are all pods healthy in my cluster", "how's the cluster", "anything failing in k8s"
+static HEALTH: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:pods?|cluster|kubernetes|k8s|nodes|namespace)\b.*",
+        r"\b(?:healthy|unhealthy|health|status|running|failing|crashing|broken|pending",
+        r"|ok|okay|up)\b",
+        r"|\b(?:healthy|unhealthy|failing|crashing|broken|pending|running)\b.*",
+        r"\b(?:pods?|cluster|kubernetes|k8s|nodes|namespace)\b",
+        r"|\b(?:check|how's|how is|how are)\s+(?:the\s+|my\s+|all\s+)?(?:the\s+)?",
+        r"(?:pods|cluster|kubernetes|k8s|nodes)\b",
+    ))
+    .expect("Failed to compile cluster health regex")
+});
+
+// "in the media namespace", "namespace media"
+static NAMESPACE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:in|for)\s+(?:the\s+)?([a-z0-9-]+)\s+namespace\b",
+        r"|\bnamespace\s+([a-z0-9-]+)",
+    ))
+    .expect("Failed to compile namespace regex")
+});
+
+// "jellyfin-7d9f8c6b5-x2k4q" as "jellyfin"; StatefulSet and DaemonSet pods keep less
+static POD_SUFFIX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:-[a-z0-9]{8,10})?-[a-z0-9]{5}$").expect("Failed to compile pod name regex")
+});
+
+// pods read out by name before the rest are only counted
+const MAX_READ: usize = 3;
+
+fn kubeconfig_path(config: &KubernetesConfig) -> Option<PathBuf> {
+    let configured = config.kubeconfig.trim();
+    if !configured.is_empty() {
+        return Some(PathBuf::from(configured));
+    }
+    // KUBECONFIG may list several files; the first is used
+    if let Some(first) = std::env::var_os("KUBECONFIG")
+        .as_deref()
+        .and_then(|v| std::env::split_paths(v).next())
+    {
+        return Some(first);
+    }
+    let home = std::env::var_os("USERPROFILE").or_else(|| std::env::var_os("HOME"))?;
+    Some(Path::new(&home).join(".kube").join("config"))
+}
+
+// Inline base64 data, or a file (relative to the kubeconfig)
+fn read_pem(data: &Option<String>, file: &Option<String>, dir: &Path) -> Result<Option<Vec<u8>>> {
+    if let Some(data) = data.as_deref().filter(|d| !d.trim().is_empty()) {
+        return Ok(Some(general_purpose::STANDARD.decode(data.trim())?));
+    }
+    match file.as_deref().filter(|f| !f.trim().is_empty()) {
+        Some(file) => Ok(Some(std::fs::read(dir.join(file))?)),
+        None => Ok(None),
+    }
+}
+
+// The API server's address and a client signed in to it
+fn connect(config: &KubernetesConfig) -> Result<(String, reqwest::Client)> {
+    let path = kubeconfig_path(config).ok_or_else(|| anyhow!("no kubeconfig found"))?;
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow!("couldn't read {}: {}", path.display(), e))?;
+    let kube: KubeConfig = serde_yaml::from_str(&text)?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+
+    let wanted = match config.context.trim() {
+        "" => kube.current_context.as_str(),
+        name => name,
+    };
+    let context = &kube
+        .contexts
+        .iter()
+        .find(|c| c.name == wanted)
+        .ok_or_else(|| anyhow!("the kubeconfig has no context \"{}\"", wanted))?
+        .context;
+    let cluster = &kube
+        .clusters
+        .iter()
+        .find(|c| c.name == context.cluster)
+        .ok_or_else(|| anyhow!("the kubeconfig has no cluster \"{}\"", context.cluster))?
+        .cluster;
+    let user = kube
+        .users
+        .iter()
+        .find(|u| u.name == context.user)
+        .map(|u| &u.user);
+
+    let mut builder = reqwest::Client::builder()
+        .user_agent(concat!("Jarvis/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(15))
+        .use_rustls_tls();
+    if let Some(ca) = read_pem(
+        &cluster.certificate_authority_data,
+        &cluster.certificate_authority,
+        dir,
+    )? {
+        builder = builder.add_root_certificate(reqwest::Certificate::from_pem(&ca)?);
+    }
+    if cluster.insecure_skip_tls_verify {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    let mut headers = reqwest::header::HeaderMap::new();
+    if let Some(user) = user {
+        let cert = read_pem(&user.client_certificate_data, &user.client_certificate, dir)?;
+        let key = read_pem(&user.client_key_data, &user.client_key, dir)?;
+        let token = match (&user.token, &user.token_file) {
+            (Some(token), _) => Some(token.trim().to_string()),
+            (None, Some(file)) => Some(std::fs::read_to_string(dir.join(file))?.trim().to_string()),
+            (None, None) => None,
+        };
+        if let (Some(mut cert), Some(key)) = (cert, key) {
+            cert.push(b'\n');
+            cert.extend_from_slice(&key);
+            builder = builder.identity(reqwest::Identity::from_pem(&cert)?);
+        } else if token.is_none() && user.exec.is_some() {
+            return Err(anyhow!(
+                "the kubeconfig signs in with an exec plugin, which Jarvis doesn't run"
+            ));
+        }
+        if let Some(token) = token {
+            headers.insert(
+                reqwest::header::AUTHORIZATION,
+                format!("Bearer {}", token).parse()?,
+            );
+        }
+    }
+    let client = builder.default_headers(headers).build()?;
+    Ok((cluster.server.trim_end_matches('/').to_string(), client))
+}
+
+async fn get(server: &str, client: &reqwest::Client, path: &str) -> Result<Value> {
+    let resp = client.get(format!("{}{}", server, path)).send().await?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(anyhow!("the cluster returned {} for {}", status, path));
+    }
+    Ok(resp.json().await?)
+}
+
+// What is wrong with a pod, e.g. "is not ready", or None when it is healthy
+fn pod_trouble(pod: &Value) -> Option<String> {
+    let phase = pod["status"]["phase"].as_str().unwrap_or("Unknown");
+    let statuses = pod["status"]["containerStatuses"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    // CrashLoopBackOff, ImagePullBackOff, ... say more than the phase
+    let waiting = statuses
+        .iter()
+        .filter_map(|s| s["state"]["waiting"]["reason"].as_str())
+        .find(|r| *r != "ContainerCreating");
+    if let Some(reason) = waiting.filter(|_| phase != "Succeeded") {
+        return Some(format!("is stuck in {}", reason));
+    }
+    match phase {
+        // finished jobs
+        "Succeeded" => None,
+        "Running" if statuses.iter().all(|s| s["ready"].as_bool() == Some(true)) => None,
+        "Running" => Some("is not ready".to_string()),
+        "Pending" => Some("is pending".to_string()),
+        "Failed" => Some(match pod["status"]["reason"].as_str() {
+            Some(reason) => format!("failed ({})", reason),
+            None => "failed".to_string(),
+        }),
+        _ => Some("is in an unknown state".to_string()),
+    }
+}
+
+fn spoken_pod(name: &str) -> String {
+    POD_SUFFIX.replace(name, "").to_string()
+}
+
+// The nodes that aren't Ready
+fn unready_nodes(nodes: &Value) -> Vec<String> {
+    nodes["items"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter(|node| {
+            !node["status"]["conditions"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+                .iter()
+                .any(|c| c["type"] == "Ready" && c["status"] == "True")
+        })
+        .map(|node| {
+            node["metadata"]["name"]
+                .as_str()
+                .unwrap_or("a node")
+                .to_string()
+        })
+        .collect()
+}
+
+fn describe(total: usize, troubles: &[Trouble], nodes_down: &[String], scope: &str) -> String {
+    let mut parts = Vec::new();
+    match nodes_down {
+        [] => {}
+        [one] => parts.push(format!("Node {} isn't ready.", one)),
+        many => parts.push(format!("Nodes {} aren't ready.", many.join(", "))),
+    }
+    if troubles.is_empty() {
+        parts.push(match total {
+            0 => format!("There are no pods {}.", scope),
+            1 => format!("The one pod {} is healthy.", scope),
+            n => format!("All {} pods {} are healthy.", n, scope),
+        });
+        return parts.join(" ");
+    }
+    let named: Vec<String> = troubles
+        .iter()
+        .take(MAX_READ)
+        .map(|t| format!("in {}, {} {}", t.namespace, spoken_pod(&t.pod), t.reason))
+        .collect();
+    parts.push(format!(
+        "{} of {} pods {} are healthy.",
+        total - troubles.len(),
+        total,
+        scope
+    ));
+    let mut line = capitalized(&named.join("; "));
+    if troubles.len() > MAX_READ {
+        line.push_str(&format!(", and {} more", troubles.len() - MAX_READ));
+    }
+    parts.push(format!("{}.", line));
+    parts.join(" ")
+}
+
+fn capitalized(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+async fn check(prompt: &str, config: &KubernetesConfig) -> Result<String> {
+    let (server, client) = connect(config)?;
+    let asked = NAMESPACE
+        .captures(prompt)
+        .and_then(|c| c.get(1).or(c.get(2)))
+        .map(|m| m.as_str().to_lowercase());
+    let namespaces: Vec<String> = match asked {
+        Some(namespace) => vec![namespace],
+        None => config.namespaces.clone(),
+    };
+
+    let mut pods = Vec::new();
+    if namespaces.is_empty() {
+        let all = get(&server, &client, "/api/v1/pods").await?;
+        pods.extend(all["items"].as_array().cloned().unwrap_or_default());
+    }
+    for namespace in &namespaces {
+        let path = format!("/api/v1/namespaces/{}/pods", namespace);
+        let list = get(&server, &client, &path).await?;
+        pods.extend(list["items"].as_array().cloned().unwrap_or_default());
+    }
+    let troubles: Vec<Trouble> = pods
+        .iter()
+        .filter_map(|pod| {
+            Some(Trouble {
+                reason: pod_trouble(pod)?,
+                namespace: pod["metadata"]["namespace"]
+                    .as_str()
+                    .unwrap_or("")
+                    .to_string(),
+                pod: pod["metadata"]["name"].as_str().unwrap_or("").to_string(),
+            })
+        })
+        .collect();
+
+    // nodes only matter when the whole cluster was asked about
+    let nodes_down = if namespaces.is_empty() {
+        match get(&server, &client, "/api/v1/nodes").await {
+            Ok(nodes) => unready_nodes(&nodes),
+            // reading nodes needs a cluster-wide permission the user may not have
+            Err(e) => {
+                eprintln!("[WARN] Couldn't read the cluster's nodes: {}", e);
+                Vec::new()
+            }
+        }
+    } else {
+        Vec::new()
+    };
+    let scope = match namespaces.as_slice() {
+        [] => "in the cluster".to_string(),
+        [one] => format!("in {}", one),
+        many => format!("in {}", many.join(", ")),
+    };
+    Ok(describe(pods.len(), &troubles, &nodes_down, &scope))
+}
+
+pub async fn answer(prompt: &str, config: &KubernetesConfig) -> Option<String> {
+    if !config.enabled || !HEALTH.is_match(prompt) {
+        return None;
+    }
+    Some(check(prompt, config).await.unwrap_or_else(|e| {
+        eprintln!("[ERROR] Kubernetes check failed: {}", e);
+        format!("Sorry, I couldn't check the cluster: {}.", e)
+    }))
+}
@@ -0,0 +1,181 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Pronunciation dictionary (`lexicon.json` in app data) for names and product words
+// the voices get wrong. Each word is replaced by a respelling before speaking; voices
+// that understand SSML phoneme tags get the IPA pronunciation instead when one is set.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use tauri::Manager;
+
+// ElevenLabs models that read <phoneme> tags (English only)
+const PHONEME_MODELS: &[&str] = &["eleven_flash_v2", "eleven_turbo_v2", "eleven_monolingual_v1"];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LexiconEntry {
+    // as written, e.g. "Hav1ck"
+    pub word: String,
+    // how to say it with ordinary letters, e.g. "Havick"
+    pub say_as: String,
+    // IPA pronunciation, used where the voice supports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phoneme: Option<String>,
+    #[serde(default)]
+    pub case_sensitive: bool,
+}
+
+struct Compiled {
+    entry: LexiconEntry,
+    pattern: Regex,
+}
+
+// kept in memory so every spoken sentence doesn't read the file
+static ENTRIES: RwLock<Vec<Compiled>> = RwLock::new(Vec::new());
+
+fn lexicon_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join("lexicon.json"))
+}
+
+pub fn load(app: &tauri::AppHandle) -> Vec<LexiconEntry> {
+    lexicon_path(app)
+        .ok()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn compile(entry: &LexiconEntry) -> Option<Regex> {
+    let flags = if entry.case_sensitive { "" } else { "(?i)" };
+    Regex::new(&format!("{}{}", flags, regex::escape(entry.word.trim()))).ok()
+}
+
+// whole words only; the edges are checked by hand so words like "C#" work too
+fn replace_words(text: &str, pattern: &Regex, replacement: &str) -> String {
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for m in pattern.find_iter(text) {
+        let before = text[..m.start()].chars().next_back();
+        let after = text[m.end()..].chars().next();
+        if is_word(before) || is_word(after) {
+            continue;
+        }
+        out.push_str(&text[last..m.start()]);
+        out.push_str(replacement);
+        last = m.end();
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+fn set_cache(entries: &[LexiconEntry]) {
+    let compiled = entries
+        .iter()
+        .filter(|e| !e.word.trim().is_empty())
+        .filter_map(|e| {
+            compile(e).map(|pattern| Compiled {
+                entry: e.clone(),
+                pattern,
+            })
+        })
+        .collect();
+    *ENTRIES.write().unwrap() = compiled;
+}
+
+// Called once at startup
+pub fn init(app: &tauri::AppHandle) {
+    set_cache(&load(app));
+}
+
+fn save(app: &tauri::AppHandle, entries: &[LexiconEntry]) -> Result<(), String> {
+    let s = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+    crate::history::atomic_write(&lexicon_path(app)?, s.as_bytes()).map_err(|e| e.to_string())?;
+    set_cache(entries);
+    crate::events::emit(app, crate::events::LexiconChanged(entries.len()));
+    Ok(())
+}
+
+pub fn supports_phonemes(model_id: &str) -> bool {
+    PHONEME_MODELS.contains(&model_id)
+}
+
+// Replaces lexicon words in text that is about to be spoken
+pub fn apply(text: &str, phonemes: bool) -> String {
+    let entries = ENTRIES.read().unwrap();
+    let mut out = text.to_string();
+    for Compiled { entry, pattern } in entries.iter() {
+        let replacement = match entry.phoneme.as_deref().map(str::trim) {
+            Some(ph) if phonemes && !ph.is_empty() => format!(
+                r#"<phoneme alphabet="ipa" ph="{}">{}</phoneme>"#,
+                ph.replace('"', ""),
+                entry.word.trim()
+            ),
+            _ if !entry.say_as.trim().is_empty() => entry.say_as.trim().to_string(),
+            _ => continue,
+        };
+        out = replace_words(&out, pattern, &replacement);
+    }
+    out
+}
+
+#[tauri::command]
+pub fn cmd_get_lexicon(app: tauri::AppHandle) -> Result<Vec<LexiconEntry>, String> {
+    Ok(load(&app))
+}
+
+// Replaces the whole dictionary, e.g. after editing it in the settings
+#[tauri::command]
+pub fn cmd_edit_lexicon(app: tauri::AppHandle, entries: Vec<LexiconEntry>) -> Result<Vec<LexiconEntry>, String> {
+    let entries: Vec<LexiconEntry> = entries
+        .into_iter()
+        .filter(|e| !e.word.trim().is_empty())
+        .collect();
+    save(&app, &entries)?;
+    Ok(entries)
+}
+
+// Adds a word or updates the entry with the same spelling
+#[tauri::command]
+pub fn cmd_set_lexicon_entry(app: tauri::AppHandle, entry: LexiconEntry) -> Result<Vec<LexiconEntry>, String> {
+    if entry.word.trim().is_empty() {
+        return Err("The word can't be empty".into());
+    }
+    let mut entries = load(&app);
+    match entries
+        .iter_mut()
+        .find(|e| e.word.trim().eq_ignore_ascii_case(entry.word.trim()))
+    {
+        Some(existing) => *existing = entry,
+        None => entries.push(entry),
+    }
+    save(&app, &entries)?;
+    Ok(entries)
+}
+
+#[tauri::command]
+pub fn cmd_remove_lexicon_entry(app: tauri::AppHandle, word: String) -> Result<Vec<LexiconEntry>, String> {
+    let mut entries = load(&app);
+    entries.retain(|e| !e.word.trim().eq_ignore_ascii_case(word.trim()));
+    save(&app, &entries)?;
+    Ok(entries)
+}
@@ -15,19 +15,97 @@ You should have received a copy of the GNU Affero General Public License
 along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+pub mod ambient;
+pub mod app_triggers;
+pub mod attachments;
 pub mod audio_input;
+pub mod audio_output;
+pub mod batch;
+pub mod benchmark;
+pub mod branches;
+pub mod briefing;
+pub mod captures;
+pub mod clipboard_tools;
+pub mod commute;
+pub mod config;
+pub mod confirm;
+pub mod context_budget;
+pub mod conversation_settings;
+pub mod cooking;
+pub mod crash;
+pub mod diagnostics;
+pub mod docker;
+pub mod events;
+pub mod finance;
+pub mod foreground;
+pub mod forget;
+pub mod game_mode;
 pub mod get_text;
+pub mod github;
+pub mod history;
+pub mod history_watch;
+pub mod hotkeys;
+pub mod image_gen;
+pub mod intent;
+pub mod keychain;
+pub mod kubernetes;
+pub mod lexicon;
+pub mod local_api;
+pub mod media;
+pub mod meeting;
+pub mod memory;
 pub mod models;
+pub mod network;
+pub mod openai_api;
+pub mod overlay;
+pub mod packages;
+pub mod pdf;
+pub mod persona;
+pub mod pipeline;
+#[cfg(feature = "pipeline-mock")]
+pub mod pipeline_mock;
+pub mod pomodoro;
+pub mod presence;
+pub mod processes;
+pub mod profiles;
+pub mod prompt_vars;
+pub mod quick_ask;
+pub mod read_aloud;
+pub mod readability;
+pub mod redact;
 pub mod run_jarvis;
+pub mod safety;
+pub mod satellite;
 pub mod send_to_llm;
+pub mod settings;
+pub mod setup;
+pub mod sports;
+pub mod stats;
+pub mod stt;
+pub mod sync;
+pub mod terminal;
+pub mod thread_priority;
+pub mod timers;
+pub mod trace;
 pub mod transform_text;
+pub mod triggers;
 pub mod tts;
+pub mod tts_normalize;
+pub mod updates;
+pub mod user_profile;
 pub mod utils;
-
-use elevenlabs_rs::Model as ElevenModel;
+pub mod voice_memos;
+pub mod web_fetch;
+pub mod wikipedia;
+pub mod world_clock;
+pub mod wyoming;
+pub mod youtube;
+
+use events::{MessageMeta, NewMessage, Role};
 use serde::{Deserialize, Serialize};
 use std::thread::JoinHandle;
 use std::{
+    collections::BTreeMap,
     fs,
     path::PathBuf,
     sync::{
@@ -52,6 +130,9 @@ struct Config {
     default_microphone_index: i32,
     default_microphone_name: Option<String>,
     default_output_device_name: Option<String>,
+    // Jarvis's voice only; None keeps it on default_output_device_name
+    #[serde(default)]
+    tts_output_device_name: Option<String>,
 
     gemini_model: String,
     elevenlabs_model: String,
@@ -63,13 +144,204 @@ struct Config {
     context_window_expiration_seconds: i32,
 
     frame_duration_ms: i32,
-    silence_threshold_seconds: i32,
+    // legacy whole-second setting; migrated to silence_threshold_ms on load
+    #[serde(default, skip_serializing)]
+    silence_threshold_seconds: Option<i32>,
+    #[serde(default)]
+    silence_threshold_ms: i32,
     speech_trigger_frames: i32,
     frame_length_wwd: i32,
 
     dock_position: Option<String>,
     input_mode: Option<String>,
     theme: Option<String>,
+
+    #[serde(default)]
+    redaction: redact::RedactionConfig,
+
+    #[serde(default)]
+    debug_capture: captures::CaptureConfig,
+
+    #[serde(default)]
+    endpointing: get_text::EndpointingConfig,
+
+    #[serde(default)]
+    wake: get_text::WakeConfig,
+
+    #[serde(default)]
+    interrupt: get_text::InterruptConfig,
+
+    #[serde(default)]
+    personas: Vec<persona::Persona>,
+    #[serde(default)]
+    active_persona: Option<String>,
+
+    #[serde(default)]
+    output_volume: Option<f32>,
+    #[serde(default)]
+    profiles: Vec<profiles::DeviceProfile>,
+    #[serde(default)]
+    active_profile: Option<String>,
+    #[serde(default)]
+    auto_select_profile: bool,
+
+    #[serde(default)]
+    tts_quota_warning: tts::QuotaWarningConfig,
+
+    // "elevenlabs" (default) or "system" for the OS voice
+    #[serde(default)]
+    tts_provider: Option<String>,
+
+    #[serde(default)]
+    memory: memory::MemoryConfig,
+
+    #[serde(default)]
+    command_feedback: transform_text::CommandFeedbackConfig,
+
+    #[serde(default)]
+    meeting: meeting::MeetingConfig,
+
+    // model for titles and other small helper calls; "offline" skips the cloud
+    #[serde(default)]
+    utility_model: Option<String>,
+
+    // "json" (default) or "jsonl" for new conversations; JSONL appends without rewriting
+    #[serde(default)]
+    history_format: Option<String>,
+
+    #[serde(default)]
+    crash_reporting: crash::CrashReportingConfig,
+
+    #[serde(default)]
+    hotkeys: hotkeys::HotkeyConfig,
+
+    #[serde(default)]
+    quick_ask: quick_ask::QuickAskConfig,
+
+    #[serde(default)]
+    image_generation: image_gen::ImageGenerationConfig,
+
+    // limits for downloading links found in prompts
+    #[serde(default)]
+    web_fetch: web_fetch::WebFetchConfig,
+
+    // text extraction for PDFs linked or named in prompts
+    #[serde(default)]
+    pdf: pdf::PdfConfig,
+
+    // how much conversation context fits in a request
+    #[serde(default)]
+    context_budget: context_budget::ContextBudgetConfig,
+
+    // temperature, top-p and answer length; unset keeps the model defaults
+    #[serde(default)]
+    generation: send_to_llm::GenerationSettings,
+
+    // Gemini safety thresholds per category
+    #[serde(default)]
+    safety: safety::SafetyConfig,
+
+    // voice answers come as a short spoken part and a fuller text shown in the chat
+    #[serde(default)]
+    dual_response: bool,
+
+    // cleanup of answers before they are spoken
+    #[serde(default)]
+    tts_normalization: tts_normalize::TtsNormalizationConfig,
+
+    // what happens with [[copy]] blocks in answers
+    #[serde(default)]
+    copy_blocks: transform_text::CopyBlocksConfig,
+
+    // end-to-end encrypted sync with other devices through WebDAV or S3, see sync.rs
+    #[serde(default)]
+    sync: sync::SyncConfig,
+
+    // HTTP/WebSocket API for other programs and satellites on this machine or network
+    #[serde(default)]
+    local_api: local_api::LocalApiConfig,
+
+    // run as a satellite of another Jarvis: wake word and recording only
+    #[serde(default)]
+    satellite: satellite::SatelliteConfig,
+
+    // Wyoming speech-to-text and text-to-speech server for Home Assistant
+    #[serde(default)]
+    wyoming: wyoming::WyomingConfig,
+
+    // act when apps gain or lose focus or go fullscreen
+    #[serde(default)]
+    app_triggers: app_triggers::AppTriggersConfig,
+
+    // free Whisper and lower priorities while a game runs fullscreen
+    #[serde(default)]
+    game_mode: game_mode::GameModeConfig,
+
+    // priorities and CPU cores of the audio threads
+    #[serde(default)]
+    thread_priority: thread_priority::ThreadPriorityConfig,
+
+    // where the voice loop's audio comes from: the microphone, or WAV files for testing
+    #[serde(default)]
+    input_source: audio_input::InputSourceConfig,
+
+    // values for {name} placeholders in llm_system_prompt, e.g. user_name or location
+    #[serde(default)]
+    prompt_variables: BTreeMap<String, String>,
+
+    #[serde(default)]
+    world_clock: world_clock::WorldClockConfig,
+
+    #[serde(default)]
+    finance: finance::FinanceConfig,
+
+    #[serde(default)]
+    sports: sports::SportsConfig,
+
+    #[serde(default)]
+    wikipedia: wikipedia::WikipediaConfig,
+
+    #[serde(default)]
+    packages: packages::PackagesConfig,
+
+    #[serde(default)]
+    commute: commute::CommuteConfig,
+
+    #[serde(default)]
+    cooking: cooking::CookingConfig,
+
+    #[serde(default)]
+    ambient: ambient::AmbientConfig,
+
+    #[serde(default)]
+    briefing: briefing::BriefingConfig,
+
+    #[serde(default)]
+    voice_memos: voice_memos::VoiceMemosConfig,
+
+    #[serde(default)]
+    pomodoro: pomodoro::PomodoroConfig,
+
+    #[serde(default)]
+    presence: presence::PresenceConfig,
+
+    #[serde(default)]
+    network: network::NetworkConfig,
+
+    #[serde(default)]
+    processes: processes::ProcessesConfig,
+
+    #[serde(default)]
+    terminal: terminal::TerminalConfig,
+
+    #[serde(default)]
+    github: github::GithubConfig,
+
+    #[serde(default)]
+    docker: docker::DockerConfig,
+
+    #[serde(default)]
+    kubernetes: kubernetes::KubernetesConfig,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -88,6 +360,9 @@ pub struct TurnDto {
     pub content: String,
     #[serde(rename = "createdAt")]
     pub created_at: i64,
+    // free-form per-turn details: model used, token estimates, latency, tool calls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<serde_json::Map<String, serde_json::Value>>,
 }
 
 fn config_path(app: &tauri::AppHandle) -> anyhow::Result<PathBuf> {
@@ -99,6 +374,76 @@ fn config_path(app: &tauri::AppHandle) -> anyhow::Result<PathBuf> {
     Ok(dir.join("config.json"))
 }
 
+// Convert the persisted config to the runtime model used by the voice loop and LLM calls
+fn to_run_config(cfg: Config) -> models::Config {
+    models::Config {
+        porcupine_key: cfg.porcupine_key,
+        gemini_key: cfg.gemini_key,
+        elevenlabs_key: cfg.elevenlabs_key,
+        whisper_language: cfg.whisper_language,
+        context_window_expiration_seconds: cfg.context_window_expiration_seconds as u64,
+        default_microphone_index: cfg.default_microphone_index as usize,
+        default_microphone_name: cfg.default_microphone_name,
+        default_output_device_name: cfg.default_output_device_name,
+        tts_output_device_name: cfg.tts_output_device_name,
+        gemini_model: cfg.gemini_model,
+        elevenlabs_model: cfg.elevenlabs_model,
+        voice_id: cfg.voice_id,
+        llm_system_prompt: cfg.llm_system_prompt,
+        vad_mode: cfg.vad_mode,
+        wwd_sensitivity: cfg.wwd_sensitivity,
+        frame_duration_ms: cfg.frame_duration_ms as usize,
+        silence_threshold_ms: cfg.silence_threshold_ms.max(0) as usize,
+        speech_trigger_frames: cfg.speech_trigger_frames as usize,
+        frame_length_wwd: cfg.frame_length_wwd as usize,
+        redaction: cfg.redaction,
+        generation: cfg.generation,
+        debug_capture: cfg.debug_capture,
+        endpointing: cfg.endpointing,
+        wake: cfg.wake,
+        interrupt: cfg.interrupt,
+        personas: cfg.personas,
+        output_volume: cfg.output_volume,
+        tts_quota_warning: cfg.tts_quota_warning,
+        tts_provider: cfg.tts_provider,
+        memory: cfg.memory,
+        command_feedback: cfg.command_feedback,
+        meeting: cfg.meeting,
+        utility_model: cfg.utility_model,
+        history_format: cfg.history_format,
+        crash_reporting: cfg.crash_reporting,
+        image_generation: cfg.image_generation,
+        web_fetch: cfg.web_fetch,
+        pdf: cfg.pdf,
+        context_budget: cfg.context_budget,
+        safety: cfg.safety,
+        dual_response: cfg.dual_response,
+        tts_normalization: cfg.tts_normalization,
+        copy_blocks: cfg.copy_blocks,
+        satellite: cfg.satellite,
+        thread_priority: cfg.thread_priority,
+        input_source: cfg.input_source,
+        prompt_variables: cfg.prompt_variables,
+        world_clock: cfg.world_clock,
+        finance: cfg.finance,
+        sports: cfg.sports,
+        wikipedia: cfg.wikipedia,
+        packages: cfg.packages,
+        commute: cfg.commute,
+        cooking: cfg.cooking,
+        ambient: cfg.ambient,
+        briefing: cfg.briefing,
+        voice_memos: cfg.voice_memos,
+        pomodoro: cfg.pomodoro,
+        network: cfg.network,
+        processes: cfg.processes,
+        terminal: cfg.terminal,
+        github: cfg.github,
+        docker: cfg.docker,
+        kubernetes: cfg.kubernetes,
+    }
+}
+
 fn copy_bundled_assets(app: &tauri::AppHandle) -> Result<(), String> {
     let roaming_dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
 
@@ -147,35 +492,27 @@ fn copy_bundled_assets(app: &tauri::AppHandle) -> Result<(), String> {
     Ok(())
 }
 
-#[tauri::command]
-fn cmd_load_config(app: tauri::AppHandle) -> Result<Config, String> {
-    // Copy bundled assets to roaming directory
-    let _ = copy_bundled_assets(&app);
-
-    let cfg_path = config_path(&app).map_err(|e| e.to_string())?;
-    println!("[DEBUG] Config path: {:?}", cfg_path);
-
-    if cfg_path.exists() {
-        println!("[DEBUG] Loading existing config from: {:?}", cfg_path);
-        let s = fs::read_to_string(&cfg_path).map_err(|e| e.to_string())?;
-        let cfg: Config = serde_json::from_str(&s).map_err(|e| e.to_string())?;
-
-        // Compute defaults but do NOT override if user already set values
-        let roaming_dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
-        println!("[DEBUG] Roaming directory: {:?}", roaming_dir);
-        // Paths are hard-coded; no longer in config
-
-        // No path migration necessary; runtime will resolve paths
-
-        println!("[DEBUG] Final config loaded (paths managed by runtime)");
-
-        return Ok(cfg);
+// Upgrade fields from older config versions in place. Returns true if anything changed.
+fn migrate_config(cfg: &mut Config) -> bool {
+    let mut changed = false;
+    if let Some(secs) = cfg.silence_threshold_seconds.take() {
+        if cfg.silence_threshold_ms <= 0 {
+            cfg.silence_threshold_ms = secs.max(0) * 1000;
+        }
+        changed = true;
     }
+    if cfg.silence_threshold_ms <= 0 {
+        cfg.silence_threshold_ms = 1000;
+        changed = true;
+    }
+    if github::store_token(&mut cfg.github, false) {
+        changed = true;
+    }
+    changed
+}
 
-    // Create default config if none exists
-    println!("[DEBUG] No config found in roaming directory, creating default config");
-    let _roaming_dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
-
+// Built-in defaults, overlaid with the bundled config.json when the installer ships one
+fn initial_config(app: &tauri::AppHandle) -> Config {
     // Create a default config with all the necessary fields
     let mut cfg = Config {
         porcupine_key: String::new(),
@@ -185,6 +522,7 @@ fn cmd_load_config(app: tauri::AppHandle) -> Result<Config, String> {
         default_microphone_index: 0,
         default_microphone_name: None,
         default_output_device_name: None,
+        tts_output_device_name: None,
         gemini_model: "gemini-2.5-flash".to_string(),
         elevenlabs_model: "eleven_flash_v2_5".to_string(),
         voice_id: "hU1ratPhBTZNviWitzAh".to_string(),
@@ -193,12 +531,70 @@ fn cmd_load_config(app: tauri::AppHandle) -> Result<Config, String> {
         wwd_sensitivity: 0.8,
         context_window_expiration_seconds: 1800,
         frame_duration_ms: 30,
-        silence_threshold_seconds: 1,
+        silence_threshold_seconds: None,
+        silence_threshold_ms: 1000,
         speech_trigger_frames: 8,
         frame_length_wwd: 512,
         dock_position: Some("right".to_string()),
         input_mode: Some("audio".to_string()),
         theme: Some("emerald".to_string()),
+        redaction: redact::RedactionConfig::default(),
+        debug_capture: captures::CaptureConfig::default(),
+        endpointing: get_text::EndpointingConfig::default(),
+        wake: get_text::WakeConfig::default(),
+        interrupt: get_text::InterruptConfig::default(),
+        personas: Vec::new(),
+        active_persona: None,
+        output_volume: None,
+        profiles: Vec::new(),
+        active_profile: None,
+        auto_select_profile: false,
+        tts_quota_warning: tts::QuotaWarningConfig::default(),
+        tts_provider: None,
+        memory: memory::MemoryConfig::default(),
+        command_feedback: transform_text::CommandFeedbackConfig::default(),
+        meeting: meeting::MeetingConfig::default(),
+        utility_model: None,
+        history_format: None,
+        crash_reporting: crash::CrashReportingConfig::default(),
+        hotkeys: hotkeys::HotkeyConfig::default(),
+        quick_ask: quick_ask::QuickAskConfig::default(),
+        image_generation: image_gen::ImageGenerationConfig::default(),
+        web_fetch: web_fetch::WebFetchConfig::default(),
+        pdf: pdf::PdfConfig::default(),
+        context_budget: context_budget::ContextBudgetConfig::default(),
+        generation: send_to_llm::GenerationSettings::default(),
+        safety: safety::SafetyConfig::default(),
+        dual_response: false,
+        tts_normalization: tts_normalize::TtsNormalizationConfig::default(),
+        copy_blocks: transform_text::CopyBlocksConfig::default(),
+        sync: sync::SyncConfig::default(),
+        local_api: local_api::LocalApiConfig::default(),
+        satellite: satellite::SatelliteConfig::default(),
+        wyoming: wyoming::WyomingConfig::default(),
+        app_triggers: app_triggers::AppTriggersConfig::default(),
+        game_mode: game_mode::GameModeConfig::default(),
+        thread_priority: thread_priority::ThreadPriorityConfig::default(),
+        input_source: audio_input::InputSourceConfig::default(),
+        prompt_variables: BTreeMap::new(),
+        world_clock: world_clock::WorldClockConfig::default(),
+        finance: finance::FinanceConfig::default(),
+        sports: sports::SportsConfig::default(),
+        wikipedia: wikipedia::WikipediaConfig::default(),
+        packages: packages::PackagesConfig::default(),
+        commute: commute::CommuteConfig::default(),
+        cooking: cooking::CookingConfig::default(),
+        ambient: ambient::AmbientConfig::default(),
+        briefing: briefing::BriefingConfig::default(),
+        voice_memos: voice_memos::VoiceMemosConfig::default(),
+        pomodoro: pomodoro::PomodoroConfig::default(),
+        presence: presence::PresenceConfig::default(),
+        network: network::NetworkConfig::default(),
+        processes: processes::ProcessesConfig::default(),
+        terminal: terminal::TerminalConfig::default(),
+        github: github::GithubConfig::default(),
+        docker: docker::DockerConfig::default(),
+        kubernetes: kubernetes::KubernetesConfig::default(),
     };
 
     // Try to load from bundled resource first to get any additional defaults
@@ -223,6 +619,7 @@ fn cmd_load_config(app: tauri::AppHandle) -> Result<Config, String> {
                             cfg.default_microphone_index = bundled_cfg.default_microphone_index;
                             cfg.default_microphone_name = bundled_cfg.default_microphone_name;
                             cfg.default_output_device_name = bundled_cfg.default_output_device_name;
+                            cfg.tts_output_device_name = bundled_cfg.tts_output_device_name;
                             cfg.gemini_model = bundled_cfg.gemini_model;
                             cfg.elevenlabs_model = bundled_cfg.elevenlabs_model;
                             cfg.voice_id = bundled_cfg.voice_id;
@@ -233,11 +630,69 @@ fn cmd_load_config(app: tauri::AppHandle) -> Result<Config, String> {
                                 bundled_cfg.context_window_expiration_seconds;
                             cfg.frame_duration_ms = bundled_cfg.frame_duration_ms;
                             cfg.silence_threshold_seconds = bundled_cfg.silence_threshold_seconds;
+                            cfg.silence_threshold_ms = bundled_cfg.silence_threshold_ms;
                             cfg.speech_trigger_frames = bundled_cfg.speech_trigger_frames;
                             cfg.frame_length_wwd = bundled_cfg.frame_length_wwd;
                             cfg.dock_position = bundled_cfg.dock_position;
                             cfg.input_mode = bundled_cfg.input_mode;
                             cfg.theme = bundled_cfg.theme;
+                            cfg.redaction = bundled_cfg.redaction;
+                            cfg.debug_capture = bundled_cfg.debug_capture;
+                            cfg.endpointing = bundled_cfg.endpointing;
+                            cfg.wake = bundled_cfg.wake;
+                            cfg.interrupt = bundled_cfg.interrupt;
+                            cfg.personas = bundled_cfg.personas;
+                            cfg.active_persona = bundled_cfg.active_persona;
+                            cfg.output_volume = bundled_cfg.output_volume;
+                            cfg.profiles = bundled_cfg.profiles;
+                            cfg.active_profile = bundled_cfg.active_profile;
+                            cfg.auto_select_profile = bundled_cfg.auto_select_profile;
+                            cfg.tts_quota_warning = bundled_cfg.tts_quota_warning;
+                            cfg.tts_provider = bundled_cfg.tts_provider;
+                            cfg.memory = bundled_cfg.memory;
+                            cfg.command_feedback = bundled_cfg.command_feedback;
+                            cfg.meeting = bundled_cfg.meeting;
+                            cfg.utility_model = bundled_cfg.utility_model;
+                            cfg.history_format = bundled_cfg.history_format;
+                            cfg.crash_reporting = bundled_cfg.crash_reporting;
+                            cfg.hotkeys = bundled_cfg.hotkeys;
+                            cfg.quick_ask = bundled_cfg.quick_ask;
+                            cfg.image_generation = bundled_cfg.image_generation;
+                            cfg.web_fetch = bundled_cfg.web_fetch;
+                            cfg.pdf = bundled_cfg.pdf;
+                            cfg.context_budget = bundled_cfg.context_budget;
+                            cfg.generation = bundled_cfg.generation;
+                            cfg.safety = bundled_cfg.safety;
+                            cfg.dual_response = bundled_cfg.dual_response;
+                            cfg.tts_normalization = bundled_cfg.tts_normalization;
+                            cfg.copy_blocks = bundled_cfg.copy_blocks;
+                            cfg.sync = bundled_cfg.sync;
+                            cfg.local_api = bundled_cfg.local_api;
+                            cfg.satellite = bundled_cfg.satellite;
+                            cfg.wyoming = bundled_cfg.wyoming;
+                            cfg.app_triggers = bundled_cfg.app_triggers;
+                            cfg.game_mode = bundled_cfg.game_mode;
+                            cfg.thread_priority = bundled_cfg.thread_priority;
+                            cfg.input_source = bundled_cfg.input_source;
+                            cfg.prompt_variables = bundled_cfg.prompt_variables;
+                            cfg.world_clock = bundled_cfg.world_clock;
+                            cfg.finance = bundled_cfg.finance;
+                            cfg.sports = bundled_cfg.sports;
+                            cfg.wikipedia = bundled_cfg.wikipedia;
+                            cfg.packages = bundled_cfg.packages;
+                            cfg.commute = bundled_cfg.commute;
+                            cfg.cooking = bundled_cfg.cooking;
+                            cfg.ambient = bundled_cfg.ambient;
+                            cfg.briefing = bundled_cfg.briefing;
+                            cfg.voice_memos = bundled_cfg.voice_memos;
+                            cfg.pomodoro = bundled_cfg.pomodoro;
+                            cfg.presence = bundled_cfg.presence;
+                            cfg.network = bundled_cfg.network;
+                            cfg.processes = bundled_cfg.processes;
+                            cfg.terminal = bundled_cfg.terminal;
+                            cfg.github = bundled_cfg.github;
+                            cfg.docker = bundled_cfg.docker;
+                            cfg.kubernetes = bundled_cfg.kubernetes;
                         }
                         Err(e) => println!("[DEBUG] Failed to parse bundled config JSON: {}", e),
                     }
@@ -251,23 +706,88 @@ fn cmd_load_config(app: tauri::AppHandle) -> Result<Config, String> {
         println!("[DEBUG] Failed to resolve bundled config path");
     }
 
+    // Bundled configs may still use legacy fields
+    migrate_config(&mut cfg);
+
+    cfg
+}
+
+#[tauri::command]
+fn cmd_load_config(app: tauri::AppHandle) -> Result<Config, String> {
+    // Copy bundled assets to roaming directory
+    let _ = copy_bundled_assets(&app);
+
+    let cfg_path = config_path(&app).map_err(|e| e.to_string())?;
+    println!("[DEBUG] Config path: {:?}", cfg_path);
+
+    if cfg_path.exists() {
+        println!("[DEBUG] Loading existing config from: {:?}", cfg_path);
+        let s = fs::read_to_string(&cfg_path).map_err(|e| e.to_string())?;
+        let mut cfg: Config = serde_json::from_str(&s).map_err(|e| e.to_string())?;
+        if migrate_config(&mut cfg) {
+            println!("[DEBUG] Migrated legacy config fields, saving");
+            let s = serde_json::to_string_pretty(&cfg).map_err(|e| e.to_string())?;
+            history::atomic_write(&cfg_path, s.as_bytes()).map_err(|e| e.to_string())?;
+        }
+
+        // Compute defaults but do NOT override if user already set values
+        let roaming_dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+        println!("[DEBUG] Roaming directory: {:?}", roaming_dir);
+        // Paths are hard-coded; no longer in config
+
+        // No path migration necessary; runtime will resolve paths
+
+        println!("[DEBUG] Final config loaded (paths managed by runtime)");
+
+        return Ok(config::layer(cfg));
+    }
+
+    // Create default config if none exists
+    println!("[DEBUG] No config found in roaming directory, creating default config");
+    let _roaming_dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+
+    let cfg = initial_config(&app);
+
     // Paths are resolved at runtime; nothing to set here
 
     // Save the default config to the roaming directory
     println!("[DEBUG] Saving default config to: {:?}", cfg_path);
     let s = serde_json::to_string_pretty(&cfg).map_err(|e| e.to_string())?;
-    fs::write(&cfg_path, s).map_err(|e| e.to_string())?;
+    history::atomic_write(&cfg_path, s.as_bytes()).map_err(|e| e.to_string())?;
 
     println!("[DEBUG] Created default config (paths managed by runtime)");
 
-    Ok(cfg)
+    Ok(config::layer(cfg))
 }
 
 #[tauri::command]
-fn cmd_save_config(app: tauri::AppHandle, config: Config) -> Result<(), String> {
+fn cmd_save_config(app: tauri::AppHandle, mut config: Config) -> Result<(), String> {
     let cfg_path = config_path(&app).map_err(|e| e.to_string())?;
-    let s = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
-    fs::write(cfg_path, s).map_err(|e| e.to_string())
+    let had_token = fs::read_to_string(&cfg_path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<Config>(&s).ok())
+        .is_some_and(|cfg| cfg.github.token_set);
+    github::store_token(&mut config.github, had_token);
+    let mut value = serde_json::to_value(&config).map_err(|e| e.to_string())?;
+
+    // Env/CLI overrides are per-run; keep whatever config.json had for those keys
+    if !config::overrides().is_empty() {
+        let on_disk = fs::read_to_string(&cfg_path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok());
+        if let (Some(map), Some(serde_json::Value::Object(disk))) =
+            (value.as_object_mut(), on_disk.as_ref())
+        {
+            config::strip_overrides(map, disk);
+        }
+    }
+
+    let s = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+    history::atomic_write(&cfg_path, s.as_bytes()).map_err(|e| e.to_string())?;
+
+    // pick up changed shortcuts without a restart
+    hotkeys::register(&app, &config.hotkeys);
+    Ok(())
 }
 
 #[tauri::command]
@@ -287,7 +807,7 @@ fn cmd_resolve_resource_path(app: tauri::AppHandle, relative: String) -> Result<
     }
 }
 
-fn history_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+pub(crate) fn history_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
     let history = dir.join("history");
     std::fs::create_dir_all(&history).map_err(|e| e.to_string())?;
@@ -303,7 +823,7 @@ fn cmd_list_history_files(app: tauri::AppHandle) -> Result<Vec<String>, String>
             let path = entry.path();
             if path.is_file() {
                 if let Some(ext) = path.extension() {
-                    if ext.eq_ignore_ascii_case("json") {
+                    if ext.eq_ignore_ascii_case("json") || ext.eq_ignore_ascii_case("jsonl") {
                         let meta = entry.metadata().map_err(|e| e.to_string())?;
                         let modified = meta.modified().unwrap_or(std::time::SystemTime::UNIX_EPOCH);
                         let name = path.file_name().unwrap().to_string_lossy().to_string();
@@ -313,7 +833,7 @@ fn cmd_list_history_files(app: tauri::AppHandle) -> Result<Vec<String>, String>
             }
         }
     }
-    files.sort_by(|a, b| b.0.cmp(&a.0));
+    files.sort_by_key(|f| std::cmp::Reverse(f.0));
     Ok(files.into_iter().map(|(_, n)| n).collect())
 }
 
@@ -321,34 +841,88 @@ fn cmd_list_history_files(app: tauri::AppHandle) -> Result<Vec<String>, String>
 fn cmd_create_conversation(app: tauri::AppHandle) -> Result<String, String> {
     let history = history_dir(&app)?;
     let ts = chrono::Utc::now().format("%Y-%m-%d_%H-%M-%S").to_string();
-    let filename = format!("New Conversation - {}.json", ts);
+    let jsonl = cmd_load_config(app.clone())
+        .map(|c| c.history_format.as_deref() == Some("jsonl"))
+        .unwrap_or(false);
+    let (filename, empty) = if jsonl {
+        (format!("New Conversation - {}.jsonl", ts), "")
+    } else {
+        (format!("New Conversation - {}.json", ts), "[]")
+    };
     let path = history.join(&filename);
-    std::fs::write(&path, "[]").map_err(|e| e.to_string())?;
+    history::atomic_write(&path, empty.as_bytes()).map_err(|e| e.to_string())?;
     Ok(filename)
 }
 
+pub(crate) fn read_turns(app: &tauri::AppHandle, filename: &str) -> Result<Vec<TurnDto>, String> {
+    let history = history_dir(app)?;
+    history::load(&history.join(filename))
+}
+
+pub(crate) fn write_turns(
+    app: &tauri::AppHandle,
+    filename: &str,
+    turns: &[TurnDto],
+) -> Result<(), String> {
+    let history = history_dir(app)?;
+    history::save(&history.join(filename), turns)
+}
+
+// Keep sidecar data (settings, branches) attached to a conversation across renames
+fn on_conversation_renamed(app: &tauri::AppHandle, old: &str, new: &str) {
+    conversation_settings::rename(app, old, new);
+    branches::rename(app, old, new);
+    attachments::rename(app, old, new);
+}
+
+// All turns, or `limit` turns starting at `offset` (0 is the oldest), so long chats can
+// be loaded from the end a page at a time while scrolling up
 #[tauri::command]
-fn cmd_read_conversation(app: tauri::AppHandle, filename: String) -> Result<Vec<TurnDto>, String> {
+fn cmd_read_conversation(
+    app: tauri::AppHandle,
+    filename: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<Vec<TurnDto>, String> {
+    if offset.is_none() && limit.is_none() {
+        return read_turns(&app, &filename);
+    }
     let history = history_dir(&app)?;
-    let path = history.join(&filename);
-    let s = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
-    let turns: Vec<TurnDto> = serde_json::from_str(&s).map_err(|e| e.to_string())?;
-    Ok(turns)
+    history::load_range(
+        &history.join(&filename),
+        offset.unwrap_or(0),
+        limit.unwrap_or(usize::MAX),
+    )
+}
+
+#[tauri::command]
+fn cmd_get_turn_count(app: tauri::AppHandle, filename: String) -> Result<usize, String> {
+    let history = history_dir(&app)?;
+    history::count(&history.join(&filename))
 }
 
 #[tauri::command]
 fn cmd_append_turn(app: tauri::AppHandle, filename: String, turn: TurnDto) -> Result<(), String> {
     let history = history_dir(&app)?;
-    let path = history.join(&filename);
-    let mut turns: Vec<TurnDto> = if path.exists() {
-        let s = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&s).unwrap_or_default()
-    } else {
-        Vec::new()
-    };
-    turns.push(turn);
-    let s = serde_json::to_string_pretty(&turns).map_err(|e| e.to_string())?;
-    std::fs::write(&path, s).map_err(|e| e.to_string())
+    history::append(&history.join(&filename), &turn)
+}
+
+// Merge late-arriving meta (e.g. latency after playback) into an already persisted turn
+#[tauri::command]
+fn cmd_update_turn_meta(
+    app: tauri::AppHandle,
+    filename: String,
+    created_at: i64,
+    meta: serde_json::Map<String, serde_json::Value>,
+) -> Result<(), String> {
+    let mut turns = read_turns(&app, &filename)?;
+    let turn = turns
+        .iter_mut()
+        .rev()
+        .find(|t| t.created_at == created_at)
+        .ok_or_else(|| "Turn not found".to_string())?;
+    turn.meta.get_or_insert_with(Default::default).extend(meta);
+    write_turns(&app, &filename, &turns)
 }
 
 #[tauri::command]
@@ -358,6 +932,9 @@ fn cmd_delete_conversation(app: tauri::AppHandle, filename: String) -> Result<()
     if path.exists() {
         std::fs::remove_file(&path).map_err(|e| e.to_string())?;
     }
+    conversation_settings::remove(&app, &filename);
+    branches::remove(&app, &filename);
+    attachments::remove(&app, &filename);
     Ok(())
 }
 
@@ -371,14 +948,20 @@ fn build_ctx_text_from_conversation(
     if !path.exists() {
         return Ok(String::new());
     }
-    let s = std::fs::read_to_string(&path)?;
-    let turns: Vec<TurnDto> = serde_json::from_str(&s).unwrap_or_default();
+    let mut turns = history::load(&path).unwrap_or_default();
+    let cutoff = conversation_settings::context_cutoff(app, filename);
+    turns.retain(|t| t.created_at >= cutoff);
+    Ok(format_ctx_text(&turns))
+}
+
+// Format the last 12 turns as "ROLE: content" lines for the system prompt
+fn format_ctx_text(turns: &[TurnDto]) -> String {
     let start = turns.len().saturating_sub(12);
     let mut out = String::new();
     for t in turns.iter().skip(start) {
         out.push_str(&format!("{}: {}\n", t.role.to_uppercase(), t.content));
     }
-    Ok(out)
+    out
 }
 
 #[tauri::command]
@@ -397,20 +980,60 @@ struct TitleResult {
     title: String,
 }
 
+// device names Windows refuses as a file name, with or without an extension
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+// keeps room for " (n) - <timestamp>.json" inside the usual 255 byte limit
+const MAX_TITLE_CHARS: usize = 120;
+
 fn sanitize_title_for_filename(title: &str) -> String {
     let mut s = title.trim().to_string();
     // Remove characters invalid on Windows filesystem
     for ch in ['\\', '/', ':', '*', '?', '"', '<', '>', '|'] {
         s = s.replace(ch, " ");
     }
+    s.retain(|c| !c.is_control());
     // Collapse whitespace
     let collapsed = s.split_whitespace().collect::<Vec<_>>().join(" ");
-    collapsed
+    let mut title: String = collapsed.chars().take(MAX_TITLE_CHARS).collect();
+    // Windows drops trailing dots and spaces, which would change the name under us
+    title = title.trim_end_matches(['.', ' ']).to_string();
+    let stem = title.split('.').next().unwrap_or("").trim().to_uppercase();
+    if WINDOWS_RESERVED_NAMES.contains(&stem.as_str()) {
+        title = format!("{}_", title);
+    }
+    title
+}
+
+// "<title> - <ts>.json", or "<title> (2) - <ts>.json" and so on (keeping `.jsonl` for JSONL chats) if that is taken by another chat.
+// Returns the filename together with the title it ended up with.
+fn unique_conversation_filename(
+    history: &std::path::Path,
+    title: &str,
+    ts: &str,
+    current: &str,
+) -> (String, String) {
+    let (_, extension) = history::split_extension(current);
+    let mut candidate_title = title.to_string();
+    let mut n = 2;
+    loop {
+        let candidate = format!("{} - {}.{}", candidate_title, ts, extension);
+        // renaming a chat to its own title is fine; case-insensitive filesystems see "a" and "A" as one file
+        let is_self = candidate.eq_ignore_ascii_case(current);
+        if is_self || !history.join(&candidate).exists() {
+            return (candidate, candidate_title);
+        }
+        candidate_title = format!("{} ({})", title, n);
+        n += 1;
+    }
 }
 
 fn extract_timestamp_stem(name: &str) -> String {
     // expects something like "<title> - YYYY-MM-DD_HH-MM-SS.json"
-    let no_ext = name.trim_end_matches(".json");
+    let (no_ext, _) = history::split_extension(name);
     if let Some(idx) = no_ext.rfind(" - ") {
         return no_ext[idx + 3..].to_string();
     }
@@ -425,12 +1048,11 @@ async fn cmd_generate_and_rename_conversation(
     // Read turns to build seed
     let history = history_dir(&app)?;
     let path = history.join(&filename);
-    let s = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
-    let turns: Vec<TurnDto> = serde_json::from_str(&s).unwrap_or_default();
+    let turns = history::load(&path)?;
     let mut seed = String::new();
     for t in turns.iter().take(4) {
         seed.push_str(&t.content);
-        seed.push_str("\n");
+        seed.push('\n');
     }
     if seed.is_empty() {
         seed = "New conversation".to_string();
@@ -438,31 +1060,18 @@ async fn cmd_generate_and_rename_conversation(
 
     // Load config to access API key
     let cfg = cmd_load_config(app.clone()).map_err(|e| e.to_string())?;
-    let run_config = crate::models::Config {
-        porcupine_key: cfg.porcupine_key,
-        gemini_key: cfg.gemini_key,
-        elevenlabs_key: cfg.elevenlabs_key,
-        whisper_language: cfg.whisper_language,
-        context_window_expiration_seconds: cfg.context_window_expiration_seconds as u64,
-        default_microphone_index: cfg.default_microphone_index as usize,
-        default_microphone_name: cfg.default_microphone_name.clone(),
-        default_output_device_name: cfg.default_output_device_name.clone(),
-        gemini_model: cfg.gemini_model,
-        elevenlabs_model: cfg.elevenlabs_model.clone(),
-        voice_id: cfg.voice_id,
-        llm_system_prompt: cfg.llm_system_prompt,
-        vad_mode: cfg.vad_mode,
-        wwd_sensitivity: cfg.wwd_sensitivity,
-        frame_duration_ms: cfg.frame_duration_ms as usize,
-        silence_threshold_seconds: cfg.silence_threshold_seconds as usize,
-        speech_trigger_frames: cfg.speech_trigger_frames as usize,
-        frame_length_wwd: cfg.frame_length_wwd as usize,
-    };
+    let run_config = to_run_config(cfg);
 
     let raw_title = crate::send_to_llm::generate_conversation_title(&seed, &run_config)
         .await
         .map_err(|e| e.to_string())?;
-    let title = sanitize_title_for_filename(&raw_title);
+    let mut title = sanitize_title_for_filename(&raw_title);
+    if title.is_empty() {
+        title = sanitize_title_for_filename(&crate::send_to_llm::fallback_title(&seed));
+    }
+    if title.is_empty() {
+        title = "Untitled chat".to_string();
+    }
 
     // Prevent AI from naming conversations "New Conversation" as it breaks the system
     if title.to_lowercase() == "new conversation" {
@@ -472,9 +1081,12 @@ async fn cmd_generate_and_rename_conversation(
         );
     }
     let ts = extract_timestamp_stem(&filename);
-    let new_filename = format!("{} - {}.json", title, ts);
-    let new_path = history.join(&new_filename);
-    std::fs::rename(&path, &new_path).map_err(|e| e.to_string())?;
+    let (new_filename, title) = unique_conversation_filename(&history, &title, &ts, &filename);
+    if new_filename != filename {
+        let new_path = history.join(&new_filename);
+        std::fs::rename(&path, &new_path).map_err(|e| e.to_string())?;
+        on_conversation_renamed(&app, &filename, &new_filename);
+    }
 
     Ok(TitleResult {
         new_filename,
@@ -500,20 +1112,130 @@ fn cmd_rename_conversation(
         return Err("Title 'New Conversation' is not allowed as it would break the system".into());
     }
 
+    if title.is_empty() {
+        return Err("Title is empty".into());
+    }
     let ts = extract_timestamp_stem(&filename);
-    let new_filename = format!("{} - {}.json", title, ts);
-    let new_path = history.join(&new_filename);
-    std::fs::rename(&path, &new_path).map_err(|e| e.to_string())?;
+    let (new_filename, title) = unique_conversation_filename(&history, &title, &ts, &filename);
+    if new_filename != filename {
+        let new_path = history.join(&new_filename);
+        std::fs::rename(&path, &new_path).map_err(|e| e.to_string())?;
+        on_conversation_renamed(&app, &filename, &new_filename);
+    }
     Ok(TitleResult {
         new_filename,
         title,
     })
 }
 
+#[tauri::command]
+async fn cmd_regenerate_last_response(
+    app: tauri::AppHandle,
+    filename: String,
+    model: Option<String>,
+    temperature: Option<f32>,
+) -> Result<Vec<TurnDto>, String> {
+    let turns = read_turns(&app, &filename)?;
+    let user_idx = turns
+        .iter()
+        .rposition(|t| t.role == "user")
+        .ok_or_else(|| "No user message to regenerate a response for".to_string())?;
+
+    let cfg = cmd_load_config(app.clone()).map_err(|e| e.to_string())?;
+    if cfg.gemini_key.trim().is_empty() {
+        return Err("Missing Gemini API key".into());
+    }
+    let mut run_config = to_run_config(cfg);
+    let persona = persona::apply_active(&app, &mut run_config);
+    if let Some(m) = model.filter(|m| !m.trim().is_empty()) {
+        run_config.gemini_model = m;
+    }
+    run_config.redaction.enabled = conversation_settings::redaction_enabled(
+        &app,
+        Some(&filename),
+        run_config.redaction.enabled,
+    );
+    conversation_settings::apply_generation(&app, Some(&filename), &mut run_config.generation);
+    if temperature.is_some() {
+        run_config.generation.temperature = temperature;
+    }
+
+    // Same context the original answer saw: everything before the last user turn
+    let mut ctx_text = format_ctx_text(&turns[..user_idx]);
+    ctx_text.insert_str(
+        0,
+        &memory::prompt_section(&app, &run_config, &turns[user_idx].content),
+    );
+    let answer = crate::send_to_llm::query_gemini(&turns[user_idx].content, &run_config, &ctx_text)
+        .await
+        .map_err(|e| e.to_string())?;
+    let answer =
+        crate::transform_text::if_contains_transform_post_llm(&app, &run_config, &answer).display;
+
+    let mut meta = serde_json::Map::new();
+    meta.insert("model".into(), run_config.gemini_model.clone().into());
+    if let Some(t) = run_config.generation.temperature {
+        meta.insert("temperature".into(), serde_json::json!(t));
+    }
+    if let Some(p) = persona {
+        meta.insert("persona".into(), p.into());
+    }
+    meta.insert("regenerated".into(), true.into());
+    let regenerated = TurnDto {
+        role: "assistant".into(),
+        content: answer,
+        created_at: chrono::Utc::now().timestamp_millis(),
+        meta: Some(meta),
+    };
+    branches::replace_tail(&app, &filename, user_idx + 1, vec![regenerated])
+}
+
+#[tauri::command]
+fn cmd_edit_turn(
+    app: tauri::AppHandle,
+    filename: String,
+    index: usize,
+    content: String,
+) -> Result<Vec<TurnDto>, String> {
+    let turns = read_turns(&app, &filename)?;
+    let original = turns
+        .get(index)
+        .ok_or_else(|| format!("Turn {} does not exist", index))?;
+    let mut meta = original.meta.clone().unwrap_or_default();
+    meta.insert("edited".into(), true.into());
+    let edited = TurnDto {
+        role: original.role.clone(),
+        content,
+        created_at: chrono::Utc::now().timestamp_millis(),
+        meta: Some(meta),
+    };
+    // Subsequent turns are kept as the previous branch variant
+    branches::replace_tail(&app, &filename, index, vec![edited])
+}
+
+#[tauri::command]
+fn cmd_list_branches(
+    app: tauri::AppHandle,
+    filename: String,
+) -> Result<Vec<branches::BranchSummary>, String> {
+    Ok(branches::list(&app, &filename))
+}
+
+#[tauri::command]
+fn cmd_switch_branch(
+    app: tauri::AppHandle,
+    filename: String,
+    turn_index: usize,
+    variant: usize,
+) -> Result<Vec<TurnDto>, String> {
+    branches::switch(&app, &filename, turn_index, variant)
+}
+
 pub struct JarvisState {
     is_running: Arc<AtomicBool>,
     handle: Mutex<Option<JoinHandle<()>>>,
     active_conversation: Mutex<Option<String>>,
+    active_persona: Mutex<Option<String>>,
 }
 
 #[tauri::command]
@@ -548,27 +1270,10 @@ fn cmd_start_jarvis(
     let new_handle = std::thread::spawn(move || {
         // Load config and start Jarvis
         if let Ok(config) = cmd_load_config(app.clone()) {
+            // Pick the device profile matching what's plugged in, if enabled
+            let config = profiles::auto_select(&app, config);
             // Convert Config to the format expected by run_jarvis
-            let run_config = models::Config {
-                porcupine_key: config.porcupine_key,
-                gemini_key: config.gemini_key,
-                elevenlabs_key: config.elevenlabs_key,
-                whisper_language: config.whisper_language,
-                context_window_expiration_seconds: config.context_window_expiration_seconds as u64,
-                default_microphone_index: config.default_microphone_index as usize,
-                default_microphone_name: config.default_microphone_name.clone(),
-                default_output_device_name: config.default_output_device_name.clone(),
-                gemini_model: config.gemini_model,
-                elevenlabs_model: config.elevenlabs_model,
-                voice_id: config.voice_id,
-                llm_system_prompt: config.llm_system_prompt,
-                vad_mode: config.vad_mode,
-                wwd_sensitivity: config.wwd_sensitivity,
-                frame_duration_ms: config.frame_duration_ms as usize,
-                silence_threshold_seconds: config.silence_threshold_seconds as usize,
-                speech_trigger_frames: config.speech_trigger_frames as usize,
-                frame_length_wwd: config.frame_length_wwd as usize,
-            };
+            let run_config = to_run_config(config);
 
             // Start the Jarvis process
             run_jarvis::start_jarvis(is_running_clone.clone(), run_config, app.clone());
@@ -614,98 +1319,233 @@ fn cmd_emit_state_change(app: tauri::AppHandle, state: JarvisStateEnum) {
 }
 
 #[tauri::command]
-fn cmd_emit_message(app: tauri::AppHandle, role: String, content: String) {
-    let message = serde_json::json!({
-        "role": role,
-        "content": content,
-        "createdAt": chrono::Utc::now().timestamp_millis()
-    });
-    let _ = app.emit("new-message", message);
+fn cmd_emit_message(app: tauri::AppHandle, role: Role, content: String) {
+    events::emit(&app, NewMessage::new(role, content));
 }
 
 #[tauri::command]
 async fn cmd_send_text(app: tauri::AppHandle, prompt: String) -> Result<String, String> {
+    send_text(app, prompt, true).await
+}
+
+// `local` is false for commands from other devices (see satellite.rs): they must not
+// answer, or open, a question that asks for a yes here, and can't read or change this
+// machine's clipboard, screenshots or files
+pub(crate) async fn send_text(
+    app: tauri::AppHandle,
+    prompt: String,
+    local: bool,
+) -> Result<String, String> {
     // Emit user message immediately for snappy UI
-    cmd_emit_message(app.clone(), "user".into(), prompt.clone());
+    let user_created_at = chrono::Utc::now().timestamp_millis();
+    events::emit(&app, NewMessage::new(Role::User, &prompt).at(user_created_at));
 
     // Load config and map to runtime model
     let cfg = cmd_load_config(app.clone()).map_err(|e| e.to_string())?;
     if cfg.gemini_key.trim().is_empty() {
         cmd_emit_message(
             app.clone(),
-            "system".into(),
+            Role::System,
             "Please enter your Gemini API key in Settings > API Keys.".into(),
         );
         return Err("Missing Gemini API key".into());
     }
-    let run_config = crate::models::Config {
-        porcupine_key: cfg.porcupine_key,
-        gemini_key: cfg.gemini_key,
-        elevenlabs_key: cfg.elevenlabs_key,
-        whisper_language: cfg.whisper_language,
-        context_window_expiration_seconds: cfg.context_window_expiration_seconds as u64,
-        default_microphone_index: cfg.default_microphone_index as usize,
-        default_microphone_name: cfg.default_microphone_name.clone(),
-        default_output_device_name: cfg.default_output_device_name.clone(),
-        gemini_model: cfg.gemini_model,
-        elevenlabs_model: cfg.elevenlabs_model.clone(),
-        voice_id: cfg.voice_id,
-        llm_system_prompt: cfg.llm_system_prompt,
-        vad_mode: cfg.vad_mode,
-        wwd_sensitivity: cfg.wwd_sensitivity,
-        frame_duration_ms: cfg.frame_duration_ms as usize,
-        silence_threshold_seconds: cfg.silence_threshold_seconds as usize,
-        speech_trigger_frames: cfg.speech_trigger_frames as usize,
-        frame_length_wwd: cfg.frame_length_wwd as usize,
-    };
+    let mut run_config = to_run_config(cfg);
+    if !local {
+        run_config.image_generation.copy_to_clipboard = false;
+    }
+
+    // "switch to <name> mode" changes the active persona without asking the LLM
+    if let Some(p) = persona::match_switch_command(&prompt, &run_config.personas) {
+        let name = p.name.clone();
+        persona::switch_by_voice(&app, p)?;
+        let reply = format!("Switched to {} mode.", name);
+        events::emit(&app, NewMessage::new(Role::Assistant, &reply).with("persona", name.as_str()));
+        return Ok(reply);
+    }
+    if local {
+        // "forget this conversation" and friends ask for confirmation first
+        if let Some(reply) = forget::handle(&app, &prompt, &run_config.whisper_language) {
+            events::emit(&app, NewMessage::new(Role::Assistant, &reply));
+            return Ok(reply);
+        }
+        // a typed "yes" answers a question a voice command asked
+        if let Some(reply) = confirm::handle(&prompt).await {
+            events::emit(&app, NewMessage::new(Role::Assistant, &reply));
+            return Ok(reply);
+        }
+    }
+
+    // "summarize the last 10 minutes" of the running meeting transcript
+    if let Some(minutes) = meeting::match_summary_request(&prompt).filter(|_| meeting::is_active()) {
+        let reply = meeting::summarize_last(&run_config, minutes).await;
+        events::emit(&app, NewMessage::new(Role::Assistant, &reply));
+        return Ok(reply);
+    }
+
+    // "summarize my clipboard" works in text mode too
+    let triggers = triggers::load(&app);
+    if local
+        && triggers
+            .summarize_clipboard
+            .matches(&prompt, &run_config.whisper_language)
+    {
+        let reply = clipboard_tools::summarize_reply(&run_config).await;
+        clipboard_tools::respond(&app, &run_config, &reply, false).await;
+        return Ok(reply);
+    }
+    if let Some(description) = image_gen::match_request(&prompt) {
+        return Ok(image_gen::handle(&app, &run_config, &description).await);
+    }
+    if let Some(instruction) = clipboard_tools::match_rewrite_command(&prompt).filter(|_| local) {
+        let reply = clipboard_tools::rewrite_and_announce(&app, &run_config, &instruction, false).await;
+        return Ok(reply);
+    }
+
+    // "forget that" removes one long-term memory
+    if let Some(description) = memory::match_forget_command(&prompt) {
+        let reply = memory::forget_one(&app, &description);
+        events::emit(&app, NewMessage::new(Role::Assistant, &reply));
+        return Ok(reply);
+    }
+    let persona = persona::apply_active(&app, &mut run_config);
 
     // Optional text transforms (clipboard, etc.)
-    let eleven_model = match run_config.elevenlabs_model.as_str() {
-        "eleven_multilingual_v2" => ElevenModel::ElevenMultilingualV2,
-        "eleven_flash_v2_5" => ElevenModel::ElevenFlashV2_5,
-        "eleven_turbo_v2_5" => ElevenModel::ElevenTurboV2_5,
-        _ => ElevenModel::ElevenMultilingualV2,
+    let eleven_model = models::elevenlabs_model_from_id(&run_config.elevenlabs_model);
+    let transformed = if local {
+        crate::transform_text::if_contains_transform(&prompt, eleven_model)
+    } else {
+        prompt.clone()
     };
-    let transformed = crate::transform_text::if_contains_transform(&prompt, eleven_model);
 
     // Build context from active conversation selection
-    let ctx_text = {
-        let state = app.state::<JarvisState>();
-        let current = state.active_conversation.lock().unwrap().clone();
-        if let Some(fname) = current {
-            build_ctx_text_from_conversation(&app, &fname).unwrap_or_default()
-        } else {
-            String::new()
+    let current = app
+        .state::<JarvisState>()
+        .active_conversation
+        .lock()
+        .unwrap()
+        .clone();
+    let mut ctx_text = if let Some(fname) = current.as_deref() {
+        build_ctx_text_from_conversation(&app, fname).unwrap_or_default()
+    } else {
+        String::new()
+    };
+    ctx_text.insert_str(0, &memory::prompt_section(&app, &run_config, &prompt));
+
+    // Keep pasted images with the conversation so they can be shown again later
+    if let Some(fname) = current.as_deref() {
+        if let Some(image) = attachments::save_prompt_image(&app, fname, user_created_at, &transformed) {
+            events::emit(
+                &app,
+                MessageMeta::new(Role::User, user_created_at)
+                    .with("attachments", serde_json::json!([image])),
+            );
         }
+    }
+
+    // Per-conversation redaction and generation settings override the global ones
+    run_config.redaction.enabled = conversation_settings::redaction_enabled(
+        &app,
+        current.as_deref(),
+        run_config.redaction.enabled,
+    );
+    conversation_settings::apply_generation(&app, current.as_deref(), &mut run_config.generation);
+
+    // Query LLM with selected chat context; local PDFs and links only for local input
+    let result = if local {
+        crate::send_to_llm::query_gemini(&transformed, &run_config, &ctx_text).await
+    } else {
+        crate::send_to_llm::query_gemini_text(&transformed, &run_config, &ctx_text).await
+    };
+    let mut answer = match result {
+        Ok(answer) => answer,
+        Err(e) => match crate::send_to_llm::Unanswered::from_error(&e) {
+            Some(unanswered) => {
+                unanswered.emit(&app);
+                return Ok(unanswered.message);
+            }
+            None => return Err(e.to_string()),
+        },
     };
+    stats::record_interaction(false);
 
-    // Query LLM with selected chat context
-    let mut answer = crate::send_to_llm::query_gemini(&transformed, &run_config, &ctx_text)
-        .await
-        .map_err(|e| e.to_string())?;
+    // Learn any new user facts without holding up the reply
+    tauri::async_runtime::spawn(memory::learn_from(app.clone(), run_config.clone(), prompt.clone()));
 
     // Post-transform (copy blocks, etc.)
-    answer = crate::transform_text::if_contains_transform_post_llm(&answer);
-    answer = answer.trim().to_string();
+    answer = if local {
+        crate::transform_text::if_contains_transform_post_llm(&app, &run_config, &answer).display
+    } else {
+        crate::transform_text::strip_copy_tags(&answer)
+    };
 
-    // Emit assistant message
-    cmd_emit_message(app.clone(), "assistant".into(), answer.clone());
+    // Emit assistant message with the model and persona that produced it
+    let mut meta = serde_json::Map::new();
+    meta.insert("model".into(), run_config.gemini_model.clone().into());
+    if let Some(p) = persona {
+        meta.insert("persona".into(), p.into());
+    }
+    events::emit(&app, NewMessage::new(Role::Assistant, &answer).with_meta(meta));
 
     Ok(answer)
 }
 
+// Overrides for a one-off question; unset fields use the settings
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct AskOnceOptions {
+    system_prompt: Option<String>,
+    model: Option<String>,
+    generation: Option<send_to_llm::GenerationSettings>,
+}
+
+// Answers a prompt without touching any conversation: no history is read or written,
+// nothing is learned into memory and no chat events are emitted
+#[tauri::command]
+async fn cmd_ask_once(
+    app: tauri::AppHandle,
+    prompt: String,
+    options: Option<AskOnceOptions>,
+) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    let mut run_config = to_run_config(cmd_load_config(app.clone())?);
+    if run_config.gemini_key.trim().is_empty() {
+        return Err("Missing Gemini API key".into());
+    }
+    if let Some(system_prompt) = options.system_prompt {
+        run_config.llm_system_prompt = system_prompt;
+    }
+    if let Some(model) = options.model.filter(|m| !m.trim().is_empty()) {
+        run_config.gemini_model = model;
+    }
+    if let Some(generation) = &options.generation {
+        run_config.generation = run_config.generation.clone().overridden_by(generation);
+    }
+    let answer = send_to_llm::query_gemini(&prompt, &run_config, "")
+        .await
+        .map_err(|e| e.to_string())?;
+    // the clipboard is left alone; only the tags are removed
+    Ok(transform_text::strip_copy_tags(&answer))
+}
+
 pub fn run() {
     tauri::Builder::default()
         .plugin(WindowStateBuilder::default().build())
         .plugin(tauri_plugin_opener::init())
+        .plugin(hotkeys::plugin())
         .manage(JarvisState {
             is_running: Arc::new(AtomicBool::new(false)),
             handle: Mutex::new(None),
             active_conversation: Mutex::new(None),
+            active_persona: Mutex::new(None),
         })
         // Intercept window close to hide to tray instead of quitting
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                // the overlay is created on demand, so it can just close
+                if window.label() == overlay::OVERLAY_LABEL {
+                    return;
+                }
                 // Save window geometry before hiding
                 let _ = window.app_handle().save_window_state(StateFlags::all());
                 api.prevent_close();
@@ -728,6 +1568,30 @@ pub fn run() {
                 let state = app.state::<JarvisState>();
                 let _ = cmd_stop_jarvis(state);
             }
+            "toggle_overlay" => {
+                let result = if overlay::is_visible(app) {
+                    overlay::hide(app)
+                } else {
+                    overlay::show(app)
+                };
+                if let Err(e) = result {
+                    eprintln!("[ERROR] Overlay: {}", e);
+                }
+            }
+            "toggle_meeting" => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let result = if meeting::is_active() {
+                        meeting::stop(&app).await
+                    } else {
+                        meeting::start(&app).await
+                    };
+                    if let Err(e) = result {
+                        eprintln!("[ERROR] Meeting notes: {}", e);
+                        cmd_emit_message(app.clone(), Role::System, e.to_string());
+                    }
+                });
+            }
             "quit" => {
                 // Save all window states before quitting
                 let _ = app.save_window_state(StateFlags::all());
@@ -737,11 +1601,36 @@ pub fn run() {
         })
         // Create the tray icon and menu
         .setup(|app| {
+            stats::init(app.handle());
+            context_budget::init(app.handle());
+            user_profile::init(app.handle());
+            trace::init(app.handle());
+            lexicon::init(app.handle());
+            history_watch::init(app.handle());
+            sync::init(app.handle());
+            local_api::init(app.handle());
+            wyoming::init(app.handle());
+            foreground::init(app.handle());
+            packages::init(app.handle());
+            presence::init(app.handle());
+            crash::install(app.handle());
+
             // Restore window state before showing
             if let Some(win) = app.get_webview_window("main") {
                 let _ = win.restore_state(StateFlags::all());
             }
 
+            // Restore the persona that was active last session
+            if let Ok(cfg) = cmd_load_config(app.handle().clone()) {
+                *app.state::<JarvisState>().active_persona.lock().unwrap() = cfg.active_persona;
+                hotkeys::register(app.handle(), &cfg.hotkeys);
+                // only does anything when the user opted in
+                let crash_reporting = cfg.crash_reporting.clone();
+                tauri::async_runtime::spawn(async move {
+                    crash::submit_pending(&crash_reporting).await;
+                });
+            }
+
             // Try to set the window/taskbar icon from bundled resources
             if let Some(win) = app.get_webview_window("main") {
                 let candidates = ["icons/icon.png", "jarvis_icon_2.png"];
@@ -803,6 +1692,12 @@ pub fn run() {
             let stop_item = MenuItemBuilder::new("Stop Wake Word")
                 .id("stop_listening")
                 .build(app)?;
+            let meeting_item = MenuItemBuilder::new("Start/Stop Meeting Notes")
+                .id("toggle_meeting")
+                .build(app)?;
+            let overlay_item = MenuItemBuilder::new("Show/Hide Overlay")
+                .id("toggle_overlay")
+                .build(app)?;
             let sep2 = PredefinedMenuItem::separator(app)?;
             let show_item = MenuItemBuilder::new("Show").id("show").build(app)?;
             let quit_item = MenuItemBuilder::new("Quit").id("quit").build(app)?;
@@ -812,6 +1707,8 @@ pub fn run() {
                     &sep1,
                     &start_item,
                     &stop_item,
+                    &meeting_item,
+                    &overlay_item,
                     &sep2,
                     &show_item,
                     &quit_item,
@@ -819,7 +1716,7 @@ pub fn run() {
                 .build()?;
 
             // Build tray icon
-            let mut tray_builder = TrayIconBuilder::new().menu(&menu);
+            let mut tray_builder = TrayIconBuilder::with_id("main").menu(&menu);
             if let Some(img) = tray_image {
                 tray_builder = tray_builder.icon(img);
             }
@@ -840,6 +1737,15 @@ pub fn run() {
                 })
                 .build(app)?;
 
+            // Quiet background check; the UI hears about it through `update-available`
+            let update_app = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                match updates::check().await {
+                    Ok(info) => updates::announce(&update_app, &info),
+                    Err(e) => println!("[DEBUG] Update check failed: {}", e),
+                }
+            });
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -856,17 +1762,115 @@ pub fn run() {
             cmd_emit_state_change,
             cmd_emit_message,
             cmd_send_text,
+            cmd_ask_once,
             cmd_set_active_conversation,
             cmd_list_history_files,
             cmd_create_conversation,
             cmd_read_conversation,
+            cmd_get_turn_count,
             cmd_append_turn,
+            cmd_update_turn_meta,
             cmd_delete_conversation,
             cmd_generate_and_rename_conversation,
-            cmd_rename_conversation
+            cmd_rename_conversation,
+            cmd_regenerate_last_response,
+            cmd_edit_turn,
+            cmd_list_branches,
+            cmd_switch_branch,
+            attachments::cmd_list_attachments,
+            attachments::cmd_read_attachment,
+            captures::cmd_list_recent_captures,
+            captures::cmd_retranscribe,
+            conversation_settings::cmd_get_conversation_settings,
+            conversation_settings::cmd_set_conversation_redaction,
+            conversation_settings::cmd_set_conversation_generation,
+            lexicon::cmd_get_lexicon,
+            batch::cmd_delete_conversations,
+            batch::cmd_archive_conversations,
+            batch::cmd_unarchive_conversations,
+            batch::cmd_list_archived_conversations,
+            batch::cmd_export_all,
+            lexicon::cmd_edit_lexicon,
+            lexicon::cmd_set_lexicon_entry,
+            lexicon::cmd_remove_lexicon_entry,
+            persona::cmd_list_personas,
+            persona::cmd_set_persona,
+            profiles::cmd_list_profiles,
+            profiles::cmd_apply_profile,
+            settings::cmd_export_settings,
+            settings::cmd_import_settings,
+            settings::cmd_reset_settings,
+            sync::cmd_sync_now,
+            game_mode::cmd_get_game_mode,
+            benchmark::cmd_run_benchmark,
+            run_jarvis::cmd_simulate_voice_command,
+            trace::cmd_get_interaction_traces,
+            prompt_vars::cmd_preview_system_prompt,
+            user_profile::cmd_get_user_profile,
+            user_profile::cmd_set_user_profile,
+            packages::cmd_list_packages,
+            packages::cmd_add_package,
+            packages::cmd_remove_package,
+            timers::cmd_list_timers,
+            timers::cmd_start_timer,
+            timers::cmd_cancel_timer,
+            cooking::cmd_load_recipe,
+            cooking::cmd_get_cooking,
+            cooking::cmd_stop_cooking,
+            ambient::cmd_play_ambient,
+            ambient::cmd_stop_ambient,
+            briefing::cmd_run_briefing,
+            voice_memos::cmd_list_voice_memos,
+            voice_memos::cmd_delete_voice_memo,
+            pomodoro::cmd_start_pomodoro,
+            pomodoro::cmd_stop_pomodoro,
+            pomodoro::cmd_get_pomodoro,
+            pomodoro::cmd_pomodoro_history,
+            presence::cmd_get_presence,
+            presence::cmd_list_presence_events,
+            network::cmd_check_connectivity,
+            processes::cmd_list_processes,
+            processes::cmd_kill_process,
+            docker::cmd_list_containers,
+            setup::cmd_setup_status,
+            setup::cmd_test_api_key,
+            setup::cmd_validate_key,
+            setup::cmd_run_audio_check,
+            tts::cmd_get_tts_quota,
+            tts::cmd_speak,
+            memory::cmd_list_memories,
+            memory::cmd_delete_memory,
+            triggers::cmd_get_triggers,
+            triggers::cmd_set_triggers,
+            media::cmd_get_now_playing,
+            audio_output::cmd_clear_audio_queue,
+            meeting::cmd_start_meeting,
+            meeting::cmd_stop_meeting,
+            meeting::cmd_get_meeting_status,
+            stats::cmd_get_dashboard,
+            crash::cmd_list_crash_reports,
+            crash::cmd_delete_crash_reports,
+            diagnostics::cmd_create_diagnostics_bundle,
+            updates::cmd_check_updates,
+            overlay::cmd_show_overlay,
+            overlay::cmd_hide_overlay,
+            quick_ask::cmd_quick_ask,
+            quick_ask::cmd_hide_quick_ask,
+            read_aloud::cmd_read_selection,
+            read_aloud::cmd_read_aloud,
+            read_aloud::cmd_stop_reading,
+            clipboard_tools::cmd_summarize_clipboard,
+            clipboard_tools::cmd_rewrite_clipboard,
+            image_gen::cmd_generate_image
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                meeting::shutdown();
+                audio_input::shutdown_audio_input();
+            }
+        });
 }
 
 #[tauri::command]
@@ -0,0 +1,147 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Local HTTP/WebSocket API for other programs and devices. Off by default and only
+// reachable from this machine unless `allow_network` is set, which needs a token.
+// Browsers are turned away: any web page could otherwise talk to 127.0.0.1, and
+// WebSockets aren't covered by CORS.
+//
+//   GET  /satellite             WebSocket for satellite instances (see satellite.rs)
+//   POST /v1/chat/completions   OpenAI-compatible chat (see openai_api.rs)
+//   GET  /v1/models             the models that endpoint accepts
+
+use axum::extract::{State, WebSocketUpgrade};
+use axum::http::{header::AUTHORIZATION, header::ORIGIN, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::net::SocketAddr;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct LocalApiConfig {
+    pub enabled: bool,
+    pub port: u16,
+    // listen on all network interfaces instead of only this machine, e.g. for satellites
+    pub allow_network: bool,
+    // clients send it as `Authorization: Bearer <token>`
+    pub token: String,
+}
+
+impl Default for LocalApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 7381,
+            allow_network: false,
+            token: String::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct ApiState {
+    pub app: tauri::AppHandle,
+    token: String,
+}
+
+// Compares the hashes byte by byte without stopping early, so how long a wrong token
+// takes to turn away says nothing about how much of it was right
+fn same_token(given: &str, token: &str) -> bool {
+    let (a, b) = (Sha256::digest(given.as_bytes()), Sha256::digest(token.as_bytes()));
+    a.iter().zip(b.iter()).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+// Every route checks the token first; with no token set only local clients get this far.
+// Requests from web pages carry an Origin, which other programs and satellites don't send.
+pub(crate) fn authorize(state: &ApiState, headers: &HeaderMap) -> Result<(), StatusCode> {
+    if headers.contains_key(ORIGIN) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    if state.token.is_empty() {
+        return Ok(());
+    }
+    let given = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim);
+    if given.is_some_and(|given| same_token(given, &state.token)) {
+        Ok(())
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+async fn satellite_socket(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if let Err(status) = authorize(&state, &headers) {
+        return status.into_response();
+    }
+    ws.on_upgrade(move |socket| crate::satellite::serve(state.app, socket))
+}
+
+async fn serve(app: tauri::AppHandle, cfg: LocalApiConfig) -> anyhow::Result<()> {
+    let host = if cfg.allow_network {
+        [0, 0, 0, 0]
+    } else {
+        [127, 0, 0, 1]
+    };
+    let addr = SocketAddr::from((host, cfg.port));
+    let state = ApiState {
+        app,
+        token: cfg.token.trim().to_string(),
+    };
+    let router = Router::new()
+        .route("/satellite", get(satellite_socket))
+        .route("/v1/chat/completions", post(crate::openai_api::chat_completions))
+        .route("/v1/models", get(crate::openai_api::models))
+        .with_state(state);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    println!("[INFO] Local API listening on {}", addr);
+    axum::serve(listener, router).await?;
+    Ok(())
+}
+
+// Called once at startup; changes to `local_api` take effect after a restart
+pub fn init(app: &tauri::AppHandle) {
+    let cfg = match crate::cmd_load_config(app.clone()) {
+        Ok(cfg) => cfg.local_api,
+        Err(e) => {
+            eprintln!("[WARN] Local API not started: {}", e);
+            return;
+        }
+    };
+    if !cfg.enabled {
+        return;
+    }
+    if cfg.allow_network && cfg.token.trim().is_empty() {
+        eprintln!("[WARN] Local API not started: set a token before allowing network access");
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = serve(app, cfg).await {
+            eprintln!("[WARN] Local API stopped: {}", e);
+        }
+    });
+}
@@ -0,0 +1,138 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+// Track reported by the OS media session (GSMTC on Windows, MPRIS via playerctl on Linux)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NowPlaying {
+    pub title: String,
+    pub artist: Option<String>,
+    // "playing", "paused", ... as reported by the player
+    pub status: Option<String>,
+    pub player: Option<String>,
+}
+
+impl NowPlaying {
+    pub fn describe(&self) -> String {
+        match self.artist.as_deref().filter(|a| !a.is_empty()) {
+            Some(artist) => format!("{} by {}", self.title, artist),
+            None => self.title.clone(),
+        }
+    }
+}
+
+// every backend prints one line: title, artist, status, player separated by tabs
+fn parse_line(out: &str) -> Option<NowPlaying> {
+    let line = out.lines().find(|l| !l.trim().is_empty())?;
+    let mut fields = line.split('\t').map(|f| f.trim().to_string());
+    let title = fields.next().filter(|t| !t.is_empty())?;
+    let mut next = || fields.next().filter(|f| !f.is_empty());
+    Some(NowPlaying {
+        title,
+        artist: next(),
+        status: next().map(|s| s.to_lowercase()),
+        player: next(),
+    })
+}
+
+fn query_command() -> Command {
+    #[cfg(target_os = "windows")]
+    let cmd = {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        let mut c = Command::new("powershell");
+        c.args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Add-Type -AssemblyName System.Runtime.WindowsRuntime; \
+             $asTask = ([System.WindowsRuntimeSystemExtensions].GetMethods() | Where-Object { \
+               $_.Name -eq 'AsTask' -and $_.GetParameters().Count -eq 1 -and \
+               $_.GetParameters()[0].ParameterType.Name -eq 'IAsyncOperation`1' })[0]; \
+             function Await($op, $type) { \
+               $t = $asTask.MakeGenericMethod($type).Invoke($null, @($op)); $t.Wait(-1) | Out-Null; $t.Result }; \
+             $mgrType = [Windows.Media.Control.GlobalSystemMediaTransportControlsSessionManager,Windows.Media.Control,ContentType=WindowsRuntime]; \
+             $propsType = [Windows.Media.Control.GlobalSystemMediaTransportControlsSessionMediaProperties,Windows.Media.Control,ContentType=WindowsRuntime]; \
+             $mgr = Await ($mgrType::RequestAsync()) $mgrType; \
+             $s = $mgr.GetCurrentSession(); \
+             if ($s) { \
+               $p = Await ($s.TryGetMediaPropertiesAsync()) $propsType; \
+               \"$($p.Title)`t$($p.Artist)`t$($s.GetPlaybackInfo().PlaybackStatus)`t$($s.SourceAppUserModelId)\" }",
+        ])
+        .creation_flags(CREATE_NO_WINDOW);
+        c
+    };
+    #[cfg(target_os = "macos")]
+    let cmd = {
+        let mut c = Command::new("osascript");
+        c.args([
+            "-e",
+            "if application \"Music\" is running then tell application \"Music\" to \
+             if player state is not stopped then return (name of current track) & tab & \
+             (artist of current track) & tab & (player state as text) & tab & \"Music\"",
+        ]);
+        c
+    };
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let cmd = {
+        let mut c = Command::new("playerctl");
+        c.args([
+            "metadata",
+            "--format",
+            "{{title}}\t{{artist}}\t{{status}}\t{{playerName}}",
+        ]);
+        c
+    };
+    cmd
+}
+
+// Blocking; None when no player is active
+pub fn now_playing() -> Result<Option<NowPlaying>> {
+    let out = query_command()
+        .output()
+        .map_err(|e| anyhow!("Failed to query media session: {}", e))?;
+    if !out.status.success() {
+        // playerctl exits non-zero when no player is running
+        return Ok(None);
+    }
+    Ok(parse_line(&String::from_utf8_lossy(&out.stdout)))
+}
+
+pub async fn now_playing_async() -> Option<NowPlaying> {
+    match tokio::task::spawn_blocking(now_playing).await {
+        Ok(Ok(np)) => np,
+        Ok(Err(e)) => {
+            eprintln!("[WARN] {}", e);
+            None
+        }
+        Err(e) => {
+            eprintln!("[WARN] Media session thread panic: {}", e);
+            None
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn cmd_get_now_playing() -> Result<Option<NowPlaying>, String> {
+    tokio::task::spawn_blocking(now_playing)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,450 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::audio_input::{self, AudioRing, SAMPLE_RATE};
+use crate::events::{self, MeetingNotes};
+use crate::models::Config;
+use crate::send_to_llm;
+use crate::stt::SttWorker;
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+use ts_rs::TS;
+
+// Long-form transcription, independent of the wake word loop
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct MeetingConfig {
+    pub record_microphone: bool,
+    // what the speakers play (the other side of a call); Windows only
+    pub record_system_audio: bool,
+    // audio is transcribed in chunks of this length
+    pub chunk_seconds: u64,
+    // rolling notes are written this often; 0 turns them off
+    pub summary_interval_minutes: u64,
+}
+
+impl Default for MeetingConfig {
+    fn default() -> Self {
+        Self {
+            record_microphone: true,
+            record_system_audio: false,
+            chunk_seconds: 20,
+            summary_interval_minutes: 10,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, rename = "MeetingSegment")]
+pub struct Segment {
+    // unix ms at the end of the chunk
+    #[ts(type = "number")]
+    pub at: i64,
+    pub text: String,
+}
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MeetingStatus {
+    pub active: bool,
+    #[ts(type = "number | null")]
+    pub started_at: Option<i64>,
+    pub transcript_path: Option<String>,
+    #[ts(type = "number")]
+    pub segments: usize,
+}
+
+struct Session {
+    stop: Arc<AtomicBool>,
+    started_at: i64,
+    path: PathBuf,
+    segments: Arc<Mutex<Vec<Segment>>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+static SESSION: Mutex<Option<Session>> = Mutex::new(None);
+
+// chunks quieter than this (RMS) are not sent to Whisper, which invents text for silence
+const SILENCE_RMS: f64 = 200.0;
+
+fn meetings_dir(app: &tauri::AppHandle) -> Result<PathBuf> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| anyhow!("Failed to resolve app config dir: {e}"))?
+        .join("meetings");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn append(path: &Path, text: &str) {
+    let result = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut f| f.write_all(text.as_bytes()));
+    if let Err(e) = result {
+        eprintln!("[ERROR] Failed to write meeting transcript: {}", e);
+    }
+}
+
+fn clock(ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ms)
+        .map(|t| t.with_timezone(&chrono::Local).format("%H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+fn is_silent(samples: &[i16]) -> bool {
+    if samples.is_empty() {
+        return true;
+    }
+    let energy: f64 = samples.iter().map(|&s| (s as f64) * (s as f64)).sum();
+    (energy / samples.len() as f64).sqrt() < SILENCE_RMS
+}
+
+// both sources are 16 kHz mono already, so mixing is a per-sample sum
+fn mix(a: Vec<i16>, b: Vec<i16>) -> Vec<i16> {
+    let (mut long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    for (l, s) in long.iter_mut().zip(short) {
+        *l = l.saturating_add(s);
+    }
+    long
+}
+
+fn status_of(session: Option<&Session>) -> MeetingStatus {
+    match session {
+        Some(s) => MeetingStatus {
+            active: true,
+            started_at: Some(s.started_at),
+            transcript_path: Some(s.path.to_string_lossy().to_string()),
+            segments: s.segments.lock().map(|v| v.len()).unwrap_or(0),
+        },
+        None => MeetingStatus {
+            active: false,
+            started_at: None,
+            transcript_path: None,
+            segments: 0,
+        },
+    }
+}
+
+pub fn status() -> MeetingStatus {
+    status_of(SESSION.lock().unwrap().as_ref())
+}
+
+pub fn is_active() -> bool {
+    SESSION.lock().map(|s| s.is_some()).unwrap_or(false)
+}
+
+// Summarizes `segments` and appends the notes to the transcript file
+fn write_notes(app: tauri::AppHandle, config: Config, path: PathBuf, segments: Vec<Segment>) {
+    if segments.is_empty() || config.gemini_key.trim().is_empty() {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        let transcript = transcript_text(&segments);
+        match send_to_llm::summarize_transcript(&transcript, &config).await {
+            Ok(notes) if !notes.is_empty() => {
+                let from = segments.first().map(|s| s.at).unwrap_or_default();
+                let to = segments.last().map(|s| s.at).unwrap_or_default();
+                append(
+                    &path,
+                    &format!("\n### Notes {} - {}\n\n{}\n\n", clock(from), clock(to), notes),
+                );
+                events::emit(&app, MeetingNotes { from, to, notes });
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("[ERROR] Failed to summarize meeting: {}", e),
+        }
+    });
+}
+
+fn transcript_text(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .map(|s| format!("[{}] {}", clock(s.at), s.text))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn capture_loop(
+    app: tauri::AppHandle,
+    config: Config,
+    stt: SttWorker,
+    stop: Arc<AtomicBool>,
+    segments: Arc<Mutex<Vec<Segment>>>,
+    path: PathBuf,
+    ready: mpsc::Sender<Result<()>>,
+) {
+    let meeting = config.meeting.clone();
+    // room for a whole chunk plus however long Whisper takes on the previous one
+    let capacity = SAMPLE_RATE * (meeting.chunk_seconds as usize + 120);
+    let mic_buffer = AudioRing::shared(capacity);
+    let system_buffer = AudioRing::shared(capacity);
+
+    let mut streams = Vec::new();
+    if meeting.record_microphone {
+        match audio_input::open_microphone(
+            config.default_microphone_name.as_deref(),
+            config.default_microphone_index,
+            Arc::clone(&mic_buffer),
+        ) {
+            Ok((name, s)) => {
+                println!("[INFO] Meeting notes: recording microphone {}", name);
+                streams.push(s);
+            }
+            Err(e) => {
+                let _ = ready.send(Err(e));
+                return;
+            }
+        }
+    }
+    if meeting.record_system_audio {
+        match audio_input::open_loopback(
+            config.default_output_device_name.as_deref(),
+            Arc::clone(&system_buffer),
+        ) {
+            Ok((name, s)) => {
+                println!("[INFO] Meeting notes: recording system audio from {}", name);
+                streams.push(s);
+            }
+            Err(e) => {
+                let _ = ready.send(Err(e));
+                return;
+            }
+        }
+    }
+    let _ = ready.send(Ok(()));
+
+    let chunk = Duration::from_secs(meeting.chunk_seconds.max(5));
+    let summary_every = Duration::from_secs(meeting.summary_interval_minutes * 60);
+    let mut last_summary = Instant::now();
+    let mut summarized = 0usize;
+
+    loop {
+        let chunk_start = Instant::now();
+        while chunk_start.elapsed() < chunk && !stop.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(100));
+        }
+        let stopping = stop.load(Ordering::Relaxed);
+
+        let mic = mic_buffer.lock().map(|mut b| b.take(usize::MAX)).unwrap_or_default();
+        let system = system_buffer
+            .lock()
+            .map(|mut b| b.take(usize::MAX))
+            .unwrap_or_default();
+        let audio = mix(mic, system);
+        if !is_silent(&audio) {
+            crate::stats::record_stt(audio.len());
+            match stt.transcribe(audio, &config.whisper_language) {
+                Ok(text) => {
+                    let text = text.trim().to_string();
+                    // Whisper marks non-speech as [BLANK_AUDIO], (music) and similar
+                    let bracketed = (text.starts_with('[') && text.ends_with(']'))
+                        || (text.starts_with('(') && text.ends_with(')'));
+                    if !text.is_empty() && !bracketed {
+                        let segment = Segment {
+                            at: chrono::Utc::now().timestamp_millis(),
+                            text,
+                        };
+                        append(&path, &format!("[{}] {}\n", clock(segment.at), segment.text));
+                        events::emit(&app, segment.clone());
+                        segments.lock().unwrap().push(segment);
+                    }
+                }
+                Err(e) => eprintln!("[ERROR] Meeting transcription failed: {}", e),
+            }
+        }
+
+        let due = !summary_every.is_zero() && last_summary.elapsed() >= summary_every;
+        if due || stopping {
+            let pending: Vec<Segment> = segments.lock().unwrap()[summarized..].to_vec();
+            summarized += pending.len();
+            last_summary = Instant::now();
+            if !summary_every.is_zero() {
+                write_notes(app.clone(), config.clone(), path.clone(), pending);
+            }
+        }
+        if stopping {
+            break;
+        }
+    }
+    drop(streams);
+    println!("[INFO] Meeting notes stopped: {:?}", path);
+}
+
+pub async fn start(app: &tauri::AppHandle) -> Result<MeetingStatus> {
+    if is_active() {
+        return Err(anyhow!("Meeting notes are already running"));
+    }
+    let cfg = crate::cmd_load_config(app.clone()).map_err(|e| anyhow!(e))?;
+    let config = crate::to_run_config(cfg);
+    if !config.meeting.record_microphone && !config.meeting.record_system_audio {
+        return Err(anyhow!("Meeting notes need the microphone or system audio enabled"));
+    }
+
+    let stt = SttWorker::load_default(app).await?;
+
+    let started_at = chrono::Utc::now().timestamp_millis();
+    let path = meetings_dir(app)?.join(format!(
+        "meeting-{}.md",
+        chrono::Local::now().format("%Y-%m-%d_%H-%M-%S")
+    ));
+    append(
+        &path,
+        &format!(
+            "# Meeting {}\n\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M")
+        ),
+    );
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let segments = Arc::new(Mutex::new(Vec::new()));
+    let (ready, opened) = mpsc::channel();
+    let handle = {
+        let (app, stop, segments, path) = (
+            app.clone(),
+            Arc::clone(&stop),
+            Arc::clone(&segments),
+            path.clone(),
+        );
+        thread::Builder::new()
+            .name("meeting-notes".into())
+            .spawn(move || capture_loop(app, config, stt, stop, segments, path, ready))
+            .map_err(|e| anyhow!("Failed to spawn meeting thread: {e}"))?
+    };
+    opened
+        .recv()
+        .map_err(|_| anyhow!("Meeting thread exited before recording started"))??;
+
+    let session = Session {
+        stop,
+        started_at,
+        path,
+        segments,
+        thread: Some(handle),
+    };
+    let status = status_of(Some(&session));
+    *SESSION.lock().unwrap() = Some(session);
+    events::emit(app, status.clone());
+    Ok(status)
+}
+
+// Finishes the current chunk (and its notes) before returning
+pub async fn stop(app: &tauri::AppHandle) -> Result<MeetingStatus> {
+    let Some(mut session) = SESSION.lock().unwrap().take() else {
+        return Err(anyhow!("No meeting is being transcribed"));
+    };
+    session.stop.store(true, Ordering::Relaxed);
+    if let Some(handle) = session.thread.take() {
+        let _ = tokio::task::spawn_blocking(move || handle.join()).await;
+    }
+    let finished = MeetingStatus {
+        active: false,
+        ..status_of(Some(&session))
+    };
+    events::emit(app, finished.clone());
+    Ok(finished)
+}
+
+// Tells a running session to wrap up without waiting (app exit)
+pub fn shutdown() {
+    let stop = SESSION
+        .lock()
+        .ok()
+        .and_then(|s| s.as_ref().map(|s| Arc::clone(&s.stop)));
+    if let Some(stop) = stop {
+        stop.store(true, Ordering::Relaxed);
+    }
+}
+
+// "summarize the last 10 minutes" -> Some(10)
+pub fn match_summary_request(prompt: &str) -> Option<u64> {
+    let re = Regex::new(
+        r"(?i)\bsummari[sz]e\s+(?:the\s+)?(?:last|past)\s+(\d+|an?|one|two|three|five|ten|fifteen|twenty|thirty)?\s*(minutes?|hours?|mins?)\b",
+    )
+    .ok()?;
+    let caps = re.captures(prompt)?;
+    let amount = match caps.get(1).map(|m| m.as_str().to_lowercase()) {
+        None => 1,
+        Some(n) => match n.as_str() {
+            "a" | "an" | "one" => 1,
+            "two" => 2,
+            "three" => 3,
+            "five" => 5,
+            "ten" => 10,
+            "fifteen" => 15,
+            "twenty" => 20,
+            "thirty" => 30,
+            digits => digits.parse().ok()?,
+        },
+    };
+    let unit = caps.get(2)?.as_str().to_lowercase();
+    Some(if unit.starts_with('h') { amount * 60 } else { amount })
+}
+
+// Answer for the voice/text query; always returns something speakable
+pub async fn summarize_last(config: &Config, minutes: u64) -> String {
+    let segments = {
+        let guard = SESSION.lock().unwrap();
+        let Some(session) = guard.as_ref() else {
+            return "I'm not taking meeting notes right now.".to_string();
+        };
+        let since = chrono::Utc::now().timestamp_millis() - (minutes as i64) * 60_000;
+        let all = session.segments.lock().unwrap();
+        all.iter().filter(|s| s.at >= since).cloned().collect::<Vec<_>>()
+    };
+    if segments.is_empty() {
+        return format!(
+            "Nothing was said in the last {} minute{}.",
+            minutes,
+            if minutes == 1 { "" } else { "s" }
+        );
+    }
+    match send_to_llm::summarize_transcript(&transcript_text(&segments), config).await {
+        Ok(summary) if !summary.is_empty() => summary,
+        Ok(_) => "I couldn't summarize that part of the meeting.".to_string(),
+        Err(e) => {
+            eprintln!("[ERROR] Failed to summarize meeting: {}", e);
+            "I couldn't summarize that part of the meeting.".to_string()
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn cmd_start_meeting(app: tauri::AppHandle) -> Result<MeetingStatus, String> {
+    start(&app).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn cmd_stop_meeting(app: tauri::AppHandle) -> Result<MeetingStatus, String> {
+    stop(&app).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn cmd_get_meeting_status() -> Result<MeetingStatus, String> {
+    Ok(status())
+}
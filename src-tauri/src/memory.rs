@@ -0,0 +1,292 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::models::Config;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Manager;
+
+// long-term facts about the user, learned from what they say
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct MemoryConfig {
+    pub enabled: bool,
+    pub max_in_prompt: usize,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_in_prompt: 8,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Memory {
+    pub id: String,
+    pub fact: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    // the user message the fact came from
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+// skip extraction for pasted documents and the like
+const MAX_SOURCE_CHARS: usize = 2000;
+
+// so "forget that" can drop what was just learned
+static LAST_LEARNED: Mutex<Option<String>> = Mutex::new(None);
+
+// lock so two turns finishing at once don't clobber each other's writes
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+fn memories_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join("memories.json"))
+}
+
+pub fn load(app: &tauri::AppHandle) -> Vec<Memory> {
+    memories_path(app)
+        .ok()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save(app: &tauri::AppHandle, memories: &[Memory]) -> Result<(), String> {
+    let s = serde_json::to_string_pretty(memories).map_err(|e| e.to_string())?;
+    crate::history::atomic_write(&memories_path(app)?, s.as_bytes()).map_err(|e| e.to_string())?;
+    crate::events::emit(app, crate::events::MemoriesChanged(memories.len()));
+    Ok(())
+}
+
+// Adds memories from another device (see sync.rs); returns whether anything was new
+pub(crate) fn merge_synced(app: &tauri::AppHandle, incoming: Vec<Memory>) -> Result<bool, String> {
+    let _guard = FILE_LOCK.lock().unwrap();
+    let mut memories = load(app);
+    let before = memories.len();
+    for m in incoming {
+        if !memories.iter().any(|existing| existing.id == m.id) {
+            memories.push(m);
+        }
+    }
+    if memories.len() == before {
+        return Ok(false);
+    }
+    memories.sort_by_key(|m| m.created_at);
+    save(app, &memories)?;
+    Ok(true)
+}
+
+fn words(text: &str) -> HashSet<String> {
+    const STOP: &[&str] = &[
+        "the", "and", "for", "are", "was", "is", "a", "an", "of", "to", "in", "on", "my", "me",
+        "i", "you", "your", "user", "user's", "what", "who", "how", "it", "that", "this", "with",
+        "do", "does", "be", "at", "named", "called",
+    ];
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|w| w.trim_matches('\'').to_lowercase())
+        .filter(|w| w.len() > 1 && !STOP.contains(&w.as_str()))
+        .collect()
+}
+
+fn overlap(a: &HashSet<String>, fact: &str) -> usize {
+    words(fact).intersection(a).count()
+}
+
+// Memories that share words with the prompt, best first. Small stores are small
+// enough to always include, so names etc. are known even without a keyword hit.
+pub fn relevant<'a>(prompt: &str, memories: &'a [Memory], limit: usize) -> Vec<&'a Memory> {
+    if memories.len() <= limit {
+        return memories.iter().collect();
+    }
+    let prompt_words = words(prompt);
+    let mut scored: Vec<(usize, &Memory)> = memories
+        .iter()
+        .map(|m| (overlap(&prompt_words, &m.fact), m))
+        .filter(|(score, _)| *score > 0)
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.created_at.cmp(&a.1.created_at)));
+    scored.into_iter().take(limit).map(|(_, m)| m).collect()
+}
+
+// Text appended to the system prompt context; empty when nothing applies
+pub fn prompt_section(app: &tauri::AppHandle, config: &Config, prompt: &str) -> String {
+    // the profile the user filled in goes in whether or not memory is on
+    let mut out = crate::user_profile::prompt_section();
+    if !config.memory.enabled {
+        return out;
+    }
+    let memories = load(app);
+    let picked = relevant(prompt, &memories, config.memory.max_in_prompt);
+    if picked.is_empty() {
+        return out;
+    }
+    crate::trace::tool(&format!("added {} remembered fact(s)", picked.len()));
+    out.push_str("\n\nThings you know about the user:\n");
+    for m in picked {
+        out.push_str(&format!("- {}\n", m.fact));
+    }
+    out.push('\n');
+    out
+}
+
+// Asks the LLM for new facts in a user message and stores them. Meant to run in
+// the background after the reply has been sent.
+pub async fn learn_from(app: tauri::AppHandle, config: Config, user_text: String) {
+    if !config.memory.enabled
+        || user_text.trim().is_empty()
+        || user_text.len() > MAX_SOURCE_CHARS
+        || config.gemini_key.trim().is_empty()
+    {
+        return;
+    }
+    let known: Vec<String> = load(&app).into_iter().map(|m| m.fact).collect();
+    let facts =
+        match crate::send_to_llm::extract_user_facts(&user_text, &known, &config).await {
+            Ok(f) => f,
+            Err(e) => {
+                eprintln!("[ERROR] Failed to extract memories: {}", e);
+                return;
+            }
+        };
+    if facts.is_empty() {
+        return;
+    }
+
+    let _guard = FILE_LOCK.lock().unwrap();
+    let mut memories = load(&app);
+    let now = chrono::Utc::now().timestamp_millis();
+    for (i, fact) in facts.into_iter().enumerate() {
+        if memories.iter().any(|m| m.fact.eq_ignore_ascii_case(&fact)) {
+            continue;
+        }
+        let id = format!("memory-{}-{}", now, i);
+        println!("[DEBUG] Learned memory: {}", fact);
+        *LAST_LEARNED.lock().unwrap() = Some(id.clone());
+        memories.push(Memory {
+            id,
+            fact,
+            created_at: now,
+            source: Some(user_text.clone()),
+        });
+    }
+    if let Err(e) = save(&app, &memories) {
+        eprintln!("[ERROR] Failed to save memories: {}", e);
+    }
+}
+
+fn delete(app: &tauri::AppHandle, id: &str) -> Result<Option<Memory>, String> {
+    let _guard = FILE_LOCK.lock().unwrap();
+    let mut memories = load(app);
+    let Some(pos) = memories.iter().position(|m| m.id == id) else {
+        return Ok(None);
+    };
+    let removed = memories.remove(pos);
+    save(app, &memories)?;
+    let mut last = LAST_LEARNED.lock().unwrap();
+    if last.as_deref() == Some(id) {
+        *last = None;
+    }
+    Ok(Some(removed))
+}
+
+// wipes every memory, returns how many there were
+pub fn clear_all(app: &tauri::AppHandle) -> Result<usize, String> {
+    let _guard = FILE_LOCK.lock().unwrap();
+    let count = load(app).len();
+    save(app, &[])?;
+    *LAST_LEARNED.lock().unwrap() = None;
+    Ok(count)
+}
+
+// "forget that", "forget that my sister lives in Berlin", "forget what I said about my job"
+pub fn match_forget_command(prompt: &str) -> Option<String> {
+    let re = Regex::new(
+        r"(?i)^\s*(?:please\s+)?forget\s+(?:that|about|what i (?:said|told you) about)\b\s*(.*?)[.!]?\s*$",
+    )
+    .expect("Failed to compile forget memory regex");
+    let cap = re.captures(prompt.trim())?;
+    Some(cap.get(1).map(|m| m.as_str().to_string()).unwrap_or_default())
+}
+
+// Removes one memory for a "forget that" command and returns the reply to show and speak.
+// With no description the most recently learned memory goes; otherwise the closest match.
+pub fn forget_one(app: &tauri::AppHandle, description: &str) -> String {
+    let memories = load(app);
+    let target = if description.trim().is_empty() {
+        let last = LAST_LEARNED.lock().unwrap().clone();
+        last.and_then(|id| memories.iter().find(|m| m.id == id))
+            .or_else(|| memories.iter().max_by_key(|m| m.created_at))
+    } else {
+        let wanted = words(description);
+        memories
+            .iter()
+            .map(|m| (overlap(&wanted, &m.fact), m))
+            .filter(|(score, _)| *score > 0)
+            .max_by_key(|(score, m)| (*score, m.created_at))
+            .map(|(_, m)| m)
+    };
+
+    let Some(target) = target else {
+        return "I don't have anything like that remembered.".to_string();
+    };
+    match delete(app, &target.id) {
+        Ok(Some(m)) => format!("Okay, I forgot that {}.", as_second_person(&m.fact)),
+        Ok(None) => "I don't have anything like that remembered.".to_string(),
+        Err(e) => {
+            eprintln!("[ERROR] Failed to delete memory: {}", e);
+            "Sorry, I couldn't update my memory.".to_string()
+        }
+    }
+}
+
+// "The user's wife is named Anna" -> "your wife is named Anna"
+fn as_second_person(fact: &str) -> String {
+    let re = Regex::new(r"(?i)\bthe user(?:'s| is| has|)\b").expect("Failed to compile user regex");
+    re.replace_all(fact.trim().trim_end_matches('.'), |c: &regex::Captures| {
+        match c[0].to_lowercase().as_str() {
+            "the user's" => "your",
+            "the user is" => "you are",
+            "the user has" => "you have",
+            _ => "you",
+        }
+    })
+    .into_owned()
+}
+
+#[tauri::command]
+pub fn cmd_list_memories(app: tauri::AppHandle) -> Result<Vec<Memory>, String> {
+    let mut memories = load(&app);
+    memories.sort_by_key(|m| std::cmp::Reverse(m.created_at));
+    Ok(memories)
+}
+
+#[tauri::command]
+pub fn cmd_delete_memory(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    match delete(&app, &id)? {
+        Some(_) => Ok(()),
+        None => Err(format!("Unknown memory '{}'", id)),
+    }
+}
@@ -15,20 +15,11 @@ You should have received a copy of the GNU Affero General Public License
 along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use anyhow::Result;
 use elevenlabs_rs::Model;
-use porcupine::Porcupine;
-use rodio::{Decoder, OutputStream, OutputStreamBuilder, Sink};
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
-use std::fs::File;
-use std::io::BufReader;
-use std::path::{Path, PathBuf};
+use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
 use webrtc_vad::Vad;
-use whisper_rs::WhisperContext;
-use tauri::Manager;
-use cpal::traits::{DeviceTrait, HostTrait};
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct Config {
@@ -40,6 +31,8 @@ pub struct Config {
     pub default_microphone_index: usize,
     pub default_microphone_name: Option<String>,
     pub default_output_device_name: Option<String>,
+    #[serde(default)]
+    pub tts_output_device_name: Option<String>,
 
     // advanced settings
     pub gemini_model: String,
@@ -51,187 +44,182 @@ pub struct Config {
     // Paths are hard-coded by the app (wakeword in resources/public; history/context in app data)
 
     pub frame_duration_ms: usize,
-    pub silence_threshold_seconds: usize,
+    pub silence_threshold_ms: usize,
     pub speech_trigger_frames: usize,
     pub frame_length_wwd: usize,
+
+    #[serde(default)]
+    pub redaction: crate::redact::RedactionConfig,
+    #[serde(default)]
+    pub generation: crate::send_to_llm::GenerationSettings,
+
+    #[serde(default)]
+    pub debug_capture: crate::captures::CaptureConfig,
+
+    #[serde(default)]
+    pub endpointing: crate::get_text::EndpointingConfig,
+
+    #[serde(default)]
+    pub wake: crate::get_text::WakeConfig,
+
+    #[serde(default)]
+    pub interrupt: crate::get_text::InterruptConfig,
+
+    #[serde(default)]
+    pub personas: Vec<crate::persona::Persona>,
+
+    // playback volume for spoken responses, None means full volume
+    #[serde(default)]
+    pub output_volume: Option<f32>,
+
+    #[serde(default)]
+    pub tts_quota_warning: crate::tts::QuotaWarningConfig,
+
+    #[serde(default)]
+    pub tts_provider: Option<String>,
+
+    #[serde(default)]
+    pub memory: crate::memory::MemoryConfig,
+
+    #[serde(default)]
+    pub command_feedback: crate::transform_text::CommandFeedbackConfig,
+
+    #[serde(default)]
+    pub meeting: crate::meeting::MeetingConfig,
+
+    #[serde(default)]
+    pub utility_model: Option<String>,
+
+    #[serde(default)]
+    pub history_format: Option<String>,
+
+    #[serde(default)]
+    pub crash_reporting: crate::crash::CrashReportingConfig,
+
+    #[serde(default)]
+    pub image_generation: crate::image_gen::ImageGenerationConfig,
+
+    #[serde(default)]
+    pub web_fetch: crate::web_fetch::WebFetchConfig,
+
+    #[serde(default)]
+    pub pdf: crate::pdf::PdfConfig,
+
+    #[serde(default)]
+    pub context_budget: crate::context_budget::ContextBudgetConfig,
+
+    #[serde(default)]
+    pub safety: crate::safety::SafetyConfig,
+
+    #[serde(default)]
+    pub dual_response: bool,
+
+    #[serde(default)]
+    pub tts_normalization: crate::tts_normalize::TtsNormalizationConfig,
+
+    #[serde(default)]
+    pub copy_blocks: crate::transform_text::CopyBlocksConfig,
+
+    #[serde(default)]
+    pub satellite: crate::satellite::SatelliteConfig,
+
+    #[serde(default)]
+    pub thread_priority: crate::thread_priority::ThreadPriorityConfig,
+
+    #[serde(default)]
+    pub input_source: crate::audio_input::InputSourceConfig,
+
+    #[serde(default)]
+    pub prompt_variables: BTreeMap<String, String>,
+
+    #[serde(default)]
+    pub world_clock: crate::world_clock::WorldClockConfig,
+
+    #[serde(default)]
+    pub finance: crate::finance::FinanceConfig,
+
+    #[serde(default)]
+    pub sports: crate::sports::SportsConfig,
+
+    #[serde(default)]
+    pub wikipedia: crate::wikipedia::WikipediaConfig,
+
+    #[serde(default)]
+    pub packages: crate::packages::PackagesConfig,
+
+    #[serde(default)]
+    pub commute: crate::commute::CommuteConfig,
+
+    #[serde(default)]
+    pub cooking: crate::cooking::CookingConfig,
+
+    #[serde(default)]
+    pub ambient: crate::ambient::AmbientConfig,
+
+    #[serde(default)]
+    pub briefing: crate::briefing::BriefingConfig,
+
+    #[serde(default)]
+    pub voice_memos: crate::voice_memos::VoiceMemosConfig,
+
+    #[serde(default)]
+    pub pomodoro: crate::pomodoro::PomodoroConfig,
+
+    #[serde(default)]
+    pub network: crate::network::NetworkConfig,
+
+    #[serde(default)]
+    pub processes: crate::processes::ProcessesConfig,
+
+    #[serde(default)]
+    pub terminal: crate::terminal::TerminalConfig,
+
+    #[serde(default)]
+    pub github: crate::github::GithubConfig,
+
+    #[serde(default)]
+    pub docker: crate::docker::DockerConfig,
+
+    #[serde(default)]
+    pub kubernetes: crate::kubernetes::KubernetesConfig,
+}
+
+impl Config {
+    // device for spoken responses and earcons: the TTS device if set, else the general output
+    pub fn voice_output_device(&self) -> Option<String> {
+        self.tts_output_device_name
+            .clone()
+            .filter(|n| !n.trim().is_empty())
+            .or_else(|| self.default_output_device_name.clone())
+    }
+}
+
+// Maps the configured model id to the ElevenLabs model; unknown ids fall back to multilingual v2
+pub fn elevenlabs_model_from_id(id: &str) -> Model {
+    match id {
+        "eleven_multilingual_v2" => Model::ElevenMultilingualV2,
+        "eleven_flash_v2_5" => Model::ElevenFlashV2_5,
+        "eleven_turbo_v2_5" => Model::ElevenTurboV2_5,
+        _ => Model::ElevenMultilingualV2,
+    }
 }
 
 pub struct AppContext {
     pub config: Config,
-    pub audio_player: AudioPlayer,
-    pub porcupine: Porcupine,
+    pub audio_output: crate::audio_output::AudioOutput,
+    // wake word, Whisper, the LLM and the voice (see pipeline.rs)
+    pub pipeline: crate::pipeline::Pipeline,
     pub vad: Mutex<Vad>,
-    pub whisper_context: Arc<WhisperContext>,
-    pub audio_buffer: Arc<Mutex<VecDeque<i16>>>,
+    pub audio_buffer: crate::audio_input::SharedAudioBuffer,
+    pub audio_input: Arc<crate::audio_input::AudioInput>,
     pub elevenlabs_model: Model,
 }
 
-pub struct AudioPlayer {
-    _stream: OutputStream,
-    app_handle: Option<tauri::AppHandle>,
-}
-
-impl AudioPlayer {
-    pub fn new(_assets_dir: std::path::PathBuf, output_device_name: Option<String>) -> Result<Self> {
-        println!(
-            "[DEBUG] Initializing AudioPlayer with assets dir: {:?}",
-            _assets_dir
-        );
-        let stream = if let Some(name) = output_device_name.as_deref() {
-            // Try to open specific output device by name
-            let host = cpal::default_host();
-            if let Ok(mut devs) = host.output_devices() {
-                let name_lower = name.to_lowercase();
-                if let Some(device) = devs.find(|d| d.name().map(|n| n.to_lowercase().contains(&name_lower)).unwrap_or(false)) {
-                    println!("[INFO] Using output device by name: {}", device.name().unwrap_or_else(|_| "<unknown>".into()));
-                    rodio::OutputStreamBuilder::from_device(device)?.open_stream()?
-                } else {
-                    println!("[WARN] Output device '{}' not found. Falling back to default.", name);
-                    OutputStreamBuilder::from_default_device()?.open_stream()?
-                }
-            } else {
-                println!("[WARN] Failed to enumerate output devices. Falling back to default output.");
-                OutputStreamBuilder::from_default_device()?.open_stream()?
-            }
-        } else {
-            OutputStreamBuilder::from_default_device()?.open_stream()?
-        };
-        Ok(Self {
-            _stream: stream,
-            app_handle: None,
-        })
-    }
-
-    pub fn new_with_app_handle(app_handle: tauri::AppHandle, output_device_name: Option<String>) -> Result<Self> {
-        println!("[DEBUG] Initializing AudioPlayer with app handle");
-        let stream = if let Some(name) = output_device_name.as_deref() {
-            let host = cpal::default_host();
-            if let Ok(mut devs) = host.output_devices() {
-                let name_lower = name.to_lowercase();
-                if let Some(device) = devs.find(|d| d.name().map(|n| n.to_lowercase().contains(&name_lower)).unwrap_or(false)) {
-                    println!("[INFO] Using output device by name: {}", device.name().unwrap_or_else(|_| "<unknown>".into()));
-                    rodio::OutputStreamBuilder::from_device(device)?.open_stream()?
-                } else {
-                    println!("[WARN] Output device '{}' not found. Falling back to default.", name);
-                    OutputStreamBuilder::from_default_device()?.open_stream()?
-                }
-            } else {
-                println!("[WARN] Failed to enumerate output devices. Falling back to default output.");
-                OutputStreamBuilder::from_default_device()?.open_stream()?
-            }
-        } else {
-            OutputStreamBuilder::from_default_device()?.open_stream()?
-        };
-        Ok(Self {
-            _stream: stream,
-            app_handle: Some(app_handle),
-        })
-    }
-    pub fn play_sound<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let requested_path = PathBuf::from(path.as_ref());
-        let sound_path = if let Some(app_handle) = &self.app_handle {
-            // 1) Prefer user-overridden asset in roaming dir: <AppData>/assets/<file>
-            let assets_dir = app_handle
-                .path()
-                .app_config_dir()
-                .unwrap_or_else(|_| PathBuf::from("."))
-                .join("assets");
-
-            // If the requested path starts with the "assets" component, strip that for user dir
-            use std::path::Component;
-            let user_rel: PathBuf = match requested_path.components().next() {
-                Some(Component::Normal(first)) if first == std::ffi::OsStr::new("assets") => {
-                    requested_path.components().skip(1).collect()
-                }
-                _ => requested_path.clone(),
-            };
-            let user_override = assets_dir.join(user_rel);
-            if user_override.exists() {
-                println!(
-                    "[DEBUG] Playing sound from user assets override: {}",
-                    user_override.display()
-                );
-                user_override
-            } else {
-                // 2) Fallback to bundled resource inside the app
-                let resource_path = app_handle
-                    .path()
-                    .resolve(&requested_path, tauri::path::BaseDirectory::Resource);
-                if let Ok(resolved_path) = resource_path {
-                    if resolved_path.exists() {
-                        println!(
-                            "[DEBUG] Playing sound from bundled resource: {}",
-                            resolved_path.display()
-                        );
-                        resolved_path
-                    } else {
-                        // 3) Dev public assets (when running in dev)
-                        let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-                        if let Some(parent) = current_dir.parent() {
-                            let dev_path = parent.join("public").join(&requested_path);
-                            if dev_path.exists() {
-                                println!(
-                                    "[DEBUG] Playing sound from dev public assets: {}",
-                                    dev_path.display()
-                                );
-                                dev_path
-                            } else {
-                                println!(
-                                    "[DEBUG] Neither user override, bundled resource, nor dev asset exists; falling back to requested path: {}",
-                                    requested_path.display()
-                                );
-                                requested_path.clone()
-                            }
-                        } else {
-                            println!(
-                                "[DEBUG] No parent dir to resolve dev assets; falling back to requested path: {}",
-                                requested_path.display()
-                            );
-                            requested_path.clone()
-                        }
-                    }
-                } else {
-                    // If resolve fails entirely, also try dev public assets
-                    let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-                    if let Some(parent) = current_dir.parent() {
-                        let dev_path = parent.join("public").join(&requested_path);
-                        if dev_path.exists() {
-                            println!(
-                                "[DEBUG] Playing sound from dev public assets: {}",
-                                dev_path.display()
-                            );
-                            dev_path
-                        } else {
-                            println!(
-                                "[DEBUG] Failed to resolve bundled resource and dev asset missing; falling back to requested path: {}",
-                                requested_path.display()
-                            );
-                            requested_path.clone()
-                        }
-                    } else {
-                        println!(
-                            "[DEBUG] Failed to resolve bundled resource and no parent dir; falling back to requested path: {}",
-                            requested_path.display()
-                        );
-                        requested_path.clone()
-                    }
-                }
-            }
-        } else {
-            // Legacy fallback - try to find the sound file in the current directory
-            println!(
-                "[DEBUG] Playing sound from legacy path: {}",
-                requested_path.display()
-            );
-            requested_path.clone()
-        };
-
-        let sink = Sink::connect_new(&self._stream.mixer());
-        let file = File::open(sound_path)?;
-        let source = Decoder::new(BufReader::new(file))?;
-        sink.append(source);
-        sink.detach();
-        Ok(())
+impl Drop for AppContext {
+    // release the microphone whenever a run ends, including on errors; the
+    // capture thread itself is kept for the next start
+    fn drop(&mut self) {
+        self.audio_input.stop();
     }
 }
 
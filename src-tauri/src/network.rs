@@ -0,0 +1,437 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Network tools: "what's my IP", "is the internet down", "turn off Wi-Fi", "turn on my
+// VPN". The internet check times TCP connections to a few well-known servers and looks
+// up a name, so a dead DNS server is told apart from no connection at all. Wi-Fi goes
+// through `netsh wlan` and the VPN through `rasdial`, both of which work without admin
+// rights; switching either one asks for confirmation first. Each tool can be turned off
+// on its own, and a turned-off tool is left to the LLM like any other question.
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::net::UdpSocket;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct NetworkConfig {
+    // "what's my IP"; the public address comes from api.ipify.org
+    pub ip_lookup: bool,
+    // "is the internet down"
+    pub connectivity_check: bool,
+    pub wifi_control: bool,
+    pub vpn_control: bool,
+    // the connection in Settings > Network & internet > VPN, with its sign-in saved
+    pub vpn_name: String,
+    // the Wi-Fi network "turn on Wi-Fi" joins when Jarvis didn't turn it off itself
+    pub wifi_profile: String,
+    // servers timed by "is the internet down", as address:port
+    pub check_hosts: Vec<String>,
+    // looked up to tell whether DNS works
+    pub check_domain: String,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            ip_lookup: true,
+            connectivity_check: true,
+            wifi_control: true,
+            vpn_control: true,
+            vpn_name: String::new(),
+            wifi_profile: String::new(),
+            check_hosts: vec![
+                "1.1.1.1:443".to_string(),
+                "8.8.8.8:443".to_string(),
+                "9.9.9.9:443".to_string(),
+            ],
+            check_domain: "example.com".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HostLatency {
+    pub host: String,
+    // None when it didn't answer in time
+    pub ms: Option<u64>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectivityReport {
+    pub local_ip: Option<String>,
+    pub hosts: Vec<HostLatency>,
+    pub dns_ok: bool,
+}
+
+// "what's my IP", "this computer's local IP address"; "the IP protocol" isn't asked here
+static IP: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:my|this pc's|this computer's)\s+(?:public\s+|local\s+|external\s+)?ip\b",
+        r"|\bthe\s+(?:public|local|external)\s+ip\b",
+    ))
+    .expect("Failed to compile IP regex")
+});
+
+// "is the internet down", "is my connection slow", "am I online"
+static DOWN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:the|my)\s+(?:internet|connection|network|wi-?fi)\s+(?:is\s+)?",
+        r"(?:down|working|up|slow|out)\b",
+        r"|^\s*(?:is\s+)?(?:the\s+)?internet\s+(?:down|working|out)\b",
+        r"|\bam i (?:online|offline|connected)\b|\bcheck (?:the |my )?(?:internet|connection)\b",
+        r"|\bdo i have (?:the )?internet\b",
+    ))
+    .expect("Failed to compile connectivity regex")
+});
+
+// "turn off the Wi-Fi", "am I connected to Wi-Fi"; not "what's the best Wi-Fi router"
+static WIFI: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:my|the)\s+(?:wi-?fi|wireless)\s*(?:[?.!]|$|\b(?:on|off|connected|network)\b)",
+        r"|\b(?:wi-?fi|wireless)\s+(?:on|off)\b",
+        r"|\b(?:turn|switch)\s+(?:on|off)\s+(?:the\s+)?(?:wi-?fi|wireless)\b",
+        r"|\b(?:connected|connect|disconnect)\s+(?:to\s+|from\s+)?(?:the\s+)?wi-?fi\b",
+    ))
+    .expect("Failed to compile Wi-Fi regex")
+});
+
+// "turn on my VPN", "is the VPN connected"; not "what is a VPN"
+static VPN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:my|the)\s+vpn\b|\bvpn\s+(?:on|off|connected|up|down)\b",
+        r"|\b(?:connect|disconnect)\s+(?:to\s+|from\s+)?vpn\b",
+    ))
+    .expect("Failed to compile VPN regex")
+});
+
+static ON: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:turn|switch)\s+(?:\S+\s+){0,2}?on\b|\b(?:connect|enable|start)\b")
+        .expect("Failed to compile switch-on regex")
+});
+
+static OFF: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:turn|switch)\s+(?:\S+\s+){0,2}?off\b|\b(?:disconnect|disable|stop)\b")
+        .expect("Failed to compile switch-off regex")
+});
+
+// "is my VPN on", "am I connected to Wi-Fi"
+static QUESTION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(?:is|am|are|what)\b").expect("Failed to compile question regex")
+});
+
+// the Wi-Fi network Jarvis disconnected from, for turning it back on
+static LAST_WIFI: Mutex<Option<String>> = Mutex::new(None);
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+// rasdial can take a while to sign in
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(45);
+// slower than this and the answer says so
+const SLOW_MS: u64 = 300;
+
+fn client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .user_agent(concat!("Jarvis/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| anyhow!("Failed to build HTTP client: {e}"))
+}
+
+// The address this PC uses to reach the internet. Connecting a UDP socket only picks a
+// route, nothing is sent.
+fn local_ip() -> Option<String> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_unspecified()).then(|| ip.to_string())
+}
+
+async fn public_ip() -> Result<String> {
+    let ip = client()?
+        .get("https://api.ipify.org")
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    Ok(ip.trim().to_string())
+}
+
+async fn connect_ms(host: &str) -> Option<u64> {
+    let started = Instant::now();
+    match tokio::time::timeout(CHECK_TIMEOUT, TcpStream::connect(host)).await {
+        Ok(Ok(_)) => Some(started.elapsed().as_millis() as u64),
+        _ => None,
+    }
+}
+
+pub async fn check(config: &NetworkConfig) -> ConnectivityReport {
+    let hosts = futures::future::join_all(config.check_hosts.iter().map(|host| async move {
+        HostLatency {
+            host: host.clone(),
+            ms: connect_ms(host).await,
+        }
+    }))
+    .await;
+    let domain = format!("{}:443", config.check_domain.trim());
+    let dns_ok = match tokio::time::timeout(CHECK_TIMEOUT, tokio::net::lookup_host(domain)).await {
+        Ok(Ok(mut addrs)) => addrs.next().is_some(),
+        _ => false,
+    };
+    ConnectivityReport {
+        local_ip: local_ip(),
+        hosts,
+        dns_ok,
+    }
+}
+
+fn describe(report: &ConnectivityReport) -> String {
+    let answered: Vec<u64> = report.hosts.iter().filter_map(|h| h.ms).collect();
+    let Some(&fastest) = answered.iter().min() else {
+        if report.local_ip.is_none() {
+            return "You're not connected to any network.".to_string();
+        }
+        return "The internet looks down. You're on the local network, but none of the \
+                servers I tried answered."
+            .to_string();
+    };
+    if !report.dns_ok {
+        return "You're online, but looking up websites fails, so most things won't load. \
+                Your DNS server may be down."
+            .to_string();
+    }
+    let mut reply = format!(
+        "The internet is up, {} milliseconds to the nearest server.",
+        fastest
+    );
+    if fastest > SLOW_MS {
+        reply.push_str(" That's slow.");
+    }
+    if answered.len() < report.hosts.len() {
+        reply.push_str(&format!(
+            " Only {} of the {} servers I tried answered, though.",
+            answered.len(),
+            report.hosts.len()
+        ));
+    }
+    reply
+}
+
+// Runs a built-in Windows tool; its output, or the line that says what went wrong
+async fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let mut cmd = tokio::process::Command::new(program);
+    cmd.args(args).kill_on_drop(true);
+    #[cfg(target_os = "windows")]
+    {
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let out = tokio::time::timeout(COMMAND_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| format!("{} took too long", program))?
+        .map_err(|e| format!("couldn't run {}: {}", program, e))?;
+    let text = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    if out.status.success() {
+        return Ok(text);
+    }
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    let lines = || text.lines().chain(stderr.lines()).map(str::trim);
+    Err(lines()
+        .find(|l| l.to_lowercase().contains("error"))
+        .or_else(|| lines().find(|l| !l.is_empty()))
+        .unwrap_or("it failed")
+        .trim_end_matches('.')
+        .to_string())
+}
+
+// (connected, network name) from `netsh wlan show interfaces`
+async fn wifi_status() -> Result<(bool, Option<String>), String> {
+    let out = run("netsh", &["wlan", "show", "interfaces"]).await?;
+    let field = |name: &str| {
+        out.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim().eq_ignore_ascii_case(name)).then(|| value.trim().to_string())
+        })
+    };
+    let connected = field("State").is_some_and(|s| s.eq_ignore_ascii_case("connected"));
+    Ok((connected, field("Profile").or_else(|| field("SSID"))))
+}
+
+async fn wifi_off() -> String {
+    if let Ok((true, Some(profile))) = wifi_status().await {
+        *LAST_WIFI.lock().unwrap() = Some(profile);
+    }
+    match run("netsh", &["wlan", "disconnect"]).await {
+        Ok(_) => "Wi-Fi is off.".to_string(),
+        Err(e) => format!("Sorry, I couldn't turn off Wi-Fi: {}.", e),
+    }
+}
+
+async fn wifi_on(profile: String) -> String {
+    let name = format!("name={}", profile);
+    match run("netsh", &["wlan", "connect", name.as_str()]).await {
+        Ok(_) => format!("Connecting to {}.", profile),
+        Err(e) => format!("Sorry, I couldn't connect to {}: {}.", profile, e),
+    }
+}
+
+async fn vpn_connected(name: &str) -> Result<bool, String> {
+    // lists the connected entries, or says "No connections"
+    let out = run("rasdial", &[]).await?;
+    Ok(out.lines().any(|l| l.trim().eq_ignore_ascii_case(name)))
+}
+
+async fn vpn_switch(name: String, on: bool) -> String {
+    let result = if on {
+        run("rasdial", &[name.as_str()]).await
+    } else {
+        run("rasdial", &[name.as_str(), "/disconnect"]).await
+    };
+    match (result, on) {
+        (Ok(_), true) => format!("You're connected to {}.", name),
+        (Ok(_), false) => format!("Disconnected from {}.", name),
+        (Err(e), true) => format!("Sorry, I couldn't connect to {}: {}.", name, e),
+        (Err(e), false) => format!("Sorry, I couldn't disconnect from {}: {}.", name, e),
+    }
+}
+
+// On or off, when exactly one of them was asked for
+fn switch_direction(prompt: &str) -> Option<bool> {
+    match (ON.is_match(prompt), OFF.is_match(prompt)) {
+        (true, false) => Some(true),
+        (false, true) => Some(false),
+        _ => None,
+    }
+}
+
+async fn wifi(prompt: &str, config: &NetworkConfig) -> Option<String> {
+    let direction = switch_direction(prompt);
+    if QUESTION.is_match(prompt) || direction.is_none() {
+        return Some(match wifi_status().await {
+            Ok((true, Some(name))) => format!("You're connected to {} over Wi-Fi.", name),
+            Ok((true, None)) => "Wi-Fi is connected.".to_string(),
+            Ok((false, _)) => "Wi-Fi isn't connected.".to_string(),
+            Err(e) => format!("Sorry, I couldn't check Wi-Fi: {}.", e),
+        });
+    }
+    if direction == Some(false) {
+        return Some(crate::confirm::ask(
+            "turn off Wi-Fi",
+            "Turn off Wi-Fi? I may not hear back from the internet until it's on again.",
+            wifi_off(),
+        ));
+    }
+    let remembered = LAST_WIFI.lock().unwrap().clone();
+    let Some(profile) = remembered.or_else(|| {
+        let configured = config.wifi_profile.trim();
+        (!configured.is_empty()).then(|| configured.to_string())
+    }) else {
+        return Some(
+            "I don't know which Wi-Fi network to join. Pick one under Network in the settings."
+                .to_string(),
+        );
+    };
+    let question = format!("Connect to the Wi-Fi network {}?", profile);
+    Some(crate::confirm::ask(
+        "turn on Wi-Fi",
+        &question,
+        wifi_on(profile),
+    ))
+}
+
+async fn vpn(prompt: &str, config: &NetworkConfig) -> Option<String> {
+    let name = config.vpn_name.trim().to_string();
+    if name.is_empty() {
+        return Some(
+            "I don't know which VPN to use. Add its name under Network in the settings."
+                .to_string(),
+        );
+    }
+    let direction = switch_direction(prompt);
+    if QUESTION.is_match(prompt) || direction.is_none() {
+        return Some(match vpn_connected(&name).await {
+            Ok(true) => format!("You're connected to {}.", name),
+            Ok(false) => format!("You're not connected to {}.", name),
+            Err(e) => format!("Sorry, I couldn't check the VPN: {}.", e),
+        });
+    }
+    let on = direction == Some(true);
+    let (what, question) = if on {
+        ("connect the VPN", format!("Connect to {}?", name))
+    } else {
+        ("disconnect the VPN", format!("Disconnect from {}?", name))
+    };
+    Some(crate::confirm::ask(what, &question, vpn_switch(name, on)))
+}
+
+async fn ip_reply() -> String {
+    let local = local_ip();
+    match (public_ip().await, local) {
+        (Ok(public), Some(local)) => format!(
+            "Your public IP is {}, and this PC is {} on the local network.",
+            public, local
+        ),
+        (Ok(public), None) => format!("Your public IP is {}.", public),
+        (Err(e), Some(local)) => {
+            eprintln!("[WARN] Public IP lookup failed: {}", e);
+            format!(
+                "This PC is {} on the local network. I couldn't get your public IP.",
+                local
+            )
+        }
+        (Err(e), None) => {
+            eprintln!("[WARN] Public IP lookup failed: {}", e);
+            "You don't seem to be connected to a network.".to_string()
+        }
+    }
+}
+
+pub async fn answer(prompt: &str, config: &NetworkConfig) -> Option<String> {
+    if VPN.is_match(prompt) {
+        if !config.vpn_control {
+            return None;
+        }
+        return vpn(prompt, config).await;
+    }
+    // "is the Wi-Fi down" is an internet check
+    if WIFI.is_match(prompt) && !DOWN.is_match(prompt) {
+        if !config.wifi_control {
+            return None;
+        }
+        return wifi(prompt, config).await;
+    }
+    if IP.is_match(prompt) && config.ip_lookup {
+        return Some(ip_reply().await);
+    }
+    if DOWN.is_match(prompt) && config.connectivity_check {
+        return Some(describe(&check(config).await));
+    }
+    None
+}
+
+#[tauri::command]
+pub async fn cmd_check_connectivity(app: tauri::AppHandle) -> Result<ConnectivityReport, String> {
+    let config = crate::cmd_load_config(app)?;
+    Ok(check(&config.network).await)
+}
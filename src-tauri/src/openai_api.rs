@@ -0,0 +1,244 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// OpenAI-compatible chat endpoint on the local API, so editors and scripts that speak
+// that format can use Jarvis as their assistant: answers come from the configured
+// model with Jarvis's system prompt, active persona and memories. Only the messages'
+// text is sent on; clipboard phrases, local files and links in them aren't acted on,
+// and Jarvis's tools aren't offered. Function calling isn't supported either: `tools` in
+// a request are ignored and answers never contain tool calls. Nothing is written to the
+// chat history and nothing new is learned into memory.
+
+use crate::local_api::{authorize, ApiState};
+use crate::models::Config;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::convert::Infallible;
+
+// the name clients use for "whatever Jarvis is set up with"
+const JARVIS_MODEL: &str = "jarvis";
+
+// the Gemini models clients may ask for by name, as offered in the settings
+const CHAT_MODELS: &[&str] = &[
+    "gemini-2.5-pro",
+    "gemini-2.5-flash",
+    "gemini-2.5-flash-lite",
+    "gemini-2.0-flash",
+    "gemini-2.0-flash-lite",
+];
+
+#[derive(Deserialize, Debug)]
+struct ChatMessage {
+    role: String,
+    // a string, or a list of parts of which the text ones are used
+    #[serde(default)]
+    content: Value,
+}
+
+impl ChatMessage {
+    fn text(&self) -> String {
+        match &self.content {
+            Value::String(s) => s.clone(),
+            Value::Array(parts) => parts
+                .iter()
+                .filter_map(|p| p.get("text").and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => String::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct ChatRequest {
+    #[serde(default)]
+    model: String,
+    messages: Vec<ChatMessage>,
+    #[serde(default)]
+    stream: bool,
+    temperature: Option<f32>,
+    top_p: Option<f32>,
+    max_tokens: Option<i32>,
+    max_completion_tokens: Option<i32>,
+    // only looked at to warn that they're ignored
+    #[serde(default)]
+    tools: Vec<Value>,
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    let body = json!({ "error": { "message": message.into(), "type": "jarvis_error" } });
+    (status, Json(body)).into_response()
+}
+
+// The request's own settings win over Jarvis's. Models other than the ones /v1/models
+// lists (clients send "gpt-4o" and the like by default) keep the configured one.
+fn apply_request(config: &mut Config, request: &ChatRequest) {
+    let model = request.model.trim();
+    if let Some(known) = CHAT_MODELS.iter().find(|m| m.eq_ignore_ascii_case(model)) {
+        config.gemini_model = known.to_string();
+    }
+    let g = &mut config.generation;
+    g.temperature = request.temperature.or(g.temperature);
+    g.top_p = request.top_p.or(g.top_p);
+    g.max_output_tokens = request
+        .max_completion_tokens
+        .or(request.max_tokens)
+        .or(g.max_output_tokens);
+}
+
+// Splits the messages into the prompt (the last user message) and the context before it,
+// formatted like a Jarvis conversation; system messages are added to the system prompt
+fn split_messages(config: &mut Config, messages: &[ChatMessage]) -> Option<(String, String)> {
+    let last_user = messages.iter().rposition(|m| m.role == "user")?;
+    let mut ctx_text = String::new();
+    for message in &messages[..last_user] {
+        let text = message.text();
+        match message.role.as_str() {
+            "system" | "developer" => {
+                config.llm_system_prompt.push_str("\n\n");
+                config.llm_system_prompt.push_str(&text);
+            }
+            role => ctx_text.push_str(&format!("{}: {}\n", role.to_uppercase(), text)),
+        }
+    }
+    Some((messages[last_user].text(), ctx_text))
+}
+
+async fn answer(
+    app: &tauri::AppHandle,
+    request: &ChatRequest,
+) -> Result<(String, Config), Response> {
+    let config = crate::cmd_load_config(app.clone())
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    if config.gemini_key.trim().is_empty() {
+        return Err(error(StatusCode::SERVICE_UNAVAILABLE, "Jarvis has no Gemini API key set"));
+    }
+    let mut config = crate::to_run_config(config);
+    crate::persona::apply_active(app, &mut config);
+    apply_request(&mut config, request);
+    let (prompt, history) = split_messages(&mut config, &request.messages).ok_or_else(|| {
+        error(StatusCode::BAD_REQUEST, "messages needs at least one user message")
+    })?;
+
+    let ctx_text = format!("{}{}", crate::memory::prompt_section(app, &config, &prompt), history);
+
+    let answer = match crate::send_to_llm::query_gemini_text(&prompt, &config, &ctx_text).await {
+        Ok(answer) => answer,
+        Err(e) => match crate::send_to_llm::Unanswered::from_error(&e) {
+            // an explanation is still a valid answer for the client to show
+            Some(unanswered) => unanswered.message,
+            None => return Err(error(StatusCode::BAD_GATEWAY, e.to_string())),
+        },
+    };
+    crate::stats::record_interaction(false);
+    Ok((crate::transform_text::strip_copy_tags(&answer), config))
+}
+
+fn completion_id() -> String {
+    format!("chatcmpl-{}", chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default())
+}
+
+pub(crate) async fn chat_completions(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Json(request): Json<ChatRequest>,
+) -> Response {
+    if let Err(status) = authorize(&state, &headers) {
+        return error(status, "Missing or wrong API token");
+    }
+    if !request.tools.is_empty() {
+        eprintln!(
+            "[WARN] Chat request offered {} tool(s); function calling isn't supported",
+            request.tools.len()
+        );
+    }
+    let (text, config) = match answer(&state.app, &request).await {
+        Ok(answered) => answered,
+        Err(response) => return response,
+    };
+    let id = completion_id();
+    let created = chrono::Utc::now().timestamp();
+    let model = config.gemini_model;
+
+    if request.stream {
+        // the answer is complete already, so it goes out as a single chunk
+        let chunk = |delta: Value, finish: Value| {
+            json!({
+                "id": id,
+                "object": "chat.completion.chunk",
+                "created": created,
+                "model": model,
+                "choices": [{ "index": 0, "delta": delta, "finish_reason": finish }],
+            })
+        };
+        let delta = json!({ "role": "assistant", "content": text });
+        let events = vec![
+            Event::default().data(chunk(delta, Value::Null).to_string()),
+            Event::default().data(chunk(json!({}), json!("stop")).to_string()),
+            Event::default().data("[DONE]"),
+        ];
+        let stream = futures_util::stream::iter(events.into_iter().map(Ok::<_, Infallible>));
+        return Sse::new(stream).into_response();
+    }
+
+    let prompt_tokens: usize = request
+        .messages
+        .iter()
+        .map(|m| crate::context_budget::estimate_tokens(&m.text()))
+        .sum();
+    let completion_tokens = crate::context_budget::estimate_tokens(&text);
+    Json(json!({
+        "id": id,
+        "object": "chat.completion",
+        "created": created,
+        "model": model,
+        "choices": [{
+            "index": 0,
+            "message": { "role": "assistant", "content": text },
+            "finish_reason": "stop",
+        }],
+        "usage": {
+            "prompt_tokens": prompt_tokens,
+            "completion_tokens": completion_tokens,
+            "total_tokens": prompt_tokens + completion_tokens,
+        },
+    }))
+    .into_response()
+}
+
+// Clients often list the models before the first request
+pub(crate) async fn models(State(state): State<ApiState>, headers: HeaderMap) -> Response {
+    if let Err(status) = authorize(&state, &headers) {
+        return error(status, "Missing or wrong API token");
+    }
+    let configured = crate::cmd_load_config(state.app.clone())
+        .map(|c| c.gemini_model)
+        .unwrap_or_default();
+    let mut ids = vec![JARVIS_MODEL, configured.as_str()];
+    ids.extend(CHAT_MODELS.iter().filter(|m| **m != configured));
+    let data: Vec<Value> = ids
+        .iter()
+        .filter(|id| !id.is_empty())
+        .map(|id| json!({ "id": id, "object": "model", "created": 0, "owned_by": "jarvis" }))
+        .collect();
+    Json(json!({ "object": "list", "data": data })).into_response()
+}
@@ -0,0 +1,120 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Small always-on-top "listening" widget. It loads the same frontend with the
+// `#overlay` route and follows `jarvis-state-changed` and `partial-transcript`
+// like the main window, so the status is visible with the main window hidden.
+
+use crate::events::{self, PartialTranscript};
+use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
+
+pub const OVERLAY_LABEL: &str = "overlay";
+
+// logical size of the widget and its distance from the screen edges
+const WIDTH: f64 = 320.0;
+const HEIGHT: f64 = 72.0;
+const MARGIN: f64 = 24.0;
+
+// Sent to every window; the overlay shows it under the state label
+pub fn emit_transcript(app: &tauri::AppHandle, text: &str, is_final: bool) {
+    events::emit(
+        app,
+        PartialTranscript {
+            text: text.trim().to_string(),
+            is_final,
+        },
+    );
+}
+
+// The monitor the cursor is on, so the widget shows up where the user is working
+fn target_monitor(app: &tauri::AppHandle) -> Option<tauri::Monitor> {
+    app.cursor_position()
+        .ok()
+        .and_then(|p| app.monitor_from_point(p.x, p.y).ok().flatten())
+        .or_else(|| app.primary_monitor().ok().flatten())
+}
+
+// Bottom corner of the monitor's work area on the `dock_position` side
+fn position_window(app: &tauri::AppHandle, win: &tauri::WebviewWindow, dock: &str) {
+    let Some(monitor) = target_monitor(app) else {
+        return;
+    };
+    let scale = monitor.scale_factor();
+    let area = monitor.work_area();
+    let width = (WIDTH * scale) as i32;
+    let height = (HEIGHT * scale) as i32;
+    let margin = (MARGIN * scale) as i32;
+    let x = if dock == "left" {
+        area.position.x + margin
+    } else {
+        area.position.x + area.size.width as i32 - width - margin
+    };
+    let y = area.position.y + area.size.height as i32 - height - margin;
+    let _ = win.set_position(tauri::PhysicalPosition::new(x, y));
+}
+
+pub fn show(app: &tauri::AppHandle) -> Result<(), String> {
+    let dock = crate::cmd_load_config(app.clone())
+        .ok()
+        .and_then(|c| c.dock_position)
+        .unwrap_or_else(|| "right".to_string());
+
+    if let Some(win) = app.get_webview_window(OVERLAY_LABEL) {
+        position_window(app, &win, &dock);
+        return win.show().map_err(|e| e.to_string());
+    }
+
+    let win = WebviewWindowBuilder::new(app, OVERLAY_LABEL, WebviewUrl::App("index.html#overlay".into()))
+        .title("Jarvis")
+        .inner_size(WIDTH, HEIGHT)
+        .resizable(false)
+        .decorations(false)
+        .transparent(true)
+        .shadow(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        // never steal focus from what the user is typing in
+        .focused(false)
+        .visible(false)
+        .build()
+        .map_err(|e| e.to_string())?;
+    position_window(app, &win, &dock);
+    win.show().map_err(|e| e.to_string())
+}
+
+pub fn hide(app: &tauri::AppHandle) -> Result<(), String> {
+    match app.get_webview_window(OVERLAY_LABEL) {
+        Some(win) => win.destroy().map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+pub fn is_visible(app: &tauri::AppHandle) -> bool {
+    app.get_webview_window(OVERLAY_LABEL)
+        .and_then(|w| w.is_visible().ok())
+        .unwrap_or(false)
+}
+
+#[tauri::command]
+pub fn cmd_show_overlay(app: tauri::AppHandle) -> Result<(), String> {
+    show(&app)
+}
+
+#[tauri::command]
+pub fn cmd_hide_overlay(app: tauri::AppHandle) -> Result<(), String> {
+    hide(&app)
+}
@@ -0,0 +1,512 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Package tracking: "track my package 1Z999AA10123456784" keeps the number in
+// packages.json next to config.json, "where's my package" reads out how each one is
+// doing. AfterShip or 17TRACK (both work out the carrier from the number) is asked
+// every `poll_minutes`, and a package that moves on, e.g. goes out for delivery, is
+// announced without being asked.
+
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+use ts_rs::TS;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackingProvider {
+    Aftership,
+    Seventeentrack,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PackagesConfig {
+    pub provider: TrackingProvider,
+    pub api_key: String,
+    pub poll_minutes: u64,
+    // speak status changes found by the background check
+    pub announce: bool,
+}
+
+impl Default for PackagesConfig {
+    fn default() -> Self {
+        Self {
+            provider: TrackingProvider::Aftership,
+            api_key: String::new(),
+            poll_minutes: 30,
+            announce: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, TS)]
+#[serde(default)]
+#[ts(export)]
+pub struct Package {
+    pub number: String,
+    // who it's from, e.g. "Amazon"; may be empty
+    pub label: String,
+    // the provider's status tag, e.g. "InTransit"; empty until the first check
+    pub status: String,
+    // the latest carrier event, e.g. "Arrived at facility in Leipzig"
+    pub detail: String,
+    pub delivered: bool,
+    // whether the provider has been told about the number yet
+    pub registered: bool,
+    pub added_at: String,
+    pub updated_at: String,
+}
+
+// "track my package 1Z 999 AA1 ...": Whisper tends to space out spelled numbers
+static TRACK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\btrack\s+(?:my|this|a|the)?\s*(?:package|parcel|shipment|order|delivery)\s+",
+        r"(?:number\s+)?([a-z0-9][a-z0-9 \-]{6,}?[a-z0-9])(?:\s+(?:from|for)\s+(.+?))?[?.!]*\s*$"
+    ))
+    .expect("Failed to compile package tracking regex")
+});
+
+static WHERE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:where(?:'s| is| are)|status of|has|have|did|when (?:is|are|will))\s+",
+        r"(?:my|the)\s+(?:packages?|parcels?|shipments?|deliver(?:y|ies))\b"
+    ))
+    .expect("Failed to compile package status regex")
+});
+
+// one load-modify-save at a time; the poller and the commands both write the file
+static STORE: Mutex<()> = Mutex::new(());
+
+fn packages_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join("packages.json"))
+}
+
+fn load(app: &tauri::AppHandle) -> Vec<Package> {
+    packages_path(app)
+        .ok()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save(app: &tauri::AppHandle, packages: &[Package]) -> Result<(), String> {
+    let s = serde_json::to_string_pretty(packages).map_err(|e| e.to_string())?;
+    crate::history::atomic_write(&packages_path(app)?, s.as_bytes()).map_err(|e| e.to_string())?;
+    crate::events::emit(app, crate::events::PackagesChanged(packages.to_vec()));
+    Ok(())
+}
+
+fn modify<T>(app: &tauri::AppHandle, f: impl FnOnce(&mut Vec<Package>) -> T) -> Result<T, String> {
+    let _guard = STORE.lock().unwrap();
+    let mut packages = load(app);
+    let out = f(&mut packages);
+    save(app, &packages)?;
+    Ok(out)
+}
+
+fn normalize_number(said: &str) -> Option<String> {
+    let number: String = said
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase();
+    let valid = (8..=40).contains(&number.len()) && number.chars().any(|c| c.is_ascii_digit());
+    valid.then_some(number)
+}
+
+// "the package from Amazon", or "the package ending in 6 7 8 4"
+fn spoken_name(package: &Package) -> String {
+    if !package.label.is_empty() {
+        return format!("the package from {}", package.label);
+    }
+    let tail: Vec<String> = package
+        .number
+        .chars()
+        .rev()
+        .take(4)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .map(String::from)
+        .collect();
+    format!("the package ending in {}", tail.join(" "))
+}
+
+// AfterShip and 17TRACK use mostly the same tags
+fn spoken_status(status: &str) -> &'static str {
+    match status.to_lowercase().replace(['_', ' '], "").as_str() {
+        "" => "hasn't been checked yet",
+        "pending" | "inforeceived" => "has been announced, but the carrier doesn't have it yet",
+        "intransit" => "is on its way",
+        "outfordelivery" => "is out for delivery",
+        "availableforpickup" => "is ready for pickup",
+        "delivered" => "has been delivered",
+        "attemptfail" | "deliveryfailure" => "couldn't be delivered",
+        "exception" => "has a problem with its delivery",
+        "expired" => "hasn't had any updates for a long time",
+        "notfound" => "isn't known to the carrier yet",
+        _ => "has an unknown status",
+    }
+}
+
+fn describe(package: &Package) -> String {
+    let name = spoken_name(package);
+    let mut line = format!("{}{}", name[..1].to_uppercase(), &name[1..]);
+    line.push(' ');
+    line.push_str(spoken_status(&package.status));
+    if !package.detail.is_empty() && !package.delivered {
+        line.push_str(&format!(". Latest: {}", package.detail.trim_end_matches('.')));
+    }
+    line.push('.');
+    line
+}
+
+struct Update {
+    status: String,
+    detail: String,
+}
+
+fn client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .user_agent(concat!("Jarvis/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| anyhow!("Failed to build HTTP client: {e}"))
+}
+
+fn event_text(description: Option<&str>, location: Option<&str>) -> String {
+    let description = description.unwrap_or("").trim();
+    match location.map(str::trim).filter(|l| !l.is_empty()) {
+        Some(location) if !description.is_empty() => format!("{} in {}", description, location),
+        _ => description.to_string(),
+    }
+}
+
+const AFTERSHIP_URL: &str = "https://api.aftership.com/v4/trackings";
+const SEVENTEENTRACK_URL: &str = "https://api.17track.net/track/v2.2";
+
+async fn register(
+    client: &reqwest::Client,
+    config: &PackagesConfig,
+    package: &Package,
+) -> Result<()> {
+    let key = config.api_key.trim();
+    let resp = match config.provider {
+        TrackingProvider::Aftership => {
+            let body = json!({ "tracking": {
+                "tracking_number": package.number,
+                "title": package.label,
+            }});
+            client
+                .post(AFTERSHIP_URL)
+                .header("aftership-api-key", key)
+                .json(&body)
+                .send()
+                .await?
+        }
+        TrackingProvider::Seventeentrack => {
+            client
+                .post(format!("{}/register", SEVENTEENTRACK_URL))
+                .header("17token", key)
+                .json(&json!([{ "number": package.number }]))
+                .send()
+                .await?
+        }
+    };
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    // AfterShip answers 4003 for a number it already tracks
+    let data: Value = resp.json().await.unwrap_or_default();
+    if data["meta"]["code"].as_i64() == Some(4003) {
+        return Ok(());
+    }
+    Err(anyhow!("The tracking service returned {}", status))
+}
+
+async fn fetch(
+    client: &reqwest::Client,
+    config: &PackagesConfig,
+    numbers: &[String],
+) -> Result<HashMap<String, Update>> {
+    let key = config.api_key.trim();
+    let mut updates = HashMap::new();
+    match config.provider {
+        TrackingProvider::Aftership => {
+            let resp = client
+                .get(AFTERSHIP_URL)
+                .header("aftership-api-key", key)
+                .query(&[("tracking_numbers", numbers.join(","))])
+                .send()
+                .await?;
+            if !resp.status().is_success() {
+                return Err(anyhow!("AfterShip returned {}", resp.status()));
+            }
+            let data: Value = resp.json().await?;
+            for t in data["data"]["trackings"].as_array().into_iter().flatten() {
+                let Some(number) = t["tracking_number"].as_str() else {
+                    continue;
+                };
+                let last = t["checkpoints"].as_array().and_then(|c| c.last());
+                let detail = last
+                    .map(|c| event_text(c["message"].as_str(), c["location"].as_str()))
+                    .unwrap_or_default();
+                updates.insert(
+                    number.to_uppercase(),
+                    Update {
+                        status: t["tag"].as_str().unwrap_or("").to_string(),
+                        detail,
+                    },
+                );
+            }
+        }
+        TrackingProvider::Seventeentrack => {
+            let body: Vec<Value> = numbers.iter().map(|n| json!({ "number": n })).collect();
+            let resp = client
+                .post(format!("{}/gettrackinfo", SEVENTEENTRACK_URL))
+                .header("17token", key)
+                .json(&body)
+                .send()
+                .await?;
+            if !resp.status().is_success() {
+                return Err(anyhow!("17TRACK returned {}", resp.status()));
+            }
+            let data: Value = resp.json().await?;
+            for t in data["data"]["accepted"].as_array().into_iter().flatten() {
+                let Some(number) = t["number"].as_str() else {
+                    continue;
+                };
+                let info = &t["track_info"];
+                let event = &info["latest_event"];
+                updates.insert(
+                    number.to_uppercase(),
+                    Update {
+                        status: info["latest_status"]["status"]
+                            .as_str()
+                            .unwrap_or("")
+                            .to_string(),
+                        detail: event_text(
+                            event["description"].as_str(),
+                            event["location"].as_str(),
+                        ),
+                    },
+                );
+            }
+        }
+    }
+    Ok(updates)
+}
+
+// Asks the provider about every package still on its way; returns the ones whose
+// status changed
+async fn refresh(app: &tauri::AppHandle, config: &PackagesConfig) -> Result<Vec<Package>> {
+    if config.api_key.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let active: Vec<Package> = load(app).into_iter().filter(|p| !p.delivered).collect();
+    if active.is_empty() {
+        return Ok(Vec::new());
+    }
+    let client = client()?;
+    let mut registered = Vec::new();
+    for package in active.iter().filter(|p| !p.registered) {
+        match register(&client, config, package).await {
+            Ok(()) => registered.push(package.number.clone()),
+            Err(e) => eprintln!("[WARN] Failed to register package {}: {}", package.number, e),
+        }
+    }
+    let numbers: Vec<String> = active.iter().map(|p| p.number.clone()).collect();
+    let updates = fetch(&client, config, &numbers).await?;
+    let now = Local::now().to_rfc3339();
+    modify(app, |packages| {
+        let mut changed = Vec::new();
+        for package in packages.iter_mut() {
+            if registered.contains(&package.number) {
+                package.registered = true;
+            }
+            let Some(update) = updates.get(&package.number) else {
+                continue;
+            };
+            let moved = !update.status.is_empty() && update.status != package.status;
+            package.status = update.status.clone();
+            if !update.detail.is_empty() {
+                package.detail = update.detail.clone();
+            }
+            package.delivered = package.status.eq_ignore_ascii_case("delivered");
+            package.updated_at = now.clone();
+            if moved {
+                changed.push(package.clone());
+            }
+        }
+        changed
+    })
+    .map_err(|e| anyhow!(e))
+}
+
+async fn announce(app: &tauri::AppHandle, changed: &[Package]) {
+    let Ok(cfg) = crate::cmd_load_config(app.clone()) else {
+        return;
+    };
+    let cfg = crate::to_run_config(cfg);
+    for package in changed {
+        let text = describe(package);
+        crate::events::emit(
+            app,
+            crate::events::NewMessage::new(crate::events::Role::Assistant, &text)
+                .with("localCommand", true),
+        );
+        if let Err(e) = crate::tts::speak_with(&cfg, &text).await {
+            eprintln!("[ERROR] Failed to announce package update: {}", e);
+        }
+    }
+}
+
+// Called once at startup; checks the packages in the background
+pub fn init(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // let startup finish before the first check
+        tokio::time::sleep(Duration::from_secs(45)).await;
+        loop {
+            let minutes = match crate::cmd_load_config(app.clone()).map(|c| c.packages) {
+                // checked once the focus session is over or you're back, so changes still
+                // get announced
+                Ok(_) if crate::pomodoro::quiet() || crate::presence::quiet() => 5,
+                Ok(cfg) => {
+                    match refresh(&app, &cfg).await {
+                        Ok(changed) if cfg.announce && !changed.is_empty() => {
+                            announce(&app, &changed).await
+                        }
+                        Ok(_) => {}
+                        Err(e) => eprintln!("[WARN] Package check failed: {}", e),
+                    }
+                    cfg.poll_minutes
+                }
+                Err(_) => 5,
+            };
+            tokio::time::sleep(Duration::from_secs(60 * minutes.max(5))).await;
+        }
+    });
+}
+
+fn add(app: &tauri::AppHandle, number: &str, label: &str) -> Result<Package, String> {
+    let number = normalize_number(number).ok_or("That doesn't look like a tracking number")?;
+    let label = label.trim().to_string();
+    modify(app, |packages| {
+        if let Some(existing) = packages.iter_mut().find(|p| p.number == number) {
+            if !label.is_empty() {
+                existing.label = label;
+            }
+            return existing.clone();
+        }
+        let package = Package {
+            number,
+            label,
+            added_at: Local::now().to_rfc3339(),
+            ..Default::default()
+        };
+        packages.push(package.clone());
+        package
+    })
+}
+
+// The spoken answer to adding or asking about packages, or None for anything else
+pub async fn answer(
+    prompt: &str,
+    app: &tauri::AppHandle,
+    config: &PackagesConfig,
+) -> Option<String> {
+    if let Some(m) = TRACK.captures(prompt) {
+        let label = m.get(2).map(|l| l.as_str()).unwrap_or("");
+        let package = match add(app, &m[1], label) {
+            Ok(package) => package,
+            Err(e) => return Some(format!("Sorry, I couldn't add that package. {}.", e)),
+        };
+        let mut reply = format!("I'll keep an eye on {}.", spoken_name(&package));
+        if config.api_key.trim().is_empty() {
+            reply.push_str(" Add a tracking service key in the settings so I can check on it.");
+        } else if let Err(e) = refresh(app, config).await {
+            eprintln!("[WARN] Failed to check new package: {}", e);
+        }
+        return Some(reply);
+    }
+    if !WHERE.is_match(prompt) {
+        return None;
+    }
+    let Some(mut reply) = summary(app, config).await else {
+        return Some("You're not waiting for any packages.".to_string());
+    };
+    if config.api_key.trim().is_empty() {
+        reply.push_str(" I can't check for news without a tracking service key.");
+    }
+    Some(reply)
+}
+
+// One sentence per package on its way, or None when there are none; also used by the
+// briefing
+pub async fn summary(app: &tauri::AppHandle, config: &PackagesConfig) -> Option<String> {
+    if let Err(e) = refresh(app, config).await {
+        eprintln!("[ERROR] Failed to check packages: {}", e);
+    }
+    // delivered packages are still mentioned on the day they arrive
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    let lines: Vec<String> = load(app)
+        .iter()
+        .filter(|p| !p.delivered || p.updated_at.starts_with(&today))
+        .map(describe)
+        .collect();
+    (!lines.is_empty()).then(|| lines.join(" "))
+}
+
+#[tauri::command]
+pub fn cmd_list_packages(app: tauri::AppHandle) -> Vec<Package> {
+    load(&app)
+}
+
+#[tauri::command]
+pub async fn cmd_add_package(
+    app: tauri::AppHandle,
+    number: String,
+    label: String,
+) -> Result<Package, String> {
+    let package = add(&app, &number, &label)?;
+    let config = crate::cmd_load_config(app.clone())?.packages;
+    if let Err(e) = refresh(&app, &config).await {
+        eprintln!("[WARN] Failed to check new package: {}", e);
+    }
+    Ok(load(&app)
+        .into_iter()
+        .find(|p| p.number == package.number)
+        .unwrap_or(package))
+}
+
+#[tauri::command]
+pub fn cmd_remove_package(app: tauri::AppHandle, number: String) -> Result<(), String> {
+    modify(&app, |packages| packages.retain(|p| p.number != number))
+}
@@ -0,0 +1,136 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Text of PDFs linked or named in a prompt ("summarize C:\papers\attention.pdf"),
+// sent page by page up to the configured limit.
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+// keeps a long paper from crowding out the question
+const MAX_PDF_CHARS: usize = 60_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PdfConfig {
+    pub enabled: bool,
+    // pages after this are left out
+    pub max_pages: usize,
+    // read PDFs named by path in a prompt, not only linked ones; off unless opted in
+    pub local_files: bool,
+}
+
+impl Default for PdfConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_pages: 30,
+            local_files: false,
+        }
+    }
+}
+
+// quoted paths may contain spaces; unquoted ones end at the first space
+static LOCAL_PDF: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)"([^"\r\n]+?\.pdf)"|((?:[a-z]:[\\/]|~[\\/]|/)[^\s"<>|]*?\.pdf)(?:[\s.,;:!?)]|$)"#)
+        .expect("Failed to compile PDF path regex")
+});
+static URL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"https?://[^\s)]+").expect("Failed to compile URL regex"));
+
+pub fn is_pdf(content_type: Option<&str>, url: &str, bytes: &[u8]) -> bool {
+    bytes.starts_with(b"%PDF")
+        && (content_type.is_some_and(|ct| ct.to_lowercase().contains("pdf"))
+            || url.to_lowercase().split(['?', '#']).next().unwrap_or("").ends_with(".pdf"))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")) {
+        Some(rest) => std::env::var_os("USERPROFILE")
+            .or_else(|| std::env::var_os("HOME"))
+            .map(|home| PathBuf::from(home).join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+// Existing PDF files named in the prompt; paths inside links are skipped
+pub fn local_paths(prompt: &str) -> Vec<PathBuf> {
+    let links: Vec<_> = URL.find_iter(prompt).map(|m| m.range()).collect();
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for cap in LOCAL_PDF.captures_iter(prompt) {
+        let Some(m) = cap.get(1).or_else(|| cap.get(2)) else {
+            continue;
+        };
+        if links.iter().any(|r| r.contains(&m.start())) {
+            continue;
+        }
+        let path = expand_home(m.as_str().trim());
+        if path.is_file() && !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+// Extraction is CPU-bound and pdf-extract panics on some malformed files,
+// so it runs on a blocking thread where a panic only fails this PDF
+pub async fn extract(bytes: Vec<u8>, max_pages: usize) -> Result<(Vec<String>, usize)> {
+    tokio::task::spawn_blocking(move || {
+        let pages = pdf_extract::extract_text_from_mem_by_pages(&bytes)
+            .map_err(|e| anyhow!("Failed to read PDF: {}", e))?;
+        let total = pages.len();
+        Ok((pages.into_iter().take(max_pages.max(1)).collect(), total))
+    })
+    .await
+    .map_err(|_| anyhow!("The PDF could not be parsed"))?
+}
+
+// Text part for the PDF, one section per page
+pub async fn text_part(source: &str, bytes: Vec<u8>, max_pages: usize) -> Result<String> {
+    let (pages, total) = extract(bytes, max_pages).await?;
+    let mut body = String::new();
+    for (i, page) in pages.iter().enumerate() {
+        let text = page
+            .lines()
+            .map(str::trim_end)
+            .filter(|l| !l.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if text.is_empty() {
+            continue;
+        }
+        body.push_str(&format!("\n\n--- Page {} ---\n{}", i + 1, text));
+    }
+    if body.trim().is_empty() {
+        return Err(anyhow!("{} has no text layer (scanned pages?)", source));
+    }
+
+    let mut header = format!("PDF {} ({} pages", source, total);
+    if pages.len() < total {
+        header.push_str(&format!(", only the first {} are included", pages.len()));
+    }
+    header.push(')');
+    let body = match body.char_indices().nth(MAX_PDF_CHARS) {
+        Some((cut, _)) => format!("{}…", &body[..cut]),
+        None => body,
+    };
+    Ok(format!("{}:{}", header, body))
+}
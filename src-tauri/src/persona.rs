@@ -0,0 +1,124 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::models::Config;
+use crate::JarvisState;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+// A named assistant profile. Unset fields fall back to the global config.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Persona {
+    pub name: String,
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    #[serde(default)]
+    pub voice_id: Option<String>,
+    #[serde(default)]
+    pub elevenlabs_model: Option<String>,
+    #[serde(default)]
+    pub gemini_model: Option<String>,
+}
+
+pub fn find<'a>(personas: &'a [Persona], name: &str) -> Option<&'a Persona> {
+    let wanted = name.trim().to_lowercase();
+    personas.iter().find(|p| p.name.trim().to_lowercase() == wanted)
+}
+
+fn non_empty(v: &Option<String>) -> Option<String> {
+    v.as_ref().filter(|s| !s.trim().is_empty()).cloned()
+}
+
+pub fn apply(config: &mut Config, persona: &Persona) {
+    if let Some(p) = non_empty(&persona.system_prompt) {
+        config.llm_system_prompt = p;
+    }
+    if let Some(v) = non_empty(&persona.voice_id) {
+        config.voice_id = v;
+    }
+    if let Some(m) = non_empty(&persona.elevenlabs_model) {
+        config.elevenlabs_model = m;
+    }
+    if let Some(m) = non_empty(&persona.gemini_model) {
+        config.gemini_model = m;
+    }
+}
+
+pub fn active_name(app: &tauri::AppHandle) -> Option<String> {
+    app.state::<JarvisState>().active_persona.lock().unwrap().clone()
+}
+
+// applies the currently active persona (if any) and returns its name for turn metadata
+pub fn apply_active(app: &tauri::AppHandle, config: &mut Config) -> Option<String> {
+    let name = active_name(app)?;
+    let persona = find(&config.personas, &name)?.clone();
+    apply(config, &persona);
+    Some(persona.name)
+}
+
+fn set_active(app: &tauri::AppHandle, name: Option<String>) -> Result<(), String> {
+    *app.state::<JarvisState>().active_persona.lock().unwrap() = name.clone();
+    let mut cfg = crate::cmd_load_config(app.clone())?;
+    cfg.active_persona = name;
+    crate::cmd_save_config(app.clone(), cfg)
+}
+
+// "switch to work mode", "use the casual persona", ...
+pub fn match_switch_command<'a>(prompt: &str, personas: &'a [Persona]) -> Option<&'a Persona> {
+    if personas.is_empty() {
+        return None;
+    }
+    let re = Regex::new(
+        r"(?i)\b(?:switch to|change to|use|activate)\s+(?:the\s+)?(.+?)\s*(?:mode|persona|personality)?[.!]?\s*$",
+    )
+    .expect("Failed to compile persona switch regex");
+    let cap = re.captures(prompt.trim())?;
+    let requested = cap.get(1)?.as_str();
+    find(personas, requested)
+}
+
+pub fn switch_by_voice(app: &tauri::AppHandle, persona: &Persona) -> Result<(), String> {
+    set_active(app, Some(persona.name.clone()))
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PersonaList {
+    pub personas: Vec<Persona>,
+    pub active: Option<String>,
+}
+
+#[tauri::command]
+pub fn cmd_list_personas(app: tauri::AppHandle) -> Result<PersonaList, String> {
+    let cfg = crate::cmd_load_config(app.clone())?;
+    Ok(PersonaList {
+        personas: cfg.personas,
+        active: active_name(&app),
+    })
+}
+
+// pass None to go back to the plain global settings
+#[tauri::command]
+pub fn cmd_set_persona(app: tauri::AppHandle, name: Option<String>) -> Result<(), String> {
+    if let Some(n) = name.as_deref() {
+        let cfg = crate::cmd_load_config(app.clone())?;
+        if find(&cfg.personas, n).is_none() {
+            return Err(format!("Unknown persona '{}'", n));
+        }
+    }
+    set_active(&app, name)
+}
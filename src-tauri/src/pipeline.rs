@@ -0,0 +1,134 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// The voice loop as a `Pipeline` of four engines behind traits: wake word,
+// speech-to-text, the language model and the voice. `Pipeline::run` (in run_jarvis.rs)
+// is the loop itself. Jarvis runs it with the real engines (Porcupine, Whisper, Gemini,
+// ElevenLabs or the system voice); pipeline_mock.rs has stand-ins for running it without
+// keys, models or hardware.
+
+use crate::models::Config;
+use crate::send_to_llm::{self, DualAnswer};
+use crate::stt::{SttWorker, TranscriptionJob};
+use crate::tts::TtsProvider;
+use anyhow::{anyhow, Result};
+use futures_util::future::BoxFuture;
+use porcupine::Porcupine;
+
+pub trait WakeWord: Send + Sync {
+    // true when the wake word ends in this frame
+    fn detect(&self, frame: &[i16]) -> Result<bool>;
+}
+
+pub trait SpeechToText: Send + Sync {
+    fn submit(&self, samples: Vec<i16>, language: &str) -> TranscriptionJob;
+
+    // Abandons whatever is being transcribed right now
+    fn cancel_current(&self) {}
+
+    // Submits and waits; for callers that are already on a blocking path
+    fn transcribe(&self, samples: Vec<i16>, language: &str) -> Result<String> {
+        self.submit(samples, language).wait()
+    }
+}
+
+pub trait LanguageModel: Send + Sync {
+    // Answers one command; with `config.dual_response` the spoken part is a short version
+    fn answer<'a>(
+        &'a self,
+        prompt: &'a str,
+        config: &'a Config,
+        ctx_text: &'a str,
+    ) -> BoxFuture<'a, Result<DualAnswer>>;
+}
+
+pub trait Voice: Send + Sync {
+    // The provider to speak with under these settings, which a persona may have changed
+    fn provider(&self, config: &Config) -> Box<dyn TtsProvider>;
+}
+
+impl WakeWord for Porcupine {
+    fn detect(&self, frame: &[i16]) -> Result<bool> {
+        self.process(frame)
+            .map(|keyword_index| keyword_index >= 0)
+            .map_err(|e| anyhow!("Porcupine process error: {:?}", e))
+    }
+}
+
+impl SpeechToText for SttWorker {
+    fn submit(&self, samples: Vec<i16>, language: &str) -> TranscriptionJob {
+        SttWorker::submit(self, samples, language)
+    }
+
+    fn cancel_current(&self) {
+        SttWorker::cancel_current(self)
+    }
+}
+
+pub struct Gemini;
+
+impl LanguageModel for Gemini {
+    fn answer<'a>(
+        &'a self,
+        prompt: &'a str,
+        config: &'a Config,
+        ctx_text: &'a str,
+    ) -> BoxFuture<'a, Result<DualAnswer>> {
+        Box::pin(async move {
+            if config.dual_response {
+                return send_to_llm::query_gemini_dual(prompt, config, ctx_text).await;
+            }
+            let answer = send_to_llm::query_gemini(prompt, config, ctx_text).await?;
+            Ok(DualAnswer {
+                spoken: answer.clone(),
+                display: answer,
+            })
+        })
+    }
+}
+
+// ElevenLabs when it is set up, otherwise the system voice (see tts::provider_for)
+pub struct ConfiguredVoice;
+
+impl Voice for ConfiguredVoice {
+    fn provider(&self, config: &Config) -> Box<dyn TtsProvider> {
+        crate::tts::provider_for(config)
+    }
+}
+
+pub struct Pipeline {
+    pub wake_word: Box<dyn WakeWord>,
+    // None on a satellite, which leaves transcription to the main instance
+    pub stt: Option<Box<dyn SpeechToText>>,
+    pub llm: Box<dyn LanguageModel>,
+    pub voice: Box<dyn Voice>,
+    // play nothing, for runs without speakers
+    pub muted: bool,
+}
+
+impl Pipeline {
+    // The engines the voice loop normally runs with
+    pub fn live(porcupine: Porcupine, stt: Option<SttWorker>) -> Self {
+        Self {
+            wake_word: Box::new(porcupine),
+            stt: stt.map(|stt| Box::new(stt) as Box<dyn SpeechToText>),
+            llm: Box::new(Gemini),
+            voice: Box::new(ConfiguredVoice),
+            muted: false,
+        }
+    }
+}
@@ -0,0 +1,356 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Stand-ins for the engines in pipeline.rs, and a harness that runs the real voice loop
+// (Pipeline::run) over a WAV recording in a mock Tauri app: the recording is fed in as
+// if spoken into the microphone, so the wake word, endpointing, voice commands, the
+// answer and the voice all go the way they do for a user. Needs no keys, models,
+// microphone or speakers, so the loop can be checked in CI. Built with the
+// `pipeline-mock` feature.
+
+use crate::audio_input::{InputKind, InputSourceConfig, SAMPLE_RATE};
+use crate::models::Config;
+use crate::pipeline::{LanguageModel, Pipeline, SpeechToText, Voice, WakeWord};
+use crate::send_to_llm::DualAnswer;
+use crate::stt::TranscriptionJob;
+use crate::tts::{Speech, TtsProvider};
+use anyhow::{anyhow, Result};
+use futures_util::future::BoxFuture;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{Listener, Manager};
+
+// Hears the wake word at fixed times. Only the audio handed to it counts, and the
+// voice loop doesn't hand it the commands, so each time is measured from the end of
+// the previous command.
+pub struct ScriptedWakeWord {
+    // sample positions still to come
+    pending: Mutex<VecDeque<usize>>,
+    heard: Mutex<usize>,
+}
+
+impl ScriptedWakeWord {
+    pub fn at_ms(times: impl IntoIterator<Item = u64>) -> Self {
+        let mut at: Vec<usize> = times
+            .into_iter()
+            .map(|ms| (ms as usize) * SAMPLE_RATE / 1000)
+            .collect();
+        at.sort_unstable();
+        Self {
+            pending: Mutex::new(at.into()),
+            heard: Mutex::new(0),
+        }
+    }
+}
+
+impl WakeWord for ScriptedWakeWord {
+    fn detect(&self, frame: &[i16]) -> Result<bool> {
+        let mut heard = self.heard.lock().unwrap();
+        *heard += frame.len();
+        let mut pending = self.pending.lock().unwrap();
+        let mut fired = false;
+        while pending.front().is_some_and(|&at| at < *heard) {
+            pending.pop_front();
+            fired = true;
+        }
+        if fired {
+            *heard = 0;
+        }
+        Ok(fired)
+    }
+}
+
+// Returns the given transcripts in order, whatever the audio; then nothing, as if the
+// speaker had mumbled
+pub struct ScriptedStt {
+    transcripts: Mutex<VecDeque<String>>,
+}
+
+impl ScriptedStt {
+    pub fn new(transcripts: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            transcripts: Mutex::new(transcripts.into_iter().map(Into::into).collect()),
+        }
+    }
+}
+
+impl SpeechToText for ScriptedStt {
+    fn submit(&self, _samples: Vec<i16>, _language: &str) -> TranscriptionJob {
+        let next = self.transcripts.lock().unwrap().pop_front();
+        TranscriptionJob::ready(Ok(next.unwrap_or_default()))
+    }
+}
+
+// Gives the canned answers in order, then repeats the prompt back
+pub struct MockLlm {
+    answers: Mutex<VecDeque<String>>,
+}
+
+impl MockLlm {
+    pub fn new(answers: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            answers: Mutex::new(answers.into_iter().map(Into::into).collect()),
+        }
+    }
+
+    pub fn echo() -> Self {
+        Self::new(Vec::<String>::new())
+    }
+}
+
+impl LanguageModel for MockLlm {
+    fn answer<'a>(
+        &'a self,
+        prompt: &'a str,
+        _config: &'a Config,
+        _ctx_text: &'a str,
+    ) -> BoxFuture<'a, Result<DualAnswer>> {
+        let answer = self
+            .answers
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| format!("You said: {}", prompt));
+        Box::pin(async move {
+            Ok(DualAnswer {
+                spoken: answer.clone(),
+                display: answer,
+            })
+        })
+    }
+}
+
+// Says nothing and keeps what it was asked to say in `said`
+pub struct SilentVoice {
+    pub said: Arc<Mutex<Vec<String>>>,
+}
+
+impl SilentVoice {
+    pub fn new() -> Self {
+        Self {
+            said: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+impl Default for SilentVoice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct SilentTts {
+    said: Arc<Mutex<Vec<String>>>,
+}
+
+impl TtsProvider for SilentTts {
+    fn name(&self) -> &'static str {
+        "silent"
+    }
+
+    fn synthesize<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Speech>> {
+        self.said.lock().unwrap().push(text.to_string());
+        Box::pin(async { Ok(Speech::Spoken) })
+    }
+}
+
+impl Voice for SilentVoice {
+    fn provider(&self, _config: &Config) -> Box<dyn TtsProvider> {
+        Box::new(SilentTts {
+            said: Arc::clone(&self.said),
+        })
+    }
+}
+
+// A pipeline made only of stand-ins: the wake word at `wake_at_ms`, then the
+// transcripts and answers in order
+pub fn scripted(wake_at_ms: Vec<u64>, transcripts: Vec<&str>, answers: Vec<&str>) -> Pipeline {
+    Pipeline {
+        wake_word: Box::new(ScriptedWakeWord::at_ms(wake_at_ms)),
+        stt: Some(Box::new(ScriptedStt::new(transcripts))),
+        llm: Box::new(MockLlm::new(answers)),
+        voice: Box::new(SilentVoice::new()),
+        muted: true,
+    }
+}
+
+// Settings for a run: the defaults of a new install, without the keys, memory or the
+// wake word cooldown (which only guards against hearing the speakers)
+pub fn config() -> Config {
+    serde_json::from_value(json!({
+        "porcupine_key": "",
+        "gemini_key": "mock",
+        "elevenlabs_key": "",
+        "whisper_language": "en",
+        "context_window_expiration_seconds": 1800,
+        "default_microphone_index": 0,
+        "default_microphone_name": null,
+        "default_output_device_name": null,
+        "gemini_model": "gemini-2.5-flash",
+        "elevenlabs_model": "eleven_flash_v2_5",
+        "voice_id": "",
+        "llm_system_prompt": "",
+        "vad_mode": "Quality",
+        "wwd_sensitivity": 0.8,
+        "frame_duration_ms": 30,
+        "silence_threshold_ms": 1000,
+        "speech_trigger_frames": 8,
+        "frame_length_wwd": 512,
+        "memory": { "enabled": false },
+        "wake": { "cooldown_ms": 0 },
+    }))
+    .expect("The mock config should deserialize")
+}
+
+// One command the voice loop heard in a recording and what it made of it
+#[derive(Debug, Clone)]
+pub struct Turn {
+    // as shown in the chat
+    pub transcript: String,
+    pub answer: String,
+    // what was handed to the voice
+    pub spoken: String,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    role: String,
+    content: String,
+    #[serde(default)]
+    meta: serde_json::Map<String, Value>,
+}
+
+// how long a run may take before it is given up on
+const RUN_TIMEOUT: Duration = Duration::from_secs(60);
+
+// Runs the voice loop with `pipeline` over a WAV file (any rate; the first channel is
+// used) until `commands` commands have been answered, and returns them. The loop runs
+// on a thread of its own, as it does in the app.
+pub fn run_wav(
+    pipeline: Pipeline,
+    mut config: Config,
+    path: &Path,
+    commands: usize,
+) -> Result<Vec<Turn>> {
+    config.input_source = InputSourceConfig {
+        kind: InputKind::Wav,
+        path: path.to_string_lossy().into_owned(),
+        // as fast as the loop reads, so every run hears the same audio
+        speed: 0.0,
+        repeat: false,
+    };
+
+    let is_running = Arc::new(AtomicBool::new(true));
+    let mut context = tauri::test::mock_context(tauri::test::noop_assets());
+    // the app's files go to a folder of their own, removed after the run
+    context.config_mut().identifier = format!("jarvis-pipeline-mock-{}", std::process::id());
+    // a real app, as the loop expects, without windows; it needs a desktop session
+    let app = tauri::Builder::default()
+        .any_thread()
+        .manage(crate::JarvisState {
+            is_running: Arc::clone(&is_running),
+            handle: Mutex::new(None),
+            active_conversation: Mutex::new(None),
+            active_persona: Mutex::new(None),
+        })
+        .build(context)?;
+    let handle = app.handle().clone();
+
+    let messages: Arc<Mutex<Vec<Message>>> = Arc::new(Mutex::new(Vec::new()));
+    {
+        let messages = Arc::clone(&messages);
+        handle.listen_any("new-message", move |event| {
+            match serde_json::from_str::<Message>(event.payload()) {
+                Ok(message) => messages.lock().unwrap().push(message),
+                Err(e) => eprintln!("[WARN] Unreadable message event: {}", e),
+            }
+        });
+    }
+
+    let loop_app = handle.clone();
+    let loop_running = Arc::clone(&is_running);
+    let voice_loop = std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(pipeline.run(config, loop_running, &loop_app))
+    });
+
+    let started = Instant::now();
+    let turns = loop {
+        let turns = turns_in(&messages.lock().unwrap());
+        if turns.len() >= commands || voice_loop.is_finished() {
+            break turns;
+        }
+        if started.elapsed() > RUN_TIMEOUT {
+            break turns;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    is_running.store(false, Ordering::Relaxed);
+    let result = voice_loop
+        .join()
+        .map_err(|_| anyhow!("The voice loop panicked"))?;
+
+    if let Ok(dir) = handle.path().app_config_dir() {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+    // stopping the loop ends it with an error; any other end is one
+    if let Err(e) = result {
+        if turns.len() < commands {
+            return Err(e);
+        }
+    }
+    if turns.len() < commands {
+        return Err(anyhow!(
+            "Only {} of {} commands were answered",
+            turns.len(),
+            commands
+        ));
+    }
+    Ok(turns)
+}
+
+// Pairs each message of the user with the answer that followed it
+fn turns_in(messages: &[Message]) -> Vec<Turn> {
+    let mut turns = Vec::new();
+    let mut heard: Option<&Message> = None;
+    for message in messages {
+        match message.role.as_str() {
+            "user" => heard = Some(message),
+            "assistant" => {
+                if let Some(user) = heard.take() {
+                    let spoken = message
+                        .meta
+                        .get("spoken")
+                        .and_then(Value::as_str)
+                        .unwrap_or(&message.content);
+                    turns.push(Turn {
+                        transcript: user.content.clone(),
+                        answer: message.content.clone(),
+                        spoken: spoken.to_string(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    turns
+}
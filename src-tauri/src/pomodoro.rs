@@ -0,0 +1,441 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Pomodoro coach: "start a 25-minute focus session" times a focus block and the break
+// after it, says when each one starts and ends, and shows the time left in the tray
+// tooltip. Every fourth break is a long one. Finished and abandoned focus blocks go
+// into pomodoro.json next to config.json, for "how many pomodoros today".
+// While focusing, Jarvis can hold back things it would say unasked (see `quiet`);
+// Windows offers no way for apps to switch on its own Do Not Disturb.
+
+use chrono::Local;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+use ts_rs::TS;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PomodoroConfig {
+    pub focus_minutes: u64,
+    pub short_break_minutes: u64,
+    pub long_break_minutes: u64,
+    // the break after this many focus blocks in a day is a long one
+    pub long_break_every: u64,
+    // hold back package updates and other unasked announcements while focusing
+    pub do_not_disturb: bool,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self {
+            focus_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            long_break_every: 4,
+            do_not_disturb: true,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum Phase {
+    Focus,
+    Break,
+}
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct Pomodoro {
+    pub phase: Phase,
+    #[ts(type = "number")]
+    pub focus_minutes: u64,
+    #[ts(type = "number")]
+    pub break_minutes: u64,
+    // unix ms; the start and end of the current phase
+    #[ts(type = "number")]
+    pub started_at: i64,
+    #[ts(type = "number")]
+    pub ends_at: i64,
+    #[serde(skip)]
+    quiet: bool,
+    #[serde(skip)]
+    generation: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct FocusSession {
+    // local time, RFC 3339
+    pub started_at: String,
+    pub minutes: u64,
+    // false when it was stopped early
+    pub completed: bool,
+}
+
+static CURRENT: Mutex<Option<Pomodoro>> = Mutex::new(None);
+// a stop or a new session makes the running phase's wait moot
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+// one load-modify-save of the history at a time
+static STORE: Mutex<()> = Mutex::new(());
+
+// "start a 25-minute focus session", "begin a pomodoro", "focus for 50 minutes"; not
+// "begin by focusing on the intro"
+static START: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:start|begin)\s+(?:a\s+|the\s+|my\s+|another\s+)?",
+        r"(?:(?:\d+|[a-z]+)[- ]minutes?[- ]?\s*)?",
+        r"(?:focus(?:\s+(?:session|timer|block))?|pomodoro|work session)\b",
+        r"|\bstart focusing\b|\bfocus for\s+(?:\d+|an?|[a-z]+)\s+(?:minutes?|hours?)\b",
+    ))
+    .expect("Failed to compile start pomodoro regex")
+});
+
+static STOP: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:stop|end|cancel|quit)\s+(?:the\s+|my\s+)?(?:focus|pomodoro)")
+        .expect("Failed to compile stop pomodoro regex")
+});
+
+// "how long until my break", "how much focus time is left"
+static LEFT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:how (?:much|long)|time left|when)\b.*",
+        r"\b(?:(?:my|the|this)\s+(?:focus|pomodoro|break)|until (?:my |the )?break)\b",
+        r"|\b(?:focus|pomodoro|break) time (?:is )?left\b",
+    ))
+    .expect("Failed to compile pomodoro time left regex")
+});
+
+static COUNT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\bhow many\s+(?:pomodoros|focus (?:sessions|blocks))\b")
+        .expect("Failed to compile pomodoro count regex")
+});
+
+fn history_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join("pomodoro.json"))
+}
+
+pub fn history(app: &tauri::AppHandle) -> Vec<FocusSession> {
+    history_path(app)
+        .ok()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn record(app: &tauri::AppHandle, session: FocusSession) {
+    let _guard = STORE.lock().unwrap();
+    let mut sessions = history(app);
+    sessions.push(session);
+    let written = history_path(app).and_then(|path| {
+        let s = serde_json::to_string_pretty(&sessions).map_err(|e| e.to_string())?;
+        crate::history::atomic_write(&path, s.as_bytes()).map_err(|e| e.to_string())
+    });
+    if let Err(e) = written {
+        eprintln!("[ERROR] Failed to save focus session: {}", e);
+    }
+}
+
+fn local_time(ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ms)
+        .map(|t| t.with_timezone(&Local).to_rfc3339())
+        .unwrap_or_default()
+}
+
+fn completed_today(app: &tauri::AppHandle) -> u64 {
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    history(app)
+        .iter()
+        .filter(|s| s.completed && s.started_at.starts_with(&today))
+        .count() as u64
+}
+
+pub fn current() -> Option<Pomodoro> {
+    CURRENT.lock().unwrap().clone()
+}
+
+// Whether Jarvis should keep unasked announcements to itself right now
+pub fn quiet() -> bool {
+    CURRENT
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|p| p.quiet && p.phase == Phase::Focus)
+}
+
+fn minutes_left(p: &Pomodoro) -> u64 {
+    let left = p.ends_at - chrono::Utc::now().timestamp_millis();
+    (left.max(0) as u64).div_ceil(60_000)
+}
+
+fn set_tray(app: &tauri::AppHandle, pomodoro: Option<&Pomodoro>) {
+    let Some(tray) = app.tray_by_id("main") else {
+        return;
+    };
+    let tooltip = match pomodoro {
+        Some(p) => {
+            let phase = match p.phase {
+                Phase::Focus => "Focus",
+                Phase::Break => "Break",
+            };
+            format!("Jarvis - {}: {} min left", phase, minutes_left(p))
+        }
+        None => "Jarvis".to_string(),
+    };
+    let _ = tray.set_tooltip(Some(tooltip));
+}
+
+fn emit_changed(app: &tauri::AppHandle) {
+    let pomodoro = current();
+    set_tray(app, pomodoro.as_ref());
+    crate::events::emit(app, crate::events::PomodoroChanged(pomodoro));
+}
+
+// a rising two-note chime, then the text
+async fn announce(app: &tauri::AppHandle, text: &str) {
+    let Ok(cfg) = crate::cmd_load_config(app.clone()) else {
+        return;
+    };
+    let cfg = crate::to_run_config(cfg);
+    for frequency in [660.0, 880.0] {
+        let tone = crate::audio_output::tone_once(
+            cfg.voice_output_device(),
+            frequency,
+            Duration::from_millis(180),
+        );
+        if let Err(e) = tone.await {
+            eprintln!("[ERROR] Failed to play pomodoro chime: {}", e);
+            break;
+        }
+    }
+    crate::events::emit(
+        app,
+        crate::events::NewMessage::new(crate::events::Role::Assistant, text)
+            .with("localCommand", true),
+    );
+    if let Err(e) = crate::tts::speak_with(&cfg, text).await {
+        eprintln!("[ERROR] Failed to announce pomodoro: {}", e);
+    }
+}
+
+fn enter(phase: Phase, minutes: u64, mut pomodoro: Pomodoro) -> Pomodoro {
+    let now = chrono::Utc::now().timestamp_millis();
+    pomodoro.phase = phase;
+    pomodoro.started_at = now;
+    pomodoro.ends_at = now + minutes as i64 * 60_000;
+    pomodoro
+}
+
+// Waits out the current phase, refreshing the tray; false when it was stopped
+async fn wait_phase(app: &tauri::AppHandle, generation: u64) -> bool {
+    loop {
+        let Some(p) = current().filter(|p| p.generation == generation) else {
+            return false;
+        };
+        let left = p.ends_at - chrono::Utc::now().timestamp_millis();
+        if left <= 0 {
+            return true;
+        }
+        set_tray(app, Some(&p));
+        tokio::time::sleep(Duration::from_millis(left.min(30_000) as u64)).await;
+    }
+}
+
+// Sets the phase if this session is still the running one
+fn advance(generation: u64, f: impl FnOnce(Pomodoro) -> Option<Pomodoro>) -> bool {
+    let mut slot = CURRENT.lock().unwrap();
+    match slot.take() {
+        Some(p) if p.generation == generation => {
+            *slot = f(p);
+            true
+        }
+        other => {
+            *slot = other;
+            false
+        }
+    }
+}
+
+async fn run(app: tauri::AppHandle, generation: u64) {
+    if !wait_phase(&app, generation).await {
+        return;
+    }
+    let Some(focus) = current() else {
+        return;
+    };
+    record(
+        &app,
+        FocusSession {
+            started_at: local_time(focus.started_at),
+            minutes: focus.focus_minutes,
+            completed: true,
+        },
+    );
+    let cfg = crate::cmd_load_config(app.clone())
+        .map(|c| c.pomodoro)
+        .unwrap_or_default();
+    let done = completed_today(&app);
+    let long = cfg.long_break_every > 0 && done.is_multiple_of(cfg.long_break_every);
+    let break_minutes = if long {
+        cfg.long_break_minutes
+    } else {
+        cfg.short_break_minutes
+    };
+    if !advance(generation, |p| {
+        let mut p = enter(Phase::Break, break_minutes, p);
+        p.break_minutes = break_minutes;
+        Some(p)
+    }) {
+        return;
+    }
+    emit_changed(&app);
+    let sessions = if done == 1 {
+        "That's your first focus session today.".to_string()
+    } else {
+        format!("That's {} focus sessions today.", done)
+    };
+    let text = format!(
+        "Time for a {}{}-minute break. {}",
+        if long { "longer " } else { "" },
+        break_minutes,
+        sessions
+    );
+    announce(&app, &text).await;
+
+    if !wait_phase(&app, generation).await {
+        return;
+    }
+    if !advance(generation, |_| None) {
+        return;
+    }
+    emit_changed(&app);
+    announce(
+        &app,
+        "Break's over. Say \"start a focus session\" when you're ready for the next one.",
+    )
+    .await;
+}
+
+pub fn start(app: &tauri::AppHandle, config: &PomodoroConfig, minutes: Option<u64>) -> Pomodoro {
+    stop(app);
+    let focus_minutes = minutes.unwrap_or(config.focus_minutes).max(1);
+    let generation = GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+    let pomodoro = enter(
+        Phase::Focus,
+        focus_minutes,
+        Pomodoro {
+            phase: Phase::Focus,
+            focus_minutes,
+            break_minutes: config.short_break_minutes,
+            started_at: 0,
+            ends_at: 0,
+            quiet: config.do_not_disturb,
+            generation,
+        },
+    );
+    *CURRENT.lock().unwrap() = Some(pomodoro.clone());
+    emit_changed(app);
+    tauri::async_runtime::spawn(run(app.clone(), generation));
+    pomodoro
+}
+
+// Ends the session; a focus block cut short is kept in the history as such
+pub fn stop(app: &tauri::AppHandle) -> Option<Pomodoro> {
+    let stopped = CURRENT.lock().unwrap().take()?;
+    if stopped.phase == Phase::Focus {
+        let elapsed = chrono::Utc::now().timestamp_millis() - stopped.started_at;
+        record(
+            app,
+            FocusSession {
+                started_at: local_time(stopped.started_at),
+                minutes: (elapsed.max(0) / 60_000) as u64,
+                completed: false,
+            },
+        );
+    }
+    emit_changed(app);
+    Some(stopped)
+}
+
+// The spoken answer to a focus command, or None when the prompt isn't one
+pub fn answer(prompt: &str, app: &tauri::AppHandle, config: &PomodoroConfig) -> Option<String> {
+    if STOP.is_match(prompt) {
+        return Some(match stop(app) {
+            Some(p) if p.phase == Phase::Focus => "Okay, I stopped the focus session.".into(),
+            Some(_) => "Okay, the break timer is off.".into(),
+            None => "There's no focus session running.".into(),
+        });
+    }
+    if COUNT.is_match(prompt) {
+        return Some(match completed_today(app) {
+            0 => "You haven't finished a focus session today yet.".into(),
+            1 => "You've finished one focus session today.".into(),
+            n => format!("You've finished {} focus sessions today.", n),
+        });
+    }
+    if START.is_match(prompt) {
+        let minutes = crate::timers::parse_duration(prompt).map(|s| s.div_ceil(60));
+        let p = start(app, config, minutes);
+        return Some(format!(
+            "Focus for {} minutes, starting now. I'll tell you when it's time for a break.",
+            p.focus_minutes
+        ));
+    }
+    if LEFT.is_match(prompt) {
+        let p = current()?;
+        let left = crate::timers::spoken_duration(minutes_left(&p) * 60);
+        return Some(match p.phase {
+            Phase::Focus => format!("{} of focus left, then a break.", left),
+            Phase::Break => format!("{} left of your break.", left),
+        });
+    }
+    None
+}
+
+#[tauri::command]
+pub fn cmd_start_pomodoro(
+    app: tauri::AppHandle,
+    minutes: Option<u64>,
+) -> Result<Pomodoro, String> {
+    let config = crate::cmd_load_config(app.clone())?.pomodoro;
+    Ok(start(&app, &config, minutes))
+}
+
+#[tauri::command]
+pub fn cmd_stop_pomodoro(app: tauri::AppHandle) {
+    stop(&app);
+}
+
+#[tauri::command]
+pub fn cmd_get_pomodoro() -> Option<Pomodoro> {
+    current()
+}
+
+#[tauri::command]
+pub fn cmd_pomodoro_history(app: tauri::AppHandle) -> Vec<FocusSession> {
+    history(&app)
+}
@@ -0,0 +1,331 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Presence: a Bluetooth scan every `scan_seconds` checks whether your phone is nearby,
+// and arriving or leaving runs a routine, e.g. a "welcome home" briefing, or keeping
+// quiet while nobody is there to hear it. Only the configured phone is looked for;
+// other devices the scan finds are not kept, and the arrivals and departures stay in
+// presence.json next to config.json. Off by default.
+//
+// A phone counts as nearby while it is connected to this PC, or when a scan finds it.
+// Phones only answer scans while their Bluetooth settings are open, so pairing the
+// phone and letting it connect (e.g. for calls or audio) works best.
+
+use chrono::{Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+use ts_rs::TS;
+use winapi::shared::minwindef::TRUE;
+use winapi::um::bluetoothapis::{
+    BluetoothFindDeviceClose, BluetoothFindFirstDevice, BluetoothFindNextDevice,
+    BLUETOOTH_DEVICE_INFO, BLUETOOTH_DEVICE_SEARCH_PARAMS,
+};
+use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::minwinbase::SYSTEMTIME;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PresenceConfig {
+    pub enabled: bool,
+    // the phone's Bluetooth address, e.g. "A4:C3:F0:12:34:56" (Settings > About on
+    // the phone)
+    pub phone_address: String,
+    pub scan_seconds: u64,
+    // scans in a row without the phone before it counts as gone; phones drop out of
+    // range for a moment now and then
+    pub away_after_misses: u32,
+    // "welcome home" and the briefing when the phone comes back
+    pub welcome_briefing: bool,
+    // hold back package updates and other unasked announcements while away
+    pub quiet_when_away: bool,
+    // run as if they had been said after the wake word, e.g. "play my focus playlist"
+    pub on_arrival: Vec<String>,
+    pub on_departure: Vec<String>,
+}
+
+impl Default for PresenceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            phone_address: String::new(),
+            scan_seconds: 60,
+            away_after_misses: 3,
+            welcome_briefing: true,
+            quiet_when_away: true,
+            on_arrival: Vec::new(),
+            on_departure: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct PresenceEvent {
+    // local time, RFC 3339
+    pub at: String,
+    pub arrived: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Default, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PresenceStatus {
+    pub enabled: bool,
+    // None until the first scan
+    pub present: Option<bool>,
+    // when the phone arrived or left; None if that happened before the app started
+    pub since: Option<String>,
+}
+
+struct State {
+    present: Option<bool>,
+    since: Option<String>,
+    misses: u32,
+    quiet_when_away: bool,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    present: None,
+    since: None,
+    misses: 0,
+    quiet_when_away: false,
+});
+
+// how long a scan listens, in units of 1.28 seconds
+const INQUIRY_MULTIPLIER: u8 = 4;
+// kept in presence.json
+const MAX_EVENTS: usize = 200;
+const ERROR_NO_MORE_ITEMS: u32 = 259;
+
+// "A4:C3:F0:12:34:56" or "a4-c3-f0-12-34-56" as the number Windows uses
+fn parse_address(text: &str) -> Option<u64> {
+    let hex: String = text.chars().filter(|c| c.is_ascii_hexdigit()).collect();
+    let separators_only = text
+        .chars()
+        .all(|c| c.is_ascii_hexdigit() || matches!(c, ':' | '-' | ' '));
+    if hex.len() != 12 || !separators_only {
+        return None;
+    }
+    u64::from_str_radix(&hex, 16).ok()
+}
+
+// SYSTEMTIME in UTC as unix seconds
+fn unix_seconds(t: &SYSTEMTIME) -> Option<i64> {
+    let date = NaiveDate::from_ymd_opt(t.wYear as i32, t.wMonth as u32, t.wDay as u32)?;
+    let time = date.and_hms_opt(t.wHour as u32, t.wMinute as u32, t.wSecond as u32)?;
+    Some(time.and_utc().timestamp())
+}
+
+// Blocks for the length of a scan (about five seconds)
+fn phone_nearby(address: u64) -> Result<bool, String> {
+    let scan_started = Utc::now().timestamp() - 2;
+    let mut params: BLUETOOTH_DEVICE_SEARCH_PARAMS = unsafe { std::mem::zeroed() };
+    params.dwSize = std::mem::size_of::<BLUETOOTH_DEVICE_SEARCH_PARAMS>() as u32;
+    params.fReturnAuthenticated = TRUE;
+    params.fReturnRemembered = TRUE;
+    params.fReturnUnknown = TRUE;
+    params.fReturnConnected = TRUE;
+    params.fIssueInquiry = TRUE;
+    params.cTimeoutMultiplier = INQUIRY_MULTIPLIER;
+    let mut info: BLUETOOTH_DEVICE_INFO = unsafe { std::mem::zeroed() };
+    info.dwSize = std::mem::size_of::<BLUETOOTH_DEVICE_INFO>() as u32;
+
+    let find = unsafe { BluetoothFindFirstDevice(&params, &mut info) };
+    if find.is_null() {
+        let error = unsafe { GetLastError() };
+        if error == ERROR_NO_MORE_ITEMS {
+            return Ok(false);
+        }
+        return Err(format!("Bluetooth scan failed (error {}); is Bluetooth on?", error));
+    }
+    let mut found = false;
+    loop {
+        if info.Address == address {
+            let seen = unix_seconds(&info.stLastSeen).is_some_and(|t| t >= scan_started);
+            found = info.fConnected != 0 || seen;
+            break;
+        }
+        if unsafe { BluetoothFindNextDevice(find, &mut info) } == 0 {
+            break;
+        }
+    }
+    unsafe { BluetoothFindDeviceClose(find) };
+    Ok(found)
+}
+
+fn events_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join("presence.json"))
+}
+
+pub fn events(app: &tauri::AppHandle) -> Vec<PresenceEvent> {
+    events_path(app)
+        .ok()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn log_event(app: &tauri::AppHandle, event: PresenceEvent) {
+    let mut all = events(app);
+    all.push(event);
+    let overflow = all.len().saturating_sub(MAX_EVENTS);
+    all.drain(..overflow);
+    let written = events_path(app).and_then(|path| {
+        let s = serde_json::to_string_pretty(&all).map_err(|e| e.to_string())?;
+        crate::history::atomic_write(&path, s.as_bytes()).map_err(|e| e.to_string())
+    });
+    if let Err(e) = written {
+        eprintln!("[ERROR] Failed to save presence event: {}", e);
+    }
+}
+
+pub fn status(app: &tauri::AppHandle) -> PresenceStatus {
+    let enabled = crate::cmd_load_config(app.clone())
+        .map(|c| c.presence.enabled)
+        .unwrap_or(false);
+    let state = STATE.lock().unwrap();
+    PresenceStatus {
+        enabled,
+        present: state.present.filter(|_| enabled),
+        since: state.since.clone().filter(|_| enabled),
+    }
+}
+
+// Whether Jarvis should keep unasked announcements to itself because you're away
+pub fn quiet() -> bool {
+    let state = STATE.lock().unwrap();
+    state.quiet_when_away && state.present == Some(false)
+}
+
+fn run_commands(app: &tauri::AppHandle, commands: &[String]) {
+    for command in commands.iter().filter(|c| !c.trim().is_empty()) {
+        if let Err(e) = crate::run_jarvis::cmd_simulate_voice_command(app.clone(), command.clone())
+        {
+            eprintln!("[WARN] Presence routine couldn't run \"{}\": {}", command, e);
+        }
+    }
+}
+
+async fn arrived(app: &tauri::AppHandle, cfg: &PresenceConfig) {
+    println!("[INFO] Phone is back, running the arrival routine");
+    if cfg.welcome_briefing {
+        match crate::cmd_load_config(app.clone()) {
+            Ok(config) => {
+                let config = crate::to_run_config(config);
+                if let Err(e) = crate::tts::speak_with(&config, "Welcome home.").await {
+                    eprintln!("[ERROR] Failed to speak welcome: {}", e);
+                }
+                crate::briefing::run(app, &config).await;
+            }
+            Err(e) => eprintln!("[ERROR] Failed to load config for the briefing: {}", e),
+        }
+    }
+    run_commands(app, &cfg.on_arrival);
+}
+
+// Counts the scan result; returns the new presence when it changed
+fn update(cfg: &PresenceConfig, nearby: bool) -> Option<bool> {
+    let mut state = STATE.lock().unwrap();
+    state.quiet_when_away = cfg.quiet_when_away;
+    state.misses = if nearby { 0 } else { state.misses + 1 };
+    let present = nearby || state.misses < cfg.away_after_misses.max(1);
+    let changed = match state.present {
+        // the first scan only tells where things stand
+        None => {
+            state.present = Some(present);
+            return None;
+        }
+        Some(was) => was != present,
+    };
+    if !changed {
+        return None;
+    }
+    state.present = Some(present);
+    state.since = Some(Local::now().to_rfc3339());
+    Some(present)
+}
+
+async fn scan(app: &tauri::AppHandle, cfg: &PresenceConfig) {
+    let Some(address) = parse_address(&cfg.phone_address) else {
+        eprintln!("[WARN] presence.phone_address isn't a Bluetooth address");
+        return;
+    };
+    let nearby = match tokio::task::spawn_blocking(move || phone_nearby(address)).await {
+        Ok(Ok(nearby)) => nearby,
+        Ok(Err(e)) => {
+            eprintln!("[WARN] {}", e);
+            return;
+        }
+        Err(e) => {
+            eprintln!("[ERROR] Bluetooth scan panicked: {}", e);
+            return;
+        }
+    };
+    let Some(present) = update(cfg, nearby) else {
+        return;
+    };
+    log_event(
+        app,
+        PresenceEvent {
+            at: Local::now().to_rfc3339(),
+            arrived: present,
+        },
+    );
+    crate::events::emit(app, status(app));
+    if present {
+        arrived(app, cfg).await;
+    } else {
+        println!("[INFO] Phone is gone, running the departure routine");
+        run_commands(app, &cfg.on_departure);
+    }
+}
+
+// Called once at startup; scans in the background while presence is enabled
+pub fn init(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let cfg = crate::cmd_load_config(app.clone())
+                .map(|c| c.presence)
+                .unwrap_or_default();
+            if cfg.enabled {
+                scan(&app, &cfg).await;
+            } else {
+                // turning it off forgets where things stood
+                let mut state = STATE.lock().unwrap();
+                state.present = None;
+                state.misses = 0;
+            }
+            tokio::time::sleep(Duration::from_secs(cfg.scan_seconds.max(15))).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn cmd_get_presence(app: tauri::AppHandle) -> PresenceStatus {
+    status(&app)
+}
+
+#[tauri::command]
+pub fn cmd_list_presence_events(app: tauri::AppHandle) -> Vec<PresenceEvent> {
+    events(&app)
+}
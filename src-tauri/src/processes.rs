@@ -0,0 +1,546 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Processes: "what's eating my CPU" names the busiest programs, "kill the process
+// eating my memory" names them too and asks which one to close, and "kill notepad" goes
+// straight to the question. Programs are counted by name, the way Task Manager groups
+// them, so closing Chrome closes all of its processes. Closing always asks for a yes
+// first (see confirm.rs). Only the user's own programs can be closed: the operating
+// system's processes, other accounts' processes, Jarvis and whatever it started (e.g. the
+// WebView's helpers) and anything in `protected` never are, whether asked by voice or
+// with cmd_kill_process.
+
+use crate::confirm;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ProcessesConfig {
+    // the voice commands, off by default; the app's process list works either way
+    pub enabled: bool,
+    // programs that are never closed, on top of the system's own, e.g. "obs64.exe"
+    pub protected: Vec<String>,
+    // how many of the busiest programs are named
+    pub offenders: usize,
+}
+
+impl Default for ProcessesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            protected: Vec::new(),
+            offenders: 3,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    // share of the whole machine, 0 to 100
+    pub cpu: f32,
+    pub memory_mb: u64,
+    // never closed, see is_protected
+    pub protected: bool,
+}
+
+// one program, all of its processes together
+struct Program {
+    name: String,
+    cpu: f32,
+    memory_mb: u64,
+}
+
+// The system can't run without these, or ends the session when they go
+#[cfg(windows)]
+const SYSTEM_PROCESSES: &[&str] = &[
+    "system",
+    "registry",
+    "memory compression",
+    "secure system",
+    "smss",
+    "csrss",
+    "wininit",
+    "winlogon",
+    "services",
+    "lsass",
+    "lsaiso",
+    "svchost",
+    "dwm",
+    "explorer",
+    "fontdrvhost",
+    "sihost",
+    "ctfmon",
+    "audiodg",
+    "spoolsv",
+    "msmpeng",
+    "securityhealthservice",
+    "conhost",
+    "runtimebroker",
+    "startmenuexperiencehost",
+    "shellexperiencehost",
+    "searchhost",
+    "taskhostw",
+];
+
+#[cfg(target_os = "linux")]
+const SYSTEM_PROCESSES: &[&str] = &[
+    "systemd",
+    "init",
+    "kthreadd",
+    "dbus-daemon",
+    "dbus-broker",
+    "xorg",
+    "xwayland",
+    "gnome-shell",
+    "gnome-session-binary",
+    "gdm",
+    "gdm-wayland-session",
+    "gdm-x-session",
+    "sddm",
+    "lightdm",
+    "kwin_x11",
+    "kwin_wayland",
+    "plasmashell",
+    "ksmserver",
+    "xfwm4",
+    "xfce4-session",
+    "mutter",
+    "pipewire",
+    "pipewire-pulse",
+    "wireplumber",
+    "pulseaudio",
+    "networkmanager",
+    "polkitd",
+    "login",
+    "sshd",
+];
+
+#[cfg(target_os = "macos")]
+const SYSTEM_PROCESSES: &[&str] = &[
+    "kernel_task",
+    "launchd",
+    "windowserver",
+    "loginwindow",
+    "dock",
+    "finder",
+    "systemuiserver",
+    "controlcenter",
+    "coreaudiod",
+    "cfprefsd",
+    "distnoted",
+    "mds",
+    "mds_stores",
+    "securityd",
+    "opendirectoryd",
+];
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+const SYSTEM_PROCESSES: &[&str] = &[];
+
+static KILL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:kill|terminate|force[- ]?quit|end|close|stop)\b")
+        .expect("Failed to compile kill regex")
+});
+
+// "the process eating my CPU", "what's slowing down my PC", "which app is using the most
+// memory"; a program has to be the one doing it, so "what's the most popular computer"
+// doesn't count
+static HOG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:what's|what is|whats|what are|whatever's|whatever is",
+        r"|(?:which|the)\s+(?:app|program|process|task)(?:s|es)?(?:\s+that's|\s+that)?",
+        r"(?:\s+is|\s+are)?)",
+        r"\s+(?:eating|hogging|using|taking|slowing)",
+        r"(?:\s+(?:up|down|all|the|most|so|much|of|my))*",
+        r"\s+(?:cpu|processor|memory|ram|pc|computer)\b",
+        r"|\b(?:busiest|heaviest)\s+(?:apps|programs|processes|tasks)\b",
+    ))
+    .expect("Failed to compile busy process regex")
+});
+
+static MEMORY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\b(?:memory|ram)\b").expect("Failed to compile memory regex"));
+
+// "kill notepad", "please end the chrome process"; the whole prompt has to be the
+// command, so "how do I kill a process in Linux" isn't one
+static KILL_NAMED: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)^\s*(?:please\s+)?(?:(?:can|could|would)\s+you\s+)?(?:please\s+)?",
+        r"(?:(?:kill|terminate|force[- ]?quit)\s+(?:the\s+)?(?P<name>[\w-]+(?:\.[\w-]+)*)",
+        r"(?:\s+(?:process|processes|task|app|program))?",
+        r"|(?:end|close|stop)\s+(?:the\s+)?(?P<task>[\w-]+(?:\.[\w-]+)*)",
+        r"\s+(?:process|processes|task))",
+        r"(?:\s+(?:now|please|for me))?\s*[.!?]?\s*$",
+    ))
+    .expect("Failed to compile named kill regex")
+});
+
+// picking from the offered programs by position; "one" alone isn't a position, or "the
+// second one" would pick the first
+const ORDINALS: &[&str] = &[
+    r"(?i)\b(?:first|1|1st|top)\b",
+    r"(?i)\b(?:second|two|2|2nd)\b",
+    r"(?i)\b(?:third|three|3|3rd)\b",
+    r"(?i)\b(?:fourth|four|4|4th)\b",
+    r"(?i)\b(?:fifth|five|5|5th)\b",
+];
+
+static ORDINAL_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    ORDINALS
+        .iter()
+        .map(|p| Regex::new(p).expect("Failed to compile ordinal regex"))
+        .collect()
+});
+
+// the programs offered by "kill the process eating my CPU", waiting for a pick
+static CHOOSING: Mutex<Option<(Vec<String>, Instant)>> = Mutex::new(None);
+
+// long enough for sysinfo to tell CPU use
+const SAMPLE_TIME: Duration = Duration::from_millis(500);
+
+// "Chrome.exe" as "chrome", for comparing names
+fn base_name(name: &str) -> String {
+    let lower = name.trim().to_lowercase();
+    lower.strip_suffix(".exe").unwrap_or(&lower).to_string()
+}
+
+// Names, owners and parents of every process; the owner is needed by is_protected
+fn refresh(sys: &mut System) {
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing()
+            .with_cpu()
+            .with_memory()
+            .with_user(UpdateKind::OnlyIfNotSet),
+    );
+}
+
+// Whether Jarvis started `pid`, directly or through one of its own children
+fn is_descendant(sys: &System, pid: Pid) -> bool {
+    let own = Pid::from_u32(std::process::id());
+    let mut parent = sys.process(pid).and_then(|p| p.parent());
+    // reused pids can make the chain loop, so it's only followed so far
+    for _ in 0..64 {
+        match parent {
+            Some(p) if p == own => return true,
+            Some(p) => parent = sys.process(p).and_then(|p| p.parent()),
+            None => return false,
+        }
+    }
+    false
+}
+
+fn is_protected(sys: &System, process: &Process, config: &ProcessesConfig) -> bool {
+    let pid = process.pid();
+    let own = Pid::from_u32(std::process::id());
+    let base = base_name(&process.name().to_string_lossy());
+    // another account's process, or one whose owner can't be read (a service)
+    let own_user = sys.process(own).and_then(|p| p.user_id());
+    let other_user = own_user.is_some() && process.user_id() != own_user;
+    // 0 to 4 are the kernel's own (idle and System on Windows, init on Linux)
+    pid.as_u32() <= 4
+        || pid == own
+        || other_user
+        || is_descendant(sys, pid)
+        || SYSTEM_PROCESSES.contains(&base.as_str())
+        || config.protected.iter().any(|p| base_name(p) == base)
+}
+
+// Blocks for SAMPLE_TIME; busiest first
+fn sample(config: &ProcessesConfig) -> Vec<ProcessInfo> {
+    let mut sys = System::new();
+    refresh(&mut sys);
+    std::thread::sleep(SAMPLE_TIME.max(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL));
+    refresh(&mut sys);
+    // sysinfo counts one busy core as 100 percent
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get()) as f32;
+    let mut list: Vec<ProcessInfo> = sys
+        .processes()
+        .values()
+        .filter(|p| p.pid().as_u32() != 0)
+        .map(|p| {
+            let pid = p.pid().as_u32();
+            let name = p.name().to_string_lossy().to_string();
+            ProcessInfo {
+                pid,
+                protected: is_protected(&sys, p, config),
+                name,
+                cpu: p.cpu_usage() / cores,
+                memory_mb: p.memory() / (1024 * 1024),
+            }
+        })
+        .collect();
+    list.sort_by(|a, b| b.cpu.total_cmp(&a.cpu));
+    list
+}
+
+async fn sample_async(config: &ProcessesConfig) -> Result<Vec<ProcessInfo>, String> {
+    let config = config.clone();
+    tokio::task::spawn_blocking(move || sample(&config))
+        .await
+        .map_err(|e| format!("Process list failed: {}", e))
+}
+
+// The busiest programs that may be closed, by CPU or by memory
+fn busiest(list: &[ProcessInfo], by_memory: bool, count: usize) -> Vec<Program> {
+    let mut programs: Vec<Program> = Vec::new();
+    for p in list.iter().filter(|p| !p.protected) {
+        let name = base_name(&p.name);
+        match programs.iter_mut().find(|g| base_name(&g.name) == name) {
+            Some(g) => {
+                g.cpu += p.cpu;
+                g.memory_mb += p.memory_mb;
+            }
+            None => programs.push(Program {
+                name: p.name.trim_end_matches(".exe").to_string(),
+                cpu: p.cpu,
+                memory_mb: p.memory_mb,
+            }),
+        }
+    }
+    if by_memory {
+        programs.sort_by_key(|p| std::cmp::Reverse(p.memory_mb));
+    } else {
+        programs.sort_by(|a, b| b.cpu.total_cmp(&a.cpu));
+    }
+    programs.truncate(count.max(1));
+    programs
+}
+
+// "35 percent" or "1.2 gigabytes"
+fn amount(program: &Program, by_memory: bool) -> String {
+    if !by_memory {
+        format!("{:.0} percent", program.cpu)
+    } else if program.memory_mb >= 1024 {
+        format!("{:.1} gigabytes", program.memory_mb as f64 / 1024.0)
+    } else {
+        format!("{} megabytes", program.memory_mb)
+    }
+}
+
+// "a, b and c"
+fn join(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [one] => one.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+// Ends every process of the program that isn't protected; (closed, failed)
+fn kill_blocking(name: &str, config: &ProcessesConfig) -> (usize, usize) {
+    let target = base_name(name);
+    let mut sys = System::new();
+    refresh(&mut sys);
+    let mut closed = 0;
+    let mut failed = 0;
+    for p in sys.processes().values() {
+        if base_name(&p.name().to_string_lossy()) != target || is_protected(&sys, p, config) {
+            continue;
+        }
+        if p.kill() {
+            closed += 1;
+        } else {
+            failed += 1;
+        }
+    }
+    (closed, failed)
+}
+
+async fn kill(name: String, config: ProcessesConfig) -> String {
+    let task_name = name.clone();
+    let result = tokio::task::spawn_blocking(move || kill_blocking(&task_name, &config)).await;
+    match result {
+        Ok((0, 0)) => format!("{} isn't running anymore.", name),
+        Ok((_, 0)) => format!("I've closed {}.", name),
+        Ok((0, _)) => format!(
+            "I wasn't allowed to close {}. It may need admin rights.",
+            name
+        ),
+        Ok((_, _)) => format!(
+            "I closed {}, but some of its processes need admin rights to end.",
+            name
+        ),
+        Err(e) => {
+            eprintln!("[ERROR] Closing {} failed: {}", name, e);
+            format!("Sorry, I couldn't close {}.", name)
+        }
+    }
+}
+
+fn ask_kill(name: &str, config: &ProcessesConfig) -> String {
+    confirm::ask(
+        &format!("close {}", name),
+        &format!("Close {}? Anything unsaved in it will be lost.", name),
+        kill(name.to_string(), config.clone()),
+    )
+}
+
+// Whether "kill the process eating my CPU" is waiting to hear which one
+pub fn is_choosing() -> bool {
+    CHOOSING
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|(_, asked_at)| asked_at.elapsed() <= confirm::CONFIRM_WINDOW)
+}
+
+// A prompt that is only "kill notepad"; these need no trigger phrase
+pub fn is_kill_command(prompt: &str) -> bool {
+    KILL_NAMED.is_match(prompt)
+}
+
+// The reply to "which one should I close?"; None drops the question and leaves the
+// prompt to the other commands
+fn choose(prompt: &str, config: &ProcessesConfig) -> Option<String> {
+    let (offered, asked_at) = CHOOSING.lock().unwrap().take()?;
+    if asked_at.elapsed() > confirm::CONFIRM_WINDOW {
+        return None;
+    }
+    let none = prompt.trim().trim_end_matches(['.', '!', '?']).eq_ignore_ascii_case("none");
+    if confirm::is_no(prompt) || none {
+        return Some("Okay, I'll leave them running.".to_string());
+    }
+    let lower = prompt.to_lowercase();
+    let picked = offered
+        .iter()
+        .find(|name| lower.contains(&base_name(name)))
+        .or_else(|| {
+            ORDINAL_PATTERNS
+                .iter()
+                .position(|re| re.is_match(prompt))
+                .and_then(|i| offered.get(i))
+        })?;
+    Some(ask_kill(picked, config))
+}
+
+pub async fn answer(prompt: &str, config: &ProcessesConfig) -> Option<String> {
+    if !config.enabled {
+        return None;
+    }
+    if let Some(reply) = choose(prompt, config) {
+        return Some(reply);
+    }
+
+    if HOG.is_match(prompt) {
+        let by_memory = MEMORY.is_match(prompt);
+        let list = match sample_async(config).await {
+            Ok(list) => list,
+            Err(e) => {
+                eprintln!("[ERROR] {}", e);
+                return Some("Sorry, I couldn't look at what's running.".to_string());
+            }
+        };
+        let programs = busiest(&list, by_memory, config.offenders);
+        let Some(top) = programs.first() else {
+            return Some("Nothing I'm allowed to close is running.".to_string());
+        };
+        if programs.len() == 1 {
+            let reply = format!(
+                "{} is the busiest, using {}.",
+                top.name,
+                amount(top, by_memory)
+            );
+            if !KILL.is_match(prompt) {
+                return Some(reply);
+            }
+            let question = format!("{} Close it? Anything unsaved in it will be lost.", reply);
+            let kill = kill(top.name.clone(), config.clone());
+            return Some(confirm::ask(
+                &format!("close {}", top.name),
+                &question,
+                kill,
+            ));
+        }
+        let named: Vec<String> = programs
+            .iter()
+            .map(|p| format!("{} using {}", p.name, amount(p, by_memory)))
+            .collect();
+        let mut reply = format!("The busiest are {}.", join(&named));
+        if KILL.is_match(prompt) {
+            let names = programs.iter().map(|p| p.name.clone()).collect();
+            *CHOOSING.lock().unwrap() = Some((names, Instant::now()));
+            reply.push_str(" Which one should I close?");
+        }
+        return Some(reply);
+    }
+
+    let captures = KILL_NAMED.captures(prompt)?;
+    let name = captures.name("name").or(captures.name("task"))?.as_str();
+    if matches!(
+        name.to_lowercase().as_str(),
+        "it" | "that" | "this" | "a" | "all" | "process" | "app" | "program" | "one"
+    ) {
+        return None;
+    }
+    if SYSTEM_PROCESSES.contains(&base_name(name).as_str())
+        || config
+            .protected
+            .iter()
+            .any(|p| base_name(p) == base_name(name))
+    {
+        return Some(format!("I won't close {}, it's protected.", name));
+    }
+    let list = sample_async(config).await.ok()?;
+    let running: Vec<&ProcessInfo> =
+        list.iter().filter(|p| base_name(&p.name) == base_name(name)).collect();
+    match running.iter().find(|p| !p.protected) {
+        Some(p) => Some(ask_kill(p.name.trim_end_matches(".exe"), config)),
+        // e.g. another account's, or started by Jarvis
+        None if !running.is_empty() => Some(format!("I won't close {}, it's protected.", name)),
+        None => Some(format!("{} isn't running.", name)),
+    }
+}
+
+#[tauri::command]
+pub async fn cmd_list_processes(app: tauri::AppHandle) -> Result<Vec<ProcessInfo>, String> {
+    let config = crate::cmd_load_config(app)?;
+    sample_async(&config.processes).await
+}
+
+// Ends one process; the app asks before calling this
+#[tauri::command]
+pub async fn cmd_kill_process(app: tauri::AppHandle, pid: u32) -> Result<(), String> {
+    let config = crate::cmd_load_config(app)?.processes;
+    tokio::task::spawn_blocking(move || {
+        let mut sys = System::new();
+        // all of them, for the owner and parent checks
+        refresh(&mut sys);
+        let process = sys
+            .process(Pid::from_u32(pid))
+            .ok_or("That process isn't running anymore")?;
+        let name = process.name().to_string_lossy().to_string();
+        if is_protected(&sys, process, &config) {
+            return Err(format!("{} is protected", name));
+        }
+        if !process.kill() {
+            return Err(format!("The system wouldn't let Jarvis end {}", name));
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
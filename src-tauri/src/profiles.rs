@@ -0,0 +1,125 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::{Deserialize, Serialize};
+
+// A named set of audio settings, e.g. "Work headset" or "Home speakers"
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DeviceProfile {
+    pub name: String,
+    #[serde(default)]
+    pub microphone_name: Option<String>,
+    #[serde(default)]
+    pub output_device_name: Option<String>,
+    // 0.0 - 1.0, applied to spoken responses
+    #[serde(default)]
+    pub output_volume: Option<f32>,
+    #[serde(default)]
+    pub wwd_sensitivity: Option<f32>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ProfileList {
+    pub profiles: Vec<DeviceProfile>,
+    pub active: Option<String>,
+    // first profile whose devices are all connected right now
+    pub detected: Option<String>,
+}
+
+fn device_present(devices: &[String], wanted: &Option<String>) -> bool {
+    match wanted.as_deref().map(str::trim) {
+        None | Some("") => true,
+        Some(w) => {
+            let w = w.to_lowercase();
+            devices.iter().any(|d| d.to_lowercase().contains(&w))
+        }
+    }
+}
+
+// Picks the first profile whose microphone and output device are both present
+pub fn detect(profiles: &[DeviceProfile]) -> Option<&DeviceProfile> {
+    let inputs = crate::cmd_list_input_devices().unwrap_or_default();
+    let outputs = crate::cmd_list_output_devices().unwrap_or_default();
+    profiles.iter().find(|p| {
+        (p.microphone_name.is_some() || p.output_device_name.is_some())
+            && device_present(&inputs, &p.microphone_name)
+            && device_present(&outputs, &p.output_device_name)
+    })
+}
+
+fn find<'a>(profiles: &'a [DeviceProfile], name: &str) -> Option<&'a DeviceProfile> {
+    let wanted = name.trim().to_lowercase();
+    profiles.iter().find(|p| p.name.trim().to_lowercase() == wanted)
+}
+
+// Copies the profile's settings into the config; unset fields are left alone
+fn apply(cfg: &mut crate::Config, profile: &DeviceProfile) {
+    if let Some(m) = &profile.microphone_name {
+        cfg.default_microphone_name = Some(m.clone());
+    }
+    if let Some(o) = &profile.output_device_name {
+        cfg.default_output_device_name = Some(o.clone());
+    }
+    if let Some(v) = profile.output_volume {
+        cfg.output_volume = Some(v.clamp(0.0, 1.0));
+    }
+    if let Some(s) = profile.wwd_sensitivity {
+        cfg.wwd_sensitivity = s.clamp(0.0, 1.0);
+    }
+    cfg.active_profile = Some(profile.name.clone());
+}
+
+// Called before the voice loop starts; switches profile when a different one matches the devices
+pub(crate) fn auto_select(app: &tauri::AppHandle, mut cfg: crate::Config) -> crate::Config {
+    if !cfg.auto_select_profile {
+        return cfg;
+    }
+    let Some(profile) = detect(&cfg.profiles).cloned() else {
+        return cfg;
+    };
+    if cfg.active_profile.as_deref() == Some(profile.name.as_str()) {
+        return cfg;
+    }
+    println!("[DEBUG] Auto-selecting device profile '{}'", profile.name);
+    apply(&mut cfg, &profile);
+    if let Err(e) = crate::cmd_save_config(app.clone(), cfg.clone()) {
+        eprintln!("[WARN] Failed to save auto-selected profile: {}", e);
+    }
+    cfg
+}
+
+#[tauri::command]
+pub fn cmd_list_profiles(app: tauri::AppHandle) -> Result<ProfileList, String> {
+    let cfg = crate::cmd_load_config(app)?;
+    let detected = detect(&cfg.profiles).map(|p| p.name.clone());
+    Ok(ProfileList {
+        profiles: cfg.profiles,
+        active: cfg.active_profile,
+        detected,
+    })
+}
+
+// Takes effect the next time the voice loop is started
+#[tauri::command]
+pub fn cmd_apply_profile(app: tauri::AppHandle, name: String) -> Result<(), String> {
+    let mut cfg = crate::cmd_load_config(app.clone())?;
+    let profile = find(&cfg.profiles, &name)
+        .cloned()
+        .ok_or_else(|| format!("Unknown profile '{}'", name))?;
+    apply(&mut cfg, &profile);
+    crate::cmd_save_config(app, cfg)
+}
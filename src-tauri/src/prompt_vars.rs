@@ -0,0 +1,90 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Placeholders in the system prompt, filled in each time a question is sent: {date},
+// {time} and {active_app} come from the machine, anything else from
+// `prompt_variables` (e.g. {user_name}, {location}), falling back to the user profile
+// for those two. Placeholders nobody knows are left as written.
+
+use crate::models::Config;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+
+static PLACEHOLDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{(\w+)\}").expect("Failed to compile placeholder regex"));
+
+// named in the settings but not filled in yet; the model is told so instead of seeing
+// the raw placeholder
+const PERSONAL: &[&str] = &["user_name", "location"];
+
+fn from_profile(name: &str) -> Option<String> {
+    let profile = crate::user_profile::current();
+    let v = match name {
+        "user_name" => profile.name,
+        "location" => profile.location,
+        _ => return None,
+    };
+    (!v.is_empty()).then_some(v)
+}
+
+fn value(name: &str, config: &Config) -> Option<String> {
+    let now = chrono::Local::now();
+    match name {
+        "date" => Some(now.format("%A, %B %-d, %Y").to_string()),
+        "time" => Some(now.format("%H:%M").to_string()),
+        "active_app" => Some(
+            crate::foreground::current()
+                .map(|w| {
+                    let app = w.exe.strip_suffix(".exe").unwrap_or(&w.exe).to_string();
+                    if w.title.trim().is_empty() {
+                        app
+                    } else {
+                        format!("{} ({})", app, w.title.trim())
+                    }
+                })
+                .unwrap_or_else(|| "unknown".to_string()),
+        ),
+        _ => config
+            .prompt_variables
+            .get(name)
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .or_else(|| from_profile(name))
+            .or_else(|| PERSONAL.contains(&name).then(|| "unknown".to_string())),
+    }
+}
+
+pub fn expand(template: &str, config: &Config) -> String {
+    PLACEHOLDER
+        .replace_all(template, |caps: &Captures| {
+            value(&caps[1], config).unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+// The system prompt as the model would get it now, with the active persona applied;
+// `template` previews an edited prompt before it is saved
+#[tauri::command]
+pub fn cmd_preview_system_prompt(
+    app: tauri::AppHandle,
+    template: Option<String>,
+) -> Result<String, String> {
+    let mut config = crate::to_run_config(crate::cmd_load_config(app.clone())?);
+    crate::persona::apply_active(&app, &mut config);
+    let template = template.unwrap_or_else(|| config.llm_system_prompt.clone());
+    Ok(expand(&template, &config))
+}
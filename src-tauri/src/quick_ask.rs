@@ -0,0 +1,111 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Spotlight-style input popup opened by the quick-ask hotkey. Prompts go through
+// `cmd_send_text`, so they land in the active conversation like typed messages.
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
+
+pub const QUICK_ASK_LABEL: &str = "quick-ask";
+
+// logical sizes: just the input line, and with room for the answer
+const WIDTH: f64 = 640.0;
+const INPUT_HEIGHT: f64 = 64.0;
+const ANSWER_HEIGHT: f64 = 280.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct QuickAskConfig {
+    // also read the answer aloud, like a voice command
+    pub speak_response: bool,
+}
+
+fn show(app: &tauri::AppHandle) -> Result<(), String> {
+    let win = match app.get_webview_window(QUICK_ASK_LABEL) {
+        Some(win) => win,
+        None => WebviewWindowBuilder::new(
+            app,
+            QUICK_ASK_LABEL,
+            WebviewUrl::App("index.html#quick-ask".into()),
+        )
+        .title("Ask Jarvis")
+        .inner_size(WIDTH, INPUT_HEIGHT)
+        .resizable(false)
+        .decorations(false)
+        .transparent(true)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .visible(false)
+        .build()
+        .map_err(|e| e.to_string())?,
+    };
+    let _ = win.set_size(tauri::LogicalSize::new(WIDTH, INPUT_HEIGHT));
+    let _ = win.center();
+    win.show().map_err(|e| e.to_string())?;
+    win.set_focus().map_err(|e| e.to_string())
+}
+
+fn hide(app: &tauri::AppHandle) -> Result<(), String> {
+    match app.get_webview_window(QUICK_ASK_LABEL) {
+        Some(win) => win.hide().map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+// Pressing the hotkey again closes the popup
+pub fn toggle(app: &tauri::AppHandle) -> Result<(), String> {
+    let visible = app
+        .get_webview_window(QUICK_ASK_LABEL)
+        .and_then(|w| w.is_visible().ok())
+        .unwrap_or(false);
+    if visible {
+        hide(app)
+    } else {
+        show(app)
+    }
+}
+
+#[tauri::command]
+pub async fn cmd_quick_ask(app: tauri::AppHandle, prompt: String) -> Result<String, String> {
+    let prompt = prompt.trim().to_string();
+    if prompt.is_empty() {
+        return Err("Nothing to ask".into());
+    }
+    let cfg = crate::cmd_load_config(app.clone())?;
+    let speak_response = cfg.quick_ask.speak_response;
+    let answer = crate::cmd_send_text(app.clone(), prompt).await?;
+
+    if let Some(win) = app.get_webview_window(QUICK_ASK_LABEL) {
+        let _ = win.set_size(tauri::LogicalSize::new(WIDTH, ANSWER_HEIGHT));
+    }
+    if speak_response && !answer.is_empty() {
+        let run_config = crate::to_run_config(cfg);
+        let spoken = answer.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = crate::tts::speak_with(&run_config, &spoken).await {
+                eprintln!("[ERROR] Failed to speak quick-ask answer: {}", e);
+            }
+        });
+    }
+    Ok(answer)
+}
+
+#[tauri::command]
+pub fn cmd_hide_quick_ask(app: tauri::AppHandle) -> Result<(), String> {
+    hide(&app)
+}
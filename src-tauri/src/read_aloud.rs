@@ -0,0 +1,287 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Reads the selected text in any app aloud. The selection is copied with a simulated
+// Ctrl+C and the clipboard is put back afterwards; long texts are spoken in chunks so
+// playback starts quickly and can be stopped between (and during) chunks.
+
+use crate::audio_output::AudioOutput;
+use crate::events::{self, ReadAloudState};
+use crate::models::Config;
+use crate::tts::{self, Speech, TtsProvider};
+use anyhow::{anyhow, Result};
+use arboard::{Clipboard, ImageData};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use winapi::um::winuser::{
+    keybd_event, KEYEVENTF_KEYUP, VK_CONTROL, VK_LWIN, VK_MENU, VK_RWIN, VK_SHIFT,
+};
+
+// ElevenLabs starts quicker on short requests; sentences are kept whole up to this
+const MAX_CHUNK_CHARS: usize = 400;
+// how long the focused app gets to answer the simulated copy
+const COPY_TIMEOUT: Duration = Duration::from_millis(600);
+
+// bumped on every start and stop; a reading only continues while it holds the current value
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+// output of the reading in progress, so stopping cuts off the current chunk too
+static OUTPUT: Mutex<Option<Arc<AudioOutput>>> = Mutex::new(None);
+
+enum SavedClipboard {
+    Text(String),
+    Image(ImageData<'static>),
+    Empty,
+}
+
+fn save_clipboard(clipboard: &mut Clipboard) -> SavedClipboard {
+    if let Ok(text) = clipboard.get_text() {
+        return SavedClipboard::Text(text);
+    }
+    if let Ok(image) = clipboard.get_image() {
+        return SavedClipboard::Image(image);
+    }
+    SavedClipboard::Empty
+}
+
+fn restore_clipboard(clipboard: &mut Clipboard, saved: SavedClipboard) {
+    let result = match saved {
+        SavedClipboard::Text(text) => clipboard.set_text(text),
+        SavedClipboard::Image(image) => clipboard.set_image(image),
+        SavedClipboard::Empty => clipboard.clear(),
+    };
+    if let Err(e) = result {
+        eprintln!("[WARN] Failed to restore clipboard: {}", e);
+    }
+}
+
+fn send_copy() {
+    unsafe {
+        // the hotkey's own modifiers may still be down and would turn this into Ctrl+Shift+C
+        for key in [VK_SHIFT, VK_MENU, VK_LWIN, VK_RWIN] {
+            keybd_event(key as u8, 0, KEYEVENTF_KEYUP, 0);
+        }
+        keybd_event(VK_CONTROL as u8, 0, 0, 0);
+        keybd_event(b'C', 0, 0, 0);
+        std::thread::sleep(Duration::from_millis(30));
+        keybd_event(b'C', 0, KEYEVENTF_KEYUP, 0);
+        keybd_event(VK_CONTROL as u8, 0, KEYEVENTF_KEYUP, 0);
+    }
+}
+
+// Copies the current selection of the focused app, leaving the clipboard as it was
+pub fn grab_selection() -> Result<String> {
+    let mut clipboard = Clipboard::new().map_err(|e| anyhow!("Failed to open clipboard: {}", e))?;
+    let saved = save_clipboard(&mut clipboard);
+    // cleared first so an app that copies nothing isn't mistaken for the old content
+    let _ = clipboard.clear();
+    send_copy();
+
+    let started = Instant::now();
+    let mut selection = String::new();
+    while started.elapsed() < COPY_TIMEOUT {
+        std::thread::sleep(Duration::from_millis(40));
+        if let Ok(text) = clipboard.get_text() {
+            if !text.trim().is_empty() {
+                selection = text;
+                break;
+            }
+        }
+    }
+    restore_clipboard(&mut clipboard, saved);
+    Ok(selection.trim().to_string())
+}
+
+// Splits at sentence ends, packing sentences into chunks of up to MAX_CHUNK_CHARS;
+// a single longer sentence is cut at the last space that fits.
+pub fn chunk_text(text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        current.push(c);
+        let at_end = c == '\n'
+            || (matches!(c, '.' | '!' | '?')
+                && chars.peek().map(|n| n.is_whitespace()).unwrap_or(true));
+        if at_end {
+            sentences.push(std::mem::take(&mut current));
+        }
+    }
+    sentences.push(current);
+
+    let mut chunks: Vec<String> = Vec::new();
+    let mut chunk = String::new();
+    for sentence in sentences.iter().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        let mut rest = sentence;
+        while rest.chars().count() > MAX_CHUNK_CHARS {
+            let limit = rest
+                .char_indices()
+                .nth(MAX_CHUNK_CHARS)
+                .map(|(i, _)| i)
+                .unwrap_or(rest.len());
+            let cut = rest[..limit].rfind(char::is_whitespace).unwrap_or(limit);
+            if !chunk.is_empty() {
+                chunks.push(std::mem::take(&mut chunk));
+            }
+            chunks.push(rest[..cut].trim().to_string());
+            rest = rest[cut..].trim_start();
+        }
+        if !chunk.is_empty() && chunk.chars().count() + rest.chars().count() + 1 > MAX_CHUNK_CHARS {
+            chunks.push(std::mem::take(&mut chunk));
+        }
+        if !chunk.is_empty() {
+            chunk.push(' ');
+        }
+        chunk.push_str(rest);
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+fn emit_state(app: &tauri::AppHandle, reading: bool, chunk: usize, total: usize) {
+    events::emit(
+        app,
+        ReadAloudState {
+            reading,
+            chunk,
+            total,
+        },
+    );
+}
+
+pub fn is_reading() -> bool {
+    OUTPUT.lock().unwrap().is_some()
+}
+
+// Stops the current reading, including the chunk being played
+pub fn stop() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    if let Some(output) = OUTPUT.lock().unwrap().take() {
+        output.clear();
+    }
+}
+
+async fn read_chunks(app: &tauri::AppHandle, config: &Config, chunks: &[String], generation: u64) -> Result<()> {
+    let current = || GENERATION.load(Ordering::SeqCst) == generation;
+    let device = config.voice_output_device();
+    let volume = config.output_volume.unwrap_or(1.0);
+    let output = tokio::task::spawn_blocking(move || AudioOutput::open(None, device.as_deref(), volume))
+        .await
+        .map_err(|e| anyhow!("Playback thread panic: {}", e))??;
+    let output = Arc::new(output);
+    {
+        let mut slot = OUTPUT.lock().unwrap();
+        // stopped while the device was opening
+        if !current() {
+            return Ok(());
+        }
+        *slot = Some(Arc::clone(&output));
+    }
+
+    let provider = tts::provider_for(config);
+    for (i, chunk) in chunks.iter().enumerate() {
+        if !current() {
+            break;
+        }
+        // synthesized while the previous chunk is still playing
+        let speech = match provider.synthesize(chunk).await {
+            Err(e) if tts::is_refusal(&e) => {
+                eprintln!("[WARN] {}; using system voice", e);
+                tts::SystemTts.synthesize(chunk).await?
+            }
+            other => other?,
+        };
+        while current() && !output.is_idle() {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+        }
+        if !current() {
+            break;
+        }
+        emit_state(app, true, i + 1, chunks.len());
+        if let Speech::Audio(bytes) = speech {
+            output.queue(bytes)?;
+        }
+    }
+    while current() && !output.is_idle() {
+        tokio::time::sleep(Duration::from_millis(30)).await;
+    }
+    Ok(())
+}
+
+// Reads `text` aloud, replacing any reading already in progress
+pub async fn read(app: &tauri::AppHandle, config: &Config, text: &str) -> Result<()> {
+    stop();
+    let generation = GENERATION.load(Ordering::SeqCst);
+    let chunks = chunk_text(&crate::tts_normalize::normalize(text, &config.tts_normalization));
+    if chunks.is_empty() {
+        return Ok(());
+    }
+    let result = read_chunks(app, config, &chunks, generation).await;
+    // a newer reading may already own the output
+    if GENERATION.load(Ordering::SeqCst) == generation {
+        *OUTPUT.lock().unwrap() = None;
+        emit_state(app, false, 0, chunks.len());
+    }
+    result
+}
+
+// Copies the selection and reads it; says so when nothing was selected
+pub async fn read_selection(app: &tauri::AppHandle, config: &Config) -> Result<()> {
+    let selection = tokio::task::spawn_blocking(grab_selection)
+        .await
+        .map_err(|e| anyhow!("Clipboard thread panic: {}", e))??;
+    if selection.is_empty() {
+        return tts::speak_with(config, "I couldn't find any selected text.").await;
+    }
+    read(app, config, &selection).await
+}
+
+// Hotkey entry point: starts reading the selection, or stops if already reading
+pub fn toggle(app: &tauri::AppHandle) -> Result<(), String> {
+    if is_reading() {
+        stop();
+        return Ok(());
+    }
+    let config = crate::to_run_config(crate::cmd_load_config(app.clone())?);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = read_selection(&app, &config).await {
+            eprintln!("[ERROR] Failed to read selection aloud: {}", e);
+        }
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn cmd_read_selection(app: tauri::AppHandle) -> Result<(), String> {
+    let config = crate::to_run_config(crate::cmd_load_config(app.clone())?);
+    read_selection(&app, &config).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn cmd_read_aloud(app: tauri::AppHandle, text: String) -> Result<(), String> {
+    let config = crate::to_run_config(crate::cmd_load_config(app.clone())?);
+    read(&app, &config, &text).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn cmd_stop_reading() -> Result<(), String> {
+    stop();
+    Ok(())
+}
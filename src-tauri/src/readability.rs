@@ -0,0 +1,253 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Pulls the article out of a web page, in the spirit of Firefox's reader view:
+// paragraphs vote for their parent element, the best-scoring container wins, and
+// navigation, cookie banners and link lists are left out.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashMap;
+
+// below this the page is probably not an article and all visible text is used instead
+const MIN_ARTICLE_CHARS: usize = 250;
+// paragraphs shorter than this don't vote (captions, buttons, "Read more")
+const MIN_PARAGRAPH_CHARS: usize = 25;
+
+#[derive(Debug, Clone, Default)]
+pub struct Article {
+    pub title: Option<String>,
+    pub byline: Option<String>,
+    pub text: String,
+}
+
+// never part of the content
+const SKIPPED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "nav", "footer", "aside", "form",
+    "button", "iframe", "select",
+];
+const BLOCK_TAGS: &[&str] = &[
+    "h1", "h2", "h3", "h4", "h5", "h6", "p", "li", "pre", "blockquote", "figcaption", "td",
+];
+
+static UNLIKELY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\b(?:nav|navbar|menu|footer|header|sidebar|cookie|consent|gdpr|banner|popup|modal|newsletter|subscribe|share|social|related|recommend|comment|advert|ads?|promo|sponsor|breadcrumbs?|skip)\b|-(?:nav|menu|footer|sidebar|cookie|banner|share|ad)\b",
+    )
+    .expect("Failed to compile unlikely candidate regex")
+});
+static LIKELY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)article|body|content|entry|main|post|story|text")
+        .expect("Failed to compile likely candidate regex")
+});
+static WHITESPACE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").unwrap());
+
+fn sel(s: &str) -> Selector {
+    Selector::parse(s).expect("Failed to parse selector")
+}
+
+fn collapse(text: &str) -> String {
+    WHITESPACE.replace_all(text, " ").trim().to_string()
+}
+
+fn class_and_id(el: &ElementRef) -> String {
+    format!(
+        "{} {}",
+        el.value().attr("class").unwrap_or(""),
+        el.value().id().unwrap_or("")
+    )
+}
+
+// hidden elements and boilerplate containers, judged by tag, role and class/id names
+fn is_boilerplate(el: &ElementRef) -> bool {
+    let v = el.value();
+    if SKIPPED_TAGS.contains(&v.name()) || v.attr("hidden").is_some() || v.attr("aria-hidden") == Some("true") {
+        return true;
+    }
+    if matches!(
+        v.attr("role"),
+        Some("navigation" | "banner" | "contentinfo" | "complementary" | "dialog" | "alertdialog" | "menu")
+    ) {
+        return true;
+    }
+    if matches!(v.name(), "html" | "body" | "article" | "main") {
+        return false;
+    }
+    let names = class_and_id(el);
+    UNLIKELY.is_match(&names) && !LIKELY.is_match(&names)
+}
+
+fn in_boilerplate(el: &ElementRef) -> bool {
+    is_boilerplate(el) || el.ancestors().filter_map(ElementRef::wrap).any(|a| is_boilerplate(&a))
+}
+
+// visible text, skipping scripts, styles and boilerplate children
+fn visible_text(el: &ElementRef, out: &mut String) {
+    for child in el.children() {
+        if let Some(text) = child.value().as_text() {
+            out.push_str(text);
+        } else if let Some(child) = ElementRef::wrap(child) {
+            if !is_boilerplate(&child) {
+                visible_text(&child, out);
+                if child.value().name() == "br" {
+                    out.push(' ');
+                }
+            }
+        }
+    }
+}
+
+fn text_of(el: &ElementRef) -> String {
+    let mut out = String::new();
+    visible_text(el, &mut out);
+    collapse(&out)
+}
+
+// share of the text that sits in links; menus and "related" lists are mostly links
+fn link_density(el: &ElementRef, text_len: usize) -> f64 {
+    if text_len == 0 {
+        return 1.0;
+    }
+    let link_len: usize = el
+        .select(&sel("a"))
+        .map(|a| collapse(&a.text().collect::<String>()).chars().count())
+        .sum();
+    (link_len as f64 / text_len as f64).min(1.0)
+}
+
+fn meta_content(doc: &Html, selectors: &str) -> Option<String> {
+    doc.select(&sel(selectors))
+        .filter_map(|m| m.value().attr("content"))
+        .map(collapse)
+        .find(|c| !c.is_empty())
+}
+
+fn find_title(doc: &Html) -> Option<String> {
+    meta_content(doc, r#"meta[property="og:title"], meta[name="twitter:title"]"#)
+        .or_else(|| {
+            doc.select(&sel("title"))
+                .map(|t| collapse(&t.text().collect::<String>()))
+                .find(|t| !t.is_empty())
+        })
+        .or_else(|| doc.select(&sel("h1")).map(|h| text_of(&h)).find(|t| !t.is_empty()))
+}
+
+fn find_byline(doc: &Html) -> Option<String> {
+    meta_content(doc, r#"meta[name="author"], meta[property="article:author"]"#)
+        .filter(|a| !a.starts_with("http"))
+        .or_else(|| {
+            doc.select(&sel(r#"[rel="author"], [itemprop="author"], .byline, .author, [class*="byline"]"#))
+                .map(|el| text_of(&el))
+                .find(|t| !t.is_empty() && t.chars().count() < 100)
+        })
+}
+
+// The element whose paragraphs carry the most text
+fn best_candidate<'a>(doc: &'a Html) -> Option<ElementRef<'a>> {
+    let mut scores: HashMap<_, f64> = HashMap::new();
+    for p in doc.select(&sel("p, pre, td, blockquote")) {
+        if in_boilerplate(&p) {
+            continue;
+        }
+        let text = text_of(&p);
+        let len = text.chars().count();
+        if len < MIN_PARAGRAPH_CHARS {
+            continue;
+        }
+        let score = 1.0 + text.matches(',').count() as f64 + (len as f64 / 100.0).min(3.0);
+        let mut ancestors = p.ancestors().filter_map(ElementRef::wrap);
+        if let Some(parent) = ancestors.next() {
+            *scores.entry(parent.id()).or_default() += score;
+        }
+        if let Some(grandparent) = ancestors.next() {
+            *scores.entry(grandparent.id()).or_default() += score / 2.0;
+        }
+    }
+
+    scores
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let el = ElementRef::wrap(doc.tree.get(id)?)?;
+            let names = class_and_id(&el);
+            let mut score = score;
+            if LIKELY.is_match(&names) || matches!(el.value().name(), "article" | "main") {
+                score *= 1.25;
+            }
+            let len = text_of(&el).chars().count();
+            Some((el, score * (1.0 - link_density(&el, len))))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(el, _)| el)
+}
+
+// headings, paragraphs and list items of the container, one per line
+fn block_text(container: &ElementRef) -> String {
+    let blocks = sel(&BLOCK_TAGS.join(", "));
+    let mut lines: Vec<String> = Vec::new();
+    for el in container.select(&blocks) {
+        // nested blocks (a <p> inside an <li>) are part of the outer one
+        let nested = el
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .take_while(|a| a.id() != container.id())
+            .any(|a| BLOCK_TAGS.contains(&a.value().name()) || is_boilerplate(&a));
+        if nested || is_boilerplate(&el) {
+            continue;
+        }
+        let text = text_of(&el);
+        if text.is_empty() {
+            continue;
+        }
+        let name = el.value().name();
+        let line = if name.len() == 2 && name.starts_with('h') {
+            format!("## {}", text)
+        } else if name == "li" {
+            format!("- {}", text)
+        } else {
+            text
+        };
+        if lines.last() != Some(&line) {
+            lines.push(line);
+        }
+    }
+    lines.join("\n")
+}
+
+pub fn extract(html: &str) -> Article {
+    let doc = Html::parse_document(html);
+    let title = find_title(&doc);
+    let byline = find_byline(&doc);
+
+    let mut text = best_candidate(&doc)
+        .map(|el| block_text(&el))
+        .unwrap_or_default();
+    if text.chars().count() < MIN_ARTICLE_CHARS {
+        // not article-shaped (a product page, a search result); take what is visible
+        text = doc
+            .select(&sel("body"))
+            .next()
+            .map(|body| text_of(&body))
+            .unwrap_or_else(|| collapse(&doc.root_element().text().collect::<String>()));
+    }
+
+    Article {
+        title,
+        byline,
+        text,
+    }
+}
@@ -0,0 +1,298 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+// settings for scrubbing personal data out of prompts before they leave the machine
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RedactionConfig {
+    pub enabled: bool,
+    pub emails: bool,
+    pub phone_numbers: bool,
+    pub credit_cards: bool,
+    pub custom_patterns: Vec<String>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            emails: true,
+            phone_numbers: true,
+            credit_cards: true,
+            custom_patterns: Vec::new(),
+        }
+    }
+}
+
+// placeholder -> original value, filled while redacting and used to restore the answer
+#[derive(Debug, Default, Clone)]
+pub struct Redactions {
+    entries: Vec<(String, String)>,
+}
+
+impl Redactions {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn placeholder_for(&mut self, kind: &str, original: &str) -> String {
+        if let Some((placeholder, _)) = self.entries.iter().find(|(_, o)| o == original) {
+            return placeholder.clone();
+        }
+        let count = self
+            .entries
+            .iter()
+            .filter(|(p, _)| p.starts_with(&format!("[{}_", kind)))
+            .count();
+        let placeholder = format!("[{}_{}]", kind, count + 1);
+        self.entries.push((placeholder.clone(), original.to_string()));
+        placeholder
+    }
+}
+
+// Luhn checksum to avoid treating arbitrary long numbers as card numbers
+fn passes_luhn(candidate: &str) -> bool {
+    let digits: Vec<u32> = candidate.chars().filter_map(|c| c.to_digit(10)).collect();
+    if digits.len() < 13 || digits.len() > 19 {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+fn build_rules(cfg: &RedactionConfig) -> Vec<(&'static str, Regex)> {
+    let mut rules: Vec<(&'static str, Regex)> = Vec::new();
+    // card numbers go first so their digits are not picked up as phone numbers
+    if cfg.credit_cards {
+        rules.push((
+            "CARD",
+            Regex::new(r"\b(?:\d[ -]?){12,18}\d\b").expect("Failed to compile card regex"),
+        ));
+    }
+    if cfg.emails {
+        rules.push((
+            "EMAIL",
+            Regex::new(r"(?i)\b[A-Z0-9._%+-]+@[A-Z0-9.-]+\.[A-Z]{2,}\b")
+                .expect("Failed to compile email regex"),
+        ));
+    }
+    if cfg.phone_numbers {
+        rules.push((
+            "PHONE",
+            Regex::new(r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{2,4}\)[\s.-]?)?\b\d{2,4}(?:[\s.-]?\d{2,4}){2,4}\b")
+                .expect("Failed to compile phone regex"),
+        ));
+    }
+    for pattern in &cfg.custom_patterns {
+        match Regex::new(pattern) {
+            Ok(re) => rules.push(("REDACTED", re)),
+            Err(e) => eprintln!("[WARN] Ignoring invalid redaction pattern '{}': {}", pattern, e),
+        }
+    }
+    rules
+}
+
+fn redact_segment(segment: &str, rules: &[(&'static str, Regex)], map: &mut Redactions) -> String {
+    let mut out = segment.to_string();
+    for (kind, re) in rules {
+        out = re
+            .replace_all(&out, |caps: &regex::Captures| {
+                let found = caps.get(0).map(|m| m.as_str()).unwrap_or_default();
+                // never re-redact a placeholder we inserted earlier
+                if found.starts_with('[') && found.ends_with(']') {
+                    return found.to_string();
+                }
+                if *kind == "CARD" && !passes_luhn(found) {
+                    return found.to_string();
+                }
+                if *kind == "PHONE" {
+                    // dates and short numbers look like phone numbers; require more digits
+                    // unless the number carries an explicit country code
+                    let digits = found.chars().filter(|c| c.is_ascii_digit()).count();
+                    let min_digits = if found.starts_with('+') { 7 } else { 9 };
+                    if digits < min_digits {
+                        return found.to_string();
+                    }
+                }
+                map.placeholder_for(kind, found)
+            })
+            .into_owned();
+    }
+    out
+}
+
+// replaces sensitive values with placeholders; URLs and data URIs are left untouched
+pub fn redact(text: &str, cfg: &RedactionConfig, map: &mut Redactions) -> String {
+    if !cfg.enabled || text.is_empty() {
+        return text.to_string();
+    }
+    let rules = build_rules(cfg);
+    let url_re = Regex::new(r"(?:https?://|data:)[^\s)]+").expect("Failed to compile URL regex");
+
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for m in url_re.find_iter(text) {
+        out.push_str(&redact_segment(&text[last..m.start()], &rules, map));
+        out.push_str(m.as_str());
+        last = m.end();
+    }
+    out.push_str(&redact_segment(&text[last..], &rules, map));
+    out
+}
+
+// puts the original values back wherever the model echoed a placeholder
+pub fn restore(text: &str, map: &Redactions) -> String {
+    let mut out = text.to_string();
+    for (placeholder, original) in &map.entries {
+        out = out.replace(placeholder, original);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled() -> RedactionConfig {
+        RedactionConfig {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn disabled_leaves_text_alone() {
+        let mut map = Redactions::default();
+        let text = "mail bob@example.com or call +1 555 123 4567";
+        assert_eq!(redact(text, &RedactionConfig::default(), &mut map), text);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn emails_round_trip() {
+        let mut map = Redactions::default();
+        let out = redact(
+            "Write to bob@example.com and Alice.Smith@mail.co.uk, cc bob@example.com",
+            &enabled(),
+            &mut map,
+        );
+        assert_eq!(out, "Write to [EMAIL_1] and [EMAIL_2], cc [EMAIL_1]");
+        assert_eq!(map.len(), 2);
+        let answer = "Sent to [EMAIL_2]; [EMAIL_1] was copied.";
+        assert_eq!(
+            restore(answer, &map),
+            "Sent to Alice.Smith@mail.co.uk; bob@example.com was copied."
+        );
+    }
+
+    #[test]
+    fn cards_need_a_valid_checksum() {
+        // with phone numbers on, the order number would still go as a phone number
+        let cfg = RedactionConfig {
+            phone_numbers: false,
+            ..enabled()
+        };
+        let mut map = Redactions::default();
+        let out = redact(
+            "card 4111 1111 1111 1111, order 1234567890123456",
+            &cfg,
+            &mut map,
+        );
+        assert_eq!(out, "card [CARD_1], order 1234567890123456");
+        assert_eq!(
+            restore(&out, &map),
+            "card 4111 1111 1111 1111, order 1234567890123456"
+        );
+    }
+
+    #[test]
+    fn phones_but_not_dates_or_short_numbers() {
+        let mut map = Redactions::default();
+        let out = redact(
+            "call +44 20 7946 0958 or 0612345678 before 2025-01-15, room 12 34",
+            &enabled(),
+            &mut map,
+        );
+        assert_eq!(
+            out,
+            "call [PHONE_1] or [PHONE_2] before 2025-01-15, room 12 34"
+        );
+        assert_eq!(
+            restore(&out, &map),
+            "call +44 20 7946 0958 or 0612345678 before 2025-01-15, room 12 34"
+        );
+    }
+
+    #[test]
+    fn urls_are_left_untouched() {
+        let mut map = Redactions::default();
+        let text = "see https://example.com/u/bob@example.com?tel=0612345678 from bob@example.com";
+        assert_eq!(
+            redact(text, &enabled(), &mut map),
+            "see https://example.com/u/bob@example.com?tel=0612345678 from [EMAIL_1]"
+        );
+    }
+
+    #[test]
+    fn custom_patterns_and_invalid_ones() {
+        let cfg = RedactionConfig {
+            custom_patterns: vec![r"ACME-\d+".to_string(), "(unclosed".to_string()],
+            ..enabled()
+        };
+        let mut map = Redactions::default();
+        let out = redact("ticket ACME-42 and ACME-7", &cfg, &mut map);
+        assert_eq!(out, "ticket [REDACTED_1] and [REDACTED_2]");
+        assert_eq!(restore(&out, &map), "ticket ACME-42 and ACME-7");
+    }
+
+    #[test]
+    fn placeholders_are_shared_across_calls() {
+        // the prompt and context are redacted separately into one map
+        let mut map = Redactions::default();
+        let prompt = redact("email bob@example.com", &enabled(), &mut map);
+        let ctx = redact("bob@example.com, eve@example.com", &enabled(), &mut map);
+        assert_eq!(prompt, "email [EMAIL_1]");
+        assert_eq!(ctx, "[EMAIL_1], [EMAIL_2]");
+        assert_eq!(
+            restore("no placeholders here", &map),
+            "no placeholders here"
+        );
+    }
+}
@@ -15,38 +15,40 @@ You should have received a copy of the GNU Affero General Public License
 along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use crate::attachments;
+use crate::events::{
+    self, AudioOverflow, DeviceLost, DeviceReconnectFailed, DeviceReconnected, MessageMeta,
+    NewMessage, Role, WhisperDownloadComplete, WhisperDownloadProgress,
+};
 use crate::audio_input::{self, SAMPLE_RATE};
 use crate::get_text;
 use crate::models;
 use crate::send_to_llm;
 use crate::transform_text;
+use crate::tts::TtsProvider;
 use crate::utils;
 use crate::JarvisState; // for app state access // to reuse context builder from lib.rs if available
 
 use anyhow::{anyhow, Context, Result};
-use elevenlabs_rs::Model;
-use models::{AppContext, AudioPlayer};
-use porcupine::PorcupineBuilder;
-use reqwest::Client;
+use crate::audio_output::AudioOutput;
+use models::AppContext;
+use porcupine::{Porcupine, PorcupineBuilder};
 use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc, Mutex,
 };
-use std::thread;
 use tauri::Emitter;
 use tauri::Manager;
-use tokio::runtime::Handle;
 use webrtc_vad::{SampleRate, Vad, VadMode};
 use whisper_rs::{WhisperContext, WhisperContextParameters}; // for buffering TTS // to access app.state() and app.path()
-use cpal::traits::{DeviceTrait, HostTrait};
 use std::time::Instant;
 
 fn estimate_tts_tokens_and_chars(text: &str) -> (usize, usize) {
     // ElevenLabs bills by characters; provide both chars and a rough token estimate (~4 chars/token)
     let chars = text.chars().count();
-    let tokens_est = (chars + 3) / 4; // ceil(chars / 4)
+    let tokens_est = chars.div_ceil(4);
     (tokens_est, chars)
 }
 
@@ -56,39 +58,45 @@ fn build_ctx_text_from_active(app: &tauri::AppHandle) -> String {
     let current = state.active_conversation.lock().unwrap().clone();
     if let Some(fname) = current {
         // Build context by reading last 12 turns from that conversation file
-        if let Ok(history_dir) = (|| -> Result<std::path::PathBuf, String> {
-            let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
-            let history = dir.join("history");
-            std::fs::create_dir_all(&history).map_err(|e| e.to_string())?;
-            Ok(history)
-        })() {
-            let path = history_dir.join(&fname);
-            if let Ok(s) = std::fs::read_to_string(&path) {
-                if let Ok(turns) = serde_json::from_str::<Vec<serde_json::Value>>(&s) {
-                    let start = turns.len().saturating_sub(12);
-                    let mut buf = String::new();
-                    for t in turns.iter().skip(start) {
-                        let role = t
-                            .get("role")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("user")
-                            .to_uppercase();
-                        let content = t.get("content").and_then(|v| v.as_str()).unwrap_or("");
-                        buf.push_str(&format!("{}: {}\n", role, content));
-                    }
-                    return buf;
-                }
+        if let Ok(mut turns) = crate::read_turns(app, &fname) {
+            let cutoff = crate::conversation_settings::context_cutoff(app, &fname);
+            turns.retain(|t| t.created_at >= cutoff);
+            let start = turns.len().saturating_sub(12);
+            let mut buf = String::new();
+            for t in turns.iter().skip(start) {
+                buf.push_str(&format!("{}: {}\n", t.role.to_uppercase(), t.content));
             }
+            return buf;
         }
     }
     String::new()
 }
 
-const WHISPER_MODEL_URL: &str =
-    "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-medium-q5_0.bin?download=true";
+pub(crate) const DEFAULT_WHISPER_MODEL: &str = "medium-q5_0";
+
+pub(crate) fn whisper_model_url(model: &str) -> String {
+    format!(
+        "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-{}.bin?download=true",
+        model
+    )
+}
+
+// Whisper models live in app data under assets
+pub(crate) fn whisper_model_path(app: &tauri::AppHandle, model: &str) -> PathBuf {
+    let path = app
+        .path()
+        .app_config_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join("assets")
+        .join(format!("ggml-{}.bin", model));
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    path
+}
 
 // Emit periodic progress updates for Whisper model download
-async fn download_whisper_with_progress(
+pub(crate) async fn download_whisper_with_progress(
     app: &tauri::AppHandle,
     url: &str,
     path: &std::path::Path,
@@ -101,11 +109,15 @@ async fn download_whisper_with_progress(
 
     if path.exists() {
         // Notify complete immediately if file already present
-        let _ = app.emit(
-            "whisper-download-progress",
-            serde_json::json!({"downloaded": 1, "total": 1, "percent": 100}),
+        events::emit(
+            app,
+            WhisperDownloadProgress {
+                downloaded: 1,
+                total: 1,
+                percent: 100,
+            },
         );
-        let _ = app.emit("whisper-download-complete", serde_json::json!({}));
+        events::emit(app, WhisperDownloadComplete {});
         return Ok(());
     }
 
@@ -128,9 +140,13 @@ async fn download_whisper_with_progress(
     let mut downloaded: u64 = 0;
     let mut stream = res.bytes_stream();
     // Emit an initial 0% event
-    let _ = app.emit(
-        "whisper-download-progress",
-        serde_json::json!({"downloaded": 0, "total": total_size, "percent": 0}),
+    events::emit(
+        app,
+        WhisperDownloadProgress {
+            downloaded: 0,
+            total: total_size,
+            percent: 0,
+        },
     );
 
     while let Some(item) = stream.next().await {
@@ -140,18 +156,22 @@ async fn download_whisper_with_progress(
         downloaded = min(downloaded + chunk.len() as u64, total_size);
 
         let percent = ((downloaded as f64 / total_size as f64) * 100.0).round() as u64;
-        let _ = app.emit(
-            "whisper-download-progress",
-            serde_json::json!({"downloaded": downloaded, "total": total_size, "percent": percent}),
+        events::emit(
+            app,
+            WhisperDownloadProgress {
+                downloaded,
+                total: total_size,
+                percent,
+            },
         );
     }
 
-    let _ = app.emit("whisper-download-complete", serde_json::json!({}));
+    events::emit(app, WhisperDownloadComplete {});
     Ok(())
 }
 
 // Helper function to emit state changes
-async fn emit_state(app: &tauri::AppHandle, state: crate::JarvisStateEnum) {
+pub(crate) async fn emit_state(app: &tauri::AppHandle, state: crate::JarvisStateEnum) {
     let label = match state {
         crate::JarvisStateEnum::Idle => "Idle",
         crate::JarvisStateEnum::WakeListening => "WakeListening",
@@ -164,13 +184,8 @@ async fn emit_state(app: &tauri::AppHandle, state: crate::JarvisStateEnum) {
 }
 
 // Helper function to emit messages
-async fn emit_message(app: &tauri::AppHandle, role: &str, content: &str) {
-    let message = serde_json::json!({
-        "role": role,
-        "content": content,
-        "createdAt": chrono::Utc::now().timestamp_millis()
-    });
-    let _ = app.emit("new-message", message);
+async fn emit_message(app: &tauri::AppHandle, role: Role, content: &str) {
+    events::emit(app, NewMessage::new(role, content));
 }
 
 pub fn start_jarvis(is_running: Arc<AtomicBool>, config: models::Config, app: tauri::AppHandle) {
@@ -180,6 +195,7 @@ pub fn start_jarvis(is_running: Arc<AtomicBool>, config: models::Config, app: ta
     let rt = tokio::runtime::Runtime::new().unwrap();
     rt.block_on(async {
         if let Err(e) = run_jarvis_with_config(config, is_running.clone(), app.clone()).await {
+            crate::crash::report_error("Voice loop stopped");
             eprintln!(
                 "\n\n\n[ERROR] {}\nIf this is your first time running, please check your config.json, model paths, and device setup.\nFor more help, see the README \n",
                 e
@@ -189,7 +205,7 @@ pub fn start_jarvis(is_running: Arc<AtomicBool>, config: models::Config, app: ta
                 "Porcupine failed to start. Please enter a valid Picovoice access key in Settings > API Keys. (Details: {})",
                 e
             );
-            emit_message(&app, "system", &err_text).await;
+            emit_message(&app, Role::System, &err_text).await;
             emit_state(&app, crate::JarvisStateEnum::Idle).await;
         }
     });
@@ -197,6 +213,127 @@ pub fn start_jarvis(is_running: Arc<AtomicBool>, config: models::Config, app: ta
     is_running.store(false, Ordering::Relaxed);
 }
 
+// Wake word model: user override, then dev assets, then bundled resource
+pub(crate) fn resolve_wakeword_path(app: &tauri::AppHandle) -> Result<PathBuf> {
+    // 1) User override
+    if let Ok(roaming) = app.path().app_config_dir() {
+        let user_ppn = roaming.join("assets").join("Jarvis_en_windows_v3_0_0.ppn");
+        println!("[DEBUG] Checking user wakeword at {:?}", user_ppn);
+        if user_ppn.exists() {
+            return Ok(user_ppn);
+        }
+    }
+
+    // 2) Dev public assets
+    let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+
+    let dev_ppn = current_dir
+        .join("assets")
+        .join("Jarvis_en_windows_v3_0_0.ppn");
+    println!("[DEBUG] Checking dev wakeword at {:?}", dev_ppn);
+    if dev_ppn.exists() {
+        return Ok(dev_ppn);
+    }
+
+    // 3) Bundled resource
+    if let Ok(p) = app.path().resolve(
+        "assets/Jarvis_en_windows_v3_0_0.ppn",
+        tauri::path::BaseDirectory::Resource,
+    ) {
+        println!("[DEBUG] Checking bundled wakeword at {:?}", p);
+        if p.exists() {
+            return Ok(p);
+        }
+    }
+    Err(anyhow!(
+        "Wakeword .ppn not found in user assets, public/assets, or resources"
+    ))
+}
+
+// Porcupine model params and native library, bundled first then the local build dir
+pub(crate) fn resolve_porcupine_paths(app: &tauri::AppHandle) -> (PathBuf, PathBuf) {
+    let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    // Prefer bundled resources first
+    let params_res = app
+        .path()
+        .resolve(
+            "build/porcupine_params.pv",
+            tauri::path::BaseDirectory::Resource,
+        )
+        .ok();
+    let lib_res = app
+        .path()
+        .resolve(
+            "build/libpv_porcupine.dll",
+            tauri::path::BaseDirectory::Resource,
+        )
+        .ok();
+
+    if let (Some(p_params), Some(p_lib)) = (params_res.clone(), lib_res.clone()) {
+        if p_params.exists() && p_lib.exists() {
+            return (p_params, p_lib);
+        }
+    }
+
+    // Fallback to local build directory (dev)
+    let build_dir = current_dir.join("build");
+    (
+        build_dir.join("porcupine_params.pv"),
+        build_dir.join("libpv_porcupine.dll"),
+    )
+}
+
+// Voice activity detector for 16 kHz audio; unknown modes fall back to "aggressive"
+pub(crate) fn build_vad(mode: &str) -> Vad {
+    let mode = match mode.to_lowercase().as_str() {
+        "quality" => VadMode::Quality,
+        "aggressive" => VadMode::Aggressive,
+        "veryaggressive" | "very_aggressive" | "very-aggressive" => VadMode::VeryAggressive,
+        _ => VadMode::Aggressive,
+    };
+    Vad::new_with_rate_and_mode(SampleRate::Rate16kHz, mode)
+}
+
+// Creates the wake word engine, falling back to the crate's embedded defaults
+pub(crate) fn build_porcupine(
+    access_key: &str,
+    sensitivity: f32,
+    wakeword_path: &std::path::Path,
+    params_path: &std::path::Path,
+    lib_path: &std::path::Path,
+) -> Result<Porcupine> {
+    // First try with explicit model and library paths
+    let attempt = PorcupineBuilder::new_with_keyword_paths(
+        access_key,
+        &[wakeword_path.to_str().unwrap()],
+    )
+    .sensitivities(&[sensitivity])
+    .model_path(params_path.to_str().unwrap())
+    .library_path(lib_path.to_str().unwrap())
+    .init();
+
+    match attempt {
+        Ok(pv) => Ok(pv),
+        Err(e1) => {
+            eprintln!(
+                "[DEBUG] Porcupine init with explicit paths failed: {:?}",
+                e1
+            );
+            // Fallback: let crate resolve embedded defaults
+            PorcupineBuilder::new_with_keyword_paths(
+                access_key,
+                &[wakeword_path.to_str().unwrap()],
+            )
+            .sensitivities(&[sensitivity])
+            .init()
+            .map_err(|e2| anyhow!(
+                "Unable to create Porcupine wake word engine: explicit paths error: {:?}; fallback error: {:?}",
+                e1, e2
+            ))
+        }
+    }
+}
+
 async fn run_jarvis_with_config(
     config: models::Config,
     is_running: Arc<AtomicBool>,
@@ -205,107 +342,24 @@ async fn run_jarvis_with_config(
     println!("[DEBUG] Entered run_jarvis_with_config()");
     // Avoid logging secrets in config; print selected devices only
     println!(
-        "[DEBUG] Loaded config: mic_name={:?}, mic_index={}, out_name={:?}",
+        "[DEBUG] Loaded config: mic_name={:?}, mic_index={}, out_name={:?}, tts_out_name={:?}",
         config.default_microphone_name,
         config.default_microphone_index,
-        config.default_output_device_name
+        config.default_output_device_name,
+        config.tts_output_device_name
     );
 
     // Let UI know we're loading heavy assets
     emit_state(&tauri_app, crate::JarvisStateEnum::Loading).await;
+    // the wake word, recording and playback all run on this thread
+    crate::thread_priority::configure(&config.thread_priority);
+    crate::thread_priority::apply(crate::thread_priority::AudioThread::WakeWord);
 
-    let wakeword_path = (|| -> Result<PathBuf> {
-        // 1) User override
-        if let Ok(roaming) = tauri_app.path().app_config_dir() {
-            let user_ppn = roaming.join("assets").join("Jarvis_en_windows_v3_0_0.ppn");
-            println!("[DEBUG] Checking user wakeword at {:?}", user_ppn);
-            if user_ppn.exists() {
-                return Ok(user_ppn);
-            }
-        }
-
-        // 2) Dev public assets
-        let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-
-        let dev_ppn = current_dir
-            .join("assets")
-            .join("Jarvis_en_windows_v3_0_0.ppn");
-        println!("[DEBUG] Checking dev wakeword at {:?}", dev_ppn);
-        if dev_ppn.exists() {
-            return Ok(dev_ppn);
-        }
-
-        // 3) Bundled resource
-        if let Ok(p) = tauri_app.path().resolve(
-            "assets/Jarvis_en_windows_v3_0_0.ppn",
-            tauri::path::BaseDirectory::Resource,
-        ) {
-            println!("[DEBUG] Checking bundled wakeword at {:?}", p);
-            if p.exists() {
-                return Ok(p);
-            }
-        }
-        Err(anyhow!(
-            "Wakeword .ppn not found in user assets, public/assets, or resources"
-        ))
-    })()?;
-
-    // Whisper model lives in app data under assets
-    let whisper_model_path = (|| {
-        let path = tauri_app
-            .path()
-            .app_config_dir()
-            .unwrap_or_else(|_| PathBuf::from("."))
-            .join("assets")
-            .join("ggml-medium-q5_0.bin");
-        if let Some(dir) = path.parent() {
-            let _ = std::fs::create_dir_all(dir);
-        }
-        path
-    })();
-
-    let audio_player = AudioPlayer::new_with_app_handle(
-        tauri_app.clone(),
-        config.default_output_device_name.clone(),
-    )
-        .with_context(|| "Failed to initialize audio output")?;
-    println!("[DEBUG] Initialized AudioPlayer");
+    let wakeword_path = resolve_wakeword_path(&tauri_app)?;
 
-    // Get the current directory to resolve relative paths
-    let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-    println!("[DEBUG] Current directory: {:?}", current_dir);
-
-    // Resolve Porcupine model and library paths
-    let (porcupine_params_path, porcupine_lib_path) = (|| {
-        // Prefer bundled resources first
-        let params_res = tauri_app
-            .path()
-            .resolve(
-                "build/porcupine_params.pv",
-                tauri::path::BaseDirectory::Resource,
-            )
-            .ok();
-        let lib_res = tauri_app
-            .path()
-            .resolve(
-                "build/libpv_porcupine.dll",
-                tauri::path::BaseDirectory::Resource,
-            )
-            .ok();
+    let whisper_model_path = whisper_model_path(&tauri_app, DEFAULT_WHISPER_MODEL);
 
-        if let (Some(p_params), Some(p_lib)) = (params_res.clone(), lib_res.clone()) {
-            if p_params.exists() && p_lib.exists() {
-                return (p_params, p_lib);
-            }
-        }
-
-        // Fallback to local build directory (dev)
-        let build_dir = current_dir.join("build");
-        (
-            build_dir.join("porcupine_params.pv"),
-            build_dir.join("libpv_porcupine.dll"),
-        )
-    })();
+    let (porcupine_params_path, porcupine_lib_path) = resolve_porcupine_paths(&tauri_app);
 
     println!("[DEBUG] Porcupine params path: {:?}", porcupine_params_path);
     println!("[DEBUG] Porcupine lib path: {:?}", porcupine_lib_path);
@@ -327,100 +381,203 @@ async fn run_jarvis_with_config(
         ));
     }
 
-    let porcupine = {
-        // First try with explicit model and library paths
-        let attempt = PorcupineBuilder::new_with_keyword_paths(
-            &config.porcupine_key,
-            &[wakeword_path.to_str().unwrap()],
-        )
-        .sensitivities(&[config.wwd_sensitivity])
-        .model_path(porcupine_params_path.to_str().unwrap())
-        .library_path(porcupine_lib_path.to_str().unwrap())
-        .init();
-
-        match attempt {
-            Ok(pv) => pv,
-            Err(e1) => {
-                eprintln!(
-                    "[DEBUG] Porcupine init with explicit paths failed: {:?}",
-                    e1
-                );
-                // Fallback: let crate resolve embedded defaults
-                PorcupineBuilder::new_with_keyword_paths(
-                    &config.porcupine_key,
-                    &[wakeword_path.to_str().unwrap()],
-                )
-                .sensitivities(&[config.wwd_sensitivity])
-                .init()
-                .map_err(|e2| anyhow!(
-                    "Unable to create Porcupine wake word engine: explicit paths error: {:?}; fallback error: {:?}",
-                    e1, e2
-                ))?
-            }
-        }
-    };
+    let porcupine = build_porcupine(
+        &config.porcupine_key,
+        config.wwd_sensitivity,
+        &wakeword_path,
+        &porcupine_params_path,
+        &porcupine_lib_path,
+    )?;
     println!(
         "[DEBUG] Initialized Porcupine with wakeword path: {:?}",
         wakeword_path
     );
 
-    let elevenlabs_model = match config.elevenlabs_model.as_str() {
-        "eleven_multilingual_v2" => Model::ElevenMultilingualV2,
-        "eleven_flash_v2_5" => Model::ElevenFlashV2_5,
-        "eleven_turbo_v2_5" => Model::ElevenTurboV2_5,
-        _ => Model::ElevenMultilingualV2,
+    // a satellite sends its commands to the main instance and needs no Whisper model
+    let stt = if config.satellite.enabled {
+        println!("[DEBUG] Satellite mode: leaving transcription to {}", config.satellite.main_url);
+        None
+    } else {
+        println!("[DEBUG] Downloading Whisper model if needed...");
+        download_whisper_with_progress(
+            &tauri_app,
+            &whisper_model_url(DEFAULT_WHISPER_MODEL),
+            &whisper_model_path,
+        )
+        .await?;
+        println!("[DEBUG] Whisper model ready at: {:?}", whisper_model_path);
+
+        let whisper_context = WhisperContext::new_with_params(
+            whisper_model_path.to_str().unwrap(),
+            WhisperContextParameters::default(),
+        )
+        .with_context(|| "Failed to load Whisper model")?;
+        // game mode may unload it, so the worker keeps the path to load it again
+        let stt = crate::stt::SttWorker::spawn_from_file(
+            Arc::new(whisper_context),
+            whisper_model_path,
+        );
+        println!("[DEBUG] WhisperContext initialized");
+        crate::game_mode::on_voice_loop_start(&tauri_app);
+        Some(stt)
     };
 
-    println!("[DEBUG] Selected ElevenLabs model: {:?}", elevenlabs_model);
+    crate::pipeline::Pipeline::live(porcupine, stt)
+        .run(config, is_running, &tauri_app)
+        .await
+}
+
+impl crate::pipeline::Pipeline {
+    // The voice loop over these engines: opens the voice output and the audio input (the
+    // microphone, or the WAV files of `config.input_source`), then listens, answers and
+    // speaks until `is_running` is cleared
+    pub async fn run(
+        self,
+        config: models::Config,
+        is_running: Arc<AtomicBool>,
+        tauri_app: &tauri::AppHandle,
+    ) -> Result<()> {
+        let audio_output = if self.muted {
+            AudioOutput::muted(Some(tauri_app.clone()))
+        } else {
+            let output = AudioOutput::open(
+                Some(tauri_app.clone()),
+                config.voice_output_device().as_deref(),
+                config.output_volume.unwrap_or(1.0),
+            )
+            .with_context(|| "Failed to initialize audio output")?;
+            output.make_active();
+            output
+        };
+        println!("[DEBUG] Initialized AudioOutput");
+
+        let elevenlabs_model = models::elevenlabs_model_from_id(&config.elevenlabs_model);
+        println!("[DEBUG] Selected ElevenLabs model: {:?}", elevenlabs_model);
+
+        // 5 s of audio; older samples are dropped (and reported) if nobody reads them in time
+        let audio_buffer = audio_input::AudioRing::shared(SAMPLE_RATE * 5);
+        {
+            let overflow_app = tauri_app.clone();
+            audio_buffer
+                .lock()
+                .map_err(|e| anyhow!("Failed to lock audio buffer (poisoned): {e}"))?
+                .set_on_overflow(move |ring| {
+                    events::emit(
+                        &overflow_app,
+                        AudioOverflow {
+                            dropped_samples: ring.dropped_samples,
+                            overflow_events: ring.overflow_events,
+                            capacity: ring.max_len(),
+                        },
+                    );
+                });
+        }
+        println!("[DEBUG] Audio buffer initialized");
 
-    println!("[DEBUG] Downloading Whisper model if needed...");
-    download_whisper_with_progress(&tauri_app, WHISPER_MODEL_URL, &whisper_model_path).await?;
-    println!("[DEBUG] Whisper model ready at: {:?}", whisper_model_path);
+        println!("[DEBUG] VAD mode set to: {}", config.vad_mode);
+        let vad = build_vad(&config.vad_mode);
 
-    let whisper_context = WhisperContext::new_with_params(
-        whisper_model_path.to_str().unwrap(),
-        WhisperContextParameters::default(),
-    )
-    .with_context(|| "Failed to load Whisper model")?;
-    let whisper_context = Arc::new(whisper_context);
-    println!("[DEBUG] WhisperContext initialized");
+        let audio_input = audio_input::start_audio_stream(
+            audio_buffer.clone(),
+            config.default_microphone_name.clone(),
+            config.default_microphone_index,
+            &config.input_source,
+        )
+        .with_context(|| "Failed to start audio input stream")?;
+        {
+            let device_app = tauri_app.clone();
+            let device_config = config.clone();
+            audio_input.set_listener(move |event| notify_input_event(&device_app, &device_config, event));
+        }
+        println!("[DEBUG] Audio input stream started");
+
+        let app = AppContext {
+            config,
+            audio_output,
+            pipeline: self,
+            vad: Mutex::new(vad),
+            audio_buffer,
+            audio_input,
+            elevenlabs_model,
+        };
+        println!("[DEBUG] AppContext initialized");
+
+        println!("\n--- Prepared environment successfully  ---");
+        // Now ready to listen for wake word
+        emit_state(tauri_app, crate::JarvisStateEnum::WakeListening).await;
 
-    let audio_buffer = Arc::new(Mutex::new(VecDeque::<i16>::with_capacity(SAMPLE_RATE * 5)));
-    println!("[DEBUG] Audio buffer initialized");
+        crate::trace::scope(main_loop_with_running(&app, is_running, tauri_app)).await
+    }
+}
 
-    let vad_mode = match config.vad_mode.to_lowercase().as_str() {
-        "quality" => VadMode::Quality,
-        "aggressive" => VadMode::Aggressive,
-        "veryaggressive" | "very_aggressive" | "very-aggressive" => VadMode::VeryAggressive,
-        _ => VadMode::Aggressive,
+// Tells the UI (and the user, out loud) when the microphone drops out or comes back
+fn notify_input_event(app: &tauri::AppHandle, config: &models::Config, event: &audio_input::InputEvent) {
+    use audio_input::InputEvent;
+    let spoken = match event {
+        InputEvent::DeviceLost { device, error } => {
+            events::emit(
+                app,
+                DeviceLost {
+                    device: device.clone(),
+                    error: error.clone(),
+                },
+            );
+            "I lost the microphone. Trying to reconnect.".to_string()
+        }
+        InputEvent::Reconnected { device, fallback } => {
+            events::emit(
+                app,
+                DeviceReconnected {
+                    device: device.clone(),
+                    fallback: *fallback,
+                },
+            );
+            if *fallback {
+                format!("Microphone is back, using {} instead.", device)
+            } else {
+                "Microphone reconnected.".to_string()
+            }
+        }
+        InputEvent::ReconnectFailed { device } => {
+            events::emit(
+                app,
+                DeviceReconnectFailed {
+                    device: device.clone(),
+                },
+            );
+            "I couldn't find a microphone. Please check your input device.".to_string()
+        }
     };
-    println!("[DEBUG] VAD mode set to: {}", config.vad_mode);
-    let vad = Vad::new_with_rate_and_mode(SampleRate::Rate16kHz, vad_mode);
+    let config = config.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = crate::tts::speak_with(&config, &spoken).await {
+            eprintln!("[ERROR] Failed to announce microphone change: {}", e);
+        }
+    });
+}
 
-    audio_input::start_audio_stream(
-        audio_buffer.clone(),
-        config.default_microphone_name.clone(),
-        config.default_microphone_index,
-    )
-        .with_context(|| "Failed to start audio input stream")?;
-    println!("[DEBUG] Audio input stream started");
-
-    let app = AppContext {
-        config,
-        audio_player,
-        porcupine,
-        vad: Mutex::new(vad),
-        whisper_context,
-        audio_buffer,
-        elevenlabs_model,
-    };
-    println!("[DEBUG] AppContext initialized");
+// Typed commands waiting to go through the voice loop as if they had been spoken
+static SIMULATED: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
 
-    println!("\n--- Prepared environment successfully  ---");
-    // Now ready to listen for wake word
-    emit_state(&tauri_app, crate::JarvisStateEnum::WakeListening).await;
+pub(crate) fn take_simulated() -> Option<String> {
+    SIMULATED.lock().unwrap().pop_front()
+}
 
-    main_loop_with_running(&app, is_running, &tauri_app).await?;
+// Runs `text` through the running voice loop as if it had been said after the wake word:
+// voice commands, transforms, the LLM and the spoken answer, without the microphone
+#[tauri::command]
+pub fn cmd_simulate_voice_command(app: tauri::AppHandle, text: String) -> Result<(), String> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("Nothing to simulate".into());
+    }
+    if !app.state::<JarvisState>().is_running.load(Ordering::Relaxed) {
+        return Err("Start listening first; simulated commands go through the voice loop".into());
+    }
+    if crate::cmd_load_config(app.clone())?.satellite.enabled {
+        return Err("A satellite sends audio to the main instance and can't simulate text".into());
+    }
+    SIMULATED.lock().unwrap().push_back(text);
     Ok(())
 }
 
@@ -430,32 +587,53 @@ async fn main_loop_with_running(
     tauri_app: &tauri::AppHandle,
 ) -> Result<()> {
     println!("[DEBUG] Entered main_loop_with_running()");
-    let http_client = Client::new();
+    // commands typed before this run are not for it
+    SIMULATED.lock().unwrap().clear();
 
     while is_running.load(Ordering::Relaxed) {
         // 1) Wake‐word detection
         println!("[DEBUG] Waiting for wake word...");
         emit_state(tauri_app, crate::JarvisStateEnum::WakeListening).await;
-        get_text::wait_for_wakeword(app, &is_running)?;
+        let wake = get_text::wait_for_wakeword(app, &is_running)?;
         let perf_start = Instant::now();
         let wake_start_ms = chrono::Utc::now().timestamp_millis();
-        println!("\nWake word detected!");
-        if let Err(e) = app.audio_player.play_sound("assets/beep.wav") {
-            eprintln!("Failed to play beep sound: {e}");
-        }
+        let simulated = match wake {
+            get_text::Wake::Simulated(text) => {
+                println!("[DEBUG] Simulated voice command: {}", text);
+                Some(text)
+            }
+            get_text::Wake::WakeWord => {
+                println!("\nWake word detected!");
+                crate::stats::record_wake();
+                if let Err(e) = app.audio_output.play_sound("assets/beep.wav") {
+                    eprintln!("Failed to play beep sound: {e}");
+                }
+                None
+            }
+        };
+        let is_simulated = simulated.is_some();
 
         // Check if we should stop
         if !is_running.load(Ordering::Relaxed) {
             break;
         }
 
-        // 2) Record user command
-        println!("Listening for command... (Speak now)");
-        println!("[DEBUG] Recording command...");
-        emit_state(tauri_app, crate::JarvisStateEnum::Recording).await;
-        let speech_segment = get_text::record_command(app, &is_running)?;
+        // 2) Record user command; a simulated one comes without audio
+        let speech_segment = if is_simulated {
+            Vec::new()
+        } else {
+            println!("Listening for command... (Speak now)");
+            println!("[DEBUG] Recording command...");
+            emit_state(tauri_app, crate::JarvisStateEnum::Recording).await;
+            get_text::record_command(app, &is_running, |frame| {
+                if let Some(partial) = frame.partial.as_deref() {
+                    crate::overlay::emit_transcript(tauri_app, partial, false);
+                }
+                events::emit(tauri_app, frame);
+            })?
+        };
 
-        if speech_segment.is_empty() {
+        if speech_segment.is_empty() && !is_simulated {
             println!("No speech detected after wake word. Please try again.");
             println!("[DEBUG] No speech detected after wake word");
             emit_state(tauri_app, crate::JarvisStateEnum::WakeListening).await;
@@ -467,30 +645,62 @@ async fn main_loop_with_running(
             println!("[DEBUG] Processing command inline (no spawn)");
             emit_state(tauri_app, crate::JarvisStateEnum::Processing).await;
 
-            let whisper_ctx = Arc::clone(&app.whisper_context);
-            let config = app.config.clone();
-            let elevenlabs_model = app.elevenlabs_model.clone();
-            let speech_segment = speech_segment.clone();
-            let client_clone = http_client.clone();
-
-            // Ensure at least 1s of audio (Whisper needs >= ~1000 ms)
-            let mut audio_for_transcribe = speech_segment.clone();
-            // Use ~1.2s to comfortably exceed Whisper's 1s minimum
-            let min_samples = (SAMPLE_RATE * 12) / 10; // 1.2s at 16kHz
-            if audio_for_transcribe.len() < min_samples {
-                audio_for_transcribe.reserve(min_samples - audio_for_transcribe.len());
-                audio_for_transcribe
-                    .extend(std::iter::repeat(0).take(min_samples - audio_for_transcribe.len()));
+            // a satellite leaves transcription, the answer and the voice to the main instance
+            if app.config.satellite.enabled {
+                crate::satellite::respond(app, tauri_app, &speech_segment).await;
+                emit_state(tauri_app, crate::JarvisStateEnum::WakeListening).await;
+                continue;
             }
 
-            // a) Transcribe
-            println!("[DEBUG] Transcribing audio to text...");
-            let mut user_prompt = get_text::transcribe(
-                &whisper_ctx,
-                &audio_for_transcribe,
-                &config.whisper_language,
-            )?;
+            let mut config = app.config.clone();
+            // the active persona may change between commands, so resolve it per turn
+            let persona = crate::persona::apply_active(tauri_app, &mut config);
+            let elevenlabs_model = if persona.is_some() {
+                models::elevenlabs_model_from_id(&config.elevenlabs_model)
+            } else {
+                app.elevenlabs_model.clone()
+            };
+            let speech_segment = speech_segment.clone();
+
+            // a) Transcribe on the Whisper worker; stopping Jarvis abandons the job
+            let mut user_prompt = match simulated {
+                Some(text) => text,
+                None => {
+                    // Ensure at least 1s of audio (Whisper needs >= ~1000 ms)
+                    let audio_for_transcribe = get_text::pad_for_whisper(&speech_segment);
+                    println!("[DEBUG] Transcribing audio to text...");
+                    crate::stats::record_stt(audio_for_transcribe.len());
+                    let job = app
+                        .pipeline
+                        .stt
+                        .as_ref()
+                        .ok_or_else(|| anyhow!("Whisper is not loaded"))?
+                        .submit(audio_for_transcribe, &config.whisper_language);
+                    let Some(text) = job.wait_while(&is_running).await? else {
+                        println!("[DEBUG] Transcription abandoned, Jarvis is stopping");
+                        continue;
+                    };
+                    text
+                }
+            };
             user_prompt = user_prompt.trim().to_string();
+            crate::overlay::emit_transcript(tauri_app, &user_prompt, true);
+            // emitted when this command is done, however it ends
+            let source = if is_simulated { "simulated" } else { "voice" };
+            let _trace = crate::trace::begin(source, &user_prompt, persona.as_deref());
+
+            // Optional debug archive of what was heard and how it was transcribed
+            if config.debug_capture.enabled && !is_simulated {
+                if let Err(e) = crate::captures::save_capture(
+                    tauri_app,
+                    &config.debug_capture,
+                    &speech_segment,
+                    &user_prompt,
+                    &config.whisper_language,
+                ) {
+                    eprintln!("[WARN] Failed to save debug capture: {}", e);
+                }
+            }
 
             // If transcription is empty, still emit a placeholder so UI shows the user message
             let transcription_was_empty = user_prompt.is_empty();
@@ -498,14 +708,34 @@ async fn main_loop_with_running(
                 user_prompt = String::from("(couldn't understand)");
             }
 
-            // Emit user message
-            emit_message(tauri_app, "user", &user_prompt).await;
+            // Emit user message, keeping the recorded audio with the active conversation
+            let active = tauri_app
+                .state::<JarvisState>()
+                .active_conversation
+                .lock()
+                .unwrap()
+                .clone();
+            let user_created_at = chrono::Utc::now().timestamp_millis();
+            let mut user_attachments = Vec::new();
+            if let Some(fname) = active.as_deref().filter(|_| !is_simulated) {
+                match attachments::save_audio(tauri_app, fname, user_created_at, &speech_segment) {
+                    Ok(r) => user_attachments.push(r),
+                    Err(e) => eprintln!("[WARN] Failed to save command audio: {}", e),
+                }
+            }
+            let mut user_message = NewMessage::new(Role::User, &user_prompt)
+                .at(user_created_at)
+                .with("attachments", serde_json::json!(user_attachments));
+            if is_simulated {
+                user_message = user_message.with("simulated", true);
+            }
+            events::emit(tauri_app, user_message);
 
             // If we couldn't understand, do not send to LLM; go back to listening
             if transcription_was_empty {
                 emit_message(
                     tauri_app,
-                    "assistant",
+                    Role::Assistant,
                     "Sorry, I didn't catch that. Please repeat.",
                 )
                 .await;
@@ -513,9 +743,21 @@ async fn main_loop_with_running(
                 continue;
             }
 
+            // a voice memo keeps listening after the command, so it needs the microphone
+            if crate::voice_memos::wants_recording(tauri_app, &config, &user_prompt) {
+                emit_state(tauri_app, crate::JarvisStateEnum::Recording).await;
+                if let Err(e) =
+                    crate::voice_memos::record(app, &is_running, tauri_app, &config).await
+                {
+                    eprintln!("[ERROR] Voice memo failed: {}", e);
+                }
+                crate::trace::update(|t| t.command = Some("voice_memo".to_string()));
+                continue;
+            }
+
             // b) Pre-transform / exit
             println!("[DEBUG] Optionally transforming prompt...");
-            if transform_text::if_contains_exit(
+            if let Some(command) = transform_text::if_contains_exit(
                 &user_prompt,
                 &config,
                 elevenlabs_model.clone(),
@@ -525,44 +767,127 @@ async fn main_loop_with_running(
             .await
             {
                 // Exit early (no more processing)
+                crate::trace::update(|t| t.command = Some(command.to_string()));
                 continue;
             }
             let transformed_prompt =
                 transform_text::if_contains_transform(&user_prompt, elevenlabs_model.clone());
+            if let Some(fname) = active.as_deref() {
+                if let Some(image) = attachments::save_prompt_image(
+                    tauri_app,
+                    fname,
+                    user_created_at,
+                    &transformed_prompt,
+                ) {
+                    user_attachments.push(image);
+                    events::emit(
+                        tauri_app,
+                        MessageMeta::new(Role::User, user_created_at)
+                            .with("attachments", serde_json::json!(user_attachments)),
+                    );
+                }
+            }
 
             // c) Query LLM with context from the currently selected conversation
             println!("[DEBUG] Sending prompt to LLM...");
             if config.gemini_key.trim().is_empty() {
                 emit_message(
                     tauri_app,
-                    "system",
+                    Role::System,
                     "Please enter your Gemini API key in Settings > API Keys.",
                 )
                 .await;
+                crate::trace::update(|t| t.error = Some("Missing Gemini API key".into()));
                 emit_state(tauri_app, crate::JarvisStateEnum::WakeListening).await;
                 continue;
             }
-            let ctx_text = build_ctx_text_from_active(tauri_app);
-            let llm_answer =
-                send_to_llm::query_gemini(&transformed_prompt, &config, &ctx_text).await?;
+            let mut ctx_text = build_ctx_text_from_active(tauri_app);
+            ctx_text.insert_str(
+                0,
+                &crate::memory::prompt_section(tauri_app, &config, &transformed_prompt),
+            );
+            // in cooking mode other questions come with the recipe
+            ctx_text.insert_str(0, &crate::cooking::prompt_section());
+            config.redaction.enabled = crate::conversation_settings::redaction_enabled(
+                tauri_app,
+                active.as_deref(),
+                config.redaction.enabled,
+            );
+            crate::conversation_settings::apply_generation(
+                tauri_app,
+                active.as_deref(),
+                &mut config.generation,
+            );
+            // a dual answer is short when spoken anyway; the limit would cut the display part
+            if !config.dual_response {
+                config.generation = config.generation.clone().for_voice();
+            }
+            let answer = app.pipeline.llm.answer(&transformed_prompt, &config, &ctx_text).await;
+            let send_to_llm::DualAnswer {
+                spoken,
+                display: llm_answer,
+            } = match answer {
+                Ok(answer) => answer,
+                Err(e) => match send_to_llm::Unanswered::from_error(&e) {
+                    // blocked or empty: say why instead of going quiet
+                    Some(unanswered) => {
+                        crate::trace::update(|t| t.error = Some(e.to_string()));
+                        unanswered.emit(tauri_app);
+                        emit_state(tauri_app, crate::JarvisStateEnum::Speaking).await;
+                        if let Err(e) = crate::tts::speak_with(&config, &unanswered.spoken).await {
+                            eprintln!("[ERROR] Failed to speak notice: {}", e);
+                        }
+                        emit_state(tauri_app, crate::JarvisStateEnum::WakeListening).await;
+                        continue;
+                    }
+                    None => return Err(e),
+                },
+            };
+            crate::stats::record_interaction(true);
+            tauri::async_runtime::spawn(crate::memory::learn_from(
+                tauri_app.clone(),
+                config.clone(),
+                user_prompt.clone(),
+            ));
+
+            // d) Post-transform: copy [[copy]] blocks, keep code out of the speech
+            println!("[DEBUG] Optionally transforming LLM response...");
+            let post =
+                transform_text::if_contains_transform_post_llm(tauri_app, &config, &llm_answer);
+            let llm_answer = post.display;
+            // with a dual answer only the short spoken part is read aloud
+            let spoken = if !config.dual_response {
+                post.spoken
+            } else if post.copied.is_some() {
+                transform_text::add_code_note(&spoken, post.copied.as_ref())
+            } else {
+                spoken
+            };
 
             // Emit assistant message with initial meta (TTS usage estimate)
-            let (tts_tokens_est, _tts_chars) = estimate_tts_tokens_and_chars(&llm_answer);
+            let (tts_tokens_est, _tts_chars) = estimate_tts_tokens_and_chars(&spoken);
             let assistant_created_at = chrono::Utc::now().timestamp_millis();
-            let assistant_payload = serde_json::json!({
-                "role": "assistant",
-                "content": llm_answer,
-                "createdAt": assistant_created_at,
-                "meta": {
-                    "ttsTokensEst": tts_tokens_est
-                }
-            });
-            let _ = tauri_app.emit("new-message", assistant_payload);
+            let mut assistant_meta = serde_json::Map::new();
+            assistant_meta.insert("ttsTokensEst".into(), tts_tokens_est.into());
+            assistant_meta.insert("model".into(), config.gemini_model.clone().into());
+            if let Some(p) = persona {
+                assistant_meta.insert("persona".into(), p.into());
+            }
+            if spoken != llm_answer {
+                assistant_meta.insert("spoken".into(), spoken.clone().into());
+            }
+            events::emit(
+                tauri_app,
+                NewMessage::new(Role::Assistant, &llm_answer)
+                    .at(assistant_created_at)
+                    .with_meta(assistant_meta),
+            );
 
-            // d) Post-transform
-            println!("[DEBUG] Optionally transforming LLM response...");
-            let llm_answer = transform_text::if_contains_transform_post_llm(&llm_answer);
+            crate::trace::update(|t| t.answer = llm_answer.clone());
+
+            let llm_answer = crate::tts_normalize::normalize(&spoken, &config.tts_normalization);
             let llm_answer = llm_answer.trim().to_string();
+            crate::trace::update(|t| t.spoken = llm_answer.clone());
 
             // If post-transform result is empty, skip TTS and return to listening
             if llm_answer.is_empty() {
@@ -573,113 +898,83 @@ async fn main_loop_with_running(
                 continue;
             }
 
-            // e) Buffer TTS audio
+            // e) Buffer TTS audio (ElevenLabs) or speak directly (system voice)
             println!("[DEBUG] Buffering TTS response...");
-            if config.elevenlabs_key.trim().is_empty() {
-                emit_message(
-                    tauri_app,
-                    "system",
-                    "Please enter your ElevenLabs API key in Settings > API Keys.",
-                )
-                .await;
-                emit_state(tauri_app, crate::JarvisStateEnum::WakeListening).await;
-                continue;
-            }
-            if config.voice_id.trim().is_empty() {
-                emit_message(
-                    tauri_app,
-                    "system",
-                    "Please enter your ElevenLabs Voice ID in Settings > API Keys.",
-                )
-                .await;
-                emit_state(tauri_app, crate::JarvisStateEnum::WakeListening).await;
-                continue;
+            let provider = app.pipeline.voice.provider(&config);
+            crate::trace::update(|t| t.voice = provider.name().to_string());
+            if provider.name() == "system" && config.tts_provider.as_deref() != Some("system") {
+                println!("[DEBUG] ElevenLabs not configured; using system voice");
             }
             emit_state(tauri_app, crate::JarvisStateEnum::Speaking).await;
-            let url = format!(
-                "https://api.elevenlabs.io/v1/text-to-speech/{}/stream?output_format=mp3_44100_128",
-                &config.voice_id
-            );
-            let resp = client_clone
-                .post(&url)
-                .header("xi-api-key", &config.elevenlabs_key)
-                .json(&serde_json::json!({
-                    "text": llm_answer,
-                    "model_id": String::from(elevenlabs_model),
-                }))
-                .send()
-                .await
-                .map_err(|e| anyhow!("TTS HTTP error: {}", e))?;
-
-            if !resp.status().is_success() {
-                let s = resp.status();
-                let b = resp.text().await.unwrap_or_default();
-                return Err(anyhow!("TTS API returned {}: {}", s, b));
-            }
 
-            let bytes = resp
-                .bytes()
-                .await
-                .map_err(|e| anyhow!("Error reading TTS body: {}", e))?;
-            let audio_bytes = bytes.to_vec();
-
-            // f) Play audio on a dedicated thread
-            println!("[DEBUG] Playing buffered audio...");
-            let tokio_handle = Handle::current();
-            let output_device_name = config.default_output_device_name.clone();
-            let join = thread::spawn(move || -> Result<(), anyhow::Error> {
-                tokio_handle.block_on(async {
-                    let cursor = std::io::Cursor::new(audio_bytes);
-                    let stream = if let Some(name) = output_device_name.as_deref() {
-                        let host = cpal::default_host();
-                        if let Ok(mut devs) = host.output_devices() {
-                            let name_lower = name.to_lowercase();
-                            if let Some(device) = devs.find(|d| d
-                                .name()
-                                .map(|n| n.to_lowercase().contains(&name_lower))
-                                .unwrap_or(false))
-                            {
-                                rodio::OutputStreamBuilder::from_device(device)?
-                                    .open_stream()
-                                    .map_err(|e| anyhow!("Audio init error: {}", e))?
-                            } else {
-                                rodio::OutputStreamBuilder::from_default_device()?
-                                    .open_stream()
-                                    .map_err(|e| anyhow!("Audio init error: {}", e))?
-                            }
-                        } else {
-                            rodio::OutputStreamBuilder::from_default_device()?
-                                .open_stream()
-                                .map_err(|e| anyhow!("Audio init error: {}", e))?
-                        }
-                    } else {
-                        rodio::OutputStreamBuilder::from_default_device()?
-                            .open_stream()
-                            .map_err(|e| anyhow!("Audio init error: {}", e))?
+            // Some(bytes) still needs playing; None means it was already spoken
+            let tts_audio = match provider.synthesize(&llm_answer).await {
+                Ok(crate::tts::Speech::Audio(bytes)) => {
+                    tokio::spawn(crate::tts::check_quota_after_synthesis(
+                        tauri_app.clone(),
+                        config.elevenlabs_key.clone(),
+                        config.tts_quota_warning.clone(),
+                    ));
+                    Some(bytes)
+                }
+                Ok(crate::tts::Speech::Spoken) => None,
+                Err(e) if crate::tts::is_refusal(&e) => {
+                    // ElevenLabs refused (key, rate limit, quota); speak with the system voice instead
+                    eprintln!("[WARN] {}; using system voice", e);
+                    let status = e
+                        .downcast_ref::<crate::tts::ProviderRefused>()
+                        .map(|r| r.status)
+                        .unwrap_or_default();
+                    let reason = match status {
+                        401 => "the API key was rejected",
+                        429 => "the rate limit or quota was reached",
+                        _ => "the character quota is used up",
                     };
-                    let sink = rodio::Sink::connect_new(&stream.mixer());
-                    let decoder =
-                        rodio::Decoder::new(cursor).map_err(|e| anyhow!("Decode error: {}", e))?;
-                    sink.append(decoder);
-                    sink.sleep_until_end();
-                    Ok(())
-                })
-            });
-
-            // 1) Catch thread panic or return
-            let thread_res = join.join().map_err(|_| anyhow!("Audio thread panicked"))?;
-            // 2) Propagate any playback error
-            thread_res?;
+                    emit_message(
+                        tauri_app,
+                        Role::System,
+                        &format!(
+                            "ElevenLabs couldn't speak this response because {}. Using the system voice instead.",
+                            reason
+                        ),
+                    )
+                    .await;
+                    if let Err(e) = crate::tts::SystemTts.synthesize(&llm_answer).await {
+                        eprintln!("[ERROR] System voice failed: {}", e);
+                    }
+                    None
+                }
+                Err(e) => return Err(anyhow!("TTS error: {}", e)),
+            };
+
+            // f) Play through the shared output; the loop keeps listening for interrupts meanwhile
+            if let Some(audio_bytes) = tts_audio {
+                println!("[DEBUG] Playing buffered audio...");
+                app.audio_output.play(audio_bytes)?;
+
+                // "stop" / "cancel" while speaking cuts the answer short
+                if config.interrupt.enabled
+                    && get_text::listen_for_interrupt(app, &config.interrupt, || {
+                        app.audio_output.is_idle()
+                    })
+                {
+                    app.audio_output.stop();
+                    events::emit(
+                        tauri_app,
+                        MessageMeta::new(Role::Assistant, assistant_created_at)
+                            .with("interrupted", true),
+                    );
+                }
+                app.audio_output.wait_until_idle();
+            }
 
             println!("[DEBUG] Finished speaking response");
             // Emit meta update with total latency (wake -> end of speech)
             let total_ms = perf_start.elapsed().as_millis() as u64;
-            let _ = tauri_app.emit(
-                "message-meta",
-                serde_json::json!({
-                    "createdAtOfAssistant": assistant_created_at,
-                    "meta": { "latencyMs": total_ms }
-                })
+            crate::stats::record_latency(total_ms);
+            events::emit(
+                tauri_app,
+                MessageMeta::new(Role::Assistant, assistant_created_at).with("latencyMs", total_ms),
             );
             emit_state(tauri_app, crate::JarvisStateEnum::WakeListening).await;
         }
@@ -0,0 +1,138 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Gemini safety thresholds, and turning a blocked answer into something the user
+// can see and hear instead of an empty reply.
+
+use google_ai_rs::{GenerateContentResponse, HarmBlockThreshold, HarmCategory, SafetySetting};
+use serde::{Deserialize, Serialize};
+
+// Per category: "default" (the model's own), "off", "block_none", "block_only_high",
+// "block_medium_and_above" or "block_low_and_above"
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SafetyConfig {
+    pub harassment: String,
+    pub hate_speech: String,
+    pub sexually_explicit: String,
+    pub dangerous_content: String,
+}
+
+impl Default for SafetyConfig {
+    fn default() -> Self {
+        Self {
+            harassment: "default".into(),
+            hate_speech: "default".into(),
+            sexually_explicit: "default".into(),
+            dangerous_content: "default".into(),
+        }
+    }
+}
+
+// An answer Gemini refused to give; shown and spoken as a system message
+#[derive(Debug)]
+pub struct Blocked {
+    // "harassment", "hate speech", ...; None when Gemini didn't say which
+    pub category: Option<String>,
+}
+
+impl std::fmt::Display for Blocked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.category {
+            Some(category) => write!(f, "Response blocked: {} threshold", category),
+            None => write!(f, "Response blocked by the safety filters"),
+        }
+    }
+}
+
+impl std::error::Error for Blocked {}
+
+impl Blocked {
+    // what Jarvis says instead of the answer
+    pub fn spoken(&self) -> String {
+        match &self.category {
+            Some(category) => format!("I can't answer that, it was blocked by the {} safety filter.", category),
+            None => "I can't answer that, it was blocked by the safety filters.".to_string(),
+        }
+    }
+}
+
+fn threshold(name: &str) -> Option<HarmBlockThreshold> {
+    match name.trim().to_lowercase().as_str() {
+        "off" => Some(HarmBlockThreshold::Off),
+        "block_none" | "none" => Some(HarmBlockThreshold::BlockNone),
+        "block_only_high" | "high" => Some(HarmBlockThreshold::BlockOnlyHigh),
+        "block_medium_and_above" | "medium" => Some(HarmBlockThreshold::BlockMediumAndAbove),
+        "block_low_and_above" | "low" => Some(HarmBlockThreshold::BlockLowAndAbove),
+        "" | "default" => None,
+        other => {
+            eprintln!("[WARN] Unknown safety threshold '{}', using the model default", other);
+            None
+        }
+    }
+}
+
+// Only categories that are not left at "default" are sent
+pub fn settings(config: &SafetyConfig) -> Vec<SafetySetting> {
+    [
+        (HarmCategory::Harassment, &config.harassment),
+        (HarmCategory::HateSpeech, &config.hate_speech),
+        (HarmCategory::SexuallyExplicit, &config.sexually_explicit),
+        (HarmCategory::DangerousContent, &config.dangerous_content),
+    ]
+    .into_iter()
+    .filter_map(|(category, name)| {
+        threshold(name).map(|t| SafetySetting {
+            category: category as i32,
+            threshold: t as i32,
+        })
+    })
+    .collect()
+}
+
+// "HARM_CATEGORY_HATE_SPEECH" -> "hate speech"
+fn category_label(category: HarmCategory) -> String {
+    category
+        .as_str_name()
+        .trim_start_matches("HARM_CATEGORY_")
+        .replace('_', " ")
+        .to_lowercase()
+}
+
+// Why a streamed chunk was blocked, if it was
+pub fn block_reason(chunk: &GenerateContentResponse) -> Option<Blocked> {
+    if let Some(feedback) = &chunk.prompt_feedback {
+        if feedback.block_reason != 0 {
+            let category = feedback
+                .safety_ratings
+                .iter()
+                .find(|r| r.blocked)
+                .map(|r| category_label(r.category()));
+            return Some(Blocked { category });
+        }
+    }
+    let candidate = chunk.candidates.first()?;
+    if candidate.finish_reason().as_str_name() != "SAFETY" {
+        return None;
+    }
+    let category = candidate
+        .safety_ratings
+        .iter()
+        .find(|r| r.blocked)
+        .map(|r| category_label(r.category()));
+    Some(Blocked { category })
+}
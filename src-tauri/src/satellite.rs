@@ -0,0 +1,283 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Satellite mode: a second Jarvis (e.g. a Raspberry Pi by the bed) only listens for
+// the wake word and records the command, then hands the audio to the main instance
+// over its local API. The main instance transcribes it, answers as if the command had
+// been typed there, and sends the speech back to be played on the satellite.
+//
+// On the socket, JSON text frames tagged by "type" carry the conversation; binary
+// frames carry the audio (16 kHz mono 16-bit little-endian PCM up, encoded speech down).
+
+use crate::events::{self, NewMessage, Role};
+use crate::models::AppContext;
+use crate::stt::SttWorker;
+use anyhow::{anyhow, Result};
+use axum::extract::ws::{Message, WebSocket};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{header::AUTHORIZATION, HeaderValue};
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+// half a second of audio per binary frame
+const CHUNK_SAMPLES: usize = 8000;
+// transcription, the LLM and TTS together
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(120);
+// a minute of audio; longer commands are dropped
+const MAX_COMMAND_SAMPLES: usize = 16_000 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SatelliteConfig {
+    pub enabled: bool,
+    // the main instance's satellite socket, e.g. ws://192.168.1.20:7381/satellite
+    pub main_url: String,
+    // the main instance's `local_api.token`
+    pub token: String,
+    // how the main instance refers to this device in its log
+    pub name: String,
+}
+
+impl Default for SatelliteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            main_url: String::new(),
+            token: String::new(),
+            name: "satellite".into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum Frame {
+    Hello { name: String },
+    // all audio of the command has been sent
+    End,
+    Transcript { text: String },
+    Answer { text: String, spoken: String },
+    // binary frames since `Answer` were the speech; with none the satellite speaks `spoken`
+    Done,
+    Error { message: String },
+}
+
+// What the main instance made of a command
+#[derive(Debug, Default)]
+pub struct Reply {
+    pub transcript: String,
+    pub answer: String,
+    pub spoken: String,
+    pub audio: Option<Vec<u8>>,
+}
+
+// Main instance side
+
+async fn send(socket: &mut WebSocket, frame: &Frame) -> Result<()> {
+    socket.send(Message::Text(serde_json::to_string(frame)?)).await?;
+    Ok(())
+}
+
+async fn answer(app: &tauri::AppHandle, socket: &mut WebSocket, audio: Vec<i16>) -> Result<()> {
+    let config = crate::cmd_load_config(app.clone()).map_err(|e| anyhow!(e))?;
+    let config = crate::to_run_config(config);
+    let stt = SttWorker::shared(app).await?;
+    let keep_going = AtomicBool::new(true);
+    let text = stt
+        .submit(crate::get_text::pad_for_whisper(&audio), &config.whisper_language)
+        .wait_while(&keep_going)
+        .await?
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    send(socket, &Frame::Transcript { text: text.clone() }).await?;
+
+    if text.is_empty() {
+        let sorry = "Sorry, I didn't catch that. Please repeat.".to_string();
+        send(socket, &Frame::Answer { text: sorry.clone(), spoken: sorry }).await?;
+        return send(socket, &Frame::Done).await;
+    }
+
+    // shows up in the main instance's chat like a typed message, but can't say yes to
+    // anything asked here or use this machine's clipboard and files
+    let reply = crate::send_text(app.clone(), text, false)
+        .await
+        .map_err(|e| anyhow!(e))?;
+    let spoken = crate::tts_normalize::normalize(
+        &crate::transform_text::speakable_summary(&reply, None),
+        &config.tts_normalization,
+    );
+    send(
+        socket,
+        &Frame::Answer {
+            text: reply,
+            spoken: spoken.clone(),
+        },
+    )
+    .await?;
+
+    // the system voice would talk here rather than on the satellite, so that's left to it
+    let provider = crate::tts::provider_for(&config);
+    if provider.name() != "system" && !spoken.trim().is_empty() {
+        match provider.synthesize(&spoken).await {
+            Ok(crate::tts::Speech::Audio(bytes)) => socket.send(Message::Binary(bytes)).await?,
+            Ok(crate::tts::Speech::Spoken) => {}
+            Err(e) => eprintln!("[WARN] Satellite speech failed, it will use its own voice: {}", e),
+        }
+    }
+    send(socket, &Frame::Done).await
+}
+
+// One satellite connection; it may send any number of commands
+pub async fn serve(app: tauri::AppHandle, mut socket: WebSocket) {
+    let mut name = String::from("satellite");
+    let mut samples: Vec<i16> = Vec::new();
+    let mut too_long = false;
+    while let Some(Ok(message)) = socket.recv().await {
+        match message {
+            Message::Binary(bytes) => {
+                if samples.len() + bytes.len() / 2 > MAX_COMMAND_SAMPLES {
+                    too_long = true;
+                    samples = Vec::new();
+                }
+                if !too_long {
+                    samples.extend(
+                        bytes
+                            .chunks_exact(2)
+                            .map(|b| i16::from_le_bytes([b[0], b[1]])),
+                    );
+                }
+            }
+            Message::Text(text) => match serde_json::from_str::<Frame>(&text) {
+                Ok(Frame::Hello { name: n }) => {
+                    println!("[INFO] Satellite '{}' connected", n);
+                    name = n;
+                }
+                Ok(Frame::End) if std::mem::take(&mut too_long) => {
+                    eprintln!("[WARN] Satellite '{}' sent a command that was too long", name);
+                    let message = "The command was too long".to_string();
+                    let _ = send(&mut socket, &Frame::Error { message }).await;
+                }
+                Ok(Frame::End) => {
+                    let audio = std::mem::take(&mut samples);
+                    if let Err(e) = answer(&app, &mut socket, audio).await {
+                        eprintln!("[WARN] Satellite '{}': {}", name, e);
+                        let message = e.to_string();
+                        let _ = send(&mut socket, &Frame::Error { message }).await;
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("[WARN] Satellite '{}' sent an unknown message: {}", name, e),
+            },
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+}
+
+// Satellite side
+
+async fn exchange(cfg: &SatelliteConfig, samples: &[i16]) -> Result<Reply> {
+    let mut request = cfg.main_url.trim().into_client_request()?;
+    if !cfg.token.trim().is_empty() {
+        let bearer = HeaderValue::from_str(&format!("Bearer {}", cfg.token.trim()))?;
+        request.headers_mut().insert(AUTHORIZATION, bearer);
+    }
+    let (mut socket, _) = tokio_tungstenite::connect_async(request).await?;
+
+    let hello = Frame::Hello {
+        name: cfg.name.clone(),
+    };
+    socket.send(WsMessage::Text(serde_json::to_string(&hello)?)).await?;
+    for chunk in samples.chunks(CHUNK_SAMPLES) {
+        let bytes = chunk.iter().flat_map(|s| s.to_le_bytes()).collect();
+        socket.send(WsMessage::Binary(bytes)).await?;
+    }
+    socket.send(WsMessage::Text(serde_json::to_string(&Frame::End)?)).await?;
+
+    let mut reply = Reply::default();
+    let mut audio = Vec::new();
+    while let Some(message) = socket.next().await {
+        match message? {
+            WsMessage::Binary(bytes) => audio.extend(bytes),
+            WsMessage::Text(text) => match serde_json::from_str::<Frame>(&text)? {
+                Frame::Transcript { text } => reply.transcript = text,
+                Frame::Answer { text, spoken } => {
+                    reply.answer = text;
+                    reply.spoken = spoken;
+                }
+                Frame::Error { message } => return Err(anyhow!("Main Jarvis: {}", message)),
+                Frame::Done => {
+                    reply.audio = (!audio.is_empty()).then_some(audio);
+                    let _ = socket.close(None).await;
+                    return Ok(reply);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    Err(anyhow!("The main Jarvis closed the connection"))
+}
+
+pub async fn ask_main(cfg: &SatelliteConfig, samples: &[i16]) -> Result<Reply> {
+    if cfg.main_url.trim().is_empty() {
+        return Err(anyhow!("Set satellite.main_url to the main Jarvis first"));
+    }
+    tokio::time::timeout(ANSWER_TIMEOUT, exchange(cfg, samples))
+        .await
+        .map_err(|_| anyhow!("The main Jarvis did not answer in time"))?
+}
+
+// Voice-loop step on a satellite: send the recorded command, show and play the answer
+pub async fn respond(app: &AppContext, tauri_app: &tauri::AppHandle, samples: &[i16]) {
+    let config = &app.config;
+    let reply = match ask_main(&config.satellite, samples).await {
+        Ok(reply) => reply,
+        Err(e) => {
+            eprintln!("[WARN] Satellite request failed: {}", e);
+            events::emit(
+                tauri_app,
+                NewMessage::new(Role::System, format!("Couldn't reach the main Jarvis: {}", e)),
+            );
+            let notice = "I can't reach the main Jarvis right now.";
+            if let Err(e) = crate::tts::speak_with(config, notice).await {
+                eprintln!("[ERROR] Failed to speak notice: {}", e);
+            }
+            return;
+        }
+    };
+    crate::overlay::emit_transcript(tauri_app, &reply.transcript, true);
+    events::emit(tauri_app, NewMessage::new(Role::User, &reply.transcript));
+    events::emit(tauri_app, NewMessage::new(Role::Assistant, &reply.answer));
+
+    crate::run_jarvis::emit_state(tauri_app, crate::JarvisStateEnum::Speaking).await;
+    match reply.audio {
+        Some(bytes) => match app.audio_output.play(bytes) {
+            Ok(()) => app.audio_output.wait_until_idle(),
+            Err(e) => eprintln!("[ERROR] Failed to play the main Jarvis's answer: {}", e),
+        },
+        None => {
+            if let Err(e) = crate::tts::speak_with(config, &reply.spoken).await {
+                eprintln!("[ERROR] Failed to speak the main Jarvis's answer: {}", e);
+            }
+        }
+    }
+}
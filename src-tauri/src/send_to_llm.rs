@@ -15,15 +15,27 @@ You should have received a copy of the GNU Affero General Public License
 along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use crate::context_budget;
+use crate::events::{self, NewMessage, Role};
 use crate::models::Config;
+use crate::pdf;
+use crate::readability;
+use crate::redact::{self, Redactions};
+use crate::safety;
 use crate::transform_text::extract_image_parts;
+use crate::web_fetch;
+use crate::youtube;
 use anyhow::{anyhow, Result};
-use google_ai_rs::{Auth, Client, Part};
+use futures_util::future::BoxFuture;
+use google_ai_rs::{Auth, Client, GenerationConfig, GenerativeModel, Part};
 use regex::Regex;
-// no serde_json imports needed in this module now
+use serde::{Deserialize, Serialize};
 use std::io::{stdout, Write};
 use url::Url;
 
+// page text beyond this is cut off
+const MAX_PAGE_CHARS: usize = 10_000;
+
 fn is_image_content_type(ct: &str) -> bool {
     let ct = ct.to_lowercase();
     ct.starts_with("image/")
@@ -38,11 +50,31 @@ fn is_image_url_by_ext(url: &str) -> bool {
         || url_lc.ends_with(".webp")
 }
 
-fn strip_html(input: &str) -> String {
-    let re = Regex::new(r"<[^>]+>").unwrap();
-    let no_tags = re.replace_all(input, " ");
-    let collapsed = Regex::new(r"\s+").unwrap().replace_all(&no_tags, " ");
-    collapsed.trim().to_string()
+// page text for the LLM; HTML goes through reader-view extraction
+fn page_text(url: &str, content_type: Option<&str>, body: &str) -> String {
+    let is_html = content_type
+        .map(|ct| ct.to_lowercase().contains("html"))
+        .unwrap_or_else(|| body.trim_start().starts_with('<'));
+    if !is_html {
+        return format!("Web content from {}:\n{}", url, truncate_chars(body.trim(), MAX_PAGE_CHARS));
+    }
+    let article = readability::extract(body);
+    let mut out = format!("Web page from {}", url);
+    if let Some(title) = &article.title {
+        out.push_str(&format!("\nTitle: {}", title));
+    }
+    if let Some(byline) = &article.byline {
+        out.push_str(&format!("\nBy: {}", byline));
+    }
+    out.push_str(&format!("\n\n{}", truncate_chars(&article.text, MAX_PAGE_CHARS)));
+    out
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text.to_string(),
+    }
 }
 
 async fn resolve_image_url(raw: &str) -> Option<String> {
@@ -69,7 +101,13 @@ async fn resolve_image_url(raw: &str) -> Option<String> {
     Some(raw.to_string())
 }
 
-async fn build_parts_with_media(system_prompt: &str, prompt: &str) -> Result<Vec<Part>> {
+// Text read from PDFs, pages and videos is redacted into `redactions` like the prompt
+async fn build_parts_with_media(
+    system_prompt: &str,
+    prompt: &str,
+    config: &Config,
+    redactions: &mut Redactions,
+) -> Result<Vec<Part>> {
     let mut parts: Vec<Part> = Vec::new();
     parts.push(Part::text(system_prompt));
 
@@ -89,9 +127,38 @@ async fn build_parts_with_media(system_prompt: &str, prompt: &str) -> Result<Vec
         }
     }
 
-    // 2) Remote URLs: try to attach images or page text
+    // 2) PDFs named by path
+    if config.pdf.enabled && config.pdf.local_files {
+        for path in pdf::local_paths(prompt) {
+            let source = path.to_string_lossy().to_string();
+            let too_big = std::fs::metadata(&path)
+                .map(|m| m.len() > config.web_fetch.max_bytes)
+                .unwrap_or(true);
+            if too_big {
+                eprintln!("[WARN] Skipping {}: larger than {} bytes", source, config.web_fetch.max_bytes);
+                continue;
+            }
+            let result = match tokio::fs::read(&path).await {
+                Ok(bytes) => pdf::text_part(&source, bytes, config.pdf.max_pages).await,
+                Err(e) => Err(e.into()),
+            };
+            match result {
+                Ok(text) => {
+                    crate::trace::tool(&format!("read PDF {}", source));
+                    parts.push(Part::text(&redact::redact(&text, &config.redaction, redactions)))
+                }
+                Err(e) => eprintln!("[WARN] Failed to read PDF {}: {}", source, e),
+            }
+        }
+    }
+
+    // 3) Remote URLs: try to attach images, PDFs or page text
+    let fetch_config = &config.web_fetch;
+    if !fetch_config.enabled {
+        return Ok(parts);
+    }
     let url_re = Regex::new(r"https?://[^\s)]+").unwrap();
-    let client = reqwest::Client::new();
+    let client = web_fetch::client(fetch_config)?;
 
     for m in url_re.find_iter(prompt) {
         let raw_url = m.as_str();
@@ -100,121 +167,472 @@ async fn build_parts_with_media(system_prompt: &str, prompt: &str) -> Result<Vec
             .await
             .unwrap_or_else(|| raw_url.to_string());
 
-        // Fetch HEAD/GET to decide type
-        let resp = match client.get(&target_url).send().await {
-            Ok(r) => r,
-            Err(_) => continue,
-        };
-        if !resp.status().is_success() {
+        // YouTube: captions and video details instead of the watch page
+        if let Some(id) = youtube::video_id(&target_url) {
+            match youtube::video_text(&client, fetch_config, &id, &config.whisper_language).await {
+                Ok(text) => {
+                    crate::trace::tool(&format!("read captions of YouTube video {}", id));
+                    parts.push(Part::text(&redact::redact(&text, &config.redaction, redactions)))
+                }
+                Err(e) => eprintln!("[WARN] Failed to read YouTube video {}: {}", id, e),
+            }
             continue;
         }
 
+        let fetched = match web_fetch::fetch(&client, fetch_config, &target_url).await {
+            Ok(f) => f,
+            Err(e) => {
+                eprintln!("[WARN] Skipping {}: {}", target_url, e);
+                crate::trace::tool(&format!("failed to fetch {}: {}", target_url, e));
+                continue;
+            }
+        };
+        crate::trace::tool(&format!("fetched {}", fetched.url));
+
         // Prefer content-type header to detect images
-        let ct_header = resp
-            .headers()
-            .get(reqwest::header::CONTENT_TYPE)
-            .and_then(|v| v.to_str().ok())
-            .map(|s| s.to_string());
-
-        if let Some(ct) = ct_header {
-            if is_image_content_type(&ct) {
-                if let Ok(bytes) = resp.bytes().await {
-                    parts.push(Part::blob(&ct, bytes.to_vec()));
-                }
+        if let Some(ct) = fetched.content_type.as_deref() {
+            if is_image_content_type(ct) {
+                parts.push(Part::blob(ct, fetched.bytes));
                 continue;
             }
         }
 
-        // If no CT header or not image, but URL looks like image by extension, try as image
-        if is_image_url_by_ext(&target_url) {
-            if let Ok(bytes) = resp.bytes().await {
-                // Guess type from extension
-                let guessed = if target_url.ends_with(".png") {
-                    "image/png"
-                } else if target_url.ends_with(".jpg") || target_url.ends_with(".jpeg") {
-                    "image/jpeg"
-                } else if target_url.ends_with(".gif") {
-                    "image/gif"
-                } else if target_url.ends_with(".webp") {
-                    "image/webp"
-                } else {
-                    "application/octet-stream"
-                };
-                parts.push(Part::blob(guessed, bytes.to_vec()));
+        if pdf::is_pdf(fetched.content_type.as_deref(), &fetched.url, &fetched.bytes) {
+            if !config.pdf.enabled {
+                continue;
+            }
+            match pdf::text_part(&fetched.url, fetched.bytes, config.pdf.max_pages).await {
+                Ok(text) => {
+                    parts.push(Part::text(&redact::redact(&text, &config.redaction, redactions)))
+                }
+                Err(e) => eprintln!("[WARN] Failed to read PDF {}: {}", fetched.url, e),
             }
             continue;
         }
 
-        // Treat as web page text
-        if let Ok(text) = resp.text().await {
-            let stripped = strip_html(&text);
-            let snippet = if stripped.len() > 10_000 {
-                format!("{}…", &stripped[..10_000])
+        // If no CT header or not image, but URL looks like image by extension, try as image
+        if is_image_url_by_ext(&target_url) {
+            // Guess type from extension
+            let guessed = if target_url.ends_with(".png") {
+                "image/png"
+            } else if target_url.ends_with(".jpg") || target_url.ends_with(".jpeg") {
+                "image/jpeg"
+            } else if target_url.ends_with(".gif") {
+                "image/gif"
+            } else if target_url.ends_with(".webp") {
+                "image/webp"
             } else {
-                stripped
+                "application/octet-stream"
             };
-            parts.push(Part::text(&format!(
-                "Web content from {}:\n{}",
-                target_url, snippet
-            )));
+            parts.push(Part::blob(guessed, fetched.bytes));
+            continue;
         }
+
+        // Treat as web page text
+        let body = String::from_utf8_lossy(&fetched.bytes);
+        let text = page_text(&fetched.url, fetched.content_type.as_deref(), &body);
+        parts.push(Part::text(&redact::redact(&text, &config.redaction, redactions)));
     }
 
     Ok(parts)
 }
 
-// sends a prompt to the Gemini API and returns the response. ctx_text is the
-// conversation context derived from the selected chat history.
-pub async fn query_gemini(prompt: &str, config: &Config, ctx_text: &str) -> Result<String> {
-    println!("[DEBUG] Entered query_gemini with prompt: {}", prompt);
-    let system_prompt = format!("{}{}", config.llm_system_prompt, ctx_text);
-    let client = Client::new(Auth::ApiKey(config.gemini_key.to_string()))
-        .await
-        .map_err(|e| anyhow!("Failed to initialize Gemini client: {e}"))?;
-    let model = client.generative_model(&config.gemini_model);
+// Sampling and length limits for chat answers; unset values keep the model defaults
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct GenerationSettings {
+    // 0.0 - 2.0, higher is more creative
+    pub temperature: Option<f32>,
+    // 0.0 - 1.0
+    pub top_p: Option<f32>,
+    pub max_output_tokens: Option<i32>,
+    // used instead of max_output_tokens for spoken answers, which get long to listen to
+    pub voice_max_output_tokens: Option<i32>,
+}
 
-    println!("[DEBUG] Gemini client and model initialized");
-    println!("[DEBUG] Starting streaming response");
+impl GenerationSettings {
+    // per-conversation values win over the global ones
+    pub fn overridden_by(mut self, other: &GenerationSettings) -> Self {
+        self.temperature = other.temperature.or(self.temperature);
+        self.top_p = other.top_p.or(self.top_p);
+        self.max_output_tokens = other.max_output_tokens.or(self.max_output_tokens);
+        self.voice_max_output_tokens = other.voice_max_output_tokens.or(self.voice_max_output_tokens);
+        self
+    }
 
-    let parts = build_parts_with_media(&system_prompt, prompt).await?;
-    let mut stream = model.stream_generate_content(parts).await?;
+    // the limits to use for an answer that will be spoken
+    pub fn for_voice(mut self) -> Self {
+        self.max_output_tokens = self.voice_max_output_tokens.or(self.max_output_tokens);
+        self
+    }
+}
+
+// applies the generation parameters and safety thresholds from config on top of the model defaults
+fn configure_model<'c>(model: GenerativeModel<'c>, config: &Config, json: bool) -> GenerativeModel<'c> {
+    let safety = safety::settings(&config.safety);
+    let model = if safety.is_empty() {
+        model
+    } else {
+        model.with_safety_settings(safety)
+    };
+    let g = &config.generation;
+    if g.temperature.is_none() && g.top_p.is_none() && g.max_output_tokens.is_none() && !json {
+        return model;
+    }
+    model.with_generation_config(GenerationConfig {
+        temperature: g.temperature.map(|t| t.clamp(0.0, 2.0)),
+        top_p: g.top_p.map(|p| p.clamp(0.0, 1.0)),
+        max_output_tokens: g.max_output_tokens.map(|n| n.max(1)),
+        response_mime_type: if json { "application/json".to_string() } else { String::new() },
+        ..Default::default()
+    })
+}
+
+// added on the retry after an empty answer
+const RETRY_NOTE: &str = "Please answer the request above. If part of it can't be answered, answer the rest and briefly say what you left out.";
+
+// Gemini ended the stream without any text, even after a retry
+#[derive(Debug)]
+pub struct EmptyResponse {
+    pub stream_error: Option<String>,
+}
+
+impl std::fmt::Display for EmptyResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.stream_error {
+            Some(e) => write!(f, "No answer from Gemini (the response stream failed: {})", e),
+            None => write!(f, "No answer from Gemini (the response was empty)"),
+        }
+    }
+}
+
+impl std::error::Error for EmptyResponse {}
+
+// A chat answer that couldn't be given, explained in the chat and out loud
+pub struct Unanswered {
+    pub message: String,
+    pub spoken: String,
+    // "blocked" or "empty", stored in the message meta
+    pub kind: &'static str,
+}
 
-    let mut full_response = String::new();
+impl Unanswered {
+    // Some for blocked and empty answers; other errors are real failures
+    pub fn from_error(e: &anyhow::Error) -> Option<Self> {
+        if let Some(blocked) = e.downcast_ref::<safety::Blocked>() {
+            return Some(Self {
+                message: blocked.to_string(),
+                spoken: blocked.spoken(),
+                kind: "blocked",
+            });
+        }
+        e.downcast_ref::<EmptyResponse>().map(|empty| Self {
+            message: empty.to_string(),
+            spoken: "Sorry, I didn't get an answer to that. Please try again or rephrase it.".to_string(),
+            kind: "empty",
+        })
+    }
+
+    pub fn emit(&self, app: &tauri::AppHandle) {
+        events::emit(
+            app,
+            NewMessage::new(Role::System, self.message.as_str()).with("unanswered", self.kind),
+        );
+    }
+}
+
+struct Streamed {
+    text: String,
+    blocked: Option<safety::Blocked>,
+    // a chunk failed mid-stream; what arrived before it is kept
+    error: Option<String>,
+}
+
+async fn stream_answer(model: &GenerativeModel<'_>, parts: Vec<Part>) -> Result<Streamed> {
+    let mut stream = model.stream_generate_content(parts).await?;
+    let mut streamed = Streamed {
+        text: String::new(),
+        blocked: None,
+        error: None,
+    };
     loop {
         match stream.next().await {
             Ok(Some(chunk)) => {
+                if let Some(reason) = safety::block_reason(&chunk) {
+                    streamed.blocked = Some(reason);
+                }
                 let text = chunk.text();
                 if !text.is_empty() {
                     print!("{}", text);
                     stdout().flush()?;
-                    full_response.push_str(&text);
+                    streamed.text.push_str(&text);
                 }
             }
             Ok(None) => break,
             Err(e) => {
                 eprintln!("[ERROR] streaming chunk error: {:?}", e);
+                streamed.error = Some(e.to_string());
                 break;
             }
         }
     }
     println!("\n[DEBUG] Streaming complete");
+    Ok(streamed)
+}
+
+// sends a prompt to the Gemini API and returns the response. ctx_text is the
+// conversation context derived from the selected chat history.
+pub async fn query_gemini(prompt: &str, config: &Config, ctx_text: &str) -> Result<String> {
+    let (answer, redactions) = query(prompt, config, ctx_text, false, true).await?;
+    Ok(redact::restore(&answer, &redactions))
+}
+
+// Like query_gemini, but only the text goes out: nothing named in the prompt (local
+// PDFs, links, pasted images) is read or attached. For prompts from other programs.
+pub async fn query_gemini_text(prompt: &str, config: &Config, ctx_text: &str) -> Result<String> {
+    let (answer, redactions) = query(prompt, config, ctx_text, false, false).await?;
+    Ok(redact::restore(&answer, &redactions))
+}
+
+// asked for at the end of the system prompt when `dual_response` is on
+const DUAL_INSTRUCTION: &str = "\n\nReply with a JSON object with two string fields. \"spoken\": one to three short sentences that answer the question and will be read aloud, in plain language without markdown, lists, links or code. \"display\": the complete answer for the screen, using markdown where it helps.";
+
+// A short answer for the voice and a full one for the chat window
+#[derive(Deserialize, Debug, Clone)]
+pub struct DualAnswer {
+    pub spoken: String,
+    pub display: String,
+}
+
+// Reads the JSON answer; anything else is used for both parts
+pub fn parse_dual(text: &str) -> DualAnswer {
+    let trimmed = text.trim();
+    let json = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|t| t.strip_suffix("```"))
+        .unwrap_or(trimmed)
+        .trim();
+    match serde_json::from_str::<DualAnswer>(json) {
+        Ok(answer) if !answer.display.trim().is_empty() => DualAnswer {
+            spoken: if answer.spoken.trim().is_empty() {
+                answer.display.clone()
+            } else {
+                answer.spoken
+            },
+            display: answer.display,
+        },
+        _ => DualAnswer {
+            spoken: text.to_string(),
+            display: text.to_string(),
+        },
+    }
+}
+
+// Like query_gemini, but asks for separate spoken and display answers. The JSON is read
+// before the redacted values go back in, since they could contain quotes or backslashes.
+pub async fn query_gemini_dual(prompt: &str, config: &Config, ctx_text: &str) -> Result<DualAnswer> {
+    let (answer, redactions) = query(prompt, config, ctx_text, true, true).await?;
+    let answer = parse_dual(&answer);
+    Ok(DualAnswer {
+        spoken: redact::restore(&answer.spoken, &redactions),
+        display: redact::restore(&answer.display, &redactions),
+    })
+}
+
+// Returns the answer as it came back, still holding the placeholders, and the values
+// they stand for
+async fn query(
+    prompt: &str,
+    config: &Config,
+    ctx_text: &str,
+    dual: bool,
+    media: bool,
+) -> Result<(String, Redactions)> {
+    // Scrub personal data before anything leaves the machine
+    let mut redactions = Redactions::default();
+    let prompt = redact::redact(prompt, &config.redaction, &mut redactions);
+    let ctx_text = redact::redact(ctx_text, &config.redaction, &mut redactions);
+    // the expanded system prompt can hold the user's name, location and other variables
+    let llm_system_prompt = redact::redact(
+        &crate::prompt_vars::expand(&config.llm_system_prompt, config),
+        &config.redaction,
+        &mut redactions,
+    );
+    let (ctx_text, prompt) = context_budget::fit(config, &llm_system_prompt, &ctx_text, &prompt);
+    crate::trace::update(|t| {
+        t.provider = "gemini".into();
+        t.model = config.gemini_model.clone();
+        t.prompt = prompt.clone();
+        t.context = ctx_text.clone();
+    });
+    let prompt = prompt.as_str();
+
+    println!("[DEBUG] Entered query_gemini with prompt: {}", prompt);
+    let mut system_prompt = format!("{}{}", llm_system_prompt, ctx_text);
+    if dual {
+        system_prompt.push_str(DUAL_INSTRUCTION);
+    }
+    let client = Client::new(Auth::ApiKey(config.gemini_key.to_string()))
+        .await
+        .map_err(|e| anyhow!("Failed to initialize Gemini client: {e}"))?;
+    let model = configure_model(client.generative_model(&config.gemini_model), config, dual);
+
+    println!("[DEBUG] Gemini client and model initialized");
+    println!("[DEBUG] Starting streaming response");
+
+    let parts = if media {
+        build_parts_with_media(&system_prompt, prompt, config, &mut redactions).await?
+    } else {
+        vec![Part::text(&system_prompt), Part::text(prompt)]
+    };
+    if !redactions.is_empty() {
+        println!("[DEBUG] Redacted {} sensitive value(s) from prompt", redactions.len());
+    }
+    crate::trace::update(|t| t.redactions = redactions.len());
+    let mut streamed = stream_answer(&model, parts.clone()).await?;
+    if streamed.text.trim().is_empty() {
+        // one more try, asking for whatever part of the request can be answered
+        eprintln!("[WARN] Empty response from Gemini, retrying once");
+        crate::trace::update(|t| t.retried = true);
+        let mut retry_parts = parts;
+        retry_parts.push(Part::text(RETRY_NOTE));
+        streamed = stream_answer(&model, retry_parts).await?;
+    }
+    let full_response = streamed.text;
+    if let Some(blocked) = streamed.blocked {
+        eprintln!("[WARN] {}", blocked);
+        // a cut-off answer is still worth showing; nothing at all gets explained
+        if full_response.trim().is_empty() {
+            return Err(blocked.into());
+        }
+    }
+    if full_response.trim().is_empty() {
+        return Err(EmptyResponse {
+            stream_error: streamed.error,
+        }
+        .into());
+    }
     println!(
         "\n--- LLM RESPONSE ---\n{}\n-----------------------\n",
         full_response
     );
+    crate::stats::record_llm(
+        system_prompt.chars().count() + prompt.chars().count(),
+        full_response.chars().count(),
+    );
     println!("[DEBUG] Returning Gemini response");
-    Ok(full_response)
+    Ok((full_response, redactions))
 }
 
-// Generate a short conversation title using Gemini Flash Lite model
-pub async fn generate_conversation_title(seed_text: &str, config: &Config) -> Result<String> {
-    let client = Client::new(Auth::ApiKey(config.gemini_key.to_string()))
-        .await
-        .map_err(|e| anyhow!("Failed to initialize Gemini client: {e}"))?;
+pub const DEFAULT_UTILITY_MODEL: &str = "gemini-2.0-flash-lite";
+
+// Backend for short one-shot jobs (titles, yes/no checks, fact extraction, notes) that
+// don't need the chat model
+pub trait UtilityProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn complete<'a>(&'a self, prompt: &'a str) -> BoxFuture<'a, Result<String>>;
+}
+
+pub struct GeminiUtility {
+    pub api_key: String,
+    pub model: String,
+}
+
+impl UtilityProvider for GeminiUtility {
+    fn name(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn complete<'a>(&'a self, prompt: &'a str) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            let client = Client::new(Auth::ApiKey(self.api_key.clone()))
+                .await
+                .map_err(|e| anyhow!("Failed to initialize Gemini client: {e}"))?;
+            let model = client.generative_model(&self.model);
+            let mut stream = model
+                .stream_generate_content(vec![Part::text(prompt)])
+                .await?;
+            let mut full = String::new();
+            while let Ok(Some(chunk)) = stream.next().await {
+                full.push_str(&chunk.text());
+            }
+            Ok(full.trim().to_string())
+        })
+    }
+}
+
+// No cloud key (or "offline" chosen): callers fall back to their rule-based answer
+pub struct OfflineUtility;
 
-    // Prefer a lightweight fast model for title generation
-    let model = client.generative_model("gemini-2.0-flash-lite");
+impl UtilityProvider for OfflineUtility {
+    fn name(&self) -> &'static str {
+        "offline"
+    }
+
+    fn complete<'a>(&'a self, _prompt: &'a str) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async { Err(anyhow!("No utility model configured")) })
+    }
+}
 
+// `utility_model` names a Gemini model; "offline" or a missing key disables cloud calls
+pub fn utility_provider_for(config: &Config) -> Box<dyn UtilityProvider> {
+    let model = config
+        .utility_model
+        .as_deref()
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .unwrap_or(DEFAULT_UTILITY_MODEL);
+    if model.eq_ignore_ascii_case("offline") || config.gemini_key.trim().is_empty() {
+        return Box::new(OfflineUtility);
+    }
+    Box::new(GeminiUtility {
+        api_key: config.gemini_key.clone(),
+        model: model.to_string(),
+    })
+}
+
+// Every utility prompt goes out through here so personal data is redacted on the way out
+// and put back into the answer
+pub async fn complete_redacted(prompt: &str, config: &Config) -> Result<String> {
+    let mut redactions = Redactions::default();
+    let prompt = redact::redact(prompt, &config.redaction, &mut redactions);
+    let full = utility_provider_for(config).complete(&prompt).await?;
+    Ok(redact::restore(&full, &redactions))
+}
+
+const MAX_FALLBACK_TITLE_WORDS: usize = 6;
+
+// First sentence of the snippet, cut to a few words; good enough to tell chats apart
+pub fn fallback_title(seed_text: &str) -> String {
+    let sentence = seed_text
+        .split(['.', '!', '?', '\n'])
+        .map(str::trim)
+        .find(|s| !s.is_empty())
+        .unwrap_or("");
+    let words: Vec<&str> = sentence.split_whitespace().collect();
+    let mut title = words
+        .iter()
+        .take(MAX_FALLBACK_TITLE_WORDS)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if words.len() > MAX_FALLBACK_TITLE_WORDS {
+        title.push_str("...");
+    }
+    // "New Conversation" is reserved for chats that still need a title
+    if title.is_empty() || title.eq_ignore_ascii_case("new conversation") {
+        return "Untitled chat".to_string();
+    }
+    let mut chars = title.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => "Untitled chat".to_string(),
+    }
+}
+
+// Short conversation title from the utility model; never fails, so naming never blocks on the network
+pub async fn generate_conversation_title(seed_text: &str, config: &Config) -> Result<String> {
     let prompt = format!(
         "You are to generate a concise, descriptive chat title (3-6 words) based on the given conversation snippet.\n\
 Do not include quotes or punctuation at the end.\n\
@@ -222,15 +640,125 @@ Return only the title text.\n\
 Snippet:\n{}",
         seed_text
     );
-
-    let parts = vec![Part::text(&prompt)];
-    let mut stream = model.stream_generate_content(parts).await?;
-    let mut full = String::new();
-    while let Ok(Some(chunk)) = stream.next().await {
-        let t = chunk.text();
-        if !t.is_empty() {
-            full.push_str(&t);
+    match complete_redacted(&prompt, config).await {
+        Ok(title) if !title.trim().is_empty() => Ok(title.trim().to_string()),
+        Ok(_) => Ok(fallback_title(seed_text)),
+        Err(e) => {
+            if utility_provider_for(config).name() != "offline" {
+                eprintln!("[WARN] Title generation failed, using first sentence: {}", e);
+            }
+            Ok(fallback_title(seed_text))
         }
     }
-    Ok(full.trim().to_string())
+}
+
+// Pulls durable personal facts about the user out of a single message. Returns short
+// third-person statements, or nothing when the message has none worth remembering.
+pub async fn extract_user_facts(
+    user_text: &str,
+    known: &[String],
+    config: &Config,
+) -> Result<Vec<String>> {
+    let known_list = if known.is_empty() {
+        "(none)".to_string()
+    } else {
+        known.join("\n")
+    };
+    let prompt = format!(
+        "Extract lasting personal facts the user states about themselves or people close to them \
+(names, relationships, allergies, preferences, where they live or work).\n\
+Ignore questions, requests, temporary states and anything already known.\n\
+Return a JSON array of short statements like \"The user's wife is named Anna\", or [] if there are none.\n\
+Return only the JSON.\n\
+Already known:\n{}\n\
+Message:\n{}",
+        known_list, user_text
+    );
+
+    let full = complete_redacted(&prompt, config).await?;
+
+    // models like to wrap JSON in a code fence
+    let json = full
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+    let facts: Vec<String> = serde_json::from_str(json).unwrap_or_default();
+    Ok(facts
+        .into_iter()
+        .map(|f| f.trim().to_string())
+        .filter(|f| !f.is_empty())
+        .collect())
+}
+
+// Yes/no check used for low-confidence command matches: does `prompt` ask to `action`?
+pub async fn confirm_intent(prompt: &str, action: &str, config: &Config) -> Result<bool> {
+    let question = format!(
+        "Is the following message a direct request to {}? Answer only yes or no.\n\nMessage:\n{}",
+        action, prompt
+    );
+    let full = complete_redacted(&question, config).await?;
+    Ok(full.to_lowercase().starts_with("yes"))
+}
+
+// Meeting notes: key points, decisions and action items from a timestamped transcript
+pub async fn summarize_transcript(transcript: &str, config: &Config) -> Result<String> {
+    let prompt = format!(
+        "Summarize this meeting transcript as short notes: the main points, any decisions, and \
+action items with who owns them if that was said.\n\
+Use plain sentences or short dash bullets, no headings. Keep it under 150 words.\n\
+Transcript:\n{}",
+        transcript
+    );
+    complete_redacted(&prompt, config).await
+}
+
+// Terminal tool: one command line for the request, using only `allowed` programs; None
+// when the model says it can't be done that way
+pub async fn propose_command(
+    request: &str,
+    allowed: &[String],
+    config: &Config,
+) -> Result<Option<String>> {
+    let prompt = format!(
+        "Turn this request into a single command line for {}.\n\
+It is run directly, not through a shell: no pipes, redirection, variables, chaining, \
+or shell built-ins like dir, cd or echo.\n\
+The program must be one of: {}.\n\
+Return only the command line, or NONE if the request can't be done that way.\n\
+Request:\n{}",
+        std::env::consts::OS,
+        allowed.join(", "),
+        request
+    );
+    let full = complete_redacted(&prompt, config).await?;
+    // models like to wrap commands in a code fence
+    let line = full
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with("```"))
+        .unwrap_or("")
+        .trim_matches('`')
+        .trim();
+    if line.is_empty() || line.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    Ok(Some(line.to_string()))
+}
+
+// Terminal tool: what the output of `command` says, to be read aloud
+pub async fn summarize_command_output(
+    command: &str,
+    output: &str,
+    config: &Config,
+) -> Result<String> {
+    let prompt = format!(
+        "Summarize what this command's output says for someone listening, in one to three short \
+sentences of plain language. Say so if the command failed.\n\
+Command: {}\n\
+Output:\n{}",
+        command, output
+    );
+    complete_redacted(&prompt, config).await
 }
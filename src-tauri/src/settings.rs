@@ -0,0 +1,311 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::{Config, JarvisState};
+use serde_json::{Map, Value};
+use tauri::Manager;
+
+const SECRET_KEYS: &[&str] = &["porcupine_key", "gemini_key", "elevenlabs_key"];
+
+// settings that hold passwords or tokens; exported and imported together with the API keys
+const CREDENTIAL_KEYS: &[&str] = &[
+    "sync", "local_api", "satellite", "finance", "sports", "packages", "commute", "github",
+    // private iCal URLs give read access to the calendar
+    "briefing",
+];
+// tied to this machine (its network setup, the phone paired for presence), never synced
+const NETWORK_KEYS: &[&str] = &["wyoming", "presence"];
+
+// settings support bundles keep as they are: model names, modes and tuning. Every other
+// string in a bundle only says whether it is set
+const SUPPORT_KEYS: &[&str] = &[
+    "vad_mode",
+    "endpointing",
+    "wake",
+    "interrupt",
+    "whisper_language",
+    "gemini_model",
+    "utility_model",
+    "elevenlabs_model",
+    "tts_provider",
+    "image_generation",
+    "generation",
+    "safety",
+    "tts_normalization",
+    "history_format",
+    "context_budget",
+    "thread_priority",
+    "dock_position",
+    "input_mode",
+    "theme",
+];
+
+// Config keys grouped the way the settings pane shows them
+const SECTIONS: &[(&str, &[&str])] = &[
+    ("api_keys", &["porcupine_key", "gemini_key", "elevenlabs_key"]),
+    (
+        "audio",
+        &[
+            "default_microphone_index",
+            "default_microphone_name",
+            "default_output_device_name",
+            "tts_output_device_name",
+            "output_volume",
+            "vad_mode",
+            "wwd_sensitivity",
+            "frame_duration_ms",
+            "silence_threshold_ms",
+            "speech_trigger_frames",
+            "frame_length_wwd",
+            "endpointing",
+            "wake",
+            "interrupt",
+            "input_source",
+        ],
+    ),
+    (
+        "models",
+        &[
+            "whisper_language",
+            "gemini_model",
+            "utility_model",
+            "elevenlabs_model",
+            "voice_id",
+            "tts_provider",
+            "tts_quota_warning",
+            "image_generation",
+            "generation",
+            "safety",
+            "tts_normalization",
+        ],
+    ),
+    (
+        "assistant",
+        &[
+            "llm_system_prompt",
+            "context_window_expiration_seconds",
+            "history_format",
+            "memory",
+            "command_feedback",
+            "meeting",
+            "hotkeys",
+            "quick_ask",
+            "context_budget",
+            "dual_response",
+            "copy_blocks",
+            "app_triggers",
+            "game_mode",
+            "prompt_variables",
+            "world_clock",
+            "finance",
+            "sports",
+            "wikipedia",
+            "packages",
+            "commute",
+            "cooking",
+            "ambient",
+            "briefing",
+            "voice_memos",
+            "pomodoro",
+            "presence",
+            "network",
+            "processes",
+            "terminal",
+            "github",
+            "docker",
+            "kubernetes",
+        ],
+    ),
+    ("appearance", &["dock_position", "input_mode", "theme"]),
+    ("privacy", &["redaction", "debug_capture", "crash_reporting", "web_fetch", "pdf", "sync"]),
+    ("network", &["local_api", "satellite", "wyoming"]),
+    ("advanced", &["thread_priority"]),
+    ("personas", &["personas", "active_persona"]),
+    (
+        "profiles",
+        &["profiles", "active_profile", "auto_select_profile"],
+    ),
+];
+
+fn to_map(cfg: &Config) -> Result<Map<String, Value>, String> {
+    match serde_json::to_value(cfg).map_err(|e| e.to_string())? {
+        Value::Object(m) => Ok(m),
+        _ => Err("Config did not serialize to an object".into()),
+    }
+}
+
+fn from_map(map: Map<String, Value>) -> Result<Config, String> {
+    let mut cfg: Config = serde_json::from_value(Value::Object(map)).map_err(|e| e.to_string())?;
+    crate::migrate_config(&mut cfg);
+    Ok(cfg)
+}
+
+// save and keep runtime state that mirrors the config in sync
+fn store(app: &tauri::AppHandle, cfg: Config) -> Result<(), String> {
+    *app.state::<JarvisState>().active_persona.lock().unwrap() = cfg.active_persona.clone();
+    crate::cmd_save_config(app.clone(), cfg)
+}
+
+// Replaces every string in `v` with whether it is set; switches and numbers are kept
+fn mask(v: &mut Value) {
+    match v {
+        Value::String(s) => {
+            let set = !s.trim().is_empty();
+            *v = Value::String(if set { "<set>" } else { "<empty>" }.into());
+        }
+        Value::Array(items) => items.iter_mut().for_each(mask),
+        Value::Object(fields) => fields.values_mut().for_each(mask),
+        _ => {}
+    }
+}
+
+// Current settings for support bundles: outside SUPPORT_KEYS, text (keys, prompts, paths,
+// names, URLs) only says whether it is set
+pub(crate) fn redacted_config(app: &tauri::AppHandle) -> Result<Value, String> {
+    let mut map = to_map(&crate::cmd_load_config(app.clone())?)?;
+    for (key, v) in map.iter_mut() {
+        if !SUPPORT_KEYS.contains(&key.as_str()) {
+            mask(v);
+        }
+    }
+    Ok(Value::Object(map))
+}
+
+// Writes the current settings to `path`. API keys are left out unless asked for.
+#[tauri::command]
+pub fn cmd_export_settings(
+    app: tauri::AppHandle,
+    path: String,
+    include_secrets: Option<bool>,
+) -> Result<(), String> {
+    let mut map = to_map(&crate::cmd_load_config(app)?)?;
+    if !include_secrets.unwrap_or(false) {
+        for key in SECRET_KEYS {
+            map.remove(*key);
+        }
+        for key in CREDENTIAL_KEYS {
+            map.remove(*key);
+        }
+    }
+    let s = serde_json::to_string_pretty(&Value::Object(map)).map_err(|e| e.to_string())?;
+    crate::history::atomic_write(std::path::Path::new(&path), s.as_bytes())
+        .map_err(|e| e.to_string())
+}
+
+// Merges settings from an exported file over the current config.
+// Keys missing from the file keep their current value; API keys are ignored unless asked for.
+#[tauri::command]
+pub fn cmd_import_settings(
+    app: tauri::AppHandle,
+    path: String,
+    include_secrets: Option<bool>,
+) -> Result<(), String> {
+    let s = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let imported = match serde_json::from_str::<Value>(&s).map_err(|e| e.to_string())? {
+        Value::Object(m) => m,
+        _ => return Err("Settings file must contain a JSON object".into()),
+    };
+
+    let mut map = to_map(&crate::cmd_load_config(app.clone())?)?;
+    let include_secrets = include_secrets.unwrap_or(false);
+    for (key, value) in imported {
+        if !map.contains_key(&key) && key != "silence_threshold_seconds" {
+            println!("[DEBUG] Ignoring unknown setting '{}' on import", key);
+            continue;
+        }
+        let secret = SECRET_KEYS.contains(&key.as_str()) || CREDENTIAL_KEYS.contains(&key.as_str());
+        if secret && !include_secrets {
+            continue;
+        }
+        map.insert(key, value);
+    }
+    let cfg = from_map(map).map_err(|e| format!("Invalid settings file: {}", e))?;
+    store(&app, cfg)
+}
+
+// Settings that stay on this machine when syncing: API keys, audio devices, the sync
+// and network setup, and the advanced tuning for this machine's hardware
+fn is_local_only(key: &str) -> bool {
+    let in_section = |section: &str| {
+        SECTIONS
+            .iter()
+            .find(|(name, _)| *name == section)
+            .is_some_and(|(_, keys)| keys.contains(&key))
+    };
+    SECRET_KEYS.contains(&key)
+        || CREDENTIAL_KEYS.contains(&key)
+        || NETWORK_KEYS.contains(&key)
+        || in_section("audio")
+        || in_section("advanced")
+}
+
+// The settings shared with other devices (see sync.rs)
+pub(crate) fn synced_values(app: &tauri::AppHandle) -> Result<Map<String, Value>, String> {
+    let mut map = to_map(&crate::cmd_load_config(app.clone())?)?;
+    map.retain(|key, _| !is_local_only(key));
+    Ok(map)
+}
+
+// Applies settings from another device over the current config, keeping local-only ones
+pub(crate) fn apply_synced(
+    app: &tauri::AppHandle,
+    values: Map<String, Value>,
+) -> Result<(), String> {
+    let mut map = to_map(&crate::cmd_load_config(app.clone())?)?;
+    for (key, value) in values {
+        if map.contains_key(&key) && !is_local_only(&key) {
+            map.insert(key, value);
+        }
+    }
+    store(app, from_map(map)?)
+}
+
+// Restores the defaults for one section (see SECTIONS) or "all".
+// "all" keeps the API keys; reset "api_keys" explicitly to clear them.
+#[tauri::command]
+pub fn cmd_reset_settings(app: tauri::AppHandle, section: String) -> Result<(), String> {
+    let defaults = to_map(&crate::initial_config(&app))?;
+    let mut map = to_map(&crate::cmd_load_config(app.clone())?)?;
+
+    let keys: Vec<&str> = if section == "all" {
+        defaults
+            .keys()
+            .map(String::as_str)
+            .filter(|k| !SECRET_KEYS.contains(k))
+            .collect()
+    } else {
+        SECTIONS
+            .iter()
+            .find(|(name, _)| *name == section)
+            .map(|(_, keys)| keys.to_vec())
+            .ok_or_else(|| {
+                let names: Vec<&str> = SECTIONS.iter().map(|(n, _)| *n).collect();
+                format!(
+                    "Unknown settings section '{}' (expected one of: all, {})",
+                    section,
+                    names.join(", ")
+                )
+            })?
+    };
+    for key in keys {
+        match defaults.get(key) {
+            Some(v) => map.insert(key.to_string(), v.clone()),
+            None => map.remove(key),
+        };
+    }
+    store(&app, from_map(map)?)
+}
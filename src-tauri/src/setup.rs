@@ -0,0 +1,351 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::audio_input::{self, SAMPLE_RATE};
+use crate::run_jarvis;
+use serde::Serialize;
+use std::time::Duration;
+use webrtc_vad::{SampleRate, Vad, VadMode};
+
+// What the onboarding wizard still needs before listening can start
+#[derive(Serialize, Debug, Clone)]
+pub struct SetupStatus {
+    pub porcupine_key: bool,
+    pub gemini_key: bool,
+    pub elevenlabs_key: bool,
+    pub voice_id: bool,
+    pub whisper_model: bool,
+    pub wakeword: bool,
+    pub porcupine_library: bool,
+    pub input_devices: Vec<String>,
+    pub output_devices: Vec<String>,
+    pub microphone_found: bool,
+    pub missing: Vec<String>,
+    pub ready: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyFailure {
+    Invalid,
+    Quota,
+    Network,
+    Other,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct KeyCheck {
+    pub ok: bool,
+    pub failure: Option<KeyFailure>,
+    pub reason: Option<String>,
+}
+
+impl KeyCheck {
+    fn ok() -> Self {
+        Self {
+            ok: true,
+            failure: None,
+            reason: None,
+        }
+    }
+
+    fn failed(failure: KeyFailure, reason: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            failure: Some(failure),
+            reason: Some(reason.into()),
+        }
+    }
+}
+
+fn provider_label(provider: &str) -> &str {
+    match provider {
+        "gemini" => "Gemini",
+        "elevenlabs" => "ElevenLabs",
+        "porcupine" => "Picovoice",
+        other => other,
+    }
+}
+
+// Turns a non-2xx provider response into something a user can act on
+fn classify_http(provider: &str, status: reqwest::StatusCode, body: &str) -> KeyCheck {
+    let label = provider_label(provider);
+    let lower = body.to_lowercase();
+    // ElevenLabs keys can be scoped without user_read; the key itself is still valid
+    if provider == "elevenlabs" && lower.contains("missing_permissions") {
+        return KeyCheck {
+            ok: true,
+            failure: None,
+            reason: Some("Key is valid but cannot read account details".into()),
+        };
+    }
+    if status.as_u16() == 429
+        || lower.contains("quota")
+        || lower.contains("resource_exhausted")
+        || lower.contains("exceeded")
+    {
+        return KeyCheck::failed(
+            KeyFailure::Quota,
+            format!("{} quota or rate limit exceeded for this key.", label),
+        );
+    }
+    if status.as_u16() == 401
+        || status.as_u16() == 403
+        || lower.contains("api_key_invalid")
+        || lower.contains("api key not valid")
+        || lower.contains("invalid_api_key")
+    {
+        return KeyCheck::failed(
+            KeyFailure::Invalid,
+            format!("{} rejected this key. Check that it was copied completely.", label),
+        );
+    }
+    if status.is_server_error() {
+        return KeyCheck::failed(
+            KeyFailure::Network,
+            format!("{} is having problems right now ({}). Try again later.", label, status),
+        );
+    }
+    KeyCheck::failed(
+        KeyFailure::Other,
+        format!("{} returned {}: {}", label, status, body.trim()),
+    )
+}
+
+// Porcupine reports activation problems through its status names
+fn classify_porcupine(err: &str) -> KeyCheck {
+    if err.contains("ActivationLimitReached") || err.contains("ActivationThrottled") {
+        KeyCheck::failed(
+            KeyFailure::Quota,
+            "Picovoice activation limit reached for this key.",
+        )
+    } else if err.contains("ActivationRefused") || err.contains("InvalidArgument") {
+        KeyCheck::failed(KeyFailure::Invalid, "Picovoice rejected this access key.")
+    } else if err.contains("ActivationError") {
+        KeyCheck::failed(
+            KeyFailure::Network,
+            "Couldn't reach Picovoice to activate the key. Check your internet connection.",
+        )
+    } else {
+        KeyCheck::failed(KeyFailure::Other, err.to_string())
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AudioCheck {
+    pub device: String,
+    pub duration_ms: u64,
+    // 0.0 - 1.0 of full scale
+    pub peak: f32,
+    pub rms: f32,
+    pub speech_detected: bool,
+    pub clipped: bool,
+    pub output_ok: bool,
+    pub output_error: Option<String>,
+}
+
+#[tauri::command]
+pub fn cmd_setup_status(app: tauri::AppHandle) -> Result<SetupStatus, String> {
+    let cfg = crate::cmd_load_config(app.clone())?;
+    let input_devices = crate::cmd_list_input_devices().unwrap_or_default();
+    let output_devices = crate::cmd_list_output_devices().unwrap_or_default();
+    let microphone_found = match cfg.default_microphone_name.as_deref().map(str::trim) {
+        Some(name) if !name.is_empty() => {
+            let name = name.to_lowercase();
+            input_devices.iter().any(|d| d.to_lowercase().contains(&name))
+        }
+        _ => !input_devices.is_empty(),
+    };
+    let (params, lib) = run_jarvis::resolve_porcupine_paths(&app);
+
+    let mut status = SetupStatus {
+        porcupine_key: !cfg.porcupine_key.trim().is_empty(),
+        gemini_key: !cfg.gemini_key.trim().is_empty(),
+        elevenlabs_key: !cfg.elevenlabs_key.trim().is_empty(),
+        voice_id: !cfg.voice_id.trim().is_empty(),
+        whisper_model: run_jarvis::whisper_model_path(&app, run_jarvis::DEFAULT_WHISPER_MODEL)
+            .exists(),
+        wakeword: run_jarvis::resolve_wakeword_path(&app).is_ok(),
+        porcupine_library: params.exists() && lib.exists(),
+        input_devices,
+        output_devices,
+        microphone_found,
+        missing: Vec::new(),
+        ready: false,
+    };
+
+    let checks = [
+        (status.porcupine_key, "porcupine_key"),
+        (status.gemini_key, "gemini_key"),
+        (status.elevenlabs_key, "elevenlabs_key"),
+        (status.voice_id, "voice_id"),
+        (status.whisper_model, "whisper_model"),
+        (status.wakeword, "wakeword"),
+        (status.microphone_found, "microphone"),
+        (!status.output_devices.is_empty(), "output_device"),
+    ];
+    status.missing = checks
+        .iter()
+        .filter(|(ok, _)| !ok)
+        .map(|(_, name)| name.to_string())
+        .collect();
+    // the Whisper model is downloaded automatically on first start
+    status.ready = status.missing.iter().all(|m| m == "whisper_model");
+    Ok(status)
+}
+
+// Makes a cheap authenticated request (or, for Picovoice, creates the engine) with `key`
+pub(crate) async fn check_key(
+    app: &tauri::AppHandle,
+    provider: &str,
+    key: &str,
+) -> Result<KeyCheck, String> {
+    if key.trim().is_empty() {
+        return Ok(KeyCheck::failed(KeyFailure::Invalid, "No key entered."));
+    }
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let resp = match provider {
+        "gemini" => client
+            .get("https://generativelanguage.googleapis.com/v1beta/models")
+            .query(&[("key", key), ("pageSize", "1")])
+            .send()
+            .await,
+        "elevenlabs" => client
+            .get("https://api.elevenlabs.io/v1/user")
+            .header("xi-api-key", key)
+            .send()
+            .await,
+        "porcupine" => {
+            let app = app.clone();
+            let key = key.to_string();
+            return tokio::task::spawn_blocking(move || {
+                let wakeword = run_jarvis::resolve_wakeword_path(&app).map_err(|e| e.to_string())?;
+                let (params, lib) = run_jarvis::resolve_porcupine_paths(&app);
+                Ok(
+                    match run_jarvis::build_porcupine(&key, 0.5, &wakeword, &params, &lib) {
+                        Ok(_) => KeyCheck::ok(),
+                        Err(e) => classify_porcupine(&format!("{:?}", e)),
+                    },
+                )
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+        other => return Err(format!("Unknown provider '{}'", other)),
+    };
+
+    Ok(match resp {
+        Ok(r) if r.status().is_success() => KeyCheck::ok(),
+        Ok(r) => {
+            let status = r.status();
+            let body = r.text().await.unwrap_or_default();
+            classify_http(provider, status, &body)
+        }
+        Err(e) => KeyCheck::failed(
+            KeyFailure::Network,
+            format!(
+                "Couldn't reach {}. Check your internet connection. ({})",
+                provider_label(provider),
+                e
+            ),
+        ),
+    })
+}
+
+// Validates the key currently saved in the config for `provider`
+#[tauri::command]
+pub async fn cmd_test_api_key(app: tauri::AppHandle, provider: String) -> Result<KeyCheck, String> {
+    let cfg = crate::cmd_load_config(app.clone())?;
+    let key = match provider.as_str() {
+        "porcupine" => cfg.porcupine_key,
+        "gemini" => cfg.gemini_key,
+        "elevenlabs" => cfg.elevenlabs_key,
+        other => return Err(format!("Unknown provider '{}'", other)),
+    };
+    check_key(&app, &provider, &key).await
+}
+
+// Records a short sample from the configured microphone and opens the output device
+#[tauri::command]
+pub async fn cmd_run_audio_check(
+    app: tauri::AppHandle,
+    seconds: Option<f32>,
+) -> Result<AudioCheck, String> {
+    let cfg = crate::cmd_load_config(app)?;
+    let duration = Duration::from_secs_f32(seconds.unwrap_or(2.0).clamp(0.5, 10.0));
+
+    tokio::task::spawn_blocking(move || -> Result<AudioCheck, String> {
+        let (device, samples) = audio_input::capture_for(
+            cfg.default_microphone_name.as_deref(),
+            cfg.default_microphone_index.max(0) as usize,
+            duration,
+        )
+        .map_err(|e| e.to_string())?;
+
+        let peak = samples.iter().map(|s| s.unsigned_abs()).max().unwrap_or(0);
+        let rms = if samples.is_empty() {
+            0.0
+        } else {
+            let sum: f64 = samples.iter().map(|&s| (s as f64) * (s as f64)).sum();
+            (sum / samples.len() as f64).sqrt()
+        };
+
+        let mut vad = Vad::new_with_rate_and_mode(SampleRate::Rate16kHz, VadMode::Quality);
+        let frame = SAMPLE_RATE * 30 / 1000;
+        let speech_frames = samples
+            .chunks_exact(frame)
+            .filter(|f| vad.is_voice_segment(f).unwrap_or(false))
+            .count();
+
+        let voice_device = crate::to_run_config(cfg).voice_output_device();
+        let (output_ok, output_error) = match crate::audio_output::AudioOutput::open(
+            None,
+            voice_device.as_deref(),
+            1.0,
+        ) {
+            Ok(_) => (true, None),
+            Err(e) => (false, Some(e.to_string())),
+        };
+
+        Ok(AudioCheck {
+            device,
+            duration_ms: (samples.len() as u64 * 1000) / SAMPLE_RATE as u64,
+            peak: peak as f32 / i16::MAX as f32,
+            rms: (rms / i16::MAX as f64) as f32,
+            // a few consecutive-ish speech frames, not a single click
+            speech_detected: speech_frames >= 5,
+            clipped: peak >= i16::MAX as u16 - 1,
+            output_ok,
+            output_error,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// Checks a key before it is saved, so Settings can show the result next to the field
+#[tauri::command]
+pub async fn cmd_validate_key(
+    app: tauri::AppHandle,
+    provider: String,
+    key: String,
+) -> Result<KeyCheck, String> {
+    check_key(&app, &provider, key.trim()).await
+}
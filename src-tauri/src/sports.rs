@@ -0,0 +1,292 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Scores and schedules: "did Arsenal win yesterday", "when do the Lakers play next".
+// Teams and games come from TheSportsDB (or anything serving its v1 API at `api_url`).
+// `teams` maps the names you use to the ones the API knows ("the gunners": "Arsenal").
+// A name the API doesn't know is left to the LLM.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SportsConfig {
+    pub api_url: String,
+    // "123" is TheSportsDB's free key
+    pub api_key: String,
+    // names you say mapped to the team name the API knows, e.g. "the gunners": "Arsenal"
+    pub teams: BTreeMap<String, String>,
+}
+
+impl Default for SportsConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "https://www.thesportsdb.com/api/v1/json".to_string(),
+            api_key: "123".to_string(),
+            teams: BTreeMap::new(),
+        }
+    }
+}
+
+static LAST_GAME: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r"(?i)\b(?:did|how did|how'd)\s+(?:the\s+)?(.+?)\s+(?:win|lose|do|play|get on)\b",
+        concat!(
+            r"(?i)\b(?:score|result)\s+(?:of|in|for)\s+(?:the\s+)?(.+?)(?:\s+(?:game|match))?",
+            r"(?:\s+(?:yesterday|today|last night))?[?.!]*$"
+        ),
+    ]
+    .iter()
+    .map(|p| Regex::new(p).expect("Failed to compile last game regex"))
+    .collect()
+});
+
+static NEXT_GAME: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r"(?i)\bwhen\s+(?:do|does|is|are)\s+(?:the\s+)?(.+?)\s+(?:play|playing|next)\b",
+        r"(?i)\bnext\s+(?:game|match)\s+(?:of|for)\s+(?:the\s+)?(.+?)[?.!]*$",
+        r"(?i)\bnext\s+(.+?)\s+(?:game|match)\b",
+    ]
+    .iter()
+    .map(|p| Regex::new(p).expect("Failed to compile next game regex"))
+    .collect()
+});
+
+// what "did you win" and friends capture, never a team
+const NOT_TEAMS: &[&str] = &["i", "you", "we", "they", "he", "she", "it", "my", "your", "that"];
+
+#[derive(Debug, Clone)]
+struct Team {
+    id: String,
+    name: String,
+}
+
+// names already looked up, so a follow-up question costs one request
+static TEAMS: Lazy<Mutex<HashMap<String, Team>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+enum Question {
+    LastGame(String),
+    NextGame(String),
+}
+
+fn parse(prompt: &str) -> Option<Question> {
+    let capture = |patterns: &[Regex]| {
+        patterns.iter().find_map(|re| {
+            let name = re.captures(prompt)?.get(1)?.as_str().trim().to_string();
+            (!name.is_empty() && !NOT_TEAMS.contains(&name.to_lowercase().as_str()))
+                .then_some(name)
+        })
+    };
+    if let Some(name) = capture(&NEXT_GAME) {
+        return Some(Question::NextGame(name));
+    }
+    capture(&LAST_GAME).map(Question::LastGame)
+}
+
+fn client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .user_agent(concat!("Jarvis/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| anyhow!("Failed to build HTTP client: {e}"))
+}
+
+async fn get(
+    client: &reqwest::Client,
+    config: &SportsConfig,
+    endpoint: &str,
+    query: &[(&str, &str)],
+) -> Result<Value> {
+    let url = format!(
+        "{}/{}/{}",
+        config.api_url.trim().trim_end_matches('/'),
+        config.api_key.trim(),
+        endpoint
+    );
+    let resp = client.get(url).query(query).send().await?;
+    if !resp.status().is_success() {
+        return Err(anyhow!("The sports service returned {}", resp.status()));
+    }
+    Ok(resp.json().await?)
+}
+
+async fn find_team(
+    client: &reqwest::Client,
+    config: &SportsConfig,
+    said: &str,
+) -> Result<Option<Team>> {
+    let key = said.to_lowercase();
+    let name = config
+        .teams
+        .iter()
+        .find(|(alias, _)| alias.trim().to_lowercase() == key)
+        .map(|(_, team)| team.trim().to_string())
+        .unwrap_or_else(|| said.to_string());
+    if let Some(team) = TEAMS.lock().unwrap().get(&name.to_lowercase()) {
+        return Ok(Some(team.clone()));
+    }
+    let data = get(client, config, "searchteams.php", &[("t", name.as_str())]).await?;
+    let team = data["teams"].get(0).and_then(|t| {
+        Some(Team {
+            id: t["idTeam"].as_str()?.to_string(),
+            name: t["strTeam"].as_str()?.to_string(),
+        })
+    });
+    if let Some(team) = &team {
+        TEAMS
+            .lock()
+            .unwrap()
+            .insert(name.to_lowercase(), team.clone());
+    }
+    Ok(team)
+}
+
+// when the game starts, from the UTC timestamp or the date and time fields
+fn kickoff(event: &Value) -> Option<DateTime<Local>> {
+    if let Some(ts) = event["strTimestamp"].as_str() {
+        if let Ok(t) = DateTime::parse_from_rfc3339(ts) {
+            return Some(t.with_timezone(&Local));
+        }
+        if let Ok(t) = NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S") {
+            return Some(t.and_utc().with_timezone(&Local));
+        }
+    }
+    let date = event["dateEvent"].as_str()?;
+    let time = event["strTime"].as_str().unwrap_or("00:00:00");
+    let t = NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M:%S")
+        .ok()?;
+    Some(t.and_utc().with_timezone(&Local))
+}
+
+fn event_date(event: &Value) -> Option<NaiveDate> {
+    kickoff(event).map(|t| t.date_naive()).or_else(|| {
+        NaiveDate::parse_from_str(event["dateEvent"].as_str()?, "%Y-%m-%d").ok()
+    })
+}
+
+// "yesterday", "on Saturday", "on March 3"
+fn spoken_day(date: NaiveDate) -> String {
+    let days = (date - Local::now().date_naive()).num_days();
+    match days {
+        0 => "today".to_string(),
+        -1 => "yesterday".to_string(),
+        1 => "tomorrow".to_string(),
+        d if d.abs() < 7 => format!("on {}", date.format("%A")),
+        _ => format!("on {}", date.format("%B %-d")),
+    }
+}
+
+fn score(v: &Value) -> Option<u32> {
+    v.as_u64()
+        .map(|n| n as u32)
+        .or_else(|| v.as_str()?.trim().parse().ok())
+}
+
+fn describe_last(team: &Team, events: &[Value]) -> String {
+    let Some(event) = events.iter().max_by_key(|e| event_date(e)) else {
+        return format!("I couldn't find a recent game for {}.", team.name);
+    };
+    let home = event["idHomeTeam"].as_str() == Some(team.id.as_str());
+    let (us, them) = if home {
+        ("intHomeScore", "intAwayScore")
+    } else {
+        ("intAwayScore", "intHomeScore")
+    };
+    let opponent = event[if home { "strAwayTeam" } else { "strHomeTeam" }]
+        .as_str()
+        .unwrap_or("their opponent");
+    let when = event_date(event).map(spoken_day).unwrap_or_default();
+    match (score(&event[us]), score(&event[them])) {
+        (Some(a), Some(b)) if a > b => {
+            format!("{} beat {} {} to {} {}.", team.name, opponent, a, b, when)
+        }
+        (Some(a), Some(b)) if a < b => {
+            format!("{} lost {} to {} against {} {}.", team.name, a, b, opponent, when)
+        }
+        (Some(a), Some(_)) => format!("{} drew {} all with {} {}.", team.name, a, opponent, when),
+        _ => format!(
+            "{} played {} {}, but there's no score yet.",
+            team.name, opponent, when
+        ),
+    }
+}
+
+fn describe_next(team: &Team, events: &[Value]) -> String {
+    let now = Local::now();
+    let next = events
+        .iter()
+        .filter_map(|e| Some((kickoff(e)?, e)))
+        .filter(|(t, _)| *t > now - chrono::Duration::hours(3))
+        .min_by_key(|(t, _)| *t);
+    let Some((start, event)) = next else {
+        return format!("I couldn't find an upcoming game for {}.", team.name);
+    };
+    let home = event["idHomeTeam"].as_str() == Some(team.id.as_str());
+    let opponent = event[if home { "strAwayTeam" } else { "strHomeTeam" }]
+        .as_str()
+        .unwrap_or("an opponent to be decided");
+    let place = if home { "at home against" } else { "away at" };
+    let time = start.format("%-I:%M %p").to_string();
+    format!(
+        "{} play {} {} {} at {}.",
+        team.name,
+        place,
+        opponent,
+        spoken_day(start.date_naive()),
+        time.replace(":00 ", " ")
+    )
+}
+
+// The spoken answer, or None when the prompt isn't about a team the API knows
+pub async fn answer(prompt: &str, config: &SportsConfig) -> Option<String> {
+    let question = parse(prompt)?;
+    let said = match &question {
+        Question::LastGame(name) | Question::NextGame(name) => name.clone(),
+    };
+    let client = client().ok()?;
+    let team = match find_team(&client, config, &said).await {
+        Ok(Some(team)) => team,
+        Ok(None) => return None,
+        Err(e) => {
+            eprintln!("[ERROR] Failed to look up team '{}': {}", said, e);
+            return None;
+        }
+    };
+    let (endpoint, field) = match question {
+        Question::LastGame(_) => ("eventslast.php", "results"),
+        Question::NextGame(_) => ("eventsnext.php", "events"),
+    };
+    let events = match get(&client, config, endpoint, &[("id", team.id.as_str())]).await {
+        Ok(data) => data[field].as_array().cloned().unwrap_or_default(),
+        Err(e) => {
+            eprintln!("[ERROR] Failed to get games for {}: {}", team.name, e);
+            return Some(format!("Sorry, I couldn't get the games of {} right now.", team.name));
+        }
+    };
+    Some(match question {
+        Question::LastGame(_) => describe_last(&team, &events),
+        Question::NextGame(_) => describe_next(&team, &events),
+    })
+}
@@ -0,0 +1,213 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Local usage counters per day for the dashboard. Nothing here leaves the machine.
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tauri::Manager;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DayStats {
+    pub date: String,
+    // answered prompts, voice and text
+    pub interactions: u64,
+    pub voice_interactions: u64,
+    pub wake_detections: u64,
+    pub latency_total_ms: u64,
+    pub latency_samples: u64,
+    pub stt_seconds: f64,
+    // estimated at ~4 characters per token, like the TTS estimate on messages
+    pub llm_tokens_in: u64,
+    pub llm_tokens_out: u64,
+    pub tts_characters: u64,
+}
+
+impl DayStats {
+    fn add(&mut self, other: &DayStats) {
+        self.interactions += other.interactions;
+        self.voice_interactions += other.voice_interactions;
+        self.wake_detections += other.wake_detections;
+        self.latency_total_ms += other.latency_total_ms;
+        self.latency_samples += other.latency_samples;
+        self.stt_seconds += other.stt_seconds;
+        self.llm_tokens_in += other.llm_tokens_in;
+        self.llm_tokens_out += other.llm_tokens_out;
+        self.tts_characters += other.tts_characters;
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Dashboard {
+    pub range: String,
+    // oldest first, one entry per day including days without use
+    pub days: Vec<DayStats>,
+    pub totals: DayStats,
+    pub average_latency_ms: Option<u64>,
+    pub stt_minutes: f64,
+}
+
+static APP: OnceLock<tauri::AppHandle> = OnceLock::new();
+// serializes read-modify-write of stats.json
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+// Called once at startup so recorders deep in the pipeline don't need an AppHandle
+pub fn init(app: &tauri::AppHandle) {
+    let _ = APP.set(app.clone());
+}
+
+fn stats_path(app: &tauri::AppHandle) -> Option<PathBuf> {
+    let dir = app.path().app_config_dir().ok()?;
+    std::fs::create_dir_all(&dir).ok()?;
+    Some(dir.join("stats.json"))
+}
+
+fn load(app: &tauri::AppHandle) -> BTreeMap<String, DayStats> {
+    stats_path(app)
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+fn record(update: impl FnOnce(&mut DayStats)) {
+    let Some(app) = APP.get() else {
+        return;
+    };
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut all = load(app);
+    let date = today();
+    let day = all.entry(date.clone()).or_insert_with(|| DayStats {
+        date,
+        ..Default::default()
+    });
+    update(day);
+    let Some(path) = stats_path(app) else {
+        return;
+    };
+    match serde_json::to_string(&all) {
+        Ok(s) => {
+            if let Err(e) = crate::history::atomic_write(&path, s.as_bytes()) {
+                eprintln!("[WARN] Failed to save usage stats: {}", e);
+            }
+        }
+        Err(e) => eprintln!("[WARN] Failed to serialize usage stats: {}", e),
+    }
+}
+
+fn tokens(text_chars: usize) -> u64 {
+    text_chars.div_ceil(4) as u64
+}
+
+pub fn record_wake() {
+    record(|d| d.wake_detections += 1);
+}
+
+pub fn record_stt(samples: usize) {
+    let seconds = samples as f64 / crate::audio_input::SAMPLE_RATE as f64;
+    record(|d| d.stt_seconds += seconds);
+}
+
+pub fn record_interaction(voice: bool) {
+    record(|d| {
+        d.interactions += 1;
+        if voice {
+            d.voice_interactions += 1;
+        }
+    });
+}
+
+// wake word to end of the spoken answer
+pub fn record_latency(ms: u64) {
+    record(|d| {
+        d.latency_total_ms += ms;
+        d.latency_samples += 1;
+    });
+}
+
+pub fn record_llm(prompt_chars: usize, answer_chars: usize) {
+    record(|d| {
+        d.llm_tokens_in += tokens(prompt_chars);
+        d.llm_tokens_out += tokens(answer_chars);
+    });
+}
+
+pub fn record_tts(text: &str) {
+    let chars = text.chars().count() as u64;
+    if chars > 0 {
+        record(|d| d.tts_characters += chars);
+    }
+}
+
+fn days_in(range: &str) -> Result<i64, String> {
+    match range {
+        "day" | "today" => Ok(1),
+        "week" => Ok(7),
+        "month" => Ok(30),
+        "year" => Ok(365),
+        other => other
+            .parse::<i64>()
+            .ok()
+            .filter(|n| (1..=3660).contains(n))
+            .ok_or_else(|| format!("Unknown range '{}'", other)),
+    }
+}
+
+// `range` is "day", "week", "month", "year" or a number of days
+#[tauri::command]
+pub fn cmd_get_dashboard(app: tauri::AppHandle, range: String) -> Result<Dashboard, String> {
+    let span = days_in(&range)?;
+    let all = {
+        let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        load(&app)
+    };
+    let end = chrono::Local::now().date_naive();
+    let start = end - Duration::days(span - 1);
+    let mut totals = DayStats::default();
+    let days: Vec<DayStats> = start
+        .iter_days()
+        .take_while(|d| *d <= end)
+        .map(|d: NaiveDate| {
+            let date = d.format("%Y-%m-%d").to_string();
+            let day = all.get(&date).cloned().unwrap_or(DayStats {
+                date,
+                ..Default::default()
+            });
+            totals.add(&day);
+            day
+        })
+        .collect();
+    totals.date = format!("{} - {}", start.format("%Y-%m-%d"), end.format("%Y-%m-%d"));
+    let average_latency_ms = (totals.latency_samples > 0)
+        .then(|| totals.latency_total_ms / totals.latency_samples);
+    let stt_minutes = totals.stt_seconds / 60.0;
+    Ok(Dashboard {
+        range,
+        days,
+        totals,
+        average_latency_ms,
+        stt_minutes,
+    })
+}
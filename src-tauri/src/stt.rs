@@ -0,0 +1,272 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::{get_text, run_jarvis};
+use anyhow::{anyhow, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use whisper_rs::{WhisperContext, WhisperContextParameters};
+
+struct Job {
+    samples: Vec<i16>,
+    language: String,
+    cancel: Arc<AtomicBool>,
+    reply: Sender<Result<String>>,
+}
+
+// Runs Whisper on its own thread so the voice loop stays responsive to stop
+// requests and barge-in while a transcription is in flight.
+pub struct SttWorker {
+    jobs: Mutex<Sender<Job>>,
+    // cancel flag of the job being transcribed right now
+    current: Arc<Mutex<Option<Arc<AtomicBool>>>>,
+}
+
+// The Whisper model of a worker. One loaded from a file can be unloaded to free memory
+// (see game_mode.rs) and is loaded again on the next transcription.
+struct Model {
+    ctx: Mutex<Option<Arc<WhisperContext>>>,
+    path: Option<PathBuf>,
+}
+
+impl Model {
+    fn get(&self) -> Result<Arc<WhisperContext>> {
+        let mut ctx = self.ctx.lock().unwrap();
+        if let Some(ctx) = ctx.as_ref() {
+            return Ok(Arc::clone(ctx));
+        }
+        let path = self.path.as_ref().ok_or_else(|| anyhow!("Whisper model was unloaded"))?;
+        println!("[DEBUG] Loading Whisper model again from {:?}", path);
+        let loaded = WhisperContext::new_with_params(
+            &path.to_string_lossy(),
+            WhisperContextParameters::default(),
+        )
+        .map_err(|e| anyhow!("Failed to load Whisper model: {e}"))?;
+        let loaded = Arc::new(loaded);
+        *ctx = Some(Arc::clone(&loaded));
+        Ok(loaded)
+    }
+}
+
+// every worker's model, so they can be unloaded and reloaded together
+static MODELS: Mutex<Vec<Weak<Model>>> = Mutex::new(Vec::new());
+
+// Handle to one submitted transcription
+pub struct TranscriptionJob {
+    cancel: Arc<AtomicBool>,
+    result: Receiver<Result<String>>,
+}
+
+impl SttWorker {
+    pub fn spawn(ctx: Arc<WhisperContext>) -> Self {
+        Self::spawn_with(ctx, None)
+    }
+
+    // A worker whose model can be unloaded and is read from `path` again when needed
+    pub fn spawn_from_file(ctx: Arc<WhisperContext>, path: PathBuf) -> Self {
+        Self::spawn_with(ctx, Some(path))
+    }
+
+    fn spawn_with(ctx: Arc<WhisperContext>, path: Option<PathBuf>) -> Self {
+        let (tx, rx) = mpsc::channel::<Job>();
+        let current: Arc<Mutex<Option<Arc<AtomicBool>>>> = Arc::new(Mutex::new(None));
+        let worker_current = Arc::clone(&current);
+        let model = Arc::new(Model {
+            ctx: Mutex::new(Some(ctx)),
+            path,
+        });
+        let worker_model = Arc::clone(&model);
+        if model.path.is_some() {
+            let mut models = MODELS.lock().unwrap();
+            models.retain(|m| m.strong_count() > 0);
+            models.push(Arc::downgrade(&model));
+        }
+        std::thread::Builder::new()
+            .name("whisper-worker".into())
+            .spawn(move || {
+                // ends when the SttWorker (and with it the sender) is dropped
+                for job in rx {
+                    if job.cancel.load(Ordering::Relaxed) {
+                        let _ = job.reply.send(Err(anyhow!("Transcription cancelled")));
+                        continue;
+                    }
+                    *worker_current.lock().unwrap() = Some(Arc::clone(&job.cancel));
+                    let result = worker_model.get().and_then(|ctx| {
+                        get_text::transcribe_cancellable(
+                            &ctx,
+                            &job.samples,
+                            &job.language,
+                            Some(Arc::clone(&job.cancel)),
+                        )
+                    });
+                    *worker_current.lock().unwrap() = None;
+                    let _ = job.reply.send(result);
+                }
+            })
+            .expect("Failed to spawn Whisper worker thread");
+        Self {
+            jobs: Mutex::new(tx),
+            current,
+        }
+    }
+
+    pub fn submit(&self, samples: Vec<i16>, language: &str) -> TranscriptionJob {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (reply, result) = mpsc::channel();
+        let job = Job {
+            samples,
+            language: language.to_string(),
+            cancel: Arc::clone(&cancel),
+            reply,
+        };
+        if let Err(mpsc::SendError(job)) = self.jobs.lock().unwrap().send(job) {
+            let _ = job.reply.send(Err(anyhow!("Whisper worker is not running")));
+        }
+        TranscriptionJob { cancel, result }
+    }
+
+    // Submits and waits; for callers that are already on a blocking path
+    pub fn transcribe(&self, samples: Vec<i16>, language: &str) -> Result<String> {
+        self.submit(samples, language).wait()
+    }
+
+    // Abandons whatever is being transcribed right now
+    pub fn cancel_current(&self) {
+        if let Some(flag) = self.current.lock().unwrap().as_ref() {
+            flag.store(true, Ordering::Relaxed);
+        }
+    }
+
+    // A worker on the default model, downloading it first if needed; for transcription
+    // outside the voice loop
+    pub async fn load_default(app: &tauri::AppHandle) -> Result<Self> {
+        let model_path = run_jarvis::whisper_model_path(app, run_jarvis::DEFAULT_WHISPER_MODEL);
+        run_jarvis::download_whisper_with_progress(
+            app,
+            &run_jarvis::whisper_model_url(run_jarvis::DEFAULT_WHISPER_MODEL),
+            &model_path,
+        )
+        .await?;
+        let path = model_path.clone();
+        let whisper = tokio::task::spawn_blocking(move || {
+            WhisperContext::new_with_params(
+                &path.to_string_lossy(),
+                WhisperContextParameters::default(),
+            )
+            .map_err(|e| anyhow!("Failed to load Whisper model: {e}"))
+        })
+        .await
+        .map_err(|e| anyhow!("Whisper loader panicked: {e}"))??;
+        Ok(Self::spawn_from_file(Arc::new(whisper), model_path))
+    }
+
+    // One worker for the network services (satellites, Wyoming), loaded on first use and
+    // kept; the voice loop has its own
+    pub async fn shared(app: &tauri::AppHandle) -> Result<&'static Self> {
+        SHARED.get_or_try_init(|| Self::load_default(app)).await
+    }
+}
+
+static SHARED: tokio::sync::OnceCell<SttWorker> = tokio::sync::OnceCell::const_new();
+
+// Frees the memory of every Whisper model that can be loaded again; a transcription in
+// progress keeps its model until it is done
+pub fn unload_all() {
+    let mut unloaded = 0;
+    for model in MODELS.lock().unwrap().iter().filter_map(Weak::upgrade) {
+        if model.ctx.lock().unwrap().take().is_some() {
+            unloaded += 1;
+        }
+    }
+    println!("[DEBUG] Unloaded {} Whisper model(s)", unloaded);
+}
+
+// Loads the unloaded models again in the background, so the next command isn't slowed down
+pub fn reload_all() {
+    let models: Vec<Arc<Model>> =
+        MODELS.lock().unwrap().iter().filter_map(Weak::upgrade).collect();
+    if models.is_empty() {
+        return;
+    }
+    std::thread::spawn(move || {
+        for model in models {
+            if let Err(e) = model.get() {
+                eprintln!("[WARN] {}", e);
+            }
+        }
+    });
+}
+
+impl TranscriptionJob {
+    // A job that is already done; for transcribers that answer straight away
+    pub fn ready(result: Result<String>) -> Self {
+        let (reply, rx) = mpsc::channel();
+        let _ = reply.send(result);
+        Self {
+            cancel: Arc::new(AtomicBool::new(false)),
+            result: rx,
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn wait(self) -> Result<String> {
+        self.result
+            .recv()
+            .map_err(|_| anyhow!("Whisper worker stopped"))?
+    }
+
+    // Blocking wait that gives up (and cancels the job) as soon as `abandon` returns true
+    pub fn wait_or_abandon<F: Fn() -> bool>(self, abandon: F) -> Result<Option<String>> {
+        loop {
+            if abandon() {
+                self.cancel();
+                return Ok(None);
+            }
+            match self.result.recv_timeout(Duration::from_millis(20)) {
+                Ok(result) => return result.map(Some),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(anyhow!("Whisper worker stopped"))
+                }
+            }
+        }
+    }
+
+    // Waits without blocking the runtime. Returns None once `keep_going` goes false,
+    // cancelling the job so Whisper stops early.
+    pub async fn wait_while(self, keep_going: &AtomicBool) -> Result<Option<String>> {
+        loop {
+            if !keep_going.load(Ordering::Relaxed) {
+                self.cancel();
+                return Ok(None);
+            }
+            match self.result.try_recv() {
+                Ok(result) => return result.map(Some),
+                Err(TryRecvError::Empty) => tokio::time::sleep(Duration::from_millis(20)).await,
+                Err(TryRecvError::Disconnected) => {
+                    return Err(anyhow!("Whisper worker stopped"))
+                }
+            }
+        }
+    }
+}
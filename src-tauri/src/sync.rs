@@ -0,0 +1,646 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Keeps conversations, memories and settings in step between machines through a
+// WebDAV folder or S3 bucket the user provides. Everything is encrypted here with a
+// key derived from the sync passphrase, so the server only ever sees opaque blobs.
+//
+// Remote layout (all names flat in the folder, or under `jarvis-sync/` in a bucket):
+//   salt      random salt for the key derivation, not secret
+//   manifest  encrypted list of items with their modification time and hash
+//   <hex>     one encrypted object per item, named by a hash of the item name
+
+use crate::events::{self, HistoryChanged, SyncReport};
+use crate::memory::Memory;
+use crate::TurnDto;
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use futures_util::future::BoxFuture;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::Manager;
+
+const NONCE_LEN: usize = 24;
+const SALT_LEN: usize = 16;
+const S3_PREFIX: &str = "jarvis-sync/";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncBackend {
+    WebDav,
+    S3,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub enabled: bool,
+    pub backend: SyncBackend,
+    // WebDAV: the folder to sync into. S3: the endpoint, e.g. https://s3.eu-central-1.amazonaws.com
+    pub url: String,
+    // WebDAV user name or S3 access key id
+    pub username: String,
+    // WebDAV password or S3 secret key
+    pub password: String,
+    // S3 only
+    pub bucket: String,
+    pub region: String,
+    // must be the same on every device; losing it means starting over with a new folder
+    pub passphrase: String,
+    pub interval_minutes: u64,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: SyncBackend::WebDav,
+            url: String::new(),
+            username: String::new(),
+            password: String::new(),
+            bucket: String::new(),
+            region: "us-east-1".into(),
+            passphrase: String::new(),
+            interval_minutes: 15,
+        }
+    }
+}
+
+trait Remote: Send + Sync {
+    // None when the object doesn't exist
+    fn get<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>, String>>;
+    fn put<'a>(&'a self, name: &'a str, bytes: Vec<u8>) -> BoxFuture<'a, Result<(), String>>;
+    fn delete<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<(), String>>;
+}
+
+struct WebDav {
+    client: reqwest::Client,
+    base: String,
+    username: String,
+    password: String,
+}
+
+impl WebDav {
+    fn request(&self, method: Method, name: &str) -> reqwest::RequestBuilder {
+        let url = format!("{}/{}", self.base.trim_end_matches('/'), name);
+        let req = self.client.request(method, url);
+        if self.username.is_empty() {
+            req
+        } else {
+            req.basic_auth(&self.username, Some(&self.password))
+        }
+    }
+}
+
+impl Remote for WebDav {
+    fn get<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>, String>> {
+        Box::pin(async move {
+            let res = self.request(Method::GET, name).send().await.map_err(|e| e.to_string())?;
+            if res.status() == StatusCode::NOT_FOUND {
+                return Ok(None);
+            }
+            let res = res.error_for_status().map_err(|e| e.to_string())?;
+            Ok(Some(res.bytes().await.map_err(|e| e.to_string())?.to_vec()))
+        })
+    }
+
+    fn put<'a>(&'a self, name: &'a str, bytes: Vec<u8>) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            self.request(Method::PUT, name)
+                .body(bytes)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| e.to_string())?;
+            Ok(())
+        })
+    }
+
+    fn delete<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let res = self.request(Method::DELETE, name).send().await.map_err(|e| e.to_string())?;
+            if res.status() != StatusCode::NOT_FOUND {
+                res.error_for_status().map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        })
+    }
+}
+
+struct S3 {
+    bucket: Box<s3::Bucket>,
+}
+
+fn s3_status(code: u16) -> Result<(), String> {
+    match code {
+        200..=299 => Ok(()),
+        code => Err(format!("S3 returned status {}", code)),
+    }
+}
+
+impl Remote for S3 {
+    fn get<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>, String>> {
+        Box::pin(async move {
+            let res = self
+                .bucket
+                .get_object(format!("{}{}", S3_PREFIX, name))
+                .await
+                .map_err(|e| e.to_string())?;
+            if res.status_code() == 404 {
+                return Ok(None);
+            }
+            s3_status(res.status_code())?;
+            Ok(Some(res.as_slice().to_vec()))
+        })
+    }
+
+    fn put<'a>(&'a self, name: &'a str, bytes: Vec<u8>) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let res = self
+                .bucket
+                .put_object(format!("{}{}", S3_PREFIX, name), &bytes)
+                .await
+                .map_err(|e| e.to_string())?;
+            s3_status(res.status_code())
+        })
+    }
+
+    fn delete<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let res = self
+                .bucket
+                .delete_object(format!("{}{}", S3_PREFIX, name))
+                .await
+                .map_err(|e| e.to_string())?;
+            if res.status_code() == 404 {
+                return Ok(());
+            }
+            s3_status(res.status_code())
+        })
+    }
+}
+
+fn remote_for(cfg: &SyncConfig) -> Result<Box<dyn Remote>, String> {
+    if cfg.url.trim().is_empty() {
+        return Err("Set the sync server URL first".into());
+    }
+    match cfg.backend {
+        SyncBackend::WebDav => Ok(Box::new(WebDav {
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(60))
+                .build()
+                .map_err(|e| e.to_string())?,
+            base: cfg.url.trim().to_string(),
+            username: cfg.username.clone(),
+            password: cfg.password.clone(),
+        })),
+        SyncBackend::S3 => {
+            let region = s3::Region::Custom {
+                region: cfg.region.trim().to_string(),
+                endpoint: cfg.url.trim().trim_end_matches('/').to_string(),
+            };
+            let credentials = s3::creds::Credentials::new(
+                Some(&cfg.username),
+                Some(&cfg.password),
+                None,
+                None,
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+            let bucket = s3::Bucket::new(cfg.bucket.trim(), region, credentials)
+                .map_err(|e| e.to_string())?
+                .with_path_style();
+            Ok(Box::new(S3 { bucket }))
+        }
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<XChaCha20Poly1305, String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| e.to_string())?;
+    Ok(XChaCha20Poly1305::new(Key::from_slice(&key)))
+}
+
+// nonce followed by the ciphertext
+fn seal(cipher: &XChaCha20Poly1305, plain: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let sealed = cipher.encrypt(&nonce, plain).map_err(|_| "Encryption failed".to_string())?;
+    let mut out = nonce.to_vec();
+    out.extend(sealed);
+    Ok(out)
+}
+
+fn open(cipher: &XChaCha20Poly1305, data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < NONCE_LEN {
+        return Err("Sync data is damaged".into());
+    }
+    let (nonce, sealed) = data.split_at(NONCE_LEN);
+    cipher
+        .decrypt(XNonce::from_slice(nonce), sealed)
+        .map_err(|_| "Could not decrypt the sync data; check the sync passphrase".into())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    // unix milliseconds
+    modified: i64,
+    hash: String,
+    // removed on some device; kept so the others remove it too
+    #[serde(default)]
+    deleted: bool,
+}
+
+type Manifest = BTreeMap<String, Entry>;
+
+// What this device had after its last sync, to tell deleted conversations from new ones
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct SyncState {
+    conversations: BTreeSet<String>,
+}
+
+fn state_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join("sync-state.json"))
+}
+
+fn load_state(app: &tauri::AppHandle) -> SyncState {
+    state_path(app)
+        .ok()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(app: &tauri::AppHandle, state: &SyncState) -> Result<(), String> {
+    let s = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    crate::history::atomic_write(&state_path(app)?, s.as_bytes()).map_err(|e| e.to_string())
+}
+
+fn modified_ms(path: &Path) -> i64 {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(|t| chrono::DateTime::<chrono::Utc>::from(t).timestamp_millis())
+        .unwrap_or(0)
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn conversation_files(dir: &Path) -> BTreeSet<String> {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().is_file())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|n| !n.starts_with('.') && (n.ends_with(".json") || n.ends_with(".jsonl")))
+        .collect()
+}
+
+// Turns from both sides matched by (createdAt, role); where both have one, the newer file's wins
+fn merge_turns(local: Vec<TurnDto>, remote: Vec<TurnDto>, remote_newer: bool) -> Vec<TurnDto> {
+    let (mut merged, newer) = if remote_newer { (local, remote) } else { (remote, local) };
+    for turn in newer {
+        match merged
+            .iter_mut()
+            .find(|t| t.created_at == turn.created_at && t.role == turn.role)
+        {
+            Some(existing) => *existing = turn,
+            None => merged.push(turn),
+        }
+    }
+    merged.sort_by_key(|t| t.created_at);
+    merged
+}
+
+fn same_turns(a: &[TurnDto], b: &[TurnDto]) -> bool {
+    serde_json::to_string(a).ok() == serde_json::to_string(b).ok()
+}
+
+struct Session {
+    remote: Box<dyn Remote>,
+    cipher: XChaCha20Poly1305,
+    salt: Vec<u8>,
+    manifest: Manifest,
+    report: SyncReport,
+    // conversations written or removed here, for the sidebar
+    changed: Vec<String>,
+}
+
+impl Session {
+    async fn connect(cfg: &SyncConfig) -> Result<Self, String> {
+        let remote = remote_for(cfg)?;
+        let salt = match remote.get("salt").await? {
+            Some(salt) => salt,
+            None => {
+                let mut salt = vec![0u8; SALT_LEN];
+                OsRng.fill_bytes(&mut salt);
+                remote.put("salt", salt.clone()).await?;
+                salt
+            }
+        };
+        let cipher = derive_key(&cfg.passphrase, &salt)?;
+        let manifest = match remote.get("manifest").await? {
+            Some(data) => serde_json::from_slice(&open(&cipher, &data)?)
+                .map_err(|e| format!("Sync manifest is damaged: {}", e))?,
+            None => Manifest::new(),
+        };
+        Ok(Self {
+            remote,
+            cipher,
+            salt,
+            manifest,
+            report: SyncReport::default(),
+            changed: Vec::new(),
+        })
+    }
+
+    // item names never reach the server in the clear
+    fn object_name(&self, name: &str) -> String {
+        let mut salted = self.salt.clone();
+        salted.extend_from_slice(name.as_bytes());
+        sha256_hex(&salted)
+    }
+
+    fn hash_of(&self, name: &str) -> Option<&str> {
+        self.manifest.get(name).filter(|e| !e.deleted).map(|e| e.hash.as_str())
+    }
+
+    async fn download(&self, name: &str) -> Result<Option<Vec<u8>>, String> {
+        match self.remote.get(&self.object_name(name)).await? {
+            Some(data) => Ok(Some(open(&self.cipher, &data)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn upload(&mut self, name: &str, bytes: &[u8], modified: i64) -> Result<(), String> {
+        let sealed = seal(&self.cipher, bytes)?;
+        self.remote.put(&self.object_name(name), sealed).await?;
+        let hash = sha256_hex(bytes);
+        self.manifest.insert(
+            name.to_string(),
+            Entry {
+                modified,
+                hash,
+                deleted: false,
+            },
+        );
+        self.report.uploaded += 1;
+        Ok(())
+    }
+
+    async fn remove(&mut self, name: &str) -> Result<(), String> {
+        self.remote.delete(&self.object_name(name)).await?;
+        self.manifest.insert(
+            name.to_string(),
+            Entry {
+                modified: now_ms(),
+                hash: String::new(),
+                deleted: true,
+            },
+        );
+        Ok(())
+    }
+
+    async fn finish(&self) -> Result<(), String> {
+        let json = serde_json::to_vec(&self.manifest).map_err(|e| e.to_string())?;
+        self.remote.put("manifest", seal(&self.cipher, &json)?).await
+    }
+
+    async fn conversation(&mut self, dir: &Path, filename: &str) -> Result<(), String> {
+        let name = format!("conversations/{}", filename);
+        let path = dir.join(filename);
+        let local = std::fs::read(&path).ok();
+        let entry = self.manifest.get(&name).cloned();
+        let (local, entry) = match (local, entry) {
+            (None, None) => return Ok(()),
+            (Some(bytes), None) => return self.upload(&name, &bytes, modified_ms(&path)).await,
+            (None, Some(e)) if e.deleted => return Ok(()),
+            (None, Some(_)) => {
+                if let Some(bytes) = self.download(&name).await? {
+                    crate::history::atomic_write(&path, &bytes).map_err(|e| e.to_string())?;
+                    self.report.downloaded += 1;
+                    self.changed.push(filename.to_string());
+                }
+                return Ok(());
+            }
+            (Some(bytes), Some(e)) => (bytes, e),
+        };
+
+        if entry.deleted {
+            // edited here after it was deleted elsewhere: keep it
+            if modified_ms(&path) > entry.modified {
+                return self.upload(&name, &local, modified_ms(&path)).await;
+            }
+            std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+            self.report.deleted += 1;
+            self.changed.push(filename.to_string());
+            return Ok(());
+        }
+        if sha256_hex(&local) == entry.hash {
+            return Ok(());
+        }
+        let Some(remote) = self.download(&name).await? else {
+            return self.upload(&name, &local, modified_ms(&path)).await;
+        };
+
+        let jsonl = filename.ends_with(".jsonl");
+        let (local_turns, _) =
+            crate::history::parse_turns(&String::from_utf8_lossy(&local), jsonl);
+        let (remote_turns, _) =
+            crate::history::parse_turns(&String::from_utf8_lossy(&remote), jsonl);
+        let remote_newer = entry.modified > modified_ms(&path);
+        let merged = merge_turns(local_turns.clone(), remote_turns, remote_newer);
+        if !same_turns(&merged, &local_turns) {
+            crate::history::save(&path, &merged)?;
+            self.report.downloaded += 1;
+            self.changed.push(filename.to_string());
+        }
+        let bytes = std::fs::read(&path).map_err(|e| e.to_string())?;
+        if sha256_hex(&bytes) != entry.hash {
+            self.upload(&name, &bytes, modified_ms(&path)).await?;
+        }
+        Ok(())
+    }
+
+    async fn conversations(&mut self, app: &tauri::AppHandle) -> Result<(), String> {
+        let dir = crate::history_dir(app)?;
+        let local = conversation_files(&dir);
+        let mut state = load_state(app);
+
+        // gone since the last sync, and not just archived: deleted here
+        for filename in &state.conversations {
+            let name = format!("conversations/{}", filename);
+            let archived = dir.join("archive").join(filename).exists();
+            if !local.contains(filename) && !archived && self.hash_of(&name).is_some() {
+                if let Err(e) = self.remove(&name).await {
+                    self.report.errors.push(format!("{}: {}", filename, e));
+                }
+            }
+        }
+
+        let remote = self
+            .manifest
+            .keys()
+            .filter_map(|n| n.strip_prefix("conversations/"))
+            .map(str::to_string)
+            .collect::<BTreeSet<_>>();
+        for filename in local.union(&remote) {
+            // archived here: it stays in the archive, where it isn't synced
+            if !local.contains(filename) && dir.join("archive").join(filename).exists() {
+                continue;
+            }
+            if let Err(e) = self.conversation(&dir, filename).await {
+                self.report.errors.push(format!("{}: {}", filename, e));
+            }
+        }
+
+        state.conversations = conversation_files(&dir);
+        save_state(app, &state)
+    }
+
+    async fn memories(&mut self, app: &tauri::AppHandle) -> Result<(), String> {
+        let bytes = serde_json::to_vec(&crate::memory::load(app)).map_err(|e| e.to_string())?;
+        let known = self.hash_of("memories").map(str::to_string);
+        if known.as_deref() == Some(sha256_hex(&bytes).as_str()) {
+            return Ok(());
+        }
+        if known.is_some() {
+            if let Some(remote) = self.download("memories").await? {
+                let incoming: Vec<Memory> =
+                    serde_json::from_slice(&remote).map_err(|e| e.to_string())?;
+                if crate::memory::merge_synced(app, incoming)? {
+                    self.report.downloaded += 1;
+                }
+            }
+        }
+        // forgetting is not synced: a memory deleted here comes back if another device has it
+        let bytes = serde_json::to_vec(&crate::memory::load(app)).map_err(|e| e.to_string())?;
+        if known.as_deref() != Some(sha256_hex(&bytes).as_str()) {
+            self.upload("memories", &bytes, now_ms()).await?;
+        }
+        Ok(())
+    }
+
+    // the whole set of shared settings from whichever device changed them last
+    async fn settings(&mut self, app: &tauri::AppHandle) -> Result<(), String> {
+        let values = crate::settings::synced_values(app)?;
+        let bytes = serde_json::to_vec(&values).map_err(|e| e.to_string())?;
+        let local_modified = crate::config_path(app).map(|p| modified_ms(&p)).unwrap_or(0);
+        let entry = self.manifest.get("settings").cloned();
+        match entry {
+            Some(e) if e.hash == sha256_hex(&bytes) => Ok(()),
+            Some(e) if e.modified > local_modified => {
+                if let Some(remote) = self.download("settings").await? {
+                    let values = serde_json::from_slice(&remote).map_err(|e| e.to_string())?;
+                    crate::settings::apply_synced(app, values)?;
+                    self.report.settings_changed = true;
+                }
+                Ok(())
+            }
+            _ => self.upload("settings", &bytes, local_modified).await,
+        }
+    }
+}
+
+// one sync at a time, whether started by the timer or from the settings
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+struct Running;
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+async fn run(app: &tauri::AppHandle, cfg: &SyncConfig) -> Result<SyncReport, String> {
+    if cfg.passphrase.trim().is_empty() {
+        return Err("Set a sync passphrase first".into());
+    }
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err("A sync is already running".into());
+    }
+    let _running = Running;
+
+    let mut session = Session::connect(cfg).await?;
+    session.conversations(app).await?;
+    if let Err(e) = session.memories(app).await {
+        session.report.errors.push(format!("memories: {}", e));
+    }
+    if let Err(e) = session.settings(app).await {
+        session.report.errors.push(format!("settings: {}", e));
+    }
+    session.finish().await?;
+
+    if !session.changed.is_empty() {
+        events::emit(
+            app,
+            HistoryChanged {
+                filenames: session.changed.clone(),
+            },
+        );
+    }
+    println!("[DEBUG] Sync finished: {:?}", session.report);
+    events::emit(app, session.report.clone());
+    Ok(session.report)
+}
+
+// Called once at startup; syncs on the configured interval while sync is enabled
+pub fn init(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // let startup finish before the first sync
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        loop {
+            let minutes = match crate::cmd_load_config(app.clone()).map(|c| c.sync) {
+                Ok(cfg) if cfg.enabled => {
+                    if let Err(e) = run(&app, &cfg).await {
+                        eprintln!("[WARN] Sync failed: {}", e);
+                    }
+                    cfg.interval_minutes
+                }
+                // check again shortly in case it gets switched on
+                _ => 1,
+            };
+            tokio::time::sleep(Duration::from_secs(60 * minutes.max(1))).await;
+        }
+    });
+}
+
+// Syncs right away, even if the periodic sync is switched off
+#[tauri::command]
+pub async fn cmd_sync_now(app: tauri::AppHandle) -> Result<SyncReport, String> {
+    let cfg = crate::cmd_load_config(app.clone())?.sync;
+    run(&app, &cfg).await
+}
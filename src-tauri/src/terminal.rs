@@ -0,0 +1,289 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Terminal: "in the terminal, ping google.com" has the LLM write a command line, which
+// is shown and read out and only run after a yes (see confirm.rs). The output is posted
+// in the chat, cut to `max_output_chars`, and summarized aloud. Off by default.
+//
+// Commands don't go through a shell: the program is started directly with its
+// arguments, so pipes, redirection and chaining don't do anything. The program has to
+// be on the `allowed` list and is looked up on PATH only, never by a path in the
+// command. That is no sandbox, though: an allowed program does whatever its arguments
+// say, which is why programs that can start others (git, interpreters, package
+// managers, ...) aren't on the default list.
+
+use crate::events::{self, NewMessage, Role};
+use crate::models::Config;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::Manager;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct TerminalConfig {
+    pub enabled: bool,
+    // program names, without ".exe"; anything that can change things or start other
+    // programs (git, package managers, interpreters, ...) is better left off
+    pub allowed: Vec<String>,
+    // where commands run; empty is the home folder
+    pub working_dir: String,
+    pub timeout_seconds: u64,
+    // longer output is cut before it is shown and summarized
+    pub max_output_chars: usize,
+}
+
+impl Default for TerminalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allowed: [
+                "ping",
+                "ipconfig",
+                "nslookup",
+                "tracert",
+                "netstat",
+                "whoami",
+                "hostname",
+                "systeminfo",
+                "tasklist",
+                "where",
+            ]
+            .iter()
+            .map(|p| p.to_string())
+            .collect(),
+            working_dir: String::new(),
+            timeout_seconds: 30,
+            max_output_chars: 4000,
+        }
+    }
+}
+
+// left for a shell to interpret, which commands here never get
+const SHELL_SYNTAX: &[&str] = &["|", "||", "&", "&&", ";", ">", ">>", "<", "2>", "2>&1"];
+
+// Splits a command line into the program and its arguments; double quotes group words.
+// None when a quote isn't closed.
+fn split_args(line: &str) -> Option<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut started = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    args.push(std::mem::take(&mut current));
+                    started = false;
+                }
+            }
+            c => {
+                current.push(c);
+                started = true;
+            }
+        }
+    }
+    if quoted {
+        return None;
+    }
+    if started {
+        args.push(current);
+    }
+    Some(args)
+}
+
+// "C:\Tools\Git.EXE" as "git"
+fn program_name(program: &str) -> String {
+    let file = program
+        .rsplit(['\\', '/'])
+        .next()
+        .unwrap_or(program)
+        .to_lowercase();
+    file.strip_suffix(".exe").unwrap_or(&file).to_string()
+}
+
+// The program's file in one of the PATH folders; the working folder isn't searched, so
+// a "git.exe" dropped there isn't picked up
+fn resolve(program: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    let files: Vec<String> =
+        if cfg!(target_os = "windows") && Path::new(program).extension().is_none() {
+            vec![format!("{}.exe", program), format!("{}.com", program)]
+        } else {
+            vec![program.to_string()]
+        };
+    std::env::split_paths(&path)
+        .filter(|dir| dir.is_absolute())
+        .flat_map(|dir| files.iter().map(move |f| dir.join(f)))
+        .find(|p| p.is_file())
+}
+
+// The program and arguments to run, or why not
+fn check(line: &str, config: &TerminalConfig) -> Result<(PathBuf, Vec<String>), String> {
+    let mut args = split_args(line).ok_or("the quotes in it don't match")?;
+    if args.is_empty() {
+        return Err("it's empty".to_string());
+    }
+    let program = args.remove(0);
+    if let Some(syntax) = args.iter().find(|a| SHELL_SYNTAX.contains(&a.as_str())) {
+        return Err(format!("it needs a shell for \"{}\"", syntax));
+    }
+    // the allowed list means the installed program, not any file with that name
+    if program.contains(['\\', '/']) {
+        return Err("it points at a program file instead of naming the program".to_string());
+    }
+    let name = program_name(&program);
+    if !config.allowed.iter().any(|a| program_name(a) == name) {
+        return Err(format!(
+            "{} isn't one of the programs I'm allowed to run",
+            name
+        ));
+    }
+    let path =
+        resolve(&program).ok_or_else(|| format!("I can't find {} on this computer", name))?;
+    Ok((path, args))
+}
+
+// `text` cut to `max` characters, with a note when something was left out
+fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!(
+            "{}\n[... cut, {} characters in total]",
+            &text[..end],
+            text.chars().count()
+        ),
+        None => text.to_string(),
+    }
+}
+
+fn working_dir(app: &tauri::AppHandle, config: &TerminalConfig) -> Option<PathBuf> {
+    let configured = config.working_dir.trim();
+    if !configured.is_empty() {
+        return Some(PathBuf::from(configured));
+    }
+    app.path().home_dir().ok()
+}
+
+// Runs the command; its output (stdout, then stderr) and exit code
+async fn execute(
+    program: &Path,
+    args: &[String],
+    dir: Option<PathBuf>,
+    config: &TerminalConfig,
+) -> Result<(String, Option<i32>), String> {
+    let mut cmd = tokio::process::Command::new(program);
+    cmd.args(args)
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true);
+    if let Some(dir) = dir {
+        cmd.current_dir(dir);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let timeout = Duration::from_secs(config.timeout_seconds.max(1));
+    let out = tokio::time::timeout(timeout, cmd.output())
+        .await
+        .map_err(|_| format!("it was still running after {} seconds", timeout.as_secs()))?
+        .map_err(|e| format!("it couldn't start: {}", e))?;
+    let mut text = String::from_utf8_lossy(&out.stdout).trim_end().to_string();
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    if !stderr.trim().is_empty() {
+        if !text.is_empty() {
+            text.push_str("\n\n");
+        }
+        text.push_str(stderr.trim_end());
+    }
+    Ok((text, out.status.code()))
+}
+
+async fn run(
+    app: tauri::AppHandle,
+    line: String,
+    (program, args): (PathBuf, Vec<String>),
+    config: Config,
+) -> String {
+    let terminal = &config.terminal;
+    println!("[INFO] Running terminal command: {}", line);
+    let dir = working_dir(&app, terminal);
+    let (output, code) = match execute(&program, &args, dir, terminal).await {
+        Ok(result) => result,
+        Err(e) => return format!("The command didn't finish: {}.", e),
+    };
+    let output = truncate(&output, terminal.max_output_chars);
+    let shown = if output.is_empty() {
+        "(no output)"
+    } else {
+        output.as_str()
+    };
+    let status = match code {
+        Some(0) => String::new(),
+        Some(code) => format!(" (exit code {})", code),
+        None => " (stopped)".to_string(),
+    };
+    let message = format!("`{}`{}\n\n```\n{}\n```", line, status, shown);
+    events::emit(
+        &app,
+        NewMessage::new(Role::Assistant, &message).with("localCommand", true),
+    );
+
+    if output.is_empty() {
+        return match code {
+            Some(0) => "Done, it didn't print anything.".to_string(),
+            _ => "It failed without saying why.".to_string(),
+        };
+    }
+    let summarized = format!("{}{}", line, status);
+    match crate::send_to_llm::summarize_command_output(&summarized, &output, &config).await {
+        Ok(summary) if !summary.trim().is_empty() => summary.trim().to_string(),
+        Ok(_) | Err(_) => "It's done, the output is in the chat.".to_string(),
+    }
+}
+
+// The proposed command, waiting for a yes; None when the terminal tool is off
+pub async fn answer(prompt: &str, app: &tauri::AppHandle, config: &Config) -> Option<String> {
+    let terminal = &config.terminal;
+    if !terminal.enabled {
+        return None;
+    }
+    let line = match crate::send_to_llm::propose_command(prompt, &terminal.allowed, config).await {
+        Ok(Some(line)) => line,
+        Ok(None) => {
+            return Some("I can't do that with the programs I'm allowed to run.".to_string())
+        }
+        Err(e) => {
+            eprintln!("[ERROR] Failed to get a command from the model: {}", e);
+            return Some("Sorry, I couldn't work out a command for that.".to_string());
+        }
+    };
+    let command = match check(&line, terminal) {
+        Ok(command) => command,
+        Err(why) => return Some(format!("I won't run {}, because {}.", line, why)),
+    };
+    let question = format!("I'd run: {}.", line);
+    Some(crate::confirm::ask(
+        "run that command",
+        &question,
+        run(app.clone(), line, command, config.clone()),
+    ))
+}
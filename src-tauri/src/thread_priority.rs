@@ -0,0 +1,112 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Priorities and CPU cores for the audio path, for machines where capture or the wake
+// word glitch under load (compiling, rendering). Everything stays at the system's
+// defaults unless set; each thread applies its own settings when it starts.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use winapi::um::processthreadsapi::{GetCurrentThread, SetThreadPriority};
+use winapi::um::winbase::{
+    SetThreadAffinityMask, THREAD_PRIORITY_ABOVE_NORMAL, THREAD_PRIORITY_BELOW_NORMAL,
+    THREAD_PRIORITY_HIGHEST, THREAD_PRIORITY_NORMAL, THREAD_PRIORITY_TIME_CRITICAL,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    BelowNormal,
+    Normal,
+    AboveNormal,
+    High,
+    // ahead of nearly everything else; can make the rest of the system sluggish
+    Realtime,
+}
+
+impl Priority {
+    fn win32(self) -> i32 {
+        (match self {
+            Priority::BelowNormal => THREAD_PRIORITY_BELOW_NORMAL,
+            Priority::Normal => THREAD_PRIORITY_NORMAL,
+            Priority::AboveNormal => THREAD_PRIORITY_ABOVE_NORMAL,
+            Priority::High => THREAD_PRIORITY_HIGHEST,
+            Priority::Realtime => THREAD_PRIORITY_TIME_CRITICAL,
+        }) as i32
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ThreadPriorityConfig {
+    // the thread that receives microphone audio
+    pub audio_input: Priority,
+    // the voice loop, which runs the wake word, recording and playback
+    pub wake_word: Priority,
+    // CPU cores (counting from 0) the audio threads may run on; empty for any
+    pub audio_cores: Vec<usize>,
+}
+
+impl Default for ThreadPriorityConfig {
+    fn default() -> Self {
+        Self {
+            audio_input: Priority::Normal,
+            wake_word: Priority::Normal,
+            audio_cores: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum AudioThread {
+    Input,
+    WakeWord,
+}
+
+static CONFIG: Mutex<Option<ThreadPriorityConfig>> = Mutex::new(None);
+
+// Called when the voice loop starts; threads started after that use these settings
+pub fn configure(cfg: &ThreadPriorityConfig) {
+    *CONFIG.lock().unwrap() = Some(cfg.clone());
+}
+
+pub fn set_current(priority: Priority) {
+    if unsafe { SetThreadPriority(GetCurrentThread(), priority.win32()) } == 0 {
+        eprintln!("[WARN] Failed to set thread priority to {:?}", priority);
+    }
+}
+
+// Applies the configured priority and cores to the calling thread
+pub fn apply(thread: AudioThread) {
+    let Some(cfg) = CONFIG.lock().unwrap().clone() else {
+        return;
+    };
+    let priority = match thread {
+        AudioThread::Input => cfg.audio_input,
+        AudioThread::WakeWord => cfg.wake_word,
+    };
+    set_current(priority);
+
+    let mask = cfg
+        .audio_cores
+        .iter()
+        .filter(|&&core| core < usize::BITS as usize)
+        .fold(0usize, |mask, core| mask | (1 << core));
+    if mask != 0 && unsafe { SetThreadAffinityMask(GetCurrentThread(), mask) } == 0 {
+        eprintln!("[WARN] Failed to pin the {:?} thread to cores {:?}", thread, cfg.audio_cores);
+    }
+}
@@ -0,0 +1,324 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Countdown timers: "set a timer for 10 minutes", "set a pasta timer for 8 minutes",
+// "how much time is left", "cancel the pasta timer". Cooking mode starts them from the
+// recipe too. When one runs out Jarvis beeps and says which one; timers only last as
+// long as the app runs.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use ts_rs::TS;
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct Timer {
+    #[ts(type = "number")]
+    pub id: u64,
+    // e.g. "pasta" or "step 3"; may be empty
+    pub label: String,
+    #[ts(type = "number")]
+    pub seconds: u64,
+    // unix milliseconds
+    #[ts(type = "number")]
+    pub ends_at: i64,
+}
+
+static TIMERS: Mutex<Vec<Timer>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+// "10 minutes", "1.5 hours", "20 to 25 minutes" (the shorter one, to check early)
+static DURATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(\d+(?:[.,]\d+)?|an?|one|two|three|four|five|six|seven|eight|nine|ten|half an?)",
+        r"(?:\s*(?:-|–|to|or)\s*\d+(?:[.,]\d+)?)?\s*",
+        r"(hours?|hrs?|minutes?|mins?|seconds?|secs?)\b"
+    ))
+    .expect("Failed to compile duration regex")
+});
+
+static SET: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:set|start)\s+(?:a|an|the|my)?\s*(?:(.+?)\s+)?timer\s+(?:for|of|to)\s+(.+?)",
+        r"[?.!]*\s*$"
+    ))
+    .expect("Failed to compile set timer regex")
+});
+
+static CANCEL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:cancel|stop|delete|remove|clear)\s+(?:the|my|all)?\s*",
+        r"(?:(.+?)\s+)?timers?\b"
+    ))
+    .expect("Failed to compile cancel timer regex")
+});
+
+static LEFT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:how (?:much|long)(?: time)? (?:is |'s )?left|time left|time remaining",
+        r"|how(?:'s| is| are) (?:the|my) (?:.+?\s+)?timers?|what timers)\b"
+    ))
+    .expect("Failed to compile time left regex")
+});
+
+fn amount(word: &str) -> Option<f64> {
+    let word = word.to_lowercase();
+    Some(match word.as_str() {
+        "a" | "an" | "one" => 1.0,
+        "two" => 2.0,
+        "three" => 3.0,
+        "four" => 4.0,
+        "five" => 5.0,
+        "six" => 6.0,
+        "seven" => 7.0,
+        "eight" => 8.0,
+        "nine" => 9.0,
+        "ten" => 10.0,
+        w if w.starts_with("half") => 0.5,
+        w => w.replace(',', ".").parse().ok()?,
+    })
+}
+
+fn seconds_of(caps: &regex::Captures) -> Option<u64> {
+    let amount = amount(&caps[1])?;
+    let unit = caps[2].to_lowercase();
+    let scale = if unit.starts_with('h') {
+        3600.0
+    } else if unit.starts_with('m') {
+        60.0
+    } else {
+        1.0
+    };
+    let seconds = (amount * scale).round() as u64;
+    (seconds > 0).then_some(seconds)
+}
+
+// All durations in the text added up: "1 hour and 30 minutes" is 5400
+pub fn parse_duration(text: &str) -> Option<u64> {
+    let total: u64 = DURATION.captures_iter(text).filter_map(|c| seconds_of(&c)).sum();
+    (total > 0).then_some(total)
+}
+
+// Only the first duration, for recipe steps that mention several
+pub fn first_duration(text: &str) -> Option<u64> {
+    DURATION.captures_iter(text).find_map(|c| seconds_of(&c))
+}
+
+// "8 minutes", "1 hour and 5 minutes", "45 seconds"
+pub fn spoken_duration(seconds: u64) -> String {
+    let plural = |n: u64, unit: &str| {
+        if n == 1 {
+            format!("1 {}", unit)
+        } else {
+            format!("{} {}s", n, unit)
+        }
+    };
+    let (h, m, s) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    let mut parts = Vec::new();
+    if h > 0 {
+        parts.push(plural(h, "hour"));
+    }
+    if m > 0 {
+        parts.push(plural(m, "minute"));
+    }
+    // seconds only matter for short timers
+    if s > 0 && h == 0 && m < 5 {
+        parts.push(plural(s, "second"));
+    }
+    if parts.is_empty() {
+        return "less than a second".to_string();
+    }
+    parts.join(" and ")
+}
+
+fn name(timer: &Timer) -> String {
+    if timer.label.is_empty() {
+        "the timer".to_string()
+    } else {
+        format!("the {} timer", timer.label)
+    }
+}
+
+fn remaining(timer: &Timer) -> u64 {
+    let left = timer.ends_at - chrono::Utc::now().timestamp_millis();
+    (left.max(0) as u64).div_ceil(1000)
+}
+
+fn emit_changed(app: &tauri::AppHandle) {
+    crate::events::emit(app, crate::events::TimersChanged(list()));
+}
+
+async fn ring(app: &tauri::AppHandle, timer: &Timer) {
+    let Ok(cfg) = crate::cmd_load_config(app.clone()) else {
+        return;
+    };
+    let cfg = crate::to_run_config(cfg);
+    for _ in 0..3 {
+        let beep = crate::audio_output::tone_once(
+            cfg.voice_output_device(),
+            988.0,
+            Duration::from_millis(250),
+        );
+        if let Err(e) = beep.await {
+            eprintln!("[ERROR] Failed to play timer alarm: {}", e);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(150)).await;
+    }
+    let name = name(timer);
+    let text = format!("{}{} is done.", name[..1].to_uppercase(), &name[1..]);
+    crate::events::emit(
+        app,
+        crate::events::NewMessage::new(crate::events::Role::Assistant, &text)
+            .with("localCommand", true),
+    );
+    if let Err(e) = crate::tts::speak_with(&cfg, &text).await {
+        eprintln!("[ERROR] Failed to announce timer: {}", e);
+    }
+}
+
+pub fn start(app: &tauri::AppHandle, label: &str, seconds: u64) -> Timer {
+    let timer = Timer {
+        id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        label: label.trim().to_string(),
+        seconds,
+        ends_at: chrono::Utc::now().timestamp_millis() + seconds as i64 * 1000,
+    };
+    TIMERS.lock().unwrap().push(timer.clone());
+    emit_changed(app);
+    let app = app.clone();
+    let id = timer.id;
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(seconds)).await;
+        let fired = {
+            let mut timers = TIMERS.lock().unwrap();
+            let index = timers.iter().position(|t| t.id == id);
+            index.map(|i| timers.remove(i))
+        };
+        // None when it was cancelled in the meantime
+        if let Some(timer) = fired {
+            emit_changed(&app);
+            ring(&app, &timer).await;
+        }
+    });
+    timer
+}
+
+pub fn list() -> Vec<Timer> {
+    TIMERS.lock().unwrap().clone()
+}
+
+pub fn cancel(id: u64) -> bool {
+    let mut timers = TIMERS.lock().unwrap();
+    let before = timers.len();
+    timers.retain(|t| t.id != id);
+    timers.len() != before
+}
+
+// the latest timer with that label, or the latest of all when no label is given
+fn find_by_label(label: &str) -> Option<Timer> {
+    let label = label.trim().to_lowercase();
+    let timers = TIMERS.lock().unwrap();
+    if label.is_empty() {
+        return timers.last().cloned();
+    }
+    timers
+        .iter()
+        .rev()
+        .find(|t| t.label.to_lowercase() == label)
+        .cloned()
+}
+
+// The spoken answer to a timer command, or None when the prompt isn't one
+pub fn answer(prompt: &str, app: &tauri::AppHandle) -> Option<String> {
+    if let Some(m) = SET.captures(prompt) {
+        let seconds = parse_duration(&m[2])?;
+        let label = m.get(1).map(|l| l.as_str()).unwrap_or("");
+        let timer = start(app, label, seconds);
+        let what = if timer.label.is_empty() {
+            "A timer".to_string()
+        } else {
+            format!("A {} timer", timer.label)
+        };
+        return Some(format!("{} for {}, starting now.", what, spoken_duration(seconds)));
+    }
+    if let Some(m) = CANCEL.captures(prompt) {
+        let timers = list();
+        if timers.is_empty() {
+            return Some("There are no timers running.".to_string());
+        }
+        if prompt.to_lowercase().contains("all") {
+            TIMERS.lock().unwrap().clear();
+            emit_changed(app);
+            return Some("All timers are cancelled.".to_string());
+        }
+        let label = m.get(1).map(|l| l.as_str()).unwrap_or("");
+        let Some(timer) = find_by_label(label) else {
+            return Some(format!("There's no {} timer.", label.trim()));
+        };
+        cancel(timer.id);
+        emit_changed(app);
+        return Some(format!("Cancelled {}.", name(&timer)));
+    }
+    if LEFT.is_match(prompt) {
+        let timers = list();
+        if timers.is_empty() {
+            return Some("There are no timers running.".to_string());
+        }
+        let lines: Vec<String> = timers
+            .iter()
+            .map(|t| {
+                let name = name(t);
+                let left = spoken_duration(remaining(t));
+                format!("{}{} has {} left.", name[..1].to_uppercase(), &name[1..], left)
+            })
+            .collect();
+        return Some(lines.join(" "));
+    }
+    None
+}
+
+#[tauri::command]
+pub fn cmd_list_timers() -> Vec<Timer> {
+    list()
+}
+
+#[tauri::command]
+pub fn cmd_start_timer(
+    app: tauri::AppHandle,
+    label: String,
+    seconds: u64,
+) -> Result<Timer, String> {
+    if seconds == 0 {
+        return Err("A timer needs a duration".into());
+    }
+    Ok(start(&app, &label, seconds))
+}
+
+#[tauri::command]
+pub fn cmd_cancel_timer(app: tauri::AppHandle, id: u64) -> bool {
+    let cancelled = cancel(id);
+    if cancelled {
+        emit_changed(&app);
+    }
+    cancelled
+}
@@ -0,0 +1,107 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Collects the decision path of each voice command (which built-in command matched,
+// what was added to the prompt, what was sent, what was cut) into an InteractionTrace.
+// The voice loop runs inside `scope`, so code deep in the pipeline can add to the
+// trace of the command being handled; outside it the calls do nothing. The last
+// traces are kept in memory only.
+
+use crate::events::{self, InteractionTrace};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+const KEEP: usize = 20;
+
+static APP: OnceLock<tauri::AppHandle> = OnceLock::new();
+static RECENT: Mutex<VecDeque<InteractionTrace>> = Mutex::new(VecDeque::new());
+
+tokio::task_local! {
+    static CURRENT: RefCell<Option<InteractionTrace>>;
+}
+
+// Called once at startup
+pub fn init(app: &tauri::AppHandle) {
+    let _ = APP.set(app.clone());
+}
+
+// Runs `f` with room for a trace; the voice loop runs in here
+pub async fn scope<F: Future>(f: F) -> F::Output {
+    CURRENT.scope(RefCell::new(None), f).await
+}
+
+// Changes the trace being collected, if any
+pub fn update(f: impl FnOnce(&mut InteractionTrace)) {
+    let _ = CURRENT.try_with(|current| {
+        if let Some(trace) = current.borrow_mut().as_mut() {
+            f(trace);
+        }
+    });
+}
+
+// Notes something that was done for the command, e.g. a link that was read
+pub fn tool(what: &str) {
+    update(|t| t.tools.push(what.to_string()));
+}
+
+// Finishes the trace when dropped, however the command ended
+pub struct Trace {
+    started: Instant,
+}
+
+// Starts the trace of one command
+pub fn begin(source: &str, input: &str, persona: Option<&str>) -> Trace {
+    let trace = InteractionTrace {
+        started_at: chrono::Utc::now().timestamp_millis(),
+        source: source.to_string(),
+        input: input.to_string(),
+        persona: persona.map(str::to_string),
+        ..Default::default()
+    };
+    let _ = CURRENT.try_with(|current| *current.borrow_mut() = Some(trace));
+    Trace {
+        started: Instant::now(),
+    }
+}
+
+impl Drop for Trace {
+    fn drop(&mut self) {
+        let Ok(Some(mut trace)) = CURRENT.try_with(|current| current.borrow_mut().take()) else {
+            return;
+        };
+        trace.duration_ms = self.started.elapsed().as_millis() as u64;
+        {
+            let mut recent = RECENT.lock().unwrap();
+            if recent.len() >= KEEP {
+                recent.pop_front();
+            }
+            recent.push_back(trace.clone());
+        }
+        if let Some(app) = APP.get() {
+            events::emit(app, trace);
+        }
+    }
+}
+
+// The last traces, newest first
+#[tauri::command]
+pub fn cmd_get_interaction_traces() -> Vec<InteractionTrace> {
+    RECENT.lock().unwrap().iter().rev().cloned().collect()
+}
@@ -15,7 +15,11 @@ You should have received a copy of the GNU Affero General Public License
 along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use crate::events::{self, CodeCopied, MessageMeta, NewMessage, Role};
+use crate::intent;
+use crate::media::{self, NowPlaying};
 use crate::models::Config;
+use crate::triggers::{CommandTriggers, Triggers};
 use crate::tts;
 use anyhow::{Context, Result};
 use arboard::Clipboard;
@@ -25,31 +29,243 @@ use elevenlabs_rs::Model;
 use png::{BitDepth, ColorType, Encoder};
 use regex::Regex;
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{str};
-use tauri::{Manager, Emitter};
 use std::{thread::sleep, time::Duration};
 use winapi::um::winuser::{
     keybd_event, KEYEVENTF_KEYUP, VK_MEDIA_NEXT_TRACK, VK_MEDIA_PLAY_PAUSE, VK_MEDIA_PREV_TRACK,
 };
-// copies text to clipboard between [[copy]] and [[/copy]] tags
-pub fn copy_to_clipboard_function_for_llm(text: &str) -> Result<String> {
+
+// said instead of reading code aloud
+const CODE_IN_CHAT_NOTE: &str = "The code is in the chat.";
+
+// what goes on the clipboard when an answer has more than one [[copy]] block
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MultipleBlocks {
+    First,
+    Concatenate,
+    Numbered,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CopyBlocksConfig {
+    // false leaves the clipboard alone; the code is still shown in the chat
+    pub auto_copy: bool,
+    pub multiple: MultipleBlocks,
+}
+
+impl Default for CopyBlocksConfig {
+    fn default() -> Self {
+        Self {
+            auto_copy: true,
+            multiple: MultipleBlocks::Concatenate,
+        }
+    }
+}
+
+fn copy_regex() -> Result<Regex> {
+    Regex::new(r"(?s)\[\[copy\]\](.*?)\[\[/copy\]\]").context("Failed to compile copy regex")
+}
+
+// the contents of every [[copy]] block, in order
+pub fn copy_blocks(text: &str) -> Vec<String> {
+    let Ok(re) = copy_regex() else {
+        return Vec::new();
+    };
+    re.captures_iter(text)
+        .map(|c| c[1].trim().to_string())
+        .filter(|b| !b.is_empty())
+        .collect()
+}
+
+// the answer as shown in the chat: copy tags removed, code kept
+pub fn strip_copy_tags(text: &str) -> String {
+    copy_regex()
+        .map(|re| re.replace_all(text, |c: &regex::Captures| c[1].trim().to_string()).into_owned())
+        .unwrap_or_else(|_| text.to_string())
+        .trim()
+        .to_string()
+}
+
+// copies text to clipboard between [[copy]] and [[/copy]] tags; returns what was copied
+pub fn copy_to_clipboard_function_for_llm(
+    text: &str,
+    config: &CopyBlocksConfig,
+) -> Result<Option<CodeCopied>> {
     println!("[DEBUG] Entered copy_to_clipboard_function_for_llm");
-    let re = Regex::new(r"(?s)\[\[copy\]\](.*?)\[\[/copy\]\]")
-        .context("Failed to compile copy regex")?;
+    let blocks = copy_blocks(text);
+    if blocks.is_empty() || !config.auto_copy {
+        println!("[DEBUG] Finished copy_to_clipboard_function_for_llm");
+        return Ok(None);
+    }
 
+    let (copied, content) = match config.multiple {
+        MultipleBlocks::First => (1, blocks[0].clone()),
+        MultipleBlocks::Concatenate => (blocks.len(), blocks.join("\n\n")),
+        MultipleBlocks::Numbered if blocks.len() > 1 => (
+            blocks.len(),
+            blocks
+                .iter()
+                .enumerate()
+                .map(|(i, b)| format!("--- {} ---\n{}", i + 1, b))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        ),
+        MultipleBlocks::Numbered => (1, blocks[0].clone()),
+    };
     let mut clipboard = Clipboard::new().context("Failed to initialize clipboard")?;
+    clipboard
+        .set_text(content.clone())
+        .context("Failed to set clipboard text")?;
+    println!("[DEBUG] Finished copy_to_clipboard_function_for_llm");
+    Ok(Some(CodeCopied {
+        copied,
+        total: blocks.len(),
+        text: content,
+    }))
+}
 
-    if let Some(cap) = re.captures(text) {
-        let content = cap.get(1).map(|m| m.as_str()).unwrap_or_default();
-        clipboard
-            .set_text(content.to_string())
-            .context("Failed to set clipboard text")?;
-        println!("[DEBUG] Finished copy_to_clipboard_function_for_llm");
-        return Ok(re.replace_all(text, "").into_owned());
+// The answer with code removed, plus a sentence saying where the code went
+pub fn speakable_summary(text: &str, copied: Option<&CodeCopied>) -> String {
+    let fence = Regex::new(r"(?s)```.*?(```|$)").expect("Failed to compile fence regex");
+    let copy = copy_regex().expect("Failed to compile copy regex");
+    let had_code = copy.is_match(text) || fence.is_match(text);
+    if !had_code {
+        return text.to_string();
+    }
+    let rest = copy.replace_all(text, "");
+    let rest = fence.replace_all(&rest, "");
+    add_code_note(&rest, copied)
+}
+
+// "Here's the function." -> "Here's the function. I've copied the code to your clipboard."
+pub fn add_code_note(text: &str, copied: Option<&CodeCopied>) -> String {
+    let rest = text.trim().trim_end_matches(':').trim_end();
+    let note = match copied {
+        None => CODE_IN_CHAT_NOTE.to_string(),
+        Some(c) if c.total == 1 => "I've copied the code to your clipboard.".to_string(),
+        Some(c) if c.copied == 1 => format!(
+            "I've copied the first of {} code blocks to your clipboard, the rest are in the chat.",
+            c.total
+        ),
+        Some(c) => format!("I've copied all {} code blocks to your clipboard.", c.copied),
+    };
+    if rest.is_empty() {
+        note
+    } else if rest.ends_with(['.', '!', '?']) {
+        format!("{} {}", rest, note)
     } else {
-        println!("[DEBUG] Finished copy_to_clipboard_function_for_llm");
-        return Ok(text.to_string());
+        format!("{}. {}", rest, note)
+    }
+}
+
+// clipboard text, or an image as a PNG data URI the LLM can look at; None when empty
+pub fn clipboard_as_prompt_text() -> Option<String> {
+    let mut clipboard = match Clipboard::new() {
+        Ok(cb) => cb,
+        Err(err) => {
+            eprintln!("[DEBUG] Clipboard init error: {}", err);
+            return None;
+        }
+    };
+
+    // try to paste text
+    if let Ok(txt) = clipboard.get_text() {
+        return Some(txt);
+    }
+
+    // if text failed, try image
+    if let Ok(img) = clipboard.get_image() {
+        let mut buf = Vec::new();
+
+        let mut encoder = Encoder::new(&mut buf, img.width as u32, img.height as u32);
+        encoder.set_color(ColorType::Rgba);
+        encoder.set_depth(BitDepth::Eight);
+
+        match encoder.write_header() {
+            Ok(mut writer) => {
+                if let Err(e) = writer.write_image_data(&img.bytes) {
+                    eprintln!("[DEBUG] Failed to write PNG data: {}", e);
+                }
+            }
+            Err(e) => {
+                eprintln!("[DEBUG] Failed to write PNG header: {}", e);
+            }
+        }
+
+        let b64 = general_purpose::STANDARD.encode(&buf);
+        return Some(format!("data:image/png;base64,{}", b64));
+    }
+
+    None
+}
+
+// screenshots older than this are not what "this screenshot" means
+const RECENT_SCREENSHOT_SECS: u64 = 10 * 60;
+
+// Newest image in the usual screenshot folders, if it was taken in the last few minutes
+fn recent_screenshot() -> Option<String> {
+    let home = std::env::var("USERPROFILE")
+        .or_else(|_| std::env::var("HOME"))
+        .ok()?;
+    let home = std::path::PathBuf::from(home);
+    let folders = [
+        home.join("Pictures").join("Screenshots"),
+        home.join("Pictures"),
+        home.join("Desktop"),
+    ];
+    let newest = folders
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|rd| rd.flatten())
+        .filter_map(|e| {
+            let path = e.path();
+            let ext = path.extension()?.to_str()?.to_lowercase();
+            let mime = match ext.as_str() {
+                "png" => "png",
+                "jpg" | "jpeg" => "jpeg",
+                _ => return None,
+            };
+            let modified = e.metadata().ok()?.modified().ok()?;
+            Some((modified, path, mime))
+        })
+        .max_by_key(|(modified, _, _)| *modified)?;
+    let age = newest.0.elapsed().ok()?;
+    if age.as_secs() > RECENT_SCREENSHOT_SECS {
+        return None;
+    }
+    let bytes = std::fs::read(&newest.1).ok()?;
+    Some(format!(
+        "data:image/{};base64,{}",
+        newest.2,
+        general_purpose::STANDARD.encode(bytes)
+    ))
+}
+
+// "what's in the image on my clipboard", "explain this screenshot": attaches the clipboard
+// image, or else a screenshot taken in the last few minutes, so spoken prompts can use
+// images the same way pasted ones do
+pub fn attach_referenced_image(prompt: &str) -> String {
+    let re = Regex::new(
+        r"(?i)\b(?:(?:image|picture|photo|screenshot|screen\s?shot)\s+(?:on|in|from)\s+(?:my|the)\s+clipboard|this\s+(?:screenshot|screen\s?shot|image|picture|photo)|(?:my|the|that)\s+(?:last|latest|recent)\s+(?:screenshot|screen\s?shot))\b",
+    )
+    .expect("Failed to compile image reference regex");
+    if !re.is_match(prompt) || prompt.contains("data:image/") {
+        return prompt.to_string();
+    }
+    let image = clipboard_as_prompt_text()
+        .filter(|c| c.starts_with("data:image/"))
+        .or_else(recent_screenshot);
+    match image {
+        Some(uri) => {
+            println!("[DEBUG] Attached referenced image to prompt");
+            crate::trace::tool("attached the referenced image");
+            format!("{}\n{}", prompt, uri)
+        }
+        None => prompt.to_string(),
     }
 }
 
@@ -82,46 +298,11 @@ pub fn paste_clipboard_instead_of_text(prompt: &str) -> String {
     };
 
     if re.is_match(prompt) {
-        let mut clipboard = match Clipboard::new() {
-            Ok(cb) => cb,
-            Err(err) => {
-                eprintln!("[DEBUG] Clipboard init error: {}", err);
-                println!("[DEBUG] Finished paste_clipboard_instead_of_text (clipboard error)");
-                return prompt.to_string();
-            }
-        };
-
-        // try to paste text
-        if let Ok(txt) = clipboard.get_text() {
-            println!("[DEBUG] Finished paste_clipboard_instead_of_text (text)");
-            return re.replace_all(prompt, &txt).into_owned();
-        }
-
-        // if text failed, try image
-        if let Ok(img) = clipboard.get_image() {
-            let mut buf = Vec::new();
-
-            let mut encoder = Encoder::new(&mut buf, img.width as u32, img.height as u32);
-            encoder.set_color(ColorType::Rgba);
-            encoder.set_depth(BitDepth::Eight);
-
-            match encoder.write_header() {
-                Ok(mut writer) => {
-                    if let Err(e) = writer.write_image_data(&img.bytes) {
-                        eprintln!("[DEBUG] Failed to write PNG data: {}", e);
-                    }
-                }
-                Err(e) => {
-                    eprintln!("[DEBUG] Failed to write PNG header: {}", e);
-                }
-            }
-
-            let b64 = general_purpose::STANDARD.encode(&buf);
-            let data_uri = format!("data:image/png;base64,{}", b64);
-            println!("[DEBUG] Finished paste_clipboard_instead_of_text (image)");
-            return re.replace_all(prompt, &data_uri).into_owned();
+        if let Some(content) = clipboard_as_prompt_text() {
+            println!("[DEBUG] Finished paste_clipboard_instead_of_text");
+            crate::trace::tool("pasted the clipboard into the prompt");
+            return re.replace_all(prompt, regex::NoExpand(&content)).into_owned();
         }
-
         // neither text nor image
         println!("[DEBUG] Finished paste_clipboard_instead_of_text (no clipboard content)");
         return prompt.to_string();
@@ -150,84 +331,36 @@ pub fn extract_image_parts(prompt: &str) -> Result<(String,), (String, String, V
             .map_err(|_| (pre.clone(), mime.clone(), Vec::new(), post.clone()))?;
 
         println!("[DEBUG] Finished extract_image_parts (found image)");
-        return Err((pre, mime, bytes, post));
+        Err((pre, mime, bytes, post))
     } else {
         println!("[DEBUG] Finished extract_image_parts (no image)");
-        return Ok((prompt.to_string(),));
+        Ok((prompt.to_string(),))
     }
 }
 
-// checks if the prompt contains a "forget" command
-pub fn contains_forget(prompt: &str, _config: &Config, app: &tauri::AppHandle) -> bool {
+// "forget this conversation" / "forget everything you know about me", confirmed with a yes
+pub async fn contains_forget(prompt: &str, config: &Config, app: &tauri::AppHandle) -> bool {
     println!("[DEBUG] Entered contains_forget");
-    let re = Regex::new(r"(?i)\b(forget|erase memories|erase memory)\b")
-        .expect("Failed to compile forget regex");
-
-    let result = re.is_match(prompt);
-    println!("[DEBUG] Finished contains_forget: {}", result);
-    if result {
-        // move all conversation history files to the history folder
-        println!("[DEBUG] Detected 'forget' in prompt, moving conversation history files");
-        move_all_conversation_history_to_history_folder(app);
+    let Some(reply) = crate::forget::handle(app, prompt, &config.whisper_language) else {
+        return false;
+    };
+    events::emit(app, NewMessage::new(Role::Assistant, &reply));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak forget confirmation: {}", e);
     }
-    result
+    true
 }
 
-// moves all conversation history files to the history folder
-fn move_all_conversation_history_to_history_folder(app: &tauri::AppHandle) {
-    println!("[DEBUG] Entered move_all_conversation_history_to_history_folder");
-    let app_dir = app
-        .path()
-        .app_config_dir()
-        .unwrap_or_else(|_| std::path::PathBuf::from("."));
-    let source_folder = app_dir.join("context");
-    let history_folder = app_dir.join("history");
-
-    if !source_folder.as_os_str().is_empty() && !history_folder.as_os_str().is_empty() {
-        println!("[DEBUG] Creating history folder: {:?}", history_folder);
-        std::fs::create_dir_all(&history_folder).expect("Failed to create history folder");
-
-        println!(
-            "[DEBUG] Reading entries from source folder: {:?}",
-            source_folder
-        );
-        let entries = std::fs::read_dir(&source_folder).expect("Failed to read source folder");
-
-        for entry in entries {
-            println!("[DEBUG] Got directory entry");
-            let entry = entry.expect("Failed to read entry");
-            let path = entry.path();
-            println!("[DEBUG] Inspecting path: {:?}", path);
-
-            let is_json = path
-                .extension()
-                .and_then(|e| e.to_str())
-                .map(|s| s.eq_ignore_ascii_case("json"))
-                .unwrap_or(false);
-            println!(
-                "[DEBUG] is_file = {}, extension == \"json\" (case‑insensitive) = {}",
-                path.is_file(),
-                is_json
-            );
-
-            if path.is_file() && is_json {
-                let file_name = path.file_name().unwrap();
-                let new_path = history_folder.join(file_name);
-                println!(
-                    "[DEBUG] Moving {:?} -> {:?}",
-                    path.display(),
-                    new_path.display()
-                );
-                std::fs::rename(&path, &new_path)
-                    .expect("Failed to move conversation history file");
-                println!("[DEBUG] Moved {} to {}", path.display(), new_path.display());
-            }
-        }
-    } else {
-        println!("[DEBUG] Source or destination folder path is empty, skipping move");
+// "yes" or "no" to a command that asked before changing something, see confirm.rs
+pub async fn contains_confirmation(prompt: &str, config: &Config, app: &tauri::AppHandle) -> bool {
+    let Some(reply) = crate::confirm::handle(prompt).await else {
+        return false;
+    };
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak confirmation reply: {}", e);
     }
-
-    println!("[DEBUG] Finished move_all_conversation_history_to_history_folder");
+    true
 }
 
 fn send_media_key(key_code: u8) {
@@ -241,11 +374,140 @@ fn send_media_key(key_code: u8) {
     }
 }
 
-pub fn skip_track(prompt: &str) -> bool {
+// how local commands (media keys, ...) acknowledge that they ran
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedbackSound {
+    None,
+    Earcon,
+    Speech,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CommandFeedbackConfig {
+    // post the confirmation as an assistant message in the chat
+    pub chat_message: bool,
+    pub sound: FeedbackSound,
+}
+
+impl Default for CommandFeedbackConfig {
+    fn default() -> Self {
+        Self {
+            chat_message: true,
+            sound: FeedbackSound::Earcon,
+        }
+    }
+}
+
+async fn command_feedback(
+    app: &tauri::AppHandle,
+    config: &Config,
+    text: &str,
+    now_playing: Option<&NowPlaying>,
+) {
+    let feedback = &config.command_feedback;
+    if feedback.chat_message {
+        events::emit(
+            app,
+            NewMessage::new(Role::Assistant, text)
+                .with("localCommand", true)
+                .with("nowPlaying", serde_json::json!(now_playing)),
+        );
+    }
+    let result = match feedback.sound {
+        FeedbackSound::None => Ok(()),
+        FeedbackSound::Earcon => tts::play_earcon(config).await,
+        FeedbackSound::Speech => tts::speak_with(config, text).await,
+    };
+    if let Err(e) = result {
+        eprintln!("[ERROR] Failed to play command feedback: {}", e);
+    }
+}
+
+// After a track change the player needs a moment before the session shows the new song
+async fn media_feedback(app: &tauri::AppHandle, config: &Config, text: &str) {
+    tokio::time::sleep(Duration::from_millis(800)).await;
+    let now_playing = media::now_playing_async().await;
+    let text = match &now_playing {
+        Some(np) => format!("{} Now playing {}.", text, np.describe()),
+        None => text.to_string(),
+    };
+    command_feedback(app, config, &text, now_playing.as_ref()).await;
+}
+
+// "what's playing?" answered from the OS media session
+pub async fn contains_now_playing(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !triggers
+        .now_playing
+        .matches(prompt, &config.whisper_language)
+    {
+        return false;
+    }
+    println!("[DEBUG] Detected now playing question");
+    let now_playing = media::now_playing_async().await;
+    let reply = match &now_playing {
+        Some(np) if np.status.as_deref() == Some("paused") => {
+            format!("{} is paused.", np.describe())
+        }
+        Some(np) => format!("This is {}.", np.describe()),
+        None => "Nothing seems to be playing right now.".to_string(),
+    };
+    events::emit(
+        app,
+        NewMessage::new(Role::Assistant, &reply)
+            .with("localCommand", true)
+            .with("nowPlaying", serde_json::json!(now_playing)),
+    );
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak now playing: {}", e);
+    }
+    true
+}
+
+// below this share of the sentence a media phrase is treated as low-confidence
+const MEDIA_CONFIDENT_COVERAGE: f32 = 0.5;
+
+// Media phrases need a clear verb ("play music", "resume playback"). A phrase that is
+// only a small part of a longer sentence is double-checked with the LLM when enabled.
+async fn media_intent(
+    prompt: &str,
+    config: &Config,
+    triggers: &Triggers,
+    command: &CommandTriggers,
+    action: &str,
+) -> bool {
+    let Some(coverage) = command.coverage(prompt, &config.whisper_language) else {
+        return false;
+    };
+    if coverage >= MEDIA_CONFIDENT_COVERAGE || !triggers.confirm_media_with_llm {
+        return true;
+    }
+    println!("[DEBUG] Low-confidence media match ({:.2}), asking LLM", coverage);
+    match crate::send_to_llm::confirm_intent(prompt, action, config).await {
+        Ok(confirmed) => confirmed,
+        Err(e) => {
+            eprintln!("[ERROR] Intent confirmation failed, keeping match: {}", e);
+            true
+        }
+    }
+}
+
+pub async fn skip_track(prompt: &str, config: &Config, triggers: &Triggers) -> bool {
     println!("[DEBUG] Entered skip_track");
-    let re = Regex::new(r"(?i)\b(skip track|next music)\b")
-        .expect("Failed to compile skip tracking regex");
-    let result = re.is_match(prompt);
+    let result = media_intent(
+        prompt,
+        config,
+        triggers,
+        &triggers.skip_track,
+        "skip to the next track",
+    )
+    .await;
     if result {
         send_media_key(VK_MEDIA_NEXT_TRACK as u8);
         println!("Next track command sent.");
@@ -254,10 +516,16 @@ pub fn skip_track(prompt: &str) -> bool {
     result
 }
 
-pub fn pause_music(prompt: &str) -> bool {
+pub async fn pause_music(prompt: &str, config: &Config, triggers: &Triggers) -> bool {
     println!("[DEBUG] Entered pause_music");
-    let re = Regex::new(r"(?i)\b(pause music|pause)\b").expect("Failed to compile pause regex");
-    let result = re.is_match(prompt);
+    let result = media_intent(
+        prompt,
+        config,
+        triggers,
+        &triggers.pause_music,
+        "pause the music",
+    )
+    .await;
     if result {
         send_media_key(VK_MEDIA_PLAY_PAUSE as u8);
         println!("Pause command sent.");
@@ -266,10 +534,16 @@ pub fn pause_music(prompt: &str) -> bool {
     result
 }
 
-pub fn play_music(prompt: &str) -> bool {
+pub async fn play_music(prompt: &str, config: &Config, triggers: &Triggers) -> bool {
     println!("[DEBUG] Entered play_music");
-    let re = Regex::new(r"(?i)\b(play music|play)\b").expect("Failed to compile play regex");
-    let result = re.is_match(prompt);
+    let result = media_intent(
+        prompt,
+        config,
+        triggers,
+        &triggers.play_music,
+        "start or resume music playback",
+    )
+    .await;
     if result {
         send_media_key(VK_MEDIA_PLAY_PAUSE as u8);
         println!("Play/Pause command sent.");
@@ -278,11 +552,16 @@ pub fn play_music(prompt: &str) -> bool {
     result
 }
 
-pub fn previous_track(prompt: &str) -> bool {
+pub async fn previous_track(prompt: &str, config: &Config, triggers: &Triggers) -> bool {
     println!("[DEBUG] Entered previous_track");
-    let re = Regex::new(r"(?i)\b(previous track|last music|previous music|last track)\b")
-        .expect("Failed to compile previous track regex");
-    let result = re.is_match(prompt);
+    let result = media_intent(
+        prompt,
+        config,
+        triggers,
+        &triggers.previous_track,
+        "go back to the previous track",
+    )
+    .await;
     if result {
         send_media_key(VK_MEDIA_PREV_TRACK as u8);
         println!("Previous track command sent.");
@@ -292,78 +571,482 @@ pub fn previous_track(prompt: &str) -> bool {
 }
 
 fn estimate_tokens_only(text: &str) -> usize {
-    let chars = text.chars().count();
-    (chars + 3) / 4
+    text.chars().count().div_ceil(4)
 }
 
 pub async fn contains_weather(
     prompt: &str,
     config: &Config,
-    elevenlabs_model: Model,
+    _elevenlabs_model: Model,
     app: &tauri::AppHandle,
     wake_start_ms: i64,
+    triggers: &Triggers,
 ) -> bool {
     println!("[DEBUG] Entered contains_weather (async)");
 
-    let re = Regex::new(r"(?i)\b(weather|what is the weather)\b")
-        .expect("Failed to compile weather regex");
-    let matched = re.is_match(prompt);
+    // a direct weather question, or a follow-up ("what about tomorrow?") to the last one
+    let slots = if triggers
+        .weather
+        .matches(prompt, &config.whisper_language)
+    {
+        Some(intent::extract_slots(prompt))
+    } else {
+        let expiry = Duration::from_secs(config.context_window_expiration_seconds);
+        match intent::follow_up(prompt, expiry) {
+            Some(("weather", slots)) => Some(slots),
+            _ => None,
+        }
+    };
+    let matched = slots.is_some();
 
     println!("[DEBUG] Finished regex match: {}", matched);
 
-    if matched {
+    if let Some(slots) = slots {
+        intent::remember("weather", &slots);
         println!("[DEBUG] Detected weather trigger, fetching report");
         
         // Emit message to chat that we're fetching weather
-        let message = serde_json::json!({
-            "role": "assistant",
-            "content": "🌤️ Fetching current weather information...",
-            "createdAt": chrono::Utc::now().timestamp_millis()
-        });
-        let _ = app.emit("new-message", message);
+        events::emit(
+            app,
+            NewMessage::new(Role::Assistant, "🌤️ Fetching current weather information..."),
+        );
         
-        let weather_report = get_weather(app).await; // async
+        let weather_report = get_weather(app, &slots).await; // async
         
         // Emit the weather report to chat with meta (tokens)
         let tts_tokens_est = estimate_tokens_only(&weather_report);
         let assistant_created_at = chrono::Utc::now().timestamp_millis();
-        let message = serde_json::json!({
-            "role": "assistant",
-            "content": weather_report.clone(),
-            "createdAt": assistant_created_at,
-            "meta": { "ttsTokensEst": tts_tokens_est }
-        });
-        let _ = app.emit("new-message", message);
+        events::emit(
+            app,
+            NewMessage::new(Role::Assistant, weather_report.clone())
+                .at(assistant_created_at)
+                .with("ttsTokensEst", tts_tokens_est),
+        );
         
         println!("[DEBUG] Speaking weather report");
-        tts::speak(
-            &weather_report,
-            &config.voice_id,
-            elevenlabs_model,
-            &config.elevenlabs_key,
-        )
-        .await
-        .expect("Failed to speak weather report");
+        if let Err(e) = tts::speak_with(config, &weather_report).await {
+            eprintln!("[ERROR] Failed to speak weather report: {}", e);
+        }
         println!("[DEBUG] Finished speaking weather report");
         // Emit meta update for latency
         let end_ms = chrono::Utc::now().timestamp_millis();
         let total_ms = (end_ms - wake_start_ms).max(0) as u64;
-        let _ = app.emit(
-            "message-meta",
-            serde_json::json!({
-                "createdAtOfAssistant": assistant_created_at,
-                "meta": { "latencyMs": total_ms }
-            })
+        events::emit(
+            app,
+            MessageMeta::new(Role::Assistant, assistant_created_at).with("latencyMs", total_ms),
         );
     }
 
     matched
 }
 
-pub async fn get_weather(app: &tauri::AppHandle) -> String {
+// "what time is it in Tokyo", "3 pm London time in New York", "when can I meet with
+// Sydney", answered offline; "and in Berlin?" right after asks again for another place
+pub async fn contains_world_clock(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    let reply = if triggers.world_clock.matches(prompt, &config.whisper_language) {
+        crate::world_clock::answer(prompt, &config.world_clock)
+    } else {
+        let expiry = Duration::from_secs(config.context_window_expiration_seconds);
+        match intent::follow_up(prompt, expiry) {
+            Some(("world_clock", slots)) if slots.day_offset.is_none() => slots
+                .location
+                .and_then(|l| crate::world_clock::answer_now(&l, &config.world_clock)),
+            _ => None,
+        }
+    };
+    let Some(reply) = reply else {
+        return false;
+    };
+    println!("[DEBUG] Answered world clock question");
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak world clock answer: {}", e);
+    }
+    true
+}
+
+// "what's the EUR to USD rate", "how's NVDA doing", "how's my watchlist"
+pub async fn contains_finance(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !triggers.finance.matches(prompt, &config.whisper_language) {
+        return false;
+    }
+    let Some(reply) = crate::finance::answer(prompt, &config.finance).await else {
+        return false;
+    };
+    println!("[DEBUG] Answered finance question");
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak finance answer: {}", e);
+    }
+    true
+}
+
+// "did Arsenal win yesterday", "when do the Lakers play next"
+pub async fn contains_sports(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !triggers.sports.matches(prompt, &config.whisper_language) {
+        return false;
+    }
+    let Some(reply) = crate::sports::answer(prompt, &config.sports).await else {
+        return false;
+    };
+    println!("[DEBUG] Answered sports question");
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak sports answer: {}", e);
+    }
+    true
+}
+
+// "how long to get to work", "how's my commute": travel time with the traffic now
+pub async fn contains_commute(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !triggers.commute.matches(prompt, &config.whisper_language) {
+        return false;
+    }
+    let Some(reply) = crate::commute::answer(prompt, &config.commute).await else {
+        return false;
+    };
+    println!("[DEBUG] Answered travel time question");
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak travel time: {}", e);
+    }
+    true
+}
+
+// "track my package 1Z...", "where's my package": kept and checked by packages
+pub async fn contains_packages(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !triggers.packages.matches(prompt, &config.whisper_language) {
+        return false;
+    }
+    let Some(reply) = crate::packages::answer(prompt, app, &config.packages).await else {
+        return false;
+    };
+    println!("[DEBUG] Answered package question");
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak package answer: {}", e);
+    }
+    true
+}
+
+// "what's my IP", "is the internet down", "turn off Wi-Fi"
+pub async fn contains_network(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !triggers.network.matches(prompt, &config.whisper_language) {
+        return false;
+    }
+    let Some(reply) = crate::network::answer(prompt, &config.network).await else {
+        return false;
+    };
+    println!("[DEBUG] Answered network command");
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak network answer: {}", e);
+    }
+    true
+}
+
+// "what's eating my CPU", "kill notepad"; after "which one should I close?" the answer
+// needs no trigger
+pub async fn contains_processes(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !crate::processes::is_choosing()
+        && !crate::processes::is_kill_command(prompt)
+        && !triggers.processes.matches(prompt, &config.whisper_language)
+    {
+        return false;
+    }
+    let Some(reply) = crate::processes::answer(prompt, &config.processes).await else {
+        return false;
+    };
+    println!("[DEBUG] Answered process command");
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak process answer: {}", e);
+    }
+    true
+}
+
+// "in the terminal, ..." proposes a command and asks before running it
+pub async fn contains_terminal(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !triggers.terminal.matches(prompt, &config.whisper_language) {
+        return false;
+    }
+    let Some(reply) = crate::terminal::answer(prompt, app, config).await else {
+        return false;
+    };
+    println!("[DEBUG] Proposed terminal command");
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak terminal command: {}", e);
+    }
+    true
+}
+
+// "any new issues on my project?", "comment on issue 42"; the comment itself, said next,
+// needs no trigger
+pub async fn contains_github(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !crate::github::is_dictating() && !triggers.github.matches(prompt, &config.whisper_language)
+    {
+        return false;
+    }
+    let Some(reply) = crate::github::answer(prompt, app, &config.github).await else {
+        return false;
+    };
+    println!("[DEBUG] Answered GitHub command");
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak GitHub answer: {}", e);
+    }
+    true
+}
+
+// "are my containers running", "restart the postgres container"
+pub async fn contains_docker(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !crate::docker::is_command(prompt)
+        && !triggers.docker.matches(prompt, &config.whisper_language)
+    {
+        return false;
+    }
+    let Some(reply) = crate::docker::answer(prompt, &config.docker).await else {
+        return false;
+    };
+    println!("[DEBUG] Answered Docker command");
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak Docker answer: {}", e);
+    }
+    true
+}
+
+// "are all pods healthy in my cluster", "how are the pods in the media namespace"
+pub async fn contains_kubernetes(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !triggers.kubernetes.matches(prompt, &config.whisper_language) {
+        return false;
+    }
+    let Some(reply) = crate::kubernetes::answer(prompt, &config.kubernetes).await else {
+        return false;
+    };
+    println!("[DEBUG] Answered Kubernetes question");
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak Kubernetes answer: {}", e);
+    }
+    true
+}
+
+// "play my last memo", "read my last memo"; recording one happens in the voice loop
+pub async fn contains_voice_memo(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !triggers.voice_memos.matches(prompt, &config.whisper_language) {
+        return false;
+    }
+    crate::voice_memos::handle(prompt, app, config).await
+}
+
+// below this share of the sentence a briefing phrase is just a greeting, as in "good
+// morning, how do I bake bread"
+const BRIEFING_MIN_COVERAGE: f32 = 0.3;
+
+// "good morning", "brief me": date, weather, calendar and whatever else the briefing
+// settings list, in one go
+pub async fn contains_briefing(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    let coverage = triggers.briefing.coverage(prompt, &config.whisper_language);
+    if coverage.is_none_or(|c| c < BRIEFING_MIN_COVERAGE) {
+        return false;
+    }
+    println!("[DEBUG] Running the briefing");
+    crate::briefing::run(app, config).await;
+    true
+}
+
+// "who was Ada Lovelace", "tell me about the Eiffel Tower": the start of the Wikipedia
+// article, with the article named at the end and linked in the chat
+pub async fn contains_wikipedia(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !triggers.wikipedia.matches(prompt, &config.whisper_language) {
+        return false;
+    }
+    let Some(summary) =
+        crate::wikipedia::lookup(prompt, &config.wikipedia, &config.whisper_language).await
+    else {
+        return false;
+    };
+    println!("[DEBUG] Answered from Wikipedia: {}", summary.title);
+    crate::trace::tool(&format!("read the Wikipedia article {}", summary.title));
+    events::emit(
+        app,
+        NewMessage::new(Role::Assistant, &summary.text)
+            .with("localCommand", true)
+            .with(
+                "citation",
+                serde_json::json!({ "title": summary.title, "url": summary.url }),
+            ),
+    );
+    let spoken = format!(
+        "{} That's according to Wikipedia's article on {}.",
+        summary.spoken, summary.title
+    );
+    if let Err(e) = tts::speak_with(config, &spoken).await {
+        eprintln!("[ERROR] Failed to speak Wikipedia answer: {}", e);
+    }
+    true
+}
+
+// builds the spoken report for the requested day (today uses current conditions)
+fn describe_weather(data: &Value, slots: &intent::Slots) -> String {
+    let place = slots
+        .location
+        .as_deref()
+        .map(|l| format!(" in {}", l))
+        .unwrap_or_default();
+    let day = slots.day_offset.unwrap_or(0);
+    let units = crate::user_profile::current().units;
+    let (unit, key) = if units == Some(crate::user_profile::Units::Imperial) {
+        ("°F", "F")
+    } else {
+        ("°C", "C")
+    };
+
+    if day > 0 {
+        let forecast = data.get("weather").and_then(|w| w.get(day as usize));
+        let field = |k: &str| {
+            forecast
+                .and_then(|f| f.get(k))
+                .and_then(Value::as_str)
+                .unwrap_or("unknown")
+                .to_string()
+        };
+        // midday slot of the 3-hourly forecast
+        let desc = forecast
+            .and_then(|f| f.get("hourly"))
+            .and_then(|h| h.get(4))
+            .and_then(|h| h.get("weatherDesc"))
+            .and_then(|descs| descs.get(0))
+            .and_then(|d| d.get("value"))
+            .and_then(Value::as_str)
+            .unwrap_or("unavailable");
+        let when = if day == 1 { "Tomorrow" } else { "The day after tomorrow" };
+        return format!(
+            "{}{} it will be {} with temperatures between {} and {}{}.",
+            when,
+            place,
+            desc.trim().to_lowercase(),
+            field(&format!("mintemp{}", key)),
+            field(&format!("maxtemp{}", key)),
+            unit
+        );
+    }
+
+    let temp = data
+        .get("current_condition")
+        .and_then(|conds| conds.get(0))
+        .and_then(|cond| cond.get(format!("temp_{}", key)))
+        .and_then(Value::as_str)
+        .unwrap_or("unknown");
+
+    let weather_desc = data
+        .get("current_condition")
+        .and_then(|conds| conds.get(0))
+        .and_then(|cond| cond.get("weatherDesc"))
+        .and_then(|descs| descs.get(0))
+        .and_then(|d| d.get("value"))
+        .and_then(Value::as_str)
+        .unwrap_or("unavailable");
+
+    println!(
+        "[DEBUG] Extracted weather data - temp: {}{}, description: {}",
+        temp, unit, weather_desc
+    );
+    format!(
+        "The current weather{} is {} with a temperature of {}{}.",
+        place, weather_desc, temp, unit
+    )
+}
+
+pub async fn get_weather(app: &tauri::AppHandle, slots: &intent::Slots) -> String {
     println!("[DEBUG] Entered get_weather()");
     let client = Client::new();
-    let url = "https://wttr.in/?format=j1";
+    // no place named: where the user profile says they live, else wttr.in resolves
+    // the location from the IP
+    let mut slots = slots.clone();
+    if slots.location.is_none() {
+        let home = crate::user_profile::current().location;
+        slots.location = (!home.is_empty()).then_some(home);
+    }
+    let mut url = reqwest::Url::parse("https://wttr.in/").expect("valid weather URL");
+    if let Some(loc) = slots.location.as_deref() {
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.pop_if_empty().push(loc);
+        }
+    }
+    url.set_query(Some("format=j1"));
     println!("[DEBUG] Making HTTP request to: {}", url);
 
     match client.get(url).send().await {
@@ -372,29 +1055,7 @@ pub async fn get_weather(app: &tauri::AppHandle) -> String {
             match resp.json::<Value>().await {
                 Ok(data) => {
                     println!("[DEBUG] Successfully parsed JSON response");
-                    
-                    let temp_c = data
-                        .get("current_condition")
-                        .and_then(|conds| conds.get(0))
-                        .and_then(|cond| cond.get("temp_C"))
-                        .and_then(Value::as_str)
-                        .unwrap_or("unknown");
-
-                    let weather_desc = data
-                        .get("current_condition")
-                        .and_then(|conds| conds.get(0))
-                        .and_then(|cond| cond.get("weatherDesc"))
-                        .and_then(|descs| descs.get(0))
-                        .and_then(|d| d.get("value"))
-                        .and_then(Value::as_str)
-                        .unwrap_or("unavailable");
-
-                    let weather_report = format!(
-                        "The current weather is {} with a temperature of {}°C.",
-                        weather_desc, temp_c
-                    );
-                    
-                    println!("[DEBUG] Extracted weather data - temp: {}°C, description: {}", temp_c, weather_desc);
+                    let weather_report = describe_weather(&data, &slots);
                     println!("[DEBUG] Generated weather report: {}", weather_report);
                     weather_report
                 }
@@ -403,12 +1064,10 @@ pub async fn get_weather(app: &tauri::AppHandle) -> String {
                     let error_msg = "Sorry, I couldn't parse the weather data.";
                     
                     // Emit error to chat
-                    let message = serde_json::json!({
-                        "role": "assistant",
-                        "content": format!("❌ {}", error_msg),
-                        "createdAt": chrono::Utc::now().timestamp_millis()
-                    });
-                    let _ = app.emit("new-message", message);
+                    events::emit(
+                        app,
+                        NewMessage::new(Role::Assistant, format!("❌ {}", error_msg)),
+                    );
                     
                     error_msg.into()
                 }
@@ -419,62 +1078,408 @@ pub async fn get_weather(app: &tauri::AppHandle) -> String {
             let error_msg = "Sorry, I couldn't get the weather right now.";
             
             // Emit error to chat
-            let message = serde_json::json!({
-                "role": "assistant",
-                "content": format!("❌ {}", error_msg),
-                "createdAt": chrono::Utc::now().timestamp_millis()
-            });
-            let _ = app.emit("new-message", message);
+            events::emit(app, NewMessage::new(Role::Assistant, format!("❌ {}", error_msg)));
             
             error_msg.into()
         }
     }
 }
 
-// here are the checks that return true and exit early
+// "switch to work mode" style commands; confirms in the new persona's voice
+pub async fn contains_persona_switch(prompt: &str, config: &Config, app: &tauri::AppHandle) -> bool {
+    let Some(persona) = crate::persona::match_switch_command(prompt, &config.personas) else {
+        return false;
+    };
+    println!("[DEBUG] Detected persona switch to '{}'", persona.name);
+    if let Err(e) = crate::persona::switch_by_voice(app, persona) {
+        eprintln!("[ERROR] Failed to switch persona: {}", e);
+        return true;
+    }
+
+    let reply = format!("Switched to {} mode.", persona.name);
+    events::emit(
+        app,
+        NewMessage::new(Role::Assistant, &reply).with("persona", persona.name.as_str()),
+    );
+
+    let mut voiced = config.clone();
+    crate::persona::apply(&mut voiced, persona);
+    if let Err(e) = tts::speak_with(&voiced, &reply).await {
+        eprintln!("[ERROR] Failed to speak persona confirmation: {}", e);
+    }
+    true
+}
+
+// "forget that" drops a single long-term memory instead of the whole context
+pub async fn contains_forget_memory(prompt: &str, config: &Config, app: &tauri::AppHandle) -> bool {
+    let Some(description) = crate::memory::match_forget_command(prompt) else {
+        return false;
+    };
+    println!("[DEBUG] Detected forget memory command");
+    let reply = crate::memory::forget_one(app, &description);
+    events::emit(app, NewMessage::new(Role::Assistant, &reply));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak memory confirmation: {}", e);
+    }
+    true
+}
+
+// "summarize the last 10 minutes" while meeting notes are running
+pub async fn contains_meeting_summary(prompt: &str, config: &Config, app: &tauri::AppHandle) -> bool {
+    if !crate::meeting::is_active() {
+        return false;
+    }
+    let Some(minutes) = crate::meeting::match_summary_request(prompt) else {
+        return false;
+    };
+    println!("[DEBUG] Detected meeting summary request ({} min)", minutes);
+    let reply = crate::meeting::summarize_last(config, minutes).await;
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak meeting summary: {}", e);
+    }
+    true
+}
+
+// "start cooking" loads the copied recipe; while cooking, "next step", "how much flour"
+// and the like are answered from it
+pub async fn contains_cooking(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !crate::cooking::is_active()
+        && !triggers.cooking.matches(prompt, &config.whisper_language)
+    {
+        return false;
+    }
+    let Some(reply) =
+        crate::cooking::answer(prompt, app, &config.cooking, &config.web_fetch).await
+    else {
+        return false;
+    };
+    println!("[DEBUG] Answered cooking command");
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak cooking answer: {}", e);
+    }
+    true
+}
+
+// "play rain sounds for 30 minutes", "play brown noise", "stop sounds"
+pub async fn contains_ambient(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !triggers.ambient.matches(prompt, &config.whisper_language) {
+        return false;
+    }
+    let Some(reply) = crate::ambient::answer(prompt, app, &config.ambient) else {
+        return false;
+    };
+    println!("[DEBUG] Answered ambient sound command");
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak ambient sound answer: {}", e);
+    }
+    true
+}
+
+// "start a 25-minute focus session", "stop the pomodoro", "how many pomodoros today"
+pub async fn contains_pomodoro(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !triggers.pomodoro.matches(prompt, &config.whisper_language) {
+        return false;
+    }
+    let Some(reply) = crate::pomodoro::answer(prompt, app, &config.pomodoro) else {
+        return false;
+    };
+    println!("[DEBUG] Answered pomodoro command");
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak pomodoro answer: {}", e);
+    }
+    true
+}
+
+// "set a timer for 10 minutes", "how much time is left", "cancel the pasta timer"
+pub async fn contains_timer(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !triggers.timers.matches(prompt, &config.whisper_language) {
+        return false;
+    }
+    let Some(reply) = crate::timers::answer(prompt, app) else {
+        return false;
+    };
+    println!("[DEBUG] Answered timer command");
+    events::emit(app, NewMessage::new(Role::Assistant, &reply).with("localCommand", true));
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak timer answer: {}", e);
+    }
+    true
+}
+
+// "read this aloud" speaks the selection in the focused app, "stop reading" ends it
+pub async fn contains_read_aloud(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if crate::read_aloud::is_reading()
+        && triggers.stop_reading.matches(prompt, &config.whisper_language)
+    {
+        println!("[DEBUG] Detected stop reading command");
+        crate::read_aloud::stop();
+        return true;
+    }
+    if !triggers.read_selection.matches(prompt, &config.whisper_language) {
+        return false;
+    }
+    println!("[DEBUG] Detected read selection command");
+    // runs in the background so "stop reading" can be heard while it plays
+    let (app, config) = (app.clone(), config.clone());
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = crate::read_aloud::read_selection(&app, &config).await {
+            eprintln!("[ERROR] Failed to read selection aloud: {}", e);
+        }
+    });
+    true
+}
+
+// "summarize my clipboard" without having to say "control V"
+pub async fn contains_summarize_clipboard(
+    prompt: &str,
+    config: &Config,
+    app: &tauri::AppHandle,
+    triggers: &Triggers,
+) -> bool {
+    if !triggers.summarize_clipboard.matches(prompt, &config.whisper_language) {
+        return false;
+    }
+    println!("[DEBUG] Detected summarize clipboard command");
+    let reply = crate::clipboard_tools::summarize_reply(config).await;
+    crate::clipboard_tools::respond(app, config, &reply, true).await;
+    true
+}
+
+// "rewrite this more formally" and friends put the result back on the clipboard
+pub async fn contains_clipboard_rewrite(prompt: &str, config: &Config, app: &tauri::AppHandle) -> bool {
+    let Some(instruction) = crate::clipboard_tools::match_rewrite_command(prompt) else {
+        return false;
+    };
+    println!("[DEBUG] Detected clipboard rewrite command");
+    crate::clipboard_tools::rewrite_and_announce(app, config, &instruction, true).await;
+    true
+}
+
+// "generate an image of ..." shows the picture in the chat and says so
+pub async fn contains_image_request(prompt: &str, config: &Config, app: &tauri::AppHandle) -> bool {
+    let Some(description) = crate::image_gen::match_request(prompt) else {
+        return false;
+    };
+    println!("[DEBUG] Detected image generation request");
+    let reply = crate::image_gen::handle(app, config, &description).await;
+    if let Err(e) = tts::speak_with(config, &reply).await {
+        eprintln!("[ERROR] Failed to speak image reply: {}", e);
+    }
+    true
+}
+
+// here are the checks that handle the command themselves and exit early; returns the name
+// of the one that did
 pub async fn if_contains_exit(
     prompt: &str,
     config: &Config,
     elevenlabs_model: Model,
     wake_start_ms: i64,
     app: tauri::AppHandle,
-) -> bool {
+) -> Option<&'static str> {
     println!("[DEBUG] Entered do_all_transformations");
-    if contains_forget(prompt, config, &app) {
+    let triggers = crate::triggers::load(&app);
+    if contains_forget(prompt, config, &app).await {
         println!("[DEBUG] Detected forget command, exiting early");
-        return true;
+        return Some("forget");
+    }
+    if contains_confirmation(prompt, config, &app).await {
+        println!("[DEBUG] Detected confirmation reply, exiting early");
+        return Some("confirm");
+    }
+    // before the others, so the answer to "which one should I close?" lands here
+    if contains_processes(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected process command, exiting early");
+        return Some("processes");
+    }
+    // likewise for a dictated issue comment, which could read like any other command
+    if contains_github(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected GitHub command, exiting early");
+        return Some("github");
+    }
+    if contains_forget_memory(prompt, config, &app).await {
+        println!("[DEBUG] Detected forget memory command, exiting early");
+        return Some("forget_memory");
+    }
+
+    if contains_meeting_summary(prompt, config, &app).await {
+        println!("[DEBUG] Detected meeting summary command, exiting early");
+        return Some("meeting_summary");
     }
 
-    if skip_track(prompt) {
+    // cooking mode comes before media, so "next" is the next step and not the next song
+    if contains_cooking(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected cooking command, exiting early");
+        return Some("cooking");
+    }
+    // before timers, so "start a focus timer" is a focus session
+    if contains_pomodoro(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected pomodoro command, exiting early");
+        return Some("pomodoro");
+    }
+    if contains_timer(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected timer command, exiting early");
+        return Some("timer");
+    }
+    // before media too: "play rain sounds" isn't music
+    if contains_ambient(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected ambient sound command, exiting early");
+        return Some("ambient");
+    }
+
+    if contains_now_playing(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected now playing command, exiting early");
+        return Some("now_playing");
+    }
+    if skip_track(prompt, config, &triggers).await {
+        media_feedback(&app, config, "Skipped to the next track.").await;
         println!("[DEBUG] Detected skip track command, exiting early");
-        return true;
+        return Some("skip_track");
     }
-    if pause_music(prompt) {
+    if pause_music(prompt, config, &triggers).await {
+        command_feedback(&app, config, "Paused the music.", None).await;
         println!("[DEBUG] Detected pause music command, exiting early");
-        return true;
+        return Some("pause_music");
     }
-    if play_music(prompt) {
+    if play_music(prompt, config, &triggers).await {
+        media_feedback(&app, config, "Playing music.").await;
         println!("[DEBUG] Detected play music command, exiting early");
-        return true;
+        return Some("play_music");
     }
-    if previous_track(prompt) {
+    if previous_track(prompt, config, &triggers).await {
+        media_feedback(&app, config, "Back to the previous track.").await;
         println!("[DEBUG] Detected previous track command, exiting early");
-        return true;
+        return Some("previous_track");
+    }
+
+    if contains_read_aloud(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected read aloud command, exiting early");
+        return Some("read_aloud");
+    }
+
+    if contains_summarize_clipboard(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected summarize clipboard command, exiting early");
+        return Some("summarize_clipboard");
+    }
+
+    if contains_clipboard_rewrite(prompt, config, &app).await {
+        println!("[DEBUG] Detected clipboard rewrite command, exiting early");
+        return Some("clipboard_rewrite");
+    }
+
+    if contains_image_request(prompt, config, &app).await {
+        println!("[DEBUG] Detected image generation request, exiting early");
+        return Some("image_request");
+    }
+
+    if contains_persona_switch(prompt, config, &app).await {
+        println!("[DEBUG] Detected persona switch command, exiting early");
+        return Some("persona_switch");
+    }
+
+    if contains_voice_memo(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected voice memo command, exiting early");
+        return Some("voice_memo");
+    }
+
+    if contains_briefing(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected briefing request, exiting early");
+        return Some("briefing");
+    }
+
+    if contains_world_clock(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected world clock question, exiting early");
+        return Some("world_clock");
+    }
+
+    if contains_finance(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected finance question, exiting early");
+        return Some("finance");
+    }
+
+    if contains_sports(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected sports question, exiting early");
+        return Some("sports");
+    }
+
+    if contains_commute(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected travel time question, exiting early");
+        return Some("commute");
+    }
+
+    if contains_packages(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected package tracking command, exiting early");
+        return Some("packages");
+    }
+
+    if contains_network(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected network command, exiting early");
+        return Some("network");
     }
 
-    if contains_weather(prompt, config, elevenlabs_model, &app, wake_start_ms).await {
+    if contains_terminal(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected terminal command, exiting early");
+        return Some("terminal");
+    }
+
+    if contains_docker(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected Docker command, exiting early");
+        return Some("docker");
+    }
+
+    if contains_kubernetes(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected Kubernetes question, exiting early");
+        return Some("kubernetes");
+    }
+
+    if contains_weather(prompt, config, elevenlabs_model, &app, wake_start_ms, &triggers).await {
         println!("[DEBUG] Detected weather command, exiting early");
-        return true;
+        return Some("weather");
+    }
+
+    if contains_wikipedia(prompt, config, &app, &triggers).await {
+        println!("[DEBUG] Detected Wikipedia question, exiting early");
+        return Some("wikipedia");
     }
 
     println!("[DEBUG] Finished do_all_transformations");
-    false
+    None
 }
 
 // here are the checks that return text for LLM
 pub fn if_contains_transform(prompt: &str, _elevenlabs_model: Model) -> String {
     println!("[DEBUG] Entered if_contains_transform");
-    let transformed_prompt = paste_clipboard_instead_of_text(&prompt);
+    let transformed_prompt = paste_clipboard_instead_of_text(prompt);
+    let transformed_prompt = attach_referenced_image(&transformed_prompt);
 
     println!(
         "[DEBUG] Finished if_contains_transform: {}",
@@ -483,14 +1488,31 @@ pub fn if_contains_transform(prompt: &str, _elevenlabs_model: Model) -> String {
     transformed_prompt
 }
 
+// An answer after the post-LLM transform: what the chat shows and what is read aloud
+pub struct PostLlm {
+    // copy tags removed, code kept
+    pub display: String,
+    // code replaced by a short note
+    pub spoken: String,
+    pub copied: Option<CodeCopied>,
+}
+
 // here are the checks that return text after it has been processed by LLM
-pub fn if_contains_transform_post_llm(prompt: &str) -> String {
+pub fn if_contains_transform_post_llm(app: &tauri::AppHandle, config: &Config, answer: &str) -> PostLlm {
     println!("[DEBUG] Entered if_contains_transform_post_llm");
-    match copy_to_clipboard_function_for_llm(prompt) {
-        Ok(result) => result,
+    let copied = match copy_to_clipboard_function_for_llm(answer, &config.copy_blocks) {
+        Ok(copied) => copied,
         Err(e) => {
             eprintln!("[ERROR] Failed to copy to clipboard: {:?}", e);
-            prompt.to_string() // fallback: return original input
+            None
         }
+    };
+    if let Some(c) = &copied {
+        events::emit(app, c.clone());
+    }
+    PostLlm {
+        display: strip_copy_tags(answer),
+        spoken: speakable_summary(answer, copied.as_ref()),
+        copied,
     }
 }
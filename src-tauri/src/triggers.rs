@@ -0,0 +1,512 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::Manager;
+
+// Phrases that fire one built-in command. Phrases are plain text, matched
+// case-insensitively on whole words; `localized` adds phrases for a Whisper
+// language code ("de", "fr", ...) on top of the shared list.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CommandTriggers {
+    pub enabled: bool,
+    pub phrases: Vec<String>,
+    pub localized: HashMap<String, Vec<String>>,
+}
+
+impl Default for CommandTriggers {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            phrases: Vec::new(),
+            localized: HashMap::new(),
+        }
+    }
+}
+
+impl CommandTriggers {
+    fn with(phrases: &[&str]) -> Self {
+        Self {
+            phrases: phrases.iter().map(|p| p.to_string()).collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn matches(&self, prompt: &str, language: &str) -> bool {
+        self.coverage(prompt, language).is_some()
+    }
+
+    // Share of the prompt's words taken up by the longest matching phrase, or None
+    // when nothing matches. "pause music" alone is 1.0; buried in a question it is low.
+    pub fn coverage(&self, prompt: &str, language: &str) -> Option<f32> {
+        if !self.enabled {
+            return None;
+        }
+        let localized = self
+            .localized
+            .get(&language.to_lowercase())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let prompt_words = prompt.split_whitespace().count().max(1);
+        self.phrases
+            .iter()
+            .chain(localized)
+            .filter(|p| phrase_matches(prompt, p))
+            .map(|p| p.split_whitespace().count() as f32 / prompt_words as f32)
+            .fold(None, |best: Option<f32>, c| Some(best.map_or(c, |b| b.max(c))))
+    }
+}
+
+// Whole-word, case-insensitive match that also works for non-ASCII letters and
+// phrases ending in punctuation, where `\b` would not.
+fn phrase_matches(prompt: &str, phrase: &str) -> bool {
+    let words: Vec<String> = phrase.split_whitespace().map(regex::escape).collect();
+    if words.is_empty() {
+        return false;
+    }
+    let pattern = format!(
+        r"(?i)(?:^|[^\p{{L}}\p{{N}}]){}(?:$|[^\p{{L}}\p{{N}}])",
+        words.join(r"\s+")
+    );
+    Regex::new(&pattern)
+        .map(|re| re.is_match(prompt))
+        .unwrap_or(false)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Triggers {
+    pub weather: CommandTriggers,
+    pub skip_track: CommandTriggers,
+    pub previous_track: CommandTriggers,
+    pub play_music: CommandTriggers,
+    pub pause_music: CommandTriggers,
+    pub now_playing: CommandTriggers,
+    pub forget_conversation: CommandTriggers,
+    pub forget_memories: CommandTriggers,
+    pub read_selection: CommandTriggers,
+    pub stop_reading: CommandTriggers,
+    pub summarize_clipboard: CommandTriggers,
+    // the place itself is found by world_clock, these only mark a time question
+    pub world_clock: CommandTriggers,
+    // likewise for quotes; finance decides what is asked for
+    pub finance: CommandTriggers,
+    // and for games; sports finds the team
+    pub sports: CommandTriggers,
+    // "who is", "tell me about", ...; wikipedia decides whether it has an article
+    pub wikipedia: CommandTriggers,
+    // "track my package ...", "where's my package"
+    pub packages: CommandTriggers,
+    // travel time questions; commute works out where to
+    pub commute: CommandTriggers,
+    // "set a timer", "how much time is left", "cancel the timer"
+    pub timers: CommandTriggers,
+    // starts cooking mode; once it is on, its commands need no trigger
+    pub cooking: CommandTriggers,
+    // "play rain sounds", "stop sounds"; ambient knows which sounds there are
+    pub ambient: CommandTriggers,
+    // "good morning", "what does my day look like"; only when it is most of what was said
+    pub briefing: CommandTriggers,
+    // "record a voice memo", "play my last memo"
+    pub voice_memos: CommandTriggers,
+    // "start a focus session", "how long until my break"
+    pub pomodoro: CommandTriggers,
+    // "what's my IP", "is the internet down", "turn on my VPN"; network picks the tool
+    pub network: CommandTriggers,
+    // "what's eating my CPU", "kill the chrome process"; processes decides what is asked
+    // for, and takes a prompt that is only "kill notepad" without a phrase
+    pub processes: CommandTriggers,
+    // "in the terminal, ..."; only used when the terminal tool is turned on
+    pub terminal: CommandTriggers,
+    // "any new issues", "what's the CI status of main", "comment on issue 42"
+    pub github: CommandTriggers,
+    // "are my containers running"; "restart the postgres container" needs no phrase
+    pub docker: CommandTriggers,
+    // "are all pods healthy in my cluster"
+    pub kubernetes: CommandTriggers,
+    // ask the LLM before acting on a media phrase buried in a longer sentence
+    pub confirm_media_with_llm: bool,
+}
+
+impl Default for Triggers {
+    fn default() -> Self {
+        Self {
+            weather: CommandTriggers::with(&["weather", "what is the weather"]),
+            skip_track: CommandTriggers::with(&["skip track", "next music"]),
+            previous_track: CommandTriggers::with(&[
+                "previous track",
+                "last music",
+                "previous music",
+                "last track",
+            ]),
+            // always a verb plus an object: bare "play"/"pause" hit normal questions
+            play_music: CommandTriggers::with(&[
+                "play music",
+                "play the music",
+                "play my music",
+                "resume music",
+                "resume the music",
+                "resume playback",
+                "continue playback",
+                "unpause music",
+            ]),
+            pause_music: CommandTriggers::with(&[
+                "pause music",
+                "pause the music",
+                "pause my music",
+                "pause playback",
+                "stop the music",
+                "stop playback",
+            ]),
+            now_playing: CommandTriggers::with(&[
+                "what's playing",
+                "what is playing",
+                "what song is this",
+                "what's this song",
+                "which song is this",
+                "what am i listening to",
+            ]),
+            forget_conversation: CommandTriggers::with(&[
+                "forget this conversation",
+                "forget the conversation",
+                "forget our conversation",
+                "forget this chat",
+                "forget everything",
+                "clear the context",
+                "clear context",
+                "clear the conversation",
+                "reset the conversation",
+                "reset the context",
+            ]),
+            forget_memories: CommandTriggers::with(&[
+                "forget about me",
+                "forget everything about me",
+                "forget everything you know about me",
+                "forget what you know about me",
+                "erase memories",
+                "erase memory",
+                "erase your memories",
+                "delete your memories",
+                "clear your memory",
+                "clear your memories",
+                "forget your memories",
+            ]),
+            read_selection: CommandTriggers::with(&[
+                "read this aloud",
+                "read this out loud",
+                "read the selection",
+                "read the selected text",
+                "read what i selected",
+            ]),
+            stop_reading: CommandTriggers::with(&["stop reading"]),
+            summarize_clipboard: CommandTriggers::with(&[
+                "summarize my clipboard",
+                "summarize the clipboard",
+                "summarize what i copied",
+                "summarise my clipboard",
+                "summarise the clipboard",
+            ]),
+            world_clock: CommandTriggers::with(&[
+                "what time",
+                "the time in",
+                "time is it in",
+                "time in",
+                "time difference",
+                "time zone",
+                "timezone",
+                "my time",
+                "convert",
+                "time to meet",
+                "meeting time",
+                "time for a call",
+                "time to call",
+            ]),
+            finance: CommandTriggers::with(&[
+                "exchange rate",
+                "exchange rates",
+                "conversion rate",
+                "dollar rate",
+                "euro rate",
+                "pound rate",
+                "yen rate",
+                "stock price",
+                "share price",
+                "stock",
+                "stocks",
+                "shares of",
+                "trading at",
+                "bitcoin",
+                "ethereum",
+                "crypto",
+                "watchlist",
+                "watch list",
+                "my stocks",
+                "the market",
+                "the markets",
+                "in dollars",
+                "in euros",
+                "in pounds",
+                "in yen",
+                "to dollars",
+                "to euros",
+                "to pounds",
+                "to yen",
+                "how many dollars",
+                "how many euros",
+                "how many pounds",
+                "how many yen",
+            ]),
+            sports: CommandTriggers::with(&[
+                "play next",
+                "playing next",
+                "next game",
+                "next match",
+                "last game",
+                "last match",
+                "the score",
+                "final score",
+                "score of",
+                "result of",
+                "results of",
+                "win last night",
+                "lose last night",
+                "win yesterday",
+                "lose yesterday",
+                "win today",
+                "lose today",
+                "win their",
+                "lose their",
+                "do last night",
+                "do yesterday",
+            ]),
+            wikipedia: CommandTriggers::with(&[
+                "who is",
+                "who was",
+                "who are",
+                "who were",
+                "tell me about",
+                "look up",
+                "wikipedia",
+            ]),
+            packages: CommandTriggers::with(&[
+                "track my package",
+                "track a package",
+                "track this package",
+                "track the package",
+                "track package",
+                "track my parcel",
+                "track parcel",
+                "track my shipment",
+                "track my order",
+                "track my delivery",
+                "my package",
+                "my packages",
+                "my parcel",
+                "my parcels",
+                "my shipment",
+                "my shipments",
+                "my delivery",
+                "my deliveries",
+                "the package",
+                "the packages",
+            ]),
+            commute: CommandTriggers::with(&[
+                "how long to get",
+                "how long to the",
+                "how long to my",
+                "how long does it take to get",
+                "how long will it take to get",
+                "how long to drive",
+                "how long is the drive",
+                "how far is",
+                "how far to",
+                "travel time",
+                "commute",
+                "traffic",
+                "drive to",
+                "drive home",
+                "get home",
+                "get to work",
+                "the drive",
+                "eta",
+            ]),
+            timers: CommandTriggers::with(&["timer", "timers", "time left", "time is left"]),
+            cooking: CommandTriggers::with(&["cooking", "cook this", "cook that", "make this"]),
+            ambient: CommandTriggers::with(&["sound", "sounds", "noise", "ambience"]),
+            briefing: CommandTriggers::with(&[
+                "good morning",
+                "briefing",
+                "brief me",
+                "morning update",
+                "daily update",
+                "what's my day look like",
+                "what does my day look like",
+                "how does my day look",
+                "what's on today",
+            ]),
+            voice_memos: CommandTriggers::with(&["memo", "voice memo", "voice note"]),
+            pomodoro: CommandTriggers::with(&[
+                "focus session",
+                "focus sessions",
+                "focus timer",
+                "start focusing",
+                "stop focusing",
+                "focus for",
+                "my focus",
+                "the focus",
+                "pomodoro",
+                "pomodoros",
+                "work session",
+                "my break",
+                "the break",
+            ]),
+            network: CommandTriggers::with(&[
+                "ip address",
+                "my ip",
+                "public ip",
+                "my internet",
+                "the internet",
+                "internet down",
+                "internet working",
+                "internet connection",
+                "my connection",
+                "the connection",
+                "am i online",
+                "am i offline",
+                "am i connected",
+                "my wifi",
+                "the wifi",
+                "my wi-fi",
+                "the wi-fi",
+                "wifi on",
+                "wifi off",
+                "wi-fi on",
+                "wi-fi off",
+                "my vpn",
+                "the vpn",
+                "vpn on",
+                "vpn off",
+            ]),
+            processes: CommandTriggers::with(&[
+                "eating my",
+                "hogging my",
+                "slowing down my",
+                "using the most",
+                "using so much",
+                "taking up",
+                "busiest programs",
+                "busiest apps",
+                "busiest processes",
+                "heaviest programs",
+                "kill the",
+                "kill process",
+                "terminate the",
+                "force quit",
+                "end the",
+                "end task",
+            ]),
+            terminal: CommandTriggers::with(&[
+                "terminal",
+                "command line",
+                "command prompt",
+                "powershell",
+                "shell",
+                "run a command",
+            ]),
+            github: CommandTriggers::with(&[
+                "github",
+                "new issues",
+                "open issues",
+                "latest issues",
+                "recent issues",
+                "ci status",
+                "build status",
+                "the build",
+                "the ci",
+                "the checks",
+                "the pipeline",
+                "on issue",
+                "to issue",
+                "that issue",
+                "the last issue",
+                "pull request",
+            ]),
+            docker: CommandTriggers::with(&[
+                "docker",
+                "my containers",
+                "the containers",
+                "all containers",
+                "all my containers",
+                "any containers",
+                "the container",
+                "containers running",
+                "container running",
+                "container up",
+                "container down",
+                "container healthy",
+            ]),
+            kubernetes: CommandTriggers::with(&[
+                "kubernetes",
+                "k8s",
+                "my cluster",
+                "the cluster",
+                "my pods",
+                "the pods",
+                "all pods",
+                "all the pods",
+                "pods healthy",
+                "the namespace",
+            ]),
+            confirm_media_with_llm: false,
+        }
+    }
+}
+
+fn triggers_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join("triggers.json"))
+}
+
+// Reads `triggers.json`; commands missing from the file keep their defaults.
+pub fn load(app: &tauri::AppHandle) -> Triggers {
+    let Ok(path) = triggers_path(app) else {
+        return Triggers::default();
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(s) => serde_json::from_str(&s).unwrap_or_else(|e| {
+            eprintln!("[WARN] Ignoring invalid triggers.json: {}", e);
+            Triggers::default()
+        }),
+        Err(_) => Triggers::default(),
+    }
+}
+
+#[tauri::command]
+pub fn cmd_get_triggers(app: tauri::AppHandle) -> Result<Triggers, String> {
+    Ok(load(&app))
+}
+
+#[tauri::command]
+pub fn cmd_set_triggers(app: tauri::AppHandle, triggers: Triggers) -> Result<(), String> {
+    let s = serde_json::to_string_pretty(&triggers).map_err(|e| e.to_string())?;
+    crate::history::atomic_write(&triggers_path(&app)?, s.as_bytes()).map_err(|e| e.to_string())
+}
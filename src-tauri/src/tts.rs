@@ -16,63 +16,365 @@ along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
 use anyhow::{Result, anyhow};
+use crate::events::{self, TtsQuotaLow};
+use crate::models::Config;
 use elevenlabs_rs::Model;
+use futures_util::future::BoxFuture;
 use reqwest::Client;
-use rodio::{Decoder, OutputStreamBuilder, Sink};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::task;
+use ts_rs::TS;
+
+// warn once usage of the ElevenLabs character quota passes this percentage
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct QuotaWarningConfig {
+    pub enabled: bool,
+    pub threshold_percent: f32,
+}
+
+impl Default for QuotaWarningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_percent: 90.0,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct TtsQuota {
+    pub tier: String,
+    #[ts(type = "number")]
+    pub character_count: u64,
+    #[ts(type = "number")]
+    pub character_limit: u64,
+    #[ts(type = "number")]
+    pub characters_remaining: u64,
+    pub used_percent: f32,
+    #[ts(type = "number | null")]
+    pub next_reset_unix: Option<i64>,
+}
+
+// only one warning until usage drops again (new billing period)
+static QUOTA_WARNED: AtomicBool = AtomicBool::new(false);
+
+// What a provider produced: encoded audio for the caller to play, or speech already played
+pub enum Speech {
+    Audio(Vec<u8>),
+    Spoken,
+}
+
+// The provider turned the request down for account reasons (see `should_fall_back`)
+#[derive(Debug)]
+pub struct ProviderRefused {
+    pub status: u16,
+    pub body: String,
+}
+
+impl std::fmt::Display for ProviderRefused {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TTS provider refused the request ({}): {}", self.status, self.body)
+    }
+}
+
+impl std::error::Error for ProviderRefused {}
+
+pub trait TtsProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn synthesize<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Speech>>;
+}
+
+pub struct ElevenLabsTts {
+    pub client: Client,
+    pub api_key: String,
+    pub voice_id: String,
+    pub model: Model,
+}
+
+impl TtsProvider for ElevenLabsTts {
+    fn name(&self) -> &'static str {
+        "elevenlabs"
+    }
+
+    fn synthesize<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Speech>> {
+        Box::pin(async move {
+            let url = format!(
+                "https://api.elevenlabs.io/v1/text-to-speech/{voice_id}/stream?output_format=mp3_44100_128",
+                voice_id = self.voice_id
+            );
+            let model_id = String::from(self.model.clone());
+            let resp = self
+                .client
+                .post(&url)
+                .header("xi-api-key", &self.api_key)
+                .json(&json!({
+                    "text": crate::lexicon::apply(text, crate::lexicon::supports_phonemes(&model_id)),
+                    "model_id": model_id,
+                }))
+                .send()
+                .await
+                .map_err(|e| anyhow!("HTTP request error: {}", e))?;
+
+            if !resp.status().is_success() {
+                let status = resp.status();
+                let body = resp.text().await.unwrap_or_default();
+                if should_fall_back(status.as_u16(), &body) {
+                    return Err(ProviderRefused {
+                        status: status.as_u16(),
+                        body,
+                    }
+                    .into());
+                }
+                return Err(anyhow!("ElevenLabs API returned {}: {}", status, body));
+            }
+
+            let bytes = resp
+                .bytes()
+                .await
+                .map_err(|e| anyhow!("Error reading TTS body: {}", e))?;
+            crate::stats::record_tts(text);
+            Ok(Speech::Audio(bytes.to_vec()))
+        })
+    }
+}
+
+// The OS voice (SAPI on Windows, `say` on macOS, speech-dispatcher on Linux); needs no keys
+pub struct SystemTts;
+
+impl TtsProvider for SystemTts {
+    fn name(&self) -> &'static str {
+        "system"
+    }
+
+    fn synthesize<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Speech>> {
+        Box::pin(async move {
+            if !text.trim().is_empty() {
+                crate::stats::record_tts(text);
+                let text = crate::lexicon::apply(text, false);
+                task::spawn_blocking(move || speak_system_blocking(&text))
+                    .await
+                    .map_err(|e| anyhow!("System TTS thread panic: {}", e))??;
+            }
+            Ok(Speech::Spoken)
+        })
+    }
+}
+
+// "system" forces the OS voice; otherwise ElevenLabs once a key and voice are configured
+pub fn provider_for(config: &Config) -> Box<dyn TtsProvider> {
+    let wants_system = config.tts_provider.as_deref() == Some("system");
+    if wants_system || config.elevenlabs_key.trim().is_empty() || config.voice_id.trim().is_empty() {
+        return Box::new(SystemTts);
+    }
+    Box::new(ElevenLabsTts {
+        client: Client::new(),
+        api_key: config.elevenlabs_key.clone(),
+        voice_id: config.voice_id.clone(),
+        model: crate::models::elevenlabs_model_from_id(&config.elevenlabs_model),
+    })
+}
+
+pub fn is_refusal(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<ProviderRefused>().is_some()
+}
+
+// short tone used to acknowledge local commands without speaking
+pub async fn play_earcon(config: &Config) -> Result<()> {
+    crate::audio_output::tone_once(
+        config.voice_output_device(),
+        880.0,
+        std::time::Duration::from_millis(120),
+    )
+    .await
+}
+
+async fn speak_via(provider: &dyn TtsProvider, text: &str, config: Option<&Config>) -> Result<()> {
+    match provider.synthesize(text).await? {
+        Speech::Audio(bytes) => {
+            let device = config.and_then(|c| c.voice_output_device());
+            let volume = config.and_then(|c| c.output_volume).unwrap_or(1.0);
+            crate::audio_output::play_once(device, volume, bytes).await
+        }
+        Speech::Spoken => Ok(()),
+    }
+}
 
 pub async fn speak(text: &str, voice_id: &str, model: Model, api_key: &str) -> Result<()> {
     if text.trim().is_empty() {
         return Ok(());
     }
+    let provider = ElevenLabsTts {
+        client: Client::new(),
+        api_key: api_key.to_string(),
+        voice_id: voice_id.to_string(),
+        model,
+    };
+    speak_via(&provider, text, None).await
+}
 
-    // 1) Send the streaming request
-    let url = format!(
-        "https://api.elevenlabs.io/v1/text-to-speech/{voice_id}/stream?output_format=mp3_44100_128",
-        voice_id = voice_id
-    );
-    let client = Client::new();
-    let resp = client
-        .post(&url)
+// Speaks with the configured provider, dropping to the system voice if ElevenLabs refuses
+pub async fn speak_with(config: &Config, text: &str) -> Result<()> {
+    let text = &crate::tts_normalize::normalize(text, &config.tts_normalization);
+    if text.trim().is_empty() {
+        return Ok(());
+    }
+    let provider = provider_for(config);
+    match speak_via(provider.as_ref(), text, Some(config)).await {
+        Err(e) if is_refusal(&e) => {
+            eprintln!("[WARN] {}; using system voice", e);
+            speak_via(&SystemTts, text, Some(config)).await
+        }
+        other => other,
+    }
+}
+
+pub async fn fetch_quota(api_key: &str) -> Result<TtsQuota> {
+    let resp = Client::new()
+        .get("https://api.elevenlabs.io/v1/user/subscription")
         .header("xi-api-key", api_key)
-        .json(&json!({
-            "text": text,
-            "model_id": String::from(model),
-        }))
         .send()
         .await
         .map_err(|e| anyhow!("HTTP request error: {}", e))?;
-
     if !resp.status().is_success() {
         let status = resp.status();
         let body = resp.text().await.unwrap_or_default();
         return Err(anyhow!("ElevenLabs API returned {}: {}", status, body));
     }
-
-    // 2) Buffer the full audio payload into a Vec<u8>
-    let bytes = resp
-        .bytes()
+    let data: serde_json::Value = resp
+        .json()
         .await
-        .map_err(|e| anyhow!("Error reading TTS body: {}", e))?;
-    let audio_bytes = bytes.to_vec();
-
-    // 3) Spawn a blocking task for playback
-    task::spawn_blocking(move || -> Result<()> {
-        // Everything here is on a blocking thread: OutputStream is OK
-        let cursor = Cursor::new(audio_bytes);
-        let stream = OutputStreamBuilder::from_default_device()?
-            .open_stream()
-            .map_err(|e| anyhow!("Audio init error: {}", e))?;
-        let sink = Sink::connect_new(&stream.mixer());
-        let decoder = Decoder::new(cursor).map_err(|e| anyhow!("Decode error: {}", e))?;
-        sink.append(decoder);
-        sink.sleep_until_end();
-        Ok(())
+        .map_err(|e| anyhow!("Invalid subscription response: {}", e))?;
+
+    let character_count = data["character_count"].as_u64().unwrap_or(0);
+    let character_limit = data["character_limit"].as_u64().unwrap_or(0);
+    let used_percent = if character_limit == 0 {
+        0.0
+    } else {
+        (character_count as f64 * 100.0 / character_limit as f64) as f32
+    };
+    Ok(TtsQuota {
+        tier: data["tier"].as_str().unwrap_or("unknown").to_string(),
+        character_count,
+        character_limit,
+        characters_remaining: character_limit.saturating_sub(character_count),
+        used_percent,
+        next_reset_unix: data["next_character_count_reset_unix"].as_i64(),
     })
-    .await
-    .map_err(|e| anyhow!("Playback thread panic: {}", e))??;
+}
+
+// Run after a synthesis; emits "tts-quota-low" the first time usage crosses the threshold
+pub async fn check_quota_after_synthesis(
+    app: tauri::AppHandle,
+    api_key: String,
+    cfg: QuotaWarningConfig,
+) {
+    if !cfg.enabled || api_key.trim().is_empty() {
+        return;
+    }
+    let quota = match fetch_quota(&api_key).await {
+        Ok(q) => q,
+        Err(e) => {
+            eprintln!("[WARN] Failed to fetch TTS quota: {}", e);
+            return;
+        }
+    };
+    if quota.used_percent < cfg.threshold_percent {
+        QUOTA_WARNED.store(false, Ordering::Relaxed);
+        return;
+    }
+    if !QUOTA_WARNED.swap(true, Ordering::Relaxed) {
+        println!(
+            "[DEBUG] TTS quota at {:.1}% (threshold {}%)",
+            quota.used_percent, cfg.threshold_percent
+        );
+        events::emit(
+            &app,
+            TtsQuotaLow {
+                quota,
+                threshold_percent: cfg.threshold_percent,
+            },
+        );
+    }
+}
+
+#[tauri::command]
+pub async fn cmd_get_tts_quota(app: tauri::AppHandle) -> Result<TtsQuota, String> {
+    let cfg = crate::cmd_load_config(app)?;
+    if cfg.elevenlabs_key.trim().is_empty() {
+        return Err("Missing ElevenLabs API key".into());
+    }
+    fetch_quota(&cfg.elevenlabs_key)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// True when ElevenLabs can't serve this request for account reasons (bad key, rate limit,
+// exhausted quota) so a local voice should take over instead of failing the interaction
+pub fn should_fall_back(status: u16, body: &str) -> bool {
+    let b = body.to_lowercase();
+    status == 401 || status == 429 || b.contains("quota_exceeded") || b.contains("quota exceeded")
+}
+
+// Speaks with the operating system's built-in voice; blocks until done
+fn speak_system_blocking(text: &str) -> Result<()> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        let mut c = Command::new("powershell");
+        c.args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Add-Type -AssemblyName System.Speech; \
+             $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+             $s.Speak([Console]::In.ReadToEnd())",
+        ])
+        .creation_flags(CREATE_NO_WINDOW);
+        c
+    };
+    #[cfg(target_os = "macos")]
+    let mut cmd = {
+        let mut c = Command::new("say");
+        c.args(["-f", "-"]);
+        c
+    };
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut cmd = {
+        // speech-dispatcher reads the text from stdin in pipe mode
+        let mut c = Command::new("spd-say");
+        c.args(["--wait", "--pipe-mode"]);
+        c
+    };
 
+    // text goes through stdin so nothing in it is interpreted by a shell
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow!("Failed to start system TTS: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow!("System TTS exited with {}", status));
+    }
     Ok(())
 }
+
+// Lets the UI speak short prompts (onboarding, errors) with whatever voice is available
+#[tauri::command]
+pub async fn cmd_speak(app: tauri::AppHandle, text: String) -> Result<(), String> {
+    let cfg = crate::to_run_config(crate::cmd_load_config(app)?);
+    speak_with(&cfg, &text).await.map_err(|e| e.to_string())
+}
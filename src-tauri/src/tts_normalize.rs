@@ -0,0 +1,232 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Turns chat-formatted text into something that sounds right when spoken: no
+// asterisks or pound signs, "km/h" as "kilometers per hour", lists read as
+// "First, ... Second, ...", and no emoji. Each rule can be switched off.
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct TtsNormalizationConfig {
+    pub enabled: bool,
+    // headings, bold/italic, links, code fences, tables
+    pub strip_markdown: bool,
+    pub read_lists: bool,
+    pub expand_units: bool,
+    pub expand_abbreviations: bool,
+    pub remove_emoji: bool,
+}
+
+impl Default for TtsNormalizationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            strip_markdown: true,
+            read_lists: true,
+            expand_units: true,
+            expand_abbreviations: true,
+            remove_emoji: true,
+        }
+    }
+}
+
+const ORDINALS: &[&str] = &[
+    "First", "Second", "Third", "Fourth", "Fifth", "Sixth", "Seventh", "Eighth", "Ninth", "Tenth",
+];
+
+// (symbol, singular, plural); longer symbols first so "km/h" wins over "km"
+const UNITS: &[(&str, &str, &str)] = &[
+    ("km/h", "kilometer per hour", "kilometers per hour"),
+    ("m/s", "meter per second", "meters per second"),
+    ("mph", "mile per hour", "miles per hour"),
+    ("°C", "degree Celsius", "degrees Celsius"),
+    ("°F", "degree Fahrenheit", "degrees Fahrenheit"),
+    ("kWh", "kilowatt hour", "kilowatt hours"),
+    ("GHz", "gigahertz", "gigahertz"),
+    ("MHz", "megahertz", "megahertz"),
+    ("TB", "terabyte", "terabytes"),
+    ("GB", "gigabyte", "gigabytes"),
+    ("MB", "megabyte", "megabytes"),
+    ("KB", "kilobyte", "kilobytes"),
+    ("km", "kilometer", "kilometers"),
+    ("cm", "centimeter", "centimeters"),
+    ("mm", "millimeter", "millimeters"),
+    ("kg", "kilogram", "kilograms"),
+    ("mg", "milligram", "milligrams"),
+    ("ml", "milliliter", "milliliters"),
+    ("ms", "millisecond", "milliseconds"),
+    ("kW", "kilowatt", "kilowatts"),
+    ("lbs", "pound", "pounds"),
+    ("lb", "pound", "pounds"),
+    ("oz", "ounce", "ounces"),
+    ("ft", "foot", "feet"),
+    ("mi", "mile", "miles"),
+    ("%", "percent", "percent"),
+    ("°", "degree", "degrees"),
+];
+
+const ABBREVIATIONS: &[(&str, &str)] = &[
+    ("e.g.", "for example"),
+    ("i.e.", "that is"),
+    ("etc.", "et cetera"),
+    ("vs.", "versus"),
+    ("approx.", "approximately"),
+    ("incl.", "including"),
+    ("max.", "maximum"),
+    ("Mr.", "Mister"),
+    ("Mrs.", "Missus"),
+    ("Dr.", "Doctor"),
+];
+
+static CODE_FENCE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*```.*$").unwrap());
+static HEADING: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s{0,3}#{1,6}\s+").unwrap());
+static BLOCKQUOTE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*>\s?").unwrap());
+static RULE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*(?:[-*_]\s*){3,}$").unwrap());
+static TABLE_DIVIDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*\|?[\s:|-]+\|[\s:|-]*$").unwrap());
+static IMAGE: Lazy<Regex> = Lazy::new(|| Regex::new(r"!\[([^\]]*)\]\([^)]*\)").unwrap());
+static LINK: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[([^\]]+)\]\([^)]*\)").unwrap());
+static BOLD: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\*\*|__)(.+?)(\*\*|__)").unwrap());
+static ITALIC: Lazy<Regex> = Lazy::new(|| Regex::new(r"(^|[^\w*])[*_]([^*_\n]+)[*_]").unwrap());
+static STRIKE: Lazy<Regex> = Lazy::new(|| Regex::new(r"~~(.+?)~~").unwrap());
+static INLINE_CODE: Lazy<Regex> = Lazy::new(|| Regex::new(r"`([^`]*)`").unwrap());
+static BULLET: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*[-*+•]\s+").unwrap());
+static NUMBERED: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*(\d{1,3})[.)]\s+").unwrap());
+static UNIT: Lazy<Regex> = Lazy::new(|| {
+    let symbols = UNITS
+        .iter()
+        .map(|(s, _, _)| regex::escape(s))
+        .collect::<Vec<_>>()
+        .join("|");
+    // a number, then the unit not followed by more letters ("5 min" is left alone, "5 km" is not)
+    Regex::new(&format!(r"(-?\d+(?:[.,]\d+)?)\s?({})(?:\b|$|\s|[.,;:!?)])", symbols)).unwrap()
+});
+
+// pictographs (flags included), dingbats and the joiners that glue emoji sequences together
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF
+        | 0xFE0F | 0x200D | 0x20E3 | 0xE0020..=0xE007F)
+}
+
+fn strip_markdown(text: &str) -> String {
+    let text = CODE_FENCE.replace_all(text, "");
+    let text = TABLE_DIVIDER.replace_all(&text, "");
+    let text = RULE.replace_all(&text, "");
+    let text = HEADING.replace_all(&text, "");
+    let text = BLOCKQUOTE.replace_all(&text, "");
+    let text = IMAGE.replace_all(&text, "$1");
+    let text = LINK.replace_all(&text, "$1");
+    let text = BOLD.replace_all(&text, "$2");
+    let text = ITALIC.replace_all(&text, "$1$2");
+    let text = STRIKE.replace_all(&text, "$1");
+    let text = INLINE_CODE.replace_all(&text, "$1");
+    // table cells become a spoken list
+    text.lines()
+        .map(|l| {
+            let l = l.trim();
+            if l.starts_with('|') || l.ends_with('|') {
+                l.trim_matches('|')
+                    .split('|')
+                    .map(str::trim)
+                    .filter(|c| !c.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            } else {
+                l.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn read_lists(text: &str) -> String {
+    let text = BULLET.replace_all(text, "");
+    NUMBERED
+        .replace_all(&text, |c: &Captures| {
+            let n: usize = c[1].parse().unwrap_or(0);
+            match ORDINALS.get(n.wrapping_sub(1)) {
+                Some(ordinal) => format!("{}, ", ordinal),
+                None => format!("Number {}, ", n),
+            }
+        })
+        .to_string()
+}
+
+fn expand_units(text: &str) -> String {
+    UNIT.replace_all(text, |c: &Captures| {
+        let number = &c[1];
+        let symbol = &c[2];
+        let (_, singular, plural) = UNITS.iter().find(|(s, _, _)| *s == symbol).unwrap();
+        let name = if number == "1" || number == "-1" { singular } else { plural };
+        // keep whatever followed the unit (punctuation, space)
+        let rest = &c[0][c[0].find(symbol).unwrap() + symbol.len()..];
+        format!("{} {}{}", number, name, rest)
+    })
+    .to_string()
+}
+
+fn expand_abbreviations(text: &str) -> String {
+    let mut out = text.to_string();
+    for (short, long) in ABBREVIATIONS {
+        let re = Regex::new(&format!(r"(^|[\s(]){}", regex::escape(short))).unwrap();
+        out = re.replace_all(&out, |c: &Captures| format!("{}{}", &c[1], long)).to_string();
+    }
+    out
+}
+
+// Lines become sentences so list items and headings get a pause instead of running together
+fn join_lines(text: &str) -> String {
+    let mut out = String::new();
+    for line in text.lines().map(|l| l.split_whitespace().collect::<Vec<_>>().join(" ")) {
+        if line.is_empty() {
+            continue;
+        }
+        if !out.is_empty() {
+            let ends_sentence = out.ends_with(['.', '!', '?', ':', ';', ',']);
+            out.push_str(if ends_sentence { " " } else { ". " });
+        }
+        out.push_str(&line);
+    }
+    out
+}
+
+pub fn normalize(text: &str, config: &TtsNormalizationConfig) -> String {
+    if !config.enabled {
+        return text.to_string();
+    }
+    let mut out = text.to_string();
+    if config.remove_emoji {
+        out = out.chars().filter(|c| !is_emoji(*c)).collect();
+    }
+    if config.strip_markdown {
+        out = strip_markdown(&out);
+    }
+    if config.read_lists {
+        out = read_lists(&out);
+    }
+    if config.expand_units {
+        out = expand_units(&out);
+    }
+    if config.expand_abbreviations {
+        out = expand_abbreviations(&out);
+    }
+    join_lines(&out)
+}
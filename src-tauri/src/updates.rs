@@ -0,0 +1,196 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Checks GitHub for a newer release and for newer recommended models. Only reads
+// public URLs; nothing about the user is sent.
+
+use crate::run_jarvis;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+const RELEASES_URL: &str = "https://api.github.com/repos/Hav1ck/Jarvis/releases/latest";
+// recommended model versions, kept in the repo next to the code
+const MODELS_URL: &str = "https://raw.githubusercontent.com/Hav1ck/Jarvis/main/models.json";
+// wake word file shipped with this version (see run_jarvis::resolve_wakeword_path)
+const WAKEWORD_FILE: &str = "Jarvis_en_windows_v3_0_0.ppn";
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+    body: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RecommendedModels {
+    whisper: Option<String>,
+    wakeword: Option<String>,
+    notes: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ModelUpdate {
+    // "whisper" or "wakeword"
+    #[ts(type = "\"whisper\" | \"wakeword\"")]
+    pub kind: String,
+    pub current: String,
+    pub recommended: String,
+}
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub latest_version: Option<String>,
+    pub app_update: bool,
+    pub release_url: Option<String>,
+    pub release_notes: Option<String>,
+    pub models: Vec<ModelUpdate>,
+    pub model_notes: Option<String>,
+}
+
+impl UpdateInfo {
+    pub fn has_updates(&self) -> bool {
+        self.app_update || !self.models.is_empty()
+    }
+}
+
+// "v0.3.10" -> [0, 3, 10]; anything after '-' (pre-release) is ignored
+fn parse_version(v: &str) -> Vec<u64> {
+    v.trim()
+        .trim_start_matches(['v', 'V'])
+        .split('-')
+        .next()
+        .unwrap_or("")
+        .split('.')
+        .map(|p| p.parse().unwrap_or(0))
+        .collect()
+}
+
+fn is_newer(latest: &str, current: &str) -> bool {
+    let (mut a, mut b) = (parse_version(latest), parse_version(current));
+    let len = a.len().max(b.len());
+    a.resize(len, 0);
+    b.resize(len, 0);
+    a > b
+}
+
+fn client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        // GitHub rejects API requests without a user agent
+        .user_agent(concat!("Jarvis/", env!("CARGO_PKG_VERSION")))
+        .timeout(std::time::Duration::from_secs(15))
+        .build()
+        .map_err(|e| anyhow!("Failed to build HTTP client: {e}"))
+}
+
+async fn latest_release(client: &reqwest::Client) -> Result<Option<Release>> {
+    let resp = client
+        .get(RELEASES_URL)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?;
+    // no published release yet
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !resp.status().is_success() {
+        return Err(anyhow!("GitHub returned {}", resp.status()));
+    }
+    let release: Release = resp.json().await?;
+    Ok(Some(release).filter(|r| !r.draft && !r.prerelease))
+}
+
+async fn recommended_models(client: &reqwest::Client) -> Option<RecommendedModels> {
+    let resp = client.get(MODELS_URL).send().await.ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    resp.json().await.ok()
+}
+
+pub async fn check() -> Result<UpdateInfo> {
+    let client = client()?;
+    let current = env!("CARGO_PKG_VERSION").to_string();
+
+    let release = latest_release(&client).await?;
+    let latest_version = release
+        .as_ref()
+        .map(|r| r.tag_name.trim_start_matches(['v', 'V']).to_string());
+    let app_update = latest_version
+        .as_deref()
+        .map(|l| is_newer(l, &current))
+        .unwrap_or(false);
+
+    let recommended = recommended_models(&client).await.unwrap_or_default();
+    let mut models = Vec::new();
+    if let Some(whisper) = recommended.whisper.filter(|w| w != run_jarvis::DEFAULT_WHISPER_MODEL) {
+        models.push(ModelUpdate {
+            kind: "whisper".into(),
+            current: run_jarvis::DEFAULT_WHISPER_MODEL.into(),
+            recommended: whisper,
+        });
+    }
+    if let Some(wakeword) = recommended.wakeword.filter(|w| w != WAKEWORD_FILE) {
+        models.push(ModelUpdate {
+            kind: "wakeword".into(),
+            current: WAKEWORD_FILE.into(),
+            recommended: wakeword,
+        });
+    }
+
+    Ok(UpdateInfo {
+        current_version: current,
+        latest_version,
+        app_update,
+        release_url: release.as_ref().map(|r| r.html_url.clone()),
+        release_notes: release.and_then(|r| r.body),
+        model_notes: recommended.notes.filter(|_| !models.is_empty()),
+        models,
+    })
+}
+
+// Emits `update-available` and marks the tray icon when something is newer
+pub fn announce(app: &tauri::AppHandle, info: &UpdateInfo) {
+    if !info.has_updates() {
+        return;
+    }
+    crate::events::emit(app, info.clone());
+    if let Some(tray) = app.tray_by_id("main") {
+        let tooltip = match &info.latest_version {
+            Some(v) if info.app_update => format!("Jarvis - version {} available", v),
+            _ => "Jarvis - model update available".to_string(),
+        };
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+}
+
+#[tauri::command]
+pub async fn cmd_check_updates(app: tauri::AppHandle) -> Result<UpdateInfo, String> {
+    let info = check().await.map_err(|e| e.to_string())?;
+    announce(&app, &info);
+    Ok(info)
+}
@@ -0,0 +1,135 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// A few facts the user states once instead of Jarvis learning or asking them: name,
+// where they live, units, job and pronouns. Kept in user_profile.json next to
+// config.json and added to every question (see memory::prompt_section); the location
+// is also where the weather is for when no place is named. The home and work addresses
+// are only used for travel times (see commute) and stay out of the questions.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Manager;
+use ts_rs::TS;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum Units {
+    Metric,
+    Imperial,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, TS)]
+#[serde(default)]
+#[ts(export)]
+pub struct UserProfile {
+    pub name: String,
+    // city or region, e.g. "Berlin"
+    pub location: String,
+    pub units: Option<Units>,
+    pub job: String,
+    pub pronouns: String,
+    // street addresses for travel times; home falls back to location
+    pub home: String,
+    pub work: String,
+}
+
+impl UserProfile {
+    fn trimmed(mut self) -> Self {
+        for field in [
+            &mut self.name,
+            &mut self.location,
+            &mut self.job,
+            &mut self.pronouns,
+            &mut self.home,
+            &mut self.work,
+        ] {
+            *field = field.trim().to_string();
+        }
+        self
+    }
+}
+
+// read on every question, so kept in memory after the first load
+static CURRENT: Mutex<Option<UserProfile>> = Mutex::new(None);
+
+fn profile_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join("user_profile.json"))
+}
+
+// Called once at startup
+pub fn init(app: &tauri::AppHandle) {
+    let profile = profile_path(app)
+        .ok()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    *CURRENT.lock().unwrap() = Some(profile);
+}
+
+pub fn current() -> UserProfile {
+    CURRENT.lock().unwrap().clone().unwrap_or_default()
+}
+
+// Text added before the conversation context; empty while the profile is blank
+pub fn prompt_section() -> String {
+    let profile = current();
+    let mut facts = Vec::new();
+    if !profile.name.is_empty() {
+        facts.push(format!("- Name: {}", profile.name));
+    }
+    if !profile.pronouns.is_empty() {
+        facts.push(format!("- Pronouns: {}", profile.pronouns));
+    }
+    if !profile.location.is_empty() {
+        facts.push(format!("- Lives in: {}", profile.location));
+    }
+    if !profile.job.is_empty() {
+        facts.push(format!("- Job: {}", profile.job));
+    }
+    match profile.units {
+        Some(Units::Metric) => facts.push("- Prefers metric units".to_string()),
+        Some(Units::Imperial) => facts.push("- Prefers imperial units".to_string()),
+        None => {}
+    }
+    if facts.is_empty() {
+        return String::new();
+    }
+    format!(
+        "\n\nAbout the user (use this instead of asking):\n{}\n",
+        facts.join("\n")
+    )
+}
+
+#[tauri::command]
+pub fn cmd_get_user_profile() -> UserProfile {
+    current()
+}
+
+#[tauri::command]
+pub fn cmd_set_user_profile(app: tauri::AppHandle, profile: UserProfile) -> Result<(), String> {
+    let profile = profile.trimmed();
+    let s = serde_json::to_string_pretty(&profile).map_err(|e| e.to_string())?;
+    crate::history::atomic_write(&profile_path(&app)?, s.as_bytes()).map_err(|e| e.to_string())?;
+    *CURRENT.lock().unwrap() = Some(profile.clone());
+    crate::events::emit(&app, profile);
+    Ok(())
+}
@@ -118,3 +118,23 @@ pub fn ensure_parent_directory_exists(path: &Path) -> Result<()> {
     }
     Ok(())
 }
+
+// writes mono 16-bit PCM samples to a WAV file
+pub fn write_wav_i16(path: &Path, samples: &[i16], sample_rate: u32) -> Result<()> {
+    ensure_parent_directory_exists(path)?;
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)
+        .with_context(|| format!("failed to create WAV file {}", path.display()))?;
+    for &s in samples {
+        writer.write_sample(s)?;
+    }
+    writer
+        .finalize()
+        .with_context(|| format!("failed to finalize WAV file {}", path.display()))?;
+    Ok(())
+}
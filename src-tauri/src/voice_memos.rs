@@ -0,0 +1,396 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Voice memos like on a phone: "record a voice memo" keeps the microphone open until
+// you say "stop", then saves the audio and its transcript as
+// memos/<date>_<time>.wav and .txt in the app's config folder. "Play my last memo"
+// plays it back and "read my last memo" reads the transcript. Recording happens in
+// the voice loop (see run_jarvis), which owns the microphone.
+
+use crate::audio_input::SAMPLE_RATE;
+use crate::models::{AppContext, Config};
+use anyhow::{anyhow, Result};
+use chrono::{Local, NaiveDateTime, TimeZone};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::Manager;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct VoiceMemosConfig {
+    // recording ends by itself after this long
+    pub max_minutes: u64,
+    // said on their own, these end the recording
+    pub stop_phrases: Vec<String>,
+}
+
+impl Default for VoiceMemosConfig {
+    fn default() -> Self {
+        Self {
+            max_minutes: 10,
+            stop_phrases: vec![
+                "stop".into(),
+                "stop recording".into(),
+                "end memo".into(),
+                "end recording".into(),
+            ],
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceMemo {
+    // the file name without extension, e.g. "2025-10-16_08-30-12"
+    pub id: String,
+    // unix ms
+    pub recorded_at: i64,
+    pub seconds: u64,
+    pub transcript: String,
+    pub audio_path: String,
+}
+
+static RECORD: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:record|take|make|start)\s+(?:a\s+|a\s+new\s+|new\s+)?(?:voice\s+)?memo\b",
+        r"|\b(?:record|take)\s+(?:a\s+)?voice\s+note\b"
+    ))
+    .expect("Failed to compile record memo regex")
+});
+
+static PLAY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:play|replay)\s+(?:back\s+)?(?:my|the)\s+",
+        r"(?:last|latest|previous|most recent)\s+(?:voice\s+)?memo\b"
+    ))
+    .expect("Failed to compile play memo regex")
+});
+
+static READ: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?i)\b(?:read(?:\s+me)?|what(?:'s| is| was| did i say in))\s+(?:my|the)\s+",
+        r"(?:last|latest|previous|most recent)\s+(?:voice\s+)?memo\b"
+    ))
+    .expect("Failed to compile read memo regex")
+});
+
+// a stretch of speech that ends in this much silence is checked for a stop phrase
+const STOP_SILENCE_MS: usize = 600;
+// longer stretches are part of the memo and not transcribed on the way
+const STOP_MAX_SPEECH_MS: usize = 2500;
+
+const FILE_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+
+fn memos_dir(app: &tauri::AppHandle) -> Result<PathBuf> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| anyhow!("Failed to resolve app config dir: {e}"))?
+        .join("memos");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+// only the phrase on its own counts, so "we need a stop sign" stays in the memo
+fn is_stop_phrase(heard: &str, phrases: &[String]) -> bool {
+    let heard = words(heard);
+    !heard.is_empty() && phrases.iter().any(|p| words(p) == heard)
+}
+
+// "record a voice memo", when the memo trigger is enabled
+pub fn wants_recording(app: &tauri::AppHandle, config: &Config, prompt: &str) -> bool {
+    let triggers = crate::triggers::load(app);
+    triggers.voice_memos.matches(prompt, &config.whisper_language) && RECORD.is_match(prompt)
+}
+
+// Everything heard until a stop phrase, without the phrase itself
+fn capture(
+    app: &AppContext,
+    is_running: &AtomicBool,
+    config: &VoiceMemosConfig,
+) -> Result<Vec<i16>> {
+    let stt = app
+        .pipeline
+        .stt
+        .as_ref()
+        .ok_or_else(|| anyhow!("Whisper is not loaded"))?;
+    let frame_ms = app.config.frame_duration_ms.max(10);
+    let frame_length = (SAMPLE_RATE / 1000) * frame_ms;
+    let end_silence_frames = STOP_SILENCE_MS.div_ceil(frame_ms);
+    let max_speech = (SAMPLE_RATE / 1000) * STOP_MAX_SPEECH_MS;
+    let max_samples = config.max_minutes.max(1) as usize * 60 * SAMPLE_RATE;
+
+    if let Ok(mut buf) = app.audio_buffer.lock() {
+        buf.clear();
+    }
+    let mut recording: Vec<i16> = Vec::new();
+    // where the stretch of speech being heard began
+    let mut speech_start: Option<usize> = None;
+    let mut silent_frames = 0usize;
+
+    while recording.len() < max_samples {
+        if !is_running.load(Ordering::Relaxed) {
+            return Err(anyhow!("Recording stopped"));
+        }
+        let waited = app.audio_buffer.take_within(frame_length, Duration::from_millis(200))?;
+        let Some(frame) = waited else {
+            continue;
+        };
+        let is_speech = app
+            .vad
+            .lock()
+            .map_err(|e| anyhow!("Failed to lock VAD mutex: {e}"))?
+            .is_voice_segment(&frame)
+            .unwrap_or(false);
+        recording.extend_from_slice(&frame);
+        if is_speech {
+            silent_frames = 0;
+            speech_start.get_or_insert(recording.len() - frame.len());
+            continue;
+        }
+        silent_frames += 1;
+        let Some(start) = speech_start else {
+            continue;
+        };
+        if silent_frames < end_silence_frames {
+            continue;
+        }
+        speech_start = None;
+        let end = recording.len() - silent_frames * frame_length;
+        if end <= start || end - start > max_speech {
+            continue;
+        }
+        let job = stt.submit(
+            crate::get_text::pad_for_whisper(&recording[start..end]),
+            &app.config.whisper_language,
+        );
+        let heard = match job.wait_or_abandon(|| !is_running.load(Ordering::Relaxed)) {
+            Ok(Some(text)) => text,
+            Ok(None) => return Err(anyhow!("Recording stopped")),
+            Err(e) => {
+                eprintln!("[WARN] Failed to check memo for a stop phrase: {}", e);
+                continue;
+            }
+        };
+        if is_stop_phrase(&heard, &config.stop_phrases) {
+            println!("[DEBUG] Stop phrase heard, ending voice memo");
+            recording.truncate(start);
+            return Ok(recording);
+        }
+    }
+    println!("[DEBUG] Voice memo reached its length limit");
+    Ok(recording)
+}
+
+fn save(app: &tauri::AppHandle, samples: &[i16], transcript: &str) -> Result<VoiceMemo> {
+    let now = Local::now();
+    let id = now.format(FILE_FORMAT).to_string();
+    let dir = memos_dir(app)?;
+    let audio_path = dir.join(format!("{}.wav", id));
+    crate::utils::write_wav_i16(&audio_path, samples, SAMPLE_RATE as u32)?;
+    std::fs::write(dir.join(format!("{}.txt", id)), transcript)?;
+    Ok(VoiceMemo {
+        id,
+        recorded_at: now.timestamp_millis(),
+        seconds: (samples.len() / SAMPLE_RATE) as u64,
+        transcript: transcript.to_string(),
+        audio_path: audio_path.to_string_lossy().into_owned(),
+    })
+}
+
+async fn say(app: &tauri::AppHandle, config: &Config, text: &str) {
+    crate::events::emit(
+        app,
+        crate::events::NewMessage::new(crate::events::Role::Assistant, text)
+            .with("localCommand", true),
+    );
+    if let Err(e) = crate::tts::speak_with(config, text).await {
+        eprintln!("[ERROR] Failed to speak voice memo reply: {}", e);
+    }
+}
+
+// Records a memo from the voice loop's microphone and saves it with its transcript
+pub async fn record(
+    app: &AppContext,
+    is_running: &AtomicBool,
+    tauri_app: &tauri::AppHandle,
+    config: &Config,
+) -> Result<Option<VoiceMemo>> {
+    let memo_config = &config.voice_memos;
+    let stop = memo_config
+        .stop_phrases
+        .first()
+        .map(|p| p.trim())
+        .unwrap_or("stop");
+    say(
+        tauri_app,
+        config,
+        &format!("Recording. Say \"{}\" when you're done.", stop),
+    )
+    .await;
+    if let Err(e) = app.audio_output.play_sound("assets/beep.wav") {
+        eprintln!("Failed to play beep sound: {e}");
+    }
+    let samples = capture(app, is_running, memo_config)?;
+    if samples.len() < SAMPLE_RATE / 2 {
+        say(tauri_app, config, "I didn't hear anything, so there's no memo.").await;
+        return Ok(None);
+    }
+    let job = app
+        .pipeline
+        .stt
+        .as_ref()
+        .ok_or_else(|| anyhow!("Whisper is not loaded"))?
+        .submit(crate::get_text::pad_for_whisper(&samples), &config.whisper_language);
+    let Some(transcript) = job.wait_while(is_running).await? else {
+        return Err(anyhow!("Recording stopped"));
+    };
+    let memo = save(tauri_app, &samples, transcript.trim())?;
+    crate::events::emit(tauri_app, crate::events::VoiceMemosChanged {});
+    let spoken = format!(
+        "Saved your {} memo.",
+        crate::timers::spoken_duration(memo.seconds.max(1))
+    );
+    crate::events::emit(
+        tauri_app,
+        crate::events::NewMessage::new(
+            crate::events::Role::Assistant,
+            format!("{}\n\n> {}", spoken, memo.transcript),
+        )
+        .with("localCommand", true),
+    );
+    if let Err(e) = crate::tts::speak_with(config, &spoken).await {
+        eprintln!("[ERROR] Failed to speak voice memo reply: {}", e);
+    }
+    Ok(Some(memo))
+}
+
+fn read_memo(wav: &Path) -> Option<VoiceMemo> {
+    let id = wav.file_stem()?.to_str()?.to_string();
+    let recorded_at = NaiveDateTime::parse_from_str(&id, FILE_FORMAT)
+        .ok()
+        .and_then(|t| Local.from_local_datetime(&t).earliest())?
+        .timestamp_millis();
+    let seconds = hound::WavReader::open(wav)
+        .map(|r| r.duration() as u64 / r.spec().sample_rate.max(1) as u64)
+        .unwrap_or(0);
+    let transcript = std::fs::read_to_string(wav.with_extension("txt")).unwrap_or_default();
+    Some(VoiceMemo {
+        id,
+        recorded_at,
+        seconds,
+        transcript: transcript.trim().to_string(),
+        audio_path: wav.to_string_lossy().into_owned(),
+    })
+}
+
+// newest first
+pub fn list(app: &tauri::AppHandle) -> Result<Vec<VoiceMemo>> {
+    let mut memos: Vec<VoiceMemo> = std::fs::read_dir(memos_dir(app)?)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "wav"))
+        .filter_map(|p| read_memo(&p))
+        .collect();
+    memos.sort_by_key(|m| std::cmp::Reverse(m.recorded_at));
+    Ok(memos)
+}
+
+fn spoken_when(memo: &VoiceMemo) -> String {
+    let Some(at) = Local.timestamp_millis_opt(memo.recorded_at).single() else {
+        return String::new();
+    };
+    let time = at.format("%-I:%M %p");
+    if at.date_naive() == Local::now().date_naive() {
+        format!("from {}", time)
+    } else {
+        format!("from {} at {}", at.format("%A, %B %-d"), time)
+    }
+}
+
+// Plays or reads out the last memo; false when the prompt asks for neither
+pub async fn handle(prompt: &str, app: &tauri::AppHandle, config: &Config) -> bool {
+    let play = PLAY.is_match(prompt);
+    if !play && !READ.is_match(prompt) {
+        return false;
+    }
+    let last = match list(app) {
+        Ok(memos) => memos.into_iter().next(),
+        Err(e) => {
+            eprintln!("[ERROR] Failed to list voice memos: {}", e);
+            None
+        }
+    };
+    let Some(memo) = last else {
+        say(app, config, "You don't have any voice memos yet.").await;
+        return true;
+    };
+    if !play {
+        let reply = if memo.transcript.is_empty() {
+            "I couldn't make out any words in your last memo.".to_string()
+        } else {
+            format!("Your memo {}: {}", spoken_when(&memo), memo.transcript)
+        };
+        say(app, config, &reply).await;
+        return true;
+    }
+    let audio = match std::fs::read(&memo.audio_path) {
+        Ok(audio) => audio,
+        Err(e) => {
+            eprintln!("[ERROR] Failed to read voice memo {}: {}", memo.audio_path, e);
+            say(app, config, "Sorry, I couldn't open your last memo.").await;
+            return true;
+        }
+    };
+    say(app, config, &format!("Your memo {}.", spoken_when(&memo))).await;
+    let volume = config.output_volume.unwrap_or(1.0);
+    let played = crate::audio_output::play_once(config.voice_output_device(), volume, audio);
+    if let Err(e) = played.await {
+        eprintln!("[ERROR] Failed to play voice memo: {}", e);
+        say(app, config, "Sorry, I couldn't play your last memo.").await;
+    }
+    true
+}
+
+#[tauri::command]
+pub fn cmd_list_voice_memos(app: tauri::AppHandle) -> Result<Vec<VoiceMemo>, String> {
+    list(&app).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn cmd_delete_voice_memo(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    // ids are file names made by `save`; anything else could point outside the folder
+    if NaiveDateTime::parse_from_str(&id, FILE_FORMAT).is_err() {
+        return Err(format!("Unknown voice memo '{}'", id));
+    }
+    let dir = memos_dir(&app).map_err(|e| e.to_string())?;
+    std::fs::remove_file(dir.join(format!("{}.wav", id))).map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_file(dir.join(format!("{}.txt", id)));
+    crate::events::emit(&app, crate::events::VoiceMemosChanged {});
+    Ok(())
+}
@@ -0,0 +1,149 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Downloads for URLs found in prompts. Everything goes through here so a link to a
+// huge file or a slow server can't hold up the answer.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use url::Url;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct WebFetchConfig {
+    pub enabled: bool,
+    // downloads larger than this are skipped
+    pub max_bytes: u64,
+    pub timeout_secs: u64,
+    // when not empty, only these domains (and their subdomains) are fetched
+    pub allowed_domains: Vec<String>,
+    pub blocked_domains: Vec<String>,
+}
+
+impl Default for WebFetchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_bytes: 10 * 1024 * 1024,
+            timeout_secs: 15,
+            allowed_domains: Vec::new(),
+            blocked_domains: Vec::new(),
+        }
+    }
+}
+
+pub struct Fetched {
+    // final URL after redirects
+    pub url: String,
+    pub content_type: Option<String>,
+    pub bytes: Vec<u8>,
+}
+
+// "example.com" matches example.com and www.example.com, not badexample.com
+fn domain_matches(host: &str, domain: &str) -> bool {
+    let domain = domain.trim().trim_start_matches("*.").trim_end_matches('.').to_lowercase();
+    !domain.is_empty() && (host == domain || host.ends_with(&format!(".{}", domain)))
+}
+
+pub fn is_allowed(config: &WebFetchConfig, url: &Url) -> bool {
+    if !matches!(url.scheme(), "http" | "https") {
+        return false;
+    }
+    let Some(host) = url.host_str().map(|h| h.to_lowercase()) else {
+        return false;
+    };
+    if config.blocked_domains.iter().any(|d| domain_matches(&host, d)) {
+        return false;
+    }
+    config.allowed_domains.iter().all(|d| d.trim().is_empty())
+        || config.allowed_domains.iter().any(|d| domain_matches(&host, d))
+}
+
+// only what build_parts_with_media knows how to use
+fn is_supported_type(ct: &str) -> bool {
+    let ct = ct.to_lowercase();
+    ct.starts_with("image/")
+        || ct.starts_with("text/")
+        || ct.starts_with("application/xhtml")
+        || ct.starts_with("application/json")
+        || ct.starts_with("application/xml")
+        || ct.starts_with("application/pdf")
+}
+
+pub fn client(config: &WebFetchConfig) -> Result<reqwest::Client> {
+    let timeout = Duration::from_secs(config.timeout_secs.max(1));
+    reqwest::Client::builder()
+        .user_agent(concat!("Jarvis/", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(timeout)
+        .timeout(timeout)
+        // redirects are checked against the lists too
+        .redirect(reqwest::redirect::Policy::custom({
+            let config = config.clone();
+            move |attempt| {
+                if attempt.previous().len() >= 5 || !is_allowed(&config, attempt.url()) {
+                    attempt.stop()
+                } else {
+                    attempt.follow()
+                }
+            }
+        }))
+        .build()
+        .map_err(|e| anyhow!("Failed to build HTTP client: {e}"))
+}
+
+// Downloads `url` within the configured limits
+pub async fn fetch(client: &reqwest::Client, config: &WebFetchConfig, url: &str) -> Result<Fetched> {
+    if !config.enabled {
+        return Err(anyhow!("URL fetching is disabled"));
+    }
+    let parsed = Url::parse(url)?;
+    if !is_allowed(config, &parsed) {
+        return Err(anyhow!("{} is not on the allowed list", parsed.host_str().unwrap_or(url)));
+    }
+
+    let mut resp = client.get(parsed).send().await?;
+    if !resp.status().is_success() {
+        return Err(anyhow!("{} returned {}", url, resp.status()));
+    }
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+    if let Some(ct) = content_type.as_deref().filter(|ct| !is_supported_type(ct)) {
+        return Err(anyhow!("Unsupported content type {}", ct));
+    }
+    if resp.content_length().is_some_and(|len| len > config.max_bytes) {
+        return Err(anyhow!("{} is larger than {} bytes", url, config.max_bytes));
+    }
+
+    // the length header can be missing or wrong, so the body is counted as it arrives
+    let final_url = resp.url().to_string();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        if bytes.len() as u64 + chunk.len() as u64 > config.max_bytes {
+            return Err(anyhow!("{} is larger than {} bytes", url, config.max_bytes));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(Fetched {
+        url: final_url,
+        content_type,
+        bytes,
+    })
+}
@@ -0,0 +1,242 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Quick facts from Wikipedia: "who was Ada Lovelace", "what is the Eiffel Tower",
+// "tell me about the Rosetta Stone". The start of the article is read out and the
+// article is named at the end; the link goes into the chat message. "Who is" and
+// "what is" only count for names (capitalized the way Whisper writes them), so
+// "what is love" still goes to the LLM, and so does anything Wikipedia has no single
+// article for.
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct WikipediaConfig {
+    // Wikipedia language edition, e.g. "de"; empty follows whisper_language
+    pub language: String,
+    // how much of the article is read out
+    pub sentences: usize,
+}
+
+impl Default for WikipediaConfig {
+    fn default() -> Self {
+        Self {
+            language: String::new(),
+            sentences: 2,
+        }
+    }
+}
+
+// asking for Wikipedia by name, or "tell me about": any subject
+static EXPLICIT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)^\s*(?:tell me about|look up|search wikipedia for|what does wikipedia say about)\s+(.+?)(?:\s+on wikipedia)?[?.!]*\s*$",
+    )
+    .expect("Failed to compile explicit lookup regex")
+});
+
+// "who was ...", "what is ...": only for names
+static QUESTION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(?:who|what)(?:\s+(?:is|was|are|were)|'s)\s+(.+?)[?.!]*\s*$")
+        .expect("Failed to compile entity question regex")
+});
+
+static PARENTHESES: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\s*\([^()]*\)").expect("Failed to compile parentheses regex"));
+
+// lowercase words that may sit inside a name: "Leonardo da Vinci", "the Bay of Pigs"
+const NAME_JOINERS: &[&str] = &[
+    "a", "an", "the", "of", "and", "de", "da", "di", "du", "del", "der", "van", "von", "la",
+    "le", "el", "in", "on", "for", "y",
+];
+
+// "my", "this", ... make it about the user or the screen, not an article
+const NOT_SUBJECTS: &[&str] = &[
+    "my", "your", "yourself", "you", "me", "our", "this", "that", "it", "there", "here",
+];
+
+// The article's opening and where it is
+pub struct Summary {
+    pub title: String,
+    pub url: String,
+    // as shown in the chat
+    pub text: String,
+    // without the parentheses (pronunciations, dates) that are awkward to hear
+    pub spoken: String,
+}
+
+fn looks_like_name(subject: &str) -> bool {
+    let words: Vec<&str> = subject.split_whitespace().collect();
+    if words.is_empty() || words.len() > 6 {
+        return false;
+    }
+    let mut capitalized = 0;
+    for word in &words {
+        let first = word.chars().next().unwrap_or(' ');
+        if first.is_uppercase() || first.is_ascii_digit() {
+            capitalized += 1;
+        } else if !NAME_JOINERS.contains(&word.to_lowercase().as_str()) {
+            return false;
+        }
+    }
+    capitalized > 0
+}
+
+fn subject(prompt: &str) -> Option<String> {
+    let subject = if let Some(m) = EXPLICIT.captures(prompt) {
+        m[1].to_string()
+    } else {
+        let m = QUESTION.captures(prompt)?;
+        looks_like_name(&m[1]).then(|| m[1].to_string())?
+    };
+    let subject = subject.trim().to_string();
+    let first = subject.split_whitespace().next()?.to_lowercase();
+    (!NOT_SUBJECTS.contains(&first.as_str())).then_some(subject)
+}
+
+fn language(config: &WikipediaConfig, whisper_language: &str) -> String {
+    let configured = config.language.trim();
+    let lang = if configured.is_empty() {
+        whisper_language.trim()
+    } else {
+        configured
+    };
+    if (2..=3).contains(&lang.len()) && lang.chars().all(|c| c.is_ascii_lowercase()) {
+        lang.to_string()
+    } else {
+        "en".to_string()
+    }
+}
+
+fn client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        // Wikimedia asks API clients to identify themselves
+        .user_agent(concat!("Jarvis/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(8))
+        .build()
+        .map_err(|e| anyhow!("Failed to build HTTP client: {e}"))
+}
+
+// The title of the best matching article
+async fn search(client: &reqwest::Client, lang: &str, subject: &str) -> Result<Option<String>> {
+    let resp = client
+        .get(format!("https://{}.wikipedia.org/w/api.php", lang))
+        .query(&[
+            ("action", "query"),
+            ("list", "search"),
+            ("srsearch", subject),
+            ("srlimit", "1"),
+            ("format", "json"),
+        ])
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        return Err(anyhow!("Wikipedia search returned {}", resp.status()));
+    }
+    let data: Value = resp.json().await?;
+    Ok(data["query"]["search"][0]["title"]
+        .as_str()
+        .map(str::to_string))
+}
+
+fn first_sentences(text: &str, count: usize) -> String {
+    let mut out = String::new();
+    let mut taken = 0;
+    for piece in text.split_inclusive(". ") {
+        out.push_str(piece);
+        taken += 1;
+        if taken >= count.max(1) {
+            break;
+        }
+    }
+    out.trim().to_string()
+}
+
+fn article_url(lang: &str, title: &str) -> String {
+    format!("https://{}.wikipedia.org/wiki/{}", lang, title.replace(' ', "_"))
+}
+
+async fn summary(
+    client: &reqwest::Client,
+    lang: &str,
+    title: &str,
+    sentences: usize,
+) -> Result<Option<Summary>> {
+    let base = format!("https://{}.wikipedia.org/api/rest_v1/page/summary/", lang);
+    let mut url = reqwest::Url::parse(&base)?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow!("Invalid Wikipedia URL"))?
+        .pop_if_empty()
+        .push(&title.replace(' ', "_"));
+    let resp = client.get(url).send().await?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !resp.status().is_success() {
+        return Err(anyhow!("Wikipedia returned {}", resp.status()));
+    }
+    let data: Value = resp.json().await?;
+    // a list of meanings isn't an answer
+    if data["type"].as_str() == Some("disambiguation") {
+        return Ok(None);
+    }
+    let Some(extract) = data["extract"].as_str().filter(|e| !e.trim().is_empty()) else {
+        return Ok(None);
+    };
+    let text = first_sentences(extract, sentences);
+    let spoken = PARENTHESES.replace_all(&text, "").into_owned();
+    Ok(Some(Summary {
+        title: data["title"].as_str().unwrap_or(title).to_string(),
+        url: data["content_urls"]["desktop"]["page"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| article_url(lang, title)),
+        text,
+        spoken,
+    }))
+}
+
+async fn find(lang: &str, subject: &str, sentences: usize) -> Result<Option<Summary>> {
+    let client = client()?;
+    match search(&client, lang, subject).await? {
+        Some(title) => summary(&client, lang, &title, sentences).await,
+        None => Ok(None),
+    }
+}
+
+// The article the prompt asks about, or None to leave the question to the LLM
+pub async fn lookup(
+    prompt: &str,
+    config: &WikipediaConfig,
+    whisper_language: &str,
+) -> Option<Summary> {
+    let subject = subject(prompt)?;
+    let lang = language(config, whisper_language);
+    match find(&lang, &subject, config.sentences).await {
+        Ok(found) => found,
+        Err(e) => {
+            eprintln!("[ERROR] Wikipedia lookup for '{}' failed: {}", subject, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,528 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// World clock, answered offline from the time zone database: "what time is it in
+// Tokyo", "what's 3 pm London time in New York", "when can I meet with Sydney". Places
+// are the cities in the database ("New York", "Tokyo"), a few countries and common
+// names (see ALIASES) and whatever `aliases` adds; "here" and "my time" mean this PC.
+
+use crate::intent::{self, Slots};
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc,
+    Weekday,
+};
+use chrono_tz::{Tz, TZ_VARIANTS};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct WorldClockConfig {
+    // extra place names and their time zone, e.g. "the office": "America/Chicago"
+    pub aliases: BTreeMap<String, String>,
+    // working hours (24-hour clock) that meeting suggestions stay within, for everyone
+    pub work_start_hour: u32,
+    pub work_end_hour: u32,
+}
+
+impl Default for WorldClockConfig {
+    fn default() -> Self {
+        Self {
+            aliases: BTreeMap::new(),
+            work_start_hour: 9,
+            work_end_hour: 17,
+        }
+    }
+}
+
+// names people use that aren't the city in a zone name
+const ALIASES: &[(&str, &str)] = &[
+    ("utc", "UTC"),
+    ("gmt", "UTC"),
+    ("pacific time", "America/Los_Angeles"),
+    ("pst", "America/Los_Angeles"),
+    ("pdt", "America/Los_Angeles"),
+    ("mountain time", "America/Denver"),
+    ("central time", "America/Chicago"),
+    ("eastern time", "America/New_York"),
+    ("est", "America/New_York"),
+    ("edt", "America/New_York"),
+    ("cet", "Europe/Berlin"),
+    ("bst", "Europe/London"),
+    ("san francisco", "America/Los_Angeles"),
+    ("seattle", "America/Los_Angeles"),
+    ("silicon valley", "America/Los_Angeles"),
+    ("california", "America/Los_Angeles"),
+    ("washington", "America/New_York"),
+    ("boston", "America/New_York"),
+    ("miami", "America/New_York"),
+    ("atlanta", "America/New_York"),
+    ("new jersey", "America/New_York"),
+    ("dallas", "America/Chicago"),
+    ("houston", "America/Chicago"),
+    ("austin", "America/Chicago"),
+    ("hawaii", "Pacific/Honolulu"),
+    ("canada", "America/Toronto"),
+    ("ottawa", "America/Toronto"),
+    ("montreal", "America/Toronto"),
+    ("mexico", "America/Mexico_City"),
+    ("brazil", "America/Sao_Paulo"),
+    ("são paulo", "America/Sao_Paulo"),
+    ("rio", "America/Sao_Paulo"),
+    ("rio de janeiro", "America/Sao_Paulo"),
+    ("uk", "Europe/London"),
+    ("england", "Europe/London"),
+    ("manchester", "Europe/London"),
+    ("ireland", "Europe/Dublin"),
+    ("germany", "Europe/Berlin"),
+    ("munich", "Europe/Berlin"),
+    ("frankfurt", "Europe/Berlin"),
+    ("hamburg", "Europe/Berlin"),
+    ("cologne", "Europe/Berlin"),
+    ("france", "Europe/Paris"),
+    ("spain", "Europe/Madrid"),
+    ("barcelona", "Europe/Madrid"),
+    ("italy", "Europe/Rome"),
+    ("milan", "Europe/Rome"),
+    ("netherlands", "Europe/Amsterdam"),
+    ("switzerland", "Europe/Zurich"),
+    ("geneva", "Europe/Zurich"),
+    ("austria", "Europe/Vienna"),
+    ("poland", "Europe/Warsaw"),
+    ("ukraine", "Europe/Kyiv"),
+    ("kiev", "Europe/Kyiv"),
+    ("turkey", "Europe/Istanbul"),
+    ("israel", "Asia/Jerusalem"),
+    ("tel aviv", "Asia/Jerusalem"),
+    ("uae", "Asia/Dubai"),
+    ("abu dhabi", "Asia/Dubai"),
+    ("india", "Asia/Kolkata"),
+    ("mumbai", "Asia/Kolkata"),
+    ("delhi", "Asia/Kolkata"),
+    ("new delhi", "Asia/Kolkata"),
+    ("bangalore", "Asia/Kolkata"),
+    ("china", "Asia/Shanghai"),
+    ("beijing", "Asia/Shanghai"),
+    ("shenzhen", "Asia/Shanghai"),
+    ("japan", "Asia/Tokyo"),
+    ("osaka", "Asia/Tokyo"),
+    ("korea", "Asia/Seoul"),
+    ("south korea", "Asia/Seoul"),
+    ("australia", "Australia/Sydney"),
+    ("canberra", "Australia/Sydney"),
+    ("new zealand", "Pacific/Auckland"),
+    ("wellington", "Pacific/Auckland"),
+];
+
+// zone-name cities that are also everyday words
+const NOT_PLACES: &[&str] = &[
+    "wake",
+    "christmas",
+    "easter",
+    "general",
+    "center",
+    "north",
+    "south",
+    "west",
+    "continental",
+    "troll",
+    "casey",
+    "davis",
+    "palmer",
+    "stanley",
+];
+
+// this PC's time zone
+const HERE: &[&str] = &["here", "my time", "local time", "my time zone", "my timezone"];
+
+// the last part of every zone name, "Asia/Tokyo" -> "tokyo"
+static CITIES: Lazy<HashMap<String, Tz>> = Lazy::new(|| {
+    TZ_VARIANTS
+        .iter()
+        .filter(|tz| tz.name().contains('/') && !tz.name().starts_with("Etc/"))
+        .filter_map(|tz| {
+            let city = tz.name().rsplit('/').next()?;
+            Some((city.replace('_', " ").to_lowercase(), *tz))
+        })
+        .collect()
+});
+
+static CLOCK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(\d{1,2})(?:[:.](\d{2}))?\s*(a\.?\s?m\b\.?|p\.?\s?m\b\.?|o'clock)?")
+        .expect("Failed to compile clock regex")
+});
+
+static NOON: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(noon|midday|midnight)\b").expect("Failed to compile noon regex")
+});
+
+static MEETING: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(meet|meeting|call|sync)\b").expect("Failed to compile meeting regex")
+});
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Zone {
+    Local,
+    Named(Tz),
+}
+
+impl Zone {
+    // the clock on the wall there at `at`
+    fn wall(self, at: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Zone::Local => at.with_timezone(&Local).naive_local(),
+            Zone::Named(tz) => at.with_timezone(&tz).naive_local(),
+        }
+    }
+
+    // None for a time skipped by a daylight saving change
+    fn instant(self, wall: NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            Zone::Local => Local
+                .from_local_datetime(&wall)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+            Zone::Named(tz) => tz
+                .from_local_datetime(&wall)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Place {
+    name: String,
+    zone: Zone,
+    // "London time": the place a time is given in
+    names_time: bool,
+}
+
+impl Place {
+    fn local() -> Self {
+        Self {
+            name: String::new(),
+            zone: Zone::Local,
+            names_time: false,
+        }
+    }
+
+    // "in Tokyo", or "your time"
+    fn label(&self) -> String {
+        match self.zone {
+            Zone::Local => "your time".to_string(),
+            Zone::Named(_) => format!("in {}", self.name),
+        }
+    }
+}
+
+enum Request {
+    Now(Vec<Place>),
+    Convert { time: NaiveTime, from: Place, to: Place },
+    Meeting(Vec<Place>),
+}
+
+fn lookup(key: &str, config: &WorldClockConfig) -> Option<Zone> {
+    if HERE.contains(&key) {
+        return Some(Zone::Local);
+    }
+    let configured = config
+        .aliases
+        .iter()
+        .find(|(name, _)| name.trim().to_lowercase() == key)
+        .map(|(_, tz)| tz.as_str());
+    let builtin = ALIASES.iter().find(|(name, _)| *name == key).map(|(_, tz)| *tz);
+    if let Some(tz) = configured.or(builtin) {
+        return tz.parse::<Tz>().ok().map(Zone::Named);
+    }
+    if NOT_PLACES.contains(&key) {
+        return None;
+    }
+    CITIES.get(key).copied().map(Zone::Named)
+}
+
+// The places named in the text, in order; longer names win ("new york" over "york")
+fn places(text: &str, config: &WorldClockConfig) -> Vec<Place> {
+    let shown: Vec<&str> = text
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()))
+        .collect();
+    let keys: Vec<String> = shown
+        .iter()
+        .map(|w| {
+            let w = w.to_lowercase();
+            let stem = w.strip_suffix("'s").or_else(|| w.strip_suffix("’s"));
+            stem.unwrap_or(&w).to_string()
+        })
+        .collect();
+
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < keys.len() {
+        let hit = (1..=3)
+            .rev()
+            .filter(|n| i + n <= keys.len())
+            .find_map(|n| lookup(&keys[i..i + n].join(" "), config).map(|zone| (n, zone)));
+        let Some((n, zone)) = hit else {
+            i += 1;
+            continue;
+        };
+        let name = shown[i..i + n].join(" ");
+        let names_time = name.to_lowercase().ends_with(" time")
+            || keys.get(i + n).is_some_and(|w| w == "time");
+        found.push(Place {
+            name: name.trim_end_matches("'s").to_string(),
+            zone,
+            names_time,
+        });
+        i += n;
+    }
+    found
+}
+
+// "3 pm", "15:30", "9 o'clock", "noon"
+fn clock_time(text: &str) -> Option<NaiveTime> {
+    if let Some(m) = NOON.captures(text) {
+        let hour = if m[1].eq_ignore_ascii_case("midnight") { 0 } else { 12 };
+        return NaiveTime::from_hms_opt(hour, 0, 0);
+    }
+    CLOCK.captures_iter(text).find_map(|m| {
+        // a bare number ("in 2 hours") is not a time
+        if m.get(2).is_none() && m.get(3).is_none() {
+            return None;
+        }
+        let mut hour: u32 = m[1].parse().ok()?;
+        let minute: u32 = m.get(2).map_or(Some(0), |v| v.as_str().parse().ok())?;
+        let suffix = m.get(3).map(|s| s.as_str().to_lowercase()).unwrap_or_default();
+        if suffix.starts_with('p') && hour < 12 {
+            hour += 12;
+        } else if suffix.starts_with('a') && hour == 12 {
+            hour = 0;
+        }
+        NaiveTime::from_hms_opt(hour, minute, 0)
+    })
+}
+
+fn parse(prompt: &str, config: &WorldClockConfig) -> Option<Request> {
+    let mut found = places(prompt, config);
+    let named: Vec<Place> = found
+        .iter()
+        .filter(|p| p.zone != Zone::Local)
+        .cloned()
+        .collect();
+    if named.is_empty() {
+        return None;
+    }
+    if MEETING.is_match(prompt) {
+        return Some(Request::Meeting(named));
+    }
+    let Some(time) = clock_time(prompt) else {
+        return Some(Request::Now(named));
+    };
+    // "3 pm London time in New York" goes from the first place to the last; with one
+    // place the time is yours unless it is "Tokyo time"
+    let (from, to) = if found.len() >= 2 {
+        let to = found.pop()?;
+        (found.swap_remove(0), to)
+    } else if found[0].names_time {
+        (found.swap_remove(0), Place::local())
+    } else {
+        (Place::local(), found.swap_remove(0))
+    };
+    Some(Request::Convert { time, from, to })
+}
+
+fn spoken_time(t: NaiveDateTime) -> String {
+    if t.minute() == 0 {
+        t.format("%-I %p").to_string()
+    } else {
+        t.format("%-I:%M %p").to_string()
+    }
+}
+
+fn spoken_span(minutes: i64) -> String {
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    let unit = |n: i64, one: &str| format!("{} {}{}", n, one, if n == 1 { "" } else { "s" });
+    match (hours, minutes) {
+        (0, m) => unit(m, "minute"),
+        (h, 0) => unit(h, "hour"),
+        (h, m) => format!("{} {}", unit(h, "hour"), unit(m, "minute")),
+    }
+}
+
+// " on Thursday" when the day there isn't the day of `base`
+fn other_day(wall: NaiveDateTime, base: NaiveDateTime) -> String {
+    if wall.date() == base.date() {
+        String::new()
+    } else {
+        format!(" on {}", wall.format("%A"))
+    }
+}
+
+fn describe_now(place: &Place, now: DateTime<Utc>) -> String {
+    let (wall, here) = (place.zone.wall(now), Zone::Local.wall(now));
+    let diff = (wall - here).num_minutes();
+    let offset = match diff {
+        0 => "the same as your time".to_string(),
+        d if d > 0 => format!("{} ahead of you", spoken_span(d)),
+        d => format!("{} behind you", spoken_span(-d)),
+    };
+    format!(
+        "It's {}{} {}, {}.",
+        spoken_time(wall),
+        other_day(wall, here),
+        place.label(),
+        offset
+    )
+}
+
+fn describe_conversion(time: NaiveTime, from: &Place, to: &Place) -> String {
+    let start = from.zone.wall(Utc::now()).date().and_time(time);
+    let Some(instant) = from.zone.instant(start) else {
+        return format!(
+            "{} doesn't exist {} today because of the daylight saving change.",
+            spoken_time(start),
+            from.label()
+        );
+    };
+    let there = to.zone.wall(instant);
+    format!(
+        "{} {} is {}{} {}.",
+        spoken_time(start),
+        from.label(),
+        spoken_time(there),
+        other_day(there, start),
+        to.label()
+    )
+}
+
+// "today", "tomorrow" or "on Monday", seen from here
+fn spoken_day(wall: NaiveDateTime, now: DateTime<Utc>) -> String {
+    let days = (wall.date() - Zone::Local.wall(now).date()).num_days();
+    match days {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        _ => format!("on {}", wall.format("%A")),
+    }
+}
+
+// The first half-hour slots in the coming week that are within working hours on a
+// weekday for you and everyone named
+fn describe_meeting(places: &[Place], config: &WorldClockConfig) -> String {
+    let now = Utc::now();
+    let (start_min, end_min) = (config.work_start_hour * 60, config.work_end_hour * 60);
+    let mut zones = vec![Zone::Local];
+    zones.extend(places.iter().map(|p| p.zone));
+    let fits = |at: DateTime<Utc>| {
+        zones.iter().all(|z| {
+            let wall = z.wall(at);
+            let minute = wall.hour() * 60 + wall.minute();
+            !matches!(wall.weekday(), Weekday::Sat | Weekday::Sun)
+                && minute >= start_min
+                && minute + 30 <= end_min
+        })
+    };
+
+    let slot = Duration::minutes(30);
+    let first = now
+        .with_second(0)
+        .and_then(|t| t.with_nanosecond(0))
+        .map(|t| t + Duration::minutes(30 - i64::from(t.minute() % 30)))
+        .unwrap_or(now);
+    let Some(begin) = (0..7 * 48).map(|i| first + slot * i).find(|t| fits(*t)) else {
+        let names: Vec<&str> = places.iter().map(|p| p.name.as_str()).collect();
+        return format!(
+            "Working hours from {} to {} don't overlap between you and {} on any weekday.",
+            config.work_start_hour,
+            config.work_end_hour,
+            names.join(" and ")
+        );
+    };
+    let mut end = begin + slot;
+    while fits(end) {
+        end += slot;
+    }
+
+    let here = (Zone::Local.wall(begin), Zone::Local.wall(end));
+    let mut reply = format!(
+        "The next time that's within working hours for everyone is {} from {} to {} your time",
+        spoken_day(here.0, now),
+        spoken_time(here.0),
+        spoken_time(here.1)
+    );
+    for place in places {
+        reply.push_str(&format!(
+            ", {} to {} {}",
+            spoken_time(place.zone.wall(begin)),
+            spoken_time(place.zone.wall(end)),
+            place.label()
+        ));
+    }
+    reply.push('.');
+    reply
+}
+
+// The spoken answer, or None when the prompt names no place this knows
+pub fn answer(prompt: &str, config: &WorldClockConfig) -> Option<String> {
+    let reply = match parse(prompt, config)? {
+        Request::Now(places) => {
+            // "and in Berlin?" asks again for another place
+            if let [place] = places.as_slice() {
+                let slots = Slots {
+                    location: Some(place.name.clone()),
+                    day_offset: None,
+                };
+                intent::remember("world_clock", &slots);
+            }
+            let now = Utc::now();
+            places
+                .iter()
+                .map(|p| describe_now(p, now))
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+        Request::Convert { time, from, to } => describe_conversion(time, &from, &to),
+        Request::Meeting(places) => describe_meeting(&places, config),
+    };
+    Some(reply)
+}
+
+// A follow-up to "what time is it in ...", with the new place
+pub fn answer_now(location: &str, config: &WorldClockConfig) -> Option<String> {
+    let found: Vec<Place> = places(location, config)
+        .into_iter()
+        .filter(|p| p.zone != Zone::Local)
+        .collect();
+    let now = Utc::now();
+    let first = found.first()?;
+    intent::remember(
+        "world_clock",
+        &Slots {
+            location: Some(first.name.clone()),
+            day_offset: None,
+        },
+    );
+    Some(
+        found
+            .iter()
+            .map(|p| describe_now(p, now))
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
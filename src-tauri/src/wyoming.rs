@@ -0,0 +1,326 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// Wyoming protocol server, so Home Assistant's Assist pipeline (and the ESPHome
+// satellites behind it) can use Jarvis's Whisper for speech-to-text and its voice for
+// text-to-speech. Add it in Home Assistant under the Wyoming Protocol integration.
+//
+// Each event is one JSON line {"type", "data", "data_length", "payload_length"},
+// followed by `data_length` bytes of extra JSON data and `payload_length` bytes of audio.
+
+use crate::audio_input::SAMPLE_RATE;
+use crate::models::Config;
+use crate::stt::SttWorker;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicBool;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+// frames of synthesized audio per audio-chunk event
+const CHUNK_FRAMES: usize = 1024;
+// offered to Home Assistant when Whisper detects the language itself
+const AUTO_LANGUAGES: &[&str] = &[
+    "en", "de", "fr", "es", "it", "nl", "pt", "pl", "sv", "da", "no", "fi", "cs", "ru", "uk",
+    "tr", "ja", "zh", "ko",
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct WyomingConfig {
+    pub enabled: bool,
+    pub port: u16,
+    // Home Assistant usually runs on another machine; the protocol has no authentication
+    pub allow_network: bool,
+}
+
+impl Default for WyomingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 10300,
+            allow_network: true,
+        }
+    }
+}
+
+struct Event {
+    kind: String,
+    data: Map<String, Value>,
+    payload: Vec<u8>,
+}
+
+async fn read_event<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<Event>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+    let header: Value = serde_json::from_str(line.trim())?;
+    let kind = header["type"]
+        .as_str()
+        .ok_or_else(|| anyhow!("Wyoming event without a type"))?
+        .to_string();
+    let mut data = header["data"].as_object().cloned().unwrap_or_default();
+    let data_length = header["data_length"].as_u64().unwrap_or(0) as usize;
+    if data_length > 0 {
+        let mut buf = vec![0u8; data_length];
+        reader.read_exact(&mut buf).await?;
+        if let Value::Object(extra) = serde_json::from_slice(&buf)? {
+            data.extend(extra);
+        }
+    }
+    let mut payload = vec![0u8; header["payload_length"].as_u64().unwrap_or(0) as usize];
+    reader.read_exact(&mut payload).await?;
+    Ok(Some(Event {
+        kind,
+        data,
+        payload,
+    }))
+}
+
+async fn write_event<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    kind: &str,
+    data: Value,
+    payload: &[u8],
+) -> Result<()> {
+    let mut header = json!({ "type": kind, "data": data });
+    if !payload.is_empty() {
+        header["payload_length"] = payload.len().into();
+    }
+    let mut line = serde_json::to_vec(&header)?;
+    line.push(b'\n');
+    writer.write_all(&line).await?;
+    writer.write_all(payload).await?;
+    Ok(())
+}
+
+fn languages(config: &Config) -> Vec<String> {
+    let lang = config.whisper_language.trim().to_lowercase();
+    if lang.is_empty() || lang == "auto" {
+        AUTO_LANGUAGES.iter().map(|l| l.to_string()).collect()
+    } else {
+        vec![lang]
+    }
+}
+
+// Only ElevenLabs produces audio that can be sent on; the system voice plays locally
+fn has_voice(config: &Config) -> bool {
+    crate::tts::provider_for(config).name() != "system"
+}
+
+fn info(config: &Config) -> Value {
+    let attribution = json!({ "name": "Jarvis", "url": "https://github.com/Hav1ck/Jarvis" });
+    let version = env!("CARGO_PKG_VERSION");
+    let languages = languages(config);
+    let asr = json!([{
+        "name": "jarvis-whisper",
+        "description": "Jarvis speech-to-text (Whisper)",
+        "attribution": attribution,
+        "installed": true,
+        "version": version,
+        "models": [{
+            "name": crate::run_jarvis::DEFAULT_WHISPER_MODEL,
+            "description": format!("Whisper {}", crate::run_jarvis::DEFAULT_WHISPER_MODEL),
+            "attribution": attribution,
+            "installed": true,
+            "version": version,
+            "languages": languages,
+        }],
+    }]);
+    let tts = if has_voice(config) {
+        json!([{
+            "name": "jarvis-voice",
+            "description": "Jarvis text-to-speech (ElevenLabs)",
+            "attribution": attribution,
+            "installed": true,
+            "version": version,
+            "voices": [{
+                "name": config.voice_id,
+                "description": "Jarvis voice",
+                "attribution": attribution,
+                "installed": true,
+                "version": version,
+                "languages": languages,
+            }],
+        }])
+    } else {
+        json!([])
+    };
+    json!({ "asr": asr, "tts": tts })
+}
+
+#[derive(Clone, Copy)]
+struct AudioFormat {
+    rate: u32,
+    width: usize,
+    channels: usize,
+}
+
+impl AudioFormat {
+    fn from_data(data: &Map<String, Value>) -> Self {
+        let get = |key: &str, default: u64| {
+            data.get(key).and_then(Value::as_u64).unwrap_or(default)
+        };
+        Self {
+            rate: get("rate", SAMPLE_RATE as u64) as u32,
+            width: get("width", 2) as usize,
+            channels: get("channels", 1).max(1) as usize,
+        }
+    }
+}
+
+// 16 kHz mono for Whisper: the first channel, resampled the same way as the microphone
+fn to_whisper_samples(payload: &[u8], format: AudioFormat) -> Vec<i16> {
+    if format.width != 2 || format.rate == 0 {
+        return Vec::new();
+    }
+    let first_channel = payload
+        .chunks_exact(2 * format.channels)
+        .map(|frame| i16::from_le_bytes([frame[0], frame[1]]));
+    crate::audio_input::resample_to_16k(first_channel, format.rate)
+}
+
+async fn transcribe(app: &tauri::AppHandle, samples: Vec<i16>, language: &str) -> Result<String> {
+    let stt = SttWorker::shared(app).await?;
+    let keep_going = AtomicBool::new(true);
+    let text = stt
+        .submit(crate::get_text::pad_for_whisper(&samples), language)
+        .wait_while(&keep_going)
+        .await?
+        .unwrap_or_default();
+    crate::stats::record_stt(samples.len());
+    Ok(text.trim().to_string())
+}
+
+async fn synthesize<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    config: &Config,
+    text: &str,
+) -> Result<()> {
+    if !has_voice(config) {
+        return Err(anyhow!("Jarvis's Wyoming voice needs an ElevenLabs key and voice"));
+    }
+    let text = crate::tts_normalize::normalize(text, &config.tts_normalization);
+    let audio = match crate::tts::provider_for(config).synthesize(&text).await? {
+        crate::tts::Speech::Audio(bytes) => bytes,
+        crate::tts::Speech::Spoken => return Err(anyhow!("The voice did not return audio")),
+    };
+    let (rate, channels, samples) =
+        tokio::task::spawn_blocking(move || crate::audio_output::decode(audio)).await??;
+    let pcm: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+    let format = json!({ "rate": rate, "width": 2, "channels": channels });
+    write_event(writer, "audio-start", format.clone(), &[]).await?;
+    for chunk in pcm.chunks(CHUNK_FRAMES * 2 * channels.max(1) as usize) {
+        write_event(writer, "audio-chunk", format.clone(), chunk).await?;
+    }
+    write_event(writer, "audio-stop", json!({}), &[]).await
+}
+
+async fn handle(app: tauri::AppHandle, stream: TcpStream) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut samples: Vec<i16> = Vec::new();
+    let mut language: Option<String> = None;
+
+    while let Some(event) = read_event(&mut reader).await? {
+        // settings may change between requests
+        let config = crate::cmd_load_config(app.clone()).map_err(|e| anyhow!(e))?;
+        let config = crate::to_run_config(config);
+        let result = match event.kind.as_str() {
+            "describe" => write_event(&mut writer, "info", info(&config), &[]).await,
+            "transcribe" => {
+                language = event.data.get("language").and_then(Value::as_str).map(str::to_string);
+                Ok(())
+            }
+            "audio-start" => {
+                samples.clear();
+                Ok(())
+            }
+            "audio-chunk" => {
+                let format = AudioFormat::from_data(&event.data);
+                samples.extend(to_whisper_samples(&event.payload, format));
+                Ok(())
+            }
+            "audio-stop" => {
+                let language = language.take().unwrap_or_else(|| config.whisper_language.clone());
+                match transcribe(&app, std::mem::take(&mut samples), &language).await {
+                    Ok(text) => {
+                        println!("[DEBUG] Wyoming transcript: {:?}", text);
+                        write_event(&mut writer, "transcript", json!({ "text": text }), &[]).await
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+            "synthesize" => {
+                let text = event.data.get("text").and_then(Value::as_str).unwrap_or_default();
+                synthesize(&mut writer, &config, text).await
+            }
+            other => {
+                println!("[DEBUG] Ignoring Wyoming event '{}'", other);
+                Ok(())
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("[WARN] Wyoming {} failed: {}", event.kind, e);
+            write_event(&mut writer, "error", json!({ "text": e.to_string() }), &[]).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn serve(app: tauri::AppHandle, cfg: WyomingConfig) -> Result<()> {
+    let host = if cfg.allow_network {
+        [0, 0, 0, 0]
+    } else {
+        [127, 0, 0, 1]
+    };
+    let addr = SocketAddr::from((host, cfg.port));
+    let listener = TcpListener::bind(addr).await?;
+    println!("[INFO] Wyoming server listening on {}", addr);
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let app = app.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle(app, stream).await {
+                eprintln!("[WARN] Wyoming connection from {} ended: {}", peer, e);
+            }
+        });
+    }
+}
+
+// Called once at startup; changes to `wyoming` take effect after a restart
+pub fn init(app: &tauri::AppHandle) {
+    let cfg = match crate::cmd_load_config(app.clone()) {
+        Ok(cfg) => cfg.wyoming,
+        Err(e) => {
+            eprintln!("[WARN] Wyoming server not started: {}", e);
+            return;
+        }
+    };
+    if !cfg.enabled {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = serve(app, cfg).await {
+            eprintln!("[WARN] Wyoming server stopped: {}", e);
+        }
+    });
+}
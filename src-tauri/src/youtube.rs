@@ -0,0 +1,199 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// YouTube links are sent as the video's captions plus title, channel and length
+// instead of the watch page, which is mostly scripts. Videos without captions fall
+// back to their description.
+
+use crate::web_fetch::{self, WebFetchConfig};
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+use url::Url;
+
+// about an hour of speech
+const MAX_TRANSCRIPT_CHARS: usize = 40_000;
+
+static CAPTION_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?s)<text start="([\d.]+)"[^>]*>(.*?)</text>"#)
+        .expect("Failed to compile caption regex")
+});
+static NUMERIC_ENTITY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"&#(x?)([0-9a-fA-F]+);").expect("Failed to compile entity regex"));
+
+struct VideoDetails {
+    title: String,
+    channel: String,
+    length_secs: u64,
+    description: String,
+}
+
+// watch?v=, youtu.be/, /shorts/, /embed/ and /live/ links -> the 11 character video id
+pub fn video_id(url: &str) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+    let host = parsed.host_str()?.trim_start_matches("www.").trim_start_matches("m.");
+    let id = match host {
+        "youtu.be" => parsed.path_segments()?.next()?.to_string(),
+        "youtube.com" | "music.youtube.com" | "youtube-nocookie.com" => {
+            let mut segments = parsed.path_segments()?;
+            match segments.next()? {
+                "watch" => parsed
+                    .query_pairs()
+                    .find(|(k, _)| k == "v")
+                    .map(|(_, v)| v.to_string())?,
+                "shorts" | "embed" | "live" | "v" => segments.next()?.to_string(),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    let valid = id.len() == 11 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then_some(id)
+}
+
+fn unescape(text: &str) -> String {
+    let decoded = NUMERIC_ENTITY.replace_all(text, |c: &regex::Captures| {
+        let radix = if c[1].is_empty() { 10 } else { 16 };
+        u32::from_str_radix(&c[2], radix)
+            .ok()
+            .and_then(char::from_u32)
+            .map(String::from)
+            .unwrap_or_default()
+    });
+    decoded
+        .replace("&quot;", "\"")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+// The player response the watch page embeds as `ytInitialPlayerResponse = {...};`
+fn player_response(html: &str) -> Option<Value> {
+    let start = html.find("ytInitialPlayerResponse")?;
+    let rest = &html[start..];
+    let json = &rest[rest.find('{')?..];
+    // reads one object and ignores the script that follows it
+    serde_json::Deserializer::from_str(json)
+        .into_iter::<Value>()
+        .next()?
+        .ok()
+}
+
+fn details(player: &Value) -> VideoDetails {
+    let d = &player["videoDetails"];
+    VideoDetails {
+        title: d["title"].as_str().unwrap_or("").to_string(),
+        channel: d["author"].as_str().unwrap_or("").to_string(),
+        length_secs: d["lengthSeconds"]
+            .as_str()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0),
+        description: d["shortDescription"].as_str().unwrap_or("").to_string(),
+    }
+}
+
+// Hand-made captions beat auto-generated ones; the user's language beats others
+fn caption_url(player: &Value, language: &str) -> Option<String> {
+    let tracks = player["captions"]["playerCaptionsTracklistRenderer"]["captionTracks"].as_array()?;
+    tracks
+        .iter()
+        .max_by_key(|t| {
+            let lang = t["languageCode"].as_str().unwrap_or("");
+            let manual = t["kind"].as_str() != Some("asr");
+            (lang.split('-').next() == Some(language), manual)
+        })?["baseUrl"]
+        .as_str()
+        .map(|s| s.to_string())
+}
+
+// "[1:05] text" lines, one per caption
+fn parse_captions(xml: &str) -> String {
+    CAPTION_LINE
+        .captures_iter(xml)
+        .filter_map(|c| {
+            let secs = c[1].parse::<f64>().unwrap_or(0.0) as u64;
+            // caption text is escaped once in the XML and often once more inside it
+            let text = unescape(&unescape(&c[2])).split_whitespace().collect::<Vec<_>>().join(" ");
+            (!text.is_empty()).then(|| format!("[{}] {}", format_duration(secs), text))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_duration(secs: u64) -> String {
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_TRANSCRIPT_CHARS) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text.to_string(),
+    }
+}
+
+// Text part for the video: details plus the transcript, or the description without captions
+pub async fn video_text(
+    client: &reqwest::Client,
+    config: &WebFetchConfig,
+    id: &str,
+    language: &str,
+) -> Result<String> {
+    let watch_url = format!("https://www.youtube.com/watch?v={}&hl=en", id);
+    let page = web_fetch::fetch(client, config, &watch_url).await?;
+    let player = player_response(&String::from_utf8_lossy(&page.bytes))
+        .ok_or_else(|| anyhow!("No player data on the watch page"))?;
+    if player["playabilityStatus"]["status"].as_str() == Some("ERROR") {
+        return Err(anyhow!("Video {} is unavailable", id));
+    }
+    let info = details(&player);
+
+    let language = language.split('-').next().unwrap_or("en");
+    let transcript = match caption_url(&player, language) {
+        Some(url) => match web_fetch::fetch(client, config, &url).await {
+            Ok(captions) => parse_captions(&String::from_utf8_lossy(&captions.bytes)),
+            Err(e) => {
+                eprintln!("[WARN] Failed to fetch captions for {}: {}", id, e);
+                String::new()
+            }
+        },
+        None => String::new(),
+    };
+
+    let mut out = format!("YouTube video https://www.youtube.com/watch?v={}", id);
+    if !info.title.is_empty() {
+        out.push_str(&format!("\nTitle: {}", info.title));
+    }
+    if !info.channel.is_empty() {
+        out.push_str(&format!("\nChannel: {}", info.channel));
+    }
+    if info.length_secs > 0 {
+        out.push_str(&format!("\nLength: {}", format_duration(info.length_secs)));
+    }
+    if transcript.is_empty() {
+        out.push_str("\n\nThis video has no captions, so only its description is available.");
+        out.push_str(&format!("\nDescription:\n{}", truncate(info.description.trim())));
+    } else {
+        out.push_str(&format!("\n\nTranscript:\n{}", truncate(&transcript)));
+    }
+    Ok(out)
+}
@@ -0,0 +1,57 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// The voice loop over tests/fixtures/command.wav: half a second of silence, about two
+// seconds of synthetic speech, then silence. Run with `cargo test --features pipeline-mock`.
+
+use jarvis_gui_lib::pipeline_mock::{self, SilentVoice};
+use std::path::Path;
+
+fn fixture(name: &str) -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+// one run per process: the audio input thread and the app are shared
+#[test]
+fn answers_a_command_after_the_wake_word() {
+    let voice = SilentVoice::new();
+    let said = voice.said.clone();
+    let mut pipeline = pipeline_mock::scripted(
+        vec![300],
+        vec!["write a haiku about the sea"],
+        vec!["Grey waves fold to foam."],
+    );
+    pipeline.voice = Box::new(voice);
+    let turns = pipeline_mock::run_wav(
+        pipeline,
+        pipeline_mock::config(),
+        &fixture("command.wav"),
+        1,
+    )
+    .expect("the voice loop should answer the command");
+
+    assert_eq!(turns.len(), 1);
+    assert_eq!(turns[0].transcript, "write a haiku about the sea");
+    assert_eq!(turns[0].answer, "Grey waves fold to foam.");
+    assert_eq!(
+        *said.lock().unwrap(),
+        vec!["Grey waves fold to foam.".to_string()]
+    );
+}
@@ -16,14 +16,13 @@ along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
 import React, { useEffect, useMemo, useState } from "react";
-import { listen as listenRawEvent } from "@tauri-apps/api/event";
 import TopBar from "./components/TopBar";
 import HistoryPane from "./components/HistoryPane";
 import ChatPane from "./components/ChatPane";
 import SettingsPane from "./components/SettingsPane";
 import OnboardingOverlay from "./components/OnboardingOverlay";
 import { ConversationSummary, Message, Config, VoiceState } from "./types";
-import { listHistoryFiles, loadConfig, getJarvisStatus, listenToStateChanges, listenToNewMessages, createConversation, readConversation, appendTurn, setActiveConversation, generateAndRenameConversation, listenToWhisperDownloadProgress, listenToWhisperDownloadComplete } from "./lib/tauri";
+import { listHistoryFiles, loadConfig, getJarvisStatus, listenToStateChanges, listenToNewMessages, createConversation, readConversation, appendTurn, updateTurnMeta, setActiveConversation, generateAndRenameConversation, listenToWhisperDownloadProgress, listenToWhisperDownloadComplete, listenToMessageMeta, listenToHistoryChanged } from "./lib/tauri";
 
 const App: React.FC = () => {
   const [historyHidden, setHistoryHidden] = useState(false);
@@ -136,6 +135,7 @@ const App: React.FC = () => {
           role: (t.role as any),
           content: t.content,
           createdAt: t.createdAt,
+          meta: t.meta,
         }));
         setMessages(msgs);
         await setActiveConversation(selectedId);
@@ -177,18 +177,17 @@ const App: React.FC = () => {
     const unsubscribeMessages = listenToNewMessages(async (messageData) => {
       console.log("New message received:", messageData);
       const newMessage: Message = {
-        id: (messageData && messageData.id) || `msg_${Date.now()}_${Math.random()}`,
-        role: messageData.role as 'user' | 'assistant' | 'system',
+        id: `msg_${Date.now()}_${Math.random()}`,
+        role: messageData.role,
         content: messageData.content,
         createdAt: messageData.createdAt,
-        meta: messageData.meta,
+        meta: messageData.meta as Message['meta'],
       };
       setMessages(prev => [...prev, newMessage]);
       // Persist to current conversation
       if (selectedId) {
         try {
-          await appendTurn(selectedId, newMessage.role, newMessage.content, newMessage.createdAt);
-          // optional: no-op
+          await appendTurn(selectedId, newMessage.role, newMessage.content, newMessage.createdAt, newMessage.meta);
         } catch (e) {
           console.error("Failed to append turn:", e);
         }
@@ -210,19 +209,20 @@ const App: React.FC = () => {
       }
     });
 
-    // Listen for meta updates that attach to previously-sent assistant message
-    const unsubscribeMeta = listenRawEvent('message-meta', (event) => {
-      const payload = event.payload as any;
-      const createdAtOfAssistant = payload?.createdAtOfAssistant as number;
-      const meta = payload?.meta as any;
-      if (typeof createdAtOfAssistant !== 'number' || !meta) return;
+    // Listen for meta updates that attach to a previously-sent message
+    const unsubscribeMeta = listenToMessageMeta(({ createdAt, role, meta }) => {
       setMessages((prev) =>
         prev.map((m) =>
-          m.role === 'assistant' && m.createdAt === createdAtOfAssistant
+          m.role === role && m.createdAt === createdAt
             ? { ...m, meta: { ...(m.meta || {}), ...meta } }
             : m
         )
       );
+      if (selectedId) {
+        updateTurnMeta(selectedId, createdAt, meta).catch((e) =>
+          console.error("Failed to persist message meta:", e)
+        );
+      }
     });
 
     // Whisper download progress listeners
@@ -233,12 +233,33 @@ const App: React.FC = () => {
       setWhisperProgress(null);
     });
 
+    // Conversations changed on disk by something else (e.g. a synced history folder)
+    const unsubHistoryPromise = listenToHistoryChanged(async ({ filenames }) => {
+      try {
+        const files = await listHistoryFiles();
+        setHistory(files.map((f) => ({ id: f, filename: f })));
+        if (selectedId && filenames.includes(selectedId) && files.includes(selectedId)) {
+          const turns = await readConversation(selectedId);
+          setMessages(turns.map((t, idx) => ({
+            id: `${selectedId}-${idx}`,
+            role: (t.role as any),
+            content: t.content,
+            createdAt: t.createdAt,
+            meta: t.meta,
+          })));
+        }
+      } catch (e) {
+        console.error("Failed to refresh history:", e);
+      }
+    });
+
     return () => {
       unsubscribeState.then(unsub => unsub());
       unsubscribeMessages.then(unsub => unsub());
       unsubscribeMeta.then((unsub: any) => unsub());
       unsubProgPromise.then(unsub => unsub());
       unsubDonePromise.then(unsub => unsub());
+      unsubHistoryPromise.then(unsub => unsub());
     };
   }, [selectedId]);
 
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Recipe } from "./Recipe";
+
+export type CookingState = { recipe: Recipe, step: number | null, timedSteps: Array<number>, };
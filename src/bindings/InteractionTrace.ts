@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type InteractionTrace = { startedAt: number, source: string, input: string, persona: string | null, command: string | null, tools: Array<string>, provider: string, model: string, prompt: string, context: string, redactions: number, droppedTurns: number, promptShortened: boolean, retried: boolean, answer: string, spoken: string, voice: string, error: string | null, durationMs: number, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type MeetingStatus = { active: boolean, startedAt: number | null, transcriptPath: string | null, segments: number, };
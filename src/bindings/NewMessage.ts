@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Role } from "./Role";
+
+export type NewMessage = { role: Role, content: string, createdAt: number, meta: Record<string, unknown>, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Package = { number: string, label: string, status: string, detail: string, delivered: boolean, registered: boolean, added_at: string, updated_at: string, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Phase } from "./Phase";
+
+export type Pomodoro = { phase: Phase, focusMinutes: number, breakMinutes: number, startedAt: number, endsAt: number, };
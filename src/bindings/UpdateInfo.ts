@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ModelUpdate } from "./ModelUpdate";
+
+export type UpdateInfo = { currentVersion: string, latestVersion: string | null, appUpdate: boolean, releaseUrl: string | null, releaseNotes: string | null, models: Array<ModelUpdate>, modelNotes: string | null, };
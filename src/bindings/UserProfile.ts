@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Units } from "./Units";
+
+export type UserProfile = { name: string, location: string, units: Units | null, job: string, pronouns: string, home: string, work: string, };
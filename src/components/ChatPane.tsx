@@ -18,7 +18,7 @@ along with this program.  If not, see <https://www.gnu.org/licenses/>.
 import React, { useCallback, useState } from "react";
 import { Message, VoiceState, Config } from "../types";
 import { startJarvis, stopJarvis } from "../lib/tauri";
-import { sendTextPrompt } from "../lib/tauri";
+import { sendTextPrompt, openExternalUrl } from "../lib/tauri";
 
 type ChatPaneProps = {
   messages: Message[];
@@ -46,6 +46,15 @@ const ChatBubble: React.FC<{ msg: Message }> = ({ msg }) => {
         }`}
       >
         <div className="text-[15px] whitespace-pre-wrap">{msg.content}</div>
+        {isAssistant && msg.meta?.citation && (
+          <button
+            type="button"
+            className="mt-2 block text-[12px] text-emerald-400 hover:underline"
+            onClick={() => openExternalUrl(msg.meta!.citation!.url)}
+          >
+            Wikipedia: {msg.meta.citation.title}
+          </button>
+        )}
         {isAssistant && msg.meta && (
           <div className="mt-2 text-[11px] text-zinc-500">
             {(() => {
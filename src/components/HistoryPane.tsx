@@ -35,7 +35,7 @@ const HistoryPane: React.FC<HistoryPaneProps> = ({
   onNewConversation,
 }) => {
   function displayName(filename: string): string {
-    let base = filename.replace(/\.json$/i, "");
+    let base = filename.replace(/\.jsonl?$/i, "");
     base = base.replace(/ - \d{4}-\d{2}-\d{2}_\d{2}-\d{2}-\d{2}$/i, "");
     // If the remaining is just a timestamp, collapse to a generic label
     if (/^\d{4}-\d{2}-\d{2}_\d{2}-\d{2}-\d{2}$/.test(base)) {
@@ -0,0 +1,63 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+import React, { useEffect, useState } from "react";
+import { listenToPartialTranscript, listenToStateChanges } from "../lib/tauri";
+
+const LABELS: Record<string, string> = {
+  Idle: "Idle",
+  Loading: "Loading…",
+  WakeListening: "Say “Jarvis”",
+  Recording: "Listening…",
+  Processing: "Thinking…",
+  Speaking: "Speaking",
+};
+
+// Rendered in the always-on-top overlay window (see overlay.rs)
+const OverlayWidget: React.FC = () => {
+  const [state, setState] = useState("Idle");
+  const [transcript, setTranscript] = useState("");
+
+  useEffect(() => {
+    const unsubscribeState = listenToStateChanges((s) => {
+      setState(s);
+      // a new command starts with an empty line
+      if (s === "Recording") setTranscript("");
+    });
+    const unsubscribeTranscript = listenToPartialTranscript((t) => setTranscript(t.text));
+    return () => {
+      unsubscribeState.then((f) => f());
+      unsubscribeTranscript.then((f) => f());
+    };
+  }, []);
+
+  const active = state === "Recording" || state === "Processing" || state === "Speaking";
+  return (
+    <div
+      className="w-screen h-screen flex items-center gap-3 px-4 rounded-2xl bg-[#0b0e14]/85 text-white backdrop-blur border border-white/10 select-none"
+      data-tauri-drag-region
+    >
+      <span className={`h-3 w-3 shrink-0 rounded-full ${active ? "bg-cyan-400 animate-pulse" : "bg-white/30"}`} />
+      <div className="min-w-0" data-tauri-drag-region>
+        <div className="text-sm font-medium">{LABELS[state] ?? state}</div>
+        {transcript && <div className="text-xs text-white/60 truncate">{transcript}</div>}
+      </div>
+    </div>
+  );
+};
+
+export default OverlayWidget;
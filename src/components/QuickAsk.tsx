@@ -0,0 +1,84 @@
+/*
+Copyright (C) 2025  Hav1ck
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU Affero General Public License as
+published by the Free Software Foundation, either version 3 of the
+License, or (at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU Affero General Public License for more details.
+
+You should have received a copy of the GNU Affero General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+import React, { useEffect, useRef, useState } from "react";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import { hideQuickAsk, quickAsk } from "../lib/tauri";
+
+// Rendered in the quick-ask popup window (see quick_ask.rs)
+const QuickAsk: React.FC = () => {
+  const [prompt, setPrompt] = useState("");
+  const [answer, setAnswer] = useState<string | null>(null);
+  const [busy, setBusy] = useState(false);
+  const inputRef = useRef<HTMLInputElement>(null);
+
+  // The window is reused: start clean every time it is opened
+  useEffect(() => {
+    const unlisten = getCurrentWindow().onFocusChanged(({ payload: focused }) => {
+      if (focused) {
+        inputRef.current?.focus();
+      }
+    });
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  const close = () => {
+    setPrompt("");
+    setAnswer(null);
+    hideQuickAsk();
+  };
+
+  const submit = async () => {
+    if (!prompt.trim() || busy) return;
+    setBusy(true);
+    try {
+      setAnswer(await quickAsk(prompt));
+      setPrompt("");
+    } catch (e) {
+      setAnswer(String(e));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <div className="w-screen h-screen flex flex-col rounded-2xl bg-[#0b0e14]/95 text-white border border-white/10 overflow-hidden">
+      <input
+        ref={inputRef}
+        autoFocus
+        className="h-16 shrink-0 px-5 bg-transparent outline-none text-lg placeholder:text-white/40"
+        placeholder={busy ? "Thinking…" : "Ask Jarvis"}
+        value={prompt}
+        disabled={busy}
+        onChange={(e) => setPrompt(e.target.value)}
+        onKeyDown={(e) => {
+          if (e.key === "Enter") submit();
+          if (e.key === "Escape") close();
+        }}
+      />
+      {answer !== null && (
+        <div className="flex-1 overflow-y-auto px-5 pb-4 pt-3 border-t border-white/10 text-sm whitespace-pre-wrap text-white/80">
+          {answer}
+        </div>
+      )}
+    </div>
+  );
+};
+
+export default QuickAsk;
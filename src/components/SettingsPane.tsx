@@ -303,6 +303,24 @@ const SettingsPane: React.FC<SettingsPaneProps> = ({
             ))}
           </select>
         </FieldRow>
+
+        <FieldRow
+          label="Voice Output"
+          hint="Send Jarvis's voice to a different device than your music."
+        >
+          <select
+            className="ui-input w-full"
+            value={local?.tts_output_device_name ?? ""}
+            onChange={(e) => update("tts_output_device_name", e.target.value)}
+          >
+            <option value="">Same as Output</option>
+            {outputDevices.map((d) => (
+              <option key={d} value={d}>
+                {d}
+              </option>
+            ))}
+          </select>
+        </FieldRow>
       </Section>
     </div>
   );
@@ -416,15 +434,17 @@ const SettingsPane: React.FC<SettingsPaneProps> = ({
           />
         </FieldRow>
         <FieldRow
-          label="Silence Threshold (s)"
-          hint="Time of silence to end speech capture."
+          label="Silence Threshold (ms)"
+          hint="Time of silence to end speech capture (minimum 300 ms)."
         >
           <input
             className="ui-input w-full"
             type="number"
-            value={local?.silence_threshold_seconds ?? 1}
+            min={300}
+            step={50}
+            value={local?.silence_threshold_ms ?? 1000}
             onChange={(e) =>
-              update("silence_threshold_seconds", Number(e.target.value))
+              update("silence_threshold_ms", Number(e.target.value))
             }
           />
         </FieldRow>
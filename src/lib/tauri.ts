@@ -17,7 +17,7 @@ along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import type { Config } from '../types';
+import type { AttachmentInfo, AudioCheck, AudioOverflow, BranchSummary, ContextTruncated, CrashReport, Dashboard, DashboardRange, DeviceLost, DeviceReconnected, DeviceReconnectFailed, GenerationSettings, MeetingNotes, MeetingSegment, MeetingStatus, CaptureInfo, Config, ConversationSettings, KeyCheck, KeyProvider, Memory, MemoriesChanged, Message, NowPlaying, PartialTranscript, PersonaList, ProfileList, ReadAloudState, SetupStatus, Triggers, TtsQuota, TtsQuotaLow, UpdateInfo, VadFrame, LexiconEntry, CodeCopied, NewMessage, MessageMeta, WhisperDownloadProgress, AskOnceOptions, BatchProgress, BatchResult, HistoryChanged, SyncReport, GameMode, BenchmarkReport, InteractionTrace, UserProfile, Package, PackagesChanged, Timer, TimersChanged, CookingState, CookingChanged, VoiceMemo, VoiceMemosChanged, Pomodoro, PomodoroChanged, FocusSession, PresenceStatus, PresenceEvent, ConnectivityReport, ProcessInfo, ContainerInfo } from '../types';
 
 export async function listHistoryFiles(): Promise<string[]> {
   return await invoke<string[]>('cmd_list_history_files');
@@ -27,12 +27,27 @@ export async function createConversation(): Promise<string> {
   return await invoke<string>('cmd_create_conversation');
 }
 
-export async function readConversation(filename: string): Promise<Array<{ role: string; content: string; createdAt: number }>> {
-  return await invoke('cmd_read_conversation', { filename });
+// Without offset/limit every turn is returned; offset 0 is the oldest turn
+export async function readConversation(filename: string, offset?: number, limit?: number): Promise<Array<{ role: string; content: string; createdAt: number; meta?: Message['meta'] }>> {
+  return await invoke('cmd_read_conversation', { filename, offset, limit });
 }
 
-export async function appendTurn(filename: string, role: string, content: string, createdAt: number): Promise<void> {
-  await invoke('cmd_append_turn', { filename, turn: { role, content, createdAt } });
+export async function getTurnCount(filename: string): Promise<number> {
+  return await invoke<number>('cmd_get_turn_count', { filename });
+}
+
+// The `pageSize` turns before `end` (the turn count for the newest page)
+export async function readConversationPage(filename: string, end: number, pageSize = 50) {
+  const offset = Math.max(0, end - pageSize);
+  return await readConversation(filename, offset, end - offset);
+}
+
+export async function appendTurn(filename: string, role: string, content: string, createdAt: number, meta?: Message['meta']): Promise<void> {
+  await invoke('cmd_append_turn', { filename, turn: { role, content, createdAt, meta } });
+}
+
+export async function updateTurnMeta(filename: string, createdAt: number, meta: Record<string, unknown>): Promise<void> {
+  await invoke('cmd_update_turn_meta', { filename, createdAt, meta });
 }
 
 export async function loadConfig(): Promise<Config> {
@@ -82,18 +97,142 @@ export function listenToStateChanges(callback: (state: string) => void) {
   });
 }
 
-export function listenToNewMessages(callback: (message: any) => void) {
-  return listen('new-message', (event) => {
+// Transcript of the command being spoken; isFinal once Whisper has the whole utterance
+export function listenToPartialTranscript(callback: (transcript: PartialTranscript) => void) {
+  return listen<PartialTranscript>('partial-transcript', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Always-on-top status widget, placed on the dock_position side of the current monitor
+export async function showOverlay(): Promise<void> {
+  await invoke('cmd_show_overlay');
+}
+
+export async function hideOverlay(): Promise<void> {
+  await invoke('cmd_hide_overlay');
+}
+
+// Sends a prompt from the quick-ask popup into the active conversation
+export async function quickAsk(prompt: string): Promise<string> {
+  return await invoke<string>('cmd_quick_ask', { prompt });
+}
+
+export async function hideQuickAsk(): Promise<void> {
+  await invoke('cmd_hide_quick_ask');
+}
+
+// Copies the selection in the focused app (clipboard is restored) and reads it aloud
+export async function readSelection(): Promise<void> {
+  await invoke('cmd_read_selection');
+}
+
+export async function readAloud(text: string): Promise<void> {
+  await invoke('cmd_read_aloud', { text });
+}
+
+export async function stopReading(): Promise<void> {
+  await invoke('cmd_stop_reading');
+}
+
+// Summarizes copied text (or the text in a copied image); the reply also lands in the chat
+export async function summarizeClipboard(speak = false): Promise<string> {
+  return await invoke<string>('cmd_summarize_clipboard', { speak });
+}
+
+// e.g. "rewrite this more formally"; the result replaces the clipboard and is returned
+export async function rewriteClipboard(instruction: string): Promise<string> {
+  return await invoke<string>('cmd_rewrite_clipboard', { instruction });
+}
+
+// Generates a picture; it arrives as a `new-message` with an image attachment
+export async function generateImage(description: string): Promise<string> {
+  return await invoke<string>('cmd_generate_image', { description });
+}
+
+export function listenToReadAloudState(callback: (state: ReadAloudState) => void) {
+  return listen<ReadAloudState>('read-aloud-state', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Sent when old turns (or part of the prompt) had to be left out of a request
+export function listenToContextTruncated(callback: (info: ContextTruncated) => void) {
+  return listen<ContextTruncated>('context-truncated', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Fired when [[copy]] blocks from an answer were put on the clipboard
+export function listenToCodeCopied(callback: (info: CodeCopied) => void) {
+  return listen<CodeCopied>('code-copied', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function listenToNewMessages(callback: (message: NewMessage) => void) {
+  return listen<NewMessage>('new-message', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Details (latency, attachments, ...) added to a message that was already shown
+export function listenToMessageMeta(callback: (update: MessageMeta) => void) {
+  return listen<MessageMeta>('message-meta', (event) => {
     callback(event.payload);
   });
 }
 
 // Whisper model download progress events
-export function listenToWhisperDownloadProgress(
-  callback: (payload: { downloaded: number; total: number; percent: number }) => void
-) {
-  return listen('whisper-download-progress', (event) => {
-    callback(event.payload as any);
+export function listenToWhisperDownloadProgress(callback: (payload: WhisperDownloadProgress) => void) {
+  return listen<WhisperDownloadProgress>('whisper-download-progress', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Per-frame speech/silence decisions while recording (~one event per VAD frame)
+export function listenToVadActivity(callback: (frame: VadFrame) => void) {
+  return listen<VadFrame>('vad-activity', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Fired once when ElevenLabs usage crosses the configured warning threshold
+export function listenToTtsQuotaLow(callback: (payload: TtsQuotaLow) => void) {
+  return listen<TtsQuotaLow>('tts-quota-low', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function listenToMemoriesChanged(callback: (count: number) => void) {
+  return listen<MemoriesChanged>('memories-changed', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Fired when microphone audio had to be dropped because nothing read it in time
+export function listenToAudioOverflow(callback: (payload: AudioOverflow) => void) {
+  return listen<AudioOverflow>('audio-overflow', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function listenToDeviceLost(callback: (payload: DeviceLost) => void) {
+  return listen<DeviceLost>('device-lost', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function listenToDeviceReconnected(callback: (payload: DeviceReconnected) => void) {
+  return listen<DeviceReconnected>('device-reconnected', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Fired after reconnecting has been retried for a minute without any input device showing up
+export function listenToDeviceReconnectFailed(callback: (payload: DeviceReconnectFailed) => void) {
+  return listen<DeviceReconnectFailed>('device-reconnect-failed', (event) => {
+    callback(event.payload);
   });
 }
 
@@ -107,6 +246,21 @@ export async function sendTextPrompt(prompt: string): Promise<string> {
   return await invoke<string>('cmd_send_text', { prompt });
 }
 
+// One-off question that leaves conversations and memory untouched, e.g. "explain this setting"
+export async function askOnce(prompt: string, options?: AskOnceOptions): Promise<string> {
+  return await invoke<string>('cmd_ask_once', { prompt, options });
+}
+
+// Times speech-to-text, the LLM and the voice; `audioPath` is a WAV file to transcribe
+export async function runBenchmark(audioPath?: string): Promise<BenchmarkReport> {
+  return await invoke<BenchmarkReport>('cmd_run_benchmark', { options: { audioPath } });
+}
+
+// Runs typed text through the running voice loop as if it had been spoken
+export async function simulateVoiceCommand(text: string): Promise<void> {
+  await invoke('cmd_simulate_voice_command', { text });
+}
+
 export async function setActiveConversation(filename: string): Promise<void> {
   await invoke('cmd_set_active_conversation', { filename });
 }
@@ -119,10 +273,468 @@ export async function deleteConversation(filename: string): Promise<void> {
   await invoke('cmd_delete_conversation', { filename });
 }
 
+// Batch operations report each handled file through listenToBatchProgress
+export async function deleteConversations(filenames: string[]): Promise<BatchResult> {
+  return await invoke<BatchResult>('cmd_delete_conversations', { filenames });
+}
+
+export async function archiveConversations(filenames: string[]): Promise<BatchResult> {
+  return await invoke<BatchResult>('cmd_archive_conversations', { filenames });
+}
+
+export async function unarchiveConversations(filenames: string[]): Promise<BatchResult> {
+  return await invoke<BatchResult>('cmd_unarchive_conversations', { filenames });
+}
+
+export async function listArchivedConversations(): Promise<string[]> {
+  return await invoke<string[]>('cmd_list_archived_conversations');
+}
+
+// Zips every conversation with its attachments; `path` is a file or a folder. Returns the zip path.
+export async function exportAllConversations(path: string): Promise<string> {
+  return await invoke<string>('cmd_export_all', { path });
+}
+
+// Conversation files changed outside Jarvis, e.g. by Syncthing or Dropbox
+export function listenToHistoryChanged(callback: (change: HistoryChanged) => void) {
+  return listen<HistoryChanged>('history-changed', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Syncs with the other devices now, even if the periodic sync is off
+export async function syncNow(): Promise<SyncReport> {
+  return await invoke<SyncReport>('cmd_sync_now');
+}
+
+export function listenToSyncFinished(callback: (report: SyncReport) => void) {
+  return listen<SyncReport>('sync-finished', (event) => {
+    callback(event.payload);
+  });
+}
+
+export async function getGameMode(): Promise<GameMode> {
+  return await invoke<GameMode>('cmd_get_game_mode');
+}
+
+export function listenToGameMode(callback: (mode: GameMode) => void) {
+  return listen<GameMode>('game-mode', (event) => {
+    callback(event.payload);
+  });
+}
+
+// The system prompt with its {placeholders} filled in; pass `template` to preview an unsaved edit
+export async function previewSystemPrompt(template?: string): Promise<string> {
+  return await invoke<string>('cmd_preview_system_prompt', { template });
+}
+
+// Facts about the user that go into every question
+export async function getUserProfile(): Promise<UserProfile> {
+  return await invoke<UserProfile>('cmd_get_user_profile');
+}
+
+export async function setUserProfile(profile: UserProfile): Promise<void> {
+  await invoke('cmd_set_user_profile', { profile });
+}
+
+export function listenToUserProfile(callback: (profile: UserProfile) => void) {
+  return listen<UserProfile>('user-profile-changed', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Tracked packages, as stored in packages.json
+export async function listPackages(): Promise<Package[]> {
+  return await invoke<Package[]>('cmd_list_packages');
+}
+
+// Resolves with the package after its first check, if a tracking service is set up
+export async function addPackage(number: string, label: string): Promise<Package> {
+  return await invoke<Package>('cmd_add_package', { number, label });
+}
+
+export async function removePackage(number: string): Promise<void> {
+  await invoke('cmd_remove_package', { number });
+}
+
+export function listenToPackages(callback: (packages: Package[]) => void) {
+  return listen<PackagesChanged>('packages-changed', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Countdown timers; they ring with a beep and a spoken note
+export async function listTimers(): Promise<Timer[]> {
+  return await invoke<Timer[]>('cmd_list_timers');
+}
+
+export async function startTimer(label: string, seconds: number): Promise<Timer> {
+  return await invoke<Timer>('cmd_start_timer', { label, seconds });
+}
+
+export async function cancelTimer(id: number): Promise<boolean> {
+  return await invoke<boolean>('cmd_cancel_timer', { id });
+}
+
+export function listenToTimers(callback: (timers: Timer[]) => void) {
+  return listen<TimersChanged>('timers-changed', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Cooking mode; source is a link to a recipe or the recipe text
+export async function loadRecipe(source: string): Promise<CookingState> {
+  return await invoke<CookingState>('cmd_load_recipe', { source });
+}
+
+export async function getCooking(): Promise<CookingState | null> {
+  return await invoke<CookingState | null>('cmd_get_cooking');
+}
+
+export async function stopCooking(): Promise<void> {
+  await invoke('cmd_stop_cooking');
+}
+
+export function listenToCooking(callback: (state: CookingState | null) => void) {
+  return listen<CookingChanged>('cooking-changed', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Background sounds on the voice loop's output; sound is a name ("rain", "brown noise")
+// or a file path, and minutes sets the sleep timer
+export async function playAmbient(sound: string, minutes?: number): Promise<void> {
+  await invoke('cmd_play_ambient', { sound, minutes });
+}
+
+export async function stopAmbient(): Promise<void> {
+  await invoke('cmd_stop_ambient');
+}
+
+// Speaks the briefing as set up in the config, e.g. from a schedule; returns its text
+export async function runBriefing(): Promise<string> {
+  return await invoke<string>('cmd_run_briefing');
+}
+
+// Voice memos, newest first; recording one is done by voice
+export async function listVoiceMemos(): Promise<VoiceMemo[]> {
+  return await invoke<VoiceMemo[]>('cmd_list_voice_memos');
+}
+
+export async function deleteVoiceMemo(id: string): Promise<void> {
+  await invoke('cmd_delete_voice_memo', { id });
+}
+
+export function listenToVoiceMemos(callback: () => void) {
+  return listen<VoiceMemosChanged>('voice-memos-changed', () => {
+    callback();
+  });
+}
+
+// Pomodoro coach; minutes defaults to pomodoro.focus_minutes
+export async function startPomodoro(minutes?: number): Promise<Pomodoro> {
+  return await invoke<Pomodoro>('cmd_start_pomodoro', { minutes });
+}
+
+export async function stopPomodoro(): Promise<void> {
+  await invoke('cmd_stop_pomodoro');
+}
+
+export async function getPomodoro(): Promise<Pomodoro | null> {
+  return await invoke<Pomodoro | null>('cmd_get_pomodoro');
+}
+
+export async function getPomodoroHistory(): Promise<FocusSession[]> {
+  return await invoke<FocusSession[]>('cmd_pomodoro_history');
+}
+
+export function listenToPomodoro(callback: (pomodoro: Pomodoro | null) => void) {
+  return listen<PomodoroChanged>('pomodoro-changed', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Whether the phone in presence.phone_address is nearby
+export async function getPresence(): Promise<PresenceStatus> {
+  return await invoke<PresenceStatus>('cmd_get_presence');
+}
+
+export async function listPresenceEvents(): Promise<PresenceEvent[]> {
+  return await invoke<PresenceEvent[]>('cmd_list_presence_events');
+}
+
+export function listenToPresence(callback: (status: PresenceStatus) => void) {
+  return listen<PresenceStatus>('presence-changed', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Times the servers in network.check_hosts and looks up network.check_domain
+export async function checkConnectivity(): Promise<ConnectivityReport> {
+  return await invoke<ConnectivityReport>('cmd_check_connectivity');
+}
+
+// Busiest first; sampling CPU use takes about half a second
+export async function listProcesses(): Promise<ProcessInfo[]> {
+  return await invoke<ProcessInfo[]>('cmd_list_processes');
+}
+
+// Ask before calling this; protected processes are refused
+export async function killProcess(pid: number): Promise<void> {
+  await invoke('cmd_kill_process', { pid });
+}
+
+// Fails while docker.enabled is off
+export async function listContainers(): Promise<ContainerInfo[]> {
+  return await invoke<ContainerInfo[]>('cmd_list_containers');
+}
+
+// What led to the answer of each voice command; the last 20 are kept, newest first
+export async function getInteractionTraces(): Promise<InteractionTrace[]> {
+  return await invoke<InteractionTrace[]>('cmd_get_interaction_traces');
+}
+
+export function listenToInteractionTrace(callback: (trace: InteractionTrace) => void) {
+  return listen<InteractionTrace>('interaction-trace', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function listenToBatchProgress(callback: (progress: BatchProgress) => void) {
+  return listen<BatchProgress>('batch-progress', (event) => {
+    callback(event.payload);
+  });
+}
+
 export async function renameConversation(filename: string, newTitle: string): Promise<{ new_filename: string; title: string }> {
   return await invoke('cmd_rename_conversation', { filename, newTitle });
 }
 
+export async function getConversationSettings(filename: string): Promise<ConversationSettings> {
+  return await invoke<ConversationSettings>('cmd_get_conversation_settings', { filename });
+}
+
+// Pass null to fall back to the global redaction setting
+export async function setConversationRedaction(filename: string, enabled: boolean | null): Promise<ConversationSettings> {
+  return await invoke<ConversationSettings>('cmd_set_conversation_redaction', { filename, enabled });
+}
+
+// Temperature and length limits for one conversation; null falls back to the global settings
+export async function setConversationGeneration(filename: string, generation: GenerationSettings | null): Promise<ConversationSettings> {
+  return await invoke<ConversationSettings>('cmd_set_conversation_generation', { filename, generation });
+}
+
+export async function getLexicon(): Promise<LexiconEntry[]> {
+  return await invoke<LexiconEntry[]>('cmd_get_lexicon');
+}
+
+// Replaces the whole pronunciation dictionary
+export async function editLexicon(entries: LexiconEntry[]): Promise<LexiconEntry[]> {
+  return await invoke<LexiconEntry[]>('cmd_edit_lexicon', { entries });
+}
+
+export async function setLexiconEntry(entry: LexiconEntry): Promise<LexiconEntry[]> {
+  return await invoke<LexiconEntry[]>('cmd_set_lexicon_entry', { entry });
+}
+
+export async function removeLexiconEntry(word: string): Promise<LexiconEntry[]> {
+  return await invoke<LexiconEntry[]>('cmd_remove_lexicon_entry', { word });
+}
+
+export async function listPersonas(): Promise<PersonaList> {
+  return await invoke<PersonaList>('cmd_list_personas');
+}
+
+// Pass null to go back to the global settings
+export async function setPersona(name: string | null): Promise<void> {
+  await invoke('cmd_set_persona', { name });
+}
+
+export async function listProfiles(): Promise<ProfileList> {
+  return await invoke<ProfileList>('cmd_list_profiles');
+}
+
+export async function applyProfile(name: string): Promise<void> {
+  await invoke('cmd_apply_profile', { name });
+}
+
+// API keys are only written/read when includeSecrets is set
+export async function exportSettings(path: string, includeSecrets = false): Promise<void> {
+  await invoke('cmd_export_settings', { path, includeSecrets });
+}
+
+export async function importSettings(path: string, includeSecrets = false): Promise<void> {
+  await invoke('cmd_import_settings', { path, includeSecrets });
+}
+
+export type SettingsSection =
+  | 'all'
+  | 'api_keys'
+  | 'audio'
+  | 'models'
+  | 'assistant'
+  | 'appearance'
+  | 'privacy'
+  | 'network'
+  | 'advanced'
+  | 'personas'
+  | 'profiles';
+
+export async function resetSettings(section: SettingsSection): Promise<void> {
+  await invoke('cmd_reset_settings', { section });
+}
+
+// Onboarding checks
+export async function getSetupStatus(): Promise<SetupStatus> {
+  return await invoke<SetupStatus>('cmd_setup_status');
+}
+
+export async function testApiKey(provider: KeyProvider): Promise<KeyCheck> {
+  return await invoke<KeyCheck>('cmd_test_api_key', { provider });
+}
+
+// Checks a key that hasn't been saved yet
+export async function validateKey(provider: KeyProvider, key: string): Promise<KeyCheck> {
+  return await invoke<KeyCheck>('cmd_validate_key', { provider, key });
+}
+
+export async function runAudioCheck(seconds?: number): Promise<AudioCheck> {
+  return await invoke<AudioCheck>('cmd_run_audio_check', { seconds });
+}
+
+export async function getTtsQuota(): Promise<TtsQuota> {
+  return await invoke<TtsQuota>('cmd_get_tts_quota');
+}
+
+// Speaks with ElevenLabs when configured, otherwise the OS voice
+export async function speak(text: string): Promise<void> {
+  await invoke('cmd_speak', { text });
+}
+
+export async function listMemories(): Promise<Memory[]> {
+  return await invoke<Memory[]>('cmd_list_memories');
+}
+
+export async function deleteMemory(id: string): Promise<void> {
+  await invoke('cmd_delete_memory', { id });
+}
+
+// Trigger phrases for built-in voice commands (weather, media keys, forget)
+export async function getTriggers(): Promise<Triggers> {
+  return await invoke<Triggers>('cmd_get_triggers');
+}
+
+export async function setTriggers(triggers: Triggers): Promise<void> {
+  await invoke('cmd_set_triggers', { triggers });
+}
+
+// Track reported by the OS media session, null when nothing is playing
+export async function getNowPlaying(): Promise<NowPlaying | null> {
+  return await invoke<NowPlaying | null>('cmd_get_now_playing');
+}
+
+// Stops whatever Jarvis is playing and drops queued sounds
+export async function clearAudioQueue(): Promise<void> {
+  await invoke('cmd_clear_audio_queue');
+}
+
+// Crash dumps waiting in the app data dir (what would be submitted after opting in)
+export async function listCrashReports(): Promise<CrashReport[]> {
+  return await invoke<CrashReport[]>('cmd_list_crash_reports');
+}
+
+export async function deleteCrashReports(): Promise<void> {
+  await invoke('cmd_delete_crash_reports');
+}
+
+export async function checkUpdates(): Promise<UpdateInfo> {
+  return await invoke<UpdateInfo>('cmd_check_updates');
+}
+
+// Fired after a check (also the one at startup) finds a newer app version or models
+export function listenToUpdateAvailable(callback: (info: UpdateInfo) => void) {
+  return listen<UpdateInfo>('update-available', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Zips redacted config, devices, model checksums and crash reports for a bug report
+export async function createDiagnosticsBundle(): Promise<{ path: string; size: number }> {
+  return await invoke<{ path: string; size: number }>('cmd_create_diagnostics_bundle');
+}
+
+// Usage per day from the local stats store; a number means that many days
+export async function getDashboard(range: DashboardRange | number): Promise<Dashboard> {
+  return await invoke<Dashboard>('cmd_get_dashboard', { range: String(range) });
+}
+
+export async function startMeeting(): Promise<MeetingStatus> {
+  return await invoke<MeetingStatus>('cmd_start_meeting');
+}
+
+export async function stopMeeting(): Promise<MeetingStatus> {
+  return await invoke<MeetingStatus>('cmd_stop_meeting');
+}
+
+export async function getMeetingStatus(): Promise<MeetingStatus> {
+  return await invoke<MeetingStatus>('cmd_get_meeting_status');
+}
+
+export function listenToMeetingState(callback: (status: MeetingStatus) => void) {
+  return listen<MeetingStatus>('meeting-state', (event) => {
+    callback(event.payload);
+  });
+}
+
+export function listenToMeetingSegment(callback: (segment: MeetingSegment) => void) {
+  return listen<MeetingSegment>('meeting-segment', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Rolling notes written every `summary_interval_minutes` and when the meeting stops
+export function listenToMeetingNotes(callback: (notes: MeetingNotes) => void) {
+  return listen<MeetingNotes>('meeting-notes', (event) => {
+    callback(event.payload);
+  });
+}
+
+type Turn = { role: string; content: string; createdAt: number; meta?: Message['meta'] };
+
+export async function regenerateLastResponse(filename: string, model?: string, temperature?: number): Promise<Turn[]> {
+  return await invoke('cmd_regenerate_last_response', { filename, model, temperature });
+}
+
+export async function editTurn(filename: string, index: number, content: string): Promise<Turn[]> {
+  return await invoke('cmd_edit_turn', { filename, index, content });
+}
+
+export async function listBranches(filename: string): Promise<BranchSummary[]> {
+  return await invoke<BranchSummary[]>('cmd_list_branches', { filename });
+}
+
+export async function switchBranch(filename: string, turnIndex: number, variant: number): Promise<Turn[]> {
+  return await invoke('cmd_switch_branch', { filename, turnIndex, variant });
+}
+
+export async function listAttachments(filename: string): Promise<AttachmentInfo[]> {
+  return await invoke<AttachmentInfo[]>('cmd_list_attachments', { filename });
+}
+
+// Returns a data URI usable directly as <audio>/<img> src
+export async function readAttachment(filename: string, id: string): Promise<string> {
+  return await invoke<string>('cmd_read_attachment', { filename, id });
+}
+
+export async function listRecentCaptures(limit?: number): Promise<CaptureInfo[]> {
+  return await invoke<CaptureInfo[]>('cmd_list_recent_captures', { limit });
+}
+
+// Re-run Whisper on a saved capture, e.g. with model "small" or language "de"
+export async function retranscribe(captureId: string, model?: string, language?: string): Promise<string> {
+  return await invoke<string>('cmd_retranscribe', { captureId, model, language });
+}
+
 // Open external URLs in the user's default browser.
 // Uses a dynamic import so the opener plugin is only loaded when needed,
 // avoiding slowing down initial app load.
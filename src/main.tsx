@@ -18,10 +18,18 @@ along with this program.  If not, see <https://www.gnu.org/licenses/>.
 import React from "react";
 import ReactDOM from "react-dom/client";
 import App from "./App";
+import OverlayWidget from "./components/OverlayWidget";
+import QuickAsk from "./components/QuickAsk";
 import "./styles.css";
 
 ReactDOM.createRoot(document.getElementById("root")!).render(
   <React.StrictMode>
-    <App />
+    {window.location.hash === "#overlay" ? (
+      <OverlayWidget />
+    ) : window.location.hash === "#quick-ask" ? (
+      <QuickAsk />
+    ) : (
+      <App />
+    )}
   </React.StrictMode>
 );
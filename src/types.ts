@@ -15,6 +15,51 @@ You should have received a copy of the GNU Affero General Public License
 along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+// Event payloads generated from src-tauri/src/events.rs
+export type { Role } from './bindings/Role';
+export type { NewMessage } from './bindings/NewMessage';
+export type { MessageMeta } from './bindings/MessageMeta';
+export type { WhisperDownloadProgress } from './bindings/WhisperDownloadProgress';
+export type { CodeCopied } from './bindings/CodeCopied';
+export type { BatchProgress } from './bindings/BatchProgress';
+export type { HistoryChanged } from './bindings/HistoryChanged';
+export type { SyncReport } from './bindings/SyncReport';
+export type { GameMode } from './bindings/GameMode';
+export type { InteractionTrace } from './bindings/InteractionTrace';
+export type { AudioOverflow } from './bindings/AudioOverflow';
+export type { DeviceLost } from './bindings/DeviceLost';
+export type { DeviceReconnected } from './bindings/DeviceReconnected';
+export type { DeviceReconnectFailed } from './bindings/DeviceReconnectFailed';
+export type { TtsQuota } from './bindings/TtsQuota';
+export type { TtsQuotaLow } from './bindings/TtsQuotaLow';
+export type { MeetingNotes } from './bindings/MeetingNotes';
+export type { MeetingSegment } from './bindings/MeetingSegment';
+export type { MeetingStatus } from './bindings/MeetingStatus';
+export type { Units } from './bindings/Units';
+export type { UserProfile } from './bindings/UserProfile';
+export type { Package } from './bindings/Package';
+export type { PackagesChanged } from './bindings/PackagesChanged';
+export type { Timer } from './bindings/Timer';
+export type { TimersChanged } from './bindings/TimersChanged';
+export type { Recipe } from './bindings/Recipe';
+export type { CookingState } from './bindings/CookingState';
+export type { CookingChanged } from './bindings/CookingChanged';
+export type { VoiceMemosChanged } from './bindings/VoiceMemosChanged';
+export type { Phase } from './bindings/Phase';
+export type { Pomodoro } from './bindings/Pomodoro';
+export type { PomodoroChanged } from './bindings/PomodoroChanged';
+export type { PresenceStatus } from './bindings/PresenceStatus';
+export type { PartialTranscript } from './bindings/PartialTranscript';
+export type { ReadAloudState } from './bindings/ReadAloudState';
+export type { ContextTruncated } from './bindings/ContextTruncated';
+export type { MemoriesChanged } from './bindings/MemoriesChanged';
+export type { LexiconChanged } from './bindings/LexiconChanged';
+export type { ModelUpdate } from './bindings/ModelUpdate';
+export type { UpdateInfo } from './bindings/UpdateInfo';
+export type { VadFrame } from './bindings/VadFrame';
+
+import type { Role } from './bindings/Role';
+
 export type ConversationSummary = {
   id: string;
   filename: string;
@@ -22,13 +67,24 @@ export type ConversationSummary = {
 
 export type Message = {
   id: string;
-  role: 'user' | 'assistant' | 'system';
+  role: Role;
   content: string;
   createdAt: number;
   meta?: {
     ttsTokensEst?: number;
     ttsChars?: number;
     latencyMs?: number;
+    model?: string;
+    persona?: string;
+    interrupted?: boolean;
+    localCommand?: boolean;
+    nowPlaying?: NowPlaying | null;
+    attachments?: AttachmentRef[];
+    // generated image saved outside any conversation
+    imagePath?: string;
+    // where a looked-up answer came from
+    citation?: { title: string; url: string };
+    [key: string]: unknown;
   };
 };
 
@@ -41,6 +97,7 @@ export type Config = {
   default_microphone_index: number;
   default_microphone_name?: string | null;
   default_output_device_name?: string | null;
+  tts_output_device_name?: string | null;
 
 
   gemini_model: string;
@@ -53,13 +110,275 @@ export type Config = {
   context_window_expiration_seconds: number;
 
   frame_duration_ms: number;
-  silence_threshold_seconds: number;
+  silence_threshold_ms: number;
   speech_trigger_frames: number;
   frame_length_wwd: number;
 
-  dock_position?: 'left' | 'right'; // optional, safe to ignore if unused
+  dock_position?: 'left' | 'right'; // also the screen side the overlay widget docks to
   input_mode?: 'audio' | 'text';
   theme?: 'emerald' | 'violet' | 'sky' | 'rose' | 'amber';
+
+  redaction?: RedactionConfig;
+  debug_capture?: { enabled: boolean; max_captures: number };
+  endpointing?: { smart: boolean; extension_ms: number };
+  wake?: { cooldown_ms: number; min_fresh_audio_ms: number; power: 'normal' | 'low_power' };
+  interrupt?: { enabled: boolean; words: string[] };
+  personas?: Persona[];
+  active_persona?: string | null;
+  output_volume?: number | null;
+  profiles?: DeviceProfile[];
+  active_profile?: string | null;
+  auto_select_profile?: boolean;
+  tts_quota_warning?: { enabled: boolean; threshold_percent: number };
+  tts_provider?: 'elevenlabs' | 'system' | null;
+  memory?: { enabled: boolean; max_in_prompt: number };
+  command_feedback?: { chat_message: boolean; sound: 'none' | 'earcon' | 'speech' };
+  // model for chat titles and helper calls; 'offline' never calls the cloud
+  utility_model?: string | null;
+  // storage for new conversations; existing files keep their format
+  history_format?: 'json' | 'jsonl' | null;
+  // crash dumps stay local unless `submit` is on and an endpoint is set
+  crash_reporting?: { submit: boolean; endpoint?: string | null };
+  meeting?: {
+    record_microphone: boolean;
+    record_system_audio: boolean;
+    chunk_seconds: number;
+    summary_interval_minutes: number;
+  };
+  // global shortcuts in Tauri accelerator syntax; '' leaves one unbound
+  hotkeys?: { quick_ask: string; read_selection: string; summarize_clipboard: string };
+  quick_ask?: { speak_response: boolean };
+  image_generation?: { enabled: boolean; model: string; copy_to_clipboard: boolean };
+  web_fetch?: WebFetchConfig;
+  generation?: GenerationSettings;
+  // 'default', 'off', 'block_none', 'block_only_high', 'block_medium_and_above' or 'block_low_and_above'
+  safety?: { harassment: string; hate_speech: string; sexually_explicit: string; dangerous_content: string };
+  pdf?: { enabled: boolean; max_pages: number; local_files: boolean };
+  // voice answers: speak a short version, show the full one
+  dual_response?: boolean;
+  tts_normalization?: {
+    enabled: boolean;
+    strip_markdown: boolean;
+    read_lists: boolean;
+    expand_units: boolean;
+    expand_abbreviations: boolean;
+    remove_emoji: boolean;
+  };
+  // max_input_tokens 0 uses the model's window
+  context_budget?: { max_input_tokens: number; reserve_tokens: number };
+  // what goes on the clipboard when an answer has more than one [[copy]] block
+  copy_blocks?: { auto_copy: boolean; multiple: 'first' | 'concatenate' | 'numbered' };
+  // end-to-end encrypted sync through a WebDAV folder or S3 bucket
+  sync?: {
+    enabled: boolean;
+    backend: 'webdav' | 's3';
+    url: string;
+    username: string;
+    password: string;
+    bucket: string;
+    region: string;
+    passphrase: string;
+    interval_minutes: number;
+  };
+  // HTTP/WebSocket API for other programs and satellites; restart Jarvis after changing it
+  local_api?: { enabled: boolean; port: number; allow_network: boolean; token: string };
+  // run as a satellite of another Jarvis: wake word and recording only
+  satellite?: { enabled: boolean; main_url: string; token: string; name: string };
+  // Wyoming speech-to-text and text-to-speech server for Home Assistant; restart after changing it
+  wyoming?: { enabled: boolean; port: number; allow_network: boolean };
+  // priorities and CPU cores of the audio threads
+  thread_priority?: {
+    audio_input: ThreadPriority;
+    wake_word: ThreadPriority;
+    audio_cores: number[];
+  };
+  // free Whisper and lower priorities while a game runs fullscreen
+  game_mode?: {
+    enabled: boolean;
+    include_borderless: boolean;
+    ignore_apps: string[];
+    unload_whisper: boolean;
+    lower_priority: boolean;
+  };
+  // act when apps gain or lose focus or go fullscreen
+  app_triggers?: {
+    enabled: boolean;
+    rules: {
+      app: string;
+      title_contains: string;
+      when: 'focus' | 'blur' | 'fullscreen';
+      action: 'start_listening' | 'stop_listening' | 'persona';
+      persona: string;
+    }[];
+  };
+  // WAV files instead of the microphone, for testing without one; speed 0 = as fast as read
+  input_source?: { kind: 'microphone' | 'wav'; path: string; speed: number; repeat: boolean };
+  // values for {name} placeholders in llm_system_prompt, e.g. user_name or location
+  prompt_variables?: Record<string, string>;
+  // extra place names mapped to IANA time zones, and the working hours meeting suggestions use
+  world_clock?: { aliases: Record<string, string>; work_start_hour: number; work_end_hour: number };
+  finance?: FinanceConfig;
+  // TheSportsDB v1 API and the team names you use mapped to the ones it knows
+  sports?: { api_url: string; api_key: string; teams: Record<string, string> };
+  // language edition ('' follows whisper_language) and how many sentences are read out
+  wikipedia?: { language: string; sentences: number };
+  // tracking service for registered packages; status changes are spoken when announce is on
+  packages?: {
+    provider: 'aftership' | 'seventeentrack';
+    api_key: string;
+    poll_minutes: number;
+    announce: boolean;
+  };
+  // route service for travel times to the profile's home and work addresses
+  commute?: {
+    provider: 'google' | 'here' | 'openrouteservice';
+    api_key: string;
+    mode: 'driving' | 'transit' | 'walking' | 'cycling';
+  };
+  // start a timer when a recipe step says how long it takes
+  cooking?: { auto_timers: boolean };
+  // looping background sounds; sounds maps extra names to sound files
+  ambient?: { volume: number; fade_seconds: number; sounds: Record<string, string> };
+  // the spoken daily update; calendars are iCalendar addresses, news_feeds RSS or Atom
+  briefing?: {
+    sections: BriefingSection[];
+    calendars: string[];
+    news_feeds: string[];
+    headlines: number;
+  };
+  // recording stops after max_minutes or when one of stop_phrases is said on its own
+  voice_memos?: { max_minutes: number; stop_phrases: string[] };
+  // do_not_disturb holds back Jarvis's own unasked announcements while focusing
+  pomodoro?: {
+    focus_minutes: number;
+    short_break_minutes: number;
+    long_break_minutes: number;
+    long_break_every: number;
+    do_not_disturb: boolean;
+  };
+  // arrival and departure of your phone, by Bluetooth; on_arrival/on_departure are
+  // commands run as if spoken
+  presence?: {
+    enabled: boolean;
+    phone_address: string;
+    scan_seconds: number;
+    away_after_misses: number;
+    welcome_briefing: boolean;
+    quiet_when_away: boolean;
+    on_arrival: string[];
+    on_departure: string[];
+  };
+  // each tool can be turned off; switching Wi-Fi or the VPN asks for a yes first
+  network?: {
+    ip_lookup: boolean;
+    connectivity_check: boolean;
+    wifi_control: boolean;
+    vpn_control: boolean;
+    vpn_name: string;
+    wifi_profile: string;
+    check_hosts: string[];
+    check_domain: string;
+  };
+  // enabled is for the voice commands; protected programs are never closed
+  processes?: { enabled: boolean; protected: string[]; offenders: number };
+  // off by default; allowed lists the programs commands may start, and nothing runs
+  // without a spoken yes
+  terminal?: {
+    enabled: boolean;
+    allowed: string[];
+    working_dir: string;
+    timeout_seconds: number;
+    max_output_chars: number;
+  };
+  // token is a new personal access token to move into the OS keychain, token_set whether
+  // one is stored there; repos are "owner/name", the first is the default
+  github?: { token?: string; token_set: boolean; repos: string[] };
+  // host is "npipe:////./pipe/docker_engine" (Docker Desktop) or "tcp://host:port"
+  docker?: { enabled: boolean; host: string; allow_control: boolean };
+  // read-only; kubeconfig and context default to kubectl's, namespaces to all of them
+  kubernetes?: { enabled: boolean; kubeconfig: string; context: string; namespaces: string[] };
+};
+
+export type BriefingSection =
+  | 'date'
+  | 'weather'
+  | 'calendar'
+  | 'reminders'
+  | 'news'
+  | 'markets'
+  | 'packages';
+
+export type FinanceConfig = {
+  stock_provider: 'yahoo' | 'alpha_vantage';
+  alpha_vantage_key: string;
+  coingecko_key: string;
+  currency_provider: 'frankfurter' | 'open_er_api';
+  base_currency: string;
+  cache_seconds: number;
+  // company names mapped to tickers
+  symbols: Record<string, string>;
+  // tickers, coins ("bitcoin") or currency pairs ("EUR/USD")
+  watchlist: string[];
+};
+
+export type ThreadPriority = 'below_normal' | 'normal' | 'above_normal' | 'high' | 'realtime';
+
+export type DeviceProfile = {
+  name: string;
+  microphone_name?: string | null;
+  output_device_name?: string | null;
+  output_volume?: number | null;
+  wwd_sensitivity?: number | null;
+};
+
+export type ProfileList = {
+  profiles: DeviceProfile[];
+  active: string | null;
+  detected: string | null;
+};
+
+export type Persona = {
+  name: string;
+  system_prompt?: string | null;
+  voice_id?: string | null;
+  elevenlabs_model?: string | null;
+  gemini_model?: string | null;
+};
+
+export type PersonaList = {
+  personas: Persona[];
+  active: string | null;
+};
+
+export type RedactionConfig = {
+  enabled: boolean;
+  emails: boolean;
+  phone_numbers: boolean;
+  credit_cards: boolean;
+  custom_patterns: string[];
+};
+
+export type WebFetchConfig = {
+  enabled: boolean;
+  max_bytes: number;
+  timeout_secs: number;
+  allowed_domains: string[];
+  blocked_domains: string[];
+};
+
+export type GenerationSettings = {
+  // 0 - 2, higher is more creative
+  temperature?: number | null;
+  top_p?: number | null;
+  max_output_tokens?: number | null;
+  // used instead of max_output_tokens for spoken answers
+  voice_max_output_tokens?: number | null;
+};
+
+export type ConversationSettings = {
+  redaction_enabled?: boolean | null;
+  context_cleared_at?: number | null;
+  generation?: GenerationSettings | null;
 };
 
 export type VoiceState =
@@ -68,4 +387,240 @@ export type VoiceState =
   | 'wake_listening'
   | 'recording'
   | 'processing'
-  | 'speaking';
\ No newline at end of file
+  | 'speaking';
+
+export type BranchSummary = {
+  turnIndex: number;
+  variantCount: number;
+  active: number;
+};
+
+export type AttachmentRef = {
+  id: string;
+  kind: 'audio' | 'image' | 'file';
+  mime: string;
+};
+
+export type AttachmentInfo = AttachmentRef & {
+  size: number;
+};
+
+export type CaptureInfo = {
+  id: string;
+  createdAt: number;
+  transcript: string;
+  language: string;
+  durationMs: number;
+};
+
+export type SetupStatus = {
+  porcupine_key: boolean;
+  gemini_key: boolean;
+  elevenlabs_key: boolean;
+  voice_id: boolean;
+  whisper_model: boolean;
+  wakeword: boolean;
+  porcupine_library: boolean;
+  input_devices: string[];
+  output_devices: string[];
+  microphone_found: boolean;
+  missing: string[];
+  ready: boolean;
+};
+
+export type KeyProvider = 'porcupine' | 'gemini' | 'elevenlabs';
+
+export type KeyCheck = {
+  ok: boolean;
+  failure?: 'invalid' | 'quota' | 'network' | 'other' | null;
+  reason?: string | null;
+};
+
+export type AudioCheck = {
+  device: string;
+  duration_ms: number;
+  peak: number;
+  rms: number;
+  speech_detected: boolean;
+  clipped: boolean;
+  output_ok: boolean;
+  output_error?: string | null;
+};
+
+export type Memory = {
+  id: string;
+  fact: string;
+  createdAt: number;
+  source?: string | null;
+};
+
+export type VoiceMemo = {
+  // file name without extension, e.g. "2025-10-16_08-30-12"
+  id: string;
+  // unix milliseconds
+  recordedAt: number;
+  seconds: number;
+  transcript: string;
+  audioPath: string;
+};
+
+export type FocusSession = {
+  started_at: string;
+  minutes: number;
+  // false when stopped early
+  completed: boolean;
+};
+
+export type PresenceEvent = {
+  at: string;
+  arrived: boolean;
+};
+
+export type HostLatency = {
+  host: string;
+  // null when it didn't answer in time
+  ms: number | null;
+};
+
+export type ConnectivityReport = {
+  localIp: string | null;
+  hosts: HostLatency[];
+  dnsOk: boolean;
+};
+
+export type ProcessInfo = {
+  pid: number;
+  name: string;
+  // share of the whole machine, 0 to 100
+  cpu: number;
+  memoryMb: number;
+  protected: boolean;
+};
+
+export type ContainerInfo = {
+  id: string;
+  name: string;
+  image: string;
+  // "running", "exited", "restarting", ...
+  state: string;
+  // e.g. "Up 2 hours (healthy)"
+  status: string;
+};
+
+export type CommandTriggers = {
+  enabled: boolean;
+  phrases: string[];
+  localized?: Record<string, string[]>;
+};
+
+export type Triggers = {
+  weather: CommandTriggers;
+  skip_track: CommandTriggers;
+  previous_track: CommandTriggers;
+  play_music: CommandTriggers;
+  pause_music: CommandTriggers;
+  now_playing: CommandTriggers;
+  forget_conversation: CommandTriggers;
+  forget_memories: CommandTriggers;
+  read_selection: CommandTriggers;
+  stop_reading: CommandTriggers;
+  summarize_clipboard: CommandTriggers;
+  world_clock: CommandTriggers;
+  finance: CommandTriggers;
+  sports: CommandTriggers;
+  wikipedia: CommandTriggers;
+  packages: CommandTriggers;
+  commute: CommandTriggers;
+  timers: CommandTriggers;
+  cooking: CommandTriggers;
+  ambient: CommandTriggers;
+  briefing: CommandTriggers;
+  voice_memos: CommandTriggers;
+  pomodoro: CommandTriggers;
+  network: CommandTriggers;
+  processes: CommandTriggers;
+  terminal: CommandTriggers;
+  github: CommandTriggers;
+  docker: CommandTriggers;
+  kubernetes: CommandTriggers;
+  confirm_media_with_llm: boolean;
+};
+
+export type NowPlaying = {
+  title: string;
+  artist?: string | null;
+  status?: string | null;
+  player?: string | null;
+};
+
+export type CrashReport = {
+  id: string;
+  kind: 'panic' | 'error';
+  createdAt: number;
+  appVersion: string;
+  os: string;
+  arch: string;
+  category: string;
+  location: string | null;
+  backtrace: string | null;
+};
+
+export type DayStats = {
+  date: string;
+  interactions: number;
+  voiceInteractions: number;
+  wakeDetections: number;
+  latencyTotalMs: number;
+  latencySamples: number;
+  sttSeconds: number;
+  llmTokensIn: number;
+  llmTokensOut: number;
+  ttsCharacters: number;
+};
+
+export type DashboardRange = 'day' | 'week' | 'month' | 'year';
+
+export type Dashboard = {
+  range: string;
+  days: DayStats[];
+  totals: DayStats;
+  averageLatencyMs: number | null;
+  sttMinutes: number;
+};
+
+export type LexiconEntry = {
+  word: string;
+  sayAs: string;
+  // IPA, used by voices that support phoneme tags
+  phoneme?: string | null;
+  caseSensitive?: boolean;
+};
+
+// unset fields use the settings
+export type AskOnceOptions = {
+  systemPrompt?: string;
+  model?: string;
+  generation?: GenerationSettings;
+};
+
+export type BatchResult = {
+  succeeded: number;
+  // "<filename>: <error>" for each conversation that failed
+  failed: string[];
+};
+
+export type BenchmarkStage = {
+  stage: 'tts' | 'stt_load' | 'stt' | 'llm';
+  model: string;
+  ms: number;
+  // characters per second for tts and llm; seconds of audio per second for stt
+  throughput: number | null;
+  output: string;
+  error: string | null;
+};
+
+export type BenchmarkReport = {
+  stages: BenchmarkStage[];
+  // 0-1, share of the test sentence's words Whisper got right
+  wordAccuracy: number | null;
+};